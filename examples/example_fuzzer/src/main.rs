@@ -1,5 +1,3 @@
-#![feature(specialization)]
-
 extern crate lain;
 extern crate ctrlc;
 
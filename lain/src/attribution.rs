@@ -0,0 +1,300 @@
+//! Attribution of coverage to the fields of a data model.
+//!
+//! [CoverageAttribution] records which fields changed in each mutation (by comparing the
+//! [field layouts][crate::traits::BinarySerialize::field_layout] of the input before and after
+//! mutating it) and whether the mutated input produced new coverage. Combined with the enum
+//! variant counts gathered by a [Mutator] with [Mutator::set_track_variants] enabled, its
+//! [AttributionReport] shows which fields are worth mutating, which never pay off, and which
+//! variants are never generated -- i.e. where the model needs work.
+//!
+//! ```compile_fail
+//! mutator.set_track_variants(true);
+//!
+//! let before = packet.clone();
+//! packet.mutate(&mut mutator, None);
+//! let new_coverage = run_target(&packet);
+//! attribution.record(&before, &packet, new_coverage);
+//!
+//! if iteration % 10_000 == 0 {
+//!     attribution.merge_variants(&mut mutator);
+//!     info!("{}", attribution.report());
+//! }
+//! ```
+
+use crate::byteorder::BigEndian;
+use crate::mutator::Mutator;
+use crate::rand::Rng;
+use crate::traits::BinarySerialize;
+use crate::types::FieldSpan;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// How often the variants of a single enum type have been generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantCounts {
+    pub names: Vec<&'static str>,
+    /// Number of times each variant was generated, indexed like `names`
+    pub counts: Vec<usize>,
+}
+
+impl VariantCounts {
+    /// Names of the variants which have never been generated
+    pub fn unused(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.names
+            .iter()
+            .zip(self.counts.iter())
+            .filter(|(_, count)| **count == 0)
+            .map(|(name, _)| *name)
+    }
+}
+
+/// Mutation statistics for a single field.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FieldStats {
+    /// Number of mutations which changed this field
+    pub mutations: usize,
+    /// Number of those mutations which produced new coverage
+    pub new_coverage: usize,
+}
+
+/// Gathers per-field and per-variant statistics over a campaign.
+#[derive(Debug, Default, Clone)]
+pub struct CoverageAttribution {
+    iterations: usize,
+    fields: BTreeMap<String, FieldStats>,
+    variants: BTreeMap<&'static str, VariantCounts>,
+}
+
+impl CoverageAttribution {
+    pub fn new() -> Self {
+        CoverageAttribution::default()
+    }
+
+    /// Records a single mutation of `before` into `after` and whether the mutated input
+    /// produced new coverage
+    pub fn record<T: BinarySerialize>(&mut self, before: &T, after: &T, new_coverage: bool) {
+        let (before_bytes, before_layout) = serialize_with_layout(before);
+        let (after_bytes, after_layout) = serialize_with_layout(after);
+
+        let previous: HashMap<&str, &[u8]> = before_layout
+            .iter()
+            .filter_map(|span| Some((span.path.as_str(), before_bytes.get(span.start..span.end)?)))
+            .collect();
+
+        let mut changed = vec![];
+        for span in after_layout.iter() {
+            let path = normalize_path(&span.path);
+            let current = after_bytes.get(span.start..span.end);
+
+            if current.is_none() || previous.get(span.path.as_str()).copied() != current {
+                changed.push(path);
+            } else {
+                // make sure fields which were never changed are still reported
+                self.fields.entry(path).or_default();
+            }
+        }
+
+        // fields which disappeared (e.g. a different enum variant was selected) also changed
+        let after_paths: Vec<&str> = after_layout.iter().map(|s| s.path.as_str()).collect();
+        changed.extend(
+            before_layout
+                .iter()
+                .filter(|span| !after_paths.contains(&span.path.as_str()))
+                .map(|span| normalize_path(&span.path)),
+        );
+
+        changed.sort();
+        changed.dedup();
+        self.record_changed_fields(changed, new_coverage);
+    }
+
+    /// Records a mutation which changed the fields at `paths`, for harnesses which track
+    /// changed fields themselves
+    pub fn record_changed_fields<I, S>(&mut self, paths: I, new_coverage: bool)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.iterations += 1;
+
+        for path in paths {
+            let stats = self.fields.entry(path.into()).or_default();
+            stats.mutations += 1;
+            if new_coverage {
+                stats.new_coverage += 1;
+            }
+        }
+    }
+
+    /// Moves the enum variant counts gathered by `mutator` into this report. Variant tracking
+    /// must be enabled with [Mutator::set_track_variants].
+    pub fn merge_variants<R: Rng>(&mut self, mutator: &mut Mutator<R>) {
+        for (type_name, counts) in mutator.take_variant_counts() {
+            self.merge_variant_counts(type_name, &counts);
+        }
+    }
+
+    fn merge_variant_counts(&mut self, type_name: &'static str, counts: &VariantCounts) {
+        let entry = self
+            .variants
+            .entry(type_name)
+            .or_insert_with(|| VariantCounts {
+                names: counts.names.clone(),
+                counts: vec![0; counts.counts.len()],
+            });
+
+        for (total, count) in entry.counts.iter_mut().zip(counts.counts.iter()) {
+            *total += count;
+        }
+    }
+
+    /// Combines the statistics of another instance (e.g. one kept by a different thread) into
+    /// this one
+    pub fn merge(&mut self, other: &CoverageAttribution) {
+        self.iterations += other.iterations;
+
+        for (path, stats) in other.fields.iter() {
+            let entry = self.fields.entry(path.clone()).or_default();
+            entry.mutations += stats.mutations;
+            entry.new_coverage += stats.new_coverage;
+        }
+
+        for (type_name, counts) in other.variants.iter() {
+            self.merge_variant_counts(type_name, counts);
+        }
+    }
+
+    /// Number of mutations recorded
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Statistics for the field at `path`. Element indices are collapsed, so the elements of a
+    /// list field `items` are reported as `items[].<field>`.
+    pub fn field(&self, path: &str) -> Option<&FieldStats> {
+        self.fields.get(path)
+    }
+
+    pub fn variants(&self, type_name: &str) -> Option<&VariantCounts> {
+        self.variants.get(type_name)
+    }
+
+    /// Summarizes the statistics gathered so far
+    pub fn report(&self) -> AttributionReport {
+        let mut productive: Vec<(String, FieldStats)> = self
+            .fields
+            .iter()
+            .filter(|(_, stats)| stats.new_coverage > 0)
+            .map(|(path, stats)| (path.clone(), *stats))
+            .collect();
+        productive.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.new_coverage));
+
+        let unproductive = self
+            .fields
+            .iter()
+            .filter(|(_, stats)| stats.mutations > 0 && stats.new_coverage == 0)
+            .map(|(path, stats)| (path.clone(), *stats))
+            .collect();
+
+        let never_mutated = self
+            .fields
+            .iter()
+            .filter(|(_, stats)| stats.mutations == 0)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        let unused_variants = self
+            .variants
+            .iter()
+            .flat_map(|(type_name, counts)| counts.unused().map(move |name| (*type_name, name)))
+            .collect();
+
+        AttributionReport {
+            iterations: self.iterations,
+            productive,
+            unproductive,
+            never_mutated,
+            unused_variants,
+        }
+    }
+}
+
+/// A snapshot of a [CoverageAttribution].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributionReport {
+    pub iterations: usize,
+    /// Fields whose mutations produced new coverage, most productive first
+    pub productive: Vec<(String, FieldStats)>,
+    /// Fields which were mutated but never produced new coverage
+    pub unproductive: Vec<(String, FieldStats)>,
+    /// Fields which were never changed by a mutation
+    pub never_mutated: Vec<String>,
+    /// `(type name, variant name)` pairs for enum variants which were never generated. Only
+    /// enums which were generated at least once are included.
+    pub unused_variants: Vec<(&'static str, &'static str)>,
+}
+
+impl fmt::Display for AttributionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "coverage attribution over {} mutations", self.iterations)?;
+
+        writeln!(f, "fields producing new coverage:")?;
+        for (path, stats) in self.productive.iter() {
+            writeln!(
+                f,
+                "  {:<40} {:>8}/{:<8}",
+                path, stats.new_coverage, stats.mutations
+            )?;
+        }
+
+        writeln!(f, "fields never producing new coverage:")?;
+        for (path, stats) in self.unproductive.iter() {
+            writeln!(f, "  {:<40} {:>8} mutations", path, stats.mutations)?;
+        }
+
+        writeln!(f, "fields never mutated:")?;
+        for path in self.never_mutated.iter() {
+            writeln!(f, "  {}", path)?;
+        }
+
+        write!(f, "variants never generated:")?;
+        for (type_name, variant) in self.unused_variants.iter() {
+            write!(f, "\n  {}::{}", type_name, variant)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn serialize_with_layout<T: BinarySerialize>(value: &T) -> (Vec<u8>, Vec<FieldSpan>) {
+    let mut bytes = vec![];
+    value.binary_serialize::<_, BigEndian>(&mut bytes);
+
+    let mut layout = vec![];
+    value.field_layout("", 0, &mut layout);
+
+    (bytes, layout)
+}
+
+/// Collapses element indices (`items[3].id` becomes `items[].id`)
+fn normalize_path(path: &str) -> String {
+    let mut normalized = String::with_capacity(path.len());
+    let mut in_index = false;
+
+    for c in path.chars() {
+        match c {
+            '[' => {
+                in_index = true;
+                normalized.push('[');
+            }
+            ']' => {
+                in_index = false;
+                normalized.push(']');
+            }
+            _ if in_index => {}
+            c => normalized.push(c),
+        }
+    }
+
+    normalized
+}
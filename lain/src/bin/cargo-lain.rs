@@ -0,0 +1,9 @@
+//! `cargo lain`: inspects, converts, and minimizes corpora. Entries can only be parsed as the
+//! `bytes` type here; see [lain::cli] for building the same tool around the types of a model.
+
+use lain::cli::CorpusTool;
+
+fn main() {
+    let tool = CorpusTool::new().register_minimizable::<Vec<u8>>("bytes");
+    std::process::exit(tool.run(std::env::args()));
+}
@@ -1,7 +1,14 @@
 use crate::traits::*;
-use crate::types::UnsafeEnum;
+use crate::types::{
+    AsciiNumber, Blob, CString, ControlWord, DeserializeError, FieldSpan, FloatVec, Lazy, Matrix,
+    Port, TimestampFormat, Ttl, UnsafeEnum, Utf16String, VariantVec, VlanTag, WideCString,
+    WindowSize,
+};
 use byteorder::{ByteOrder, WriteBytesExt};
 use paste::paste;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
 use std::io::Write;
 
 /// Default implementation of SerializedSize for slices of items. This runs in O(n) complexity since
@@ -11,7 +18,7 @@ where
     T: SerializedSize,
 {
     #[inline]
-    default fn serialized_size(&self) -> usize {
+    fn serialized_size(&self) -> usize {
         trace!("using default serialized_size for array");
         if self.is_empty() {
             return 0;
@@ -36,46 +43,27 @@ where
     }
 }
 
-macro_rules! impl_serialized_size_array {
-    ( $($size:expr),* ) => {
-        $(
-            impl<T> SerializedSize for [T; $size]
-            where T: SerializedSize {
-                #[inline]
-                fn serialized_size(&self) -> usize {
-                    trace!("using default serialized_size for array");
-                    if $size == 0 {
-                        return 0;
-                    }
-
-                    let size = self
-                        .iter()
-                        .map(SerializedSize::serialized_size)
-                        .fold(0, |sum, i| sum + i);
-
-                    size
-                }
+impl<T, const N: usize> SerializedSize for [T; N]
+where
+    T: SerializedSize,
+{
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        trace!("using default serialized_size for array");
+        self.iter().map(SerializedSize::serialized_size).sum()
+    }
 
-                #[inline]
-                fn min_nonzero_elements_size() -> usize {
-                    T::min_nonzero_elements_size() * $size
-                }
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        T::min_nonzero_elements_size() * N
+    }
 
-                #[inline]
-                fn max_default_object_size() -> usize {
-                    T::max_default_object_size() * $size
-                }
-            }
-        )*
+    #[inline]
+    fn max_default_object_size() -> usize {
+        T::max_default_object_size() * N
     }
 }
 
-impl_serialized_size_array!(
-    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
-    26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
-    50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60
-);
-
 impl<T> SerializedSize for Vec<T>
 where
     T: SerializedSize,
@@ -106,6 +94,26 @@ where
     }
 }
 
+impl<T> SerializedSize for VariantVec<T>
+where
+    T: SerializedSize,
+{
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.inner.serialized_size()
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        T::min_nonzero_elements_size()
+    }
+
+    #[inline]
+    fn max_default_object_size() -> usize {
+        T::max_default_object_size()
+    }
+}
+
 impl SerializedSize for str {
     #[inline]
     fn serialized_size(&self) -> usize {
@@ -142,6 +150,85 @@ impl SerializedSize for String {
     }
 }
 
+/// Sorts the entries of a map by the bytes of their keys serialized with `E`. `HashMap`s iterate
+/// in a different order on every run, so they're serialized and mutated in this order instead to
+/// give the same bytes for the same seed.
+pub(crate) fn sort_by_key_bytes<Q, K, V, E>(entries: &mut [(Q, V)])
+where
+    Q: std::borrow::Borrow<K>,
+    K: BinarySerialize,
+    E: ByteOrder,
+{
+    entries.sort_by_cached_key(|(key, _)| {
+        let mut bytes = vec![];
+        key.borrow().binary_serialize::<_, E>(&mut bytes);
+        bytes
+    });
+}
+
+macro_rules! impl_map_serialize {
+    ( $($map:ident($sort:expr)),* ) => {
+        $(
+            impl<K: SerializedSize, V: SerializedSize> SerializedSize for $map<K, V> {
+                #[inline]
+                fn serialized_size(&self) -> usize {
+                    self.iter()
+                        .map(|(key, value)| key.serialized_size() + value.serialized_size())
+                        .sum()
+                }
+
+                #[inline]
+                fn min_nonzero_elements_size() -> usize {
+                    K::min_nonzero_elements_size() + V::min_nonzero_elements_size()
+                }
+
+                #[inline]
+                fn max_default_object_size() -> usize {
+                    K::max_default_object_size() + V::max_default_object_size()
+                }
+            }
+
+            /// Serializes each entry as its key followed by its value, in the order of their
+            /// keys: the `BTreeMap` order, or that of the serialized keys for a `HashMap`
+            impl<K: BinarySerialize, V: BinarySerialize> BinarySerialize for $map<K, V> {
+                fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+                    let mut entries: Vec<(&K, &V)> = self.iter().collect();
+                    ($sort)(&mut entries);
+
+                    let mut bytes_written = 0;
+                    for (key, value) in entries {
+                        bytes_written += key.binary_serialize::<W, E>(buffer);
+                        bytes_written += value.binary_serialize::<W, E>(buffer);
+                    }
+
+                    bytes_written
+                }
+            }
+        )*
+    }
+}
+
+impl_map_serialize!(
+    HashMap(sort_by_key_bytes::<_, K, _, E>),
+    BTreeMap(|_entries: &mut [(&K, &V)]| ())
+);
+
+/// Reports the fields of each of `items` under `path[i]` for containers of derived types
+/// (e.g. `Vec<Vec<T>>`). Their serialized size isn't available through `BinarySerialize`
+/// alone, so each item is measured by serializing it to a sink.
+fn nested_field_layout<T: BinarySerialize>(
+    items: &[T],
+    path: &str,
+    offset: usize,
+    layout: &mut Vec<FieldSpan>,
+) {
+    let mut offset = offset;
+    for (i, item) in items.iter().enumerate() {
+        item.field_layout(&format!("{}[{}]", path, i), offset, layout);
+        offset += item.binary_serialize::<_, byteorder::BigEndian>(&mut std::io::sink());
+    }
+}
+
 impl<T> BinarySerialize for Vec<T>
 where
     T: BinarySerialize,
@@ -150,11 +237,44 @@ where
         let inner_ref: &[T] = self.as_ref();
         inner_ref.binary_serialize::<_, E>(buffer)
     }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        self.as_slice().field_layout(path, offset, layout)
+    }
+
+    fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        nested_field_layout(items, path, offset, layout)
+    }
+
+    fn set_field(&mut self, path: &str, value: &dyn Any) -> bool {
+        self.as_mut_slice().set_field(path, value)
+    }
+}
+
+impl<T> BinarySerialize for VariantVec<T>
+where
+    T: BinarySerialize,
+{
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.inner.binary_serialize::<_, E>(buffer)
+    }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        self.inner.field_layout(path, offset, layout)
+    }
+
+    fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        nested_field_layout(items, path, offset, layout)
+    }
+
+    fn set_field(&mut self, path: &str, value: &dyn Any) -> bool {
+        self.inner.set_field(path, value)
+    }
 }
 
 impl BinarySerialize for bool {
     #[inline(always)]
-    default fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
         // unsafe code here for non-binary booleans. i.e. when we do unsafe mutations
         // sometimes a bool is represented as 3 or some other non-0/1 number
         let value = unsafe { *((self as *const bool) as *const u8) };
@@ -178,12 +298,10 @@ impl BinarySerialize for u8 {
         buffer.write_u8(*self).unwrap();
         std::mem::size_of::<u8>()
     }
-}
 
-impl BinarySerialize for [u8] {
     #[inline(always)]
-    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
-        buffer.write(self).unwrap()
+    fn binary_serialize_slice<W: Write, E: ByteOrder>(items: &[u8], buffer: &mut W) -> usize {
+        buffer.write(items).unwrap()
     }
 }
 
@@ -192,13 +310,33 @@ where
     T: BinarySerialize,
 {
     #[inline(always)]
-    default fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
-        let mut bytes_written = 0;
-        for item in self.iter() {
-            bytes_written += item.binary_serialize::<W, E>(buffer);
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        T::binary_serialize_slice::<W, E>(self, buffer)
+    }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        // elements are not reported themselves, but any fields they contain are
+        T::field_layout_slice(self, path, offset, layout)
+    }
+
+    fn set_field(&mut self, path: &str, value: &dyn Any) -> bool {
+        // paths into elements look like `[2]` or `[2].id`
+        if !path.starts_with('[') {
+            return false;
         }
 
-        bytes_written
+        let end = match path.find(']') {
+            Some(end) => end,
+            None => return false,
+        };
+
+        let rest = &path[end + 1..];
+        let rest = rest.strip_prefix('.').unwrap_or(rest);
+
+        match path[1..end].parse::<usize>() {
+            Ok(i) if i < self.len() => self[i].set_field(rest, value),
+            _ => false,
+        }
     }
 }
 
@@ -210,6 +348,18 @@ where
     fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
         self.as_ref().binary_serialize::<W, E>(buffer)
     }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        self.as_ref().field_layout(path, offset, layout)
+    }
+
+    fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        nested_field_layout(items, path, offset, layout)
+    }
+
+    fn set_field(&mut self, path: &str, value: &dyn Any) -> bool {
+        self.as_mut().set_field(path, value)
+    }
 }
 
 impl<T, I> BinarySerialize for UnsafeEnum<T, I>
@@ -217,14 +367,197 @@ where
     T: BinarySerialize,
     I: BinarySerialize + Clone,
 {
-    default fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
         match *self {
             UnsafeEnum::Invalid(ref value) => value.binary_serialize::<_, E>(buffer),
             UnsafeEnum::Valid(ref value) => value.binary_serialize::<_, E>(buffer),
         }
     }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        if let UnsafeEnum::Valid(ref value) = *self {
+            value.field_layout(path, offset, layout);
+        }
+    }
+
+    fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        nested_field_layout(items, path, offset, layout)
+    }
+
+    fn set_field(&mut self, path: &str, value: &dyn Any) -> bool {
+        match *self {
+            UnsafeEnum::Valid(ref mut inner) => inner.set_field(path, value),
+            UnsafeEnum::Invalid(_) => false,
+        }
+    }
+}
+
+impl BinarySerialize for Blob {
+    #[inline(always)]
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.inner.as_slice().binary_serialize::<_, E>(buffer)
+    }
+}
+
+impl<T: BinarySerialize> BinarySerialize for FloatVec<T> {
+    #[inline(always)]
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.inner.as_slice().binary_serialize::<_, E>(buffer)
+    }
+}
+
+/// The digits are written as ASCII regardless of byte order
+impl<T> BinarySerialize for AsciiNumber<T> {
+    #[inline(always)]
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        buffer.write(self.text.as_bytes()).unwrap()
+    }
+}
+
+impl BinarySerialize for Utf16String {
+    #[inline(always)]
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.inner.as_slice().binary_serialize::<_, E>(buffer)
+    }
+}
+
+impl BinarySerialize for WideCString {
+    #[inline(always)]
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.inner.as_slice().binary_serialize::<_, E>(buffer)
+            + 0u16.binary_serialize::<_, E>(buffer)
+    }
+}
+
+/// The terminator is left out if it was dropped
+impl BinarySerialize for CString {
+    #[inline(always)]
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        let mut written = self.inner.as_slice().binary_serialize::<_, E>(buffer);
+        if self.terminated {
+            written += 0u8.binary_serialize::<_, E>(buffer);
+        }
+
+        written
+    }
 }
 
+/// Serialized as the packed integer. Every subfield is reported by `field_layout` as
+/// occupying the whole integer, like the members of a bitfield.
+impl<L: ControlWordLayout> BinarySerialize for ControlWord<L> {
+    #[inline(always)]
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.word.binary_serialize::<_, E>(buffer)
+    }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        let end = offset + self.word.serialized_size();
+        for subfield in L::SUBFIELDS.iter() {
+            layout.push(FieldSpan {
+                path: FieldSpan::child_path(path, subfield.name()),
+                start: offset,
+                end,
+            });
+        }
+    }
+
+    fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        let mut offset = offset;
+        for (i, item) in items.iter().enumerate() {
+            item.field_layout(&format!("{}[{}]", path, i), offset, layout);
+            offset += item.serialized_size();
+        }
+    }
+}
+
+/// Only the elements are serialized (in row-major order), not the shape
+impl<T: BinarySerialize> BinarySerialize for Matrix<T> {
+    #[inline(always)]
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.data.as_slice().binary_serialize::<_, E>(buffer)
+    }
+}
+
+impl<T> BinarySerialize for Lazy<T>
+where
+    T: NewFuzzed + BinarySerialize,
+{
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.generate().binary_serialize::<_, E>(buffer)
+    }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        self.generate().field_layout(path, offset, layout)
+    }
+
+    fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        nested_field_layout(items, path, offset, layout)
+    }
+}
+
+macro_rules! impl_semantic_serialize {
+    ( $($name:ident),* ) => {
+        $(
+            impl BinarySerialize for $name {
+                #[inline(always)]
+                fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+                    self.0.binary_serialize::<_, E>(buffer)
+                }
+            }
+        )*
+    }
+}
+
+impl_semantic_serialize!(Port, VlanTag, Ttl, WindowSize);
+
+macro_rules! impl_timestamp_serialize {
+    ( $($name:ty),* ) => {
+        $(
+            /// Serialized as [TimestampFormat::UnixSeconds] unless the field selects another format
+            impl BinarySerialize for $name {
+                #[inline(always)]
+                fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+                    TimestampFormat::UnixSeconds.serialize::<_, E, _>(self, buffer)
+                }
+            }
+
+            /// Parses a [TimestampFormat::UnixSeconds] timestamp
+            impl BinaryDeserialize for $name {
+                fn binary_deserialize<E: ByteOrder>(
+                    bytes: &[u8],
+                ) -> Result<(Self, usize), DeserializeError> {
+                    i64::binary_deserialize::<E>(bytes).map(|(seconds, consumed)| {
+                        (
+                            Timestamp::from_unix_millis(seconds.saturating_mul(1000)),
+                            consumed,
+                        )
+                    })
+                }
+            }
+
+            impl SerializedSize for $name {
+                #[inline]
+                fn serialized_size(&self) -> usize {
+                    TimestampFormat::UnixSeconds.min_size()
+                }
+
+                #[inline]
+                fn min_nonzero_elements_size() -> usize {
+                    TimestampFormat::UnixSeconds.min_size()
+                }
+            }
+        )*
+    }
+}
+
+impl_timestamp_serialize!(std::time::SystemTime);
+
+#[cfg(feature = "chrono_support")]
+impl_timestamp_serialize!(chrono::DateTime<chrono::Utc>);
+
+#[cfg(feature = "time_support")]
+impl_timestamp_serialize!(time::OffsetDateTime);
+
 impl BinarySerialize for String {
     #[inline(always)]
     fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
@@ -276,6 +609,23 @@ where
             0
         }
     }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        if let Some(ref inner) = self {
+            inner.field_layout(path, offset, layout);
+        }
+    }
+
+    fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        nested_field_layout(items, path, offset, layout)
+    }
+
+    fn set_field(&mut self, path: &str, value: &dyn Any) -> bool {
+        match self {
+            Some(ref mut inner) => inner.set_field(path, value),
+            None => false,
+        }
+    }
 }
 
 impl<T> BinarySerialize for Box<T>
@@ -286,6 +636,18 @@ where
     fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
         BinarySerialize::binary_serialize::<_, E>(self.as_ref(), buffer)
     }
+
+    fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        self.as_ref().field_layout(path, offset, layout)
+    }
+
+    fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<FieldSpan>) {
+        nested_field_layout(items, path, offset, layout)
+    }
+
+    fn set_field(&mut self, path: &str, value: &dyn Any) -> bool {
+        self.as_mut().set_field(path, value)
+    }
 }
 
 macro_rules! impl_binary_serialize {
@@ -306,7 +668,290 @@ macro_rules! impl_binary_serialize {
     }
 }
 
-impl_binary_serialize!(i64, u64, i32, u32, i16, u16, f32, f64);
+impl_binary_serialize!(i128, u128, i64, u64, i32, u32, i16, u16, f32, f64);
+
+macro_rules! impl_pointer_width_serialize {
+    ( $($name:ident($width:ident)),* ) => {
+        $(
+            impl BinarySerialize for $name {
+                #[inline(always)]
+                fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+                    (*self as $width).binary_serialize::<W, E>(buffer)
+                }
+            }
+
+            impl BinaryDeserialize for $name {
+                #[inline]
+                fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+                    let (value, size) = $width::binary_deserialize::<E>(bytes)?;
+                    Ok((value as $name, size))
+                }
+            }
+        )*
+    }
+}
+
+// usize and isize are serialized with the width they have on the target platform
+#[cfg(target_pointer_width = "64")]
+impl_pointer_width_serialize!(usize(u64), isize(i64));
+#[cfg(target_pointer_width = "32")]
+impl_pointer_width_serialize!(usize(u32), isize(i32));
+#[cfg(target_pointer_width = "16")]
+impl_pointer_width_serialize!(usize(u16), isize(i16));
+
+/// Returns the first `size` bytes of `bytes` or an error if there aren't enough
+#[inline]
+fn take_bytes(bytes: &[u8], size: usize) -> Result<&[u8], DeserializeError> {
+    bytes
+        .get(..size)
+        .ok_or_else(|| DeserializeError::unexpected_end(0, size - bytes.len()))
+}
+
+impl BinaryDeserialize for u8 {
+    #[inline]
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        Ok((take_bytes(bytes, 1)?[0], 1))
+    }
+}
+
+impl BinaryDeserialize for i8 {
+    #[inline]
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        Ok((take_bytes(bytes, 1)?[0] as i8, 1))
+    }
+}
+
+impl BinaryDeserialize for bool {
+    #[inline]
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        match take_bytes(bytes, 1)?[0] {
+            0 => Ok((false, 1)),
+            1 => Ok((true, 1)),
+            b => Err(DeserializeError::new(
+                0,
+                format!("invalid bool value 0x{:02X}", b),
+            )),
+        }
+    }
+}
+
+macro_rules! impl_binary_deserialize {
+    ( $($name:ident),* ) => {
+        $(
+            impl BinaryDeserialize for $name {
+                #[inline]
+                fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+                    let size = std::mem::size_of::<$name>();
+                    let bytes = take_bytes(bytes, size)?;
+
+                    paste! {
+                        Ok((E::[<read_ $name>](bytes), size))
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_binary_deserialize!(i128, u128, i64, u64, i32, u32, i16, u16, f32, f64);
+
+impl<T, const N: usize> BinaryDeserialize for [T; N]
+where
+    T: BinaryDeserialize,
+{
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        let mut items = Vec::with_capacity(N);
+        let mut offset = 0;
+        for _i in 0..N {
+            let (item, consumed) =
+                T::binary_deserialize::<E>(&bytes[offset..]).map_err(|e| e.offset_by(offset))?;
+            items.push(item);
+            offset += consumed;
+        }
+
+        match std::convert::TryInto::<[T; N]>::try_into(items) {
+            Ok(array) => Ok((array, offset)),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+/// Parses elements until the end of the buffer is reached
+impl<T> BinaryDeserialize for Vec<T>
+where
+    T: BinaryDeserialize,
+{
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        let mut items = vec![];
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let (item, consumed) =
+                T::binary_deserialize::<E>(&bytes[offset..]).map_err(|e| e.offset_by(offset))?;
+            if consumed == 0 {
+                return Err(DeserializeError::new(offset, "element consumed no bytes"));
+            }
+
+            items.push(item);
+            offset += consumed;
+        }
+
+        Ok((items, offset))
+    }
+}
+
+/// Parses key/value pairs until the end of the buffer is reached
+fn deserialize_map_entries<K, V, E>(bytes: &[u8]) -> Result<(Vec<(K, V)>, usize), DeserializeError>
+where
+    K: BinaryDeserialize,
+    V: BinaryDeserialize,
+    E: ByteOrder,
+{
+    let mut entries = vec![];
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let (key, key_len) =
+            K::binary_deserialize::<E>(&bytes[offset..]).map_err(|e| e.offset_by(offset))?;
+        let value_offset = offset + key_len;
+        let (value, value_len) = V::binary_deserialize::<E>(&bytes[value_offset..])
+            .map_err(|e| e.offset_by(value_offset))?;
+        if key_len + value_len == 0 {
+            return Err(DeserializeError::new(offset, "entry consumed no bytes"));
+        }
+
+        entries.push((key, value));
+        offset = value_offset + value_len;
+    }
+
+    Ok((entries, offset))
+}
+
+/// Parses entries until the end of the buffer is reached. Later entries replace earlier ones
+/// with the same key.
+impl<K, V> BinaryDeserialize for HashMap<K, V>
+where
+    K: BinaryDeserialize + Eq + Hash,
+    V: BinaryDeserialize,
+{
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        deserialize_map_entries::<K, V, E>(bytes)
+            .map(|(entries, consumed)| (entries.into_iter().collect(), consumed))
+    }
+}
+
+/// Parses entries until the end of the buffer is reached. Later entries replace earlier ones
+/// with the same key.
+impl<K, V> BinaryDeserialize for BTreeMap<K, V>
+where
+    K: BinaryDeserialize + Ord,
+    V: BinaryDeserialize,
+{
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        deserialize_map_entries::<K, V, E>(bytes)
+            .map(|(entries, consumed)| (entries.into_iter().collect(), consumed))
+    }
+}
+
+/// Consumes elements until the end of the buffer
+impl<T: BinaryDeserialize> BinaryDeserialize for FloatVec<T> {
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        Vec::<T>::binary_deserialize::<E>(bytes)
+            .map(|(inner, consumed)| (FloatVec::new(inner), consumed))
+    }
+}
+
+/// Consumes an optional sign followed by as many ASCII digits as there are. The value is not
+/// required to fit in a `T`.
+impl<T> BinaryDeserialize for AsciiNumber<T> {
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        let sign_len = match bytes.first() {
+            Some(b'-') | Some(b'+') => 1,
+            _ => 0,
+        };
+        let num_digits = bytes[sign_len..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+
+        if num_digits == 0 {
+            return Err(DeserializeError::new(sign_len, "expected an ASCII digit"));
+        }
+
+        let len = sign_len + num_digits;
+        let text = String::from_utf8(bytes[..len].to_vec()).unwrap();
+
+        Ok((
+            AsciiNumber {
+                text,
+                marker: std::marker::PhantomData,
+            },
+            len,
+        ))
+    }
+}
+
+/// Consumes the remainder of the buffer, which must be a whole number of code units
+impl BinaryDeserialize for Utf16String {
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        if !bytes.len().is_multiple_of(2) {
+            return Err(DeserializeError::new(
+                bytes.len() - 1,
+                "UTF-16 string ends in half a code unit",
+            ));
+        }
+
+        Vec::<u16>::binary_deserialize::<E>(bytes)
+            .map(|(inner, consumed)| (Utf16String { inner }, consumed))
+    }
+}
+
+/// Consumes code units up to and including the first null
+impl BinaryDeserialize for WideCString {
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        let mut inner = vec![];
+        for (i, unit) in bytes.chunks_exact(2).enumerate() {
+            match E::read_u16(unit) {
+                0 => return Ok((WideCString { inner }, (i + 1) * 2)),
+                unit => inner.push(unit),
+            }
+        }
+
+        Err(DeserializeError::new(
+            inner.len() * 2,
+            "expected a null terminator",
+        ))
+    }
+}
+
+/// Consumes bytes up to and including the first NUL. Without one, the remainder of the buffer
+/// is an unterminated string.
+impl BinaryDeserialize for CString {
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        Ok(match bytes.iter().position(|b| *b == 0) {
+            Some(end) => (CString::from_bytes(bytes[..end].to_vec()), end + 1),
+            None => (
+                CString {
+                    inner: bytes.to_vec(),
+                    terminated: false,
+                },
+                bytes.len(),
+            ),
+        })
+    }
+}
+
+impl<L: ControlWordLayout> BinaryDeserialize for ControlWord<L> {
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        let (word, size) = L::Word::binary_deserialize::<E>(bytes)?;
+        Ok((ControlWord::new(word), size))
+    }
+}
+
+/// Consumes the remainder of the buffer
+impl BinaryDeserialize for Blob {
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        Ok((Blob::new(bytes.to_vec()), bytes.len()))
+    }
+}
 
 macro_rules! impl_serialized_size {
     ( $($name:ident),* ) => {
@@ -331,29 +976,165 @@ macro_rules! impl_serialized_size {
     }
 }
 
-impl_serialized_size!(i64, u64, i32, u32, i16, u16, f32, f64, u8, i8, bool);
+impl_serialized_size!(
+    i128, u128, i64, u64, i32, u32, i16, u16, f32, f64, u8, i8, bool, usize, isize
+);
+impl_serialized_size!(Port, VlanTag, Ttl, WindowSize);
+
+/// Valid or not, an unsafe enum is serialized as its primitive representation
+impl<T, I> SerializedSize for UnsafeEnum<T, I> {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        std::mem::size_of::<I>()
+    }
 
-impl<T, U> SerializedSize for T
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        std::mem::size_of::<I>()
+    }
+
+    #[inline]
+    fn max_default_object_size() -> usize {
+        std::mem::size_of::<I>()
+    }
+}
+
+impl<T> SerializedSize for Lazy<T>
 where
-    T: ToPrimitive<Output = U>,
+    T: NewFuzzed + SerializedSize,
 {
     #[inline]
-    default fn serialized_size(&self) -> usize {
-        std::mem::size_of::<U>()
+    fn serialized_size(&self) -> usize {
+        self.generate().serialized_size()
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        T::min_nonzero_elements_size()
+    }
+
+    #[inline]
+    fn max_default_object_size() -> usize {
+        T::max_default_object_size()
+    }
+}
+
+impl SerializedSize for Utf16String {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.inner.len() * 2
     }
 
     #[inline]
-    default fn min_nonzero_elements_size() -> usize {
-        std::mem::size_of::<U>()
+    fn min_nonzero_elements_size() -> usize {
+        2
     }
+}
 
+/// Includes the null terminator
+impl SerializedSize for WideCString {
     #[inline]
-    default fn max_default_object_size() -> usize {
-        std::mem::size_of::<U>()
+    fn serialized_size(&self) -> usize {
+        (self.inner.len() + 1) * 2
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        2
+    }
+}
+
+/// Includes the terminator, unless it was dropped
+impl SerializedSize for CString {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.inner.len() + self.terminated as usize
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        1
+    }
+}
+
+impl<L: ControlWordLayout> SerializedSize for ControlWord<L> {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.word.serialized_size()
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        L::Word::min_nonzero_elements_size()
     }
 
-    default fn min_enum_variant_size(&self) -> usize {
-        std::mem::size_of::<U>()
+    #[inline]
+    fn max_default_object_size() -> usize {
+        L::Word::max_default_object_size()
+    }
+}
+
+impl<T> SerializedSize for AsciiNumber<T> {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.text.len()
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        1
+    }
+}
+
+impl<T: SerializedSize> SerializedSize for FloatVec<T> {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.inner.serialized_size()
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        T::min_nonzero_elements_size()
+    }
+
+    #[inline]
+    fn max_default_object_size() -> usize {
+        T::max_default_object_size()
+    }
+}
+
+impl<T: SerializedSize> SerializedSize for Matrix<T> {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.data.serialized_size()
+    }
+
+    /// A 1x1 matrix
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        T::min_nonzero_elements_size()
+    }
+
+    #[inline]
+    fn max_default_object_size() -> usize {
+        T::max_default_object_size()
+    }
+}
+
+impl SerializedSize for Blob {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        1
+    }
+
+    #[inline]
+    fn max_default_object_size() -> usize {
+        1
     }
 }
 
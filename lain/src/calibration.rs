@@ -0,0 +1,339 @@
+//! Warm-up calibration of mutator settings against a target.
+//!
+//! Before a campaign starts, [Calibration] generates a number of random inputs, runs each
+//! through the target, and measures how long the target takes, how large the inputs are, and how
+//! often the target rejects them. Each sample is generated with a single [BlobContent] class so
+//! that the rejection rate of every class can be compared. The resulting [CalibrationReport]
+//! suggests tuned settings: blob content weights which favor the classes the target accepts, and
+//! a smaller `max_size` if most rejected inputs were larger than anything the target accepted.
+//!
+//! ```compile_fail
+//! let report = Calibration::<Packet>::new()
+//!     .samples(500)
+//!     .run(|bytes, _packet| send_packet(bytes));
+//!
+//! info!("{}", report);
+//! report.apply(&mut mutator);
+//! let packet = Packet::new_fuzzed(&mut mutator, report.constraints().as_ref());
+//! ```
+//!
+//! The driver can also run this phase itself before starting a pipeline fuzzer. See
+//! [FuzzerDriver::set_calibration_samples][crate::driver::FuzzerDriver::set_calibration_samples].
+
+use crate::byteorder::BigEndian;
+use crate::driver::Outcome;
+use crate::mutator::Mutator;
+use crate::rand::rngs::StdRng;
+use crate::rand::{Rng, SeedableRng};
+use crate::traits::{BinarySerialize, NewFuzzed};
+use crate::types::{BlobContent, BlobContentWeights, Constraints};
+use num_traits::Bounded;
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_CALIBRATION_SAMPLES: usize = 200;
+pub const DEFAULT_REJECTION_THRESHOLD: f64 = 0.5;
+
+/// Tuned blob content weights are the default weights scaled by the class' acceptance rate.
+/// Scaling up first keeps the weights of classes which are mostly accepted distinguishable.
+const WEIGHT_SCALE: u64 = 10;
+
+/// Rejection statistics for inputs generated with a single [BlobContent] class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentStats {
+    pub content: BlobContent,
+    pub samples: usize,
+    pub rejections: usize,
+}
+
+impl ContentStats {
+    pub fn rejection_rate(&self) -> f64 {
+        ratio(self.rejections, self.samples)
+    }
+}
+
+/// Distribution of the serialized size of calibration inputs, in bytes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SizeStats {
+    pub min: usize,
+    pub max: usize,
+    pub mean: usize,
+    pub median: usize,
+    /// 90th percentile
+    pub p90: usize,
+}
+
+impl SizeStats {
+    fn from_sorted(sizes: &[usize]) -> Self {
+        if sizes.is_empty() {
+            return SizeStats::default();
+        }
+
+        SizeStats {
+            min: sizes[0],
+            max: sizes[sizes.len() - 1],
+            mean: sizes.iter().sum::<usize>() / sizes.len(),
+            median: percentile(sizes, 50),
+            p90: percentile(sizes, 90),
+        }
+    }
+}
+
+/// Measurements taken by [Calibration::run] and the settings tuned from them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationReport {
+    pub samples: usize,
+    /// Number of samples the target reported as [Outcome::Reject]
+    pub rejections: usize,
+    pub mean_exec_time: Duration,
+    pub median_exec_time: Duration,
+    pub max_exec_time: Duration,
+    pub sizes: SizeStats,
+    /// Rejection statistics for each blob content class
+    pub content: Vec<ContentStats>,
+    /// Blob content weights favoring the classes the target accepted
+    pub blob_content_weights: BlobContentWeights,
+    /// The `max_size` constraint inputs should be generated with, if any
+    pub max_size: Option<usize>,
+}
+
+impl CalibrationReport {
+    pub fn rejection_rate(&self) -> f64 {
+        ratio(self.rejections, self.samples)
+    }
+
+    /// Configures `mutator` with the tuned settings
+    pub fn apply<R: Rng>(&self, mutator: &mut Mutator<R>) {
+        mutator.set_blob_content_weights(self.blob_content_weights.clone());
+    }
+
+    /// Constraints carrying the tuned `max_size`, or `None` if inputs should be unconstrained
+    pub fn constraints<T: Bounded + Debug + Default>(&self) -> Option<Constraints<T>> {
+        self.max_size.map(|max_size| {
+            let mut constraints = Constraints::new();
+            constraints.max_size(max_size);
+            constraints
+        })
+    }
+}
+
+impl fmt::Display for CalibrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "calibrated over {} samples: {:.1}% rejected",
+            self.samples,
+            self.rejection_rate() * 100.0
+        )?;
+        writeln!(
+            f,
+            "  exec time: mean {:?}, median {:?}, max {:?}",
+            self.mean_exec_time, self.median_exec_time, self.max_exec_time
+        )?;
+        writeln!(
+            f,
+            "  size: min {}, median {}, p90 {}, max {}",
+            self.sizes.min, self.sizes.median, self.sizes.p90, self.sizes.max
+        )?;
+
+        for stats in self.content.iter() {
+            writeln!(
+                f,
+                "  {:<20} {:>5.1}% rejected, weight {}",
+                format!("{:?}", stats.content),
+                stats.rejection_rate() * 100.0,
+                self.blob_content_weights.weight(stats.content)
+            )?;
+        }
+
+        match self.max_size {
+            Some(max_size) => write!(f, "  max_size: {}", max_size),
+            None => write!(f, "  max_size: unconstrained"),
+        }
+    }
+}
+
+/// Configurable warm-up phase which tunes mutator settings for a target.
+pub struct Calibration<T> {
+    samples: usize,
+    seed: u64,
+    max_size: Option<usize>,
+    rejection_threshold: f64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: NewFuzzed + BinarySerialize> Default for Calibration<T> {
+    fn default() -> Self {
+        Calibration::new()
+    }
+}
+
+impl<T: NewFuzzed + BinarySerialize> Calibration<T> {
+    pub fn new() -> Self {
+        Calibration {
+            samples: DEFAULT_CALIBRATION_SAMPLES,
+            seed: 0,
+            max_size: None,
+            rejection_threshold: DEFAULT_REJECTION_THRESHOLD,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of inputs to generate
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Root seed. Sample `n` is generated from `seed + n`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The `max_size` inputs are currently generated with. Calibration never suggests a larger
+    /// one.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Fraction of rejected samples above which `max_size` may be shrunk
+    pub fn rejection_threshold(mut self, threshold: f64) -> Self {
+        self.rejection_threshold = threshold;
+        self
+    }
+
+    /// Generates each sample, serializes it in big endian order, and passes both to `execute`
+    pub fn run<F, O>(&self, mut execute: F) -> CalibrationReport
+    where
+        F: FnMut(&[u8], &T) -> O,
+        O: Into<Outcome>,
+    {
+        let constraints = self.max_size.map(|max_size| {
+            let mut c = Constraints::<T::RangeType>::new();
+            c.max_size(max_size);
+            c
+        });
+
+        let mut content: Vec<ContentStats> = BlobContent::ALL
+            .iter()
+            .map(|class| ContentStats {
+                content: *class,
+                samples: 0,
+                rejections: 0,
+            })
+            .collect();
+        let mut exec_times = Vec::with_capacity(self.samples);
+        let mut accepted_sizes = vec![];
+        let mut rejected_sizes = vec![];
+
+        for i in 0..self.samples {
+            let stats = &mut content[i % BlobContent::ALL.len()];
+
+            // only generate blobs of a single class so its rejection rate can be measured
+            let mut weights = BlobContentWeights::default();
+            for class in BlobContent::ALL.iter() {
+                weights.set_weight(*class, 0);
+            }
+            weights.set_weight(stats.content, 1);
+
+            let mut mutator = Mutator::new(StdRng::seed_from_u64(self.seed.wrapping_add(i as u64)));
+            mutator.set_blob_content_weights(weights);
+            mutator.random_flags();
+
+            let input = T::new_fuzzed(&mut mutator, constraints.as_ref());
+            let mut bytes = vec![];
+            input.binary_serialize::<_, BigEndian>(&mut bytes);
+
+            let start = Instant::now();
+            let outcome = execute(&bytes, &input).into();
+            exec_times.push(start.elapsed());
+
+            stats.samples += 1;
+            if outcome == Outcome::Reject {
+                stats.rejections += 1;
+                rejected_sizes.push(bytes.len());
+            } else {
+                accepted_sizes.push(bytes.len());
+            }
+        }
+
+        exec_times.sort();
+        let mut sizes: Vec<usize> = accepted_sizes
+            .iter()
+            .chain(rejected_sizes.iter())
+            .copied()
+            .collect();
+        sizes.sort_unstable();
+
+        let mut report = CalibrationReport {
+            samples: self.samples,
+            rejections: rejected_sizes.len(),
+            mean_exec_time: exec_times
+                .iter()
+                .sum::<Duration>()
+                .checked_div(exec_times.len() as u32)
+                .unwrap_or_default(),
+            median_exec_time: exec_times
+                .get(exec_times.len() / 2)
+                .copied()
+                .unwrap_or_default(),
+            max_exec_time: exec_times.last().copied().unwrap_or_default(),
+            sizes: SizeStats::from_sorted(&sizes),
+            blob_content_weights: tune_blob_content_weights(&content),
+            content,
+            max_size: self.max_size,
+        };
+
+        if report.rejection_rate() > self.rejection_threshold {
+            if let Some(largest_accepted) = accepted_sizes.iter().max().copied() {
+                let too_large = rejected_sizes
+                    .iter()
+                    .filter(|size| **size > largest_accepted)
+                    .count();
+
+                // most rejected inputs were larger than anything the target accepted
+                if too_large * 2 > rejected_sizes.len() {
+                    report.max_size = Some(
+                        self.max_size
+                            .map_or(largest_accepted, |max| max.min(largest_accepted)),
+                    );
+                }
+            }
+        }
+
+        report
+    }
+}
+
+fn tune_blob_content_weights(content: &[ContentStats]) -> BlobContentWeights {
+    let mut weights = BlobContentWeights::default();
+
+    for stats in content.iter() {
+        if stats.samples == 0 {
+            continue;
+        }
+
+        let default = weights.weight(stats.content) * WEIGHT_SCALE;
+        let accepted = (stats.samples - stats.rejections) as u64;
+        // never disable a class entirely; the target may only reject it some of the time
+        let weight = std::cmp::max(default * accepted / stats.samples as u64, 1);
+        weights.set_weight(stats.content, weight);
+    }
+
+    weights
+}
+
+fn percentile(sorted: &[usize], percent: usize) -> usize {
+    sorted[(sorted.len() - 1) * percent / 100]
+}
+
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
@@ -0,0 +1,292 @@
+//! Checksums which are recomputed whenever their structure is serialized.
+//!
+//! A [Checksum] field of a type deriving `BinarySerialize` is marked with `#[lain(checksum)]`.
+//! Instead of its own value, it serializes the checksum of the bytes serialized before it in
+//! the same structure, or of the bytes starting at the field named by
+//! `#[lain(checksum_from = "...")]`:
+//!
+//! ```compile_fail
+//! #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+//! struct Message {
+//!     kind: u8,
+//!     payload: Vec<u8>,
+//!     #[lain(checksum_from = "payload")]
+//!     crc: Checksum<u32, Crc32>,
+//! }
+//! ```
+//!
+//! With probability [Mutator::bad_checksum_chance], generating or mutating a [Checksum] makes
+//! it serialize a wrong value, so that the target's handling of corrupt messages is exercised
+//! without hand-written fixups for the correct case.
+//!
+//! Algorithms implement [ChecksumAlgorithm]. Checksums which can't be expressed as a type can
+//! still be computed by a function with `#[lain(trailer = "...")]`.
+
+use crate::byteorder::ByteOrder;
+use crate::mutator::Mutator;
+use crate::operators::MutationOperator;
+use crate::rand::Rng;
+use crate::traits::{BinaryDeserialize, BinarySerialize, Mutatable, NewFuzzed, SerializedSize};
+use crate::types::{Constraints, DeserializeError};
+use num_traits::{AsPrimitive, PrimInt};
+use std::fmt;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::mem;
+
+/// A checksum over a range of bytes
+pub trait ChecksumAlgorithm {
+    /// The checksum of `bytes`. Checksums are truncated to the width of the [Checksum] holding
+    /// them.
+    fn checksum(bytes: &[u8]) -> u64;
+}
+
+/// CRC-16/CCITT-FALSE: polynomial 0x1021, initial value 0xFFFF, no reflection
+#[derive(Debug, Clone, Copy)]
+pub struct Crc16;
+
+impl ChecksumAlgorithm for Crc16 {
+    fn checksum(bytes: &[u8]) -> u64 {
+        let mut crc: u16 = 0xFFFF;
+        for byte in bytes {
+            crc ^= (*byte as u16) << 8;
+            for _bit in 0..8 {
+                crc = if crc & 0x8000 != 0 {
+                    (crc << 1) ^ 0x1021
+                } else {
+                    crc << 1
+                };
+            }
+        }
+
+        crc as u64
+    }
+}
+
+/// The CRC-32 of zlib, PNG, and Ethernet: reflected polynomial 0xEDB88320
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32;
+
+impl ChecksumAlgorithm for Crc32 {
+    fn checksum(bytes: &[u8]) -> u64 {
+        let mut crc: u32 = 0xFFFF_FFFF;
+        for byte in bytes {
+            crc ^= *byte as u32;
+            for _bit in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+
+        !crc as u64
+    }
+}
+
+/// The Adler-32 checksum of zlib streams
+#[derive(Debug, Clone, Copy)]
+pub struct Adler32;
+
+impl ChecksumAlgorithm for Adler32 {
+    fn checksum(bytes: &[u8]) -> u64 {
+        const MOD_ADLER: u32 = 65521;
+
+        let (mut a, mut b) = (1u32, 0u32);
+        for byte in bytes {
+            a = (a + *byte as u32) % MOD_ADLER;
+            b = (b + a) % MOD_ADLER;
+        }
+
+        ((b << 16) | a) as u64
+    }
+}
+
+/// Fletcher-16 over single bytes
+#[derive(Debug, Clone, Copy)]
+pub struct Fletcher16;
+
+impl ChecksumAlgorithm for Fletcher16 {
+    fn checksum(bytes: &[u8]) -> u64 {
+        let (mut sum1, mut sum2) = (0u32, 0u32);
+        for byte in bytes {
+            sum1 = (sum1 + *byte as u32) % 255;
+            sum2 = (sum2 + sum1) % 255;
+        }
+
+        ((sum2 << 8) | sum1) as u64
+    }
+}
+
+/// Fletcher-32 over little-endian 16-bit words. An odd trailing byte is padded with a zero.
+#[derive(Debug, Clone, Copy)]
+pub struct Fletcher32;
+
+impl ChecksumAlgorithm for Fletcher32 {
+    fn checksum(bytes: &[u8]) -> u64 {
+        let (mut sum1, mut sum2) = (0u32, 0u32);
+        for word in bytes.chunks(2) {
+            let word = word[0] as u32 | (*word.get(1).unwrap_or(&0) as u32) << 8;
+            sum1 = (sum1 + word) % 65535;
+            sum2 = (sum2 + sum1) % 65535;
+        }
+
+        ((sum2 << 16) | sum1) as u64
+    }
+}
+
+/// An integer of type `T` holding the `A` checksum of other fields.
+///
+/// The checksum itself isn't stored: it's computed when a field marked `#[lain(checksum)]` is
+/// serialized by a derived type. A [Checksum] serialized on its own holds the checksum of no
+/// bytes at all.
+pub struct Checksum<T, A> {
+    /// Mask XORed into the correct checksum. 0 for a correct checksum.
+    error: T,
+    algorithm: PhantomData<fn() -> A>,
+}
+
+impl<T, A> Checksum<T, A>
+where
+    T: PrimInt + 'static,
+    A: ChecksumAlgorithm,
+    u64: AsPrimitive<T>,
+{
+    /// A checksum which is always serialized correctly
+    pub fn new() -> Self {
+        Checksum {
+            error: T::zero(),
+            algorithm: PhantomData,
+        }
+    }
+
+    /// Whether a wrong checksum is serialized
+    pub fn is_bad(&self) -> bool {
+        self.error != T::zero()
+    }
+
+    /// The value serialized for a checksum over `bytes`
+    pub fn compute(&self, bytes: &[u8]) -> T {
+        A::checksum(bytes).as_() ^ self.error
+    }
+
+    /// Decides anew whether the checksum is serialized correctly
+    fn regenerate<R: Rng>(&mut self, mutator: &mut Mutator<R>) {
+        if !mutator.gen_chance(mutator.bad_checksum_chance()) {
+            self.error = T::zero();
+            return;
+        }
+
+        mutator.record_operator(MutationOperator::CorruptChecksum);
+        let error: T = mutator.rng.gen::<u64>().as_();
+        self.error = if error == T::zero() { T::one() } else { error };
+    }
+}
+
+impl<T, A> Default for Checksum<T, A>
+where
+    T: PrimInt + 'static,
+    A: ChecksumAlgorithm,
+    u64: AsPrimitive<T>,
+{
+    fn default() -> Self {
+        Checksum::new()
+    }
+}
+
+impl<T: Copy, A> Clone for Checksum<T, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Copy, A> Copy for Checksum<T, A> {}
+
+impl<T: PartialEq, A> PartialEq for Checksum<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error
+    }
+}
+
+impl<T: fmt::Debug, A> fmt::Debug for Checksum<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Checksum")
+            .field("algorithm", &std::any::type_name::<A>())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<T, A> NewFuzzed for Checksum<T, A>
+where
+    T: PrimInt + 'static,
+    A: ChecksumAlgorithm,
+    u64: AsPrimitive<T>,
+{
+    type RangeType = u8;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        let mut checksum = Checksum::new();
+        checksum.regenerate(mutator);
+
+        checksum
+    }
+}
+
+impl<T, A> Mutatable for Checksum<T, A>
+where
+    T: PrimInt + 'static,
+    A: ChecksumAlgorithm,
+    u64: AsPrimitive<T>,
+{
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        self.regenerate(mutator);
+    }
+}
+
+impl<T, A> SerializedSize for Checksum<T, A> {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        mem::size_of::<T>()
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        mem::size_of::<T>()
+    }
+}
+
+impl<T, A> BinarySerialize for Checksum<T, A>
+where
+    T: PrimInt + BinarySerialize + 'static,
+    A: ChecksumAlgorithm,
+    u64: AsPrimitive<T>,
+{
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.compute(&[]).binary_serialize::<W, E>(buffer)
+    }
+}
+
+/// Parsed checksums are serialized correctly, whether or not the parsed value was
+impl<T, A> BinaryDeserialize for Checksum<T, A>
+where
+    T: PrimInt + BinaryDeserialize + 'static,
+    A: ChecksumAlgorithm,
+    u64: AsPrimitive<T>,
+{
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        let (_value, size) = T::binary_deserialize::<E>(bytes)?;
+
+        Ok((Checksum::new(), size))
+    }
+}
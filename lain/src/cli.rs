@@ -0,0 +1,631 @@
+//! A command-line front-end for inspecting, converting, and minimizing corpora.
+//!
+//! Corpus entries are only meaningful together with the types they were generated from, so a
+//! [CorpusTool] is built into a small binary which registers them by name:
+//!
+//! ```compile_fail
+//! fn main() {
+//!     let tool = CorpusTool::new()
+//!         .register::<Handshake>("handshake")
+//!         .register_minimizable::<Packet>("packet");
+//!     std::process::exit(tool.run(std::env::args()));
+//! }
+//! ```
+//!
+//! lain ships one such binary as the `cargo lain` subcommand, which only knows the `bytes`
+//! type (an entry as a plain byte buffer). It's built with the `cli_support` feature:
+//! `cargo install lain --features cli_support`.
+//!
+//! The commands are:
+//!
+//! - `types` lists the registered types
+//! - `print <type> <file>` parses a corpus entry and prints it along with the bytes of each of
+//!   its fields
+//! - `convert <type> <input> <output>` parses every entry of `input` and saves the ones which
+//!   parse to the directory `output` as a raw [Corpus] (one file per entry, named after its
+//!   hash). `input` is either a directory of raw entries, such as an AFL or libFuzzer corpus,
+//!   or a [snapshot][crate::corpus::ShardedCorpus::write_snapshot]. `--from` and `--to` give
+//!   the byte order entries are parsed and written with, so a corpus can also be converted
+//!   from one byte order to the other.
+//! - `stats <input>` prints the number of entries of a corpus and their sizes, and with
+//!   `--type <type>` how many of them parse as that type
+//! - `minimize <type> <file> <output> -- <command>...` shrinks the entry with
+//!   [minimize][crate::minimize::minimize] for as long as `command` still fails on it (exits
+//!   unsuccessfully or is killed by a signal), and writes the result to `output`. `@@` in the
+//!   command is replaced by the path of a file holding the candidate, as in AFL; without it the
+//!   candidate is written to the command's stdin.
+//!
+//! Byte orders are given as `be` or `le` with `--endian` (or `--from` and `--to` for
+//! `convert`), and default to big endian.
+
+use crate::byteorder::{BigEndian, LittleEndian};
+use crate::corpus::Corpus;
+use crate::minimize::{minimize, Minimized};
+use crate::traits::{BinaryDeserialize, BinarySerialize, Minimize};
+use crate::types::DeserializeError;
+use std::fmt::{self, Debug, Write as _};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Maximum number of candidates `minimize` runs the command on, unless set otherwise with
+/// `--max-attempts`
+pub const DEFAULT_MAX_ATTEMPTS: usize = 10_000;
+
+const USAGE: &str = "\
+usage: cargo lain <command> [options]
+
+commands:
+    types                                      list the registered types
+    print <type> <file>                        print a corpus entry and the bytes of its fields
+    convert <type> <input> <output>            parse a corpus and save the entries which parse
+    stats <input>                              print the number and sizes of corpus entries
+    minimize <type> <file> <output> -- <cmd>   shrink an entry while <cmd> still fails on it
+
+options:
+    --endian be|le        byte order of entries (print, stats, minimize)
+    --from be|le          byte order entries are parsed with (convert)
+    --to be|le            byte order entries are written with (convert)
+    --type <type>         also count the entries which parse as <type> (stats)
+    --max-attempts <n>    maximum number of candidates to try (minimize)
+";
+
+/// An error which stops a [CorpusTool] command
+#[derive(Debug)]
+pub enum CliError {
+    /// The arguments don't form a valid command
+    Usage(String),
+    /// A file or directory could not be read or written
+    Io(PathBuf, io::Error),
+    /// An entry doesn't parse as the requested type
+    Deserialize(PathBuf, DeserializeError),
+    /// The entry to minimize doesn't make the command fail to begin with
+    NotFailing,
+    /// The command's output could not be written
+    Output(io::Error),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}", message),
+            CliError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            CliError::Deserialize(path, e) => write!(f, "{}: {}", path.display(), e),
+            CliError::NotFailing => write!(f, "the command doesn't fail on the input"),
+            CliError::Output(e) => write!(f, "could not write output: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Output(e)
+    }
+}
+
+fn usage<T>(message: impl Into<String>) -> Result<T, CliError> {
+    Err(CliError::Usage(message.into()))
+}
+
+type PrintFn = fn(&[u8], bool) -> Result<String, DeserializeError>;
+type ConvertFn = fn(&[u8], bool, bool) -> Result<Vec<u8>, DeserializeError>;
+type MinimizeFn = fn(
+    &[u8],
+    bool,
+    usize,
+    &mut dyn FnMut(&[u8]) -> bool,
+) -> Result<Minimized<Vec<u8>>, DeserializeError>;
+
+/// The operations of a type registered with a [CorpusTool], monomorphized for it
+struct RegisteredType {
+    name: &'static str,
+    print: PrintFn,
+    convert: ConvertFn,
+    minimize: Option<MinimizeFn>,
+}
+
+fn parse<T: BinaryDeserialize>(bytes: &[u8], big_endian: bool) -> Result<T, DeserializeError> {
+    if big_endian {
+        T::from_bytes::<BigEndian>(bytes)
+    } else {
+        T::from_bytes::<LittleEndian>(bytes)
+    }
+}
+
+fn serialize<T: BinarySerialize>(value: &T, big_endian: bool) -> Vec<u8> {
+    let mut bytes = vec![];
+    if big_endian {
+        value.binary_serialize::<_, BigEndian>(&mut bytes);
+    } else {
+        value.binary_serialize::<_, LittleEndian>(&mut bytes);
+    }
+
+    bytes
+}
+
+fn print_entry<T>(bytes: &[u8], big_endian: bool) -> Result<String, DeserializeError>
+where
+    T: BinaryDeserialize + BinarySerialize + Debug,
+{
+    let value = parse::<T>(bytes, big_endian)?;
+    let mut output = format!("{:#?}\n", value);
+
+    let mut layout = vec![];
+    value.field_layout("", 0, &mut layout);
+    if !layout.is_empty() {
+        output.push('\n');
+    }
+    for span in layout.iter().filter(|span| span.end <= bytes.len()) {
+        write!(output, "{:>6}..{:<6} {}:", span.start, span.end, span.path).unwrap();
+        for byte in bytes[span.start..span.end].iter().take(32) {
+            write!(output, " {:02x}", byte).unwrap();
+        }
+        if span.len() > 32 {
+            output.push_str(" ...");
+        }
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+fn convert_entry<T>(bytes: &[u8], from_big: bool, to_big: bool) -> Result<Vec<u8>, DeserializeError>
+where
+    T: BinaryDeserialize + BinarySerialize,
+{
+    Ok(serialize(&parse::<T>(bytes, from_big)?, to_big))
+}
+
+fn minimize_entry<T>(
+    bytes: &[u8],
+    big_endian: bool,
+    max_attempts: usize,
+    still_fails: &mut dyn FnMut(&[u8]) -> bool,
+) -> Result<Minimized<Vec<u8>>, DeserializeError>
+where
+    T: BinaryDeserialize + BinarySerialize + Minimize,
+{
+    let value = parse::<T>(bytes, big_endian)?;
+    let minimized = minimize(value, max_attempts, |candidate| {
+        still_fails(&serialize(candidate, big_endian))
+    });
+
+    Ok(Minimized {
+        input: serialize(&minimized.input, big_endian),
+        attempts: minimized.attempts,
+        reductions: minimized.reductions,
+    })
+}
+
+/// Positional arguments and `--name value` options of a command
+struct Arguments {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+    /// Everything after `--`
+    command: Vec<String>,
+}
+
+impl Arguments {
+    fn parse(args: &[String]) -> Result<Arguments, CliError> {
+        let mut arguments = Arguments {
+            positional: vec![],
+            options: vec![],
+            command: vec![],
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                arguments.command = args.cloned().collect();
+                break;
+            }
+
+            match arg.strip_prefix("--") {
+                Some(name) => match args.next() {
+                    Some(value) => arguments.options.push((name.to_string(), value.clone())),
+                    None => return usage(format!("--{} requires a value", name)),
+                },
+                None => arguments.positional.push(arg.clone()),
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    /// Checks that there are exactly as many positional arguments as `names`
+    fn expect(&self, names: &[&str]) -> Result<(), CliError> {
+        if self.positional.len() != names.len() {
+            return usage(format!("expected arguments: {}", names.join(" ")));
+        }
+
+        Ok(())
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the byte order given with `--<name>` is big endian
+    fn big_endian(&self, name: &str) -> Result<bool, CliError> {
+        match self.option(name) {
+            None | Some("be") => Ok(true),
+            Some("le") => Ok(false),
+            Some(other) => usage(format!("--{} must be be or le, not {}", name, other)),
+        }
+    }
+
+    /// Checks that no options other than `allowed` were given
+    fn allow_options(&self, allowed: &[&str]) -> Result<(), CliError> {
+        match self
+            .options
+            .iter()
+            .find(|(name, _)| !allowed.contains(&name.as_str()))
+        {
+            Some((name, _)) => usage(format!("unknown option --{}", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, CliError> {
+    std::fs::read(path).map_err(|e| CliError::Io(path.to_path_buf(), e))
+}
+
+/// The files holding the entries of the corpus at `path`: a directory of raw entries, or the
+/// `entries` directory of a snapshot
+fn entry_paths(path: &Path) -> Result<Vec<PathBuf>, CliError> {
+    let snapshot_entries = path.join("entries");
+    let dir = if snapshot_entries.is_dir() {
+        snapshot_entries
+    } else {
+        path.to_path_buf()
+    };
+
+    let mut paths = vec![];
+    let entries = std::fs::read_dir(&dir).map_err(|e| CliError::Io(dir.clone(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| CliError::Io(dir.clone(), e))?;
+        if entry.path().is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Runs `command` on `input` and returns whether it failed
+fn command_fails(command: &[String], input: &[u8], input_path: &Path) -> bool {
+    let uses_file = command.iter().any(|arg| arg.contains("@@"));
+    if uses_file && std::fs::write(input_path, input).is_err() {
+        return false;
+    }
+
+    let path = input_path.to_string_lossy();
+    let mut process = Command::new(&command[0]);
+    process
+        .args(command[1..].iter().map(|arg| arg.replace("@@", &path)))
+        .stdin(if uses_file {
+            Stdio::null()
+        } else {
+            Stdio::piped()
+        })
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    let mut child = match process.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("could not run {}: {}", command[0], e);
+            return false;
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // the command may exit without reading all of its input
+        let _ = stdin.write_all(input);
+    }
+
+    child
+        .wait()
+        .map(|status| !status.success())
+        .unwrap_or(false)
+}
+
+/// A command-line tool for the corpora of the registered types. See the [module
+/// documentation][crate::cli] for the commands it understands.
+#[derive(Default)]
+pub struct CorpusTool {
+    types: Vec<RegisteredType>,
+}
+
+impl CorpusTool {
+    /// A tool without any types
+    pub fn new() -> Self {
+        CorpusTool::default()
+    }
+
+    /// Makes `T` available to the `print` and `convert` commands as `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if a type was already registered as `name`.
+    pub fn register<T>(mut self, name: &'static str) -> Self
+    where
+        T: BinaryDeserialize + BinarySerialize + Debug,
+    {
+        assert!(
+            self.find(name).is_none(),
+            "a type was already registered as {}",
+            name
+        );
+
+        self.types.push(RegisteredType {
+            name,
+            print: print_entry::<T>,
+            convert: convert_entry::<T>,
+            minimize: None,
+        });
+        self
+    }
+
+    /// Same as [CorpusTool::register], but also makes `T` available to the `minimize` command
+    pub fn register_minimizable<T>(mut self, name: &'static str) -> Self
+    where
+        T: BinaryDeserialize + BinarySerialize + Minimize + Debug,
+    {
+        self = self.register::<T>(name);
+        self.types.last_mut().unwrap().minimize = Some(minimize_entry::<T>);
+        self
+    }
+
+    /// The names of the registered types, in the order they were registered
+    pub fn type_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.types.iter().map(|t| t.name)
+    }
+
+    fn find(&self, name: &str) -> Option<&RegisteredType> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    fn get(&self, name: &str) -> Result<&RegisteredType, CliError> {
+        match self.find(name) {
+            Some(registered) => Ok(registered),
+            None => usage(format!(
+                "unknown type {} (registered types: {})",
+                name,
+                self.type_names().collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    /// Runs the command given by `args`, which start with the program's name as in
+    /// [std::env::args], and returns the process's exit code. Errors are printed to stderr.
+    pub fn run<I, S>(&self, args: I) -> i32
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let stdout = io::stdout();
+        match self.execute(args, &mut stdout.lock()) {
+            Ok(()) => 0,
+            Err(e @ CliError::Usage(_)) => {
+                eprintln!("error: {}\n\n{}", e, USAGE);
+                2
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                1
+            }
+        }
+    }
+
+    /// Same as [CorpusTool::run], but writes the command's output to `output` and returns any
+    /// error instead of printing it
+    pub fn execute<I, S>(&self, args: I, output: &mut dyn Write) -> Result<(), CliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args: Vec<String> = args.into_iter().skip(1).map(Into::into).collect();
+        // cargo passes the subcommand's name on to `cargo-lain`
+        if args.first().map(String::as_str) == Some("lain") {
+            args.remove(0);
+        }
+
+        if args.is_empty() {
+            return usage("no command given");
+        }
+
+        let command = args.remove(0);
+        let arguments = Arguments::parse(&args)?;
+        match command.as_str() {
+            "types" => self.types_command(&arguments, output),
+            "print" => self.print_command(&arguments, output),
+            "convert" => self.convert_command(&arguments, output),
+            "stats" => self.stats_command(&arguments, output),
+            "minimize" => self.minimize_command(&arguments, output),
+            "help" | "--help" | "-h" => Ok(output.write_all(USAGE.as_bytes())?),
+            other => usage(format!("unknown command {}", other)),
+        }
+    }
+
+    fn types_command(&self, args: &Arguments, output: &mut dyn Write) -> Result<(), CliError> {
+        args.expect(&[])?;
+        args.allow_options(&[])?;
+
+        for registered in self.types.iter() {
+            let minimizable = if registered.minimize.is_some() {
+                " (minimizable)"
+            } else {
+                ""
+            };
+            writeln!(output, "{}{}", registered.name, minimizable)?;
+        }
+
+        Ok(())
+    }
+
+    fn print_command(&self, args: &Arguments, output: &mut dyn Write) -> Result<(), CliError> {
+        args.expect(&["<type>", "<file>"])?;
+        args.allow_options(&["endian"])?;
+
+        let registered = self.get(&args.positional[0])?;
+        let path = Path::new(&args.positional[1]);
+        let bytes = read(path)?;
+        let printed = (registered.print)(&bytes, args.big_endian("endian")?)
+            .map_err(|e| CliError::Deserialize(path.to_path_buf(), e))?;
+
+        output.write_all(printed.as_bytes())?;
+        Ok(())
+    }
+
+    fn convert_command(&self, args: &Arguments, output: &mut dyn Write) -> Result<(), CliError> {
+        args.expect(&["<type>", "<input>", "<output>"])?;
+        args.allow_options(&["from", "to"])?;
+
+        let registered = self.get(&args.positional[0])?;
+        let (from_big, to_big) = (args.big_endian("from")?, args.big_endian("to")?);
+        let mut corpus = Corpus::new();
+        let mut failed = 0;
+        for path in entry_paths(Path::new(&args.positional[1]))? {
+            match (registered.convert)(&read(&path)?, from_big, to_big) {
+                Ok(converted) => {
+                    corpus.add(converted);
+                }
+                Err(e) => {
+                    writeln!(output, "skipping {}: {}", path.display(), e)?;
+                    failed += 1;
+                }
+            }
+        }
+
+        let destination = Path::new(&args.positional[2]);
+        let written = corpus
+            .save(destination)
+            .map_err(|e| CliError::Io(destination.to_path_buf(), e))?;
+        writeln!(
+            output,
+            "converted {} entries ({} new files), skipped {}",
+            corpus.len(),
+            written,
+            failed
+        )?;
+
+        Ok(())
+    }
+
+    fn stats_command(&self, args: &Arguments, output: &mut dyn Write) -> Result<(), CliError> {
+        args.expect(&["<input>"])?;
+        args.allow_options(&["type", "endian"])?;
+
+        let registered = match args.option("type") {
+            Some(name) => Some(self.get(name)?),
+            None => None,
+        };
+        let big_endian = args.big_endian("endian")?;
+
+        let mut corpus = Corpus::new();
+        let mut sizes = vec![];
+        let mut parsed = 0;
+        for path in entry_paths(Path::new(&args.positional[0]))? {
+            let bytes = read(&path)?;
+            if let Some(registered) = registered {
+                if (registered.convert)(&bytes, big_endian, big_endian).is_ok() {
+                    parsed += 1;
+                }
+            }
+            sizes.push(bytes.len());
+            corpus.add(bytes);
+        }
+        sizes.sort_unstable();
+
+        writeln!(output, "entries:    {}", sizes.len())?;
+        writeln!(output, "unique:     {}", corpus.len())?;
+        if !sizes.is_empty() {
+            let total: usize = sizes.iter().sum();
+            writeln!(output, "total size: {}", total)?;
+            writeln!(
+                output,
+                "sizes:      min {}, median {}, mean {:.1}, max {}",
+                sizes[0],
+                sizes[sizes.len() / 2],
+                total as f64 / sizes.len() as f64,
+                sizes[sizes.len() - 1]
+            )?;
+        }
+        if let Some(registered) = registered {
+            writeln!(
+                output,
+                "parsed:     {} of {} as {}",
+                parsed,
+                sizes.len(),
+                registered.name
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn minimize_command(&self, args: &Arguments, output: &mut dyn Write) -> Result<(), CliError> {
+        args.expect(&["<type>", "<file>", "<output>"])?;
+        args.allow_options(&["endian", "max-attempts"])?;
+        if args.command.is_empty() {
+            return usage("expected a command after --");
+        }
+
+        let registered = self.get(&args.positional[0])?;
+        let minimize = match registered.minimize {
+            Some(minimize) => minimize,
+            None => return usage(format!("{} isn't minimizable", registered.name)),
+        };
+        let max_attempts = match args.option("max-attempts") {
+            Some(value) => match value.parse() {
+                Ok(max_attempts) => max_attempts,
+                Err(_) => return usage(format!("invalid --max-attempts {}", value)),
+            },
+            None => DEFAULT_MAX_ATTEMPTS,
+        };
+
+        let path = Path::new(&args.positional[1]);
+        let bytes = read(path)?;
+        let candidate_path = std::env::temp_dir().join(format!(
+            "lain-minimize-{}-{}",
+            std::process::id(),
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        if !command_fails(&args.command, &bytes, &candidate_path) {
+            let _ = std::fs::remove_file(&candidate_path);
+            return Err(CliError::NotFailing);
+        }
+
+        let minimized = minimize(
+            &bytes,
+            args.big_endian("endian")?,
+            max_attempts,
+            &mut |candidate| command_fails(&args.command, candidate, &candidate_path),
+        );
+        let _ = std::fs::remove_file(&candidate_path);
+        let minimized = minimized.map_err(|e| CliError::Deserialize(path.to_path_buf(), e))?;
+
+        let destination = Path::new(&args.positional[2]);
+        std::fs::write(destination, &minimized.input)
+            .map_err(|e| CliError::Io(destination.to_path_buf(), e))?;
+        writeln!(
+            output,
+            "minimized {} to {} bytes with {} reductions in {} attempts",
+            bytes.len(),
+            minimized.input.len(),
+            minimized.reductions,
+            minimized.attempts
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+//! Checking that serialized output stays byte-for-byte stable across versions of lain.
+//!
+//! Corpora and replay files collected over months of campaigns are only useful after a crate
+//! upgrade if the same values still serialize to the same bytes. A [WireCompat] serializes a set
+//! of named values in both byte orders and compares the result against a golden file recorded
+//! with an earlier version. [WireCompat::canonical] covers lain's own primitive, container, and
+//! derived encodings; harnesses can add cases for their own data models:
+//!
+//! ```compile_fail
+//! // e.g. in a unit test alongside the data model
+//! WireCompat::canonical()
+//!     .case("packet", &Packet::example())
+//!     .verify_file("tests/wire_compat.golden")
+//!     .unwrap();
+//! ```
+//!
+//! A missing golden file is created by [WireCompat::verify_file]. Format changes are never
+//! accepted silently: either a case is explicitly allowed to differ with
+//! [WireCompat::allow_change], or the whole file is re-recorded by setting [BLESS_ENV_VAR] (or
+//! calling [WireCompat::bless]). Cases which are only present in the golden file count as
+//! changes, while new cases are recorded the next time the file is blessed.
+
+use crate::byteorder::{BigEndian, LittleEndian};
+use crate::traits::BinarySerialize;
+use crate::types::{Port, Ttl, UnsafeEnum};
+use crate::BinarySerialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Setting this environment variable to `1` makes [WireCompat::verify_file] re-record the golden
+/// file instead of checking it
+pub const BLESS_ENV_VAR: &str = "LAIN_BLESS_WIRE_COMPAT";
+
+/// First line of every golden file
+const GOLDEN_HEADER: &str = "# lain wire-compat v1";
+
+/// A named value serialized in one byte order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireCase {
+    /// Name of the case, suffixed with `/be` or `/le`
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// A case whose serialized bytes differ from the golden file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireMismatch {
+    pub name: String,
+    pub expected: Vec<u8>,
+    /// `None` if the case is no longer serialized at all
+    pub found: Option<Vec<u8>>,
+}
+
+impl fmt::Display for WireMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.found {
+            Some(ref found) => write!(
+                f,
+                "{}: expected {} but found {}",
+                self.name,
+                to_hex(&self.expected),
+                to_hex(found)
+            ),
+            None => write!(
+                f,
+                "{}: present in the golden file but not checked",
+                self.name
+            ),
+        }
+    }
+}
+
+/// Errors which may occur while checking a [WireCompat].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireCompatError {
+    /// The golden file could not be read or written
+    Io(String),
+    /// The golden file is malformed
+    Parse { line: usize, message: String },
+    /// Serialized bytes differ from the golden file
+    Changed(Vec<WireMismatch>),
+}
+
+impl fmt::Display for WireCompatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WireCompatError::Io(message) => write!(f, "could not access golden file: {}", message),
+            WireCompatError::Parse { line, message } => {
+                write!(f, "golden file line {}: {}", line, message)
+            }
+            WireCompatError::Changed(mismatches) => {
+                write!(
+                    f,
+                    "{} case(s) changed their wire format (set {}=1 if this is intentional):",
+                    mismatches.len(),
+                    BLESS_ENV_VAR
+                )?;
+
+                for mismatch in mismatches.iter() {
+                    write!(f, "\n  {}", mismatch)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for WireCompatError {}
+
+/// A set of values whose serialized bytes are checked against a golden file.
+#[derive(Debug, Clone, Default)]
+pub struct WireCompat {
+    cases: Vec<WireCase>,
+    allowed_changes: HashMap<String, String>,
+    bless: bool,
+}
+
+impl WireCompat {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A set of cases covering the encodings lain itself is responsible for: primitives,
+    /// strings, containers, enums, timestamps, and the layout produced by
+    /// `#[derive(BinarySerialize)]` including bitfields
+    pub fn canonical() -> Self {
+        WireCompat::new()
+            .case("u8", &0xA5u8)
+            .case("i8", &-2i8)
+            .case("u16", &0x1234u16)
+            .case("i16", &-0x1234i16)
+            .case("u32", &0x1234_5678u32)
+            .case("i32", &-0x1234_5678i32)
+            .case("u64", &0x0123_4567_89AB_CDEFu64)
+            .case("i64", &-0x0123_4567_89AB_CDEFi64)
+            .case("f32", &1.5f32)
+            .case("f64", &-2.25f64)
+            .case("bool", &true)
+            .case("string", &String::from("läin"))
+            .case("vec_u16", &vec![1u16, 0x0203, 0xFFFF])
+            .case("array_u32", &[0xDEAD_BEEFu32, 1])
+            .case("option_some", &Some(7u32))
+            .case("option_none", &None::<u32>)
+            .case(
+                "unsafe_enum_invalid",
+                &UnsafeEnum::<u8, u16>::Invalid(0x0102),
+            )
+            .case(
+                "system_time",
+                &(UNIX_EPOCH + Duration::from_secs(1_234_567_890)),
+            )
+            .case("semantic_port", &Port::new(443))
+            .case("derived_struct", &CanonicalHeader::example())
+    }
+
+    /// Adds `value`, serialized in both big and little endian as `<name>/be` and `<name>/le`.
+    /// Names may not contain whitespace.
+    pub fn case<T: BinarySerialize>(mut self, name: &str, value: &T) -> Self {
+        assert!(
+            !name.is_empty() && !name.contains(char::is_whitespace),
+            "wire-compat case names must be non-empty and contain no whitespace"
+        );
+
+        let mut big_endian = vec![];
+        value.binary_serialize::<_, BigEndian>(&mut big_endian);
+        let mut little_endian = vec![];
+        value.binary_serialize::<_, LittleEndian>(&mut little_endian);
+
+        self.cases.push(WireCase {
+            name: format!("{}/be", name),
+            bytes: big_endian,
+        });
+        self.cases.push(WireCase {
+            name: format!("{}/le", name),
+            bytes: little_endian,
+        });
+        self
+    }
+
+    /// Allows both byte orders of the case `name` to differ from (or be missing from) the
+    /// golden file. `reason` is logged whenever the change is accepted.
+    pub fn allow_change(mut self, name: &str, reason: &str) -> Self {
+        for suffix in ["be", "le"].iter() {
+            self.allowed_changes
+                .insert(format!("{}/{}", name, suffix), reason.to_string());
+        }
+        self
+    }
+
+    /// Makes [WireCompat::verify_file] re-record the golden file instead of checking it
+    pub fn bless(mut self, bless: bool) -> Self {
+        self.bless = bless;
+        self
+    }
+
+    pub fn cases(&self) -> &[WireCase] {
+        &self.cases
+    }
+
+    /// Renders the cases in the golden file format: a header line followed by one
+    /// `<name> <hex bytes>` line per case
+    pub fn to_golden(&self) -> String {
+        let mut golden = String::from(GOLDEN_HEADER);
+        golden.push('\n');
+
+        for case in self.cases.iter() {
+            golden.push_str(&case.name);
+            if !case.bytes.is_empty() {
+                golden.push(' ');
+                golden.push_str(&to_hex(&case.bytes));
+            }
+            golden.push('\n');
+        }
+
+        golden
+    }
+
+    /// Compares the cases against the contents of a golden file
+    pub fn check(&self, golden: &str) -> Result<(), WireCompatError> {
+        let expected = parse_golden(golden)?;
+        let found: HashMap<&str, &[u8]> = self
+            .cases
+            .iter()
+            .map(|case| (case.name.as_str(), case.bytes.as_slice()))
+            .collect();
+
+        let mut mismatches = vec![];
+        for (name, expected_bytes) in expected {
+            let found_bytes = found.get(name.as_str()).copied();
+            if found_bytes == Some(expected_bytes.as_slice()) {
+                continue;
+            }
+
+            if let Some(reason) = self.allowed_changes.get(&name) {
+                info!("accepting wire format change of {}: {}", name, reason);
+                continue;
+            }
+
+            mismatches.push(WireMismatch {
+                name,
+                expected: expected_bytes,
+                found: found_bytes.map(<[u8]>::to_vec),
+            });
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(WireCompatError::Changed(mismatches))
+        }
+    }
+
+    /// Checks the cases against the golden file at `path`. The file is (re-)recorded instead
+    /// if it doesn't exist yet, if [WireCompat::bless] was set, or if [BLESS_ENV_VAR] is `1`.
+    pub fn verify_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WireCompatError> {
+        let path = path.as_ref();
+        let bless = self.bless || std::env::var(BLESS_ENV_VAR).as_deref() == Ok("1");
+
+        if bless || !path.exists() {
+            info!("recording wire-compat golden file {}", path.display());
+            return fs::write(path, self.to_golden())
+                .map_err(|e| WireCompatError::Io(e.to_string()));
+        }
+
+        let golden = fs::read_to_string(path).map_err(|e| WireCompatError::Io(e.to_string()))?;
+        self.check(&golden)
+    }
+}
+
+/// A derived structure exercising the field layouts pinned by [WireCompat::canonical]
+#[derive(Debug, Clone, BinarySerialize)]
+struct CanonicalHeader {
+    #[lain(bits = 4)]
+    version: u8,
+    #[lain(bits = 4)]
+    header_len: u8,
+    ttl: Ttl,
+    length: u16,
+    #[lain(timestamp = "ms")]
+    sent_at: SystemTime,
+    payload: Vec<u8>,
+}
+
+impl CanonicalHeader {
+    fn example() -> Self {
+        CanonicalHeader {
+            version: 4,
+            header_len: 5,
+            ttl: Ttl::new(64),
+            length: 0x0102,
+            sent_at: UNIX_EPOCH + Duration::from_millis(1_600_000_000_123),
+            payload: vec![0xDE, 0xAD],
+        }
+    }
+}
+
+fn parse_golden(golden: &str) -> Result<Vec<(String, Vec<u8>)>, WireCompatError> {
+    let mut lines = golden.lines().enumerate();
+
+    match lines.next() {
+        Some((_, header)) if header.trim() == GOLDEN_HEADER => {}
+        _ => {
+            return Err(WireCompatError::Parse {
+                line: 1,
+                message: format!("expected `{}`", GOLDEN_HEADER),
+            })
+        }
+    }
+
+    let mut cases = vec![];
+    for (index, line) in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parse_error = |message: &str| WireCompatError::Parse {
+            line: index + 1,
+            message: message.to_string(),
+        };
+
+        let mut parts = line.split_whitespace();
+        let name = parts.next().unwrap();
+        let hex = parts.next().unwrap_or("");
+        if parts.next().is_some() {
+            return Err(parse_error("expected `<name> <hex bytes>`"));
+        }
+
+        let bytes = from_hex(hex).ok_or_else(|| parse_error("invalid hex bytes"))?;
+        cases.push((name.to_string(), bytes));
+    }
+
+    Ok(cases)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match std::str::from_utf8(pair) {
+            Ok(pair) if pair.len() == 2 => u8::from_str_radix(pair, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
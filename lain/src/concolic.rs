@@ -0,0 +1,330 @@
+//! Exchanging inputs with an external concolic executor for hybrid fuzzing.
+//!
+//! Random mutation struggles with branches guarded by exact comparisons (magic values,
+//! checksums, length checks). A concolic executor can solve those branches, but it needs to be
+//! told which inputs to explore and needs a way to hand its solutions back. A
+//! [ConcolicExecutor] is that integration point: the driver [submits][ConcolicExecutor::submit]
+//! serialized inputs along with their field layout, and [polls][ConcolicExecutor::poll] for
+//! [Suggestion]s, each of which is a set of byte substitutions to make in a submitted input.
+//! Suggestions are run ahead of the mutation pipeline by [start_pipeline_fuzzer].
+//!
+//! [FileExchange] implements the exchange through a directory so that any external tool can
+//! take part:
+//!
+//! ```compile_fail
+//! let mut driver = FuzzerDriver::<()>::new(4);
+//! // hand over every interesting input, plus every 1000th input regardless
+//! driver.set_concolic_executor(Arc::new(FileExchange::new("/tmp/exchange")?), 1000);
+//! ```
+//!
+//! [start_pipeline_fuzzer]: crate::driver::start_pipeline_fuzzer
+
+use crate::driver::Outcome;
+use crate::types::FieldSpan;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Minimum time between two polls of the executor for new suggestions
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Replaces the bytes at `offset` with `bytes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteSubstitution {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// Byte substitutions to make in a previously submitted input, e.g. to flip the outcome of a
+/// branch the executor solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The submitted input the substitutions apply to
+    pub base: Vec<u8>,
+    pub substitutions: Vec<ByteSubstitution>,
+}
+
+impl Suggestion {
+    /// Returns `base` with every substitution applied in order. Substitutions which start past
+    /// the end of the input are skipped; ones which run past the end extend it.
+    pub fn apply(&self) -> Vec<u8> {
+        let mut bytes = self.base.clone();
+
+        for substitution in self.substitutions.iter() {
+            if substitution.offset > bytes.len() {
+                warn!(
+                    "skipping substitution at 0x{:X} past the end of a 0x{:X} byte input",
+                    substitution.offset,
+                    bytes.len()
+                );
+                continue;
+            }
+
+            let end = substitution.offset + substitution.bytes.len();
+            if end > bytes.len() {
+                bytes.resize(end, 0);
+            }
+
+            bytes[substitution.offset..end].copy_from_slice(&substitution.bytes);
+        }
+
+        bytes
+    }
+}
+
+/// An external engine which explores submitted inputs and suggests new ones.
+pub trait ConcolicExecutor: Send + Sync {
+    /// Hands an input and the layout of its fields to the executor. This should return
+    /// quickly; the exploration itself is expected to happen asynchronously.
+    fn submit(&self, input: &[u8], layout: &[FieldSpan]);
+
+    /// Returns the suggestions produced since the last poll
+    fn poll(&self) -> Vec<Suggestion>;
+}
+
+/// A [ConcolicExecutor] which exchanges inputs and suggestions through files in a directory.
+///
+/// Each submitted input is written to `inputs/<id>.bin`, where `<id>` is a 16 digit hex hash
+/// of its content, and its field layout to `inputs/<id>.fields` with one
+/// `<start> <end> <path>` line per field. The external tool reports suggestions by creating
+/// files named `suggestions/<id>.txt` (or `suggestions/<id>.<anything>.txt` for several
+/// suggestions per input) containing one `<offset> <hex bytes>` substitution per line.
+/// Blank lines and lines starting with `#` are ignored. Suggestion files are deleted once
+/// they have been read, so they should be written elsewhere and moved into place.
+#[derive(Debug)]
+pub struct FileExchange {
+    root: PathBuf,
+}
+
+impl FileExchange {
+    /// Uses the directory at `root`, creating it and its subdirectories if needed
+    pub fn new<P: AsRef<Path>>(root: P) -> io::Result<FileExchange> {
+        let exchange = FileExchange {
+            root: root.as_ref().to_path_buf(),
+        };
+        fs::create_dir_all(exchange.inputs_dir())?;
+        fs::create_dir_all(exchange.suggestions_dir())?;
+
+        Ok(exchange)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn inputs_dir(&self) -> PathBuf {
+        self.root.join("inputs")
+    }
+
+    pub fn suggestions_dir(&self) -> PathBuf {
+        self.root.join("suggestions")
+    }
+
+    /// The ID an input is submitted under
+    pub fn input_id(input: &[u8]) -> String {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+
+        format!("{:016x}", hasher.finish())
+    }
+
+    fn write_input(&self, input: &[u8], layout: &[FieldSpan]) -> io::Result<()> {
+        let id = FileExchange::input_id(input);
+        let path = self.inputs_dir().join(format!("{}.bin", id));
+        if path.exists() {
+            return Ok(());
+        }
+
+        let fields: String = layout
+            .iter()
+            .map(|span| format!("{} {} {}\n", span.start, span.end, span.path))
+            .collect();
+
+        // write the field map first so it's complete by the time the input shows up
+        fs::write(self.inputs_dir().join(format!("{}.fields", id)), fields)?;
+        fs::write(path, input)
+    }
+
+    fn read_suggestion(&self, path: &Path) -> Result<Suggestion, String> {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| String::from("invalid file name"))?;
+        let id = name.split('.').next().unwrap_or("");
+        let base = fs::read(self.inputs_dir().join(format!("{}.bin", id)))
+            .map_err(|e| format!("no submitted input with ID {}: {}", id, e))?;
+
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let substitutions = parse_substitutions(&contents)?;
+
+        Ok(Suggestion {
+            base,
+            substitutions,
+        })
+    }
+}
+
+impl ConcolicExecutor for FileExchange {
+    fn submit(&self, input: &[u8], layout: &[FieldSpan]) {
+        if let Err(e) = self.write_input(input, layout) {
+            error!(
+                "could not submit input to {}: {}",
+                self.inputs_dir().display(),
+                e
+            );
+        }
+    }
+
+    fn poll(&self) -> Vec<Suggestion> {
+        let entries = match fs::read_dir(self.suggestions_dir()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("could not read {}: {}", self.suggestions_dir().display(), e);
+                return vec![];
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+        // keep the order suggestions are run in reproducible
+        paths.sort();
+
+        let mut suggestions = vec![];
+        for path in paths {
+            match self.read_suggestion(&path) {
+                Ok(suggestion) => suggestions.push(suggestion),
+                Err(e) => warn!("ignoring suggestion {}: {}", path.display(), e),
+            }
+
+            if let Err(e) = fs::remove_file(&path) {
+                error!("could not remove suggestion {}: {}", path.display(), e);
+            }
+        }
+
+        suggestions
+    }
+}
+
+/// Parses `<offset> <hex bytes>` lines. Offsets may be decimal or `0x`-prefixed hex.
+fn parse_substitutions(contents: &str) -> Result<Vec<ByteSubstitution>, String> {
+    let mut substitutions = vec![];
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: &str| format!("line {}: {}", index + 1, message);
+
+        let mut parts = line.split_whitespace();
+        let offset = parts.next().unwrap();
+        let offset = match offset.strip_prefix("0x") {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => offset.parse(),
+        }
+        .map_err(|_| error("invalid offset"))?;
+
+        let hex = parts.next().ok_or_else(|| error("missing bytes"))?;
+        let bytes = hex
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| match std::str::from_utf8(pair) {
+                Ok(pair) if pair.len() == 2 => u8::from_str_radix(pair, 16).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| error("invalid hex bytes"))?;
+
+        if parts.next().is_some() {
+            return Err(error("expected `<offset> <hex bytes>`"));
+        }
+
+        substitutions.push(ByteSubstitution { offset, bytes });
+    }
+
+    Ok(substitutions)
+}
+
+/// The driver's side of the exchange: decides which inputs are submitted and queues
+/// suggestions until a fuzzer thread picks them up.
+pub(crate) struct ConcolicBridge {
+    executor: Arc<dyn ConcolicExecutor>,
+    submit_interval: usize,
+    iterations: AtomicUsize,
+    submitted: AtomicUsize,
+    suggestions_run: AtomicUsize,
+    pending: Mutex<PendingSuggestions>,
+}
+
+struct PendingSuggestions {
+    queue: VecDeque<Suggestion>,
+    last_poll: Option<Instant>,
+}
+
+impl ConcolicBridge {
+    pub(crate) fn new(executor: Arc<dyn ConcolicExecutor>, submit_interval: usize) -> Self {
+        ConcolicBridge {
+            executor,
+            submit_interval,
+            iterations: AtomicUsize::new(0),
+            submitted: AtomicUsize::new(0),
+            suggestions_run: AtomicUsize::new(0),
+            pending: Mutex::new(PendingSuggestions {
+                queue: VecDeque::new(),
+                last_poll: None,
+            }),
+        }
+    }
+
+    /// Takes the next suggestion to run, polling the executor if none are queued and it hasn't
+    /// been polled recently
+    pub(crate) fn next_suggestion(&self) -> Option<Suggestion> {
+        let mut pending = self.pending.lock().unwrap();
+
+        let should_poll = pending
+            .last_poll
+            .is_none_or(|last_poll| last_poll.elapsed() >= DEFAULT_POLL_INTERVAL);
+        if pending.queue.is_empty() && should_poll {
+            pending.last_poll = Some(Instant::now());
+            pending.queue.extend(self.executor.poll());
+        }
+
+        let suggestion = pending.queue.pop_front();
+        if suggestion.is_some() {
+            self.suggestions_run.fetch_add(1, Ordering::SeqCst);
+        }
+
+        suggestion
+    }
+
+    /// Submits `input` if it was interesting or the submit interval has elapsed
+    pub(crate) fn observe(&self, outcome: &Outcome, input: &[u8], layout: &[FieldSpan]) {
+        let iteration = self.iterations.fetch_add(1, Ordering::SeqCst) + 1;
+        let interval_elapsed =
+            self.submit_interval != 0 && iteration.is_multiple_of(self.submit_interval);
+
+        if !matches!(outcome, Outcome::Interesting(_)) && !interval_elapsed {
+            return;
+        }
+
+        self.submitted.fetch_add(1, Ordering::SeqCst);
+        self.executor.submit(input, layout);
+    }
+
+    pub(crate) fn num_submitted(&self) -> usize {
+        self.submitted.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn num_suggestions_run(&self) -> usize {
+        self.suggestions_run.load(Ordering::SeqCst)
+    }
+}
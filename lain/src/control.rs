@@ -0,0 +1,333 @@
+//! Runtime control of a running campaign.
+//!
+//! Long-running campaigns sometimes need adjusting without restarting the process and losing
+//! its in-memory state: pausing while the target machine is needed for something else, checking
+//! progress, flushing the corpus to disk before a risky change, or turning a mutator setting up
+//! or down. [start_control_socket] listens on a local unix socket for line-based commands and
+//! applies them to a [FuzzerDriver]:
+//!
+//! | Command                | Effect                                                           |
+//! |------------------------|------------------------------------------------------------------|
+//! | `pause`                | Fuzzer threads stop starting new iterations                      |
+//! | `resume`               | Fuzzer threads continue                                          |
+//! | `stats`                | Replies with the driver's counters as a single line of JSON      |
+//! | `flush`                | [Flushes][FuzzerDriver::flush_corpus] the feedback corpus        |
+//! | `set <chance> <value>` | Sets a [MutatorChance] on every fuzzer thread's mutator          |
+//! | `help`                 | Lists the commands                                               |
+//!
+//! Every command is answered with one line: `ok`, the requested data, or `error: <reason>`.
+//!
+//! ```compile_fail
+//! let control = start_control_socket(driver.clone(), "/tmp/campaign.sock")?;
+//! ```
+//!
+//! ```text
+//! $ echo "set dictionary 0.2" | nc -U /tmp/campaign.sock
+//! ok
+//! ```
+//!
+//! Only unix sockets are supported. On other platforms, [handle_command] can be connected to
+//! whatever transport is available.
+
+use crate::driver::FuzzerDriver;
+use crate::mutator::Mutator;
+use crate::rand::Rng;
+use std::fmt;
+
+const HELP: &str =
+    "commands: pause, resume, stats, flush, set <chance> <value>, help; chances: invalid_value, \
+     invalid_enum, option_some, option_toggle, dictionary, sequence_anomaly, timestamp_extreme, \
+     crossover, variant_switch, length_corruption, bad_checksum, interesting_value, \
+     byte_block, zero_fill, c_string_anomaly, shared_mismatch";
+
+/// A mutator setting which can be adjusted while a campaign is running.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MutatorChance {
+    /// See [Mutator::set_invalid_value_chance]
+    InvalidValue,
+    /// See [Mutator::set_invalid_enum_chance]
+    InvalidEnum,
+    /// See [Mutator::set_option_some_chance]
+    OptionSome,
+    /// See [Mutator::set_option_toggle_chance]
+    OptionToggle,
+    /// See [Mutator::set_dictionary_chance]
+    Dictionary,
+    /// See [Mutator::set_sequence_anomaly_chance]
+    SequenceAnomaly,
+    /// See [Mutator::set_timestamp_extreme_chance]
+    TimestampExtreme,
+    /// See [Mutator::set_crossover_chance]
+    Crossover,
+    /// See [Mutator::set_variant_switch_chance]
+    VariantSwitch,
+    /// See [Mutator::set_length_corruption_chance]
+    LengthCorruption,
+    /// See [Mutator::set_bad_checksum_chance]
+    BadChecksum,
+    /// See [Mutator::set_interesting_value_chance]
+    InterestingValue,
+    /// See [Mutator::set_byte_block_chance]
+    ByteBlock,
+    /// See [Mutator::set_zero_fill_chance]
+    ZeroFill,
+    /// See [Mutator::set_c_string_anomaly_chance]
+    CStringAnomaly,
+    /// See [Mutator::set_shared_mismatch_chance]
+    SharedMismatch,
+}
+
+impl MutatorChance {
+    pub const ALL: [MutatorChance; 16] = [
+        MutatorChance::InvalidValue,
+        MutatorChance::InvalidEnum,
+        MutatorChance::OptionSome,
+        MutatorChance::OptionToggle,
+        MutatorChance::Dictionary,
+        MutatorChance::SequenceAnomaly,
+        MutatorChance::TimestampExtreme,
+        MutatorChance::Crossover,
+        MutatorChance::VariantSwitch,
+        MutatorChance::LengthCorruption,
+        MutatorChance::BadChecksum,
+        MutatorChance::InterestingValue,
+        MutatorChance::ByteBlock,
+        MutatorChance::ZeroFill,
+        MutatorChance::CStringAnomaly,
+        MutatorChance::SharedMismatch,
+    ];
+
+    /// Name used to refer to the setting in control commands
+    pub fn name(self) -> &'static str {
+        match self {
+            MutatorChance::InvalidValue => "invalid_value",
+            MutatorChance::InvalidEnum => "invalid_enum",
+            MutatorChance::OptionSome => "option_some",
+            MutatorChance::OptionToggle => "option_toggle",
+            MutatorChance::Dictionary => "dictionary",
+            MutatorChance::SequenceAnomaly => "sequence_anomaly",
+            MutatorChance::TimestampExtreme => "timestamp_extreme",
+            MutatorChance::Crossover => "crossover",
+            MutatorChance::VariantSwitch => "variant_switch",
+            MutatorChance::LengthCorruption => "length_corruption",
+            MutatorChance::BadChecksum => "bad_checksum",
+            MutatorChance::InterestingValue => "interesting_value",
+            MutatorChance::ByteBlock => "byte_block",
+            MutatorChance::ZeroFill => "zero_fill",
+            MutatorChance::CStringAnomaly => "c_string_anomaly",
+            MutatorChance::SharedMismatch => "shared_mismatch",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MutatorChance> {
+        MutatorChance::ALL
+            .iter()
+            .copied()
+            .find(|chance| chance.name() == name)
+    }
+
+    /// Sets this chance on `mutator`
+    pub fn apply<R: Rng>(self, mutator: &mut Mutator<R>, chance: f64) {
+        match self {
+            MutatorChance::InvalidValue => mutator.set_invalid_value_chance(chance),
+            MutatorChance::InvalidEnum => mutator.set_invalid_enum_chance(chance),
+            MutatorChance::OptionSome => mutator.set_option_some_chance(chance),
+            MutatorChance::OptionToggle => mutator.set_option_toggle_chance(chance),
+            MutatorChance::Dictionary => mutator.set_dictionary_chance(chance),
+            MutatorChance::SequenceAnomaly => mutator.set_sequence_anomaly_chance(chance),
+            MutatorChance::TimestampExtreme => mutator.set_timestamp_extreme_chance(chance),
+            MutatorChance::Crossover => mutator.set_crossover_chance(chance),
+            MutatorChance::VariantSwitch => mutator.set_variant_switch_chance(chance),
+            MutatorChance::LengthCorruption => mutator.set_length_corruption_chance(chance),
+            MutatorChance::BadChecksum => mutator.set_bad_checksum_chance(chance),
+            MutatorChance::InterestingValue => mutator.set_interesting_value_chance(chance),
+            MutatorChance::ByteBlock => mutator.set_byte_block_chance(chance),
+            MutatorChance::ZeroFill => mutator.set_zero_fill_chance(chance),
+            MutatorChance::CStringAnomaly => mutator.set_c_string_anomaly_chance(chance),
+            MutatorChance::SharedMismatch => mutator.set_shared_mismatch_chance(chance),
+        }
+    }
+}
+
+impl fmt::Display for MutatorChance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Applies a single control command to `driver` and returns the reply. Leading and trailing
+/// whitespace is ignored.
+pub fn handle_command<T>(driver: &FuzzerDriver<T>, command: &str) -> String
+where
+    T: 'static + Send + Sync,
+{
+    let mut words = command.split_whitespace();
+    let name = match words.next() {
+        Some(name) => name,
+        None => return String::from("error: empty command"),
+    };
+    let args: Vec<&str> = words.collect();
+
+    match (name, args.as_slice()) {
+        ("pause", []) => {
+            driver.pause();
+            String::from("ok")
+        }
+        ("resume", []) => {
+            driver.resume();
+            String::from("ok")
+        }
+        ("stats", []) => stats_json(driver),
+        ("flush", []) => format!("ok: {} entries", driver.flush_corpus()),
+        ("set", [chance, value]) => {
+            let chance = match MutatorChance::from_name(chance) {
+                Some(chance) => chance,
+                None => return format!("error: unknown chance {:?}", chance),
+            };
+            match value.parse::<f64>() {
+                Ok(value) if (0.0..=1.0).contains(&value) => {
+                    driver.set_mutator_chance(chance, value);
+                    String::from("ok")
+                }
+                _ => format!("error: {:?} is not a probability", value),
+            }
+        }
+        ("help", []) => String::from(HELP),
+        ("pause", _) | ("resume", _) | ("stats", _) | ("flush", _) | ("set", _) | ("help", _) => {
+            format!("error: wrong number of arguments to {}", name)
+        }
+        _ => format!("error: unknown command {:?}", name),
+    }
+}
+
+fn stats_json<T>(driver: &FuzzerDriver<T>) -> String
+where
+    T: 'static + Send + Sync,
+{
+    format!(
+        "{{\"paused\":{},\"iterations\":{},\"crashes\":{},\"hangs\":{},\"rejected\":{},\
+         \"interesting\":{},\"slow_units\":{},\"ooms\":{},\"new_coverage\":{},\
+         \"duplicates\":{},\"poisoned_contexts\":{},\"corpus_entries\":{}}}",
+        driver.is_paused(),
+        driver.num_iterations(),
+        driver.num_crashes(),
+        driver.num_hangs(),
+        driver.num_rejected_inputs(),
+        driver.num_interesting_inputs(),
+        driver.num_slow_units(),
+        driver.num_ooms(),
+        driver.num_new_coverage_inputs(),
+        driver.num_duplicate_inputs(),
+        driver.num_poisoned_contexts(),
+        driver.feedback_corpus().len()
+    )
+}
+
+#[cfg(unix)]
+pub use self::socket::{start_control_socket, ControlHandle};
+
+#[cfg(unix)]
+mod socket {
+    use super::handle_command;
+    use crate::driver::FuzzerDriver;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// How often the control thread checks whether it should exit while no client is connected
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// Handle to a control socket started with [start_control_socket].
+    pub struct ControlHandle {
+        exit: Arc<AtomicBool>,
+        path: PathBuf,
+        handle: thread::JoinHandle<()>,
+    }
+
+    impl ControlHandle {
+        /// The path of the socket
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// Signals the control thread to exit, waits for it, and removes the socket. A client
+        /// which is still connected is served until it disconnects.
+        pub fn stop(self) {
+            self.exit.store(true, Ordering::SeqCst);
+
+            self.handle
+                .join()
+                .unwrap_or_else(|_| println!("control socket thread failed to join"));
+
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    /// Starts a thread which accepts control commands for `driver` on a unix socket at `path`.
+    /// Clients are served one at a time. Fails if `path` already exists.
+    pub fn start_control_socket<T, P>(
+        driver: Arc<FuzzerDriver<T>>,
+        path: P,
+    ) -> io::Result<ControlHandle>
+    where
+        T: 'static + Send + Sync,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let thread_exit = exit.clone();
+
+        let handle = thread::Builder::new()
+            .name(String::from("Control socket"))
+            .spawn(move || {
+                while !thread_exit.load(Ordering::SeqCst) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            if let Err(e) = serve(&driver, stream) {
+                                error!("control socket client failed: {}", e);
+                            }
+                        }
+                        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(POLL_INTERVAL)
+                        }
+                        Err(e) => {
+                            error!("could not accept control socket client: {}", e);
+                            thread::sleep(POLL_INTERVAL)
+                        }
+                    }
+                }
+            })
+            .unwrap_or_else(|_| panic!("could not create control socket thread"));
+
+        Ok(ControlHandle { exit, path, handle })
+    }
+
+    /// Answers every command sent by a client until it disconnects
+    fn serve<T>(driver: &FuzzerDriver<T>, stream: UnixStream) -> io::Result<()>
+    where
+        T: 'static + Send + Sync,
+    {
+        stream.set_nonblocking(false)?;
+        let mut writer = stream.try_clone()?;
+
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = handle_command(driver, &line);
+            info!("control command {:?}: {}", line.trim(), reply);
+            writeln!(writer, "{}", reply)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,968 @@
+//! Corpora of inputs to base new inputs on.
+//!
+//! A [Corpus] is a plain, single-threaded collection of serialized test cases. Entries are
+//! deduplicated by the hash of their content, can be [saved][Corpus::save] to and
+//! [loaded][Corpus::load] from a directory so that a campaign can pick up where an earlier one
+//! left off, and are picked with [Corpus::pick_weighted] according to their weights:
+//!
+//! ```compile_fail
+//! let mut corpus = Corpus::new();
+//! corpus.load("corpus/")?;
+//!
+//! // in the fuzzer routine
+//! let base = corpus.pick_weighted(mutator.rng_mut()).unwrap().to_vec();
+//! if let Outcome::Interesting(_) = run_target(&mutated) {
+//!     corpus.add(mutated);
+//! }
+//!
+//! // once the campaign is over
+//! corpus.save("corpus/")?;
+//! ```
+//!
+//! Files are named after the same content hash the driver names
+//! [persisted inputs][crate::driver::FuzzerDriver::set_output_dir] after, so the driver's
+//! `interesting/<tag>/` directories can be loaded directly.
+//!
+//! A [ShardedCorpus] holds structured inputs and can be shared between fuzzer threads without
+//! the threads contending on a single lock.
+//!
+//! Each worker appends new entries to its own shard. A merger (either [ShardedCorpus::merge]
+//! called manually, or the thread started by [start_merger]) periodically drains every shard,
+//! drops duplicate entries, and appends what's left to the shared corpus that workers read from.
+//!
+//! For long campaigns, [start_snapshotter] periodically writes the shared corpus and its metrics
+//! to timestamped directories (see [ShardedCorpus::write_snapshot]) and removes the oldest ones,
+//! giving restore points from which corpus growth over time can be reconstructed.
+//!
+//! Entries can be picked at random with [ShardedCorpus::choose], but over a long campaign some
+//! entries may go unpicked for hours. [ShardedCorpus::next_scheduled] instead walks the corpus in
+//! epochs: every entry is visited exactly once per epoch, in an order shuffled from the seed set
+//! with [ShardedCorpus::set_schedule_seed], before the corpus is reshuffled for the next epoch.
+//!
+//! Entries can carry tags (e.g. `handshake`, `bulk-data`, or [IMPORTED_TAG]) so that one shared
+//! corpus can serve several focused campaigns. Tags are given when an entry is added with
+//! [ShardedCorpus::add_tagged] or later with [ShardedCorpus::tag], and are merged when the same
+//! entry is found more than once. A campaign then picks entries through a [TagFilter] which
+//! restricts it to, and weights it towards, entries with particular tags:
+//!
+//! ```compile_fail
+//! corpus.add_tagged(input, &["handshake"]);
+//!
+//! // a campaign focused on handshakes which never picks imported seeds
+//! let filter = TagFilter::new().require("handshake").exclude(IMPORTED_TAG);
+//! let seed = corpus.choose_filtered(&mut rng, &filter);
+//! ```
+
+use crate::byteorder::ByteOrder;
+use crate::driver::current_thread_index;
+use crate::rand::rngs::StdRng;
+use crate::rand::seq::SliceRandom;
+use crate::rand::{Rng, SeedableRng};
+use crate::traits::{BinaryDeserialize, BinarySerialize};
+use crate::types::DeserializeError;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Counters describing how the corpus has been used and how often threads had to wait on
+/// one another.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CorpusMetrics {
+    /// Number of entries added to shards
+    pub entries_added: usize,
+    /// Number of merges performed
+    pub merges: usize,
+    /// Number of entries which made it into the shared corpus
+    pub entries_merged: usize,
+    /// Number of entries dropped during merging because they were already in the corpus
+    pub duplicates_dropped: usize,
+    /// Number of times a shard lock was held by another thread when requested
+    pub shard_contentions: usize,
+    /// Number of times the shared corpus lock was held by another thread when requested
+    pub shared_contentions: usize,
+    /// Total time spent waiting on contended locks
+    pub lock_wait_time: Duration,
+    /// Number of scheduling epochs in which every entry was visited
+    pub epochs_completed: usize,
+    /// Number of entries visited so far in the current epoch
+    pub epoch_position: usize,
+    /// Number of entries the current epoch visits
+    pub epoch_len: usize,
+}
+
+#[derive(Default)]
+struct AtomicMetrics {
+    entries_added: AtomicUsize,
+    merges: AtomicUsize,
+    entries_merged: AtomicUsize,
+    duplicates_dropped: AtomicUsize,
+    shard_contentions: AtomicUsize,
+    shared_contentions: AtomicUsize,
+    lock_wait_nanos: AtomicU64,
+}
+
+/// Tag given to entries added by [ShardedCorpus::import_raw_dir]
+pub const IMPORTED_TAG: &str = "imported";
+
+/// Selects and weights corpus entries by their tags for [ShardedCorpus::choose_filtered].
+///
+/// An entry matches if it has every [required][TagFilter::require] tag and none of the
+/// [excluded][TagFilter::exclude] ones. Matching entries are picked with a probability
+/// proportional to the product of the [weights][TagFilter::weight] of their tags, where tags
+/// without a weight count as 1.0.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TagFilter {
+    required: Vec<String>,
+    excluded: Vec<String>,
+    weights: Vec<(String, f64)>,
+}
+
+impl TagFilter {
+    /// A filter matching every entry with equal weight
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Only matches entries tagged with `tag`
+    pub fn require(mut self, tag: &str) -> Self {
+        self.required.push(tag.to_string());
+        self
+    }
+
+    /// Never matches entries tagged with `tag`
+    pub fn exclude(mut self, tag: &str) -> Self {
+        self.excluded.push(tag.to_string());
+        self
+    }
+
+    /// Scales the chance of picking entries tagged with `tag` by `weight`
+    pub fn weight(mut self, tag: &str, weight: f64) -> Self {
+        assert!(weight >= 0.0, "tag weights may not be negative");
+
+        self.weights.push((tag.to_string(), weight));
+        self
+    }
+
+    pub fn matches(&self, tags: &[String]) -> bool {
+        self.required.iter().all(|tag| tags.contains(tag))
+            && !self.excluded.iter().any(|tag| tags.contains(tag))
+    }
+
+    /// The relative chance of picking an entry with `tags`, or 0.0 if it doesn't match
+    pub fn weight_of(&self, tags: &[String]) -> f64 {
+        if !self.matches(tags) {
+            return 0.0;
+        }
+
+        self.weights
+            .iter()
+            .filter(|(tag, _)| tags.contains(tag))
+            .map(|(_, weight)| weight)
+            .product()
+    }
+}
+
+/// State of the epoch schedule used by [ShardedCorpus::next_scheduled].
+struct EpochSchedule {
+    seed: u64,
+    rng: StdRng,
+    /// Shared corpus indices in the order they are visited this epoch
+    order: Vec<usize>,
+    position: usize,
+    epochs_completed: usize,
+}
+
+impl EpochSchedule {
+    fn new(seed: u64) -> Self {
+        EpochSchedule {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            order: vec![],
+            position: 0,
+            epochs_completed: 0,
+        }
+    }
+
+    /// Starts a new epoch over the first `len` entries of the shared corpus. The order of each
+    /// epoch only depends on the seed and the epoch number.
+    fn reshuffle(&mut self, len: usize) {
+        self.rng = StdRng::seed_from_u64(
+            self.seed
+                .wrapping_add((self.epochs_completed as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)),
+        );
+        self.order = (0..len).collect();
+        self.order.shuffle(&mut self.rng);
+        self.position = 0;
+    }
+
+    /// Adds entries merged since the epoch started at random positions among the entries not
+    /// visited yet, so they are still visited exactly once this epoch
+    fn extend(&mut self, len: usize) {
+        for index in self.order.len()..len {
+            let at = self.rng.gen_range(self.position..=self.order.len());
+            self.order.insert(at, index);
+        }
+    }
+}
+
+/// An entry waiting in a shard along with its tags
+type TaggedEntry<I> = (I, Vec<String>);
+
+/// Corpus split into one append-only shard per worker thread plus a deduplicated shared corpus.
+pub struct ShardedCorpus<I> {
+    shards: Vec<Mutex<Vec<TaggedEntry<I>>>>,
+    shared: RwLock<Vec<I>>,
+    /// Tags of each entry in the shared corpus. Always locked after `shared`.
+    shared_tags: RwLock<Vec<Vec<String>>>,
+    /// Maps the hash of every merged entry to its index in the shared corpus
+    seen: Mutex<HashMap<u64, usize>>,
+    metrics: AtomicMetrics,
+    schedule: Mutex<EpochSchedule>,
+}
+
+impl<I: Hash + Clone> ShardedCorpus<I> {
+    /// Creates a new corpus with `num_shards` shards. This should usually match
+    /// [FuzzerDriver::thread_count][crate::driver::FuzzerDriver::thread_count].
+    pub fn new(num_shards: usize) -> Self {
+        let num_shards = std::cmp::max(num_shards, 1);
+
+        ShardedCorpus {
+            shards: (0..num_shards).map(|_| Mutex::new(Vec::new())).collect(),
+            shared: RwLock::new(Vec::new()),
+            shared_tags: RwLock::new(Vec::new()),
+            seen: Mutex::new(HashMap::new()),
+            metrics: AtomicMetrics::default(),
+            schedule: Mutex::new(EpochSchedule::new(0)),
+        }
+    }
+
+    pub fn num_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Adds an entry to the shard owned by the calling fuzzer thread. Threads not started by
+    /// [start_fuzzer][crate::driver::start_fuzzer] use the first shard.
+    pub fn add(&self, item: I) {
+        self.add_to_shard(current_thread_index().unwrap_or(0), item);
+    }
+
+    /// Adds an entry to the shard at `shard` (modulo the number of shards)
+    pub fn add_to_shard(&self, shard: usize, item: I) {
+        self.add_to_shard_tagged(shard, item, &[]);
+    }
+
+    /// Like [ShardedCorpus::add], but tags the entry with `tags`
+    pub fn add_tagged(&self, item: I, tags: &[&str]) {
+        self.add_to_shard_tagged(current_thread_index().unwrap_or(0), item, tags);
+    }
+
+    /// Like [ShardedCorpus::add_to_shard], but tags the entry with `tags`. If the entry turns
+    /// out to be a duplicate, the tags are added to the existing entry instead.
+    pub fn add_to_shard_tagged(&self, shard: usize, item: I, tags: &[&str]) {
+        let mut entry_tags = vec![];
+        for tag in tags.iter() {
+            add_tag(&mut entry_tags, tag);
+        }
+
+        let mut shard = self.lock_shard(shard % self.shards.len());
+        shard.push((item, entry_tags));
+
+        self.metrics.entries_added.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Moves the entries of every shard into the shared corpus, dropping duplicates. Returns the
+    /// number of entries added to the shared corpus.
+    pub fn merge(&self) -> usize {
+        let mut pending = vec![];
+        for i in 0..self.shards.len() {
+            pending.append(&mut self.lock_shard(i));
+        }
+
+        // held until the new entries are in the shared corpus so their indices stay valid
+        let mut seen = self.seen.lock().unwrap();
+        let total = pending.len();
+        let first_index = seen.len();
+
+        let mut unique = vec![];
+        let mut unique_tags = vec![];
+        let mut retagged = vec![];
+        for (item, tags) in pending {
+            match seen.entry(hash_entry(&item)) {
+                Entry::Occupied(existing) => {
+                    if !tags.is_empty() {
+                        retagged.push((*existing.get(), tags));
+                    }
+                }
+                Entry::Vacant(vacant) => {
+                    vacant.insert(first_index + unique.len());
+                    unique.push(item);
+                    unique_tags.push(tags);
+                }
+            }
+        }
+
+        let merged = unique.len();
+        if merged != 0 || !retagged.is_empty() {
+            let mut shared = self.write_shared();
+            let mut shared_tags = self.shared_tags.write().unwrap();
+
+            shared.extend(unique);
+            shared_tags.extend(unique_tags);
+            for (index, tags) in retagged {
+                for tag in tags.iter() {
+                    add_tag(&mut shared_tags[index], tag);
+                }
+            }
+        }
+        drop(seen);
+
+        self.metrics.merges.fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .entries_merged
+            .fetch_add(merged, Ordering::Relaxed);
+        self.metrics
+            .duplicates_dropped
+            .fetch_add(total - merged, Ordering::Relaxed);
+
+        merged
+    }
+
+    /// Number of entries in the shared corpus. Entries which have not been merged yet are not
+    /// counted.
+    pub fn len(&self) -> usize {
+        self.read_shared().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a copy of the entry at `index` in the shared corpus
+    pub fn get(&self, index: usize) -> Option<I> {
+        self.read_shared().get(index).cloned()
+    }
+
+    /// Returns a copy of a random entry from the shared corpus
+    pub fn choose<R: Rng>(&self, rng: &mut R) -> Option<I> {
+        let shared = self.read_shared();
+        if shared.is_empty() {
+            return None;
+        }
+
+        Some(shared[rng.gen_range(0..shared.len())].clone())
+    }
+
+    /// Returns a copy of a random entry from the shared corpus which matches `filter`, picked
+    /// according to the filter's tag weights
+    pub fn choose_filtered<R: Rng>(&self, rng: &mut R, filter: &TagFilter) -> Option<I> {
+        let shared = self.read_shared();
+        let shared_tags = self.shared_tags.read().unwrap();
+
+        let weights: Vec<f64> = shared_tags
+            .iter()
+            .map(|tags| filter.weight_of(tags))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut remaining = rng.gen_range(0.0..total);
+        for (index, weight) in weights.iter().enumerate() {
+            if *weight > 0.0 && remaining < *weight {
+                return Some(shared[index].clone());
+            }
+            remaining -= weight;
+        }
+
+        // floating point error can leave a sliver past the last entry
+        weights
+            .iter()
+            .rposition(|weight| *weight > 0.0)
+            .map(|index| shared[index].clone())
+    }
+
+    /// Indices of the shared corpus entries which match `filter`
+    pub fn filter(&self, filter: &TagFilter) -> Vec<usize> {
+        self.shared_tags
+            .read()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .filter(|(_, tags)| filter.matches(tags))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Tags of the entry at `index` in the shared corpus, in sorted order
+    pub fn tags(&self, index: usize) -> Option<Vec<String>> {
+        self.shared_tags.read().unwrap().get(index).cloned()
+    }
+
+    /// Tags the entry at `index` in the shared corpus. Returns `false` if there is no such
+    /// entry.
+    pub fn tag(&self, index: usize, tag: &str) -> bool {
+        match self.shared_tags.write().unwrap().get_mut(index) {
+            Some(tags) => {
+                add_tag(tags, tag);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the seed the order of each scheduling epoch is derived from and starts over with a
+    /// new first epoch. Defaults to 0.
+    pub fn set_schedule_seed(&self, seed: u64) {
+        *self.schedule.lock().unwrap() = EpochSchedule::new(seed);
+    }
+
+    /// Returns a copy of the next entry in the current scheduling epoch. Every entry in the
+    /// shared corpus is returned exactly once per epoch, in a shuffled order; once all of them
+    /// have been returned, a new epoch starts with a fresh order. Entries merged during an
+    /// epoch are scheduled somewhere in its remainder.
+    pub fn next_scheduled(&self) -> Option<I> {
+        let shared = self.read_shared();
+        if shared.is_empty() {
+            return None;
+        }
+
+        let mut schedule = self.schedule.lock().unwrap();
+        if schedule.order.is_empty() {
+            schedule.reshuffle(shared.len());
+        } else if schedule.position == schedule.order.len() {
+            schedule.epochs_completed += 1;
+            schedule.reshuffle(shared.len());
+        } else {
+            schedule.extend(shared.len());
+        }
+
+        let index = schedule.order[schedule.position];
+        schedule.position += 1;
+
+        Some(shared[index].clone())
+    }
+
+    /// Returns a copy of the entire shared corpus
+    pub fn snapshot(&self) -> Vec<I> {
+        self.read_shared().clone()
+    }
+
+    pub fn metrics(&self) -> CorpusMetrics {
+        let metrics = &self.metrics;
+        let schedule = self.schedule.lock().unwrap();
+
+        CorpusMetrics {
+            entries_added: metrics.entries_added.load(Ordering::Relaxed),
+            merges: metrics.merges.load(Ordering::Relaxed),
+            entries_merged: metrics.entries_merged.load(Ordering::Relaxed),
+            duplicates_dropped: metrics.duplicates_dropped.load(Ordering::Relaxed),
+            shard_contentions: metrics.shard_contentions.load(Ordering::Relaxed),
+            shared_contentions: metrics.shared_contentions.load(Ordering::Relaxed),
+            lock_wait_time: Duration::from_nanos(metrics.lock_wait_nanos.load(Ordering::Relaxed)),
+            epochs_completed: schedule.epochs_completed,
+            epoch_position: schedule.position,
+            epoch_len: schedule.order.len(),
+        }
+    }
+
+    fn record_wait(&self, contentions: &AtomicUsize, started: Instant) {
+        contentions.fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .lock_wait_nanos
+            .fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn lock_shard(&self, index: usize) -> MutexGuard<'_, Vec<TaggedEntry<I>>> {
+        match self.shards[index].try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => {
+                let started = Instant::now();
+                let guard = self.shards[index].lock().unwrap();
+                self.record_wait(&self.metrics.shard_contentions, started);
+
+                guard
+            }
+            Err(TryLockError::Poisoned(e)) => panic!("corpus shard lock poisoned: {}", e),
+        }
+    }
+
+    fn read_shared(&self) -> RwLockReadGuard<'_, Vec<I>> {
+        match self.shared.try_read() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => {
+                let started = Instant::now();
+                let guard = self.shared.read().unwrap();
+                self.record_wait(&self.metrics.shared_contentions, started);
+
+                guard
+            }
+            Err(TryLockError::Poisoned(e)) => panic!("shared corpus lock poisoned: {}", e),
+        }
+    }
+
+    fn write_shared(&self) -> RwLockWriteGuard<'_, Vec<I>> {
+        match self.shared.try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => {
+                let started = Instant::now();
+                let guard = self.shared.write().unwrap();
+                self.record_wait(&self.metrics.shared_contentions, started);
+
+                guard
+            }
+            Err(TryLockError::Poisoned(e)) => panic!("shared corpus lock poisoned: {}", e),
+        }
+    }
+}
+
+/// Results of [ShardedCorpus::import_raw_dir].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Number of files parsed into the corpus
+    pub imported: usize,
+    /// Files which could not be parsed along with the reason why
+    pub failed: Vec<(PathBuf, DeserializeError)>,
+}
+
+impl<I: Hash + Clone + BinaryDeserialize> ShardedCorpus<I> {
+    /// Parses every file in the directory `path` as an `I` using the byte order `E` and merges
+    /// the results into the shared corpus. This allows raw corpora from other fuzzers (e.g. AFL
+    /// or libFuzzer) to be reused as typed seeds.
+    ///
+    /// Imported entries are tagged with [IMPORTED_TAG]. Files which cannot be parsed are logged
+    /// along with the offset at which parsing failed and are returned in
+    /// [ImportSummary::failed]. Subdirectories are skipped.
+    pub fn import_raw_dir<E, P>(&self, path: P) -> io::Result<ImportSummary>
+    where
+        E: ByteOrder,
+        P: AsRef<Path>,
+    {
+        let mut paths = vec![];
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let mut summary = ImportSummary::default();
+        for path in paths {
+            let bytes = std::fs::read(&path)?;
+
+            match I::from_bytes::<E>(&bytes) {
+                Ok(item) => {
+                    self.add_to_shard_tagged(0, item, &[IMPORTED_TAG]);
+                    summary.imported += 1;
+                }
+                Err(e) => {
+                    warn!("could not import {}: {}", path.display(), e);
+                    summary.failed.push((path, e));
+                }
+            }
+        }
+
+        self.merge();
+
+        Ok(summary)
+    }
+}
+
+/// Prefix of the directories written by [ShardedCorpus::write_snapshot]
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+
+impl<I: Hash + Clone + BinarySerialize> ShardedCorpus<I> {
+    /// Writes the shared corpus and its metrics to a new directory under `root` named
+    /// `snapshot-<unix time in milliseconds>`, returning the path of the new directory. Each
+    /// entry is serialized with the byte order `E` to `entries/<index>`, the tags of tagged
+    /// entries are written to `tags.txt` as `<index> <tag>...` lines, and the metrics are
+    /// written to `stats.json`. Entries which have not been merged yet are not included.
+    ///
+    /// The snapshot is written to a temporary directory first and renamed once complete, so
+    /// an interrupted snapshot is never picked up by [list_snapshots].
+    pub fn write_snapshot<E, P>(&self, root: P) -> io::Result<PathBuf>
+    where
+        E: ByteOrder,
+        P: AsRef<Path>,
+    {
+        let root = root.as_ref();
+        std::fs::create_dir_all(root)?;
+
+        let mut timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let mut path = root.join(format!("{}{:013}", SNAPSHOT_PREFIX, timestamp));
+        while path.exists() {
+            timestamp += 1;
+            path = root.join(format!("{}{:013}", SNAPSHOT_PREFIX, timestamp));
+        }
+
+        let temp_path = root.join(format!(".{}{:013}.tmp", SNAPSHOT_PREFIX, timestamp));
+        let entries_path = temp_path.join("entries");
+        std::fs::create_dir_all(&entries_path)?;
+
+        let (entries, tags) = {
+            let shared = self.read_shared();
+            let shared_tags = self.shared_tags.read().unwrap();
+            (shared.clone(), shared_tags.clone())
+        };
+        let mut bytes = vec![];
+        for (i, entry) in entries.iter().enumerate() {
+            bytes.clear();
+            entry.binary_serialize::<_, E>(&mut bytes);
+            std::fs::write(entries_path.join(format!("{:06}", i)), &bytes)?;
+        }
+
+        let tag_lines: String = tags
+            .iter()
+            .enumerate()
+            .filter(|(_, tags)| !tags.is_empty())
+            .map(|(i, tags)| format!("{:06} {}\n", i, tags.join(" ")))
+            .collect();
+        std::fs::write(temp_path.join("tags.txt"), tag_lines)?;
+
+        let metrics = self.metrics();
+        let stats = format!(
+            concat!(
+                "{{\"timestamp_ms\":{},\"entries\":{},\"entries_added\":{},\"merges\":{},",
+                "\"entries_merged\":{},\"duplicates_dropped\":{},\"shard_contentions\":{},",
+                "\"shared_contentions\":{},\"lock_wait_ns\":{},\"epochs_completed\":{}}}"
+            ),
+            timestamp,
+            entries.len(),
+            metrics.entries_added,
+            metrics.merges,
+            metrics.entries_merged,
+            metrics.duplicates_dropped,
+            metrics.shard_contentions,
+            metrics.shared_contentions,
+            metrics.lock_wait_time.as_nanos(),
+            metrics.epochs_completed
+        );
+        std::fs::write(temp_path.join("stats.json"), stats)?;
+
+        std::fs::rename(&temp_path, &path)?;
+        debug!(
+            "wrote corpus snapshot of {} entries to {}",
+            entries.len(),
+            path.display()
+        );
+
+        Ok(path)
+    }
+}
+
+/// Returns the snapshot directories under `root`, oldest first
+pub fn list_snapshots<P: AsRef<Path>>(root: P) -> io::Result<Vec<PathBuf>> {
+    let mut snapshots = vec![];
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir()
+            && entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(SNAPSHOT_PREFIX)
+        {
+            snapshots.push(entry.path());
+        }
+    }
+    snapshots.sort();
+
+    Ok(snapshots)
+}
+
+/// Deletes all but the newest `retain` snapshot directories under `root`. Returns the number of
+/// snapshots removed.
+pub fn rotate_snapshots<P: AsRef<Path>>(root: P, retain: usize) -> io::Result<usize> {
+    let snapshots = list_snapshots(root)?;
+    let expired = snapshots.len().saturating_sub(retain);
+
+    for path in snapshots.iter().take(expired) {
+        std::fs::remove_dir_all(path)?;
+    }
+
+    Ok(expired)
+}
+
+/// Adds `tag` to the sorted list `tags` unless it's already present
+fn add_tag(tags: &mut Vec<String>, tag: &str) {
+    assert!(
+        !tag.is_empty() && !tag.contains(char::is_whitespace),
+        "corpus tags must be non-empty and contain no whitespace"
+    );
+
+    if let Err(index) = tags.binary_search_by(|existing| existing.as_str().cmp(tag)) {
+        tags.insert(index, tag.to_string());
+    }
+}
+
+fn hash_entry<I: Hash>(item: &I) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// A serialized test case in a [Corpus].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusEntry {
+    /// The serialized input
+    pub data: Vec<u8>,
+    /// Hash of `data`, which the entry is saved under
+    pub hash: u64,
+    /// Relative likelihood of the entry being picked by [Corpus::pick_weighted]. A weight of 0
+    /// means it's never picked.
+    pub weight: f64,
+}
+
+/// Serialized test cases, deduplicated by content hash.
+#[derive(Debug, Default, Clone)]
+pub struct Corpus {
+    entries: Vec<CorpusEntry>,
+    /// Maps the hash of every entry to its index
+    hashes: HashMap<u64, usize>,
+}
+
+impl Corpus {
+    pub fn new() -> Self {
+        Corpus::default()
+    }
+
+    /// Adds `data` with a weight of 1. Returns `false` if an entry with the same content is
+    /// already in the corpus.
+    pub fn add<T: Into<Vec<u8>>>(&mut self, data: T) -> bool {
+        self.add_weighted(data, 1.0)
+    }
+
+    /// Like [Corpus::add], but with the given weight. The weight of an existing entry is left
+    /// unchanged.
+    pub fn add_weighted<T: Into<Vec<u8>>>(&mut self, data: T, weight: f64) -> bool {
+        let data = data.into();
+        let hash = hash_entry(&data.as_slice());
+
+        match self.hashes.entry(hash) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(vacant) => {
+                vacant.insert(self.entries.len());
+                self.entries.push(CorpusEntry { data, hash, weight });
+                true
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entries, in the order they were added
+    pub fn entries(&self) -> &[CorpusEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, index: usize) -> Option<&CorpusEntry> {
+        self.entries.get(index)
+    }
+
+    /// Whether an entry with the same content as `data` is in the corpus
+    pub fn contains(&self, data: &[u8]) -> bool {
+        self.hashes.contains_key(&hash_entry(&data))
+    }
+
+    /// Sets the weight of the entry at `index`. Returns `false` if there is no such entry.
+    pub fn set_weight(&mut self, index: usize, weight: f64) -> bool {
+        match self.entries.get_mut(index) {
+            Some(entry) => {
+                entry.weight = weight;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Picks an entry at random, each with a probability proportional to its weight. Returns
+    /// `None` if the corpus is empty or every weight is 0.
+    pub fn pick_weighted<R: Rng>(&self, rng: &mut R) -> Option<&[u8]> {
+        let total: f64 = self.entries.iter().map(|entry| entry.weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut remaining = rng.gen_range(0.0..total);
+        for entry in self.entries.iter() {
+            if entry.weight > 0.0 && remaining < entry.weight {
+                return Some(&entry.data);
+            }
+            remaining -= entry.weight.max(0.0);
+        }
+
+        // floating point error can leave a sliver past the last entry
+        self.entries
+            .iter()
+            .rfind(|entry| entry.weight > 0.0)
+            .map(|entry| entry.data.as_slice())
+    }
+
+    /// Writes every entry which isn't already there to the directory `path`, creating it if
+    /// necessary. Each entry is named after the hash of its content. Returns the number of
+    /// entries written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<usize> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path)?;
+
+        let mut written = 0;
+        for entry in self.entries.iter() {
+            let entry_path = path.join(format!("{:016x}", entry.hash));
+            if !entry_path.exists() {
+                std::fs::write(&entry_path, &entry.data)?;
+                written += 1;
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Adds every file in the directory `path` to the corpus with a weight of 1. Subdirectories
+    /// are skipped. Returns the number of entries added, which excludes files whose content was
+    /// already in the corpus.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize> {
+        let mut paths = vec![];
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let mut added = 0;
+        for path in paths {
+            if self.add(std::fs::read(&path)?) {
+                added += 1;
+            }
+        }
+
+        Ok(added)
+    }
+}
+
+/// Handle to a merger thread started with [start_merger].
+pub struct MergerHandle {
+    exit: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl MergerHandle {
+    /// Signals the merger thread to exit and waits for it. A final merge is performed before
+    /// the thread exits so no entries are left behind in the shards.
+    pub fn stop(self) {
+        self.exit.store(true, Ordering::SeqCst);
+        self.handle.thread().unpark();
+
+        self.handle
+            .join()
+            .unwrap_or_else(|_| println!("corpus merger thread failed to join"));
+    }
+}
+
+/// Starts a thread which merges the shards of `corpus` every `interval`.
+pub fn start_merger<I>(corpus: Arc<ShardedCorpus<I>>, interval: Duration) -> MergerHandle
+where
+    I: Hash + Clone + Send + Sync + 'static,
+{
+    let exit = Arc::new(AtomicBool::new(false));
+    let thread_exit = exit.clone();
+
+    let handle = thread::Builder::new()
+        .name(String::from("Corpus merger"))
+        .spawn(move || loop {
+            thread::park_timeout(interval);
+
+            let merged = corpus.merge();
+            trace!("merged {} new corpus entries", merged);
+
+            if thread_exit.load(Ordering::SeqCst) {
+                return;
+            }
+        })
+        .unwrap_or_else(|_| panic!("could not create corpus merger thread"));
+
+    MergerHandle { exit, handle }
+}
+
+/// Handle to a snapshot thread started with [start_snapshotter].
+pub struct SnapshotHandle {
+    exit: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl SnapshotHandle {
+    /// Signals the snapshot thread to exit and waits for it. A final snapshot is written before
+    /// the thread exits.
+    pub fn stop(self) {
+        self.exit.store(true, Ordering::SeqCst);
+        self.handle.thread().unpark();
+
+        self.handle
+            .join()
+            .unwrap_or_else(|_| println!("corpus snapshot thread failed to join"));
+    }
+}
+
+/// Starts a thread which writes a snapshot of `corpus` to `root` every `interval` using the
+/// byte order `E`, keeping only the newest `retain` snapshots. Failures are logged and do not
+/// stop the thread.
+pub fn start_snapshotter<I, E>(
+    corpus: Arc<ShardedCorpus<I>>,
+    root: PathBuf,
+    interval: Duration,
+    retain: usize,
+) -> SnapshotHandle
+where
+    I: Hash + Clone + BinarySerialize + Send + Sync + 'static,
+    E: ByteOrder + 'static,
+{
+    let exit = Arc::new(AtomicBool::new(false));
+    let thread_exit = exit.clone();
+
+    let handle = thread::Builder::new()
+        .name(String::from("Corpus snapshotter"))
+        .spawn(move || loop {
+            thread::park_timeout(interval);
+
+            if let Err(e) = corpus.write_snapshot::<E, _>(&root) {
+                error!(
+                    "could not write corpus snapshot to {}: {}",
+                    root.display(),
+                    e
+                );
+            }
+
+            if let Err(e) = rotate_snapshots(&root, retain) {
+                error!(
+                    "could not rotate corpus snapshots in {}: {}",
+                    root.display(),
+                    e
+                );
+            }
+
+            if thread_exit.load(Ordering::SeqCst) {
+                return;
+            }
+        })
+        .unwrap_or_else(|_| panic!("could not create corpus snapshot thread"));
+
+    SnapshotHandle { exit, handle }
+}
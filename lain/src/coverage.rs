@@ -0,0 +1,334 @@
+//! Accumulated coverage which can be exported for visualization.
+//!
+//! lain doesn't collect coverage itself; targets report it. A [CoverageMap] accumulates the
+//! edge bitmaps they produce (one hit counter per edge, as with AFL-style instrumentation) into
+//! a campaign-wide map, keeps the edges reached by each corpus entry, and records how coverage
+//! grew over time. The result can be exported as JSON for plotting coverage over time and
+//! spotting plateaus, or as an lcov tracefile for tools like `genhtml` once edges have been
+//! mapped to source lines:
+//!
+//! ```compile_fail
+//! let coverage = Arc::new(CoverageMap::new());
+//!
+//! // in the fuzzer routine
+//! let bitmap = run_target(bytes);
+//! if coverage.record_entry(bytes, &bitmap) > 0 {
+//!     return Outcome::Interesting("new_edges".into());
+//! }
+//!
+//! // once the campaign is over
+//! coverage.write_json("coverage.json")?;
+//! coverage.write_lcov("coverage.info")?;
+//! ```
+
+use crate::report::json_string;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as FmtWrite;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The source line an edge belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+}
+
+/// Total coverage at a point in the campaign.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoveragePoint {
+    /// Time since the map was created
+    pub elapsed: Duration,
+    /// Number of bitmaps recorded so far
+    pub executions: usize,
+    /// Number of distinct edges hit so far
+    pub edges: usize,
+}
+
+/// The edges reached by a single corpus entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryCoverage {
+    /// Hash of the entry, matching the name it's persisted under by the driver
+    pub input_hash: u64,
+    /// Indices of the edges the entry hit, in ascending order
+    pub edges: Vec<usize>,
+    /// Number of those edges no earlier input had hit
+    pub new_edges: usize,
+    /// Time since the map was created
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Default)]
+struct CoverageState {
+    /// Total hit count of every edge
+    hits: Vec<u64>,
+    num_edges: usize,
+    executions: usize,
+    entries: Vec<EntryCoverage>,
+    /// A point is added every time new edges are hit
+    timeline: Vec<CoveragePoint>,
+    last_new_coverage: Option<Instant>,
+    sources: HashMap<usize, SourceLocation>,
+}
+
+/// Campaign-wide edge coverage. Safe to share between fuzzer threads.
+#[derive(Debug)]
+pub struct CoverageMap {
+    started: Instant,
+    state: Mutex<CoverageState>,
+}
+
+impl Default for CoverageMap {
+    fn default() -> Self {
+        CoverageMap::new()
+    }
+}
+
+impl CoverageMap {
+    pub fn new() -> Self {
+        CoverageMap {
+            started: Instant::now(),
+            state: Mutex::new(CoverageState::default()),
+        }
+    }
+
+    /// Adds the hit counts of one execution to the map, returning the number of edges it hit
+    /// for the first time. Byte `i` of `bitmap` is the hit count of edge `i`.
+    pub fn record(&self, bitmap: &[u8]) -> usize {
+        let mut state = self.state.lock().unwrap();
+        self.record_bitmap(&mut state, bitmap)
+    }
+
+    /// Like [CoverageMap::record], additionally keeping the edges hit by `input` so they can be
+    /// exported per corpus entry
+    pub fn record_entry(&self, input: &[u8], bitmap: &[u8]) -> usize {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+
+        let mut state = self.state.lock().unwrap();
+        let new_edges = self.record_bitmap(&mut state, bitmap);
+        state.entries.push(EntryCoverage {
+            input_hash: hasher.finish(),
+            edges: hit_edges(bitmap).collect(),
+            new_edges,
+            elapsed: self.started.elapsed(),
+        });
+
+        new_edges
+    }
+
+    fn record_bitmap(&self, state: &mut CoverageState, bitmap: &[u8]) -> usize {
+        if state.hits.len() < bitmap.len() {
+            state.hits.resize(bitmap.len(), 0);
+        }
+
+        let mut new_edges = 0;
+        for edge in hit_edges(bitmap) {
+            if state.hits[edge] == 0 {
+                new_edges += 1;
+            }
+            state.hits[edge] += u64::from(bitmap[edge]);
+        }
+
+        state.executions += 1;
+        state.num_edges += new_edges;
+
+        if new_edges > 0 || state.timeline.is_empty() {
+            let point = CoveragePoint {
+                elapsed: self.started.elapsed(),
+                executions: state.executions,
+                edges: state.num_edges,
+            };
+            state.timeline.push(point);
+        }
+        if new_edges > 0 {
+            state.last_new_coverage = Some(Instant::now());
+        }
+
+        new_edges
+    }
+
+    /// Maps `edge` to a source line for [CoverageMap::to_lcov]
+    pub fn set_source_location<S: Into<String>>(&self, edge: usize, file: S, line: u32) {
+        self.state.lock().unwrap().sources.insert(
+            edge,
+            SourceLocation {
+                file: file.into(),
+                line,
+            },
+        );
+    }
+
+    /// Number of distinct edges hit so far
+    pub fn num_edges(&self) -> usize {
+        self.state.lock().unwrap().num_edges
+    }
+
+    /// Number of bitmaps recorded so far
+    pub fn executions(&self) -> usize {
+        self.state.lock().unwrap().executions
+    }
+
+    /// Total hit count of `edge`
+    pub fn hits(&self, edge: usize) -> u64 {
+        self.state
+            .lock()
+            .unwrap()
+            .hits
+            .get(edge)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Coverage after the first execution and after every execution which hit new edges
+    pub fn timeline(&self) -> Vec<CoveragePoint> {
+        self.state.lock().unwrap().timeline.clone()
+    }
+
+    /// Coverage of the inputs recorded with [CoverageMap::record_entry], in the order they were
+    /// recorded
+    pub fn entries(&self) -> Vec<EntryCoverage> {
+        self.state.lock().unwrap().entries.clone()
+    }
+
+    /// Time since an execution last hit new edges, or since the map was created if none has
+    pub fn time_since_new_coverage(&self) -> Duration {
+        self.state
+            .lock()
+            .unwrap()
+            .last_new_coverage
+            .unwrap_or(self.started)
+            .elapsed()
+    }
+
+    /// Whether coverage has stopped growing: no new edges have been hit for at least `window`
+    pub fn is_plateaued(&self, window: Duration) -> bool {
+        self.time_since_new_coverage() >= window
+    }
+
+    /// Serializes the map as a JSON object with the hit count of every hit edge (`edges`, as
+    /// `[index, hits]` pairs), the edges of every recorded entry (`entries`), and the growth of
+    /// coverage over time (`timeline`). Times are in milliseconds since the map was created.
+    pub fn to_json(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = String::from("{\"tool\":\"lain\"");
+        write!(
+            out,
+            ",\"version\":{}",
+            json_string(env!("CARGO_PKG_VERSION"))
+        )
+        .unwrap();
+        write!(
+            out,
+            ",\"map_size\":{},\"edges_covered\":{},\"executions\":{}",
+            state.hits.len(),
+            state.num_edges,
+            state.executions
+        )
+        .unwrap();
+
+        out.push_str(",\"edges\":[");
+        let hit: Vec<String> = state
+            .hits
+            .iter()
+            .enumerate()
+            .filter(|(_, hits)| **hits != 0)
+            .map(|(edge, hits)| format!("[{},{}]", edge, hits))
+            .collect();
+        out.push_str(&hit.join(","));
+
+        out.push_str("],\"entries\":[");
+        for (i, entry) in state.entries.iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            let edges: Vec<String> = entry.edges.iter().map(|edge| edge.to_string()).collect();
+            write!(
+                out,
+                "{{\"input_hash\":\"{:016x}\",\"elapsed_ms\":{},\"new_edges\":{},\"edges\":[{}]}}",
+                entry.input_hash,
+                entry.elapsed.as_millis(),
+                entry.new_edges,
+                edges.join(",")
+            )
+            .unwrap();
+        }
+
+        out.push_str("],\"timeline\":[");
+        for (i, point) in state.timeline.iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            write!(
+                out,
+                "{{\"elapsed_ms\":{},\"executions\":{},\"edges\":{}}}",
+                point.elapsed.as_millis(),
+                point.executions,
+                point.edges
+            )
+            .unwrap();
+        }
+        out.push_str("]}");
+
+        out
+    }
+
+    /// Serializes the map as an lcov tracefile. Each line's hit count is the total of the edges
+    /// mapped to it with [CoverageMap::set_source_location]; edges with no source location are
+    /// left out, so without any the tracefile is empty.
+    pub fn to_lcov(&self) -> String {
+        let state = self.state.lock().unwrap();
+
+        let mut files: BTreeMap<&str, BTreeMap<u32, u64>> = BTreeMap::new();
+        for (edge, location) in state.sources.iter() {
+            let hits = state.hits.get(*edge).copied().unwrap_or(0);
+            *files
+                .entry(&location.file)
+                .or_default()
+                .entry(location.line)
+                .or_insert(0) += hits;
+        }
+
+        let mut out = String::new();
+        for (file, lines) in files {
+            out.push_str("TN:\n");
+            writeln!(out, "SF:{}", file).unwrap();
+            for (line, hits) in lines.iter() {
+                writeln!(out, "DA:{},{}", line, hits).unwrap();
+            }
+            writeln!(out, "LF:{}", lines.len()).unwrap();
+            writeln!(
+                out,
+                "LH:{}",
+                lines.values().filter(|hits| **hits != 0).count()
+            )
+            .unwrap();
+            out.push_str("end_of_record\n");
+        }
+
+        out
+    }
+
+    /// Writes the output of [CoverageMap::to_json] to `path`
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Writes the output of [CoverageMap::to_lcov] to `path`
+    pub fn write_lcov<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_lcov())
+    }
+}
+
+/// Indices of the edges with a nonzero hit count
+fn hit_edges(bitmap: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    bitmap
+        .iter()
+        .enumerate()
+        .filter(|(_, hits)| **hits != 0)
+        .map(|(edge, _)| edge)
+}
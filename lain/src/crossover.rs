@@ -0,0 +1,169 @@
+//! Combining two inputs into a new one.
+//!
+//! Mutating a single input over and over only explores the neighborhood of that input.
+//! Crossover takes two inputs of the same type, typically two corpus entries which each reached
+//! some new coverage, and combines them field by field: structs cross each of their fields,
+//! enums of the same variant cross the variant's fields, lists are spliced together at a random
+//! point, and everything else is taken from either parent. See [Crossover] and
+//! [Mutator::crossover].
+//!
+//! ```compile_fail
+//! #[derive(Clone, NewFuzzed, Mutatable, BinarySerialize, Crossover)]
+//! struct Request {
+//!     header: Header,
+//!     body: Vec<Chunk>,
+//! }
+//!
+//! let child = mutator.crossover(&first, &second);
+//! ```
+//!
+//! [start_pipeline_fuzzer][crate::driver::start_pipeline_fuzzer] crosses the corpus entries it
+//! starts iterations from with probability [Mutator::crossover_chance].
+//!
+//! Crossover builds a new structured value, which then has to be serialized in full. When only
+//! the serialized input is needed, [Mutator::transplant] instead copies the serialized bytes of
+//! one field of a random corpus entry over the same field of the serialized input, finding both
+//! through their [field layouts][crate::traits::BinarySerialize::field_layout]. It works with
+//! any type deriving `BinarySerialize`, and since the bytes are copied as they are, the
+//! transplanted field keeps whatever lengths and checksums its donor had:
+//!
+//! ```compile_fail
+//! let bytes = mutator.transplant::<_, BigEndian>(&request, &corpus)?;
+//! ```
+
+use crate::byteorder::ByteOrder;
+use crate::differential::serialize_with_layout;
+use crate::mutator::{Mutator, CHANCE_TO_CROSS_VALUE, CHANCE_TO_SPLICE_LIST};
+use crate::rand::Rng;
+use crate::traits::{BinarySerialize, ControlWordLayout, Crossover};
+use crate::types::{
+    AsciiChar, AsciiString, ControlWord, FieldSpan, UnsafeEnum, Utf8Char, Utf8String,
+};
+
+/// Returns the serialization of `input` with the bytes of the field at `path` replaced by the
+/// bytes of the same field of `donor`, or `None` if either of them has no such field. The
+/// fields after it move if the two fields differ in size.
+pub fn transplant_field<T: BinarySerialize, E: ByteOrder>(
+    input: &T,
+    donor: &T,
+    path: &str,
+) -> Option<Vec<u8>> {
+    let (bytes, layout) = serialize_with_layout::<T, E>(input);
+    let (donor_bytes, donor_layout) = serialize_with_layout::<T, E>(donor);
+
+    let span = find_span(&layout, path)?;
+    let donor_span = find_span(&donor_layout, path)?;
+
+    Some(splice(
+        &bytes,
+        span,
+        &donor_bytes[donor_span.start..donor_span.end],
+    ))
+}
+
+pub(crate) fn find_span<'a>(layout: &'a [FieldSpan], path: &str) -> Option<&'a FieldSpan> {
+    layout.iter().find(|span| span.path == path)
+}
+
+pub(crate) fn splice(bytes: &[u8], span: &FieldSpan, field: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(bytes.len() - span.len() + field.len());
+    output.extend_from_slice(&bytes[..span.start]);
+    output.extend_from_slice(field);
+    output.extend_from_slice(&bytes[span.end..]);
+
+    output
+}
+
+impl<T: Crossover> Crossover for Vec<T> {
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+        if !other.is_empty() && mutator.gen_chance(CHANCE_TO_SPLICE_LIST) {
+            // keep the start of this list and continue with the end of the other one
+            let cut = mutator.gen_range(0, self.len() + 1);
+            let other_cut = mutator.gen_range(0, other.len());
+
+            self.truncate(cut);
+            self.extend_from_slice(&other[other_cut..]);
+        } else {
+            for (element, other_element) in self.iter_mut().zip(other.iter()) {
+                element.crossover(other_element, mutator);
+            }
+        }
+    }
+}
+
+impl<T: Crossover, const N: usize> Crossover for [T; N] {
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+        for (element, other_element) in self.iter_mut().zip(other.iter()) {
+            element.crossover(other_element, mutator);
+        }
+    }
+}
+
+impl<T: Crossover> Crossover for Option<T> {
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+        match (self.as_mut(), other.as_ref()) {
+            (Some(value), Some(other_value)) => value.crossover(other_value, mutator),
+            _ => {
+                if mutator.gen_chance(CHANCE_TO_CROSS_VALUE) {
+                    *self = other.clone();
+                }
+            }
+        }
+    }
+}
+
+impl<T: Crossover> Crossover for Box<T> {
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+        (**self).crossover(&**other, mutator);
+    }
+}
+
+impl Crossover for Utf8String {
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+        self.inner.crossover(&other.inner, mutator);
+    }
+}
+
+impl Crossover for AsciiString {
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+        self.inner.crossover(&other.inner, mutator);
+    }
+}
+
+impl<T: Clone, I: Clone> Crossover for UnsafeEnum<T, I> {
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+        if mutator.gen_chance(CHANCE_TO_CROSS_VALUE) {
+            *self = other.clone();
+        }
+    }
+}
+
+macro_rules! impl_crossover_value {
+    ( $($name:ident),* ) => {
+        $(
+            impl Crossover for $name {
+                fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+                    if mutator.gen_chance(CHANCE_TO_CROSS_VALUE) {
+                        *self = other.clone();
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_crossover_value!(
+    u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64, bool, char, String,
+    Utf8Char, AsciiChar
+);
+
+/// Takes each subfield from the other word independently
+impl<L: ControlWordLayout> Crossover for ControlWord<L> {
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>) {
+        for index in 0..L::SUBFIELDS.len() {
+            if mutator.gen_chance(CHANCE_TO_CROSS_VALUE) {
+                self.set_at(index, other.get_at(index));
+            }
+        }
+    }
+}
@@ -41,6 +41,17 @@ static DANGEROUS_NUMBERS_U64: &[u64] = &[
     0x0000_0000_0000_0080,
 ];
 
+static DANGEROUS_NUMBERS_U128: &[u128] = &[
+    // big-endian variants
+    u128::MIN,
+    u128::MAX,
+    i128::MAX as u128,
+    (i128::MAX as u128) + 1,
+    // little-endian variants
+    0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ff7f,
+    0x0000_0000_0000_0000_0000_0000_0000_0080,
+];
+
 static DANGEROUS_NUMBERS_F32: &[f32] = &[
     f32::INFINITY,
     f32::MAX,
@@ -85,5 +96,19 @@ dangerous_number!(u32, DANGEROUS_NUMBERS_U32);
 dangerous_number!(i32, DANGEROUS_NUMBERS_U32);
 dangerous_number!(u64, DANGEROUS_NUMBERS_U64);
 dangerous_number!(i64, DANGEROUS_NUMBERS_U64);
+dangerous_number!(u128, DANGEROUS_NUMBERS_U128);
+dangerous_number!(i128, DANGEROUS_NUMBERS_U128);
+#[cfg(target_pointer_width = "64")]
+dangerous_number!(usize, DANGEROUS_NUMBERS_U64);
+#[cfg(target_pointer_width = "64")]
+dangerous_number!(isize, DANGEROUS_NUMBERS_U64);
+#[cfg(target_pointer_width = "32")]
+dangerous_number!(usize, DANGEROUS_NUMBERS_U32);
+#[cfg(target_pointer_width = "32")]
+dangerous_number!(isize, DANGEROUS_NUMBERS_U32);
+#[cfg(target_pointer_width = "16")]
+dangerous_number!(usize, DANGEROUS_NUMBERS_U16);
+#[cfg(target_pointer_width = "16")]
+dangerous_number!(isize, DANGEROUS_NUMBERS_U16);
 dangerous_number!(f32, DANGEROUS_NUMBERS_F32);
 dangerous_number!(f64, DANGEROUS_NUMBERS_F64);
@@ -0,0 +1,106 @@
+//! Skipping inputs which were executed recently.
+//!
+//! When constraints leave little room for mutation (small enums, tightly bounded integers,
+//! fixed-size structures) the fuzzer produces the exact same bytes over and over, and every
+//! copy costs a full execution of the target. A [DuplicateCache] remembers hashes of the
+//! serialized inputs executed most recently, and [start_pipeline_fuzzer] skips the callback for
+//! inputs which are already in it:
+//!
+//! ```compile_fail
+//! // remember the last 65536 inputs
+//! driver.set_duplicate_cache(DuplicateCache::new(0x10000));
+//! ```
+//!
+//! Skipped iterations still count towards [FuzzerDriver::num_iterations] but aren't timed,
+//! measured, or reported. [FuzzerDriver::num_duplicate_inputs] counts them.
+//!
+//! [start_pipeline_fuzzer]: crate::driver::start_pipeline_fuzzer
+//! [FuzzerDriver::num_iterations]: crate::driver::FuzzerDriver::num_iterations
+//! [FuzzerDriver::num_duplicate_inputs]: crate::driver::FuzzerDriver::num_duplicate_inputs
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+/// Number of inputs remembered by [DuplicateCache::default]
+pub const DEFAULT_CAPACITY: usize = 0x10000;
+
+#[derive(Debug, Default)]
+struct RecentInputs {
+    hashes: HashSet<u64>,
+    /// The same hashes, oldest first
+    order: VecDeque<u64>,
+}
+
+/// Hashes of the most recently executed inputs. Once it's full, the oldest input is forgotten
+/// for every new one. Safe to share between fuzzer threads.
+///
+/// Inputs are compared by a 64-bit hash of their bytes, so a new input may very rarely be
+/// mistaken for a duplicate.
+#[derive(Debug)]
+pub struct DuplicateCache {
+    capacity: usize,
+    recent: Mutex<RecentInputs>,
+}
+
+impl Default for DuplicateCache {
+    fn default() -> Self {
+        DuplicateCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl DuplicateCache {
+    /// A cache which remembers the last `capacity` distinct inputs
+    pub fn new(capacity: usize) -> Self {
+        DuplicateCache {
+            capacity,
+            recent: Mutex::new(RecentInputs::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of inputs currently remembered
+    pub fn len(&self) -> usize {
+        self.recent.lock().unwrap().order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Records `input` as executed. Returns `false` if it was already in the cache, in which
+    /// case it isn't recorded again.
+    pub fn insert(&self, input: &[u8]) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mut recent = self.recent.lock().unwrap();
+        if !recent.hashes.insert(hash) {
+            return false;
+        }
+
+        recent.order.push_back(hash);
+        if recent.order.len() > self.capacity {
+            let oldest = recent.order.pop_front().unwrap();
+            recent.hashes.remove(&oldest);
+        }
+
+        true
+    }
+
+    /// Forgets every input
+    pub fn clear(&self) {
+        let mut recent = self.recent.lock().unwrap();
+        recent.hashes.clear();
+        recent.order.clear();
+    }
+}
@@ -0,0 +1,303 @@
+//! Dictionaries of tokens spliced into inputs.
+//!
+//! Parsers often only reach deeper code once an input contains a particular token: a magic
+//! number, a keyword (`Content-Length:`, `BEGIN`, `<?xml`), or a protocol verb. Random mutation
+//! rarely produces one by chance, so lain can splice tokens from a dictionary instead.
+//!
+//! A [Dictionary] holds tokens supplied up front. Once it's [set][Mutator::set_dictionary] on
+//! the [Mutator], mutating a `Vec<u8>`, [AsciiString], or [Utf8String] occasionally inserts a
+//! token or overwrites part of the value with one, and the [havoc][crate::pipeline::MutationPipeline::havoc]
+//! stage splices tokens into serialized inputs:
+//!
+//! ```compile_fail
+//! let dictionary: Dictionary = ["GET ", "POST ", "HTTP/1.1", "\r\n\r\n"].iter().collect();
+//! mutator.set_dictionary(dictionary);
+//! ```
+//!
+//! A dictionary can also hold replacements: pairs of a token to find in a serialized input and
+//! a related token to put in its place. Negotiation-style protocols often only take a different
+//! path when a version or algorithm name is swapped for another one the parser knows, which
+//! splicing a token at a random offset rarely achieves:
+//!
+//! ```compile_fail
+//! let mut dictionary = Dictionary::new();
+//! dictionary.add_replacement("TLSv1.2", "SSLv3");
+//! dictionary.add_replacement("v1.0", "v9.9");
+//! mutator.set_dictionary(dictionary);
+//!
+//! let pipeline = MutationPipeline::<Handshake>::default().replace_tokens(0.25);
+//! ```
+//!
+//! A [KeywordDictionary] instead learns tokens over the course of a campaign: every input the
+//! target reports as [Outcome::Interesting] is [observed][KeywordDictionary::observe], the
+//! printable strings it contains are counted, and strings seen in enough distinct interesting
+//! inputs are promoted to keywords. A pipeline stage then splices keywords into the serialized
+//! inputs:
+//!
+//! ```compile_fail
+//! let keywords = Arc::new(KeywordDictionary::new());
+//! driver.set_keyword_dictionary(keywords.clone());
+//!
+//! let pipeline = MutationPipeline::<Request>::default()
+//!     .havoc(0.25, 8)
+//!     .keywords(0.1, keywords);
+//! ```
+//!
+//! [AsciiString]: crate::types::AsciiString
+//! [Utf8String]: crate::types::Utf8String
+//! [Outcome::Interesting]: crate::driver::Outcome::Interesting
+
+use crate::mutator::Mutator;
+use crate::operators::MutationOperator;
+use crate::rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::iter::FromIterator;
+use std::sync::RwLock;
+
+/// Shortest printable string considered a keyword candidate
+pub const DEFAULT_MIN_KEYWORD_LEN: usize = 4;
+
+/// Number of distinct interesting inputs a string must appear in before it's promoted
+pub const DEFAULT_PROMOTION_THRESHOLD: usize = 2;
+
+/// Most keywords a dictionary holds
+pub const DEFAULT_MAX_KEYWORDS: usize = 0x100;
+
+/// Longer strings are truncated to this many bytes before they're counted
+pub const MAX_KEYWORD_LEN: usize = 0x40;
+
+/// Most candidates tracked at once. Strings first seen once this many are being tracked are
+/// ignored.
+const MAX_CANDIDATES: usize = 0x4000;
+
+/// A fixed set of user-provided tokens, such as magic bytes, keywords, and protocol verbs, and
+/// of (find, replace) token pairs.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    tokens: Vec<Vec<u8>>,
+    replacements: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        Dictionary::default()
+    }
+
+    /// Adds `token` unless it's empty or already in the dictionary
+    pub fn add<T: AsRef<[u8]>>(&mut self, token: T) {
+        let token = token.as_ref();
+        if !token.is_empty() && !self.tokens.iter().any(|t| t.as_slice() == token) {
+            self.tokens.push(token.to_vec());
+        }
+    }
+
+    /// The tokens, in the order they were added
+    pub fn tokens(&self) -> &[Vec<u8>] {
+        &self.tokens
+    }
+
+    /// Number of tokens, not counting replacements
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Whether the dictionary has no tokens. It may still have replacements.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Adds a replacement of `find` with `replace` unless `find` is empty, the two are equal, or
+    /// the pair is already in the dictionary. Replacements only apply in one direction; add the
+    /// reverse pair as well to swap tokens either way.
+    pub fn add_replacement<F: AsRef<[u8]>, T: AsRef<[u8]>>(&mut self, find: F, replace: T) {
+        let (find, replace) = (find.as_ref(), replace.as_ref());
+        if find.is_empty() || find == replace {
+            return;
+        }
+
+        if !self
+            .replacements
+            .iter()
+            .any(|(f, r)| f.as_slice() == find && r.as_slice() == replace)
+        {
+            self.replacements.push((find.to_vec(), replace.to_vec()));
+        }
+    }
+
+    /// The (find, replace) pairs, in the order they were added
+    pub fn replacements(&self) -> &[(Vec<u8>, Vec<u8>)] {
+        &self.replacements
+    }
+
+    /// Every occurrence in `bytes` of the find token of each replacement, as (replacement index,
+    /// offset) pairs
+    pub(crate) fn find_replaceable(&self, bytes: &[u8]) -> Vec<(usize, usize)> {
+        self.replacements
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (find, _))| {
+                bytes
+                    .windows(find.len())
+                    .enumerate()
+                    .filter(move |(_, window)| window == find)
+                    .map(move |(offset, _)| (i, offset))
+            })
+            .collect()
+    }
+}
+
+impl<T: AsRef<[u8]>> FromIterator<T> for Dictionary {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut dictionary = Dictionary::new();
+        for token in iter {
+            dictionary.add(token);
+        }
+
+        dictionary
+    }
+}
+
+#[derive(Debug, Default)]
+struct Keywords {
+    /// Number of distinct observed inputs each candidate appeared in
+    candidates: HashMap<Vec<u8>, usize>,
+    /// Promoted keywords, in promotion order
+    keywords: Vec<Vec<u8>>,
+    num_observed: usize,
+}
+
+/// A self-improving list of keywords extracted from interesting inputs. Safe to share between
+/// fuzzer threads.
+#[derive(Debug)]
+pub struct KeywordDictionary {
+    min_keyword_len: usize,
+    promotion_threshold: usize,
+    max_keywords: usize,
+    state: RwLock<Keywords>,
+}
+
+impl Default for KeywordDictionary {
+    fn default() -> Self {
+        KeywordDictionary::new()
+    }
+}
+
+impl KeywordDictionary {
+    pub fn new() -> Self {
+        KeywordDictionary {
+            min_keyword_len: DEFAULT_MIN_KEYWORD_LEN,
+            promotion_threshold: DEFAULT_PROMOTION_THRESHOLD,
+            max_keywords: DEFAULT_MAX_KEYWORDS,
+            state: RwLock::new(Keywords::default()),
+        }
+    }
+
+    /// Sets the shortest printable string considered a keyword candidate
+    pub fn min_keyword_len(mut self, len: usize) -> Self {
+        self.min_keyword_len = std::cmp::max(len, 1);
+        self
+    }
+
+    /// Sets the number of distinct interesting inputs a string must appear in before it's
+    /// promoted to a keyword
+    pub fn promotion_threshold(mut self, threshold: usize) -> Self {
+        self.promotion_threshold = std::cmp::max(threshold, 1);
+        self
+    }
+
+    /// Sets the most keywords the dictionary holds. Once full, no further strings are promoted.
+    pub fn max_keywords(mut self, max: usize) -> Self {
+        self.max_keywords = max;
+        self
+    }
+
+    /// Counts the printable strings in a coverage-increasing `input`, promoting any which have
+    /// now been seen often enough
+    pub fn observe(&self, input: &[u8]) {
+        let strings = self.extract_strings(input);
+        let mut state = self.state.write().unwrap();
+        state.num_observed += 1;
+
+        for string in strings {
+            let count = if let Some(count) = state.candidates.get_mut(&string) {
+                *count += 1;
+                *count
+            } else if state.candidates.len() < MAX_CANDIDATES {
+                state.candidates.insert(string.clone(), 1);
+                1
+            } else {
+                continue;
+            };
+
+            if count == self.promotion_threshold && state.keywords.len() < self.max_keywords {
+                debug!("promoting keyword {:?}", String::from_utf8_lossy(&string));
+                state.keywords.push(string);
+            }
+        }
+    }
+
+    /// The distinct maximal runs of printable ASCII in `input` which are long enough to be
+    /// candidates
+    fn extract_strings(&self, input: &[u8]) -> HashSet<Vec<u8>> {
+        input
+            .split(|b| !is_printable(*b))
+            .filter(|run| run.len() >= self.min_keyword_len)
+            .map(|run| run[..std::cmp::min(run.len(), MAX_KEYWORD_LEN)].to_vec())
+            .collect()
+    }
+
+    /// The promoted keywords, in promotion order
+    pub fn keywords(&self) -> Vec<Vec<u8>> {
+        self.state.read().unwrap().keywords.clone()
+    }
+
+    /// Number of promoted keywords
+    pub fn len(&self) -> usize {
+        self.state.read().unwrap().keywords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of inputs observed so far
+    pub fn num_observed(&self) -> usize {
+        self.state.read().unwrap().num_observed
+    }
+
+    /// Splices a random keyword into `bytes`, either inserting it or overwriting the bytes at a
+    /// random offset. Returns `false` (leaving `bytes` untouched) if no keywords have been
+    /// promoted yet.
+    pub fn mutate<R: Rng>(&self, mutator: &mut Mutator<R>, bytes: &mut Vec<u8>) -> bool {
+        let state = self.state.read().unwrap();
+        if state.keywords.is_empty() {
+            return false;
+        }
+
+        mutator.record_operator(MutationOperator::Keyword);
+
+        let keyword = &state.keywords[mutator.gen_range(0, state.keywords.len())];
+        splice_token(mutator, bytes, keyword.iter().copied());
+
+        true
+    }
+}
+
+/// Either inserts `token` into `values` or overwrites the values at a random offset with it
+pub(crate) fn splice_token<T, R: Rng, I>(mutator: &mut Mutator<R>, values: &mut Vec<T>, token: I)
+where
+    I: ExactSizeIterator<Item = T>,
+{
+    let offset = mutator.gen_range(0, values.len() + 1);
+
+    if mutator.gen_chance(0.5) {
+        values.splice(offset..offset, token);
+    } else {
+        let end = std::cmp::min(offset + token.len(), values.len());
+        values.splice(offset..end, token);
+    }
+}
+
+fn is_printable(b: u8) -> bool {
+    (0x20..0x7f).contains(&b)
+}
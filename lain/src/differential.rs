@@ -0,0 +1,362 @@
+//! Helpers for differential fuzzing: running two implementations of the same parser against
+//! one input and narrowing any disagreement down to the fields responsible for it.
+//!
+//! The narrowing works on serialized bytes using the layout reported by
+//! [BinarySerialize::field_layout]. Starting from a `baseline` input that both
+//! implementations agree on and a `candidate` input they disagree on, fields of the candidate
+//! are reverted to their baseline bytes until the smallest set of fields which still
+//! reproduces the disagreement remains.
+//!
+//! Parsers which detect the byte order of their input (TIFF, PCAP, ELF, ...) can be checked
+//! against themselves: [DualEndian] serializes one value in both byte orders, and
+//! [blame_byte_order] runs a parser on both and attributes any difference in its output to the
+//! fields it misreads.
+//!
+//! ```compile_fail
+//! if let Some(disagreement) = blame_byte_order(&header, |bytes| parse_tiff(bytes)) {
+//!     println!("{}", disagreement);
+//! }
+//! ```
+
+use crate::byteorder::{BigEndian, ByteOrder, LittleEndian};
+use crate::traits::BinarySerialize;
+use crate::types::FieldSpan;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A field which is (part of) the cause of a disagreement between two targets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlamedField {
+    /// Path of the field. Fields which share the same bytes (e.g. members of a bitfield) are
+    /// joined with `|`
+    pub path: String,
+    /// The serialized bytes of this field in input both targets agreed on
+    pub baseline: Vec<u8>,
+    /// The serialized bytes of this field in the input which caused the disagreement
+    pub candidate: Vec<u8>,
+}
+
+/// The minimal set of fields found to cause a disagreement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disagreement {
+    pub fields: Vec<BlamedField>,
+    /// The smallest input which still reproduces the disagreement
+    pub minimized_input: Vec<u8>,
+}
+
+impl fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.fields.is_empty() {
+            return write!(f, "disagreement could not be attributed to any field");
+        }
+
+        write!(f, "disagreement caused by ")?;
+        for (i, field) in self.fields.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(
+                f,
+                "field {} ∈ {{{:02X?}, {:02X?}}}",
+                field.path, field.baseline, field.candidate
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Pair of targets which are expected to produce the same output for every input.
+pub struct DifferentialTarget<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B, O> DifferentialTarget<A, B>
+where
+    A: FnMut(&[u8]) -> O,
+    B: FnMut(&[u8]) -> O,
+    O: PartialEq,
+{
+    pub fn new(first: A, second: B) -> Self {
+        DifferentialTarget { first, second }
+    }
+
+    /// Runs both targets on `input` and returns whether their outputs differ
+    pub fn disagrees(&mut self, input: &[u8]) -> bool {
+        (self.first)(input) != (self.second)(input)
+    }
+
+    /// Runs both targets on `candidate` and, if they disagree, attributes the disagreement to
+    /// the fields which differ from `baseline`. Returns `None` if the targets agree on
+    /// `candidate`.
+    pub fn blame<T: BinarySerialize, E: ByteOrder>(
+        &mut self,
+        baseline: &T,
+        candidate: &T,
+    ) -> Option<Disagreement> {
+        let (baseline_bytes, baseline_layout) = serialize_with_layout::<T, E>(baseline);
+        let (candidate_bytes, candidate_layout) = serialize_with_layout::<T, E>(candidate);
+
+        blame_disagreement(
+            &baseline_bytes,
+            &baseline_layout,
+            &candidate_bytes,
+            &candidate_layout,
+            |input| self.disagrees(input),
+        )
+    }
+}
+
+/// A value serialized in both byte orders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DualEndian {
+    pub little_endian: Vec<u8>,
+    pub big_endian: Vec<u8>,
+}
+
+impl DualEndian {
+    /// Serializes `value` once in each byte order
+    pub fn serialize<T: BinarySerialize>(value: &T) -> Self {
+        let mut little_endian = vec![];
+        value.binary_serialize::<_, LittleEndian>(&mut little_endian);
+
+        let mut big_endian = vec![];
+        value.binary_serialize::<_, BigEndian>(&mut big_endian);
+
+        DualEndian {
+            little_endian,
+            big_endian,
+        }
+    }
+}
+
+/// Runs `target` on `value` serialized in each byte order. A target which detects the byte
+/// order of its input should produce the same output for both. If it doesn't, the difference is
+/// attributed to the fields whose big endian bytes change the output, with the little endian
+/// serialization as the baseline. Returns `None` if the outputs are the same.
+///
+/// A byte order mark has to be modeled as a field whose serialization depends on the byte
+/// order, such as a custom [BinarySerialize] implementation writing `II` or `MM`. Otherwise
+/// the target reads both serializations in the same order.
+pub fn blame_byte_order<T, F, O>(value: &T, mut target: F) -> Option<Disagreement>
+where
+    T: BinarySerialize,
+    F: FnMut(&[u8]) -> O,
+    O: PartialEq,
+{
+    let (little_endian, layout) = serialize_with_layout::<T, LittleEndian>(value);
+    let (big_endian, _) = serialize_with_layout::<T, BigEndian>(value);
+    let expected = target(&little_endian);
+
+    blame_disagreement(&little_endian, &layout, &big_endian, &layout, |input| {
+        target(input) != expected
+    })
+}
+
+/// Serializes `value` and returns the bytes alongside the layout of its fields
+pub fn serialize_with_layout<T: BinarySerialize, E: ByteOrder>(
+    value: &T,
+) -> (Vec<u8>, Vec<FieldSpan>) {
+    let mut bytes = vec![];
+    value.binary_serialize::<_, E>(&mut bytes);
+
+    let mut layout = vec![];
+    value.field_layout("", 0, &mut layout);
+
+    (bytes, layout)
+}
+
+/// A contiguous range of the candidate input which can be swapped out independently
+struct Unit {
+    paths: Vec<String>,
+    start: usize,
+    end: usize,
+    baseline: Option<(usize, usize)>,
+}
+
+/// Returns the smallest byte ranges described by `layout`, i.e. spans which do not
+/// contain any other span. Spans with identical ranges are merged.
+pub(crate) fn leaf_spans(layout: &[FieldSpan]) -> Vec<(Vec<String>, usize, usize)> {
+    let mut leaves: Vec<(Vec<String>, usize, usize)> = vec![];
+
+    for span in layout.iter().filter(|s| !s.is_empty()) {
+        let has_children = layout.iter().any(|other| {
+            !other.is_empty()
+                && (other.start, other.end) != (span.start, span.end)
+                && other.start >= span.start
+                && other.end <= span.end
+        });
+
+        if has_children {
+            continue;
+        }
+
+        match leaves
+            .iter_mut()
+            .find(|(_, start, end)| (*start, *end) == (span.start, span.end))
+        {
+            Some((paths, _, _)) => paths.push(span.path.clone()),
+            None => leaves.push((vec![span.path.clone()], span.start, span.end)),
+        }
+    }
+
+    leaves.sort_by_key(|(_, start, _)| *start);
+    leaves
+}
+
+/// Attributes the disagreement observed on `candidate` to the smallest set of fields which
+/// differ from `baseline`. `disagrees` should return `true` if the targets disagree on the
+/// provided input.
+///
+/// Returns `None` if `disagrees` does not hold for `candidate` itself.
+pub fn blame_disagreement<F>(
+    baseline: &[u8],
+    baseline_layout: &[FieldSpan],
+    candidate: &[u8],
+    candidate_layout: &[FieldSpan],
+    mut disagrees: F,
+) -> Option<Disagreement>
+where
+    F: FnMut(&[u8]) -> bool,
+{
+    if !disagrees(candidate) {
+        return None;
+    }
+
+    let baseline_spans: HashMap<&str, (usize, usize)> = baseline_layout
+        .iter()
+        .map(|s| (s.path.as_str(), (s.start, s.end)))
+        .collect();
+
+    let mut leaves = leaf_spans(candidate_layout);
+    if leaves.is_empty() {
+        // no field information is available, so treat the input as a single field
+        leaves.push((vec![String::from("<root>")], 0, candidate.len()));
+    }
+
+    let units: Vec<Unit> = leaves
+        .into_iter()
+        .map(|(paths, start, end)| {
+            let baseline = if paths[0] == "<root>" {
+                Some((0, baseline.len()))
+            } else {
+                baseline_spans.get(paths[0].as_str()).copied()
+            };
+
+            Unit {
+                paths,
+                start,
+                end,
+                baseline,
+            }
+        })
+        .collect();
+
+    let differing: Vec<usize> = units
+        .iter()
+        .enumerate()
+        .filter(|(_, unit)| match unit.baseline {
+            Some((start, end)) => baseline[start..end] != candidate[unit.start..unit.end],
+            None => false,
+        })
+        .map(|(i, _)| i)
+        .collect();
+
+    // builds an input where only the units in `kept` retain their candidate bytes
+    let build = |kept: &[usize]| -> Vec<u8> {
+        let mut output = Vec::with_capacity(candidate.len());
+        let mut position = 0;
+
+        for (i, unit) in units.iter().enumerate() {
+            output.extend_from_slice(&candidate[position..unit.start]);
+
+            match unit.baseline {
+                Some((start, end)) if differing.contains(&i) && !kept.contains(&i) => {
+                    output.extend_from_slice(&baseline[start..end]);
+                }
+                _ => output.extend_from_slice(&candidate[unit.start..unit.end]),
+            }
+
+            position = unit.end;
+        }
+
+        output.extend_from_slice(&candidate[position..]);
+        output
+    };
+
+    let minimal = ddmin(differing.clone(), |kept| disagrees(&build(kept)));
+
+    let fields = minimal
+        .iter()
+        .map(|&i| {
+            let unit = &units[i];
+            let (start, end) = unit.baseline.unwrap();
+
+            BlamedField {
+                path: unit.paths.join("|"),
+                baseline: baseline[start..end].to_vec(),
+                candidate: candidate[unit.start..unit.end].to_vec(),
+            }
+        })
+        .collect();
+
+    Some(Disagreement {
+        fields,
+        minimized_input: build(&minimal),
+    })
+}
+
+/// Classic delta debugging: finds a 1-minimal subset of `items` for which `test` holds,
+/// assuming it holds for `items` as a whole
+pub(crate) fn ddmin<F>(mut items: Vec<usize>, mut test: F) -> Vec<usize>
+where
+    F: FnMut(&[usize]) -> bool,
+{
+    if !items.is_empty() && test(&[]) {
+        return vec![];
+    }
+
+    let mut granularity = 2;
+    while items.len() >= 2 {
+        let chunk_size = items.len().div_ceil(granularity);
+        let chunks: Vec<Vec<usize>> = items.chunks(chunk_size).map(|c| c.to_vec()).collect();
+
+        let mut reduced = false;
+        for chunk in chunks.iter() {
+            if test(chunk) {
+                items = chunk.clone();
+                granularity = 2;
+                reduced = true;
+                break;
+            }
+        }
+
+        if !reduced && chunks.len() > 2 {
+            for chunk in chunks.iter() {
+                let complement: Vec<usize> = items
+                    .iter()
+                    .copied()
+                    .filter(|i| !chunk.contains(i))
+                    .collect();
+                if test(&complement) {
+                    items = complement;
+                    granularity = std::cmp::max(granularity - 1, 2);
+                    reduced = true;
+                    break;
+                }
+            }
+        }
+
+        if !reduced {
+            if granularity >= items.len() {
+                break;
+            }
+
+            granularity = std::cmp::min(granularity * 2, items.len());
+        }
+    }
+
+    items
+}
@@ -1,10 +1,165 @@
-use crate::mutator::Mutator;
+use crate::calibration::{Calibration, CalibrationReport};
+use crate::concolic::{ConcolicBridge, ConcolicExecutor};
+use crate::control::MutatorChance;
+use crate::corpus::ShardedCorpus;
+use crate::dedup::DuplicateCache;
+use crate::dictionary::KeywordDictionary;
+use crate::feedback::{self, FeedbackProvider, NEW_COVERAGE_TAG};
+use crate::memory;
+use crate::mutator::{EntropySource, Mutator, MutatorMode};
+use crate::operators::MutationOperator;
+use crate::panics::{catch_panic, CaughtPanic};
+use crate::pipeline::MutationPipeline;
+#[cfg(feature = "plugin_support")]
+use crate::plugin::{Plugin, PluginError};
+use crate::report::{Finding, FindingKind, FindingsReport};
+use crate::slow_units::{SlowUnitDetector, SlowUnitThreshold};
+use crate::swarm::{Swarm, DEFAULT_SWARM_DISABLE_CHANCE};
+use crate::target_snapshot::TargetSnapshot;
+use crate::traits::{BinarySerialize, Mutatable, NewFuzzed, Validate};
+use crate::types::Constraints;
+use byteorder::ByteOrder;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use std::any::{Any, TypeId};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    static THREAD_INDEX: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+    /// Number of iterations the fuzzer thread has started
+    static THREAD_ITERATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    /// Set when the fuzzer thread's context has been created with `C::default()` and not yet
+    /// seen by the callback
+    static FRESH_CONTEXT: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    /// Set when the iteration's input was a duplicate and the callback wasn't run
+    static SKIPPED_ITERATION: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Returns the index of the calling fuzzer thread, or `None` if the calling thread was not
+/// started by [start_fuzzer]
+pub fn current_thread_index() -> Option<usize> {
+    THREAD_INDEX.with(|index| index.get())
+}
+
+/// Result of a single fuzzing iteration as reported by the callback passed to [start_fuzzer] or
+/// [start_pipeline_fuzzer]. The driver routes each outcome to the appropriate handling: crashes
+/// and hangs are recorded in [FuzzerDriver::findings] and, when the input is known, persisted to
+/// [FuzzerDriver::set_output_dir]; interesting inputs are saved for use as a corpus; and every
+/// outcome is counted.
+///
+/// Callbacks returning `Result<(), ()>` are still accepted: `Ok(())` maps to [Outcome::Ok] and
+/// `Err(())` to [Outcome::Crash].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Outcome {
+    /// The target handled the input without any notable behavior
+    Ok,
+    /// The target crashed
+    Crash,
+    /// The target stopped responding
+    Hang,
+    /// The target rejected the input before processing it (e.g. a parse or checksum error)
+    Reject,
+    /// The input triggered new or otherwise interesting behavior, identified by the tag
+    Interesting(String),
+    /// The target panicked in-process. Only produced by the driver when
+    /// [FuzzerDriver::set_catch_panics] is enabled.
+    Panic(CaughtPanic),
+}
+
+impl Outcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Crash => "crash",
+            Outcome::Hang => "hang",
+            Outcome::Reject => "reject",
+            Outcome::Interesting(_) => "interesting",
+            Outcome::Panic(_) => "panic",
+        }
+    }
+
+    fn finding_kind(&self) -> Option<FindingKind> {
+        match self {
+            Outcome::Crash => Some(FindingKind::Crash),
+            Outcome::Hang => Some(FindingKind::Hang),
+            Outcome::Panic(panic) => Some(panic.kind),
+            _ => None,
+        }
+    }
+}
+
+/// Returns `tag` if it can be used as is for the directory interesting inputs are persisted to,
+/// or otherwise a sanitized name which stays within that directory
+fn tag_dir_name(tag: &str) -> Result<&str, String> {
+    let is_separator = |c: char| c == '/' || c == '\\' || c == ':' || c == '\0';
+    if !tag.contains(is_separator) && !matches!(tag, "" | "." | "..") {
+        return Ok(tag);
+    }
+
+    let name: String = tag
+        .chars()
+        .map(|c| if is_separator(c) { '_' } else { c })
+        .collect();
+    if name.chars().all(|c| c == '.') {
+        return Err(format!("_{}", name));
+    }
+
+    Err(name)
+}
+
+impl From<Result<(), ()>> for Outcome {
+    fn from(result: Result<(), ()>) -> Self {
+        match result {
+            Ok(()) => Outcome::Ok,
+            Err(()) => Outcome::Crash,
+        }
+    }
+}
+
+/// How often paused fuzzer threads check whether they've been resumed
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+type ErasedValidator = Box<dyn Fn(&dyn Any) -> Result<(), String> + Send + Sync>;
+
+/// An `Arc<dyn TargetSnapshot<C>>` set with [FuzzerDriver::set_target_snapshot], along with the
+/// name of `C`
+type ErasedSnapshot = (Arc<dyn Any + Send + Sync>, &'static str);
+
+/// An `Arc<dyn Fn(&C) -> bool>` set with [FuzzerDriver::set_context_check], along with the name
+/// of `C`
+type ErasedContextCheck = (Arc<dyn Any + Send + Sync>, &'static str);
+
+type ContextCheck<C> = Arc<dyn Fn(&C) -> bool + Send + Sync>;
+
+type ErasedGenerator =
+    Box<dyn Fn(&mut Mutator<StdRng>, Option<usize>) -> Option<Box<dyn Any>> + Send + Sync>;
+
+type ErasedInputCheck = Box<dyn Fn(&dyn Any, &[u8]) -> bool + Send + Sync>;
+
+type ErasedClone = Box<dyn Fn(&dyn Any) -> Box<dyn Any> + Send + Sync>;
+
+/// Type-erased hooks set with [FuzzerDriver::set_input_validation]
+struct InputValidation {
+    input_type: TypeId,
+    input_type_name: &'static str,
+    /// Generates an input with [Mutator::new_validated], within the given `max_size`
+    generate: ErasedGenerator,
+    validate: ErasedInputCheck,
+    clone: ErasedClone,
+}
+
+/// Type-erased predicate set with [FuzzerDriver::set_admission_validator]
+struct AdmissionValidator {
+    input_type: TypeId,
+    input_type_name: &'static str,
+    validate: ErasedValidator,
+}
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum DriverMode {
@@ -18,14 +173,55 @@ pub struct FuzzerDriver<T> {
     threads: RwLock<Vec<thread::JoinHandle<()>>>,
     num_iterations: AtomicUsize,
     num_failed_iterations: AtomicUsize,
+    num_invalid_inputs: AtomicUsize,
+    num_crashes: AtomicUsize,
+    num_hangs: AtomicUsize,
+    num_rejected_inputs: AtomicUsize,
+    num_interesting_inputs: AtomicUsize,
+    num_inadmissible_inputs: AtomicUsize,
+    num_slow_units: AtomicUsize,
+    num_ooms: AtomicUsize,
+    num_new_coverage_inputs: AtomicUsize,
+    num_duplicate_inputs: AtomicUsize,
+    num_poisoned_contexts: AtomicUsize,
+    num_deterministic_passes: AtomicUsize,
+    operator_counts: Vec<AtomicUsize>,
+    findings: FindingsReport,
+    output_dir: Option<PathBuf>,
     exit: AtomicBool,
+    paused: AtomicBool,
     seed: u64,
+    entropy_source: EntropySource,
     global_context: Option<Arc<RwLock<T>>>,
     mode: DriverMode,
     start_iteration: u64,
     end_iteration: u64,
     thread_last_execution_time: Vec<AtomicUsize>,
     thread_timeout: Duration,
+    validation_attempts: usize,
+    mutator_mode: MutatorMode,
+    max_invalid_discriminants: Option<usize>,
+    swarm_epoch_length: Option<usize>,
+    swarm_disable_chance: f64,
+    catch_panics: bool,
+    calibration_samples: usize,
+    calibration: RwLock<Option<CalibrationReport>>,
+    concolic: Option<Arc<ConcolicBridge>>,
+    admission_validator: Option<Arc<AdmissionValidator>>,
+    input_validation: Option<Arc<InputValidation>>,
+    target_snapshot: Option<ErasedSnapshot>,
+    context_check: Option<ErasedContextCheck>,
+    keywords: Option<Arc<KeywordDictionary>>,
+    slow_units: Option<SlowUnitDetector>,
+    memory_budget: Option<usize>,
+    duplicates: Option<DuplicateCache>,
+    feedback: Option<Arc<dyn FeedbackProvider>>,
+    feedback_corpus: Arc<ShardedCorpus<Vec<u8>>>,
+    mutator_chances: RwLock<Vec<(MutatorChance, f64)>>,
+    /// Incremented whenever `mutator_chances` changes
+    mutator_chances_version: AtomicUsize,
+    #[cfg(feature = "plugin_support")]
+    plugins: Vec<Arc<Plugin>>,
 }
 
 impl<T: 'static + Send + Sync> Default for FuzzerDriver<T> {
@@ -52,14 +248,57 @@ impl<T: 'static + Send + Sync> FuzzerDriver<T> {
             threads: RwLock::new(Vec::with_capacity(num_threads)),
             num_iterations: Default::default(),
             num_failed_iterations: Default::default(),
+            num_invalid_inputs: Default::default(),
+            num_crashes: Default::default(),
+            num_hangs: Default::default(),
+            num_rejected_inputs: Default::default(),
+            num_interesting_inputs: Default::default(),
+            num_inadmissible_inputs: Default::default(),
+            num_slow_units: Default::default(),
+            num_ooms: Default::default(),
+            num_new_coverage_inputs: Default::default(),
+            num_duplicate_inputs: Default::default(),
+            num_poisoned_contexts: Default::default(),
+            num_deterministic_passes: Default::default(),
+            operator_counts: MutationOperator::ALL
+                .iter()
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+            findings: FindingsReport::new(),
+            output_dir: None,
             exit: Default::default(),
+            paused: Default::default(),
             seed: rand::random(),
+            entropy_source: EntropySource::default(),
             global_context: Default::default(),
             mode: DriverMode::Run,
             start_iteration: 0,
             end_iteration: 0,
             thread_last_execution_time: last_execution_times,
             thread_timeout: Duration::from_secs(10u64),
+            validation_attempts: crate::mutator::DEFAULT_VALIDATION_ATTEMPTS,
+            mutator_mode: MutatorMode::default(),
+            max_invalid_discriminants: None,
+            swarm_epoch_length: None,
+            swarm_disable_chance: DEFAULT_SWARM_DISABLE_CHANCE,
+            catch_panics: false,
+            calibration_samples: 0,
+            calibration: RwLock::new(None),
+            concolic: None,
+            admission_validator: None,
+            input_validation: None,
+            target_snapshot: None,
+            context_check: None,
+            keywords: None,
+            slow_units: None,
+            memory_budget: None,
+            duplicates: None,
+            feedback: None,
+            feedback_corpus: Arc::new(ShardedCorpus::new(num_threads)),
+            mutator_chances: RwLock::new(vec![]),
+            mutator_chances_version: Default::default(),
+            #[cfg(feature = "plugin_support")]
+            plugins: vec![],
         }
     }
 
@@ -89,11 +328,687 @@ impl<T: 'static + Send + Sync> FuzzerDriver<T> {
         self.num_iterations.store(iterations, Ordering::SeqCst);
     }
 
-    /// Returns the number of iterations that returned an error result
+    /// Returns the number of iterations which ended in a crash or hang
     pub fn num_failed_iterations(&self) -> usize {
         self.num_failed_iterations.load(Ordering::SeqCst)
     }
 
+    /// Returns the number of iterations which reported [Outcome::Crash]
+    pub fn num_crashes(&self) -> usize {
+        self.num_crashes.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of iterations which reported [Outcome::Hang]
+    pub fn num_hangs(&self) -> usize {
+        self.num_hangs.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of iterations which reported [Outcome::Reject]
+    pub fn num_rejected_inputs(&self) -> usize {
+        self.num_rejected_inputs.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of iterations which reported [Outcome::Interesting]
+    pub fn num_interesting_inputs(&self) -> usize {
+        self.num_interesting_inputs.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of interesting inputs which were kept out of the corpus by the
+    /// [admission validator][FuzzerDriver::set_admission_validator]
+    pub fn num_inadmissible_inputs(&self) -> usize {
+        self.num_inadmissible_inputs.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of iterations flagged as slow units
+    pub fn num_slow_units(&self) -> usize {
+        self.num_slow_units.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of iterations which exceeded the memory budget
+    pub fn num_ooms(&self) -> usize {
+        self.num_ooms.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of iterations the [feedback provider][FuzzerDriver::set_feedback_provider]
+    /// judged to have reached new coverage
+    pub fn num_new_coverage_inputs(&self) -> usize {
+        self.num_new_coverage_inputs.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of iterations whose input was skipped by the
+    /// [duplicate cache][FuzzerDriver::set_duplicate_cache]
+    pub fn num_duplicate_inputs(&self) -> usize {
+        self.num_duplicate_inputs.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of inputs of [start_pipeline_fuzzer] which went through a whole
+    /// [deterministic pass][MutatorMode::Deterministic]
+    pub fn num_deterministic_passes(&self) -> usize {
+        self.num_deterministic_passes.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of times a thread context was recreated because it was found
+    /// poisoned after an iteration: the global context's lock was poisoned, or the context
+    /// failed the [check][FuzzerDriver::set_context_check]
+    pub fn num_poisoned_contexts(&self) -> usize {
+        self.num_poisoned_contexts.load(Ordering::SeqCst)
+    }
+
+    /// Number of times each mutation operator has been applied across all threads
+    pub fn operator_counts(&self) -> Vec<(MutationOperator, usize)> {
+        MutationOperator::ALL
+            .iter()
+            .map(|operator| {
+                (
+                    *operator,
+                    self.operator_counts[operator.index()].load(Ordering::SeqCst),
+                )
+            })
+            .collect()
+    }
+
+    /// Adds the operators a thread's mutator has applied since `reported` was last updated
+    fn add_operator_counts<R: Rng>(&self, mutator: &Mutator<R>, reported: &mut [usize]) {
+        for (operator, count) in mutator.operator_counts() {
+            let index = operator.index();
+            self.operator_counts[index].fetch_add(count - reported[index], Ordering::SeqCst);
+            reported[index] = count;
+        }
+    }
+
+    /// Crashes and hangs found so far, along with the seed and iteration needed to reproduce
+    /// them
+    pub fn findings(&self) -> &FindingsReport {
+        &self.findings
+    }
+
+    /// Sets the directory inputs are persisted to. When the input of an iteration is known (as
+    /// with [start_pipeline_fuzzer]), crashing inputs are written to `crashes/`, hanging inputs
+    /// to `hangs/`, interesting inputs to `interesting/<tag>/`, slow units to `slow/`, and inputs
+    /// exceeding the memory budget to `ooms/`, each named after the hash of its content.
+    ///
+    /// Tags which aren't a single plain directory name (empty, `.` or `..`, or containing path
+    /// separators) have the offending characters replaced with `_` so that inputs are never
+    /// written outside of `interesting/`.
+    pub fn set_output_dir<P: AsRef<Path>>(&mut self, path: P) {
+        self.output_dir = Some(path.as_ref().to_path_buf());
+    }
+
+    pub fn output_dir(&self) -> Option<&Path> {
+        self.output_dir.as_deref()
+    }
+
+    /// Counts `outcome`, records crashes and hangs along with the `operators` which produced
+    /// them, and persists `input` if it is known
+    fn route_outcome(
+        &self,
+        outcome: &Outcome,
+        input: Option<&[u8]>,
+        iteration: usize,
+        operators: Vec<MutationOperator>,
+    ) {
+        let counter = match outcome {
+            Outcome::Ok => return,
+            Outcome::Crash | Outcome::Panic(_) => &self.num_crashes,
+            Outcome::Hang => &self.num_hangs,
+            Outcome::Reject => &self.num_rejected_inputs,
+            Outcome::Interesting(_) => &self.num_interesting_inputs,
+        };
+        counter.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(kind) = outcome.finding_kind() {
+            self.num_failed_iterations.fetch_add(1, Ordering::SeqCst);
+            let mut finding = self
+                .new_finding(kind, input)
+                .iteration(iteration as u64)
+                .operators(operators);
+            if let Outcome::Panic(panic) = outcome {
+                finding = finding
+                    .message(panic.message.clone())
+                    .stack(panic.location.iter().cloned());
+            }
+            self.findings.record(finding);
+        }
+
+        let subdir = match outcome {
+            Outcome::Crash | Outcome::Panic(_) => PathBuf::from("crashes"),
+            Outcome::Hang => PathBuf::from("hangs"),
+            Outcome::Interesting(tag) => match tag_dir_name(tag) {
+                Ok(name) => Path::new("interesting").join(name),
+                Err(name) => {
+                    warn!(
+                        "interesting tag {:?} is not a valid directory name, persisting to interesting/{}",
+                        tag, name
+                    );
+                    Path::new("interesting").join(name)
+                }
+            },
+            _ => return,
+        };
+
+        self.persist_input(&subdir, input, outcome.name());
+    }
+
+    /// Creates a finding for `input` in this campaign. Findings are only marked with the root
+    /// seed if they can be reproduced from it.
+    fn new_finding(&self, kind: FindingKind, input: Option<&[u8]>) -> Finding {
+        let finding = Finding::new(kind, input.unwrap_or(&[]), self.seed);
+        match self.entropy_source {
+            EntropySource::Seeded => finding,
+            EntropySource::Os => finding.non_reproducible(),
+        }
+    }
+
+    /// Records an iteration flagged by the slow unit detector, and persists its input to
+    /// `slow/` if it is known
+    fn route_slow_unit(
+        &self,
+        reason: String,
+        input: Option<&[u8]>,
+        iteration: usize,
+        operators: Vec<MutationOperator>,
+    ) {
+        self.num_slow_units.fetch_add(1, Ordering::SeqCst);
+        self.findings.record(
+            self.new_finding(FindingKind::SlowUnit, input)
+                .iteration(iteration as u64)
+                .operators(operators)
+                .message(reason),
+        );
+
+        self.persist_input(Path::new("slow"), input, FindingKind::SlowUnit.name());
+    }
+
+    /// Records an iteration whose peak memory usage exceeded the budget, and persists its input
+    /// to `ooms/` if it is known
+    fn route_oom(
+        &self,
+        peak_memory: usize,
+        input: Option<&[u8]>,
+        iteration: usize,
+        operators: Vec<MutationOperator>,
+    ) {
+        self.num_ooms.fetch_add(1, Ordering::SeqCst);
+        self.findings.record(
+            self.new_finding(FindingKind::Oom, input)
+                .iteration(iteration as u64)
+                .operators(operators)
+                .peak_memory(peak_memory)
+                .message(format!(
+                    "peak memory usage of {} bytes exceeded the budget of {} bytes",
+                    peak_memory,
+                    self.memory_budget.unwrap_or(0)
+                )),
+        );
+
+        self.persist_input(Path::new("ooms"), input, FindingKind::Oom.name());
+    }
+
+    /// Writes `input` to `subdir` of the output directory, named after the hash of its content
+    fn persist_input(&self, subdir: &Path, input: Option<&[u8]>, kind: &str) {
+        if let (Some(output_dir), Some(input)) = (self.output_dir.as_ref(), input) {
+            let dir = output_dir.join(subdir);
+            let mut hasher = DefaultHasher::new();
+            input.hash(&mut hasher);
+            let path = dir.join(format!("{:016x}", hasher.finish()));
+
+            if let Err(e) = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, input))
+            {
+                error!(
+                    "could not persist {} input to {}: {}",
+                    kind,
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Returns the number of generated inputs which failed [Validate] checks and had to be
+    /// repaired or regenerated
+    pub fn num_invalid_inputs(&self) -> usize {
+        self.num_invalid_inputs.load(Ordering::SeqCst)
+    }
+
+    /// Sets the mode of each thread's mutator. In [MutatorMode::Deterministic],
+    /// [start_pipeline_fuzzer] starts a new deterministic pass whenever a thread moves on to
+    /// another input. The progress of a pass depends on the inputs a thread fuzzed before, so
+    /// iterations of a deterministic pass can't be reproduced from the seed alone.
+    pub fn set_mutator_mode(&mut self, mode: MutatorMode) {
+        self.mutator_mode = mode;
+    }
+
+    pub fn mutator_mode(&self) -> MutatorMode {
+        self.mutator_mode
+    }
+
+    /// Sets the maximum number of times each thread's mutator will repair or regenerate an
+    /// input that fails validation. See [Mutator::new_validated].
+    pub fn set_validation_attempts(&mut self, attempts: usize) {
+        self.validation_attempts = attempts;
+    }
+
+    pub fn validation_attempts(&self) -> usize {
+        self.validation_attempts
+    }
+
+    /// Makes [start_pipeline_fuzzer] check every input with its [Validate] implementation
+    /// before running the target on it. New inputs are generated with [Mutator::new_validated]
+    /// (serializing them with the byteorder `E`), and the output of the pipeline is validated
+    /// with the input it was produced from. If it fails, the pipeline is run again on the input
+    /// as it was before, up to [FuzzerDriver::validation_attempts] times. An iteration which
+    /// doesn't produce a valid input is skipped without running the target, and the thread
+    /// starts over with a newly generated input.
+    ///
+    /// `I` must be the input type of the pipeline the driver is started with.
+    pub fn set_input_validation<I, E>(&mut self)
+    where
+        I: 'static + NewFuzzed + BinarySerialize + Validate + Clone,
+        E: ByteOrder,
+    {
+        // the input type is checked when the pipeline fuzzer starts
+        self.input_validation = Some(Arc::new(InputValidation {
+            input_type: TypeId::of::<I>(),
+            input_type_name: std::any::type_name::<I>(),
+            generate: Box::new(|mutator: &mut Mutator<StdRng>, max_size: Option<usize>| {
+                let constraints = max_size.map(|max_size| {
+                    let mut c = Constraints::<<I as NewFuzzed>::RangeType>::new();
+                    c.max_size(max_size);
+                    c
+                });
+
+                mutator
+                    .new_validated::<I, E>(constraints.as_ref())
+                    .map(|input| Box::new(input) as Box<dyn Any>)
+            }),
+            validate: Box::new(|input: &dyn Any, serialized: &[u8]| {
+                input.downcast_ref::<I>().unwrap().validate(serialized)
+            }),
+            clone: Box::new(|input: &dyn Any| Box::new(input.downcast_ref::<I>().unwrap().clone())),
+        }));
+    }
+
+    /// Limits every `UnsafeEnum` type to at most `max` distinct invalid discriminants for the
+    /// whole campaign. The discriminants are sampled from the [root seed][FuzzerDriver::seed],
+    /// so every thread explores the same ones. See [Mutator::set_invalid_discriminant_cap].
+    pub fn set_max_invalid_discriminants(&mut self, max: usize) {
+        self.max_invalid_discriminants = Some(max);
+    }
+
+    pub fn max_invalid_discriminants(&self) -> Option<usize> {
+        self.max_invalid_discriminants
+    }
+
+    /// Enables swarm testing: the campaign is split into epochs of `iterations` iterations
+    /// each, and every epoch leaves out a different subset of enum variants, operators, and
+    /// optional fields derived from the [root seed][FuzzerDriver::seed]. See [crate::swarm].
+    pub fn set_swarm_epoch_length(&mut self, iterations: usize) {
+        self.swarm_epoch_length = Some(std::cmp::max(iterations, 1));
+    }
+
+    pub fn swarm_epoch_length(&self) -> Option<usize> {
+        self.swarm_epoch_length
+    }
+
+    /// Sets the chance for each feature to be left out of a swarm testing epoch. Defaults to
+    /// [DEFAULT_SWARM_DISABLE_CHANCE].
+    pub fn set_swarm_disable_chance(&mut self, chance: f64) {
+        self.swarm_disable_chance = chance;
+    }
+
+    pub fn swarm_disable_chance(&self) -> f64 {
+        self.swarm_disable_chance
+    }
+
+    /// The features left out of the epoch `iteration` belongs to, if swarm testing is enabled
+    pub fn swarm(&self, iteration: usize) -> Option<Swarm> {
+        self.swarm_epoch_length.map(|length| {
+            Swarm::new(self.seed, (iteration / length) as u64)
+                .disable_chance(self.swarm_disable_chance)
+        })
+    }
+
+    /// Sets whether panics raised by the callback are caught. When enabled, a panicking
+    /// iteration is reported as [Outcome::Panic] and recorded in [FuzzerDriver::findings] as an
+    /// assertion failure, overflow, or explicit panic; the thread's context is then reset to its
+    /// default and the thread carries on. When disabled (the default), a panic ends the thread.
+    /// See [crate::panics], and [FuzzerDriver::set_context_check] for state a panic can poison
+    /// beyond its own thread's context.
+    pub fn set_catch_panics(&mut self, catch_panics: bool) {
+        self.catch_panics = catch_panics;
+    }
+
+    pub fn catch_panics(&self) -> bool {
+        self.catch_panics
+    }
+
+    /// Sets the number of inputs generated during the calibration phase [start_pipeline_fuzzer]
+    /// runs before spawning its threads. A value of 0 (the default) disables calibration. See
+    /// [Calibration].
+    pub fn set_calibration_samples(&mut self, samples: usize) {
+        self.calibration_samples = samples;
+    }
+
+    pub fn calibration_samples(&self) -> usize {
+        self.calibration_samples
+    }
+
+    /// Sets the calibration whose tuned settings every fuzzer thread's mutator is configured
+    /// with, e.g. one produced by running [Calibration] manually
+    pub fn set_calibration(&mut self, report: CalibrationReport) {
+        *self.calibration.write().unwrap() = Some(report);
+    }
+
+    /// The result of the calibration phase, if one was run or set
+    pub fn calibration(&self) -> Option<CalibrationReport> {
+        self.calibration.read().unwrap().clone()
+    }
+
+    /// Connects an external concolic executor. [start_pipeline_fuzzer] submits every input
+    /// reported as [Outcome::Interesting] to it, plus every `submit_interval`th input if
+    /// `submit_interval` is non-zero, and runs the inputs it suggests before resuming regular
+    /// mutation. See [crate::concolic].
+    pub fn set_concolic_executor(
+        &mut self,
+        executor: Arc<dyn ConcolicExecutor>,
+        submit_interval: usize,
+    ) {
+        self.concolic = Some(Arc::new(ConcolicBridge::new(executor, submit_interval)));
+    }
+
+    /// Returns the number of inputs submitted to the concolic executor
+    pub fn num_concolic_submissions(&self) -> usize {
+        self.concolic
+            .as_ref()
+            .map_or(0, |concolic| concolic.num_submitted())
+    }
+
+    /// Returns the number of inputs suggested by the concolic executor which have been run
+    pub fn num_concolic_suggestions_run(&self) -> usize {
+        self.concolic
+            .as_ref()
+            .map_or(0, |concolic| concolic.num_suggestions_run())
+    }
+
+    /// Sets a predicate which every input reported as [Outcome::Interesting] by
+    /// [start_pipeline_fuzzer] must pass before it's admitted to the corpus. Inputs the target
+    /// will never accept in practice (e.g. messages whose session ID doesn't belong to the
+    /// campaign) can otherwise still trigger new behavior and pollute the corpus. A rejected
+    /// input is logged with the returned reason and its outcome is downgraded to [Outcome::Ok],
+    /// so it is neither persisted nor submitted to a concolic executor.
+    ///
+    /// `I` must be the input type of the pipeline the driver is started with. Inputs suggested
+    /// by a concolic executor have no structured form and are admitted unchecked.
+    pub fn set_admission_validator<I, V>(&mut self, validator: V)
+    where
+        I: 'static,
+        V: 'static + Fn(&I) -> Result<(), String> + Send + Sync,
+    {
+        self.admission_validator = Some(Arc::new(AdmissionValidator {
+            input_type: TypeId::of::<I>(),
+            input_type_name: std::any::type_name::<I>(),
+            validate: Box::new(move |input: &dyn Any| {
+                // the input type is checked when the pipeline fuzzer starts
+                validator(input.downcast_ref::<I>().unwrap())
+            }),
+        }));
+    }
+
+    /// Calls `snapshot` around every iteration to keep the target's state from carrying over
+    /// from one iteration to the next. See [crate::target_snapshot].
+    ///
+    /// `C` must be the type of the thread context the driver is started with. For
+    /// [start_pipeline_fuzzer], that's the user-provided context rather than the
+    /// [PipelineThreadContext].
+    pub fn set_target_snapshot<C, S>(&mut self, snapshot: S)
+    where
+        C: 'static,
+        S: 'static + TargetSnapshot<C>,
+    {
+        let snapshot: Arc<dyn TargetSnapshot<C>> = Arc::new(snapshot);
+        self.target_snapshot = Some((Arc::new(snapshot), std::any::type_name::<C>()));
+    }
+
+    /// The snapshot set with [FuzzerDriver::set_target_snapshot]. Panics if it was set for a
+    /// context other than `C`.
+    fn target_snapshot<C: 'static>(&self) -> Option<Arc<dyn TargetSnapshot<C>>> {
+        self.target_snapshot
+            .as_ref()
+            .map(|(snapshot, context_type_name)| {
+                snapshot
+                    .downcast_ref::<Arc<dyn TargetSnapshot<C>>>()
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "target snapshot expects a {} context but the fuzzer uses {}",
+                            context_type_name,
+                            std::any::type_name::<C>()
+                        )
+                    })
+                    .clone()
+            })
+    }
+
+    /// Sets a check which every thread context must pass after each iteration. A panic caught
+    /// with [FuzzerDriver::set_catch_panics] can leave state behind that outlives the iteration,
+    /// such as a mutex shared between threads which is now poisoned; a context failing the
+    /// check is replaced with `C::default()` before the next iteration, just like after a
+    /// panic on its own thread.
+    ///
+    /// Independently of this check, a global context whose lock was poisoned is cleared of the
+    /// poison, and the context of the thread which noticed is recreated.
+    ///
+    /// `C` must be the type of the thread context the driver is started with. For
+    /// [start_pipeline_fuzzer], that's the user-provided context rather than the
+    /// [PipelineThreadContext].
+    pub fn set_context_check<C, F>(&mut self, check: F)
+    where
+        C: 'static,
+        F: 'static + Fn(&C) -> bool + Send + Sync,
+    {
+        let check: ContextCheck<C> = Arc::new(check);
+        self.context_check = Some((Arc::new(check), std::any::type_name::<C>()));
+    }
+
+    /// The check set with [FuzzerDriver::set_context_check]. Panics if it was set for a
+    /// context other than `C`.
+    fn context_check<C: 'static>(&self) -> Option<ContextCheck<C>> {
+        self.context_check
+            .as_ref()
+            .map(|(check, context_type_name)| {
+                check
+                    .downcast_ref::<ContextCheck<C>>()
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "context check expects a {} context but the fuzzer uses {}",
+                            context_type_name,
+                            std::any::type_name::<C>()
+                        )
+                    })
+                    .clone()
+            })
+    }
+
+    /// Clears the poison of the global context's lock, returning whether it was poisoned
+    fn clear_poisoned_global_context(&self) -> bool {
+        match self.global_context.as_ref() {
+            Some(context) if context.is_poisoned() => {
+                context.clear_poison();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sets the dictionary [start_pipeline_fuzzer] feeds every input reported as
+    /// [Outcome::Interesting] to. Adding a [MutationPipeline::keywords] stage with the same
+    /// dictionary splices the keywords it learns back into new inputs. See
+    /// [crate::dictionary].
+    pub fn set_keyword_dictionary(&mut self, dictionary: Arc<KeywordDictionary>) {
+        self.keywords = Some(dictionary);
+    }
+
+    pub fn keyword_dictionary(&self) -> Option<&Arc<KeywordDictionary>> {
+        self.keywords.as_ref()
+    }
+
+    /// Flags iterations whose execution time exceeds `threshold` as slow units: they are
+    /// recorded in [FuzzerDriver::findings] as [FindingKind::SlowUnit] and, when the input is
+    /// known, persisted to `slow/`. Iterations which crash or hang are not timed. See
+    /// [crate::slow_units].
+    pub fn set_slow_unit_threshold(&mut self, threshold: SlowUnitThreshold) {
+        self.set_slow_unit_detector(SlowUnitDetector::new(threshold));
+    }
+
+    /// Like [FuzzerDriver::set_slow_unit_threshold], with a detector configured beyond its
+    /// threshold
+    pub fn set_slow_unit_detector(&mut self, detector: SlowUnitDetector) {
+        self.slow_units = Some(detector);
+    }
+
+    pub fn slow_unit_detector(&self) -> Option<&SlowUnitDetector> {
+        self.slow_units.as_ref()
+    }
+
+    /// Records iterations whose peak memory usage exceeds `bytes` in [FuzzerDriver::findings]
+    /// as [FindingKind::Oom] and, when the input is known, persists them to `ooms/`. Usage is
+    /// measured by a [TrackingAllocator][memory::TrackingAllocator] installed as the global
+    /// allocator or reported by the callback with [memory::report_peak_usage]. Iterations which
+    /// crash or hang are not measured. See [crate::memory].
+    pub fn set_memory_budget(&mut self, bytes: usize) {
+        self.memory_budget = Some(bytes);
+    }
+
+    pub fn memory_budget(&self) -> Option<usize> {
+        self.memory_budget
+    }
+
+    /// Skips the callback for inputs which `cache` has seen recently. Skipped iterations are
+    /// counted by [FuzzerDriver::num_duplicate_inputs]. Only used by [start_pipeline_fuzzer],
+    /// since [start_fuzzer]'s callback serializes the input itself. See [crate::dedup].
+    pub fn set_duplicate_cache(&mut self, cache: DuplicateCache) {
+        self.duplicates = Some(cache);
+    }
+
+    pub fn duplicate_cache(&self) -> Option<&DuplicateCache> {
+        self.duplicates.as_ref()
+    }
+
+    /// Passes the coverage reported by the callback with [feedback::report_coverage] to
+    /// `provider` after every iteration. Iterations which reach new coverage are treated as
+    /// [Outcome::Interesting] with the [NEW_COVERAGE_TAG] tag and, when the input is known, the
+    /// input is added to [FuzzerDriver::feedback_corpus]. Iterations which crash, hang, or are
+    /// rejected are not observed. See [crate::feedback].
+    pub fn set_feedback_provider(&mut self, provider: Arc<dyn FeedbackProvider>) {
+        self.feedback = Some(provider);
+    }
+
+    pub fn feedback_provider(&self) -> Option<&Arc<dyn FeedbackProvider>> {
+        self.feedback.as_ref()
+    }
+
+    /// Serialized inputs which reached new coverage, with one shard per fuzzer thread. Entries
+    /// are added to the shard of the thread which found them and become visible once the corpus
+    /// is [merged][ShardedCorpus::merge].
+    pub fn feedback_corpus(&self) -> &Arc<ShardedCorpus<Vec<u8>>> {
+        &self.feedback_corpus
+    }
+
+    /// Merges the shards of [FuzzerDriver::feedback_corpus] and, if an output directory is set,
+    /// writes every entry to `corpus/`, named after the hash of its content. Returns the number
+    /// of entries in the corpus.
+    pub fn flush_corpus(&self) -> usize {
+        self.feedback_corpus.merge();
+
+        let entries = self.feedback_corpus.snapshot();
+        for entry in entries.iter() {
+            self.persist_input(Path::new("corpus"), Some(entry), "corpus");
+        }
+
+        entries.len()
+    }
+
+    /// Sets `chance` on the mutator of every fuzzer thread, including threads which are already
+    /// running. Running threads pick up the change before their next iteration.
+    pub fn set_mutator_chance(&self, chance: MutatorChance, value: f64) {
+        let mut chances = self.mutator_chances.write().unwrap();
+        chances.retain(|(existing, _)| *existing != chance);
+        chances.push((chance, value));
+
+        self.mutator_chances_version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// The chances set with [FuzzerDriver::set_mutator_chance], in the order they were last set
+    pub fn mutator_chances(&self) -> Vec<(MutatorChance, f64)> {
+        self.mutator_chances.read().unwrap().clone()
+    }
+
+    /// Applies the chances set with [FuzzerDriver::set_mutator_chance] to `mutator` if they
+    /// changed since `version`
+    fn apply_mutator_chances<R: Rng>(&self, mutator: &mut Mutator<R>, version: &mut usize) {
+        let current = self.mutator_chances_version.load(Ordering::SeqCst);
+        if current == *version {
+            return;
+        }
+
+        for (chance, value) in self.mutator_chances.read().unwrap().iter() {
+            chance.apply(mutator, *value);
+        }
+        *version = current;
+    }
+
+    /// Hands the coverage reported for the current iteration to the feedback provider. Returns
+    /// whether it was new, in which case `input` is added to the feedback corpus if it is known.
+    fn observe_feedback(&self, outcome: &Outcome, input: Option<&[u8]>) -> bool {
+        let signal = match feedback::take_signal() {
+            Some(signal) => signal,
+            None => return false,
+        };
+        let provider = match self.feedback.as_ref() {
+            Some(provider) => provider,
+            None => return false,
+        };
+
+        match outcome {
+            Outcome::Ok | Outcome::Interesting(_) => (),
+            _ => return false,
+        }
+
+        if !provider.observe(&signal) {
+            return false;
+        }
+
+        self.num_new_coverage_inputs.fetch_add(1, Ordering::SeqCst);
+        if let Some(input) = input {
+            self.feedback_corpus.add(input.to_vec());
+        }
+
+        true
+    }
+
+    /// Loads the plugin library at `path` and keeps it loaded for the lifetime of the driver.
+    /// The returned plugin can be added to a pipeline with
+    /// [MutationPipeline::plugin_mutate] or [MutationPipeline::plugin_serialize].
+    ///
+    /// # Safety
+    ///
+    /// See [Plugin::load].
+    #[cfg(feature = "plugin_support")]
+    pub unsafe fn load_plugin<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<Arc<Plugin>, PluginError> {
+        let plugin = Arc::new(Plugin::load(path)?);
+        self.plugins.push(plugin.clone());
+
+        Ok(plugin)
+    }
+
+    /// The plugins loaded with [FuzzerDriver::load_plugin], in load order
+    #[cfg(feature = "plugin_support")]
+    pub fn plugins(&self) -> &[Arc<Plugin>] {
+        &self.plugins
+    }
+
     pub fn set_global_context(&mut self, context: Arc<RwLock<T>>) {
         self.global_context = Some(context);
     }
@@ -112,6 +1027,31 @@ impl<T: 'static + Send + Sync> FuzzerDriver<T> {
         self.seed
     }
 
+    /// Sets where fuzzer threads get their randomness from. Defaults to
+    /// [EntropySource::Seeded].
+    pub fn set_entropy_source(&mut self, source: EntropySource) {
+        self.entropy_source = source;
+    }
+
+    pub fn entropy_source(&self) -> EntropySource {
+        self.entropy_source
+    }
+
+    /// Stops fuzzer threads from starting new iterations until [FuzzerDriver::resume] is
+    /// called. Iterations which are already running are finished first. Paused threads are not
+    /// considered stalled.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
     /// Signals that all threads should be exiting
     pub fn signal_exit(&self) {
         self.exit.store(true, Ordering::SeqCst);
@@ -196,17 +1136,360 @@ impl<T: 'static + Send + Sync> FuzzerDriver<T> {
 /// The callback should look something like:
 ///
 /// ```compile_fail
-/// fn iteration_routine<R: Rng>(mutator: &mut Mutator<R>, thread_context: &mut FuzzerThreadContext, _global_context: Option<Arc<RwLock<GlobalContext>>>) -> Result<(), ()>
+/// fn iteration_routine<R: Rng>(mutator: &mut Mutator<R>, thread_context: &mut FuzzerThreadContext, _global_context: Option<Arc<RwLock<GlobalContext>>>) -> Outcome
 /// ```
-pub fn start_fuzzer<F: 'static, C: 'static, T: 'static + Send + Sync>(
+///
+/// Since the driver does not see the input generated by the callback, crashes and hangs are
+/// recorded in [FuzzerDriver::findings] without any input metadata and nothing is persisted to
+/// the output directory. For the same reason [FuzzerDriver::set_input_validation] has no effect
+/// here; a callback whose input implements [Validate] should produce it with
+/// [Mutator::new_validated] and [Mutator::mutate_validated].
+pub fn start_fuzzer<F: 'static, C: 'static, T: 'static + Send + Sync, O>(
     driver: Arc<FuzzerDriver<T>>,
     callback: F,
 ) where
-    F: Fn(&mut Mutator<StdRng>, &mut C, Option<Arc<RwLock<T>>>) -> Result<(), ()>
+    F: Fn(&mut Mutator<StdRng>, &mut C, Option<Arc<RwLock<T>>>) -> O
         + std::marker::Send
         + std::marker::Sync
         + Copy,
     C: Default,
+    O: Into<Outcome>,
+{
+    let snapshot = driver.target_snapshot::<C>();
+    let context_check = driver.context_check::<C>();
+
+    spawn_fuzzer_threads(
+        driver,
+        move |context: &C| context_check.as_ref().is_none_or(|check| check(context)),
+        move |mutator: &mut Mutator<StdRng>, context: &mut C, global_context| {
+            let snapshot = match snapshot.as_ref() {
+                Some(snapshot) => snapshot,
+                None => return (callback(mutator, context, global_context).into(), None),
+            };
+
+            let iterations = begin_snapshot_iteration(snapshot.as_ref(), context);
+            let outcome = callback(mutator, context, global_context).into();
+            snapshot.after_iteration(context, &outcome, iterations);
+
+            (outcome, None)
+        },
+    );
+}
+
+/// Calls the hooks of `snapshot` which run before an iteration, returning the number of
+/// iterations the calling thread has run
+fn begin_snapshot_iteration<C>(snapshot: &dyn TargetSnapshot<C>, context: &mut C) -> usize {
+    if FRESH_CONTEXT.with(|fresh| fresh.replace(false)) {
+        snapshot.context_created(context);
+    }
+
+    let iterations = THREAD_ITERATIONS.with(|iterations| iterations.get());
+    snapshot.before_iteration(context, iterations);
+
+    iterations
+}
+
+/// Per-thread state used by [start_pipeline_fuzzer]
+pub struct PipelineThreadContext<I, C> {
+    /// The input which is mutated by the pipeline on every iteration
+    pub input: Option<I>,
+    /// The user-provided thread context
+    pub context: C,
+}
+
+impl<I, C: Default> Default for PipelineThreadContext<I, C> {
+    fn default() -> Self {
+        PipelineThreadContext {
+            input: None,
+            context: C::default(),
+        }
+    }
+}
+
+/// Kicks off a fuzzing job where each thread runs its input through `pipeline` every
+/// iteration. The first iteration on each thread generates the input with
+/// [NewFuzzed::new_fuzzed][crate::traits::NewFuzzed::new_fuzzed]; subsequent iterations
+/// mutate it further. The callback receives the serialized bytes produced by the pipeline along
+/// with the structured input:
+///
+/// ```compile_fail
+/// fn iteration_routine(data: &[u8], packet: &Packet, thread_context: &mut FuzzerThreadContext, _global_context: Option<Arc<RwLock<GlobalContext>>>) -> Outcome
+/// ```
+///
+/// If the target rejects the input with [Outcome::Reject], the thread starts over with a newly
+/// generated input on its next iteration.
+///
+/// If [FuzzerDriver::set_calibration_samples] is non-zero, the callback is first run against
+/// that many generated inputs (with a default thread context) to calibrate the mutators. New
+/// inputs are then generated with the calibrated `max_size`.
+///
+/// If a concolic executor was connected with [FuzzerDriver::set_concolic_executor], the inputs
+/// it suggests are run in place of the pipeline's output whenever any are pending.
+///
+/// If an admission validator was set with [FuzzerDriver::set_admission_validator], interesting
+/// inputs which fail it are treated as [Outcome::Ok].
+///
+/// If input validation was enabled with [FuzzerDriver::set_input_validation], inputs which fail
+/// [Validate::validate] never reach the callback. Inputs suggested by a concolic executor are
+/// validated along with the structured input they were derived from, and skipped if invalid.
+///
+/// If a keyword dictionary was set with [FuzzerDriver::set_keyword_dictionary], every
+/// interesting input (including ones suggested by a concolic executor) is observed by it.
+///
+/// If a feedback provider was set with [FuzzerDriver::set_feedback_provider] and `I` implements
+/// `Clone` (see [Mutatable::clone_seed]), inputs which reach new coverage are also kept in
+/// structured form, and a thread whose input reached no new coverage continues from a copy of a
+/// random one of them.
+///
+/// If the mutator mode was set to [MutatorMode::Deterministic] with
+/// [FuzzerDriver::set_mutator_mode], every new input of a thread goes through a deterministic
+/// pass before it's mutated. [FuzzerDriver::num_deterministic_passes] counts completed passes.
+pub fn start_pipeline_fuzzer<I, F, C, T, O>(
+    driver: Arc<FuzzerDriver<T>>,
+    pipeline: Arc<MutationPipeline<I>>,
+    callback: F,
+) where
+    I: 'static + NewFuzzed + Mutatable + BinarySerialize + Send + Sync,
+    F: 'static
+        + Fn(&[u8], &I, &mut C, Option<Arc<RwLock<T>>>) -> O
+        + std::marker::Send
+        + std::marker::Sync
+        + Copy,
+    C: 'static + Default,
+    T: 'static + Send + Sync,
+    O: Into<Outcome>,
+{
+    let input_validation = driver.input_validation.clone();
+    if let Some(validation) = input_validation.as_ref() {
+        assert!(
+            validation.input_type == TypeId::of::<I>(),
+            "input validation expects {} inputs but the pipeline produces {}",
+            validation.input_type_name,
+            std::any::type_name::<I>()
+        );
+    }
+
+    if driver.calibration_samples() > 0 {
+        let mut context = C::default();
+        let report = Calibration::<I>::new()
+            .samples(driver.calibration_samples())
+            .seed(driver.seed())
+            .run(|bytes, input| match input_validation.as_ref() {
+                // calibration samples aren't repaired, so an invalid one counts as rejected
+                Some(validation) if !(validation.validate)(input, bytes) => Outcome::Reject,
+                _ => callback(bytes, input, &mut context, driver.global_context()).into(),
+            });
+
+        info!("{}", report);
+        *driver.calibration.write().unwrap() = Some(report);
+    }
+
+    let max_size = driver.calibration().and_then(|report| report.max_size);
+    let catch_panics = driver.catch_panics();
+    let concolic = driver.concolic.clone();
+    let admission_validator = driver.admission_validator.clone();
+    let keywords = driver.keywords.clone();
+    let snapshot = driver.target_snapshot::<C>();
+    let context_check = driver.context_check::<C>();
+    let seeds: Arc<RwLock<Vec<I>>> = Arc::new(RwLock::new(vec![]));
+    if let Some(validator) = admission_validator.as_ref() {
+        assert!(
+            validator.input_type == TypeId::of::<I>(),
+            "admission validator expects {} inputs but the pipeline produces {}",
+            validator.input_type_name,
+            std::any::type_name::<I>()
+        );
+    }
+    let thread_driver = driver.clone();
+
+    spawn_fuzzer_threads(
+        driver,
+        move |thread_context: &PipelineThreadContext<I, C>| {
+            context_check
+                .as_ref()
+                .is_none_or(|check| check(&thread_context.context))
+        },
+        move |mutator: &mut Mutator<StdRng>,
+              thread_context: &mut PipelineThreadContext<I, C>,
+              global_context| {
+            let input = match thread_context.input {
+                Some(ref mut input) => input,
+                None => {
+                    mutator.reset_deterministic_progress();
+                    let input = match input_validation.as_ref() {
+                        Some(validation) => match (validation.generate)(mutator, max_size) {
+                            Some(input) => *input.downcast::<I>().unwrap(),
+                            None => {
+                                SKIPPED_ITERATION.with(|skipped| skipped.set(true));
+                                return (Outcome::Ok, None);
+                            }
+                        },
+                        None => {
+                            let constraints = max_size.map(|max_size| {
+                                let mut c = Constraints::<<I as NewFuzzed>::RangeType>::new();
+                                c.max_size(max_size);
+                                c
+                            });
+                            I::new_fuzzed(mutator, constraints.as_ref())
+                        }
+                    };
+                    thread_context.input.insert(input)
+                }
+            };
+
+            // inputs suggested by the concolic executor take priority over mutation. they're
+            // derived from an earlier input's bytes, so the structured input won't match them
+            let suggestion = concolic.as_ref().and_then(|c| c.next_suggestion());
+            let from_suggestion = suggestion.is_some();
+            let bytes = match suggestion {
+                Some(suggestion) => {
+                    mutator.record_operator(MutationOperator::Concolic);
+                    let bytes = suggestion.apply();
+                    if let Some(validation) = input_validation.as_ref() {
+                        if !(validation.validate)(input, &bytes) {
+                            thread_driver
+                                .num_invalid_inputs
+                                .fetch_add(1, Ordering::SeqCst);
+                            SKIPPED_ITERATION.with(|skipped| skipped.set(true));
+                            return (Outcome::Ok, None);
+                        }
+                    }
+
+                    bytes
+                }
+                None => {
+                    let in_deterministic_pass = mutator.in_deterministic_pass();
+                    let bytes = match input_validation.as_ref() {
+                        Some(validation) => {
+                            let mut valid_bytes = None;
+                            for _i in 0..std::cmp::max(mutator.validation_attempts(), 1) {
+                                let previous = (validation.clone)(input);
+                                let bytes = pipeline.run(mutator, input);
+                                if (validation.validate)(input, &bytes) {
+                                    valid_bytes = Some(bytes);
+                                    break;
+                                }
+
+                                thread_driver
+                                    .num_invalid_inputs
+                                    .fetch_add(1, Ordering::SeqCst);
+                                *input = *previous.downcast::<I>().unwrap();
+                            }
+                            valid_bytes
+                        }
+                        None => Some(pipeline.run(mutator, input)),
+                    };
+                    if in_deterministic_pass && !mutator.in_deterministic_pass() {
+                        thread_driver
+                            .num_deterministic_passes
+                            .fetch_add(1, Ordering::SeqCst);
+                    }
+
+                    match bytes {
+                        Some(bytes) => bytes,
+                        None => {
+                            thread_context.input = None;
+                            SKIPPED_ITERATION.with(|skipped| skipped.set(true));
+                            return (Outcome::Ok, None);
+                        }
+                    }
+                }
+            };
+
+            if let Some(duplicates) = thread_driver.duplicates.as_ref() {
+                if !duplicates.insert(&bytes) {
+                    thread_driver
+                        .num_duplicate_inputs
+                        .fetch_add(1, Ordering::SeqCst);
+                    SKIPPED_ITERATION.with(|skipped| skipped.set(true));
+                    return (Outcome::Ok, None);
+                }
+            }
+
+            let context = &mut thread_context.context;
+            let iterations = snapshot
+                .as_ref()
+                .map(|snapshot| begin_snapshot_iteration(snapshot.as_ref(), context));
+            let outcome = if catch_panics {
+                // catch the panic here rather than in the thread so the input is still known
+                catch_panic(|| callback(&bytes, input, context, global_context).into())
+                    .unwrap_or_else(Outcome::Panic)
+            } else {
+                callback(&bytes, input, context, global_context).into()
+            };
+            if let (Some(snapshot), Some(iterations)) = (snapshot.as_ref(), iterations) {
+                snapshot.after_iteration(context, &outcome, iterations);
+            }
+            let new_coverage = thread_driver.observe_feedback(&outcome, Some(&bytes));
+            let outcome = match outcome {
+                Outcome::Ok if new_coverage => Outcome::Interesting(NEW_COVERAGE_TAG.to_string()),
+                outcome => outcome,
+            };
+            let outcome = match (outcome, admission_validator.as_ref()) {
+                (Outcome::Interesting(tag), Some(validator)) if !from_suggestion => {
+                    match (validator.validate)(input) {
+                        Ok(()) => Outcome::Interesting(tag),
+                        Err(reason) => {
+                            debug!("keeping interesting input out of the corpus: {}", reason);
+                            thread_driver
+                                .num_inadmissible_inputs
+                                .fetch_add(1, Ordering::SeqCst);
+                            Outcome::Ok
+                        }
+                    }
+                }
+                (outcome, _) => outcome,
+            };
+            if let (Outcome::Interesting(_), Some(keywords)) = (&outcome, keywords.as_ref()) {
+                keywords.observe(&bytes);
+            }
+            if let Some(concolic) = concolic.as_ref() {
+                let mut layout = vec![];
+                if !from_suggestion {
+                    input.field_layout("", 0, &mut layout);
+                }
+                concolic.observe(&outcome, &bytes, &layout);
+            }
+
+            if outcome == Outcome::Reject && !from_suggestion {
+                thread_context.input = None;
+            } else if new_coverage && !from_suggestion {
+                if let Some(seed) = input.clone_seed() {
+                    seeds.write().unwrap().push(seed);
+                }
+            } else if thread_driver.feedback.is_some() && outcome == Outcome::Ok {
+                let seeds = seeds.read().unwrap();
+                if !seeds.is_empty() {
+                    let seed = &seeds[mutator.gen_range(0, seeds.len())];
+                    thread_context.input = if mutator.gen_chance(mutator.crossover_chance()) {
+                        let other = &seeds[mutator.gen_range(0, seeds.len())];
+                        seed.crossover_seed(other, mutator)
+                            .or_else(|| seed.clone_seed())
+                    } else {
+                        seed.clone_seed()
+                    };
+                    mutator.reset_deterministic_progress();
+                }
+            }
+
+            (outcome, Some(bytes))
+        },
+    );
+}
+
+/// Runs `callback` on every fuzzer thread until the driver signals them to exit. A thread's
+/// context is recreated after an iteration which panicked or poisoned the global context, or
+/// after which the context fails `context_ok`.
+fn spawn_fuzzer_threads<K, F, C, T>(driver: Arc<FuzzerDriver<T>>, context_ok: K, callback: F)
+where
+    K: 'static + Fn(&C) -> bool + std::marker::Send + std::marker::Sync + Clone,
+    F: 'static
+        + Fn(&mut Mutator<StdRng>, &mut C, Option<Arc<RwLock<T>>>) -> (Outcome, Option<Vec<u8>>)
+        + std::marker::Send
+        + std::marker::Sync
+        + Clone,
+    C: 'static + Default,
+    T: 'static + Send + Sync,
 {
     let mut root_rng = StdRng::seed_from_u64(driver.seed());
 
@@ -217,6 +1500,8 @@ pub fn start_fuzzer<F: 'static, C: 'static, T: 'static + Send + Sync>(
         let thread_name = format!("Fuzzer thread {}", i);
 
         let thread_seed: u64 = root_rng.gen();
+        let callback = callback.clone();
+        let context_ok = context_ok.clone();
 
         let join_handle = thread::Builder::new()
             .name(thread_name)
@@ -225,16 +1510,37 @@ pub fn start_fuzzer<F: 'static, C: 'static, T: 'static + Send + Sync>(
                 // on the first loop iteration
                 let thread_rng = StdRng::seed_from_u64(0u64);
                 let mut mutator = Mutator::new(thread_rng);
+                mutator.set_validation_attempts(thread_driver.validation_attempts());
+                mutator.set_mode(thread_driver.mutator_mode());
+                if let Some(max) = thread_driver.max_invalid_discriminants() {
+                    mutator.set_invalid_discriminant_cap(max, thread_driver.seed());
+                }
+                if let Some(report) = thread_driver.calibration() {
+                    report.apply(&mut mutator);
+                }
                 let mut context = C::default();
+                let mut reported_operators = [0usize; MutationOperator::ALL.len()];
+                let mut mutator_chances_version = 0;
+
+                THREAD_INDEX.with(|index| index.set(Some(i)));
+                FRESH_CONTEXT.with(|fresh| fresh.set(true));
 
                 // loop until we get a signal that we should exit
                 loop {
                     thread_driver.set_thread_last_execution_time(i);
 
+                    if thread_driver.is_paused() && !thread_driver.should_exit() {
+                        thread::sleep(PAUSE_POLL_INTERVAL);
+                        continue;
+                    }
+
                     // TODO: here be dragons? num_iterations is a usize and we're casting it to a u64. on 64-bit systems this
                     // isn't a problem since usize should be a u64, but it's worth noting that this could be a potential issue
                     let new_seed = thread_seed.wrapping_add(thread_driver.num_iterations() as u64);
-                    mutator.rng = StdRng::seed_from_u64(new_seed);
+                    mutator.rng = match thread_driver.entropy_source() {
+                        EntropySource::Seeded => StdRng::seed_from_u64(new_seed),
+                        EntropySource::Os => StdRng::from_entropy(),
+                    };
 
                     if thread_driver.should_exit() {
                         log::info!("{} exiting", thread::current().name().unwrap());
@@ -242,15 +1548,89 @@ pub fn start_fuzzer<F: 'static, C: 'static, T: 'static + Send + Sync>(
                     }
 
                     mutator.random_flags();
+                    thread_driver.apply_mutator_chances(&mut mutator, &mut mutator_chances_version);
+
+                    let iteration = thread_driver.num_iterations();
+                    mutator.set_swarm(thread_driver.swarm(iteration));
+                    let global_context = thread_driver.global_context();
+                    if thread_driver.memory_budget.is_some() {
+                        memory::begin_iteration();
+                    }
+                    // discard any signal left behind by an iteration which panicked
+                    feedback::take_signal();
+                    THREAD_ITERATIONS.with(|iterations| iterations.set(iterations.get() + 1));
+                    let start = Instant::now();
+                    let (outcome, input) = if thread_driver.catch_panics() {
+                        catch_panic(|| (callback)(&mut mutator, &mut context, global_context))
+                            .unwrap_or_else(|panic| (Outcome::Panic(panic), None))
+                    } else {
+                        (callback)(&mut mutator, &mut context, global_context)
+                    };
+                    let elapsed = start.elapsed();
+                    let peak_memory = thread_driver
+                        .memory_budget
+                        .and_then(|_| memory::end_iteration());
+                    let skipped = SKIPPED_ITERATION.with(|skipped| skipped.replace(false));
+
+                    let outcome = match outcome {
+                        Outcome::Ok
+                            if thread_driver.observe_feedback(&outcome, input.as_deref()) =>
+                        {
+                            Outcome::Interesting(NEW_COVERAGE_TAG.to_string())
+                        }
+                        outcome => outcome,
+                    };
 
-                    if (callback)(&mut mutator, &mut context, thread_driver.global_context())
-                        .is_err()
+                    let operators = mutator.take_applied_operators();
+                    if let Some(detector) = thread_driver.slow_units.as_ref() {
+                        if outcome.finding_kind().is_none() && !skipped {
+                            if let Some(reason) = detector.observe(elapsed) {
+                                thread_driver.route_slow_unit(
+                                    reason,
+                                    input.as_deref(),
+                                    iteration,
+                                    operators.clone(),
+                                );
+                            }
+                        }
+                    }
+                    if let (Some(budget), Some(peak_memory)) =
+                        (thread_driver.memory_budget, peak_memory)
                     {
+                        if outcome.finding_kind().is_none() && peak_memory > budget {
+                            thread_driver.route_oom(
+                                peak_memory,
+                                input.as_deref(),
+                                iteration,
+                                operators.clone(),
+                            );
+                        }
+                    }
+                    thread_driver.route_outcome(&outcome, input.as_deref(), iteration, operators);
+                    thread_driver.add_operator_counts(&mutator, &mut reported_operators);
+
+                    // the panic may have left the context half-updated. a panic on another
+                    // thread may also have poisoned state this thread shares with it
+                    let panicked = matches!(outcome, Outcome::Panic(_));
+                    let poisoned = thread_driver.clear_poisoned_global_context()
+                        || (!panicked && !context_ok(&context));
+                    if poisoned {
+                        warn!(
+                            "{} found its context poisoned, recreating it",
+                            thread::current().name().unwrap()
+                        );
                         thread_driver
-                            .num_failed_iterations
+                            .num_poisoned_contexts
                             .fetch_add(1, Ordering::SeqCst);
                     }
+                    if panicked || poisoned {
+                        context = C::default();
+                        FRESH_CONTEXT.with(|fresh| fresh.set(true));
+                    }
 
+                    thread_driver
+                        .num_invalid_inputs
+                        .fetch_add(mutator.take_validation_failures(), Ordering::SeqCst);
                     thread_driver.num_iterations.fetch_add(1, Ordering::SeqCst);
                 }
             })
@@ -0,0 +1,281 @@
+//! A/B experiments for comparing mutator configurations.
+//!
+//! An [Experiment] runs the same fuzzing routine under two configurations ("arms") with an
+//! identical seed budget: iteration `n` of both arms starts from the same RNG seed, so any
+//! difference in results comes from the configuration rather than from luck. Both arms run
+//! side by side on their own thread and the results are gathered in an [ExperimentReport].
+//!
+//! ```compile_fail
+//! let report = Experiment::new(1000)
+//!     .arm("default", |_mutator| {})
+//!     .arm("text heavy", |mutator| {
+//!         let mut weights = BlobContentWeights::default();
+//!         weights.text_like = 20;
+//!         mutator.set_blob_content_weights(weights);
+//!     })
+//!     .run(fuzzer_routine);
+//!
+//! println!("{}", report);
+//! ```
+
+use crate::mutator::Mutator;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What happened during a single iteration of an experiment, as reported by the fuzzing routine.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IterationOutcome {
+    /// The target crashed (or otherwise reported a finding)
+    pub crashed: bool,
+    /// The input was interesting enough to be added to the corpus
+    pub new_corpus_entry: bool,
+    /// Total coverage (e.g. number of edges hit) observed so far, if the target provides it
+    pub coverage: Option<usize>,
+}
+
+type Configure = Arc<dyn Fn(&mut Mutator<StdRng>) + Send + Sync>;
+
+struct Arm {
+    name: String,
+    configure: Configure,
+}
+
+/// Statistics gathered for a single arm of an [Experiment].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArmStats {
+    pub name: String,
+    pub iterations: usize,
+    pub crashes: usize,
+    /// The iteration at which the first crash was observed
+    pub first_crash: Option<usize>,
+    pub corpus_size: usize,
+    /// Corpus size sampled every [Experiment::sample_interval] iterations, as
+    /// `(iteration, corpus size)` pairs
+    pub corpus_growth: Vec<(usize, usize)>,
+    /// Highest coverage reported, if the routine reported any
+    pub coverage: Option<usize>,
+    pub elapsed: Duration,
+}
+
+impl ArmStats {
+    fn new(name: &str) -> Self {
+        ArmStats {
+            name: name.to_string(),
+            iterations: 0,
+            crashes: 0,
+            first_crash: None,
+            corpus_size: 0,
+            corpus_growth: vec![],
+            coverage: None,
+            elapsed: Duration::default(),
+        }
+    }
+
+    /// Iterations executed per second
+    pub fn execution_rate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+
+        self.iterations as f64 / secs
+    }
+}
+
+/// Results of both arms of an [Experiment].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExperimentReport {
+    pub seed: u64,
+    pub a: ArmStats,
+    pub b: ArmStats,
+}
+
+impl fmt::Display for ExperimentReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn optional<T: fmt::Display>(value: Option<T>) -> String {
+            value.map_or_else(|| String::from("-"), |v| v.to_string())
+        }
+
+        writeln!(f, "experiment seed: 0x{:X}", self.seed)?;
+        writeln!(f, "{:<16} {:>20} {:>20}", "", self.a.name, self.b.name)?;
+        writeln!(
+            f,
+            "{:<16} {:>20} {:>20}",
+            "iterations", self.a.iterations, self.b.iterations
+        )?;
+        writeln!(
+            f,
+            "{:<16} {:>20} {:>20}",
+            "crashes", self.a.crashes, self.b.crashes
+        )?;
+        writeln!(
+            f,
+            "{:<16} {:>20} {:>20}",
+            "first crash",
+            optional(self.a.first_crash),
+            optional(self.b.first_crash)
+        )?;
+        writeln!(
+            f,
+            "{:<16} {:>20} {:>20}",
+            "corpus size", self.a.corpus_size, self.b.corpus_size
+        )?;
+        writeln!(
+            f,
+            "{:<16} {:>20} {:>20}",
+            "coverage",
+            optional(self.a.coverage),
+            optional(self.b.coverage)
+        )?;
+        write!(
+            f,
+            "{:<16} {:>20.1} {:>20.1}",
+            "execs/sec",
+            self.a.execution_rate(),
+            self.b.execution_rate()
+        )
+    }
+}
+
+/// Runs a fuzzing routine under two mutator configurations with the same seed budget.
+pub struct Experiment {
+    seed: u64,
+    iterations: usize,
+    sample_interval: usize,
+    arms: Vec<Arm>,
+}
+
+impl Experiment {
+    /// Creates an experiment which runs each arm for `iterations` iterations using a random
+    /// root seed
+    pub fn new(iterations: usize) -> Self {
+        Experiment {
+            seed: rand::random(),
+            iterations,
+            sample_interval: std::cmp::max(iterations / 100, 1),
+            arms: Vec::with_capacity(2),
+        }
+    }
+
+    /// Sets the root seed shared by both arms
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets how often (in iterations) the corpus size is sampled for [ArmStats::corpus_growth]
+    pub fn sample_interval(mut self, interval: usize) -> Self {
+        self.sample_interval = std::cmp::max(interval, 1);
+        self
+    }
+
+    /// Adds an arm. `configure` is applied to the mutator at the start of every iteration,
+    /// after it has been reseeded. Exactly two arms must be added before calling [Experiment::run].
+    pub fn arm<F>(mut self, name: &str, configure: F) -> Self
+    where
+        F: Fn(&mut Mutator<StdRng>) + Send + Sync + 'static,
+    {
+        if self.arms.len() == 2 {
+            panic!("an experiment may only have two arms");
+        }
+
+        self.arms.push(Arm {
+            name: name.to_string(),
+            configure: Arc::new(configure),
+        });
+        self
+    }
+
+    /// Runs both arms side by side and waits for them to finish. `callback` is the fuzzing
+    /// routine, with `C` as its per-arm context (similar to the thread context used by
+    /// [start_fuzzer][crate::driver::start_fuzzer]).
+    pub fn run<F, C>(self, callback: F) -> ExperimentReport
+    where
+        F: Fn(&mut Mutator<StdRng>, &mut C) -> IterationOutcome + Send + Sync + Copy + 'static,
+        C: Default,
+    {
+        if self.arms.len() != 2 {
+            panic!(
+                "an experiment requires exactly two arms, {} were given",
+                self.arms.len()
+            );
+        }
+
+        let seed = self.seed;
+        let iterations = self.iterations;
+        let sample_interval = self.sample_interval;
+
+        let handles: Vec<thread::JoinHandle<ArmStats>> = self
+            .arms
+            .into_iter()
+            .map(|arm| {
+                thread::Builder::new()
+                    .name(format!("Experiment arm {}", arm.name))
+                    .spawn(move || {
+                        run_arm::<F, C>(&arm, seed, iterations, sample_interval, callback)
+                    })
+                    .unwrap_or_else(|_| panic!("could not create new thread"))
+            })
+            .collect();
+
+        let mut stats = handles
+            .into_iter()
+            .map(|handle| handle.join().expect("experiment arm panicked"));
+
+        ExperimentReport {
+            seed,
+            a: stats.next().unwrap(),
+            b: stats.next().unwrap(),
+        }
+    }
+}
+
+fn run_arm<F, C>(
+    arm: &Arm,
+    seed: u64,
+    iterations: usize,
+    sample_interval: usize,
+    callback: F,
+) -> ArmStats
+where
+    F: Fn(&mut Mutator<StdRng>, &mut C) -> IterationOutcome,
+    C: Default,
+{
+    let mut stats = ArmStats::new(&arm.name);
+    let mut mutator = Mutator::new(StdRng::seed_from_u64(seed));
+    let mut context = C::default();
+
+    let start = Instant::now();
+    for iteration in 0..iterations {
+        mutator.rng = StdRng::seed_from_u64(seed.wrapping_add(iteration as u64));
+        (arm.configure)(&mut mutator);
+        mutator.random_flags();
+
+        let outcome = callback(&mut mutator, &mut context);
+        stats.iterations += 1;
+
+        if outcome.crashed {
+            stats.crashes += 1;
+            stats.first_crash.get_or_insert(iteration);
+        }
+
+        if outcome.new_corpus_entry {
+            stats.corpus_size += 1;
+        }
+
+        if let Some(coverage) = outcome.coverage {
+            stats.coverage = Some(std::cmp::max(stats.coverage.unwrap_or(0), coverage));
+        }
+
+        if (iteration + 1) % sample_interval == 0 || iteration + 1 == iterations {
+            stats.corpus_growth.push((iteration + 1, stats.corpus_size));
+        }
+    }
+    stats.elapsed = start.elapsed();
+
+    stats
+}
@@ -0,0 +1,299 @@
+//! Exporting the layout of a lain model to the formats of standard reverse engineering tools.
+//!
+//! [to_kaitai] emits a Kaitai Struct (`.ksy`) description and [to_010_template] an 010 Editor
+//! binary template, both from the [field layout][BinarySerialize::field_layout] of a sample
+//! value. Loading a generated corpus with either shows which bytes belong to which field, and
+//! comparing the export against an existing description of the real format shows where the
+//! model drifts from it:
+//!
+//! ```compile_fail
+//! let packet = Packet::new_fuzzed(&mut mutator, None);
+//! std::fs::write("packet.ksy", to_kaitai(&packet, "packet"))?;
+//! ```
+//!
+//! The layout is that of one value, so variable-sized fields (`Vec`s, strings, enums with
+//! fields) are described with the size they have in the sample. Fields are described as raw
+//! bytes since the layout doesn't record their types, bytes which no field claims (such as the
+//! padding up to a `#[lain(serialized_size)]`) are described as `gap_<offset>`, and the members
+//! of a bitfield are described together as the whole packed integer. Empty fields are left out.
+
+use crate::traits::{BinarySerialize, SerializedSize};
+use crate::types::FieldSpan;
+use std::fmt::Write;
+
+/// A field of the exported description: either raw bytes or a structure of other fields
+struct Node {
+    /// Paths of every field occupying exactly these bytes. Only members of a bitfield share
+    /// their bytes with another field.
+    paths: Vec<String>,
+    start: usize,
+    end: usize,
+    children: Vec<Node>,
+}
+
+/// Whether `path` is a field nested (directly or not) in the field at `parent`
+fn is_ancestor(parent: &str, path: &str) -> bool {
+    parent.is_empty()
+        || (path.len() > parent.len()
+            && path.starts_with(parent)
+            && matches!(path.as_bytes()[parent.len()], b'.' | b'['))
+}
+
+impl Node {
+    fn from_layout(layout: &[FieldSpan], size: usize) -> Node {
+        let mut spans: Vec<&FieldSpan> = layout.iter().filter(|s| !s.is_empty()).collect();
+        // parents are reported before their fields, which the stable sort preserves for
+        // parents with a single field
+        spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+        let mut root = Node {
+            paths: vec![String::new()],
+            start: 0,
+            end: spans.iter().map(|s| s.end).fold(size, usize::max),
+            children: vec![],
+        };
+
+        for span in spans {
+            root.insert(span);
+        }
+
+        root
+    }
+
+    fn insert(&mut self, span: &FieldSpan) {
+        if let Some(last) = self.children.last_mut() {
+            if span.start >= last.start && span.end <= last.end {
+                let is_child = last.paths.iter().any(|p| is_ancestor(p, &span.path));
+                if !is_child && (span.start, span.end) == (last.start, last.end) {
+                    last.paths.push(span.path.clone());
+                } else {
+                    last.insert(span);
+                }
+
+                return;
+            }
+
+            // a field overlapping its predecessor can't be described as a sequence
+            if span.start < last.end {
+                return;
+            }
+        }
+
+        self.children.push(Node {
+            paths: vec![span.path.clone()],
+            start: span.start,
+            end: span.end,
+            children: vec![],
+        });
+    }
+
+    /// The identifier of this field within `parent`, e.g. `version` for `header.version` or
+    /// `field_2` for `items[2]`. Bitfields are named after their first member.
+    fn id(&self, parent: &Node) -> String {
+        let path = &self.paths[0];
+        let relative = parent
+            .paths
+            .iter()
+            .filter(|p| !p.is_empty() && is_ancestor(p, path))
+            .map(|p| path[p.len()..].trim_start_matches('.'))
+            .next()
+            .unwrap_or(path);
+
+        sanitize(relative)
+    }
+
+    /// The fields of this structure, with the unclaimed bytes between them
+    fn entries(&self) -> Vec<Entry<'_>> {
+        let mut entries = vec![];
+        let mut offset = self.start;
+        for child in self.children.iter() {
+            if child.start > offset {
+                entries.push(Entry::Gap(offset, child.start - offset));
+            }
+            entries.push(Entry::Field(child));
+            offset = child.end;
+        }
+
+        if self.end > offset {
+            entries.push(Entry::Gap(offset, self.end - offset));
+        }
+
+        entries
+    }
+}
+
+enum Entry<'a> {
+    Field(&'a Node),
+    /// Offset and length of bytes which no field claims
+    Gap(usize, usize),
+}
+
+/// Turns a field path into an identifier both formats accept: lowercase ASCII letters, digits,
+/// and underscores, starting with a letter.
+fn sanitize(path: &str) -> String {
+    let mut id = String::new();
+    for c in path.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+
+    let id = id.trim_end_matches('_');
+    match id.chars().next() {
+        None => "field".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("field_{}", id),
+        Some(_) => id.to_string(),
+    }
+}
+
+/// Identifiers which are types or keywords in 010 Editor templates
+const RESERVED_010: &[&str] = &[
+    "break", "byte", "case", "char", "const", "continue", "default", "do", "double", "dword",
+    "else", "enum", "float", "for", "hfloat", "if", "int", "int16", "int32", "int64", "local",
+    "long", "qword", "quad", "return", "short", "signed", "sizeof", "string", "struct", "switch",
+    "typedef", "ubyte", "uchar", "uint", "uint16", "uint32", "uint64", "ulong", "union",
+    "unsigned", "ushort", "void", "while", "word", "wchar_t", "wstring",
+];
+
+/// Makes `id` unique among `taken` by appending a number to it
+fn unique(id: String, taken: &mut Vec<String>) -> String {
+    let mut candidate = id.clone();
+    let mut n = 2;
+    while taken.contains(&candidate) {
+        candidate = format!("{}_{}", id, n);
+        n += 1;
+    }
+
+    taken.push(candidate.clone());
+    candidate
+}
+
+/// Returns a Kaitai Struct description of the layout of `value`, with `id` as the identifier
+/// of the top-level type.
+///
+/// Each structure gets its own entry in `types`, named after its path.
+pub fn to_kaitai<T: BinarySerialize + SerializedSize>(value: &T, id: &str) -> String {
+    let mut layout = vec![];
+    value.field_layout("", 0, &mut layout);
+    let root = Node::from_layout(&layout, value.serialized_size());
+
+    let mut output = String::new();
+    let mut types = String::new();
+    let mut type_names = vec![];
+
+    writeln!(output, "meta:\n  id: {}", sanitize(id)).unwrap();
+    write_kaitai_seq(&root, 0, &mut output, &mut types, &mut type_names);
+
+    if !types.is_empty() {
+        output.push_str("types:\n");
+        output.push_str(&types);
+    }
+
+    output
+}
+
+fn write_kaitai_seq(
+    node: &Node,
+    indent: usize,
+    output: &mut String,
+    types: &mut String,
+    type_names: &mut Vec<String>,
+) {
+    let pad = " ".repeat(indent);
+    writeln!(output, "{}seq:", pad).unwrap();
+
+    let mut ids = vec![];
+    for entry in node.entries() {
+        match entry {
+            Entry::Gap(offset, size) => {
+                let id = unique(format!("gap_{}", offset), &mut ids);
+                writeln!(output, "{}  - id: {}\n{}    size: {}", pad, id, pad, size).unwrap();
+            }
+            Entry::Field(child) => {
+                let id = unique(child.id(node), &mut ids);
+                writeln!(output, "{}  - id: {}", pad, id).unwrap();
+
+                if child.children.is_empty() {
+                    writeln!(output, "{}    size: {}", pad, child.end - child.start).unwrap();
+                } else {
+                    let type_name = unique(sanitize(&child.paths[0]), type_names);
+                    writeln!(output, "{}    type: {}", pad, type_name).unwrap();
+
+                    let mut definition = String::new();
+                    writeln!(definition, "  {}:", type_name).unwrap();
+                    write_kaitai_seq(child, 4, &mut definition, types, type_names);
+                    types.push_str(&definition);
+                }
+
+                if child.paths.len() > 1 {
+                    writeln!(
+                        output,
+                        "{}    doc: bitfield of {}",
+                        pad,
+                        child.paths.join(", ")
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+}
+
+/// Returns an 010 Editor binary template describing the layout of `value`, declaring it as a
+/// variable named `name`.
+pub fn to_010_template<T: BinarySerialize + SerializedSize>(value: &T, name: &str) -> String {
+    let mut layout = vec![];
+    value.field_layout("", 0, &mut layout);
+    let root = Node::from_layout(&layout, value.serialized_size());
+
+    let mut output = String::new();
+    let mut name = sanitize(name);
+    if RESERVED_010.contains(&name.as_str()) {
+        name.push('_');
+    }
+    write_010_struct(&root, &name, 0, &mut output);
+
+    output
+}
+
+fn write_010_struct(node: &Node, name: &str, indent: usize, output: &mut String) {
+    let pad = " ".repeat(indent);
+    writeln!(output, "{}struct {{", pad).unwrap();
+
+    let mut names = vec![];
+    for entry in node.entries() {
+        match entry {
+            Entry::Gap(offset, size) => {
+                let name = unique(format!("gap_{}", offset), &mut names);
+                writeln!(output, "{}    uchar {}[{}];", pad, name, size).unwrap();
+            }
+            Entry::Field(child) => {
+                let mut name = child.id(node);
+                if RESERVED_010.contains(&name.as_str()) {
+                    name.push('_');
+                }
+                let name = unique(name, &mut names);
+                if child.children.is_empty() {
+                    write!(
+                        output,
+                        "{}    uchar {}[{}];",
+                        pad,
+                        name,
+                        child.end - child.start
+                    )
+                    .unwrap();
+                    if child.paths.len() > 1 {
+                        write!(output, " // bitfield of {}", child.paths.join(", ")).unwrap();
+                    }
+                    output.push('\n');
+                } else {
+                    write_010_struct(child, &name, indent + 4, output);
+                }
+            }
+        }
+    }
+
+    writeln!(output, "{}}} {};", pad, name).unwrap();
+}
@@ -0,0 +1,250 @@
+//! Optional trait implementations in derived code.
+//!
+//! Code generated by lain's derives calls [Fixup], [Invariant], [Minimize], [Crossover],
+//! [VariableSizeObject], and [BinarySerialize] on fields whose types may not implement them, resets
+//! fields whose types may not implement `Default`, and implements the seed
+//! methods of [Mutatable][crate::traits::Mutatable] for types which may not implement `Clone`. Each capability is
+//! implemented for [Probe] only when the probed type implements the trait, and a fallback method
+//! of the same name is implemented for `&mut Probe`. Method lookup only autorefs the probe if
+//! the first implementation doesn't apply, so
+//!
+//! ```
+//! use lain::fallback::{FixupFallback, FixupProbe, Probe};
+//! use lain::prelude::*;
+//!
+//! struct Length(u8);
+//!
+//! impl Fixup for Length {
+//!     fn fixup<R: Rng>(&mut self, _mutator: &mut Mutator<R>) {
+//!         self.0 = self.0.min(16);
+//!     }
+//! }
+//!
+//! struct Opaque(u8);
+//!
+//! let mut mutator = Mutator::fast(0);
+//! let (mut length, mut opaque) = (Length(200), Opaque(200));
+//! Probe(&mut length).fixup(&mut mutator);
+//! Probe(&mut opaque).fixup(&mut mutator);
+//! assert_eq!((length.0, opaque.0), (16, 200));
+//! ```
+//!
+//! calls the value's `Fixup` implementation if it has one and does nothing otherwise. This is
+//! resolved where the probe is written, not where the type is used, so a field of a generic
+//! type `T` only uses a trait if it's one of `T`'s bounds: a `Wrapper<T>` deriving `Mutatable`
+//! never fixes up its `T` field, even when `T: Fixup`, unless it's declared as
+//! `Wrapper<T: Fixup>`.
+
+use crate::mutator::Mutator;
+use crate::operators::MutationOperator;
+use crate::rand::Rng;
+use crate::traits::{BinarySerialize, Crossover, Fixup, Invariant, Minimize, VariableSizeObject};
+use std::marker::PhantomData;
+
+/// Wraps a value (or for associated functions, a `PhantomData` of its type) whose trait
+/// implementations are looked up through the traits in this module
+pub struct Probe<T>(pub T);
+
+pub trait FixupProbe {
+    fn fixup<R: Rng>(self, mutator: &mut Mutator<R>);
+}
+
+impl<T: Fixup> FixupProbe for Probe<&mut T> {
+    #[inline(always)]
+    fn fixup<R: Rng>(self, mutator: &mut Mutator<R>) {
+        self.0.fixup(mutator);
+    }
+}
+
+pub trait FixupFallback {
+    fn fixup<R: Rng>(self, mutator: &mut Mutator<R>);
+}
+
+impl<T> FixupFallback for &mut Probe<&mut T> {
+    #[inline(always)]
+    fn fixup<R: Rng>(self, _mutator: &mut Mutator<R>) { /* nop */
+    }
+}
+
+pub trait MinimizeProbe<'a, T> {
+    fn shrink(self) -> Box<dyn Iterator<Item = T> + 'a>;
+}
+
+impl<'a, T: Minimize> MinimizeProbe<'a, T> for Probe<&'a T> {
+    fn shrink(self) -> Box<dyn Iterator<Item = T> + 'a> {
+        self.0.shrink()
+    }
+}
+
+pub trait MinimizeFallback<'a, T> {
+    fn shrink(self) -> Box<dyn Iterator<Item = T> + 'a>;
+}
+
+impl<'a, T> MinimizeFallback<'a, T> for &mut Probe<&'a T> {
+    fn shrink(self) -> Box<dyn Iterator<Item = T> + 'a> {
+        Box::new(std::iter::empty())
+    }
+}
+
+pub trait CrossoverProbe<T> {
+    fn crossover<R: Rng>(self, other: &T, mutator: &mut Mutator<R>);
+}
+
+impl<T: Crossover> CrossoverProbe<T> for Probe<&mut T> {
+    #[inline(always)]
+    fn crossover<R: Rng>(self, other: &T, mutator: &mut Mutator<R>) {
+        self.0.crossover(other, mutator);
+    }
+}
+
+pub trait CrossoverFallback<T> {
+    fn crossover<R: Rng>(self, other: &T, mutator: &mut Mutator<R>);
+}
+
+/// Values without a [Crossover] implementation are taken from either parent as a whole
+impl<T: Clone> CrossoverFallback<T> for &mut Probe<&mut T> {
+    fn crossover<R: Rng>(self, other: &T, mutator: &mut Mutator<R>) {
+        if mutator.gen_chance(crate::mutator::CHANCE_TO_CROSS_VALUE) {
+            *self.0 = other.clone();
+        }
+    }
+}
+
+pub trait VariableSizeProbe {
+    fn variable_size(self) -> bool;
+}
+
+impl<T: VariableSizeObject> VariableSizeProbe for Probe<PhantomData<T>> {
+    #[inline(always)]
+    fn variable_size(self) -> bool {
+        T::is_variable_size()
+    }
+}
+
+pub trait VariableSizeFallback {
+    fn variable_size(self) -> bool;
+}
+
+impl<T> VariableSizeFallback for &mut Probe<PhantomData<T>> {
+    #[inline(always)]
+    fn variable_size(self) -> bool {
+        false
+    }
+}
+
+pub trait CloneProbe<T> {
+    fn clone_seed(self) -> Option<T>;
+}
+
+impl<T: Clone> CloneProbe<T> for Probe<&T> {
+    fn clone_seed(self) -> Option<T> {
+        Some(self.0.clone())
+    }
+}
+
+pub trait CloneFallback<T> {
+    fn clone_seed(self) -> Option<T>;
+}
+
+impl<T> CloneFallback<T> for &mut Probe<&T> {
+    fn clone_seed(self) -> Option<T> {
+        None
+    }
+}
+
+pub trait CrossoverSeedProbe<T> {
+    fn crossover_seed<R: Rng>(self, other: &T, mutator: &mut Mutator<R>) -> Option<T>;
+}
+
+impl<T: Crossover> CrossoverSeedProbe<T> for Probe<&T> {
+    fn crossover_seed<R: Rng>(self, other: &T, mutator: &mut Mutator<R>) -> Option<T> {
+        Some(mutator.crossover(self.0, other))
+    }
+}
+
+pub trait CrossoverSeedFallback<T> {
+    fn crossover_seed<R: Rng>(self, other: &T, mutator: &mut Mutator<R>) -> Option<T>;
+}
+
+impl<T> CrossoverSeedFallback<T> for &mut Probe<&T> {
+    fn crossover_seed<R: Rng>(self, _other: &T, _mutator: &mut Mutator<R>) -> Option<T> {
+        None
+    }
+}
+
+pub trait PinnedFieldsProbe {
+    fn apply_pinned_fields<R: Rng>(self, mutator: &Mutator<R>) -> bool;
+}
+
+impl<T: BinarySerialize> PinnedFieldsProbe for Probe<&mut T> {
+    #[inline(always)]
+    fn apply_pinned_fields<R: Rng>(self, mutator: &Mutator<R>) -> bool {
+        mutator.apply_pinned_fields(self.0)
+    }
+}
+
+pub trait PinnedFieldsFallback {
+    fn apply_pinned_fields<R: Rng>(self, mutator: &Mutator<R>) -> bool;
+}
+
+/// Fields can only be pinned by their [BinarySerialize::field_layout] paths
+impl<T> PinnedFieldsFallback for &mut Probe<&mut T> {
+    #[inline(always)]
+    fn apply_pinned_fields<R: Rng>(self, _mutator: &Mutator<R>) -> bool {
+        false
+    }
+}
+
+pub trait ZeroFillProbe {
+    fn zero_fill<R: Rng>(self, mutator: &mut Mutator<R>) -> bool;
+}
+
+impl<T: Default> ZeroFillProbe for Probe<&mut T> {
+    fn zero_fill<R: Rng>(self, mutator: &mut Mutator<R>) -> bool {
+        *self.0 = T::default();
+        mutator.record_operator(MutationOperator::ZeroFill);
+        true
+    }
+}
+
+pub trait ZeroFillFallback {
+    fn zero_fill<R: Rng>(self, mutator: &mut Mutator<R>) -> bool;
+}
+
+/// Values without a `Default` are left for the caller to mutate
+impl<T> ZeroFillFallback for &mut Probe<&mut T> {
+    #[inline(always)]
+    fn zero_fill<R: Rng>(self, _mutator: &mut Mutator<R>) -> bool {
+        false
+    }
+}
+
+pub trait InvariantProbe {
+    fn check_invariant(self);
+}
+
+/// Panics if the invariant doesn't hold, but only in debug builds with the `invariant_checks`
+/// feature
+impl<T: Invariant> InvariantProbe for Probe<&T> {
+    fn check_invariant(self) {
+        if cfg!(all(debug_assertions, feature = "invariant_checks")) {
+            if let Err(message) = self.0.check() {
+                panic!(
+                    "invariant of {} doesn't hold: {}",
+                    std::any::type_name::<T>(),
+                    message
+                );
+            }
+        }
+    }
+}
+
+pub trait InvariantFallback {
+    fn check_invariant(self);
+}
+
+impl<T> InvariantFallback for &mut Probe<&T> {
+    #[inline(always)]
+    fn check_invariant(self) { /* nop */
+    }
+}
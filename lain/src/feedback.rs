@@ -0,0 +1,116 @@
+//! Coverage feedback for guiding the driver.
+//!
+//! By default the driver fuzzes blind: every thread keeps mutating its own input regardless of
+//! what the target did with it. Once a [FeedbackProvider] is
+//! [set][FuzzerDriver::set_feedback_provider], the callback reports the coverage each iteration
+//! reached with [report_coverage], and the driver hands that signal to the provider after the
+//! iteration. Iterations the provider judges to have reached new coverage are treated as
+//! [Outcome::Interesting] with the [NEW_COVERAGE_TAG] tag, and their inputs are kept in the
+//! driver's [in-memory corpus][FuzzerDriver::feedback_corpus].
+//!
+//! [start_pipeline_fuzzer] additionally mutates from that corpus: once it holds any entries, a
+//! thread whose input reached no new coverage starts its next iteration from a copy of a random
+//! entry instead, or with probability [Mutator::crossover_chance] from the
+//! [crossover][crate::crossover] of two entries. This requires the input type to implement
+//! `Clone` and derive `Mutatable` (see [Mutatable::clone_seed]), and crossover additionally
+//! requires it to implement [Crossover]. Other inputs are still kept in the corpus in their
+//! serialized form.
+//!
+//! ```compile_fail
+//! driver.set_feedback_provider(Arc::new(EdgeFeedback::new()));
+//!
+//! // in the fuzzer routine
+//! let bitmap = run_target(bytes);
+//! report_coverage(CoverageSignal::Bitmap(bitmap));
+//! ```
+//!
+//! [FuzzerDriver::set_feedback_provider]: crate::driver::FuzzerDriver::set_feedback_provider
+//! [FuzzerDriver::feedback_corpus]: crate::driver::FuzzerDriver::feedback_corpus
+//! [Outcome::Interesting]: crate::driver::Outcome::Interesting
+//! [start_pipeline_fuzzer]: crate::driver::start_pipeline_fuzzer
+//! [Mutator::crossover_chance]: crate::mutator::Mutator::crossover_chance
+//! [Mutatable::clone_seed]: crate::traits::Mutatable::clone_seed
+//! [Crossover]: crate::traits::Crossover
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Tag of the [Outcome::Interesting][crate::driver::Outcome::Interesting] outcome given to
+/// iterations which reached new coverage
+pub const NEW_COVERAGE_TAG: &str = "coverage";
+
+thread_local! {
+    /// Signal reported by the callback for the current iteration
+    static SIGNAL: RefCell<Option<CoverageSignal>> = const { RefCell::new(None) };
+}
+
+/// Coverage reached by a single iteration. The driver doesn't interpret it; it's only passed on
+/// to the [FeedbackProvider].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverageSignal {
+    /// One hit counter per edge, as produced by AFL-style instrumentation
+    Bitmap(Vec<u8>),
+    /// Identifiers of the edges which were hit
+    Edges(HashSet<u64>),
+}
+
+/// Decides which iterations reached new coverage. Called by the driver after every iteration
+/// the callback reported a [CoverageSignal] for, from every fuzzer thread.
+pub trait FeedbackProvider: Send + Sync {
+    /// Returns whether `signal` contains coverage no earlier iteration reached
+    fn observe(&self, signal: &CoverageSignal) -> bool;
+}
+
+/// A [FeedbackProvider] which considers an iteration new if it hit any edge no earlier iteration
+/// hit. Byte `i` of a [CoverageSignal::Bitmap] is counted as edge `i`.
+#[derive(Debug, Default)]
+pub struct EdgeFeedback {
+    edges: Mutex<HashSet<u64>>,
+}
+
+impl EdgeFeedback {
+    pub fn new() -> Self {
+        EdgeFeedback::default()
+    }
+
+    /// Number of distinct edges hit so far
+    pub fn num_edges(&self) -> usize {
+        self.edges.lock().unwrap().len()
+    }
+}
+
+impl FeedbackProvider for EdgeFeedback {
+    fn observe(&self, signal: &CoverageSignal) -> bool {
+        let mut edges = self.edges.lock().unwrap();
+        let mut new_coverage = false;
+
+        match signal {
+            CoverageSignal::Bitmap(bitmap) => {
+                for (edge, hits) in bitmap.iter().enumerate() {
+                    if *hits != 0 {
+                        new_coverage |= edges.insert(edge as u64);
+                    }
+                }
+            }
+            CoverageSignal::Edges(hit) => {
+                for edge in hit.iter() {
+                    new_coverage |= edges.insert(*edge);
+                }
+            }
+        }
+
+        new_coverage
+    }
+}
+
+/// Reports the coverage reached by the current iteration. Called by the callback before it
+/// returns; if it's called more than once, only the last signal is kept.
+pub fn report_coverage(signal: CoverageSignal) {
+    SIGNAL.with(|current| *current.borrow_mut() = Some(signal));
+}
+
+/// Takes the signal reported for the current iteration, if any
+pub(crate) fn take_signal() -> Option<CoverageSignal> {
+    SIGNAL.with(|current| current.borrow_mut().take())
+}
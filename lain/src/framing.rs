@@ -0,0 +1,350 @@
+//! Delivering serialized inputs through a framing layer.
+//!
+//! Application-layer parsers are often only reachable through a stream protocol which frames
+//! their messages: a length prefix, or the frames of a WebSocket connection. A [Framing] wraps
+//! a serialized payload in that layer, and a [FramedStream] writes the result to the target:
+//!
+//! ```compile_fail
+//! let stream = TcpStream::connect("127.0.0.1:8080")?;
+//! let mut target = FramedStream::new(stream, LengthPrefixed::new(4).max_frame_size(1024));
+//!
+//! let mut bytes = Vec::new();
+//! packet.binary_serialize::<_, BigEndian>(&mut bytes);
+//! target.send(&bytes, &mut mutator)?;
+//! ```
+//!
+//! Payloads may be split over several frames. With the chance set by `corrupt_chance`, each
+//! frame is corrupted (wrong length, reserved bits set, bad opcode, ...) and the
+//! [CorruptFrame][MutationOperator::CorruptFrame] operator is recorded, so that the framing
+//! layer's own error handling is exercised as well.
+//!
+//! WebSocket frames require the `websocket_support` feature. The opening handshake isn't
+//! performed here: `send` expects a stream on which it has already completed.
+
+use crate::byteorder::{BigEndian, ByteOrder, LittleEndian};
+use crate::mutator::Mutator;
+use crate::operators::MutationOperator;
+use crate::rand::Rng;
+use std::io::{self, Write};
+
+/// Chance for each frame to be corrupted, unless set otherwise
+pub const DEFAULT_CORRUPT_FRAME_CHANCE: f64 = 0.05;
+
+/// A framing layer around serialized payloads
+pub trait Framing {
+    /// Appends the frames carrying `payload` to `output`
+    fn frame<R: Rng>(&self, payload: &[u8], mutator: &mut Mutator<R>, output: &mut Vec<u8>);
+}
+
+/// Splits `payload` into pieces of at most `max_size` bytes. An empty payload is still
+/// carried by a single (empty) piece.
+fn split_payload(payload: &[u8], max_size: Option<usize>) -> Vec<&[u8]> {
+    match max_size {
+        Some(max_size) if !payload.is_empty() => payload.chunks(max_size.max(1)).collect(),
+        _ => vec![payload],
+    }
+}
+
+/// Frames made of a big- or little-endian length of 1, 2, 4, or 8 bytes followed by that many
+/// bytes of payload.
+#[derive(Debug, Clone)]
+pub struct LengthPrefixed {
+    width: usize,
+    little_endian: bool,
+    max_frame_size: Option<usize>,
+    corrupt_chance: f64,
+}
+
+impl LengthPrefixed {
+    /// Frames with a big-endian length prefix of `width` bytes. Panics unless `width` is 1, 2,
+    /// 4, or 8.
+    pub fn new(width: usize) -> Self {
+        assert!(
+            [1, 2, 4, 8].contains(&width),
+            "length prefixes are 1, 2, 4, or 8 bytes wide"
+        );
+
+        LengthPrefixed {
+            width,
+            little_endian: false,
+            max_frame_size: None,
+            corrupt_chance: DEFAULT_CORRUPT_FRAME_CHANCE,
+        }
+    }
+
+    /// Writes the length prefix in little-endian byte order
+    pub fn little_endian(mut self) -> Self {
+        self.little_endian = true;
+        self
+    }
+
+    /// Splits payloads over several frames of at most `size` bytes of payload each
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.max_frame_size = Some(size);
+        self
+    }
+
+    /// Chance for each frame to be corrupted
+    pub fn corrupt_chance(mut self, chance: f64) -> Self {
+        self.corrupt_chance = chance;
+        self
+    }
+
+    fn write_length(&self, length: u64, output: &mut Vec<u8>) {
+        let mask = if self.width == 8 {
+            u64::MAX
+        } else {
+            (1u64 << (self.width * 8)) - 1
+        };
+
+        let mut bytes = [0u8; 8];
+        if self.little_endian {
+            LittleEndian::write_uint(&mut bytes, length & mask, self.width);
+        } else {
+            BigEndian::write_uint(&mut bytes, length & mask, self.width);
+        }
+
+        output.extend_from_slice(&bytes[..self.width]);
+    }
+}
+
+impl Framing for LengthPrefixed {
+    fn frame<R: Rng>(&self, payload: &[u8], mutator: &mut Mutator<R>, output: &mut Vec<u8>) {
+        for piece in split_payload(payload, self.max_frame_size) {
+            let mut length = piece.len() as u64;
+            let mut piece = piece;
+
+            if mutator.gen_chance(self.corrupt_chance) {
+                mutator.record_operator(MutationOperator::CorruptFrame);
+                match mutator.gen_range(0, 3) {
+                    // a length close to the right one
+                    0 => {
+                        let delta = mutator.gen_range(1, 9);
+                        length = if mutator.gen() {
+                            length.wrapping_add(delta)
+                        } else {
+                            length.wrapping_sub(delta)
+                        };
+                    }
+                    // the largest length the prefix can hold
+                    1 => length = u64::MAX,
+                    // a frame cut short of its length
+                    _ => piece = &piece[..mutator.gen_range(0, piece.len() + 1)],
+                }
+            }
+
+            self.write_length(length, output);
+            output.extend_from_slice(piece);
+        }
+    }
+}
+
+/// The kind of message carried by [WebSocket] frames
+#[cfg(feature = "websocket_support")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Text,
+    Binary,
+}
+
+#[cfg(feature = "websocket_support")]
+impl Opcode {
+    /// Opcode of the frames following the first frame of a fragmented message
+    pub const CONTINUATION: u8 = 0x0;
+
+    pub fn code(&self) -> u8 {
+        match self {
+            Opcode::Text => 0x1,
+            Opcode::Binary => 0x2,
+        }
+    }
+}
+
+/// WebSocket data frames as described in RFC 6455. Payloads larger than the maximum fragment
+/// size are sent as a fragmented message: the first frame carries the message's opcode, the
+/// others are continuation frames, and only the last one has its FIN bit set.
+#[cfg(feature = "websocket_support")]
+#[derive(Debug, Clone)]
+pub struct WebSocket {
+    opcode: Opcode,
+    masked: bool,
+    max_fragment_size: Option<usize>,
+    corrupt_chance: f64,
+}
+
+#[cfg(feature = "websocket_support")]
+impl WebSocket {
+    /// Binary frames from a client, which are masked with a random key
+    pub fn client() -> Self {
+        WebSocket {
+            opcode: Opcode::Binary,
+            masked: true,
+            max_fragment_size: None,
+            corrupt_chance: DEFAULT_CORRUPT_FRAME_CHANCE,
+        }
+    }
+
+    /// Binary frames from a server, which aren't masked
+    pub fn server() -> Self {
+        WebSocket {
+            masked: false,
+            ..WebSocket::client()
+        }
+    }
+
+    /// Sends payloads as text messages instead
+    pub fn text(mut self) -> Self {
+        self.opcode = Opcode::Text;
+        self
+    }
+
+    /// Fragments payloads into frames of at most `size` bytes of payload each
+    pub fn max_fragment_size(mut self, size: usize) -> Self {
+        self.max_fragment_size = Some(size);
+        self
+    }
+
+    /// Chance for each frame to be corrupted
+    pub fn corrupt_chance(mut self, chance: f64) -> Self {
+        self.corrupt_chance = chance;
+        self
+    }
+}
+
+#[cfg(feature = "websocket_support")]
+impl Framing for WebSocket {
+    fn frame<R: Rng>(&self, payload: &[u8], mutator: &mut Mutator<R>, output: &mut Vec<u8>) {
+        let fragments = split_payload(payload, self.max_fragment_size);
+        let count = fragments.len();
+
+        for (i, fragment) in fragments.into_iter().enumerate() {
+            let mut header = FrameHeader {
+                fin: i + 1 == count,
+                reserved: 0,
+                opcode: if i == 0 {
+                    self.opcode.code()
+                } else {
+                    Opcode::CONTINUATION
+                },
+                masked: self.masked,
+                length: fragment.len() as u64,
+                extended_length: false,
+            };
+
+            if mutator.gen_chance(self.corrupt_chance) {
+                mutator.record_operator(MutationOperator::CorruptFrame);
+                header.corrupt(mutator);
+            }
+
+            header.write(output);
+
+            if header.masked {
+                let key: [u8; 4] = mutator.rng.gen();
+                output.extend_from_slice(&key);
+                output.extend(
+                    fragment
+                        .iter()
+                        .enumerate()
+                        .map(|(i, byte)| byte ^ key[i % 4]),
+                );
+            } else {
+                output.extend_from_slice(fragment);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "websocket_support")]
+struct FrameHeader {
+    fin: bool,
+    /// The RSV1-3 bits, which must be 0 without a negotiated extension
+    reserved: u8,
+    opcode: u8,
+    masked: bool,
+    length: u64,
+    /// Whether a length which fits in fewer bytes is written as a 64-bit length anyway
+    extended_length: bool,
+}
+
+#[cfg(feature = "websocket_support")]
+impl FrameHeader {
+    fn corrupt<R: Rng>(&mut self, mutator: &mut Mutator<R>) {
+        match mutator.gen_range(0, 6) {
+            0 => self.reserved = mutator.gen_range(1, 8),
+            // any other opcode, including the reserved and control ones
+            1 => self.opcode = (self.opcode + mutator.gen_range(1, 16)) & 0xF,
+            2 => self.fin = !self.fin,
+            3 => self.masked = !self.masked,
+            4 => self.extended_length = true,
+            _ => {
+                let delta = mutator.gen_range(1, 9);
+                self.length = if mutator.gen() {
+                    self.length.wrapping_add(delta)
+                } else {
+                    self.length.wrapping_sub(delta)
+                };
+            }
+        }
+    }
+
+    fn write(&self, output: &mut Vec<u8>) {
+        output.push((self.fin as u8) << 7 | self.reserved << 4 | self.opcode);
+
+        let mask_bit = (self.masked as u8) << 7;
+        if self.length < 126 && !self.extended_length {
+            output.push(mask_bit | self.length as u8);
+        } else if self.length <= u16::MAX as u64 && !self.extended_length {
+            output.push(mask_bit | 126);
+            output.extend_from_slice(&(self.length as u16).to_be_bytes());
+        } else {
+            output.push(mask_bit | 127);
+            output.extend_from_slice(&self.length.to_be_bytes());
+        }
+    }
+}
+
+/// A stream to the target which payloads are written to through a [Framing]
+#[derive(Debug)]
+pub struct FramedStream<W, F> {
+    stream: W,
+    framing: F,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write, F: Framing> FramedStream<W, F> {
+    pub fn new(stream: W, framing: F) -> Self {
+        FramedStream {
+            stream,
+            framing,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Frames `payload` and writes every frame to the stream. Returns the number of bytes
+    /// written, framing included.
+    pub fn send<R: Rng>(&mut self, payload: &[u8], mutator: &mut Mutator<R>) -> io::Result<usize> {
+        self.buffer.clear();
+        self.framing.frame(payload, mutator, &mut self.buffer);
+
+        self.stream.write_all(&self.buffer)?;
+        self.stream.flush()?;
+
+        Ok(self.buffer.len())
+    }
+
+    pub fn framing(&self) -> &F {
+        &self.framing
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.stream
+    }
+
+    pub fn into_inner(self) -> W {
+        self.stream
+    }
+}
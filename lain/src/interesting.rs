@@ -0,0 +1,166 @@
+//! Interesting values for integer mutations.
+//!
+//! Bit flips and small arithmetic rarely turn a value into the ones that break parsers:
+//! boundaries such as 0, -1, or the type's maximum, and the values around powers of two where
+//! sizes overflow and buffers are rounded. With probability
+//! [Mutator::interesting_value_chance], [Mutator::mutate] replaces an integer with one of these
+//! instead:
+//!
+//! - 0, 1, and -1 (all bits set)
+//! - the minimum and maximum of the signed and unsigned types of the same width, and the
+//!   values next to them
+//! - every power of two, its negation, and the values one above and below both
+//!
+//! Signed and unsigned types of the same width share the same bit patterns. Protocol-specific
+//! constants (magic numbers, opcodes, well-known ports) can be added to these tables with
+//! [Mutator::add_interesting_value]; they're picked as often as all built-in values together,
+//! and are sometimes off by one:
+//!
+//! ```compile_fail
+//! mutator.add_interesting_value(0xCAFE_BABEu32);
+//! mutator.add_interesting_value(8080u16);
+//! ```
+//!
+//! [Mutator::interesting_value_chance]: crate::mutator::Mutator::interesting_value_chance
+//! [Mutator::mutate]: crate::mutator::Mutator::mutate
+//! [Mutator::add_interesting_value]: crate::mutator::Mutator::add_interesting_value
+
+/// The bit patterns of the interesting values of integers `bits` wide, without duplicates
+fn interesting_bit_patterns(bits: u32) -> Vec<u128> {
+    let mask = if bits == 128 {
+        u128::MAX
+    } else {
+        (1u128 << bits) - 1
+    };
+    let signed_max = mask >> 1;
+    let signed_min = signed_max + 1;
+
+    let mut values = vec![
+        0,
+        1,
+        mask,
+        mask - 1,
+        signed_max,
+        signed_max - 1,
+        signed_min,
+        signed_min + 1,
+    ];
+
+    for shift in 1..bits {
+        let power = 1u128 << shift;
+        let negated = power.wrapping_neg();
+        for value in [power, negated].iter() {
+            values.push(*value);
+            values.push(value.wrapping_add(1));
+            values.push(value.wrapping_sub(1));
+        }
+    }
+
+    let mut unique = vec![];
+    for value in values.into_iter().map(|v| v & mask) {
+        if !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+
+    unique
+}
+
+/// An integer type with a table of interesting values
+pub trait InterestingValue: Copy + 'static {
+    /// Width of the type in bytes. Values added with [Mutator::add_interesting_value] are shared
+    /// between all types of the same width.
+    ///
+    /// [Mutator::add_interesting_value]: crate::mutator::Mutator::add_interesting_value
+    const WIDTH: usize;
+
+    /// The built-in interesting values of this type
+    fn interesting_values() -> &'static [Self];
+
+    #[doc(hidden)]
+    fn to_bits(self) -> u128;
+
+    #[doc(hidden)]
+    fn from_bits(bits: u128) -> Self;
+}
+
+macro_rules! impl_interesting_value {
+    ( $($name:ident($table:ident)),* ) => {
+        $(
+            lazy_static::lazy_static! {
+                static ref $table: Vec<$name> = interesting_bit_patterns($name::BITS)
+                    .into_iter()
+                    .map(|bits| bits as $name)
+                    .collect();
+            }
+
+            impl InterestingValue for $name {
+                const WIDTH: usize = std::mem::size_of::<$name>();
+
+                fn interesting_values() -> &'static [Self] {
+                    &$table
+                }
+
+                fn to_bits(self) -> u128 {
+                    self as u128
+                }
+
+                fn from_bits(bits: u128) -> Self {
+                    bits as $name
+                }
+            }
+        )*
+    }
+}
+
+impl_interesting_value!(
+    u8(INTERESTING_U8),
+    i8(INTERESTING_I8),
+    u16(INTERESTING_U16),
+    i16(INTERESTING_I16),
+    u32(INTERESTING_U32),
+    i32(INTERESTING_I32),
+    u64(INTERESTING_U64),
+    i64(INTERESTING_I64),
+    u128(INTERESTING_U128),
+    i128(INTERESTING_I128),
+    usize(INTERESTING_USIZE),
+    isize(INTERESTING_ISIZE)
+);
+
+/// Interesting values added with [Mutator::add_interesting_value], as bit patterns, by width
+///
+/// [Mutator::add_interesting_value]: crate::mutator::Mutator::add_interesting_value
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UserInterestingValues {
+    values: [Vec<u128>; 5],
+}
+
+impl UserInterestingValues {
+    fn index(width: usize) -> usize {
+        match width {
+            1 => 0,
+            2 => 1,
+            4 => 2,
+            8 => 3,
+            _ => 4,
+        }
+    }
+
+    pub(crate) fn add<T: InterestingValue>(&mut self, value: T) {
+        let values = &mut self.values[Self::index(T::WIDTH)];
+        if !values.contains(&value.to_bits()) {
+            values.push(value.to_bits());
+        }
+    }
+
+    pub(crate) fn get<T: InterestingValue>(&self) -> &[u128] {
+        &self.values[Self::index(T::WIDTH)]
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for values in self.values.iter_mut() {
+            values.clear();
+        }
+    }
+}
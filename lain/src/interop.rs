@@ -0,0 +1,141 @@
+//! Adapters which allow types implementing [NewFuzzed] to be used with property-based testing
+//! frameworks, so that the same data models can drive both fuzzers and unit tests.
+//!
+//! - `quickcheck_support` enables [QuickcheckAdapter], which implements `quickcheck::Arbitrary`
+//! - `proptest_support` enables [NewFuzzedStrategy], which implements `proptest::strategy::Strategy`
+//!
+//! Values are generated by seeding a [Mutator] from the framework's RNG, so failing cases are
+//! reproducible through the framework's own seed handling. Shrinking is not supported.
+
+#[cfg(feature = "proptest_support")]
+pub use self::proptest_adapter::*;
+#[cfg(feature = "quickcheck_support")]
+pub use self::quickcheck_adapter::*;
+
+#[cfg(feature = "quickcheck_support")]
+mod quickcheck_adapter {
+    use crate::mutator::Mutator;
+    use crate::rand::rngs::StdRng;
+    use crate::rand::SeedableRng;
+    use crate::traits::NewFuzzed;
+
+    /// Wrapper implementing `quickcheck::Arbitrary` for any `T: NewFuzzed`.
+    ///
+    /// ```compile_fail
+    /// #[quickcheck]
+    /// fn parses_any_header(header: QuickcheckAdapter<Header>) -> bool {
+    ///     parse_header(&header.0).is_ok()
+    /// }
+    /// ```
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct QuickcheckAdapter<T>(pub T);
+
+    impl<T> QuickcheckAdapter<T> {
+        pub fn into_inner(self) -> T {
+            self.0
+        }
+    }
+
+    impl<T> std::ops::Deref for QuickcheckAdapter<T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.0
+        }
+    }
+
+    impl<T> quickcheck::Arbitrary for QuickcheckAdapter<T>
+    where
+        T: NewFuzzed + Clone + 'static,
+    {
+        fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+            let seed = <u64 as quickcheck::Arbitrary>::arbitrary(g);
+            let mut mutator = Mutator::new(StdRng::seed_from_u64(seed));
+
+            QuickcheckAdapter(T::new_fuzzed(&mut mutator, None))
+        }
+    }
+}
+
+#[cfg(feature = "proptest_support")]
+mod proptest_adapter {
+    use crate::mutator::Mutator;
+    use crate::rand::rngs::StdRng;
+    use crate::rand::SeedableRng;
+    use crate::traits::NewFuzzed;
+    use crate::types::Constraints;
+    use proptest::prelude::Rng as _;
+    use proptest::strategy::{Just, NewTree, Strategy};
+    use proptest::test_runner::TestRunner;
+    use std::fmt::{self, Debug};
+    use std::marker::PhantomData;
+
+    /// A proptest `Strategy` which generates values using [NewFuzzed::new_fuzzed].
+    ///
+    /// ```compile_fail
+    /// proptest! {
+    ///     #[test]
+    ///     fn parses_any_header(header in new_fuzzed_strategy::<Header>()) {
+    ///         prop_assert!(parse_header(&header).is_ok());
+    ///     }
+    /// }
+    /// ```
+    pub struct NewFuzzedStrategy<T: NewFuzzed> {
+        constraints: Option<Constraints<T::RangeType>>,
+        _marker: PhantomData<fn() -> T>,
+    }
+
+    impl<T: NewFuzzed> NewFuzzedStrategy<T> {
+        pub fn new() -> Self {
+            NewFuzzedStrategy {
+                constraints: None,
+                _marker: PhantomData,
+            }
+        }
+
+        /// Creates a strategy which passes `constraints` to every call to `new_fuzzed`
+        pub fn with_constraints(constraints: Constraints<T::RangeType>) -> Self {
+            NewFuzzedStrategy {
+                constraints: Some(constraints),
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    impl<T: NewFuzzed> Default for NewFuzzedStrategy<T> {
+        fn default() -> Self {
+            NewFuzzedStrategy::new()
+        }
+    }
+
+    impl<T: NewFuzzed> Debug for NewFuzzedStrategy<T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("NewFuzzedStrategy")
+                .field("constraints", &self.constraints)
+                .finish()
+        }
+    }
+
+    impl<T> Strategy for NewFuzzedStrategy<T>
+    where
+        T: NewFuzzed + Clone + Debug,
+    {
+        type Tree = Just<T>;
+        type Value = T;
+
+        fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+            let seed = runner.rng().next_u64();
+            let mut mutator = Mutator::new(StdRng::seed_from_u64(seed));
+
+            Ok(Just(T::new_fuzzed(&mut mutator, self.constraints.as_ref())))
+        }
+    }
+
+    /// Returns a strategy generating values of `T` with [NewFuzzed::new_fuzzed]
+    pub fn new_fuzzed_strategy<T>() -> NewFuzzedStrategy<T>
+    where
+        T: NewFuzzed + Clone + Debug,
+    {
+        NewFuzzedStrategy::new()
+    }
+}
@@ -1,10 +1,7 @@
-//! This crate provides functionality one may find useful while developing a fuzzer. A recent
-//! nightly Rust build is required for the specialization feature.
+//! This crate provides functionality one may find useful while developing a fuzzer.
 //!
 //! Please consider this crate in "beta" and subject to breaking changes for minor version releases for pre-1.0.
 
-#![feature(specialization)]
-
 extern crate num;
 extern crate num_derive;
 extern crate num_traits;
@@ -21,19 +18,60 @@ pub use lain_derive::*;
 #[macro_use]
 pub extern crate log;
 
+pub mod attribution;
 #[doc(hidden)]
 pub mod buffer;
+pub mod calibration;
+pub mod checksum;
+#[cfg(feature = "cli_support")]
+pub mod cli;
+pub mod compat;
+pub mod concolic;
+pub mod control;
+pub mod corpus;
+pub mod coverage;
+pub mod crossover;
 #[doc(hidden)]
 pub mod dangerous_numbers;
+pub mod dedup;
+pub mod dictionary;
+pub mod differential;
 pub mod driver;
+pub mod experiments;
+pub mod export;
+#[doc(hidden)]
+pub mod fallback;
+pub mod feedback;
+#[cfg(feature = "framing_support")]
+pub mod framing;
+pub mod interesting;
+#[cfg(any(feature = "quickcheck_support", feature = "proptest_support"))]
+pub mod interop;
+pub mod memory;
+pub mod minimize;
 #[doc(hidden)]
 pub mod mutatable;
 pub mod mutator;
 #[doc(hidden)]
 pub mod new_fuzzed;
+pub mod operators;
+pub mod panics;
+pub mod pipeline;
+pub mod plain;
+#[cfg(feature = "plugin_support")]
+pub mod plugin;
 pub mod prelude;
+pub mod report;
+pub mod schema;
+pub mod selftest;
+pub mod slow_units;
+pub mod swarm;
+pub mod target_snapshot;
 pub mod traits;
 pub mod types;
+pub mod walk;
+
+pub use crate::selftest::selftest;
 
 pub fn hexdump(data: &[u8]) -> String {
     let mut ret = "------".to_string();
@@ -0,0 +1,179 @@
+//! Per-iteration memory usage tracking.
+//!
+//! An input which makes the target allocate an excessive amount of memory (a length field read
+//! straight into `Vec::with_capacity`, a decompression bomb) is a bug even when the allocation
+//! happens to succeed. Once the driver is given a
+//! [memory budget][FuzzerDriver::set_memory_budget], it measures the peak memory usage of every
+//! iteration and records inputs exceeding the budget as [FindingKind::Oom] findings, persisted to
+//! `ooms/`, separately from crashes.
+//!
+//! In-process targets are measured by installing a [TrackingAllocator] as the global allocator.
+//! Allocations are attributed to the thread which makes them, so each fuzzer thread measures
+//! only its own iterations:
+//!
+//! ```compile_fail
+//! #[global_allocator]
+//! static ALLOCATOR: TrackingAllocator = TrackingAllocator::new(System);
+//!
+//! driver.set_memory_budget(256 * 1024 * 1024);
+//! ```
+//!
+//! Targets running in a child process can't be measured by the allocator. The callback
+//! instead measures the child itself, e.g. with `wait_with_peak_rss` (available with the `libc`
+//! feature), and hands the result to [report_peak_usage] before returning.
+//!
+//! [FuzzerDriver::set_memory_budget]: crate::driver::FuzzerDriver::set_memory_budget
+//! [FindingKind::Oom]: crate::report::FindingKind::Oom
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+thread_local! {
+    /// Bytes allocated by this thread and not yet freed. Memory freed by a different thread
+    /// than the one which allocated it can make this negative.
+    static CURRENT: Cell<isize> = const { Cell::new(0) };
+    /// Highest value of `CURRENT` since the iteration began
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+    /// Value of `CURRENT` when the iteration began
+    static BASELINE: Cell<isize> = const { Cell::new(0) };
+    /// Peak usage reported by the callback for this iteration
+    static REPORTED: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Set once any allocation has gone through a [TrackingAllocator]
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// A global allocator which records how much memory each thread has allocated, deferring the
+/// allocations themselves to another allocator.
+#[derive(Debug, Default)]
+pub struct TrackingAllocator<A = System> {
+    inner: A,
+}
+
+impl<A> TrackingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        TrackingAllocator { inner }
+    }
+}
+
+fn record_allocation(size: usize) {
+    TRACKING.store(true, Ordering::Relaxed);
+
+    // the thread's locals may already be destroyed if it's exiting
+    let _ = CURRENT.try_with(|current| {
+        let usage = current.get().wrapping_add(size as isize);
+        current.set(usage);
+
+        let _ = PEAK.try_with(|peak| {
+            if usage > peak.get() {
+                peak.set(usage);
+            }
+        });
+    });
+}
+
+fn record_deallocation(size: usize) {
+    let _ = CURRENT.try_with(|current| current.set(current.get().wrapping_sub(size as isize)));
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        record_deallocation(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_deallocation(layout.size());
+            record_allocation(new_size);
+        }
+
+        new_ptr
+    }
+}
+
+/// Whether a [TrackingAllocator] is installed as the global allocator
+pub fn is_tracking() -> bool {
+    TRACKING.load(Ordering::Relaxed)
+}
+
+/// Starts measuring the calling thread's peak memory usage from its current usage, and
+/// discards any usage reported with [report_peak_usage]
+pub fn begin_iteration() {
+    let current = CURRENT.with(|current| current.get());
+    BASELINE.with(|baseline| baseline.set(current));
+    PEAK.with(|peak| peak.set(current));
+    REPORTED.with(|reported| reported.set(None));
+}
+
+/// Returns the peak memory usage of the calling thread since [begin_iteration] was called, in
+/// bytes, or the usage reported with [report_peak_usage] if that is higher. Returns `None` if
+/// no [TrackingAllocator] is installed and no usage was reported.
+pub fn end_iteration() -> Option<usize> {
+    let reported = REPORTED.with(|reported| reported.take());
+    if !is_tracking() {
+        return reported;
+    }
+
+    let baseline = BASELINE.with(|baseline| baseline.get());
+    let tracked = PEAK.with(|peak| peak.get()).saturating_sub(baseline).max(0) as usize;
+
+    Some(reported.map_or(tracked, |reported| std::cmp::max(reported, tracked)))
+}
+
+/// Reports the peak memory usage of the current iteration in bytes, for targets the
+/// [TrackingAllocator] can't measure such as child processes
+pub fn report_peak_usage(bytes: usize) {
+    REPORTED.with(|reported| {
+        let peak = reported
+            .get()
+            .map_or(bytes, |peak| std::cmp::max(peak, bytes));
+        reported.set(Some(peak));
+    });
+}
+
+/// Waits for `child` to exit and returns its exit status along with its peak resident set size
+/// in bytes.
+#[cfg(all(unix, feature = "libc"))]
+pub fn wait_with_peak_rss(
+    child: &mut std::process::Child,
+) -> std::io::Result<(std::process::ExitStatus, usize)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut status = 0;
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let pid = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, 0, &mut usage) };
+    if pid < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    // ru_maxrss is in bytes on macOS and kilobytes everywhere else
+    let max_rss = usage.ru_maxrss as usize;
+    let max_rss = if cfg!(target_os = "macos") {
+        max_rss
+    } else {
+        max_rss * 1024
+    };
+
+    Ok((std::process::ExitStatus::from_raw(status), max_rss))
+}
@@ -0,0 +1,498 @@
+//! Test-case minimization for triaging crashes.
+//!
+//! A crash found while fuzzing usually comes with an input full of values which have nothing to
+//! do with it. [minimize] repeatedly replaces the input with a simpler candidate for as long as
+//! a predicate says it still reproduces the crash: lists get shorter, integers move toward zero,
+//! options become `None`, and enums fall back to earlier unit variants. Candidates come from
+//! [Minimize], which is implemented for primitives and lain's types and can be derived for
+//! structs and enums with `#[derive(Minimize)]`.
+//!
+//! ```compile_fail
+//! #[derive(Clone, NewFuzzed, BinarySerialize, Minimize)]
+//! struct Packet {
+//!     kind: PacketKind,
+//!     payload: Vec<u8>,
+//! }
+//!
+//! let minimized = minimize(crashing_packet, 10_000, |packet| {
+//!     let mut bytes = vec![];
+//!     packet.binary_serialize::<_, BigEndian>(&mut bytes);
+//!     run_target(&bytes) == Outcome::Crash
+//! });
+//! ```
+//!
+//! Candidates are passed to the predicate as they are: fields which are normally computed by a
+//! [Fixup][crate::traits::Fixup] (lengths, checksums) are shrunk like any other field.
+//!
+//! When the crashing input was mutated from one which doesn't crash, [minimize_delta] instead
+//! finds which of the changes between the two are needed for the crash. It reverts the fields
+//! of the crashing input to their values in the parent, and then the bytes of the remaining
+//! fields, until no more can be reverted:
+//!
+//! ```compile_fail
+//! let delta = minimize_delta::<_, BigEndian, _>(&parent, &crashing, |bytes| {
+//!     run_target(bytes) == Outcome::Crash
+//! });
+//! if let Some(delta) = delta {
+//!     println!("{}", delta);
+//! }
+//! ```
+
+use crate::byteorder::ByteOrder;
+use crate::differential::{ddmin, leaf_spans, serialize_with_layout};
+use crate::traits::{BinarySerialize, Minimize};
+use crate::types::{AsciiChar, AsciiString, UnsafeEnum, Utf8Char, Utf8String};
+use std::collections::HashMap;
+use std::{fmt, iter};
+
+/// The result of [minimize].
+#[derive(Debug, Clone)]
+pub struct Minimized<T> {
+    /// The simplest input found which still satisfied the predicate
+    pub input: T,
+    /// Number of times the predicate was called
+    pub attempts: usize,
+    /// Number of candidates which were accepted
+    pub reductions: usize,
+}
+
+/// Reduces `input` by repeatedly replacing it with the first of its [Minimize::shrink]
+/// candidates for which `still_crashes` returns `true`, until no candidate does or the
+/// predicate has been called `max_attempts` times.
+///
+/// `input` itself is assumed to satisfy the predicate and is never passed to it.
+pub fn minimize<T, F>(input: T, max_attempts: usize, mut still_crashes: F) -> Minimized<T>
+where
+    T: Minimize,
+    F: FnMut(&T) -> bool,
+{
+    let mut minimized = Minimized {
+        input,
+        attempts: 0,
+        reductions: 0,
+    };
+
+    while minimized.attempts < max_attempts {
+        let attempts = &mut minimized.attempts;
+        let simpler = minimized
+            .input
+            .shrink()
+            .take(max_attempts - *attempts)
+            .find(|candidate| {
+                *attempts += 1;
+                still_crashes(candidate)
+            });
+
+        match simpler {
+            Some(simpler) => {
+                minimized.input = simpler;
+                minimized.reductions += 1;
+            }
+            None => break,
+        }
+    }
+
+    debug!(
+        "minimized input with {} reductions in {} attempts",
+        minimized.reductions, minimized.attempts
+    );
+
+    minimized
+}
+
+/// A field which has to keep (some of) its value in the crashing input for the crash to
+/// reproduce, as found by [minimize_delta].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeltaField {
+    /// Path of the field. Fields which share the same bytes (e.g. members of a bitfield) are
+    /// joined with `|`
+    pub path: String,
+    /// The serialized bytes of this field in the parent, which are empty if the field was added
+    /// by the mutation
+    pub parent: Vec<u8>,
+    /// The serialized bytes of this field in the crashing input
+    pub crashing: Vec<u8>,
+    /// Offsets within the field of the bytes which have to keep their crashing value, or `None`
+    /// if the field is needed as a whole: its size changed, or the bytes it needs depend on
+    /// other fields
+    pub bytes: Option<Vec<usize>>,
+}
+
+/// The result of [minimize_delta].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimizedDelta {
+    /// The changes which are needed for the crash, in the order of the fields
+    pub fields: Vec<DeltaField>,
+    /// The parent with only those changes applied, which still crashes
+    pub minimized_input: Vec<u8>,
+    /// Number of times the predicate was called
+    pub attempts: usize,
+}
+
+impl fmt::Display for MinimizedDelta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.fields.is_empty() {
+            return write!(f, "crash doesn't depend on any change from the parent");
+        }
+
+        write!(f, "crash caused by ")?;
+        for (i, field) in self.fields.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(
+                f,
+                "field {} {:02X?} -> {:02X?}",
+                field.path, field.parent, field.crashing
+            )?;
+            if let Some(ref bytes) = field.bytes {
+                write!(f, " (bytes {:?})", bytes)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A field of the crashing input which differs from the parent
+struct Change {
+    paths: Vec<String>,
+    start: usize,
+    end: usize,
+    /// Range of the field in the parent, or `None` if the parent doesn't have it
+    parent: Option<(usize, usize)>,
+}
+
+/// Finds the smallest set of changes from `parent` to `crashing` (its mutated child) for which
+/// `crashes` still returns `true`, working on both inputs serialized with byte order `E`.
+///
+/// Changes are first narrowed down to whole fields, as reported by
+/// [BinarySerialize::field_layout], and then to the bytes within those fields which are the same
+/// size in both inputs. Fields are matched up by their paths, so a field which moved (e.g. the
+/// elements after one inserted into a list) counts as changed. Bytes which aren't part of any
+/// field are left as they are in `crashing`, and inputs without a field layout are compared as a
+/// single field.
+///
+/// Returns `None` if `crashes` doesn't hold for `crashing` itself, and no fields if it holds
+/// for `parent` as well.
+pub fn minimize_delta<T, E, F>(parent: &T, crashing: &T, mut crashes: F) -> Option<MinimizedDelta>
+where
+    T: BinarySerialize,
+    E: ByteOrder,
+    F: FnMut(&[u8]) -> bool,
+{
+    let (parent_bytes, parent_layout) = serialize_with_layout::<T, E>(parent);
+    let (crashing_bytes, crashing_layout) = serialize_with_layout::<T, E>(crashing);
+
+    let mut attempts = 1;
+    if !crashes(&crashing_bytes) {
+        return None;
+    }
+
+    let parent_spans: HashMap<&str, (usize, usize)> = parent_layout
+        .iter()
+        .map(|s| (s.path.as_str(), (s.start, s.end)))
+        .collect();
+
+    let mut leaves = leaf_spans(&crashing_layout);
+    if leaves.is_empty() {
+        leaves.push((vec![String::from("<root>")], 0, crashing_bytes.len()));
+    }
+
+    let changes: Vec<Change> = leaves
+        .into_iter()
+        .map(|(paths, start, end)| {
+            let parent = if paths[0] == "<root>" {
+                Some((0, parent_bytes.len()))
+            } else {
+                parent_spans.get(paths[0].as_str()).copied()
+            };
+
+            Change {
+                paths,
+                start,
+                end,
+                parent,
+            }
+        })
+        .filter(|change| match change.parent {
+            Some((start, end)) => {
+                parent_bytes[start..end] != crashing_bytes[change.start..change.end]
+            }
+            None => true,
+        })
+        .collect();
+
+    // builds the crashing input with the changes not in `kept` reverted. Of the changes in
+    // `partial`, only the bytes at the given offsets are kept.
+    let build = |kept: &[usize], partial: &[(usize, &[usize])]| -> Vec<u8> {
+        let mut output = Vec::with_capacity(crashing_bytes.len());
+        let mut position = 0;
+
+        for (i, change) in changes.iter().enumerate() {
+            output.extend_from_slice(&crashing_bytes[position..change.start]);
+            position = change.end;
+
+            let crashing_field = &crashing_bytes[change.start..change.end];
+            let parent_field = change
+                .parent
+                .map_or(&[][..], |(start, end)| &parent_bytes[start..end]);
+            match partial.iter().find(|(index, _)| *index == i) {
+                Some((_, offsets)) => {
+                    let mut field = parent_field.to_vec();
+                    for &offset in offsets.iter() {
+                        field[offset] = crashing_field[offset];
+                    }
+                    output.extend_from_slice(&field);
+                }
+                None if kept.contains(&i) => output.extend_from_slice(crashing_field),
+                None => output.extend_from_slice(parent_field),
+            }
+        }
+
+        output.extend_from_slice(&crashing_bytes[position..]);
+        output
+    };
+
+    let fields = ddmin((0..changes.len()).collect(), |kept| {
+        attempts += 1;
+        crashes(&build(kept, &[]))
+    });
+
+    let mut bytes: Vec<Option<Vec<usize>>> = vec![None; fields.len()];
+    for (position, &index) in fields.iter().enumerate() {
+        let change = &changes[index];
+        let (start, end) = match change.parent {
+            Some(range) if range.1 - range.0 == change.end - change.start => range,
+            _ => continue,
+        };
+
+        let differing: Vec<usize> = (0..end - start)
+            .filter(|&offset| parent_bytes[start + offset] != crashing_bytes[change.start + offset])
+            .collect();
+        let others: Vec<usize> = fields.iter().copied().filter(|&i| i != index).collect();
+        bytes[position] = Some(ddmin(differing, |offsets| {
+            attempts += 1;
+            crashes(&build(&others, &[(index, offsets)]))
+        }));
+    }
+
+    // each field's bytes were narrowed down with the others complete, and may depend on the
+    // bytes of another field, so they only stay narrowed down if they still crash together
+    let partial: Vec<(usize, &[usize])> = fields
+        .iter()
+        .zip(bytes.iter())
+        .filter_map(|(&index, offsets)| offsets.as_ref().map(|offsets| (index, &offsets[..])))
+        .collect();
+    let mut minimized_input = build(&fields, &partial);
+    if !partial.is_empty() {
+        attempts += 1;
+        if !crashes(&minimized_input) {
+            minimized_input = build(&fields, &[]);
+            bytes.iter_mut().for_each(|offsets| *offsets = None);
+        }
+    }
+
+    let delta_fields: Vec<DeltaField> = fields
+        .iter()
+        .zip(bytes)
+        .map(|(&index, bytes)| {
+            let change = &changes[index];
+
+            DeltaField {
+                path: change.paths.join("|"),
+                parent: change
+                    .parent
+                    .map_or_else(Vec::new, |(start, end)| parent_bytes[start..end].to_vec()),
+                crashing: crashing_bytes[change.start..change.end].to_vec(),
+                bytes,
+            }
+        })
+        .collect();
+
+    debug!(
+        "minimized delta to {} fields in {} attempts",
+        delta_fields.len(),
+        attempts
+    );
+
+    Some(MinimizedDelta {
+        fields: delta_fields,
+        minimized_input,
+        attempts,
+    })
+}
+
+/// Ranges of a list of `len` items to remove: the whole list first, then halves, quarters, and
+/// so on down to single items
+fn removals(len: usize) -> impl Iterator<Item = (usize, usize)> {
+    iter::successors(
+        Some(len),
+        |&size| if size > 1 { Some(size / 2) } else { None },
+    )
+    .filter(|&size| size > 0)
+    .flat_map(move |size| {
+        (0..len)
+            .step_by(size)
+            .map(move |start| (start, std::cmp::min(start + size, len)))
+    })
+}
+
+impl<T: Minimize> Minimize for Vec<T> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        let shorter = removals(self.len()).map(move |(start, end)| {
+            let mut candidate = Vec::with_capacity(self.len() - (end - start));
+            candidate.extend_from_slice(&self[..start]);
+            candidate.extend_from_slice(&self[end..]);
+            candidate
+        });
+
+        let simpler_elements = (0..self.len()).flat_map(move |i| {
+            self[i].shrink().map(move |element| {
+                let mut candidate = self.clone();
+                candidate[i] = element;
+                candidate
+            })
+        });
+
+        Box::new(shorter.chain(simpler_elements))
+    }
+}
+
+impl<T: Minimize, const N: usize> Minimize for [T; N] {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new((0..N).flat_map(move |i| {
+            self[i].shrink().map(move |element| {
+                let mut candidate = self.clone();
+                candidate[i] = element;
+                candidate
+            })
+        }))
+    }
+}
+
+impl<T: Minimize> Minimize for Option<T> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        match self {
+            Some(value) => Box::new(iter::once(None).chain(value.shrink().map(Some))),
+            None => Box::new(iter::empty()),
+        }
+    }
+}
+
+impl<T: Minimize> Minimize for Box<T> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new((**self).shrink().map(Box::new))
+    }
+}
+
+impl<T: Minimize, I: Minimize> Minimize for UnsafeEnum<T, I> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        match self {
+            UnsafeEnum::Valid(value) => Box::new(value.shrink().map(UnsafeEnum::Valid)),
+            UnsafeEnum::Invalid(value) => Box::new(value.shrink().map(UnsafeEnum::Invalid)),
+        }
+    }
+}
+
+impl Minimize for String {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(removals(self.chars().count()).map(move |(start, end)| {
+            self.chars()
+                .take(start)
+                .chain(self.chars().skip(end))
+                .collect()
+        }))
+    }
+}
+
+impl Minimize for Utf8String {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(self.inner.shrink().map(|inner| Utf8String { inner }))
+    }
+}
+
+impl Minimize for AsciiString {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(self.inner.shrink().map(|inner| AsciiString { inner }))
+    }
+}
+
+/// Characters of lain's string types are only ever removed, not simplified
+macro_rules! impl_minimize_irreducible {
+    ( $($name:ident),* ) => {
+        $(
+            impl Minimize for $name {
+                fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+                    Box::new(iter::empty())
+                }
+            }
+        )*
+    }
+}
+
+impl_minimize_irreducible!(Utf8Char, AsciiChar);
+
+impl Minimize for bool {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(self.then_some(false).into_iter())
+    }
+}
+
+macro_rules! impl_minimize_integer {
+    ( $($name:ident),* ) => {
+        $(
+            impl Minimize for $name {
+                fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+                    let value = *self;
+                    let mut candidates = vec![];
+
+                    if value != 0 {
+                        candidates.push(0);
+
+                        let half = value / 2;
+                        if half != 0 {
+                            candidates.push(half);
+                        }
+
+                        let closer = if value > 0 { value - 1 } else { value + 1 };
+                        if closer != 0 && closer != half {
+                            candidates.push(closer);
+                        }
+                    }
+
+                    Box::new(candidates.into_iter())
+                }
+            }
+        )*
+    }
+}
+
+impl_minimize_integer!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+macro_rules! impl_minimize_float {
+    ( $($name:ident),* ) => {
+        $(
+            impl Minimize for $name {
+                fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+                    let value = *self;
+                    let mut candidates = vec![];
+
+                    if value != 0.0 {
+                        candidates.push(0.0);
+
+                        let whole = value.trunc();
+                        if value.is_finite() && whole != value && whole != 0.0 {
+                            candidates.push(whole);
+                        }
+                    }
+
+                    Box::new(candidates.into_iter())
+                }
+            }
+        )*
+    }
+}
+
+impl_minimize_float!(f32, f64);
@@ -1,5 +1,10 @@
+use crate::buffer::sort_by_key_bytes;
+use crate::byteorder::BigEndian;
+use crate::interesting::InterestingValue;
 use crate::mutator::Mutator;
+use crate::operators::MutationOperator;
 use crate::rand::seq::index;
+use crate::rand::seq::SliceRandom;
 use crate::rand::Rng;
 use crate::traits::*;
 use crate::types::*;
@@ -7,7 +12,9 @@ use crate::NewFuzzed;
 
 use num_traits::{Bounded, NumCast};
 use num_traits::{WrappingAdd, WrappingSub};
-use std::cmp::min;
+use std::cmp::{self, min};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
 use std::ops::BitXor;
 
 // we'll shrink by a factor of 1/4, 1/2, 3/4, or down to [0, 8] bytes
@@ -24,21 +31,19 @@ enum VecResizeCount {
 enum VecResizeDirection {
     FromBeginning,
     FromEnd,
-}
-
-#[derive(Copy, Clone, PartialEq, NewFuzzed)]
-enum VecResizeType {
-    Grow,
-    Shrink,
+    RandomOffset,
 }
 
 /// Grows a `Vec`.
 /// This will randomly select to grow by a factor of 1/4, 1/2, 3/4, or a fixed number of bytes
-/// in the range of [1, 8]. Elements may be added randomly to the beginning or end of the the vec
+/// in the range of [1, 8]. Elements may be added randomly to the beginning or end of the the vec,
+/// or inserted together at a random offset within it. Elements are added in whole groups of
+/// `group` elements, at offsets between groups
 fn grow_vec<T: NewFuzzed + SerializedSize, R: Rng>(
     vec: &mut Vec<T>,
     mutator: &mut Mutator<R>,
     mut max_size: Option<usize>,
+    group: usize,
 ) {
     let resize_count = VecResizeCount::new_fuzzed(mutator, None);
     let mut num_elements = if vec.is_empty() {
@@ -53,83 +58,84 @@ fn grow_vec<T: NewFuzzed + SerializedSize, R: Rng>(
         }
     };
 
+    // round up to whole groups
+    num_elements = num_elements.div_ceil(group) * group;
+
     // If we were given a size constraint, we need to respect it
     if let Some(max_size) = max_size {
         num_elements = min(num_elements, max_size / T::max_default_object_size());
     }
 
+    num_elements -= num_elements % group;
     if num_elements == 0 {
         return;
     }
 
-    match VecResizeDirection::new_fuzzed(mutator, None) {
-        VecResizeDirection::FromBeginning => {
-            // to avoid shifting the the entire vec on every iteration, we will
-            // instead allocate a new vec, then extend it with the previous one
-            let mut new_vec = Vec::with_capacity(num_elements);
-            for _i in 0..num_elements {
-                let constraints = max_size.map(|max_size| {
-                    let mut c = Constraints::new();
-                    c.max_size(max_size);
-                    c.base_object_size_accounted_for = true;
-
-                    c
-                });
-
-                let element = T::new_fuzzed(mutator, constraints.as_ref());
-                if let Some(inner_max_size) = max_size {
-                    // if this element is larger than the size we're allotted,
-                    // then let's just exit
-                    let element_size = element.serialized_size();
-                    if element_size > inner_max_size {
-                        break;
-                    }
+    mutator.record_operator(MutationOperator::GrowList);
 
-                    max_size = Some(inner_max_size - element_size);
-                }
+    let direction = VecResizeDirection::new_fuzzed(mutator, None);
+
+    // to avoid shifting the the entire vec on every iteration, we will
+    // instead allocate a new vec, then splice it into the previous one
+    let mut new_vec = Vec::with_capacity(num_elements);
+    for _i in 0..num_elements {
+        let constraints = max_size.map(|max_size| {
+            let mut c = Constraints::new();
+            c.max_size(max_size);
+            c.base_object_size_accounted_for = true;
+
+            c
+        });
 
-                new_vec.push(element);
+        let element = T::new_fuzzed(mutator, constraints.as_ref());
+        if let Some(inner_max_size) = max_size {
+            // if this element is larger than the size we're allotted,
+            // then let's just exit
+            let element_size = element.serialized_size();
+            if element_size > inner_max_size {
+                break;
             }
 
-            new_vec.append(vec);
-            *vec = new_vec
+            max_size = Some(inner_max_size - element_size);
         }
-        VecResizeDirection::FromEnd => {
-            for _i in 0..num_elements {
-                let constraints = max_size.map(|max_size| {
-                    let mut c = Constraints::new();
-                    c.max_size(max_size);
-                    c.base_object_size_accounted_for = true;
-
-                    c
-                });
-
-                let element = T::new_fuzzed(mutator, constraints.as_ref());
-                if let Some(inner_max_size) = max_size {
-                    // if this element is larger than the size we're allotted,
-                    // then let's just exit
-                    let element_size = element.serialized_size();
-                    if element_size > inner_max_size {
-                        break;
-                    }
 
-                    max_size = Some(inner_max_size - element_size);
-                }
+        new_vec.push(element);
+    }
 
-                vec.push(element);
-            }
+    // a group which didn't fit is left out entirely
+    new_vec.truncate(new_vec.len() - new_vec.len() % group);
+
+    match direction {
+        VecResizeDirection::FromBeginning => {
+            new_vec.append(vec);
+            *vec = new_vec
+        }
+        VecResizeDirection::FromEnd => vec.append(&mut new_vec),
+        VecResizeDirection::RandomOffset => {
+            let offset = mutator.gen_range(0, vec.len() / group + 1) * group;
+            vec.splice(offset..offset, new_vec);
         }
     }
 }
 
 /// Shrinks a `Vec`.
 /// This will randomly select to resize by a factor of 1/4, 1/2, 3/4, or a fixed number of bytes
-/// in the range of [1, 8]. Elements may be removed randomly from the beginning or end of the the vec
-fn shrink_vec<T, R: Rng>(vec: &mut Vec<T>, mutator: &mut Mutator<R>) {
+/// in the range of [1, 8]. Elements may be removed randomly from the beginning or end of the the vec,
+/// or from a random offset within it, but never so many that the rest serialize to less than
+/// `min_size` bytes. Elements are removed in whole groups of `group` elements, counted from the
+/// beginning of the vec, where a partial group at its end counts as one
+fn shrink_vec<T: SerializedSize, R: Rng>(
+    vec: &mut Vec<T>,
+    mutator: &mut Mutator<R>,
+    min_size: Option<usize>,
+    group: usize,
+) {
     if vec.is_empty() {
         return;
     }
 
+    mutator.record_operator(MutationOperator::ShrinkList);
+
     let resize_count = VecResizeCount::new_fuzzed(mutator, None);
     let mut num_elements = match resize_count {
         VecResizeCount::Quarter => vec.len() / 4,
@@ -146,196 +152,576 @@ fn shrink_vec<T, R: Rng>(vec: &mut Vec<T>, mutator: &mut Mutator<R>) {
     num_elements = std::cmp::min(num_elements, vec.len());
 
     // Special case probably isn't required here, but better to be explicit
-    if num_elements == vec.len() {
+    if num_elements == vec.len() && min_size.unwrap_or(0) == 0 {
         vec.drain(..);
         return;
     }
 
-    match VecResizeDirection::new_fuzzed(mutator, None) {
+    let direction = VecResizeDirection::new_fuzzed(mutator, None);
+
+    // rounds a number of elements to remove down to whole groups
+    let partial_group = match direction {
+        VecResizeDirection::FromEnd => vec.len() % group,
+        _ => 0,
+    };
+    let whole_groups = |num_elements: usize| {
+        if num_elements < partial_group {
+            0
+        } else {
+            partial_group + (num_elements - partial_group) / group * group
+        }
+    };
+
+    if group > 1 {
+        let smallest = if partial_group > 0 {
+            partial_group
+        } else {
+            min(group, vec.len())
+        };
+        num_elements = cmp::max(whole_groups(num_elements), smallest);
+    }
+
+    // elements removed from a random offset are removed from there onwards
+    let offset = match direction {
+        VecResizeDirection::RandomOffset => {
+            mutator.gen_range(0, (vec.len() - num_elements) / group + 1) * group
+        }
+        _ => 0,
+    };
+
+    if let Some(min_size) = min_size {
+        let len = vec.len();
+        let mut remaining: usize = vec.iter().map(SerializedSize::serialized_size).sum();
+        let mut removable = 0;
+        for i in 0..len - offset {
+            let element = match direction {
+                VecResizeDirection::FromBeginning => &vec[i],
+                VecResizeDirection::FromEnd => &vec[len - 1 - i],
+                VecResizeDirection::RandomOffset => &vec[offset + i],
+            };
+
+            let size = element.serialized_size();
+            if remaining < min_size + size {
+                break;
+            }
+
+            remaining -= size;
+            removable += 1;
+        }
+
+        num_elements = whole_groups(std::cmp::min(num_elements, removable));
+    }
+
+    match direction {
         VecResizeDirection::FromBeginning => {
             vec.drain(0..num_elements);
         }
         VecResizeDirection::FromEnd => {
             vec.drain(vec.len() - num_elements..);
         }
+        VecResizeDirection::RandomOffset => {
+            vec.drain(offset..offset + num_elements);
+        }
     }
 }
 
+/// Number of consecutive elements forming one record, as set with [Constraints::element_group]
+fn element_group(constraints: Option<&Constraints<usize>>) -> usize {
+    constraints
+        .and_then(|c| c.element_group)
+        .map_or(1, |group| cmp::max(group, 1))
+}
+
 impl<T> Mutatable for Vec<T>
 where
-    T: Mutatable + SerializedSize,
-    T::RangeType: Clone,
+    T: Mutatable + NewFuzzed + SerializedSize + Clone,
+    <T as Mutatable>::RangeType: Clone,
 {
-    default type RangeType = usize;
+    type RangeType = usize;
 
-    default fn mutate<R: rand::Rng>(
+    fn mutate<R: rand::Rng>(
         &mut self,
         mutator: &mut Mutator<R>,
         constraints: Option<&Constraints<Self::RangeType>>,
     ) {
-        const CHANCE_TO_RESIZE_VEC: f64 = 0.01;
-
-        // 1% chance to resize this vec
-        if mutator.gen_chance(CHANCE_TO_RESIZE_VEC) {
-            shrink_vec(self, mutator);
-        } else {
-            // Recreate the constraints so that the min/max types match
-            let constraints = constraints.and_then(|c| {
-                if c.max_size.is_none() {
-                    None
-                } else {
-                    let mut new_constraints = Constraints::new();
-                    new_constraints.base_object_size_accounted_for =
-                        c.base_object_size_accounted_for;
-                    new_constraints.max_size = c.max_size;
-
-                    Some(new_constraints)
-                }
-            });
-
-            self.as_mut_slice().mutate(mutator, constraints.as_ref());
-        }
+        T::mutate_vec(self, mutator, constraints);
     }
 }
 
-impl<T> Mutatable for Vec<T>
-where
+pub(crate) fn mutate_vec<T, R: rand::Rng>(
+    vec: &mut Vec<T>,
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<usize>>,
+) where
     T: Mutatable + NewFuzzed + SerializedSize + Clone,
     <T as Mutatable>::RangeType: Clone,
 {
-    fn mutate<R: rand::Rng>(
-        &mut self,
-        mutator: &mut Mutator<R>,
-        constraints: Option<&Constraints<Self::RangeType>>,
-    ) {
-        const CHANCE_TO_RESIZE_VEC: f64 = 0.01;
-
-        if T::max_default_object_size() == 0 {
-            return;
-        }
+    const CHANCE_TO_RESIZE_VEC: f64 = 0.01;
 
-        // we can grow the vector if we have no size constraint or the max size quota hasn't
-        // been fulfilled
-        let can_grow = constraints
-            .map(|c| {
-                c.max_size
-                    .map(|s| s > 0 && s > T::max_default_object_size())
-                    .unwrap_or(true)
-            })
-            .unwrap_or(false);
+    if T::max_default_object_size() == 0 {
+        return;
+    }
 
-        if mutator.gen_chance(CHANCE_TO_RESIZE_VEC) {
-            let resize_type = VecResizeType::new_fuzzed(mutator, None);
-            if resize_type == VecResizeType::Grow && can_grow {
-                grow_vec(self, mutator, constraints.and_then(|c| c.max_size));
+    // we can grow the vector if we have no size constraint or the max size quota hasn't
+    // been fulfilled
+    let can_grow = constraints
+        .map(|c| {
+            c.max_size
+                .map(|s| s > 0 && s > T::max_default_object_size())
+                .unwrap_or(true)
+        })
+        .unwrap_or(false);
+
+    if mutator.gen_chance(CHANCE_TO_RESIZE_VEC) {
+        let group = element_group(constraints);
+        let min_size = constraints.and_then(|c| c.min_size);
+        let max_size = constraints.and_then(|c| c.max_size);
+        let size: usize = vec.iter().map(SerializedSize::serialized_size).sum();
+
+        // how full the vec is between its min and max size, with anything below the min
+        // always growing
+        let fill = match (min_size.unwrap_or(0), max_size) {
+            (min_size, _) if size < min_size => -1.0,
+            (min_size, Some(max_size)) if max_size > min_size => {
+                (size - min_size) as f64 / (max_size - min_size) as f64
+            }
+            _ if vec.is_empty() => 0.0,
+            _ => 0.5,
+        };
+
+        let can_grow = can_grow && mutator.operator_enabled(MutationOperator::GrowList);
+        let can_shrink = mutator.operator_enabled(MutationOperator::ShrinkList);
+        if can_grow && (!can_shrink || mutator.gen_grow_list(fill)) {
+            // the maximum size covers the elements already in the vec
+            let room = max_size.map(|max_size| max_size.saturating_sub(size));
+            grow_vec(vec, mutator, room, group);
+        } else if can_shrink {
+            shrink_vec(vec, mutator, min_size, group);
+        }
+    } else {
+        // Recreate the constraints so that the min/max types match
+        let constraints = constraints.and_then(|c| {
+            if c.max_size.is_none() {
+                None
             } else {
-                shrink_vec(self, mutator);
+                let mut new_constraints = Constraints::new();
+                new_constraints.base_object_size_accounted_for = c.base_object_size_accounted_for;
+                new_constraints.max_size = c.max_size;
+
+                Some(new_constraints)
             }
-        } else {
-            // Recreate the constraints so that the min/max types match
-            let constraints = constraints.and_then(|c| {
-                if c.max_size.is_none() {
-                    None
-                } else {
-                    let mut new_constraints = Constraints::new();
-                    new_constraints.base_object_size_accounted_for =
-                        c.base_object_size_accounted_for;
-                    new_constraints.max_size = c.max_size;
+        });
 
-                    Some(new_constraints)
-                }
-            });
+        vec.as_mut_slice().mutate(mutator, constraints.as_ref());
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, NewFuzzed)]
+enum ByteBlockMutation {
+    Duplicate,
+    Overwrite,
+    Swap,
+    Insert,
+}
+
+/// Picks the length of a block of at most `limit` bytes, favoring short blocks
+fn gen_block_len<R: Rng>(mutator: &mut Mutator<R>, limit: usize) -> usize {
+    let limit = match mutator.gen_range(0, 3) {
+        0 => min(limit, 8),
+        1 => min(limit, 32),
+        _ => limit,
+    };
+
+    mutator.gen_range(1, limit + 1)
+}
+
+/// Duplicates, overwrites, swaps, or inserts a block of bytes at any offset of `vec`. Blocks are
+/// only added while `vec` stays within the `max_size` of `constraints`, and the length of `vec`
+/// is otherwise kept. Blocks consist of whole [element groups][Constraints::element_group] and
+/// start between them.
+fn mutate_byte_blocks<R: Rng>(
+    vec: &mut Vec<u8>,
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<usize>>,
+) {
+    let group = element_group(constraints);
+    let room = constraints
+        .and_then(|c| c.max_size)
+        .map_or(usize::MAX, |max_size| max_size.saturating_sub(vec.len()));
+    // blocks are counted in groups, and the vec only in the groups it holds entirely
+    let (room, groups) = (room / group, vec.len() / group);
+
+    let mutation = if groups == 0 {
+        ByteBlockMutation::Insert
+    } else {
+        ByteBlockMutation::new_fuzzed(mutator, None)
+    };
+    let operator = match mutation {
+        ByteBlockMutation::Duplicate => MutationOperator::DuplicateBlock,
+        ByteBlockMutation::Overwrite => MutationOperator::OverwriteBlock,
+        ByteBlockMutation::Swap => MutationOperator::SwapBlocks,
+        ByteBlockMutation::Insert => MutationOperator::InsertBlock,
+    };
+    if !mutator.operator_enabled(operator) {
+        return mutate_vec(vec, mutator, constraints);
+    }
 
-            self.as_mut_slice().mutate(mutator, constraints.as_ref());
+    match mutation {
+        ByteBlockMutation::Duplicate if room > 0 => {
+            mutator.record_operator(MutationOperator::DuplicateBlock);
+            let len = gen_block_len(mutator, min(groups, room));
+            let start = mutator.gen_range(0, groups - len + 1) * group;
+            let block = vec[start..start + len * group].to_vec();
+            let at = mutator.gen_range(0, groups + 1) * group;
+            vec.splice(at..at, block);
+        }
+        ByteBlockMutation::Overwrite => {
+            mutator.record_operator(MutationOperator::OverwriteBlock);
+            let len = gen_block_len(mutator, groups);
+            let start = mutator.gen_range(0, groups - len + 1) * group;
+            let byte = match mutator.gen_range(0, 3) {
+                0 => 0x00,
+                1 => 0xFF,
+                _ => mutator.gen(),
+            };
+            vec[start..start + len * group]
+                .iter_mut()
+                .for_each(|b| *b = byte);
+        }
+        ByteBlockMutation::Swap if groups >= 2 => {
+            mutator.record_operator(MutationOperator::SwapBlocks);
+            let len = gen_block_len(mutator, groups / 2);
+            let first = mutator.gen_range(0, groups - 2 * len + 1);
+            let second = mutator.gen_range(first + len, groups - len + 1);
+            let (head, tail) = vec.split_at_mut(second * group);
+            head[first * group..(first + len) * group].swap_with_slice(&mut tail[..len * group]);
+        }
+        ByteBlockMutation::Insert if room > 0 => {
+            mutator.record_operator(MutationOperator::InsertBlock);
+            let len = gen_block_len(mutator, min(cmp::max(groups, 8), room)) * group;
+            let block: Vec<u8> = (0..len).map(|_| mutator.gen()).collect();
+            let at = mutator.gen_range(0, groups + 1) * group;
+            vec.splice(at..at, block);
         }
+        // there's no room for another block, or too few bytes to swap
+        _ => {}
     }
 }
 
-impl<T> Mutatable for Vec<T>
-where
-    T: Mutatable + NewFuzzed + SerializedSize,
-    <T as Mutatable>::RangeType: Clone,
+#[derive(Copy, Clone, PartialEq, NewFuzzed)]
+enum MapMutation {
+    Insert,
+    Remove,
+    MutateKey,
+}
+
+/// Inserts, removes, or mutates the key of a single entry of a map, given as its entries.
+/// New entries are only inserted if they fit in `max_size`.
+fn resize_map_entries<K, V, R: Rng>(
+    entries: &mut Vec<(K, V)>,
+    mutator: &mut Mutator<R>,
+    max_size: Option<usize>,
+) where
+    K: NewFuzzed + Mutatable + SerializedSize,
+    V: NewFuzzed + SerializedSize,
 {
-    type RangeType = usize;
+    let mutation = if entries.is_empty() {
+        MapMutation::Insert
+    } else {
+        MapMutation::new_fuzzed(mutator, None)
+    };
 
-    default fn mutate<R: rand::Rng>(
-        &mut self,
-        mutator: &mut Mutator<R>,
-        constraints: Option<&Constraints<Self::RangeType>>,
-    ) {
-        const CHANCE_TO_RESIZE_VEC: f64 = 0.01;
+    match mutation {
+        MapMutation::Insert => {
+            let constraints = max_size.map(|max_size| {
+                let mut c = Constraints::new();
+                c.max_size(max_size);
+                c.base_object_size_accounted_for = true;
+                c
+            });
 
-        if T::max_default_object_size() == 0 {
-            return;
+            let key = K::new_fuzzed(mutator, None);
+            let value = V::new_fuzzed(mutator, constraints.as_ref());
+            if max_size
+                .is_some_and(|max_size| key.serialized_size() + value.serialized_size() > max_size)
+            {
+                return;
+            }
+
+            mutator.record_operator(MutationOperator::GrowList);
+            let position = mutator.gen_range(0, entries.len() + 1);
+            entries.insert(position, (key, value));
         }
+        MapMutation::Remove => {
+            mutator.record_operator(MutationOperator::ShrinkList);
+            let position = mutator.gen_range(0, entries.len());
+            entries.remove(position);
+        }
+        MapMutation::MutateKey => {
+            let position = mutator.gen_range(0, entries.len());
+            entries[position].0.mutate(mutator, None);
+        }
+    }
+}
 
-        // we can grow the vector if we have no size constraint or the max size quota hasn't
-        // been fulfilled
-        let can_grow = constraints
-            .map(|c| c.max_size.map(|s| s > 0).unwrap_or(true))
-            .unwrap_or(false);
+macro_rules! impl_map_mutatable {
+    ( $($map:ident<K: $($key_bound:path),+>($sort:expr)),* ) => {
+        $(
+            /// Mostly mutates values, occasionally inserting, removing, or re-keying an entry.
+            /// A mutated key which collides with another replaces that entry. Entries are visited
+            /// in the order they're serialized in so that the same seed gives the same result.
+            impl<K, V> Mutatable for $map<K, V>
+            where
+                K: NewFuzzed + Mutatable + SerializedSize $(+ $key_bound)+,
+                V: NewFuzzed + Mutatable + SerializedSize,
+            {
+                type RangeType = usize;
 
-        if mutator.gen_chance(CHANCE_TO_RESIZE_VEC) {
-            let resize_type = VecResizeType::new_fuzzed(mutator, None);
-            if resize_type == VecResizeType::Grow && can_grow {
-                grow_vec(self, mutator, constraints.and_then(|c| c.max_size));
-            } else {
-                shrink_vec(self, mutator);
-            }
-        } else {
-            // Recreate the constraints so that the min/max types match
-            let constraints = constraints.and_then(|c| {
-                if c.max_size.is_none() {
-                    None
-                } else {
-                    let mut new_constraints = Constraints::new();
-                    new_constraints.base_object_size_accounted_for =
-                        c.base_object_size_accounted_for;
-                    new_constraints.max_size = c.max_size;
+                fn mutate<R: Rng>(
+                    &mut self,
+                    mutator: &mut Mutator<R>,
+                    constraints: Option<&Constraints<Self::RangeType>>,
+                ) {
+                    const CHANCE_TO_RESIZE_MAP: f64 = 0.01;
+
+                    let mut max_size = constraints.and_then(|c| c.max_size);
 
-                    Some(new_constraints)
+                    let mut entries: Vec<(K, V)> = std::mem::take(self).into_iter().collect();
+                    ($sort)(&mut entries);
+
+                    if mutator.gen_chance(CHANCE_TO_RESIZE_MAP) {
+                        resize_map_entries(&mut entries, mutator, max_size);
+                        *self = entries.into_iter().collect();
+
+                        return;
+                    }
+
+                    let window = mutator.gen_mutation_window(entries.len());
+                    for (_, value) in entries[window.clone()].iter_mut() {
+                        let prev_size = value.serialized_size();
+                        let constraints = max_size.map(|max_size| {
+                            let mut c = Constraints::new();
+                            c.max_size(max_size);
+                            c.base_object_size_accounted_for = true;
+                            c
+                        });
+
+                        value.mutate(mutator, constraints.as_ref());
+
+                        if let Some(max_size) = max_size.as_mut() {
+                            let delta = value.serialized_size() as isize - prev_size as isize;
+                            *max_size = cmp::max(*max_size as isize - delta, 0) as usize;
+                        }
+
+                        if mutator.should_early_bail_mutation() {
+                            break;
+                        }
+                    }
+
+                    *self = entries.into_iter().collect();
                 }
-            });
+            }
+        )*
+    }
+}
+
+impl_map_mutatable!(
+    HashMap<K: Eq, Hash, BinarySerialize>(sort_by_key_bytes::<_, K, _, BigEndian>),
+    BTreeMap<K: Ord>(|_entries: &mut [(K, V)]| ())
+);
 
-            self.as_mut_slice().mutate(mutator, constraints.as_ref());
+#[derive(Copy, Clone, PartialEq, NewFuzzed)]
+enum VariantVecMutation {
+    Grow,
+    Shrink,
+    Reorder,
+}
+
+/// Brings the number of occurrences of each variant in `vec` within the limits reported by
+/// [EnumVariants::variant_count_limits]. Surplus elements are removed at random and missing
+/// variants are generated and inserted at random positions. If an inserted element takes the
+/// list past `max_size`, elements which aren't required are removed at random to make room for it.
+pub(crate) fn fix_variant_counts<T: EnumVariants + SerializedSize, R: Rng>(
+    vec: &mut VariantVec<T>,
+    mutator: &mut Mutator<R>,
+    max_size: Option<usize>,
+) {
+    let mut counts = vec.variant_counts();
+
+    for (variant, count) in counts.iter_mut().enumerate() {
+        let max = match T::variant_count_limits(variant).1 {
+            Some(max) if *count > max => max,
+            _ => continue,
+        };
+
+        let mut positions: Vec<usize> = vec
+            .inner
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.variant_index() == variant)
+            .map(|(i, _)| i)
+            .collect();
+
+        let surplus = *count - max;
+        positions.partial_shuffle(&mut mutator.rng, surplus);
+
+        let mut removed: Vec<usize> = positions[..surplus].to_vec();
+        removed.sort_unstable_by(|a, b| b.cmp(a));
+        for position in removed {
+            vec.inner.remove(position);
+        }
+
+        *count = max;
+    }
+
+    for (variant, count) in counts.iter_mut().enumerate() {
+        let min = T::variant_count_limits(variant).0;
+        while *count < min {
+            let position = mutator.gen_range(0, vec.inner.len() + 1);
+            let element = T::new_fuzzed_variant(mutator, variant);
+            vec.inner.insert(position, element);
+
+            *count += 1;
+        }
+    }
+
+    if let Some(max_size) = max_size {
+        while vec.serialized_size() > max_size {
+            let removable: Vec<usize> = vec
+                .inner
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| {
+                    let variant = item.variant_index();
+                    counts[variant] > T::variant_count_limits(variant).0
+                })
+                .map(|(i, _)| i)
+                .collect();
+
+            let position = match removable.choose(&mut mutator.rng) {
+                Some(position) => *position,
+                None => break,
+            };
+
+            counts[vec.inner[position].variant_index()] -= 1;
+            vec.inner.remove(position);
         }
     }
 }
 
-impl<T> Mutatable for [T]
+impl<T> Mutatable for VariantVec<T>
 where
-    T: Mutatable + SerializedSize,
-    T::RangeType: Clone,
+    T: Mutatable + NewFuzzed + EnumVariants + SerializedSize + Clone,
+    <T as Mutatable>::RangeType: Clone,
 {
-    type RangeType = T::RangeType;
+    type RangeType = usize;
 
-    default fn mutate<R: Rng>(
+    fn mutate<R: Rng>(
         &mut self,
         mutator: &mut Mutator<R>,
         constraints: Option<&Constraints<Self::RangeType>>,
     ) {
-        let constraints = constraints.and_then(|c| {
-            if c.max_size.is_none() {
-                None
-            } else {
-                let mut new_constraints = Constraints::new();
-                new_constraints.base_object_size_accounted_for = c.base_object_size_accounted_for;
-                new_constraints.max_size = c.max_size;
+        const CHANCE_TO_RESIZE_OR_REORDER: f64 = 0.01;
+
+        let max_size = constraints.and_then(|c| c.max_size);
+
+        if mutator.gen_chance(CHANCE_TO_RESIZE_OR_REORDER) {
+            let mut counts = self.variant_counts();
+
+            match VariantVecMutation::new_fuzzed(mutator, None) {
+                VariantVecMutation::Grow => {
+                    mutator.record_operator(MutationOperator::GrowList);
+                    let mut remaining_size =
+                        max_size.map(|max_size| max_size.saturating_sub(self.serialized_size()));
+                    for _i in 0..mutator.gen_range(1, 9) {
+                        let constraints = remaining_size.map(|remaining_size| {
+                            let mut c = Constraints::new();
+                            c.max_size(remaining_size);
+                            c.base_object_size_accounted_for = true;
+
+                            c
+                        });
+
+                        let element = T::new_fuzzed(mutator, constraints.as_ref());
+                        let variant = element.variant_index();
+                        let at_limit = T::variant_count_limits(variant)
+                            .1
+                            .map(|max| counts[variant] >= max)
+                            .unwrap_or(false);
+
+                        if at_limit {
+                            continue;
+                        }
 
-                Some(new_constraints)
-            }
-        });
+                        if let Some(size) = remaining_size {
+                            let element_size = element.serialized_size();
+                            if element_size > size {
+                                break;
+                            }
 
-        // Check if we can even mutate this item
-        if let Some(max_size) = constraints.as_ref().and_then(|c| c.max_size) {
-            if T::min_nonzero_elements_size() < max_size || T::max_default_object_size() > max_size
-            {
-                return;
+                            remaining_size = Some(size - element_size);
+                        }
+
+                        let position = mutator.gen_range(0, self.inner.len() + 1);
+                        self.inner.insert(position, element);
+                        counts[variant] += 1;
+                    }
+                }
+                VariantVecMutation::Shrink => {
+                    if !self.inner.is_empty() {
+                        mutator.record_operator(MutationOperator::ShrinkList);
+                        for _i in 0..mutator.gen_range(1, 9) {
+                            if self.inner.is_empty() {
+                                break;
+                            }
+
+                            let position = mutator.gen_range(0, self.inner.len());
+                            let variant = self.inner[position].variant_index();
+                            if counts[variant] > T::variant_count_limits(variant).0 {
+                                self.inner.remove(position);
+                                counts[variant] -= 1;
+                            }
+                        }
+                    }
+                }
+                VariantVecMutation::Reorder => {
+                    mutator.record_operator(MutationOperator::ReorderList);
+                    self.inner.shuffle(&mut mutator.rng);
+                }
             }
+
+            return;
         }
 
-        for item in self.iter_mut() {
-            T::mutate(item, mutator, constraints.as_ref());
+        let mut remaining_size =
+            max_size.map(|max_size| max_size.saturating_sub(self.serialized_size()));
+        for i in 0..self.inner.len() {
+            let previous = self.inner[i].clone();
+            let previous_size = previous.serialized_size();
+            let constraints = remaining_size.map(|remaining_size| {
+                let mut c = Constraints::new();
+                c.max_size(remaining_size);
+                c.base_object_size_accounted_for = true;
+
+                c
+            });
+
+            T::mutate(&mut self.inner[i], mutator, constraints.as_ref());
+
+            let variant = self.inner[i].variant_index();
+            let size = self.inner[i].serialized_size();
+            let too_large = remaining_size
+                .map(|remaining_size| size > previous_size + remaining_size)
+                .unwrap_or(false);
+
+            // changing the variant of an element may violate the count limits
+            if too_large
+                || (variant != previous.variant_index() && !self.satisfies_variant_limits())
+            {
+                self.inner[i] = previous;
+            } else if let Some(remaining_size) = remaining_size.as_mut() {
+                *remaining_size = *remaining_size + previous_size - size;
+            }
 
             if mutator.should_early_bail_mutation() {
                 return;
@@ -349,6 +735,8 @@ where
     T: Mutatable + SerializedSize + Clone,
     T::RangeType: Clone,
 {
+    type RangeType = T::RangeType;
+
     fn mutate<R: Rng>(
         &mut self,
         mutator: &mut Mutator<R>,
@@ -373,7 +761,22 @@ where
             }
         }
 
-        for item in self.iter_mut() {
+        let window = mutator.gen_mutation_window(self.len());
+
+        // without a size budget the elements don't need to be measured or backed up
+        if constraints.is_none() {
+            for item in self[window].iter_mut() {
+                T::mutate(item, mutator, None);
+
+                if mutator.should_early_bail_mutation() {
+                    return;
+                }
+            }
+
+            return;
+        }
+
+        for item in self[window].iter_mut() {
             let parent_constraints = constraints.clone();
             if let Some(constraints) = constraints.as_mut() {
                 if let Some(max_size) = constraints.max_size.as_mut() {
@@ -431,6 +834,7 @@ where
         + std::fmt::Debug
         + Default
         + DangerousNumber<I>
+        + InterestingValue
         + std::fmt::Display
         + WrappingAdd
         + WrappingSub,
@@ -442,6 +846,19 @@ where
         mutator: &mut Mutator<R>,
         _constraints: Option<&Constraints<Self::RangeType>>,
     ) {
+        if let Some(samples) = mutator.capped_invalid_discriminants::<T, I>() {
+            let valid = matches!(*self, UnsafeEnum::Valid(_));
+            if valid && !mutator.gen_chance(mutator.invalid_enum_chance()) {
+                return;
+            }
+
+            if let Some(value) = samples.choose(&mut mutator.rng) {
+                *self = UnsafeEnum::Invalid(*value);
+            }
+
+            return;
+        }
+
         if let UnsafeEnum::Valid(ref value) = *self {
             *self = UnsafeEnum::Invalid(value.to_primitive());
         }
@@ -455,42 +872,560 @@ where
     }
 }
 
-impl Mutatable for AsciiString {
-    type RangeType = u8;
+#[derive(Copy, Clone, PartialEq, NewFuzzed)]
+enum StringResize {
+    InsertRun,
+    DeleteRange,
+    DuplicateSubstring,
+}
 
-    fn mutate<R: Rng>(
-        &mut self,
-        mutator: &mut Mutator<R>,
-        _constraints: Option<&Constraints<Self::RangeType>>,
-    ) {
-        trace!("performing mutation on an AsciiString");
+/// Mutates the characters of a string. Usually some characters are overwritten in place, but
+/// the string may also be resized by inserting a run of a repeated character, deleting a range
+/// of characters, or duplicating a substring elsewhere in it. The string's size in bytes (as
+/// measured by `char_size`) is kept within the `min_size` and `max_size` of `constraints`.
+fn mutate_string_chars<C, R, F>(
+    chars: &mut Vec<C>,
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<u8>>,
+    char_size: fn(&C) -> usize,
+    new_char: F,
+) where
+    C: Clone,
+    R: Rng,
+    F: Fn(&mut Mutator<R>) -> C,
+{
+    const CHANCE_TO_RESIZE_STRING: f64 = 0.25;
 
-        // TODO: Implement logic for resizing?
-        let num_mutations = mutator.gen_range(1, self.inner.len());
-        for idx in index::sample(&mut mutator.rng, self.inner.len(), num_mutations).iter() {
-            self.inner[idx] = AsciiChar::new_fuzzed(mutator, None);
+    if (chars.is_empty() || mutator.gen_chance(CHANCE_TO_RESIZE_STRING))
+        && resize_string_chars(chars, mutator, constraints, char_size, &new_char)
+    {
+        return;
+    }
+
+    if chars.is_empty() {
+        return;
+    }
+
+    // characters of another width only replace each other while the string stays within its
+    // bounds
+    let min_size = constraints.and_then(|c| c.min_size).unwrap_or(0);
+    let max_size = constraints.and_then(|c| c.max_size).unwrap_or(usize::MAX);
+    let mut size: usize = chars.iter().map(char_size).sum();
+    let num_mutations = mutator.gen_range(1, cmp::max(chars.len(), 2));
+    for idx in index::sample(&mut mutator.rng, chars.len(), num_mutations).iter() {
+        let c = new_char(mutator);
+        let resized = size - char_size(&chars[idx]) + char_size(&c);
+        if (resized <= max_size || resized <= size) && (resized >= min_size || resized >= size) {
+            size = resized;
+            chars[idx] = c;
         }
     }
 }
 
-impl Mutatable for Utf8String {
-    type RangeType = u8;
-
-    fn mutate<R: Rng>(
-        &mut self,
+/// Grows or shrinks a string as described by [mutate_string_chars]. Returns whether it was
+/// resized.
+fn resize_string_chars<C, R, F>(
+    chars: &mut Vec<C>,
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<u8>>,
+    char_size: fn(&C) -> usize,
+    new_char: &F,
+) -> bool
+where
+    C: Clone,
+    R: Rng,
+    F: Fn(&mut Mutator<R>) -> C,
+{
+    let size: usize = chars.iter().map(char_size).sum();
+    let room = constraints
+        .and_then(|c| c.max_size)
+        .map_or(usize::MAX, |max_size| max_size.saturating_sub(size));
+    let removable = size.saturating_sub(constraints.and_then(|c| c.min_size).unwrap_or(0));
+
+    let resize = if chars.is_empty() {
+        StringResize::InsertRun
+    } else {
+        StringResize::new_fuzzed(mutator, None)
+    };
+    let operator = match resize {
+        StringResize::InsertRun => MutationOperator::GrowList,
+        StringResize::DeleteRange => MutationOperator::ShrinkList,
+        StringResize::DuplicateSubstring => MutationOperator::DuplicateBlock,
+    };
+    if !mutator.operator_enabled(operator) {
+        return false;
+    }
+
+    match resize {
+        StringResize::InsertRun => {
+            let c = new_char(mutator);
+            let max_len = cmp::min(room / char_size(&c), cmp::max(chars.len(), 8));
+            if max_len == 0 {
+                return false;
+            }
+
+            let len = gen_block_len(mutator, max_len);
+            let at = mutator.gen_range(0, chars.len() + 1);
+            chars.splice(at..at, std::iter::repeat_n(c, len));
+        }
+        StringResize::DeleteRange => {
+            let len = gen_block_len(mutator, chars.len());
+            let start = mutator.gen_range(0, chars.len() - len + 1);
+            let mut end = start + len;
+            while end > start && chars[start..end].iter().map(char_size).sum::<usize>() > removable
+            {
+                end -= 1;
+            }
+            if end == start {
+                return false;
+            }
+
+            chars.drain(start..end);
+        }
+        StringResize::DuplicateSubstring => {
+            let len = gen_block_len(mutator, chars.len());
+            let start = mutator.gen_range(0, chars.len() - len + 1);
+            let mut substring = chars[start..start + len].to_vec();
+            while substring.iter().map(char_size).sum::<usize>() > room {
+                substring.pop();
+            }
+            if substring.is_empty() {
+                return false;
+            }
+
+            let at = mutator.gen_range(0, chars.len() + 1);
+            chars.splice(at..at, substring);
+        }
+    }
+
+    mutator.record_operator(operator);
+    true
+}
+
+/// Mutates the code units of a UTF-16 string like the characters of other strings, or inserts
+/// one of the sequences of [gen_utf16_sequence][crate::new_fuzzed::gen_utf16_sequence] (such as
+/// a surrogate pair) anywhere in it
+fn mutate_utf16_units<R: Rng>(
+    units: &mut Vec<u16>,
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<u8>>,
+) {
+    const CHANCE_TO_INSERT_SEQUENCE: f64 = 0.10;
+
+    if mutator.gen_chance(CHANCE_TO_INSERT_SEQUENCE)
+        && mutator.operator_enabled(MutationOperator::GrowList)
+    {
+        let sequence = crate::new_fuzzed::gen_utf16_sequence(mutator);
+        let fits = constraints
+            .and_then(|c| c.max_size)
+            .is_none_or(|max_size| (units.len() + sequence.len()) * 2 <= max_size);
+        if fits {
+            mutator.record_operator(MutationOperator::GrowList);
+            let at = mutator.gen_range(0, units.len() + 1);
+            units.splice(at..at, sequence);
+            return;
+        }
+    }
+
+    mutate_string_chars(
+        units,
+        mutator,
+        constraints,
+        |_| 2,
+        |mutator| crate::new_fuzzed::gen_utf16_sequence(mutator)[0],
+    );
+}
+
+impl Mutatable for Utf16String {
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
         mutator: &mut Mutator<R>,
-        _constraints: Option<&Constraints<Self::RangeType>>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        trace!("performing mutation on a Utf16String");
+
+        mutate_utf16_units(&mut self.inner, mutator, constraints);
+    }
+}
+
+/// `min_size` and `max_size` include the null terminator
+impl Mutatable for WideCString {
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        trace!("performing mutation on a WideCString");
+
+        let constraints = constraints.map(|c| {
+            let mut c = c.clone();
+            c.max_size = c.max_size.map(|size| size.saturating_sub(2));
+            c.min_size = c.min_size.map(|size| size.saturating_sub(2));
+            c
+        });
+        mutate_utf16_units(&mut self.inner, mutator, constraints.as_ref());
+    }
+}
+
+impl Mutatable for AsciiString {
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        trace!("performing mutation on an AsciiString");
+
+        if let Some(token) = mutator.gen_dictionary_token(|token| token.is_ascii()) {
+            let chars = token.into_iter().map(|b| AsciiChar(b as char));
+            crate::dictionary::splice_token(mutator, &mut self.inner, chars);
+            return;
+        }
+
+        mutate_string_chars(
+            &mut self.inner,
+            mutator,
+            constraints,
+            |_| 1,
+            |mutator| AsciiChar::new_fuzzed(mutator, None),
+        );
+    }
+}
+
+impl Mutatable for Utf8String {
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
     ) {
         trace!("performing mutation on a Utf8String");
 
-        // TODO: Implement logic for resizing?
-        let num_mutations = mutator.gen_range(1, self.inner.len());
+        let token = mutator.gen_dictionary_token(|token| std::str::from_utf8(token).is_ok());
+        if let Some(token) = token {
+            let chars: Vec<Utf8Char> = String::from_utf8(token)
+                .unwrap()
+                .chars()
+                .map(Utf8Char)
+                .collect();
+            crate::dictionary::splice_token(mutator, &mut self.inner, chars.into_iter());
+            return;
+        }
+
+        mutate_string_chars(
+            &mut self.inner,
+            mutator,
+            constraints,
+            |c| c.0.len_utf8(),
+            |mutator| Utf8Char::new_fuzzed(mutator, None),
+        );
+    }
+}
+
+impl Mutatable for Blob {
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        const CHANCE_TO_REGENERATE_CONTENT: f64 = 0.10;
+
+        trace!("performing mutation on a Blob");
+
+        if self.inner.is_empty() {
+            return;
+        }
+
+        // regenerating keeps the current length so that size constraints are still met
+        if mutator.gen_chance(CHANCE_TO_REGENERATE_CONTENT) {
+            let class = mutator.gen_blob_content();
+            self.inner = crate::new_fuzzed::gen_blob_content(mutator, class, self.inner.len());
+
+            return;
+        }
+
+        let num_mutations = mutator.gen_range(1, cmp::min(self.inner.len(), 16) + 1);
         for idx in index::sample(&mut mutator.rng, self.inner.len(), num_mutations).iter() {
-            self.inner[idx] = Utf8Char::new_fuzzed(mutator, None);
+            mutator.mutate(&mut self.inner[idx]);
+        }
+    }
+}
+
+/// Replaces `value` with a special or random float, or nudges it towards a nearby value
+fn mutate_float<T: num_traits::Float, R: Rng>(mutator: &mut Mutator<R>, value: &mut T) {
+    let two = T::one() + T::one();
+
+    *value = match mutator.gen_range(0u8, 5u8) {
+        0 => -*value,
+        1 => *value * two,
+        2 => *value / two,
+        // the smallest relative change which still alters the value
+        3 => *value * (T::one() + T::epsilon()),
+        _ => crate::new_fuzzed::gen_float(mutator),
+    };
+}
+
+macro_rules! impl_mutatable_float {
+    ( $($name:ident),* ) => {
+        $(
+            /// Replaces the value with a [special float][crate::new_fuzzed::gen_special_float],
+            /// mutates its bit pattern like an integer of the same width (which reaches other
+            /// NaN payloads, subnormals, and flipped signs and exponents), or changes it
+            /// arithmetically as in a [FloatVec]
+            impl Mutatable for $name {
+                type RangeType = $name;
+
+                fn mutate<R: Rng>(
+                    &mut self,
+                    mutator: &mut Mutator<R>,
+                    _constraints: Option<&Constraints<Self::RangeType>>,
+                ) {
+                    match mutator.gen_range(0u8, 4u8) {
+                        0 if mutator.operator_enabled(MutationOperator::DangerousNumber) => {
+                            mutator.record_operator(MutationOperator::DangerousNumber);
+                            *self = crate::new_fuzzed::gen_special_float(mutator);
+                        }
+                        0 | 1 => {
+                            let mut bits = self.to_bits();
+                            mutator.mutate(&mut bits);
+                            *self = $name::from_bits(bits);
+                        }
+                        _ => mutate_float(mutator, self),
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_mutatable_float!(f32, f64);
+
+/// Mutates between 1 and 16 of `values`
+fn mutate_floats<T: num_traits::Float, R: Rng>(mutator: &mut Mutator<R>, values: &mut [T]) {
+    if values.is_empty() {
+        return;
+    }
+
+    let num_mutations = mutator.gen_range(1, cmp::min(values.len(), 16) + 1);
+    for idx in index::sample(&mut mutator.rng, values.len(), num_mutations).iter() {
+        mutate_float(mutator, &mut values[idx]);
+    }
+}
+
+/// The length is kept so that size constraints are still met
+impl<T: num_traits::Float> Mutatable for FloatVec<T> {
+    type RangeType = usize;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        trace!("performing mutation on a FloatVec");
+
+        mutate_floats(mutator, &mut self.inner);
+    }
+}
+
+/// The number of elements is kept so that size constraints are still met, but the matrix may
+/// be transposed
+impl<T: num_traits::Float> Mutatable for Matrix<T> {
+    type RangeType = usize;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        const CHANCE_TO_TRANSPOSE: f64 = 0.05;
+
+        trace!("performing mutation on a Matrix");
+
+        if mutator.gen_chance(CHANCE_TO_TRANSPOSE) {
+            let mut transposed = Vec::with_capacity(self.data.len());
+            for col in 0..self.cols {
+                for row in 0..self.rows {
+                    transposed.push(self.data[row * self.cols + col]);
+                }
+            }
+
+            self.data = transposed;
+            std::mem::swap(&mut self.rows, &mut self.cols);
+
+            return;
+        }
+
+        mutate_floats(mutator, &mut self.data);
+    }
+}
+
+impl<T: NewFuzzed> Mutatable for Lazy<T> {
+    type RangeType = T::RangeType;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        trace!("performing mutation on a Lazy");
+
+        // the content can only be changed by generating it from a different seed
+        self.seed = mutator.rng.gen();
+    }
+}
+
+/// Ways the text of an [AsciiNumber] is changed other than mutating its value
+#[derive(Copy, Clone, NewFuzzed)]
+enum AsciiNumberMutation {
+    Boundary,
+    Negate,
+    LeadingZeros,
+    ExcessiveDigits,
+}
+
+/// Replaces `text` with a boundary of `[min, max]`, or a value just outside of it
+fn set_ascii_boundary<R: Rng>(mutator: &mut Mutator<R>, text: &mut String, min: i128, max: i128) {
+    let boundaries = [min, max, 0, min - 1, max + 1, min / 2, max / 2 + 1];
+    *text = boundaries.choose(&mut mutator.rng).unwrap().to_string();
+}
+
+/// Adds a `-` in front of `text` or removes it
+fn negate_ascii_number(text: &mut String) {
+    let digits = text.trim_start_matches(['-', '+']);
+    *text = if text.starts_with('-') {
+        digits.to_string()
+    } else {
+        format!("-{}", digits)
+    };
+}
+
+/// Inserts zeros between the sign and the first digit of `text`
+fn add_ascii_leading_zeros<R: Rng>(mutator: &mut Mutator<R>, text: &mut String, max_zeros: usize) {
+    let sign_len = text.len() - text.trim_start_matches(['-', '+']).len();
+    let zeros = mutator.gen_range(1, max_zeros + 1);
+    text.insert_str(sign_len, &"0".repeat(zeros));
+}
+
+/// Replaces `text` with a number of between 20 digits (more than any 64-bit integer has) and
+/// `max_digits` digits
+fn set_excessive_ascii_digits<R: Rng>(
+    mutator: &mut Mutator<R>,
+    text: &mut String,
+    max_digits: usize,
+) {
+    const MIN_EXCESSIVE_DIGITS: usize = 20;
+
+    let max_digits = cmp::max(max_digits, 1);
+    let num_digits = mutator.gen_range(cmp::min(MIN_EXCESSIVE_DIGITS, max_digits), max_digits + 1);
+    *text = if mutator.gen_chance(0.5) {
+        "9".repeat(num_digits)
+    } else {
+        let mut digits = String::with_capacity(num_digits);
+        digits.push(char::from(b'1' + mutator.gen_range(0, 9u8)));
+        for _i in 1..num_digits {
+            digits.push(char::from(b'0' + mutator.gen_range(0, 10u8)));
         }
+        digits
+    };
+}
+
+macro_rules! impl_ascii_number_mutatable {
+    ( $($name:ident),* ) => {
+        $(
+            /// Half of all mutations change the value; the rest change the text in ways the
+            /// value can't express. A `max_size` constraint limits the number of digits.
+            impl Mutatable for AsciiNumber<$name> {
+                type RangeType = $name;
+
+                fn mutate<R: Rng>(
+                    &mut self,
+                    mutator: &mut Mutator<R>,
+                    constraints: Option<&Constraints<Self::RangeType>>,
+                ) {
+                    trace!("performing mutation on an AsciiNumber");
+
+                    if mutator.gen_chance(0.50) {
+                        let mut value = match self.value() {
+                            Some(value) => value,
+                            None => $name::new_fuzzed(mutator, constraints),
+                        };
+                        mutator.mutate(&mut value);
+                        self.text = value.to_string();
+
+                        return;
+                    }
+
+                    mutator.record_operator(MutationOperator::AsciiNumber);
+
+                    let max_size = constraints
+                        .and_then(|c| c.max_size)
+                        .unwrap_or(AsciiNumber::<$name>::MAX_DIGITS);
+                    match AsciiNumberMutation::new_fuzzed(mutator, None) {
+                        AsciiNumberMutation::Boundary => set_ascii_boundary(
+                            mutator,
+                            &mut self.text,
+                            $name::MIN as i128,
+                            $name::MAX as i128,
+                        ),
+                        AsciiNumberMutation::Negate => negate_ascii_number(&mut self.text),
+                        AsciiNumberMutation::LeadingZeros => {
+                            let room = max_size.saturating_sub(self.text.len());
+                            if room > 0 {
+                                add_ascii_leading_zeros(
+                                    mutator,
+                                    &mut self.text,
+                                    cmp::min(room, AsciiNumber::<$name>::MAX_LEADING_ZEROS),
+                                );
+                            }
+                        }
+                        AsciiNumberMutation::ExcessiveDigits => set_excessive_ascii_digits(
+                            mutator,
+                            &mut self.text,
+                            cmp::min(max_size, AsciiNumber::<$name>::MAX_DIGITS),
+                        ),
+                    }
+                }
+            }
+        )*
     }
 }
 
+impl_ascii_number_mutatable!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+macro_rules! impl_semantic_mutatable {
+    ( $($name:ident($inner:ident)),* ) => {
+        $(
+            impl Mutatable for $name {
+                type RangeType = $inner;
+
+                fn mutate<R: Rng>(
+                    &mut self,
+                    mutator: &mut Mutator<R>,
+                    constraints: Option<&Constraints<Self::RangeType>>,
+                ) {
+                    // picking a fresh value may select one of the attack values, which plain
+                    // numeric mutations are unlikely to hit
+                    if mutator.gen_chance(0.50) {
+                        *self = $name::new_fuzzed(mutator, constraints);
+                    } else {
+                        mutator.mutate(&mut self.0);
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_semantic_mutatable!(Port(u16), VlanTag(u16), Ttl(u8), WindowSize(u16));
+
 macro_rules! impl_mutatable {
     ( $($name:ident),* ) => {
         $(
@@ -506,7 +1441,44 @@ macro_rules! impl_mutatable {
     }
 }
 
-impl_mutatable!(u64, u32, u16, u8);
+impl_mutatable!(usize, u128, u64, u32, u16);
+
+impl Mutatable for u8 {
+    type RangeType = u8;
+
+    #[inline(always)]
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        mutator.mutate(self);
+    }
+
+    fn mutate_vec<R: Rng>(
+        vec: &mut Vec<u8>,
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<usize>>,
+    ) {
+        // tokens are spliced in at any offset, which would split the records of a grouped vec
+        let max_size = constraints.and_then(|c| c.max_size);
+        let token = if element_group(constraints) > 1 {
+            None
+        } else {
+            mutator.gen_dictionary_token(|token| {
+                max_size.is_none_or(|max| vec.len() + token.len() <= max)
+            })
+        };
+
+        match token {
+            Some(token) => crate::dictionary::splice_token(mutator, vec, token.into_iter()),
+            None if mutator.gen_chance(mutator.byte_block_chance()) => {
+                mutate_byte_blocks(vec, mutator, constraints)
+            }
+            None => mutate_vec(vec, mutator, constraints),
+        }
+    }
+}
 
 impl Mutatable for i8 {
     type RangeType = i8;
@@ -568,18 +1540,33 @@ impl Mutatable for i64 {
     }
 }
 
-impl<T> Mutatable for [T; 0]
-where
-    T: Mutatable,
-{
-    type RangeType = u8;
+impl Mutatable for i128 {
+    type RangeType = i128;
 
+    #[inline(always)]
     fn mutate<R: Rng>(
         &mut self,
-        _mutator: &mut Mutator<R>,
+        mutator: &mut Mutator<R>,
         _constraints: Option<&Constraints<Self::RangeType>>,
     ) {
-        // nop
+        let mut val = *self as u128;
+        mutator.mutate(&mut val);
+        *self = val as i128;
+    }
+}
+
+impl Mutatable for isize {
+    type RangeType = isize;
+
+    #[inline(always)]
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        let mut val = *self as usize;
+        mutator.mutate(&mut val);
+        *self = val as isize;
     }
 }
 
@@ -618,18 +1605,18 @@ where
         mutator: &mut Mutator<R>,
         constraints: Option<&Constraints<Self::RangeType>>,
     ) {
-        const CHANCE_TO_FLIP_OPTION_STATE: f64 = 0.01;
+        let toggle_chance = mutator.option_toggle_chance();
         match self {
             Some(inner) => {
                 // small chance to make this None
-                if mutator.gen_chance(CHANCE_TO_FLIP_OPTION_STATE) {
+                if mutator.gen_chance(toggle_chance) {
                     *self = None;
                 } else {
                     inner.mutate(mutator, constraints);
                 }
             }
             None => {
-                if mutator.gen_chance(CHANCE_TO_FLIP_OPTION_STATE) {
+                if mutator.gen_chance(toggle_chance) {
                     let new_item = T::new_fuzzed(mutator, constraints);
 
                     *self = Some(new_item);
@@ -654,28 +1641,212 @@ where
     }
 }
 
-macro_rules! impl_mutatable_array {
-    ( $($size:expr),* ) => {
+impl<T, const N: usize> Mutatable for [T; N]
+where
+    T: Mutatable + SerializedSize + Clone,
+    T::RangeType: Clone,
+{
+    type RangeType = T::RangeType;
+
+    #[inline(always)]
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        if N == 0 {
+            return;
+        }
+
+        // Treat this as a slice
+        self[..].mutate(mutator, constraints);
+    }
+}
+
+/// Steps by which timestamps are shifted, from a millisecond up to a year
+const TIMESTAMP_STEPS: [i64; 7] = [
+    1,
+    1000,
+    60 * 1000,
+    60 * 60 * 1000,
+    24 * 60 * 60 * 1000,
+    29 * 24 * 60 * 60 * 1000,
+    365 * 24 * 60 * 60 * 1000,
+];
+
+macro_rules! impl_timestamp_mutatable {
+    ( $($name:ty),* ) => {
         $(
-            impl<T> Mutatable for [T; $size]
-            where
-                T: Mutatable + SerializedSize,
-                T::RangeType: Clone,
-            {
-                type RangeType = T::RangeType;
+            /// Shifts the time by a calendar-sized step (a millisecond up to a year) in either
+            /// direction, or occasionally replaces it entirely
+            impl Mutatable for $name {
+                type RangeType = u8;
+
+                fn mutate<R: Rng>(
+                    &mut self,
+                    mutator: &mut Mutator<R>,
+                    constraints: Option<&Constraints<Self::RangeType>>,
+                ) {
+                    trace!("performing mutation on a {}", stringify!($name));
+
+                    if mutator.gen_chance(0.10) {
+                        mutator.record_operator(MutationOperator::Regenerate);
+                        *self = Self::new_fuzzed(mutator, constraints);
+                        return;
+                    }
 
-                #[inline(always)]
-                fn mutate<R: Rng>(&mut self, mutator: &mut Mutator<R>, constraints: Option<&Constraints<Self::RangeType>>) {
-                    // Treat this as a slice
-                    self[..].mutate(mutator, constraints);
+                    let step = *TIMESTAMP_STEPS.choose(&mut mutator.rng).unwrap();
+                    let millis = if mutator.gen_chance(0.5) {
+                        self.unix_millis().saturating_add(step)
+                    } else {
+                        self.unix_millis().saturating_sub(step)
+                    };
+
+                    *self = Timestamp::from_unix_millis(millis);
                 }
             }
         )*
     }
 }
 
-impl_mutatable_array!(
-    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
-    27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50,
-    51, 52, 53, 54, 55, 56, 57, 58, 59, 60
-);
+impl_timestamp_mutatable!(std::time::SystemTime);
+
+#[cfg(feature = "chrono_support")]
+impl_timestamp_mutatable!(chrono::DateTime<chrono::Utc>);
+
+#[cfg(feature = "time_support")]
+impl_timestamp_mutatable!(time::OffsetDateTime);
+
+#[derive(Copy, Clone, PartialEq, NewFuzzed)]
+enum CStringAnomaly {
+    ToggleTerminator,
+    EmbedNul,
+    Overflow,
+}
+
+/// Drops or restores the terminator of `string`, embeds a NUL in it, or extends it past the
+/// `max_size` of `constraints` (or to several times its size, without one)
+fn corrupt_c_string<R: Rng>(
+    string: &mut CString,
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<u8>>,
+) {
+    // overflows by a byte or two catch off-by-one errors, longer ones reach the return address
+    const OVERFLOW_LENGTHS: [usize; 6] = [1, 2, 8, 64, 256, 4096];
+
+    match CStringAnomaly::new_fuzzed(mutator, None) {
+        CStringAnomaly::ToggleTerminator => string.terminated = !string.terminated,
+        CStringAnomaly::EmbedNul => {
+            let at = mutator.gen_range(0, string.inner.len() + 1);
+            if at < string.inner.len() && mutator.gen_chance(0.5) {
+                string.inner[at] = 0;
+            } else {
+                string.inner.insert(at, 0);
+            }
+        }
+        CStringAnomaly::Overflow => {
+            let buffer_size = constraints
+                .and_then(|c| c.max_size)
+                .unwrap_or_else(|| string.serialized_size());
+            let len = buffer_size + OVERFLOW_LENGTHS.choose(&mut mutator.rng).unwrap();
+            let byte = crate::new_fuzzed::gen_c_string_byte(mutator);
+            if string.inner.len() < len {
+                string.inner.resize(len, byte);
+            }
+        }
+    }
+
+    mutator.record_operator(MutationOperator::CorruptTerminator);
+}
+
+/// `min_size` and `max_size` include the terminator, unless it was dropped
+impl Mutatable for CString {
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        trace!("performing mutation on a CString");
+
+        if mutator.gen_chance(mutator.c_string_anomaly_chance())
+            && mutator.operator_enabled(MutationOperator::CorruptTerminator)
+        {
+            corrupt_c_string(self, mutator, constraints);
+            return;
+        }
+
+        let terminator = self.terminated as usize;
+        let constraints = constraints.map(|c| {
+            let mut c = c.clone();
+            c.max_size = c.max_size.map(|size| size.saturating_sub(terminator));
+            c.min_size = c.min_size.map(|size| size.saturating_sub(terminator));
+            c
+        });
+        mutate_string_chars(
+            &mut self.inner,
+            mutator,
+            constraints.as_ref(),
+            |_| 1,
+            crate::new_fuzzed::gen_c_string_byte,
+        );
+    }
+}
+
+/// Mutates a single subfield at a time: one of its bits is flipped, it's stepped up or down
+/// within its range, or it's generated anew. See [ControlWord].
+impl<L: ControlWordLayout> Mutatable for ControlWord<L> {
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        if L::SUBFIELDS.is_empty() {
+            return;
+        }
+
+        let index = mutator.gen_range(0, L::SUBFIELDS.len());
+        let subfield = &L::SUBFIELDS[index];
+        let value = self.get_at(index);
+        trace!("mutating control word subfield {}", subfield.name());
+
+        let value = match mutator.gen_range(0u8, 3u8) {
+            0 if subfield.bits() > 0 => {
+                mutator.record_operator(MutationOperator::BitFlip);
+                value ^ (1 << mutator.gen_range(0, subfield.bits().min(64)))
+            }
+            1 => {
+                // wraps around within the range, which may be narrower than the bits
+                mutator.record_operator(MutationOperator::Arithmetic);
+                let (min, max) = (subfield.min(), subfield.max().min(subfield.mask()));
+                let up: bool = mutator.rng.gen();
+                if value < min || value > max {
+                    if up {
+                        min
+                    } else {
+                        max
+                    }
+                } else if up {
+                    if value == max {
+                        min
+                    } else {
+                        value + 1
+                    }
+                } else if value == min {
+                    max
+                } else {
+                    value - 1
+                }
+            }
+            _ => {
+                mutator.record_operator(MutationOperator::Regenerate);
+                crate::new_fuzzed::gen_subfield(mutator, subfield)
+            }
+        };
+
+        self.set_at(index, value);
+    }
+}
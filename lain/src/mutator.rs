@@ -1,15 +1,29 @@
-use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::rngs::{OsRng, SmallRng, StdRng};
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
 
+use crate::attribution::VariantCounts;
+use crate::crossover::{find_span, splice};
+use crate::dictionary::Dictionary;
+use crate::differential::serialize_with_layout;
+use crate::interesting::{InterestingValue, UserInterestingValues};
+use crate::operators::MutationOperator;
 use crate::rand::distributions::uniform::{SampleBorrow, SampleUniform};
+use crate::swarm::Swarm;
 use crate::traits::*;
 use crate::types::*;
 use num::{Bounded, NumCast};
 use num_traits::{WrappingAdd, WrappingSub};
 
 use crate::lain_derive::NewFuzzed;
+use byteorder::ByteOrder;
 
-use std::ops::{Add, BitXor, Div, Mul, Sub};
+use std::any::Any;
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, BitXor, Div, Mul, Range, RangeInclusive, Sub};
 
 #[cfg(feature = "serde_support")]
 use serde::{Deserialize, Serialize};
@@ -17,7 +31,49 @@ use serde::{Deserialize, Serialize};
 // set these to 0 to disable
 pub const CHANCE_TO_REPEAT_ARRAY_VALUE: f64 = 0.05;
 pub const CHANCE_TO_PICK_INVALID_ENUM: f64 = 0.10;
+pub const CHANCE_TO_PICK_ENUM_GAP: f64 = 0.50;
 pub const CHANCE_TO_IGNORE_MIN_MAX: f64 = 0.05;
+pub const CHANCE_TO_MUTATE_OFFSETS: f64 = 0.05;
+pub const CHANCE_TO_MUTATE_WINDOW: f64 = 0.25;
+pub const CHANCE_TO_CROSS_VALUE: f64 = 0.50;
+pub const CHANCE_TO_SPLICE_LIST: f64 = 0.50;
+
+pub const DEFAULT_VALIDATION_ATTEMPTS: usize = 10;
+pub const DEFAULT_INVALID_VALUE_CHANCE: f64 = 0.10;
+pub const DEFAULT_SEQUENCE_ANOMALY_CHANCE: f64 = 0.05;
+pub const DEFAULT_TIMESTAMP_EXTREME_CHANCE: f64 = 0.05;
+pub const DEFAULT_OPTION_SOME_CHANCE: f64 = 0.75;
+pub const DEFAULT_OPTION_TOGGLE_CHANCE: f64 = 0.01;
+pub const DEFAULT_DICTIONARY_CHANCE: f64 = 0.05;
+pub const DEFAULT_CROSSOVER_CHANCE: f64 = 0.25;
+pub const DEFAULT_VARIANT_SWITCH_CHANCE: f64 = 0.10;
+pub const DEFAULT_INTERESTING_VALUE_CHANCE: f64 = 0.10;
+pub const DEFAULT_MEMOIZED_REGENERATION_CHANCE: f64 = 0.01;
+pub const DEFAULT_SHARED_MISMATCH_CHANCE: f64 = 0.05;
+pub const DEFAULT_LENGTH_CORRUPTION_CHANCE: f64 = 0.05;
+pub const DEFAULT_BAD_CHECKSUM_CHANCE: f64 = 0.05;
+pub const DEFAULT_BYTE_BLOCK_CHANCE: f64 = 0.10;
+pub const DEFAULT_ZERO_FILL_CHANCE: f64 = 0.0;
+pub const DEFAULT_C_STRING_ANOMALY_CHANCE: f64 = 0.05;
+pub const DEFAULT_RESIZE_BIAS: f64 = 1.0;
+
+/// Deltas by which `#[lain(offset)]` fields are shifted together, in either direction. These
+/// mimic common alignments, page sizes, and header sizes.
+const OFFSET_DELTAS: [u64; 9] = [1, 2, 4, 8, 0x10, 0x40, 0x100, 0x1000, 0x10000];
+
+/// Slices shorter than this are always mutated in full by [Mutator::gen_mutation_window]
+pub const MIN_WINDOWED_LEN: usize = 0x10;
+
+/// Most operators [Mutator::take_applied_operators] holds on to between calls. Operators
+/// applied beyond this are still counted in [Mutator::operator_counts].
+const MAX_APPLIED_OPERATORS: usize = 0x100;
+
+/// Largest gap introduced when a sequence number is deliberately skipped
+const MAX_SEQUENCE_SKIP: u64 = 0x10;
+
+/// How far into the past or future an extreme timestamp may be
+const EXTREME_TIMESTAMP_OFFSET: std::time::Duration =
+    std::time::Duration::from_secs(10 * 365 * 24 * 60 * 60);
 
 #[repr(u8)]
 #[derive(Debug, Copy, Clone, NewFuzzed)]
@@ -27,6 +83,35 @@ enum MutatorOperation {
     Flip,
 
     Arithmetic,
+
+    ProportionalDelta,
+}
+
+impl MutatorOperation {
+    const ALL: [MutatorOperation; 4] = [
+        MutatorOperation::BitFlip,
+        MutatorOperation::Flip,
+        MutatorOperation::Arithmetic,
+        MutatorOperation::ProportionalDelta,
+    ];
+
+    fn operator(&self) -> MutationOperator {
+        match self {
+            MutatorOperation::BitFlip => MutationOperator::BitFlip,
+            MutatorOperation::Flip => MutationOperator::Flip,
+            MutatorOperation::Arithmetic => MutationOperator::Arithmetic,
+            MutatorOperation::ProportionalDelta => MutationOperator::ProportionalDelta,
+        }
+    }
+}
+
+/// How [Mutator::proportional_delta] scales a number
+#[derive(Debug, Copy, Clone, NewFuzzed)]
+enum ProportionalDelta {
+    OnePercent,
+    TenPercent,
+    Double,
+    Halve,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -35,6 +120,43 @@ struct MutatorFlags {
     all_chances_succeed: bool,
 }
 
+/// Picks up to `max` distinct values of `I` which aren't in `valid`
+fn sample_invalid_discriminants<I, R>(
+    valid: &[I],
+    max: usize,
+    sampler: &mut Mutator<R>,
+) -> Vec<i128>
+where
+    I: NumCast + Bounded + Copy,
+    R: Rng,
+{
+    let valid: Vec<i128> = valid.iter().filter_map(|v| num::cast(*v)).collect();
+    let min: i128 = num::cast(I::min_value()).unwrap_or(i128::MIN);
+    let max_value: i128 = num::cast(I::max_value()).unwrap_or(i128::MAX);
+
+    let mut samples = vec![];
+    for _i in 0..max.saturating_mul(4) {
+        if samples.len() == max {
+            break;
+        }
+
+        let candidate = if sampler.gen_chance(CHANCE_TO_PICK_ENUM_GAP) {
+            sampler
+                .gen_invalid_discriminant(&valid)
+                .unwrap_or_else(|| sampler.rng.gen_range(min..=max_value))
+        } else {
+            sampler.rng.gen_range(min..=max_value)
+        };
+
+        let in_range = (min..=max_value).contains(&candidate);
+        if in_range && !valid.contains(&candidate) && !samples.contains(&candidate) {
+            samples.push(candidate);
+        }
+    }
+
+    samples
+}
+
 /// Represents the state of the current corpus item being fuzzed.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
@@ -49,20 +171,249 @@ impl CorpusFuzzingState {
     }
 }
 
+/// The invalid discriminants each `UnsafeEnum` type is limited to. See
+/// [Mutator::set_invalid_discriminant_cap].
+#[derive(Debug)]
+struct InvalidDiscriminantCap {
+    max: usize,
+    seed: u64,
+    /// Discriminants sampled so far, keyed by the name of the enum type
+    samples: HashMap<&'static str, Vec<i128>>,
+}
+
+/// Named [IdPool]s of arbitrary ID types
+#[derive(Default)]
+struct IdPoolRegistry {
+    pools: HashMap<String, Box<dyn Any + Send>>,
+}
+
+impl std::fmt::Debug for IdPoolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_set().entries(self.pools.keys()).finish()
+    }
+}
+
+/// Values generated for `#[lain(memoize)]` fields, keyed by the name of the type containing the
+/// field and the field's name
+#[derive(Default)]
+struct MemoizedValues {
+    values: HashMap<(&'static str, &'static str), Box<dyn Any + Send>>,
+}
+
+impl std::fmt::Debug for MemoizedValues {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_set().entries(self.values.keys()).finish()
+    }
+}
+
+/// Values of `#[lain(shared = "name")]` fields, keyed by the shared name
+#[derive(Default)]
+struct SharedValues {
+    values: HashMap<String, Box<dyn Any + Send>>,
+}
+
+impl std::fmt::Debug for SharedValues {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_set().entries(self.values.keys()).finish()
+    }
+}
+
+/// Values pinned with [Mutator::pin_field], in the order they were pinned
+#[derive(Default)]
+struct PinnedFields {
+    values: Vec<(String, Box<dyn Any + Send>)>,
+}
+
+impl std::fmt::Debug for PinnedFields {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.values.iter().map(|(path, _)| path))
+            .finish()
+    }
+}
+
+/// Where the randomness of the driver's fuzzer threads comes from. See
+/// [FuzzerDriver::set_entropy_source][crate::driver::FuzzerDriver::set_entropy_source].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntropySource {
+    /// Every iteration's RNG is seeded from the driver's root seed, so any iteration can be
+    /// replayed. Anyone who learns the seed can predict every input.
+    #[default]
+    Seeded,
+    /// Every iteration's RNG is a CSPRNG seeded from the operating system. Inputs can't be
+    /// predicted, but can't be replayed from the seed either, so findings are reported without
+    /// one (see [Finding::reproducible][crate::report::Finding::reproducible]).
+    Os,
+}
+
+/// How the havoc stage of a [MutationPipeline][crate::pipeline::MutationPipeline] mutates the
+/// serialized input. See [Mutator::set_mode].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MutatorMode {
+    /// Random byte-level mutations
+    #[default]
+    Havoc,
+    /// Like AFL's deterministic stage: every bit of the serialized input is flipped in turn,
+    /// then every byte, one flip per iteration and each on the unmodified input. The structured
+    /// input isn't mutated until every flip was tried, after which mutation falls back to
+    /// [MutatorMode::Havoc].
+    Deterministic,
+}
+
+/// How far the deterministic pass over the current input has come. See
+/// [Mutator::deterministic_progress].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct DeterministicProgress {
+    step: usize,
+    /// Number of flips in the pass, known once the first one was applied
+    total: Option<usize>,
+}
+
+impl DeterministicProgress {
+    /// Number of flips applied so far
+    pub fn steps_done(&self) -> usize {
+        self.step
+    }
+
+    /// Number of flips in the whole pass: one per bit and one per byte of the serialized input.
+    /// `None` until the pass has started.
+    pub fn total_steps(&self) -> Option<usize> {
+        self.total
+    }
+
+    /// Whether every flip of the pass was applied
+    pub fn is_complete(&self) -> bool {
+        self.total.is_some_and(|total| self.step >= total)
+    }
+}
+
 /// Object which provides helper routines for mutating data structures and RNG management.
 #[derive(Debug)]
 pub struct Mutator<R: Rng> {
     pub rng: R,
     flags: MutatorFlags,
     corpus_state: CorpusFuzzingState,
+    blob_content_weights: BlobContentWeights,
+    validation_attempts: usize,
+    validation_failures: usize,
+    invalid_value_chance: f64,
+    invalid_enum_chance: f64,
+    invalid_discriminant_cap: Option<InvalidDiscriminantCap>,
+    id_pools: IdPoolRegistry,
+    memoized: MemoizedValues,
+    memoized_regeneration_chance: f64,
+    shared: SharedValues,
+    shared_mismatch_chance: f64,
+    pinned: PinnedFields,
+    sequence_numbers: HashMap<String, u64>,
+    sequence_anomaly_chance: f64,
+    timestamp_extreme_chance: f64,
+    length_corruption_chance: f64,
+    bad_checksum_chance: f64,
+    option_some_chance: f64,
+    option_toggle_chance: f64,
+    dictionary: Dictionary,
+    dictionary_chance: f64,
+    crossover_chance: f64,
+    variant_switch_chance: f64,
+    havoc_stacking: RangeInclusive<usize>,
+    mode: MutatorMode,
+    deterministic: DeterministicProgress,
+    interesting_value_chance: f64,
+    interesting_values: UserInterestingValues,
+    byte_block_chance: f64,
+    zero_fill_chance: f64,
+    c_string_anomaly_chance: f64,
+    swarm: Option<Swarm>,
+    resize_bias: f64,
+    variant_counts: Option<HashMap<&'static str, VariantCounts>>,
+    forced_variants: HashMap<&'static str, usize>,
+    operator_counts: [usize; MutationOperator::ALL.len()],
+    applied_operators: Vec<MutationOperator>,
+    depth: usize,
+}
+
+impl Mutator<SmallRng> {
+    /// A mutator using a fast, non-cryptographic PRNG seeded with `seed`
+    pub fn fast(seed: u64) -> Self {
+        Mutator::new_with_rng(SmallRng::seed_from_u64(seed))
+    }
+}
+
+impl Mutator<StdRng> {
+    /// A mutator using a CSPRNG seeded from the operating system's randomness
+    pub fn secure() -> Self {
+        Mutator::new_with_rng(StdRng::from_entropy())
+    }
+}
+
+impl Mutator<OsRng> {
+    /// A mutator reading every random value from the operating system
+    pub fn os() -> Self {
+        Mutator::new_with_rng(OsRng)
+    }
 }
 
 impl<R: Rng> Mutator<R> {
+    /// Same as [Mutator::new_with_rng]
     pub fn new(rng: R) -> Mutator<R> {
+        Mutator::new_with_rng(rng)
+    }
+
+    /// A mutator drawing all of its randomness from `rng`. The RNG decides how fast and how
+    /// predictable generation is:
+    ///
+    /// - a fast PRNG such as `SmallRng` ([Mutator::fast]) is cheapest, and reproducible from
+    ///   its seed
+    /// - a CSPRNG such as `StdRng` seeded from the operating system ([Mutator::secure]) makes
+    ///   the generated values unpredictable to anyone who sees some of them, e.g. when
+    ///   generating test vectors for an adversarial review
+    /// - `OsRng` ([Mutator::os]) asks the operating system for every value, and is by far the
+    ///   slowest
+    pub fn new_with_rng(rng: R) -> Mutator<R> {
         Mutator {
             rng,
             flags: MutatorFlags::default(),
             corpus_state: CorpusFuzzingState::default(),
+            blob_content_weights: BlobContentWeights::default(),
+            validation_attempts: DEFAULT_VALIDATION_ATTEMPTS,
+            validation_failures: 0,
+            invalid_value_chance: DEFAULT_INVALID_VALUE_CHANCE,
+            invalid_enum_chance: CHANCE_TO_PICK_INVALID_ENUM,
+            invalid_discriminant_cap: None,
+            id_pools: IdPoolRegistry::default(),
+            memoized: MemoizedValues::default(),
+            memoized_regeneration_chance: DEFAULT_MEMOIZED_REGENERATION_CHANCE,
+            shared: SharedValues::default(),
+            shared_mismatch_chance: DEFAULT_SHARED_MISMATCH_CHANCE,
+            pinned: PinnedFields::default(),
+            sequence_numbers: HashMap::new(),
+            sequence_anomaly_chance: DEFAULT_SEQUENCE_ANOMALY_CHANCE,
+            timestamp_extreme_chance: DEFAULT_TIMESTAMP_EXTREME_CHANCE,
+            length_corruption_chance: DEFAULT_LENGTH_CORRUPTION_CHANCE,
+            bad_checksum_chance: DEFAULT_BAD_CHECKSUM_CHANCE,
+            option_some_chance: DEFAULT_OPTION_SOME_CHANCE,
+            option_toggle_chance: DEFAULT_OPTION_TOGGLE_CHANCE,
+            dictionary: Dictionary::new(),
+            dictionary_chance: DEFAULT_DICTIONARY_CHANCE,
+            crossover_chance: DEFAULT_CROSSOVER_CHANCE,
+            variant_switch_chance: DEFAULT_VARIANT_SWITCH_CHANCE,
+            havoc_stacking: 1..=1,
+            mode: MutatorMode::default(),
+            deterministic: DeterministicProgress::default(),
+            interesting_value_chance: DEFAULT_INTERESTING_VALUE_CHANCE,
+            interesting_values: UserInterestingValues::default(),
+            byte_block_chance: DEFAULT_BYTE_BLOCK_CHANCE,
+            zero_fill_chance: DEFAULT_ZERO_FILL_CHANCE,
+            c_string_anomaly_chance: DEFAULT_C_STRING_ANOMALY_CHANCE,
+            swarm: None,
+            resize_bias: DEFAULT_RESIZE_BIAS,
+            variant_counts: None,
+            forced_variants: HashMap::new(),
+            operator_counts: [0; MutationOperator::ALL.len()],
+            applied_operators: vec![],
+            depth: 0,
         }
     }
 
@@ -74,6 +425,1217 @@ impl<R: Rng> Mutator<R> {
         self.corpus_state = state;
     }
 
+    /// Sets the relative weights used to pick the content class of newly generated [Blob]s
+    pub fn set_blob_content_weights(&mut self, weights: BlobContentWeights) {
+        self.blob_content_weights = weights;
+    }
+
+    pub fn blob_content_weights(&self) -> &BlobContentWeights {
+        &self.blob_content_weights
+    }
+
+    /// Picks a [BlobContent] class according to the configured weights. Falls back to
+    /// [BlobContent::Random] if every class has a weight of 0.
+    pub fn gen_blob_content(&mut self) -> BlobContent {
+        use crate::rand::distributions::{Distribution, WeightedIndex};
+
+        let weights = BlobContent::ALL
+            .iter()
+            .map(|class| self.blob_content_weights.weight(*class));
+
+        match WeightedIndex::new(weights) {
+            Ok(dist) => BlobContent::ALL[dist.sample(&mut self.rng)],
+            Err(_) => BlobContent::Random,
+        }
+    }
+
+    /// Sets the probability that semantic types such as [Port] or [Ttl] are generated with one
+    /// of their boundary or reserved values instead of a realistic one
+    pub fn set_invalid_value_chance(&mut self, chance: f64) {
+        self.invalid_value_chance = chance;
+    }
+
+    pub fn invalid_value_chance(&self) -> f64 {
+        self.invalid_value_chance
+    }
+
+    /// Sets the probability that a newly generated [UnsafeEnum] holds an invalid discriminant
+    /// instead of one of its valid variants
+    pub fn set_invalid_enum_chance(&mut self, chance: f64) {
+        self.invalid_enum_chance = chance;
+    }
+
+    pub fn invalid_enum_chance(&self) -> f64 {
+        self.invalid_enum_chance
+    }
+
+    /// Limits each [UnsafeEnum] type to at most `max` distinct invalid discriminants. The
+    /// discriminants are sampled the first time a type goes invalid, deterministically from
+    /// `seed` and the type's name, so every mutator configured with the same seed explores the
+    /// same ones. Sampling favors values next to the valid discriminants, as generation does.
+    ///
+    /// While a cap is set, mutating a valid `UnsafeEnum` only makes it invalid with probability
+    /// [Mutator::invalid_enum_chance], and an invalid one is only ever changed to another of its
+    /// sampled discriminants. Without a cap, mutation always makes the value invalid.
+    pub fn set_invalid_discriminant_cap(&mut self, max: usize, seed: u64) {
+        self.invalid_discriminant_cap = Some(InvalidDiscriminantCap {
+            max,
+            seed,
+            samples: HashMap::new(),
+        });
+    }
+
+    /// Removes the cap set with [Mutator::set_invalid_discriminant_cap]
+    pub fn clear_invalid_discriminant_cap(&mut self) {
+        self.invalid_discriminant_cap = None;
+    }
+
+    /// The maximum number of invalid discriminants per [UnsafeEnum] type, if capped
+    pub fn invalid_discriminant_cap(&self) -> Option<usize> {
+        self.invalid_discriminant_cap.as_ref().map(|cap| cap.max)
+    }
+
+    /// The invalid discriminants `T` is limited to by [Mutator::set_invalid_discriminant_cap],
+    /// sampling them if this is the first time they're needed. Returns `None` if no cap is set.
+    pub fn capped_invalid_discriminants<T, I>(&mut self) -> Option<Vec<I>>
+    where
+        T: ToPrimitive<Output = I>,
+        I: NumCast + Bounded + Copy,
+    {
+        let cap = self.invalid_discriminant_cap.as_mut()?;
+        let type_name = std::any::type_name::<T>();
+
+        if !cap.samples.contains_key(type_name) {
+            let mut hasher = DefaultHasher::new();
+            cap.seed.hash(&mut hasher);
+            type_name.hash(&mut hasher);
+
+            let samples = sample_invalid_discriminants(
+                &T::valid_primitives(),
+                cap.max,
+                &mut Mutator::new(StdRng::seed_from_u64(hasher.finish())),
+            );
+            cap.samples.insert(type_name, samples);
+        }
+
+        Some(
+            cap.samples[type_name]
+                .iter()
+                .filter_map(|value| num::cast(*value))
+                .collect(),
+        )
+    }
+
+    /// Sets the probability that a newly generated `Option<T>` is `Some`
+    pub fn set_option_some_chance(&mut self, chance: f64) {
+        self.option_some_chance = chance;
+    }
+
+    pub fn option_some_chance(&self) -> f64 {
+        self.option_some_chance
+    }
+
+    /// Sets the probability that mutating an `Option<T>` flips it between `Some` and `None`
+    /// instead of mutating the value it holds
+    pub fn set_option_toggle_chance(&mut self, chance: f64) {
+        self.option_toggle_chance = chance;
+    }
+
+    pub fn option_toggle_chance(&self) -> f64 {
+        self.option_toggle_chance
+    }
+
+    /// Sets the tokens spliced into `Vec<u8>`, `AsciiString`, and `Utf8String` values and into
+    /// serialized inputs by the havoc stage
+    pub fn set_dictionary(&mut self, dictionary: Dictionary) {
+        self.dictionary = dictionary;
+    }
+
+    pub fn dictionary(&self) -> &Dictionary {
+        &self.dictionary
+    }
+
+    pub fn dictionary_mut(&mut self) -> &mut Dictionary {
+        &mut self.dictionary
+    }
+
+    /// Sets the probability that mutating a `Vec<u8>`, `AsciiString`, or `Utf8String` splices
+    /// in a dictionary token instead of mutating its elements
+    pub fn set_dictionary_chance(&mut self, chance: f64) {
+        self.dictionary_chance = chance;
+    }
+
+    pub fn dictionary_chance(&self) -> f64 {
+        self.dictionary_chance
+    }
+
+    /// Sets the probability that [start_pipeline_fuzzer][crate::driver::start_pipeline_fuzzer]
+    /// crosses the corpus entry it starts an iteration from with another entry
+    pub fn set_crossover_chance(&mut self, chance: f64) {
+        self.crossover_chance = chance;
+    }
+
+    pub fn crossover_chance(&self) -> f64 {
+        self.crossover_chance
+    }
+
+    /// Sets the probability that mutating a derived enum whose variants have fields replaces it
+    /// with a newly generated value, possibly of another variant, instead of mutating the fields
+    /// of its current variant
+    pub fn set_variant_switch_chance(&mut self, chance: f64) {
+        self.variant_switch_chance = chance;
+    }
+
+    pub fn variant_switch_chance(&self) -> f64 {
+        self.variant_switch_chance
+    }
+
+    /// Sets the probability that [Mutator::mutate] replaces a number with one of its
+    /// [interesting values][crate::interesting]
+    pub fn set_interesting_value_chance(&mut self, chance: f64) {
+        self.interesting_value_chance = chance;
+    }
+
+    pub fn interesting_value_chance(&self) -> f64 {
+        self.interesting_value_chance
+    }
+
+    /// Sets the probability that mutating a `Vec<u8>` duplicates, overwrites, swaps, or inserts
+    /// a block of bytes instead of mutating single bytes
+    pub fn set_byte_block_chance(&mut self, chance: f64) {
+        self.byte_block_chance = chance;
+    }
+
+    pub fn byte_block_chance(&self) -> f64 {
+        self.byte_block_chance
+    }
+
+    /// Sets the probability that a field of a type deriving `Mutatable` is reset to its
+    /// `Default` value instead of being mutated, leaving e.g. an empty list or an absent
+    /// section. Fields whose types don't implement `Default`, or with a `#[lain(min)]` or
+    /// `#[lain(max)]`, are always mutated. Zero filling is off (0.0) unless enabled here.
+    pub fn set_zero_fill_chance(&mut self, chance: f64) {
+        self.zero_fill_chance = chance;
+    }
+
+    pub fn zero_fill_chance(&self) -> f64 {
+        self.zero_fill_chance
+    }
+
+    /// Sets the probability that mutating a [CString][crate::types::CString] drops or restores
+    /// its terminator, embeds a NUL in it, or extends it past its `max_size`, instead of
+    /// mutating its bytes
+    pub fn set_c_string_anomaly_chance(&mut self, chance: f64) {
+        self.c_string_anomaly_chance = chance;
+    }
+
+    pub fn c_string_anomaly_chance(&self) -> f64 {
+        self.c_string_anomaly_chance
+    }
+
+    /// Leaves out the enum variants, operators, and optional fields `swarm` disables, or
+    /// nothing if it's `None`. See [crate::swarm].
+    pub fn set_swarm(&mut self, swarm: Option<Swarm>) {
+        self.swarm = swarm;
+    }
+
+    pub fn swarm(&self) -> Option<&Swarm> {
+        self.swarm.as_ref()
+    }
+
+    /// Whether `operator` may be applied, i.e. it isn't left out by the [Swarm]
+    pub fn operator_enabled(&self, operator: MutationOperator) -> bool {
+        self.swarm
+            .as_ref()
+            .is_none_or(|swarm| !swarm.disables_operator(operator))
+    }
+
+    /// Whether the variant of `T` at `index` is left out by the [Swarm]. This is called by the
+    /// code generated for `#[derive(NewFuzzed)]` on enums.
+    pub fn variant_disabled<T: EnumVariants>(&self, index: usize) -> bool {
+        self.swarm
+            .as_ref()
+            .is_some_and(|swarm| swarm.disables_variant(std::any::type_name::<T>(), index))
+    }
+
+    /// Whether optional fields of type `T` are left out by the [Swarm]
+    pub fn option_disabled<T>(&self) -> bool {
+        self.swarm
+            .as_ref()
+            .is_some_and(|swarm| swarm.disables_option(std::any::type_name::<T>()))
+    }
+
+    /// Adds `value` to the interesting values of every integer type as wide as `T`
+    pub fn add_interesting_value<T: InterestingValue>(&mut self, value: T) {
+        self.interesting_values.add(value);
+    }
+
+    /// Removes every value added with [Mutator::add_interesting_value]
+    pub fn clear_interesting_values(&mut self) {
+        self.interesting_values.clear();
+    }
+
+    /// Picks one of the interesting values of `T`. Values added with
+    /// [Mutator::add_interesting_value] are picked half of the time, and are then off by one
+    /// half of the time.
+    pub fn gen_interesting_value<T: InterestingValue>(&mut self) -> T {
+        let added = self.interesting_values.get::<T>();
+        if added.is_empty() || self.rng.gen_bool(0.5) {
+            let builtin = T::interesting_values();
+            return builtin[self.rng.gen_range(0..builtin.len())];
+        }
+
+        let value = added[self.rng.gen_range(0..added.len())];
+        let value = match self.rng.gen_range(0..4) {
+            0 => value.wrapping_add(1),
+            1 => value.wrapping_sub(1),
+            _ => value,
+        };
+
+        T::from_bits(value)
+    }
+
+    /// Sets how many rounds of mutation [Mutator::mutate_stacked] applies to an input, picked
+    /// uniformly from `stacking`. Each round is a full call to [Mutatable::mutate] with a fresh
+    /// field budget, so later rounds mutate the results of earlier ones as in AFL's stacked
+    /// havoc. The default of `1..=1` mutates inputs once; wider ranges mutate more aggressively.
+    pub fn set_havoc_stacking(&mut self, stacking: RangeInclusive<usize>) {
+        let start = cmp::max(*stacking.start(), 1);
+        let end = cmp::max(*stacking.end(), start);
+
+        self.havoc_stacking = start..=end;
+    }
+
+    pub fn havoc_stacking(&self) -> RangeInclusive<usize> {
+        self.havoc_stacking.clone()
+    }
+
+    /// Sets how the havoc stage of a pipeline mutates serialized inputs. The progress of a
+    /// [MutatorMode::Deterministic] pass belongs to the input being fuzzed, so it must be reset
+    /// with [Mutator::reset_deterministic_progress] (or restored with
+    /// [Mutator::set_deterministic_progress]) whenever the mutator moves on to another input.
+    pub fn set_mode(&mut self, mode: MutatorMode) {
+        self.mode = mode;
+    }
+
+    pub fn mode(&self) -> MutatorMode {
+        self.mode
+    }
+
+    /// Whether the mutator is in [MutatorMode::Deterministic] and hasn't finished the pass over
+    /// the current input yet
+    pub fn in_deterministic_pass(&self) -> bool {
+        self.mode == MutatorMode::Deterministic && !self.deterministic.is_complete()
+    }
+
+    pub fn deterministic_progress(&self) -> DeterministicProgress {
+        self.deterministic
+    }
+
+    /// Continues a deterministic pass from `progress`, as returned by
+    /// [Mutator::deterministic_progress] while fuzzing the same input earlier
+    pub fn set_deterministic_progress(&mut self, progress: DeterministicProgress) {
+        self.deterministic = progress;
+    }
+
+    /// Starts the deterministic pass over from the first bit
+    pub fn reset_deterministic_progress(&mut self) {
+        self.deterministic = DeterministicProgress::default();
+    }
+
+    /// Applies the next flip of the deterministic pass to `bytes`: bits are flipped one at a
+    /// time from the most significant bit of the first byte, then whole bytes are inverted.
+    /// Returns `false` and leaves `bytes` alone once the pass is complete.
+    pub fn deterministic_step(&mut self, bytes: &mut [u8]) -> bool {
+        let bits = bytes.len() * 8;
+        let total = bits + bytes.len();
+        self.deterministic.total = Some(total);
+
+        let step = self.deterministic.step;
+        if step >= total {
+            return false;
+        }
+
+        if step < bits {
+            self.record_operator(MutationOperator::DeterministicBitFlip);
+            bytes[step / 8] ^= 0x80 >> (step % 8);
+        } else {
+            self.record_operator(MutationOperator::DeterministicByteFlip);
+            bytes[step - bits] ^= 0xFF;
+        }
+
+        self.deterministic.step += 1;
+        true
+    }
+
+    /// Mutates `value` a number of times picked from [Mutator::havoc_stacking]
+    pub fn mutate_stacked<T: Mutatable>(&mut self, value: &mut T) {
+        let (start, end) = (*self.havoc_stacking.start(), *self.havoc_stacking.end());
+        let rounds = if start == end {
+            start
+        } else {
+            self.rng.gen_range(start..=end)
+        };
+
+        for round in 0..rounds {
+            if round > 0 {
+                self.corpus_state.reset();
+            }
+
+            value.mutate(self, None);
+        }
+    }
+
+    /// Produces a child of `first` and `second` by replacing parts of a copy of `first` with
+    /// the corresponding parts of `second`. See [Crossover].
+    pub fn crossover<T: Crossover>(&mut self, first: &T, second: &T) -> T {
+        let mut child = first.clone();
+        child.crossover(second, self);
+        self.record_operator(MutationOperator::Crossover);
+
+        child
+    }
+
+    /// Serializes `input` with the bytes of one of its fields replaced by the bytes of the same
+    /// field of a random entry of `corpus`. Only fields whose bytes differ between the two are
+    /// picked. Returns `None` if `corpus` is empty or the entry picked has no such field.
+    pub fn transplant<T: BinarySerialize, E: ByteOrder>(
+        &mut self,
+        input: &T,
+        corpus: &[T],
+    ) -> Option<Vec<u8>> {
+        if corpus.is_empty() {
+            return None;
+        }
+
+        let donor = &corpus[self.gen_range(0, corpus.len())];
+        let (bytes, layout) = serialize_with_layout::<T, E>(input);
+        let (donor_bytes, donor_layout) = serialize_with_layout::<T, E>(donor);
+
+        let candidates: Vec<(&FieldSpan, &FieldSpan)> = layout
+            .iter()
+            .filter(|span| !span.path.is_empty())
+            .filter_map(|span| Some((span, find_span(&donor_layout, &span.path)?)))
+            .filter(|(span, donor_span)| {
+                bytes[span.start..span.end] != donor_bytes[donor_span.start..donor_span.end]
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let (span, donor_span) = candidates[self.gen_range(0, candidates.len())];
+        trace!("transplanting {}", span.path);
+        self.record_operator(MutationOperator::Transplant);
+
+        Some(splice(
+            &bytes,
+            span,
+            &donor_bytes[donor_span.start..donor_span.end],
+        ))
+    }
+
+    /// With probability [Mutator::dictionary_chance], returns a random dictionary token for
+    /// which `accept` returns true. Never consumes randomness when the dictionary is empty.
+    pub(crate) fn gen_dictionary_token<F: Fn(&[u8]) -> bool>(
+        &mut self,
+        accept: F,
+    ) -> Option<Vec<u8>> {
+        if self.dictionary.is_empty()
+            || !self.operator_enabled(MutationOperator::DictionaryToken)
+            || !self.gen_chance(self.dictionary_chance)
+        {
+            return None;
+        }
+
+        self.random_dictionary_token(accept)
+    }
+
+    /// Returns a random dictionary token for which `accept` returns true, recording
+    /// [MutationOperator::DictionaryToken] if there is one
+    pub(crate) fn random_dictionary_token<F: Fn(&[u8]) -> bool>(
+        &mut self,
+        accept: F,
+    ) -> Option<Vec<u8>> {
+        let token = self
+            .dictionary
+            .tokens()
+            .iter()
+            .filter(|token| accept(token))
+            .choose(&mut self.rng)?
+            .clone();
+        self.record_operator(MutationOperator::DictionaryToken);
+
+        Some(token)
+    }
+
+    /// Replaces a random occurrence in `bytes` of the find token of one of the dictionary's
+    /// [replacements][Dictionary::add_replacement] with its paired token. Returns `false`
+    /// (leaving `bytes` untouched) if none of the find tokens occur in `bytes`.
+    pub fn replace_dictionary_token(&mut self, bytes: &mut Vec<u8>) -> bool {
+        let occurrences = self.dictionary.find_replaceable(bytes);
+        let (index, offset) = match occurrences.choose(&mut self.rng) {
+            Some(occurrence) => *occurrence,
+            None => return false,
+        };
+
+        let (find, replace) = &self.dictionary.replacements()[index];
+        bytes.splice(offset..offset + find.len(), replace.iter().copied());
+        self.record_operator(MutationOperator::TokenReplace);
+
+        true
+    }
+
+    /// Registers `pool` under `name`, replacing any pool previously registered with that name.
+    /// Fields annotated with `#[lain(from_pool = "name")]` draw their values from it.
+    pub fn register_id_pool<T: Send + 'static>(&mut self, name: &str, pool: IdPool<T>) {
+        self.id_pools.pools.insert(name.to_string(), Box::new(pool));
+    }
+
+    /// Returns the pool registered under `name`, or `None` if there is no such pool or it holds
+    /// IDs of a different type
+    pub fn id_pool<T: 'static>(&self, name: &str) -> Option<&IdPool<T>> {
+        self.id_pools
+            .pools
+            .get(name)
+            .and_then(|pool| pool.downcast_ref::<IdPool<T>>())
+    }
+
+    pub fn id_pool_mut<T: 'static>(&mut self, name: &str) -> Option<&mut IdPool<T>> {
+        self.id_pools
+            .pools
+            .get_mut(name)
+            .and_then(|pool| pool.downcast_mut::<IdPool<T>>())
+    }
+
+    /// Adds an ID issued by or observed from the target to the pool `name`, registering a new
+    /// pool with the default settings if one doesn't exist
+    pub fn record_id<T: PartialEq + Send + 'static>(&mut self, name: &str, id: T) {
+        if self.id_pool::<T>(name).is_none() {
+            self.register_id_pool(name, IdPool::<T>::new());
+        }
+
+        self.id_pool_mut::<T>(name).unwrap().insert(id);
+    }
+
+    /// Picks an ID from the pool `name`. Returns `None` if the pool is missing or empty, or if
+    /// the pool's garbage chance says a random value should be used instead.
+    fn choose_pooled_id<T: Clone + 'static>(&mut self, name: &str) -> Option<T> {
+        let (len, garbage_chance) = match self.id_pool::<T>(name) {
+            Some(pool) if !pool.is_empty() => (pool.len(), pool.garbage_chance()),
+            _ => {
+                trace!("id pool {} is empty or not registered", name);
+                return None;
+            }
+        };
+
+        if self.gen_chance(garbage_chance) {
+            return None;
+        }
+
+        let index = self.rng.gen_range(0..len);
+        self.id_pool::<T>(name).unwrap().get(index).cloned()
+    }
+
+    /// Generates a value for a field annotated with `#[lain(from_pool = "name")]`: an ID from the
+    /// pool `name` most of the time, or a random value otherwise
+    pub fn gen_from_pool<T>(
+        &mut self,
+        name: &str,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) -> T
+    where
+        T: NewFuzzed + Clone + 'static,
+    {
+        match self.choose_pooled_id::<T>(name) {
+            Some(id) => id,
+            None => T::new_fuzzed(self, constraints),
+        }
+    }
+
+    /// Mutates a field annotated with `#[lain(from_pool = "name")]` by replacing it with an ID
+    /// from the pool `name` most of the time, or by mutating it normally otherwise
+    pub fn mutate_from_pool<T>(
+        &mut self,
+        name: &str,
+        value: &mut T,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) where
+        T: Mutatable + Clone + 'static,
+    {
+        match self.choose_pooled_id::<T>(name) {
+            Some(id) => *value = id,
+            None => value.mutate(self, constraints),
+        }
+    }
+
+    /// Sets the probability that fixing up a `#[lain(length_of)]` field deliberately sets it to
+    /// a wrong length
+    pub fn set_length_corruption_chance(&mut self, chance: f64) {
+        self.length_corruption_chance = chance;
+    }
+
+    pub fn length_corruption_chance(&self) -> f64 {
+        self.length_corruption_chance
+    }
+
+    /// Sets the probability that a newly generated or mutated
+    /// [Checksum][crate::checksum::Checksum] is serialized with a wrong value
+    pub fn set_bad_checksum_chance(&mut self, chance: f64) {
+        self.bad_checksum_chance = chance;
+    }
+
+    pub fn bad_checksum_chance(&self) -> f64 {
+        self.bad_checksum_chance
+    }
+
+    /// Sets a field annotated with `#[lain(length_of = "...")]` to `len`, saturating at the
+    /// largest value it can hold. With probability [Mutator::length_corruption_chance], the
+    /// field is instead set to a length which is off by one, 0, or its largest value.
+    pub fn fix_length<T: NumCast + Bounded>(&mut self, value: &mut T, len: usize) {
+        if !self.gen_chance(self.length_corruption_chance) {
+            *value = num::cast(len).unwrap_or_else(T::max_value);
+            return;
+        }
+
+        self.record_operator(MutationOperator::CorruptLength);
+        let corrupted = match self.rng.gen_range(0..4) {
+            0 => len.checked_add(1),
+            1 => len.checked_sub(1),
+            2 => Some(0),
+            _ => None,
+        };
+        *value = corrupted.and_then(num::cast).unwrap_or_else(T::max_value);
+    }
+
+    /// Sets the probability that a `#[lain(memoize)]` field is generated anew, replacing the
+    /// value which is otherwise copied into every input
+    pub fn set_memoized_regeneration_chance(&mut self, chance: f64) {
+        self.memoized_regeneration_chance = chance;
+    }
+
+    pub fn memoized_regeneration_chance(&self) -> f64 {
+        self.memoized_regeneration_chance
+    }
+
+    /// Generates a value for the field `field` of `owner` annotated with `#[lain(memoize)]`: a
+    /// copy of the value this mutator generated for the field before, or the first time and with
+    /// probability [Mutator::memoized_regeneration_chance], a new value which replaces it.
+    /// `constraints` only apply when a new value is generated.
+    pub fn gen_memoized<T>(
+        &mut self,
+        owner: &'static str,
+        field: &'static str,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) -> T
+    where
+        T: NewFuzzed + Clone + Send + 'static,
+    {
+        let memoized = self
+            .memoized
+            .values
+            .get(&(owner, field))
+            .and_then(|value| value.downcast_ref::<T>())
+            .cloned();
+
+        match memoized {
+            Some(value) if !self.gen_chance(self.memoized_regeneration_chance) => value,
+            _ => self.regenerate_memoized(owner, field, constraints),
+        }
+    }
+
+    /// Mutates a field annotated with `#[lain(memoize)]` by replacing it with a newly generated
+    /// value with probability [Mutator::memoized_regeneration_chance]. The field is left as it
+    /// is otherwise.
+    pub fn mutate_memoized<T>(
+        &mut self,
+        owner: &'static str,
+        field: &'static str,
+        value: &mut T,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) where
+        T: NewFuzzed + Clone + Send + 'static,
+    {
+        if self.gen_chance(self.memoized_regeneration_chance) {
+            self.record_operator(MutationOperator::Regenerate);
+            *value = self.regenerate_memoized(owner, field, constraints);
+        }
+    }
+
+    fn regenerate_memoized<T>(
+        &mut self,
+        owner: &'static str,
+        field: &'static str,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) -> T
+    where
+        T: NewFuzzed + Clone + Send + 'static,
+    {
+        let value = T::new_fuzzed(self, constraints);
+        self.memoized
+            .values
+            .insert((owner, field), Box::new(value.clone()));
+
+        value
+    }
+
+    /// Forgets the values generated for `#[lain(memoize)]` fields so far
+    pub fn clear_memoized(&mut self) {
+        self.memoized.values.clear();
+    }
+
+    /// Sets the probability that mutating a `#[lain(shared)]` field mutates it on its own,
+    /// leaving it inconsistent with the other fields sharing its value
+    pub fn set_shared_mismatch_chance(&mut self, chance: f64) {
+        self.shared_mismatch_chance = chance;
+    }
+
+    pub fn shared_mismatch_chance(&self) -> f64 {
+        self.shared_mismatch_chance
+    }
+
+    /// Generates a value for a field annotated with `#[lain(shared = "name")]`: the shared value
+    /// `name`, which is generated with `constraints` the first time it's needed. Every field
+    /// sharing `name` gets the same value until [Mutator::clear_shared_values] is called, so
+    /// e.g. a request and its response generated one after the other carry the same session ID.
+    ///
+    /// Fields sharing a name must have the same type. A field of another type gets a value of
+    /// its own.
+    pub fn gen_shared<T>(
+        &mut self,
+        name: &str,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) -> T
+    where
+        T: NewFuzzed + Clone + Send + 'static,
+    {
+        match self.shared.values.get(name) {
+            Some(value) => match value.downcast_ref::<T>() {
+                Some(value) => value.clone(),
+                None => {
+                    trace!("shared value {} has a different type", name);
+                    T::new_fuzzed(self, constraints)
+                }
+            },
+            None => {
+                let value = T::new_fuzzed(self, constraints);
+                self.shared
+                    .values
+                    .insert(name.to_string(), Box::new(value.clone()));
+                value
+            }
+        }
+    }
+
+    /// Mutates a field annotated with `#[lain(shared = "name")]`. Usually this sets the field
+    /// to the shared value `name`, adopting the field's current value as the shared value if
+    /// there is none yet. With probability [Mutator::shared_mismatch_chance] the field is
+    /// mutated on its own instead.
+    pub fn mutate_shared<T>(
+        &mut self,
+        name: &str,
+        value: &mut T,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) where
+        T: Mutatable + Clone + Send + 'static,
+    {
+        if self.gen_chance(self.shared_mismatch_chance) {
+            trace!("mismatching shared value {}", name);
+            value.mutate(self, constraints);
+            return;
+        }
+
+        match self.shared.values.get(name) {
+            Some(shared) => {
+                if let Some(shared) = shared.downcast_ref::<T>() {
+                    *value = shared.clone();
+                }
+            }
+            None => {
+                self.shared
+                    .values
+                    .insert(name.to_string(), Box::new(value.clone()));
+            }
+        }
+    }
+
+    /// Sets the shared value `name` (e.g. to a session ID the target issued)
+    pub fn set_shared_value<T: Clone + Send + 'static>(&mut self, name: &str, value: T) {
+        self.shared.values.insert(name.to_string(), Box::new(value));
+    }
+
+    /// Returns the shared value `name`, or `None` if it hasn't been resolved yet or has another
+    /// type
+    pub fn shared_value<T: 'static>(&self, name: &str) -> Option<&T> {
+        self.shared
+            .values
+            .get(name)
+            .and_then(|value| value.downcast_ref::<T>())
+    }
+
+    /// Forgets every shared value, so the next input generates new ones. Call this when starting
+    /// a new session with the target.
+    pub fn clear_shared_values(&mut self) {
+        self.shared.values.clear();
+    }
+
+    /// Pins the field at `path` to `value` in every input this mutator generates or mutates from
+    /// now on. Paths are the ones reported by
+    /// [BinarySerialize::field_layout][crate::traits::BinarySerialize::field_layout], relative to
+    /// the top-level input: `header.version`, `items[2].id`, or `Request.id` for the field of
+    /// an enum variant. `value` must have exactly the field's type (`3u8` for a `u8` field).
+    /// Fields which don't exist in an input, including those of variants which weren't
+    /// generated, are left alone.
+    ///
+    /// Pinning a field again replaces its previous value.
+    pub fn pin_field<T: Clone + Send + 'static>(&mut self, path: &str, value: T) {
+        self.unpin_field(path);
+        self.pinned.values.push((path.to_string(), Box::new(value)));
+    }
+
+    /// Lets the field at `path` be fuzzed again
+    pub fn unpin_field(&mut self, path: &str) {
+        self.pinned.values.retain(|(pinned, _)| pinned != path);
+    }
+
+    /// Lets every pinned field be fuzzed again
+    pub fn clear_pinned_fields(&mut self) {
+        self.pinned.values.clear();
+    }
+
+    /// Sets the fields of `value` which were pinned with [Mutator::pin_field]. Returns whether
+    /// any field was set. Derived implementations of [NewFuzzed] and [Mutatable] call this on
+    /// top-level inputs.
+    pub fn apply_pinned_fields<T: BinarySerialize>(&self, value: &mut T) -> bool {
+        let mut applied = false;
+        for (path, pinned) in self.pinned.values.iter() {
+            applied |= value.set_field(path, &**pinned);
+        }
+
+        applied
+    }
+
+    /// Sets the probability that mutating an `#[lain(auto_increment)]` field deliberately skips
+    /// ahead or replays the previous sequence number instead of using the next one
+    pub fn set_sequence_anomaly_chance(&mut self, chance: f64) {
+        self.sequence_anomaly_chance = chance;
+    }
+
+    pub fn sequence_anomaly_chance(&self) -> f64 {
+        self.sequence_anomaly_chance
+    }
+
+    /// Sets the next value the sequence counter `name` will produce (e.g. to an initial
+    /// sequence number negotiated with the target)
+    pub fn set_sequence_number(&mut self, name: &str, next: u64) {
+        self.sequence_numbers.insert(name.to_string(), next);
+    }
+
+    /// Resets every sequence counter to 0. Call this when starting a new session with the target.
+    pub fn reset_sequence_numbers(&mut self) {
+        self.sequence_numbers.clear();
+    }
+
+    /// Returns the next value of the sequence counter `name` and advances it. The counter wraps
+    /// around to 0 once it exceeds the max value of `T`.
+    pub fn next_sequence_number<T: NumCast + Bounded>(&mut self, name: &str) -> T {
+        let max: u64 = num::cast(T::max_value()).unwrap_or(u64::MAX);
+        let counter = self.sequence_numbers.entry(name.to_string()).or_insert(0);
+        if *counter > max {
+            *counter = 0;
+        }
+
+        let value = *counter;
+        *counter = counter.wrapping_add(1);
+
+        num::cast(value).unwrap()
+    }
+
+    /// Mutates an `#[lain(auto_increment)]` field. Usually this assigns the next value of the
+    /// sequence counter `name`, but with probability [Mutator::sequence_anomaly_chance] the
+    /// counter is either skipped ahead by a random amount or the previous value is replayed.
+    pub fn mutate_sequence_number<T: NumCast + Bounded>(&mut self, name: &str, value: &mut T) {
+        if self.gen_chance(self.sequence_anomaly_chance) {
+            let current = self.sequence_numbers.get(name).copied().unwrap_or(0);
+
+            if current != 0 && self.rng.gen() {
+                trace!("replaying sequence number for {}", name);
+                *value = num::cast(current - 1).unwrap_or_else(T::max_value);
+                return;
+            }
+
+            let skip = self.rng.gen_range(1..=MAX_SEQUENCE_SKIP);
+            trace!("skipping {} sequence numbers for {}", skip, name);
+            self.sequence_numbers
+                .insert(name.to_string(), current.wrapping_add(skip));
+        }
+
+        *value = self.next_sequence_number(name);
+    }
+
+    /// Sets the probability that a `#[lain(now)]` field is given an extreme value (0, the max
+    /// value of its type, or a time years in the past or future) instead of the current time
+    pub fn set_timestamp_extreme_chance(&mut self, chance: f64) {
+        self.timestamp_extreme_chance = chance;
+    }
+
+    pub fn timestamp_extreme_chance(&self) -> f64 {
+        self.timestamp_extreme_chance
+    }
+
+    /// Sets how strongly resizing a list favors growing it when it's near empty and shrinking
+    /// it when it's near its `max_size`. At 0.0 both are equally likely, and at 1.0 an empty list
+    /// is always grown and a full one always shrunk. Lists below their `min_size` are always
+    /// grown.
+    pub fn set_resize_bias(&mut self, bias: f64) {
+        self.resize_bias = bias;
+    }
+
+    pub fn resize_bias(&self) -> f64 {
+        self.resize_bias
+    }
+
+    /// Picks whether to grow a list rather than shrink it, given how full it is as a fraction
+    /// of the space between its `min_size` and `max_size`. See [Mutator::set_resize_bias].
+    pub fn gen_grow_list(&mut self, fill: f64) -> bool {
+        if fill < 0.0 {
+            return true;
+        }
+
+        let chance = 0.5 + self.resize_bias * (0.5 - fill.min(1.0));
+        self.gen_chance(chance.clamp(0.0, 1.0))
+    }
+
+    /// Generates a value for a `#[lain(now)]` field: the current time in `unit` since the Unix
+    /// epoch, offset by a random amount within `jitter` in either direction. With probability
+    /// [Mutator::timestamp_extreme_chance] an extreme value is returned instead. Times which
+    /// don't fit in `T` saturate to its bounds.
+    pub fn gen_timestamp<T: NumCast + Bounded>(
+        &mut self,
+        unit: TimeUnit,
+        jitter: std::time::Duration,
+    ) -> T {
+        let now = unit.count(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default(),
+        ) as i128;
+
+        let timestamp = if self.gen_chance(self.timestamp_extreme_chance) {
+            let offset = unit.count(EXTREME_TIMESTAMP_OFFSET) as i128;
+            match self.rng.gen_range(0u8..5u8) {
+                0 => return T::min_value(),
+                1 => return T::max_value(),
+                2 => now - offset,
+                3 => now + offset,
+                // end of 32-bit time
+                _ => unit.count(std::time::Duration::from_secs(i32::MAX as u64)) as i128,
+            }
+        } else {
+            let jitter = unit.count(jitter) as i128;
+            now + self.rng.gen_range(-jitter..=jitter)
+        };
+
+        num::cast(timestamp).unwrap_or_else(|| {
+            if timestamp < 0 {
+                T::min_value()
+            } else {
+                T::max_value()
+            }
+        })
+    }
+
+    /// Enables or disables counting which enum variants are generated. See
+    /// [CoverageAttribution::merge_variants][crate::attribution::CoverageAttribution::merge_variants].
+    pub fn set_track_variants(&mut self, track: bool) {
+        if !track {
+            self.variant_counts = None;
+        } else if self.variant_counts.is_none() {
+            self.variant_counts = Some(HashMap::new());
+        }
+    }
+
+    pub fn track_variants(&self) -> bool {
+        self.variant_counts.is_some()
+    }
+
+    /// Notes that the variant of `T` at `index` was generated. This is called by the code
+    /// generated for `#[derive(NewFuzzed)]` on enums and does nothing unless variant tracking
+    /// is enabled.
+    pub fn record_variant<T: EnumVariants>(&mut self, index: usize) {
+        let variant_counts = match self.variant_counts {
+            Some(ref mut variant_counts) => variant_counts,
+            None => return,
+        };
+
+        // enums used internally by the mutator aren't part of the user's model
+        let type_name = std::any::type_name::<T>();
+        if type_name.starts_with("lain::") {
+            return;
+        }
+
+        let counts = variant_counts
+            .entry(type_name)
+            .or_insert_with(|| VariantCounts {
+                names: (0..T::variant_count()).map(T::variant_name).collect(),
+                counts: vec![0; T::variant_count()],
+            });
+
+        if let Some(count) = counts.counts.get_mut(index) {
+            *count += 1;
+        }
+    }
+
+    /// Returns the variant counts gathered since the last call and resets them
+    pub fn take_variant_counts(&mut self) -> HashMap<&'static str, VariantCounts> {
+        match self.variant_counts {
+            Some(ref mut variant_counts) => std::mem::take(variant_counts),
+            None => HashMap::new(),
+        }
+    }
+
+    /// Forces newly generated instances of the enum named `type_name` (as returned by
+    /// [std::any::type_name]) to be the variant at `index`. If that variant can't be generated
+    /// (e.g. it is `#[lain(ignore)]`d) a random variant is picked as usual. Used by
+    /// [VariantWalk][crate::walk::VariantWalk].
+    pub fn force_variant(&mut self, type_name: &'static str, index: usize) {
+        self.forced_variants.insert(type_name, index);
+    }
+
+    /// Removes every variant forced by [Mutator::force_variant]
+    pub fn clear_forced_variants(&mut self) {
+        self.forced_variants.clear();
+    }
+
+    /// The declaration index of the variant new instances of `T` are forced to be, if any. This
+    /// is called by the code generated for `#[derive(NewFuzzed)]` on enums.
+    pub fn forced_variant<T: EnumVariants>(&self) -> Option<usize> {
+        if self.forced_variants.is_empty() {
+            return None;
+        }
+
+        self.forced_variants
+            .get(std::any::type_name::<T>())
+            .copied()
+    }
+
+    /// Picks a value which isn't in `valid` but is close to it: just below or above one of the
+    /// valid values, or somewhere in a gap between two of them. Returns `None` if `valid` is
+    /// empty or no such value could be represented by `T`.
+    pub fn gen_invalid_discriminant<T: NumCast + Copy>(&mut self, valid: &[T]) -> Option<T> {
+        let mut values: Vec<i128> = valid.iter().filter_map(|v| num::cast(*v)).collect();
+        values.sort_unstable();
+        values.dedup();
+
+        if values.is_empty() {
+            return None;
+        }
+
+        let gaps: Vec<(i128, i128)> = values
+            .windows(2)
+            .filter(|pair| pair[1] - pair[0] > 1)
+            .map(|pair| (pair[0] + 1, pair[1]))
+            .collect();
+
+        for _i in 0..4 {
+            let candidate = match self.gen_range(0u8, 3u8) {
+                0 => values.choose(&mut self.rng).unwrap() - 1,
+                1 => values.choose(&mut self.rng).unwrap() + 1,
+                _ => match gaps.choose(&mut self.rng) {
+                    Some((start, end)) => self.rng.gen_range(*start..*end),
+                    None => values[values.len() - 1] + 1,
+                },
+            };
+
+            if values.binary_search(&candidate).is_err() {
+                if let Some(value) = num::cast(candidate) {
+                    return Some(value);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Notes that `operator` was applied. This is called by lain's own operators; custom
+    /// [Mutatable] implementations may call it to attribute their changes.
+    pub fn record_operator(&mut self, operator: MutationOperator) {
+        trace!("applying operator {} ({})", operator, operator.id());
+
+        self.operator_counts[operator.index()] += 1;
+        if self.applied_operators.len() < MAX_APPLIED_OPERATORS {
+            self.applied_operators.push(operator);
+        }
+    }
+
+    /// Returns the operators applied since this was last called, in the order they were
+    /// applied
+    pub fn take_applied_operators(&mut self) -> Vec<MutationOperator> {
+        std::mem::take(&mut self.applied_operators)
+    }
+
+    /// Number of times each operator has been applied by this mutator, in ID order
+    pub fn operator_counts(&self) -> Vec<(MutationOperator, usize)> {
+        MutationOperator::ALL
+            .iter()
+            .copied()
+            .zip(self.operator_counts.iter().copied())
+            .collect()
+    }
+
+    /// Mutates the `#[lain(offset)]` fields of a structure together: either every offset is
+    /// shifted by the same delta, as if the data they point to had been relocated, or two
+    /// offsets swap values. Either way the offsets stay consistent with each other in a way
+    /// independent per-field mutation rarely produces. Arithmetic wraps so the results can be
+    /// truncated back to the fields' own types.
+    pub fn mutate_offsets(&mut self, offsets: &mut [u64]) {
+        if offsets.len() >= 2 && self.gen_chance(0.5) {
+            let first = self.gen_range(0, offsets.len());
+            let second = (first + self.gen_range(1, offsets.len())) % offsets.len();
+            offsets.swap(first, second);
+            self.record_operator(MutationOperator::SwapOffsets);
+
+            return;
+        }
+
+        self.record_operator(MutationOperator::RelocateOffsets);
+        let delta = *OFFSET_DELTAS.choose(&mut self.rng).unwrap();
+        let shift_up = self.gen_chance(0.5);
+        for offset in offsets.iter_mut() {
+            *offset = if shift_up {
+                offset.wrapping_add(delta)
+            } else {
+                offset.wrapping_sub(delta)
+            };
+        }
+    }
+
+    /// Picks the elements of a slice of `len` elements which should be mutated. Usually this is
+    /// the whole slice, but for slices of at least [MIN_WINDOWED_LEN] elements there's a
+    /// [CHANCE_TO_MUTATE_WINDOW] chance of a random contiguous window of at most a quarter of
+    /// them instead. Mutating a window is cheaper than a full pass over a large array and leaves
+    /// the rest of it intact.
+    pub fn gen_mutation_window(&mut self, len: usize) -> Range<usize> {
+        if len < MIN_WINDOWED_LEN || !self.gen_chance(CHANCE_TO_MUTATE_WINDOW) {
+            return 0..len;
+        }
+
+        self.record_operator(MutationOperator::MutateWindow);
+        let window_len = self.gen_range(1, len / 4 + 1);
+        let start = self.gen_range(0, len - window_len + 1);
+
+        start..start + window_len
+    }
+
+    /// Number of derived structures and enums enclosing the value currently being generated or
+    /// mutated. This is 0 for a top-level value, 1 for a field of it, and so on. Custom
+    /// [NewFuzzed] and [Mutatable] implementations of recursive types can use this to stop
+    /// growing once they are deeply nested.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Notes that the fields of a structure are about to be generated or mutated. Derived
+    /// implementations call this around each field; custom container implementations should
+    /// do the same around their elements if they want [Mutator::depth] to include them.
+    pub fn descend(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Undoes [Mutator::descend]
+    pub fn ascend(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Sets the maximum number of times [Mutator::new_validated] and [Mutator::mutate_validated]
+    /// will try to produce an input which passes validation
+    pub fn set_validation_attempts(&mut self, attempts: usize) {
+        self.validation_attempts = attempts;
+    }
+
+    pub fn validation_attempts(&self) -> usize {
+        self.validation_attempts
+    }
+
+    /// Returns the number of inputs which have failed validation since the last call and
+    /// resets the count
+    pub fn take_validation_failures(&mut self) -> usize {
+        std::mem::replace(&mut self.validation_failures, 0)
+    }
+
+    /// Serializes `value` and checks it with [Validate::validate], attempting a single
+    /// [Validate::repair] on failure
+    fn validate_or_repair<T, E>(&mut self, value: &mut T) -> bool
+    where
+        T: BinarySerialize + Validate,
+        E: ByteOrder,
+    {
+        let mut serialized = vec![];
+        value.binary_serialize::<_, E>(&mut serialized);
+        if value.validate(&serialized) {
+            return true;
+        }
+
+        self.validation_failures += 1;
+
+        value.repair(self);
+        serialized.clear();
+        value.binary_serialize::<_, E>(&mut serialized);
+
+        value.validate(&serialized)
+    }
+
+    /// Generates a new instance of `T` which passes [Validate::validate] when serialized with
+    /// the byteorder `E`. Inputs which fail validation are repaired, or regenerated if they
+    /// cannot be repaired, up to [Mutator::validation_attempts] times.
+    ///
+    /// Returns `None` if no valid input could be generated.
+    pub fn new_validated<T, E>(
+        &mut self,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) -> Option<T>
+    where
+        T: NewFuzzed + BinarySerialize + Validate,
+        E: ByteOrder,
+    {
+        for _i in 0..cmp::max(self.validation_attempts, 1) {
+            let mut value = T::new_fuzzed(self, constraints);
+            if self.validate_or_repair::<T, E>(&mut value) {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+
+    /// Mutates `value` such that it still passes [Validate::validate] when serialized with the
+    /// byteorder `E`. Mutations which fail validation are repaired, or discarded and retried if
+    /// they cannot be repaired, up to [Mutator::validation_attempts] times.
+    ///
+    /// Returns `false` and leaves `value` untouched if no valid mutation was found.
+    pub fn mutate_validated<T, E>(
+        &mut self,
+        value: &mut T,
+        constraints: Option<&Constraints<T::RangeType>>,
+    ) -> bool
+    where
+        T: Mutatable + BinarySerialize + Validate + Clone,
+        E: ByteOrder,
+    {
+        for _i in 0..cmp::max(self.validation_attempts, 1) {
+            let mut mutated = value.clone();
+            mutated.mutate(self, constraints);
+
+            if self.validate_or_repair::<T, E>(&mut mutated) {
+                *value = mutated;
+                return true;
+            }
+        }
+
+        false
+    }
+
     /// Generates a random choice of the given type
     pub fn gen<T: 'static>(&mut self) -> T
     where
@@ -96,6 +1658,7 @@ impl<R: Rng> Mutator<R> {
             + WrappingAdd<Output = T>
             + WrappingSub<Output = T>
             + DangerousNumber<T>
+            + InterestingValue
             + std::fmt::Debug,
     {
         // dirty but needs to be done so we can call self.gen_chance_ignore_flags
@@ -107,18 +1670,48 @@ impl<R: Rng> Mutator<R> {
             self.corpus_state.fields_fuzzed += 1;
         }
 
-        if self.gen_chance(0.10) {
+        if self.operator_enabled(MutationOperator::DangerousNumber) && self.gen_chance(0.10) {
+            self.record_operator(MutationOperator::DangerousNumber);
             *num = T::select_dangerous_number(&mut self.rng);
             return;
         }
 
-        let operation = MutatorOperation::new_fuzzed(self, None);
+        if self.operator_enabled(MutationOperator::InterestingValue)
+            && self.gen_chance(self.interesting_value_chance)
+        {
+            self.record_operator(MutationOperator::InterestingValue);
+            *num = self.gen_interesting_value();
+            return;
+        }
+
+        let mut operation = MutatorOperation::new_fuzzed(self, None);
+        if !self.operator_enabled(operation.operator()) {
+            // fall back to any enabled operation, or keep this one if there's none
+            if let Some(enabled) = MutatorOperation::ALL
+                .iter()
+                .find(|op| self.operator_enabled(op.operator()))
+            {
+                operation = *enabled;
+            }
+        }
 
-        trace!("Operation selected: {:?}", operation);
         match operation {
-            MutatorOperation::BitFlip => self.bit_flip(num),
-            MutatorOperation::Flip => self.flip(num),
-            MutatorOperation::Arithmetic => self.arithmetic(num),
+            MutatorOperation::BitFlip => {
+                self.record_operator(MutationOperator::BitFlip);
+                self.bit_flip(num)
+            }
+            MutatorOperation::Flip => {
+                self.record_operator(MutationOperator::Flip);
+                self.flip(num)
+            }
+            MutatorOperation::Arithmetic => {
+                self.record_operator(MutationOperator::Arithmetic);
+                self.arithmetic(num)
+            }
+            MutatorOperation::ProportionalDelta => {
+                self.record_operator(MutationOperator::ProportionalDelta);
+                self.proportional_delta(num)
+            }
         }
     }
 
@@ -132,7 +1725,7 @@ impl<R: Rng> Mutator<R> {
 
         trace!("xoring bit {}", idx);
 
-        *num = (*num) ^ num::cast(1u64 << idx).unwrap();
+        *num = (*num) ^ num::cast(1u128 << idx).unwrap();
     }
 
     /// Flip more than 1 bit in this number. This is a flip potentially up to
@@ -144,10 +1737,10 @@ impl<R: Rng> Mutator<R> {
         let num_bits = (std::mem::size_of::<T>() * 8) as u8;
         let bits_to_flip = self.rng.gen_range(1..=num_bits) as usize;
 
-        // 64 is chosen here as it's the the max primitive size (in bits) that we support
+        // 128 is chosen here as it's the the max primitive size (in bits) that we support
         // we choose to do this approach over a vec to avoid an allocation
-        assert!(num_bits <= 64);
-        let mut potential_bit_indices = [0u8; 64];
+        assert!(num_bits <= 128);
+        let mut potential_bit_indices = [0u8; 128];
         for i in 0..num_bits {
             potential_bit_indices[i as usize] = i;
         }
@@ -157,7 +1750,7 @@ impl<R: Rng> Mutator<R> {
             .partial_shuffle(&mut self.rng, num_bits as usize);
 
         for idx in bit_indices {
-            *num = (*num) ^ num::cast(1u64 << *idx).unwrap()
+            *num = (*num) ^ num::cast(1u128 << *idx).unwrap()
         }
     }
 
@@ -182,6 +1775,50 @@ impl<R: Rng> Mutator<R> {
         }
     }
 
+    /// Moves the number by an amount relative to its magnitude: up or down by 1% or 10% (and
+    /// by at least 1), or doubles or halves it. Unlike [Mutator::arithmetic], this still makes a
+    /// difference to large values such as file sizes and offsets. Overflows wrap.
+    fn proportional_delta<T>(&mut self, num: &mut T)
+    where
+        T: NumCast + Copy + InterestingValue + std::fmt::Debug,
+    {
+        // the arithmetic is done on the two's complement bits, which wraps like the type does
+        let bits = num.to_bits();
+        let (negative, magnitude) = match num::cast::<T, i128>(*num) {
+            Some(value) => (value < 0, value.unsigned_abs()),
+            None => (false, bits),
+        };
+
+        let delta = match ProportionalDelta::new_fuzzed(self, None) {
+            ProportionalDelta::OnePercent => cmp::max(magnitude / 100, 1),
+            ProportionalDelta::TenPercent => cmp::max(magnitude / 10, 1),
+            ProportionalDelta::Double => {
+                trace!("doubling {:?}", num);
+                *num = T::from_bits(bits.wrapping_mul(2));
+                return;
+            }
+            ProportionalDelta::Halve => {
+                trace!("halving {:?}", num);
+                let halved = magnitude / 2;
+                *num = T::from_bits(if negative {
+                    halved.wrapping_neg()
+                } else {
+                    halved
+                });
+                return;
+            }
+        };
+
+        let scaled = if self.rng.gen::<bool>() {
+            trace!("adding {} to {:?}", delta, num);
+            bits.wrapping_add(delta)
+        } else {
+            trace!("subtracting {} from {:?}", delta, num);
+            bits.wrapping_sub(delta)
+        };
+        *num = T::from_bits(scaled);
+    }
+
     /// Generates a number in the range from [min, max) (**note**: non-inclusive). Panics if min >= max.
     pub fn gen_range<T, B1>(&mut self, min: B1, max: B1) -> B1
     where
@@ -299,6 +1936,7 @@ impl<R: Rng> Mutator<R> {
 
     /// Helper function for quitting the recursive mutation early if the target field has already
     /// been mutated.
+    #[inline]
     pub fn should_early_bail_mutation(&self) -> bool {
         self.flags
             .field_count
@@ -307,6 +1945,7 @@ impl<R: Rng> Mutator<R> {
     }
 
     /// Returns a boolean value indicating whether or not the chance event occurred
+    #[inline]
     pub fn gen_chance(&mut self, chance_percentage: f64) -> bool {
         if chance_percentage <= 0.0 {
             return false;
@@ -324,6 +1963,8 @@ impl<R: Rng> Mutator<R> {
     pub fn random_flags(&mut self) {
         self.flags = MutatorFlags::default();
         self.corpus_state.reset();
+        // a panic in the previous iteration may have skipped an ascend
+        self.depth = 0;
 
         if self.rng.gen_bool(0.95) {
             self.flags.field_count = Some(self.gen_range(1, 100));
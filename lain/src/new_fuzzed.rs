@@ -4,9 +4,10 @@ use crate::rand::seq::SliceRandom;
 use crate::rand::Rng;
 use crate::traits::*;
 use crate::types::*;
-use num_traits::Bounded;
+use num_traits::{Bounded, NumCast};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
-use std::mem::MaybeUninit;
+use std::hash::Hash;
 use std::{char, cmp};
 
 impl<T> NewFuzzed for Option<T>
@@ -15,11 +16,11 @@ where
 {
     type RangeType = T::RangeType;
 
-    default fn new_fuzzed<R: Rng>(
+    fn new_fuzzed<R: Rng>(
         mutator: &mut Mutator<R>,
         constraints: Option<&Constraints<Self::RangeType>>,
     ) -> Option<T> {
-        if mutator.gen_chance(0.75) {
+        if !mutator.option_disabled::<T>() && mutator.gen_chance(mutator.option_some_chance()) {
             Some(T::new_fuzzed(mutator, constraints))
         } else {
             None
@@ -33,7 +34,7 @@ where
 {
     type RangeType = T::RangeType;
 
-    default fn new_fuzzed<R: Rng>(
+    fn new_fuzzed<R: Rng>(
         mutator: &mut Mutator<R>,
         constraints: Option<&Constraints<Self::RangeType>>,
     ) -> Box<T> {
@@ -43,120 +44,10 @@ where
 
 impl<T> NewFuzzed for Vec<T>
 where
-    T: NewFuzzed + SerializedSize,
+    T: NewFuzzed + Clone + SerializedSize,
 {
     type RangeType = usize;
 
-    default fn new_fuzzed<R: Rng>(
-        mutator: &mut Mutator<R>,
-        constraints: Option<&Constraints<Self::RangeType>>,
-    ) -> Vec<T> {
-        const MAX_NUM_ELEMENTS: usize = 0x200;
-
-        let mut min: Self::RangeType;
-        let mut max: Self::RangeType;
-        let weight: Weighted;
-        let max_size: Option<usize>;
-        let mut used_size: usize = 0;
-        let mut output: Vec<T>;
-
-        if T::max_default_object_size() == 0 {
-            warn!("Size of element in vec is 0... returning early");
-            return vec![];
-        }
-
-        trace!(
-            "Generating random Vec with constraints: {:#X?}",
-            constraints
-        );
-
-        // if no min/max were supplied, we'll take a conservative approach of 64 elements
-        match constraints {
-            Some(constraints) => {
-                min = constraints.min.unwrap_or(0);
-                max = constraints.max.unwrap_or(MAX_NUM_ELEMENTS);
-
-                if min != max {
-                    if min != 0 && mutator.gen_chance(crate::mutator::CHANCE_TO_IGNORE_MIN_MAX) {
-                        min = 0;
-                    }
-
-                    if constraints.max.is_some()
-                        && mutator.gen_chance(crate::mutator::CHANCE_TO_IGNORE_MIN_MAX)
-                    {
-                        if let Some(new_max) = constraints.max.unwrap().checked_mul(2) {
-                            max = new_max;
-                        }
-                    }
-                }
-
-                weight = constraints.weighted;
-
-                max_size = constraints.max_size;
-                if let Some(max_size) = max_size {
-                    max = cmp::min(max, max_size / T::max_default_object_size());
-                }
-            }
-            None => {
-                min = 0;
-                max = MAX_NUM_ELEMENTS;
-                max_size = None;
-                weight = Weighted::None;
-            }
-        }
-
-        if max == 0 {
-            return vec![];
-        }
-
-        if min > max {
-            min = 0;
-        }
-
-        // If min == max, that means the user probably wants this to be exactly that many elements.
-        let num_elements: usize = if min == max {
-            min
-        } else {
-            mutator.gen_weighted_range(min, max, weight)
-        };
-
-        output = Vec::with_capacity(num_elements);
-
-        for _i in 0..num_elements {
-            let element = if let Some(ref max_size) = max_size {
-                T::new_fuzzed(
-                    mutator,
-                    Some(
-                        Constraints::new()
-                            .max_size(max_size - used_size)
-                            .set_base_size_accounted_for(),
-                    ),
-                )
-            } else {
-                T::new_fuzzed(mutator, None)
-            };
-
-            let element_serialized_size = element.serialized_size();
-
-            if let Some(ref max_size) = max_size {
-                if used_size + element_serialized_size > *max_size {
-                    break;
-                } else {
-                    used_size += element_serialized_size;
-                }
-            }
-
-            output.push(element);
-        }
-
-        output
-    }
-}
-
-impl<T> NewFuzzed for Vec<T>
-where
-    T: NewFuzzed + Clone + SerializedSize,
-{
     fn new_fuzzed<R: Rng>(
         mutator: &mut Mutator<R>,
         constraints: Option<&Constraints<Self::RangeType>>,
@@ -167,6 +58,7 @@ where
         let mut max: Self::RangeType;
         let weight: Weighted;
         let max_size: Option<usize>;
+        let min_size = constraints.and_then(|c| c.min_size);
         let mut used_size: usize = 0;
         let mut output: Vec<T>;
 
@@ -194,8 +86,9 @@ where
                     if constraints.max.is_some()
                         && mutator.gen_chance(crate::mutator::CHANCE_TO_IGNORE_MIN_MAX)
                     {
-                        // we just hope this doesn't overflow.
-                        max = constraints.max.unwrap() * 2;
+                        if let Some(new_max) = constraints.max.unwrap().checked_mul(2) {
+                            max = new_max;
+                        }
                     }
                 }
 
@@ -222,6 +115,12 @@ where
             min = 0;
         }
 
+        // enough elements of their default size to fill min_size
+        if let Some(min_size) = min_size {
+            let min_elements = min_size.div_ceil(T::max_default_object_size());
+            min = cmp::min(cmp::max(min, min_elements), max);
+        }
+
         // If min == max, that means the user probably wants this to be exactly that many elements.
         let num_elements: usize = if min == max {
             min
@@ -229,11 +128,23 @@ where
             mutator.gen_weighted_range(min, max, weight)
         };
 
-        output = Vec::with_capacity(num_elements);
-
         let should_reuse_array_item =
             mutator.gen_chance(crate::mutator::CHANCE_TO_REPEAT_ARRAY_VALUE);
 
+        // without a size budget there's nothing to account for per element, so skip measuring
+        // each one. this draws from the RNG in the same order as the loops below
+        if max_size.is_none() && min_size.is_none() {
+            return if should_reuse_array_item {
+                vec![T::new_fuzzed(mutator, None); num_elements]
+            } else {
+                (0..num_elements)
+                    .map(|_| T::new_fuzzed(mutator, None))
+                    .collect()
+            };
+        }
+
+        output = Vec::with_capacity(num_elements);
+
         if should_reuse_array_item {
             let element: T = if let Some(ref max_size) = max_size {
                 T::new_fuzzed(
@@ -254,11 +165,10 @@ where
                 if let Some(ref max_size) = max_size {
                     if used_size + element_serialized_size > *max_size {
                         break;
-                    } else {
-                        used_size += element_serialized_size;
                     }
                 }
 
+                used_size += element_serialized_size;
                 output.push(element.clone());
             }
         } else {
@@ -281,63 +191,194 @@ where
                 if let Some(ref max_size) = max_size {
                     if used_size + element_serialized_size > *max_size {
                         break;
-                    } else {
-                        used_size += element_serialized_size;
                     }
                 }
 
+                used_size += element_serialized_size;
                 output.push(element);
             }
         }
 
+        // elements smaller than their default size may leave the vec short of min_size
+        while min_size.is_some_and(|min_size| used_size < min_size) && output.len() < max {
+            let element: T = match max_size {
+                Some(max_size) => T::new_fuzzed(
+                    mutator,
+                    Some(
+                        Constraints::new()
+                            .max_size(max_size - used_size)
+                            .set_base_size_accounted_for(),
+                    ),
+                ),
+                None => T::new_fuzzed(mutator, None),
+            };
+
+            let element_serialized_size = element.serialized_size();
+            if max_size.is_some_and(|max_size| used_size + element_serialized_size > max_size) {
+                break;
+            }
+
+            used_size += element_serialized_size;
+            output.push(element);
+        }
+
         output
     }
 }
 
-// TODO: Uncomment once const generics are more stable
-// impl<T, const SIZE: usize> NewFuzzed for [T; SIZE]
-// where T: NewFuzzed + Clone {
-//     type RangeType = usize;
+/// Generates the entries of a map, using the min/max (number of entries) and `max_size`
+/// constraints the same way `Vec<T>` does. Entries whose keys collide are generated but only
+/// the last one is kept.
+fn new_fuzzed_map_entries<K, V, R: Rng>(
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<usize>>,
+) -> Vec<(K, V)>
+where
+    K: NewFuzzed + SerializedSize,
+    V: NewFuzzed + SerializedSize,
+{
+    const MAX_NUM_ENTRIES: usize = 0x100;
+
+    trace!(
+        "Generating random map with constraints: {:#X?}",
+        constraints
+    );
+
+    let entry_size = K::max_default_object_size() + V::max_default_object_size();
+    let (mut min, max, weight, max_size) = match constraints {
+        Some(constraints) => {
+            let mut max = constraints.max.unwrap_or(MAX_NUM_ENTRIES);
+            if let Some(max_size) = constraints.max_size {
+                max = cmp::min(max, max_size / cmp::max(entry_size, 1));
+            }
+
+            (
+                constraints.min.unwrap_or(0),
+                max,
+                constraints.weighted,
+                constraints.max_size,
+            )
+        }
+        None => (0, MAX_NUM_ENTRIES, Weighted::None, None),
+    };
+
+    if min != max && min != 0 && mutator.gen_chance(crate::mutator::CHANCE_TO_IGNORE_MIN_MAX) {
+        min = 0;
+    }
+
+    if max == 0 {
+        return vec![];
+    }
+
+    if min > max {
+        min = 0;
+    }
+
+    let num_entries = if min == max {
+        min
+    } else {
+        mutator.gen_weighted_range(min, max, weight)
+    };
+
+    let mut used_size = 0;
+    let mut entries = Vec::with_capacity(num_entries);
+    for _i in 0..num_entries {
+        let remaining = max_size.map(|max_size| max_size - used_size);
+        let key_constraints = remaining.map(|remaining| {
+            let mut c = Constraints::new();
+            c.max_size(remaining);
+            c.base_object_size_accounted_for = true;
+            c
+        });
+        let key = K::new_fuzzed(mutator, key_constraints.as_ref());
+
+        let value_constraints = remaining.map(|remaining| {
+            let mut c = Constraints::new();
+            c.max_size(remaining.saturating_sub(key.serialized_size()));
+            c.base_object_size_accounted_for = true;
+            c
+        });
+        let value = V::new_fuzzed(mutator, value_constraints.as_ref());
+
+        if let Some(max_size) = max_size {
+            let size = key.serialized_size() + value.serialized_size();
+            if used_size + size > max_size {
+                break;
+            }
+
+            used_size += size;
+        }
+
+        entries.push((key, value));
+    }
+
+    entries
+}
+
+impl<K, V> NewFuzzed for HashMap<K, V>
+where
+    K: NewFuzzed + SerializedSize + Eq + Hash,
+    V: NewFuzzed + SerializedSize,
+{
+    type RangeType = usize;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> HashMap<K, V> {
+        new_fuzzed_map_entries(mutator, constraints)
+            .into_iter()
+            .collect()
+    }
+}
 
-//     fn new_fuzzed<R: Rng>(mutator: &mut Mutator<R>, constraints: Option<&Constraints<Self::RangeType>>) -> [T; SIZE] {
-//         if constraints.is_some() {
-//             warn!("Constraints passed to new_fuzzed on fixed-size array do nothing");
-//         }
+impl<K, V> NewFuzzed for BTreeMap<K, V>
+where
+    K: NewFuzzed + SerializedSize + Ord,
+    V: NewFuzzed + SerializedSize,
+{
+    type RangeType = usize;
 
-//         let mut output: MaybeUninit<[T; SIZE]> = MaybeUninit::uninit();
-//         let arr_ptr = output.as_mut_ptr() as *mut T;
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> BTreeMap<K, V> {
+        new_fuzzed_map_entries(mutator, constraints)
+            .into_iter()
+            .collect()
+    }
+}
 
-//         let mut idx = 0;
-//         let mut element: T = T::new_fuzzed(mutator, None);
-//         while idx < SIZE {
-//             arr_ptr.add(idx).write(element.clone());
+impl<T> NewFuzzed for VariantVec<T>
+where
+    T: NewFuzzed + EnumVariants + SerializedSize + Clone,
+{
+    type RangeType = usize;
 
-//             idx += 1;
-//             if SIZE - idx > 0 {
-//                 if mutator.gen_chance(crate::mutator::CHANCE_TO_REPEAT_ARRAY_VALUE) {
-//                     let repeat_end_idx = mutator.gen_range(idx, SIZE);
-//                     while idx < repeat_end_idx {
-//                         arr_ptr.add(idx).write(element.clone());
-//                         idx += 1;
-//                     }
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> VariantVec<T> {
+        trace!(
+            "Generating random VariantVec with constraints: {:#X?}",
+            constraints
+        );
 
-//                     if SIZE - idx > 0 {
-//                         element = T::new_fuzzed(mutator, None);
-//                     }
-//                 } else {
-//                     element = T::new_fuzzed(mutator, None);
-//                 }
-//             }
-//         }
+        let mut output = VariantVec::new(Vec::<T>::new_fuzzed(mutator, constraints));
+        crate::mutatable::fix_variant_counts(
+            &mut output,
+            mutator,
+            constraints.and_then(|c| c.max_size),
+        );
 
-//         unsafe { output.assume_init() }
-//     }
-// }
+        output
+    }
+}
 
 impl<T, I> NewFuzzed for UnsafeEnum<T, I>
 where
-    T: NewFuzzed,
-    I: NewFuzzed<RangeType = I> + Bounded + Debug + Default,
+    T: NewFuzzed + ToPrimitive<Output = I>,
+    I: NewFuzzed<RangeType = I> + Bounded + Debug + Default + NumCast + Copy,
 {
     type RangeType = I;
 
@@ -350,14 +391,33 @@ where
             constraints
         );
 
-        if mutator.gen_chance(crate::mutator::CHANCE_TO_PICK_INVALID_ENUM) {
-            UnsafeEnum::Invalid(I::new_fuzzed(mutator, constraints))
-        } else {
-            // TODO/BUG: We should be passing on the constraints, but all
-            // objects are generated with RangeType = u8, which causes
-            // complications when I is not a u8...
-            UnsafeEnum::Valid(T::new_fuzzed(mutator, None))
+        if mutator.gen_chance(mutator.invalid_enum_chance()) {
+            match mutator.capped_invalid_discriminants::<T, I>() {
+                Some(samples) => {
+                    // with no invalid discriminants to pick from, fall back to a valid variant
+                    if let Some(value) = samples.choose(&mut mutator.rng) {
+                        return UnsafeEnum::Invalid(*value);
+                    }
+                }
+                None => {
+                    // values next to the valid ones are the most likely to slip past a sloppy check
+                    if mutator.gen_chance(crate::mutator::CHANCE_TO_PICK_ENUM_GAP) {
+                        if let Some(value) =
+                            mutator.gen_invalid_discriminant(&T::valid_primitives())
+                        {
+                            return UnsafeEnum::Invalid(value);
+                        }
+                    }
+
+                    return UnsafeEnum::Invalid(I::new_fuzzed(mutator, constraints));
+                }
+            }
         }
+
+        // TODO/BUG: We should be passing on the constraints, but all
+        // objects are generated with RangeType = u8, which causes
+        // complications when I is not a u8...
+        UnsafeEnum::Valid(T::new_fuzzed(mutator, None))
     }
 }
 
@@ -392,6 +452,7 @@ impl NewFuzzed for Utf8String {
             }
         }
 
+        let (min, max) = string_length_bounds(min, max, constraints);
         let string_length = mutator.gen_weighted_range(min, max, weight);
 
         output = Utf8String {
@@ -421,6 +482,14 @@ impl NewFuzzed for Utf8String {
             }
         }
 
+        fit_string_size(
+            &mut output.inner,
+            mutator,
+            constraints,
+            |c| c.0.len_utf8(),
+            Utf8Char,
+        );
+
         output
     }
 }
@@ -456,6 +525,7 @@ impl NewFuzzed for AsciiString {
             }
         }
 
+        let (min, max) = string_length_bounds(min, max, constraints);
         let string_length = mutator.gen_weighted_range(min, max, weight);
 
         output = AsciiString {
@@ -485,10 +555,642 @@ impl NewFuzzed for AsciiString {
             }
         }
 
+        fit_string_size(&mut output.inner, mutator, constraints, |_| 1, AsciiChar);
+
         output
     }
 }
 
+/// Picks one or two UTF-16 code units: usually a character, but sometimes one of the sequences
+/// UTF-16 decoders get wrong: a surrogate pair, an unpaired or reversed surrogate, a null, or a
+/// byte order mark
+pub(crate) fn gen_utf16_sequence<R: Rng>(mutator: &mut Mutator<R>) -> Vec<u16> {
+    const HIGH_SURROGATES: (u16, u16) = (0xD800, 0xDC00);
+    const LOW_SURROGATES: (u16, u16) = (0xDC00, 0xE000);
+
+    match mutator.gen_range(0, 100) {
+        0..=59 => vec![mutator.gen_range(0x20u16, 0x7F)],
+        60..=74 => {
+            // anything else in the Basic Multilingual Plane, skipping the surrogates
+            let unit = mutator.gen_range(0x80u16, 0xF800);
+            vec![if unit >= HIGH_SURROGATES.0 {
+                unit + 0x800
+            } else {
+                unit
+            }]
+        }
+        75..=84 => vec![
+            mutator.gen_range(HIGH_SURROGATES.0, HIGH_SURROGATES.1),
+            mutator.gen_range(LOW_SURROGATES.0, LOW_SURROGATES.1),
+        ],
+        85..=89 => vec![mutator.gen_range(HIGH_SURROGATES.0, HIGH_SURROGATES.1)],
+        90..=92 => vec![mutator.gen_range(LOW_SURROGATES.0, LOW_SURROGATES.1)],
+        93..=94 => vec![
+            mutator.gen_range(LOW_SURROGATES.0, LOW_SURROGATES.1),
+            mutator.gen_range(HIGH_SURROGATES.0, HIGH_SURROGATES.1),
+        ],
+        95..=97 => vec![0x0000],
+        _ => vec![*[0xFEFF, 0xFFFE, 0xFFFF].choose(&mut mutator.rng).unwrap()],
+    }
+}
+
+/// Generates the code units of a UTF-16 string within `constraints`
+fn gen_utf16_units<R: Rng>(
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<usize>>,
+) -> Vec<u16> {
+    let (min, max, weight) = match constraints {
+        Some(constraints) => (
+            constraints.min.unwrap_or(0),
+            constraints.max.unwrap_or(256),
+            constraints.weighted,
+        ),
+        None => (0, 256, Weighted::None),
+    };
+
+    let (min, max) = string_length_bounds(min, max, constraints);
+    let len = mutator.gen_weighted_range(min, max, weight);
+
+    let mut units = Vec::with_capacity(len + 1);
+    while units.len() < len {
+        units.extend(gen_utf16_sequence(mutator));
+    }
+
+    // a surrogate pair at the end may not have fit
+    units.truncate(len);
+    fit_string_size(&mut units, mutator, constraints, |_| 2, |c| c as u16);
+
+    units
+}
+
+impl NewFuzzed for Utf16String {
+    type RangeType = usize;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        trace!(
+            "Generating random Utf16String with constraints: {:#?}",
+            constraints
+        );
+
+        Utf16String {
+            inner: gen_utf16_units(mutator, constraints),
+        }
+    }
+}
+
+/// `min_size` and `max_size` include the null terminator
+impl NewFuzzed for WideCString {
+    type RangeType = usize;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        trace!(
+            "Generating random WideCString with constraints: {:#?}",
+            constraints
+        );
+
+        let constraints = constraints.map(|c| {
+            let mut c = c.clone();
+            c.max_size = c.max_size.map(|size| size.saturating_sub(2));
+            c.min_size = c.min_size.map(|size| size.saturating_sub(2));
+            c
+        });
+
+        WideCString {
+            inner: gen_utf16_units(mutator, constraints.as_ref()),
+        }
+    }
+}
+
+/// Picks a byte of a C string: usually printable ASCII, but sometimes any other byte except NUL
+pub(crate) fn gen_c_string_byte<R: Rng>(mutator: &mut Mutator<R>) -> u8 {
+    if mutator.gen_chance(0.9) {
+        mutator.gen_range(0x20u8, 0x7F)
+    } else {
+        mutator.gen_range(1u16, 0x100) as u8
+    }
+}
+
+/// `min_size` and `max_size` include the terminator, which generated strings always have
+impl NewFuzzed for CString {
+    type RangeType = usize;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        trace!(
+            "Generating random CString with constraints: {:#?}",
+            constraints
+        );
+
+        let constraints = constraints.map(|c| {
+            let mut c = c.clone();
+            c.max_size = c.max_size.map(|size| size.saturating_sub(1));
+            c.min_size = c.min_size.map(|size| size.saturating_sub(1));
+            c
+        });
+        let constraints = constraints.as_ref();
+
+        let (min, max, weight) = match constraints {
+            Some(constraints) => (
+                constraints.min.unwrap_or(0),
+                constraints.max.unwrap_or(256),
+                constraints.weighted,
+            ),
+            None => (0, 256, Weighted::None),
+        };
+
+        let (min, max) = string_length_bounds(min, max, constraints);
+        let len = mutator.gen_weighted_range(min, max, weight);
+        let mut inner: Vec<u8> = (0..len).map(|_| gen_c_string_byte(mutator)).collect();
+        fit_string_size(&mut inner, mutator, constraints, |_| 1, |c| c as u8);
+
+        CString::from_bytes(inner)
+    }
+}
+
+/// A value for `subfield`: one of the ends of its range 10% of the time and anywhere in it
+/// otherwise, or with probability [Mutator::invalid_value_chance] anything that fits in its bits
+pub(crate) fn gen_subfield<R: Rng>(mutator: &mut Mutator<R>, subfield: &Subfield) -> u64 {
+    let mask = subfield.mask();
+    if mutator.gen_chance(mutator.invalid_value_chance()) {
+        return mutator.rng.gen::<u64>() & mask;
+    }
+
+    let (min, max) = (subfield.min() & mask, subfield.max() & mask);
+    if min >= max {
+        min
+    } else if mutator.gen_chance(0.10) {
+        if mutator.rng.gen() {
+            min
+        } else {
+            max
+        }
+    } else {
+        mutator.rng.gen_range(min..=max)
+    }
+}
+
+/// Each subfield is generated on its own. See [ControlWord].
+impl<L: ControlWordLayout> NewFuzzed for ControlWord<L> {
+    type RangeType = u8;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        let mut word = ControlWord::default();
+        for (index, subfield) in L::SUBFIELDS.iter().enumerate() {
+            let value = gen_subfield(mutator, subfield);
+            word.set_at(index, value);
+        }
+
+        word
+    }
+}
+
+/// Narrows the range of a string's length in characters (or a [Blob]'s length in bytes) so that
+/// it can meet the `min_size` and `max_size` (in bytes) of `constraints`. Every character takes
+/// up at least a byte.
+fn string_length_bounds(
+    min: usize,
+    max: usize,
+    constraints: Option<&Constraints<usize>>,
+) -> (usize, usize) {
+    let constraints = match constraints {
+        Some(constraints) => constraints,
+        None => return (min, max),
+    };
+
+    let mut min = cmp::max(min, constraints.min_size.unwrap_or(0));
+    let mut max = max;
+    if let Some(max_size) = constraints.max_size {
+        min = cmp::min(min, max_size);
+        max = cmp::min(max, max_size + 1);
+    }
+
+    // the maximum is exclusive
+    if max <= min {
+        max = min + 1;
+    }
+
+    (min, max)
+}
+
+/// Drops characters from the end of a generated string until it fits in `max_size` bytes, then
+/// appends printable ASCII until it takes up `min_size`
+fn fit_string_size<C, R: Rng>(
+    chars: &mut Vec<C>,
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<usize>>,
+    char_size: fn(&C) -> usize,
+    from_ascii: fn(char) -> C,
+) {
+    let constraints = match constraints {
+        Some(constraints) => constraints,
+        None => return,
+    };
+
+    let mut size: usize = chars.iter().map(char_size).sum();
+    if let Some(max_size) = constraints.max_size {
+        while size > max_size {
+            size -= chars.pop().as_ref().map_or(0, char_size);
+        }
+    }
+
+    let min_size = match (constraints.min_size, constraints.max_size) {
+        (Some(min_size), Some(max_size)) => cmp::min(min_size, max_size),
+        (Some(min_size), None) => min_size,
+        (None, _) => return,
+    };
+
+    while size < min_size {
+        chars.push(from_ascii(mutator.gen_range(0x20u8, 0x7F) as char));
+        size += 1;
+    }
+}
+
+/// Fills a buffer of `len` bytes with content of the given class
+pub(crate) fn gen_blob_content<R: Rng>(
+    mutator: &mut Mutator<R>,
+    class: BlobContent,
+    len: usize,
+) -> Vec<u8> {
+    trace!("generating {} bytes of {:?} blob content", len, class);
+
+    match class {
+        BlobContent::Zeros => vec![0x00; len],
+        BlobContent::Ones => vec![0xFF; len],
+        BlobContent::RepeatingPattern => {
+            let pattern_len = mutator.gen_range(1, 9);
+            let pattern: Vec<u8> = (0..pattern_len).map(|_| mutator.rng.gen()).collect();
+
+            pattern.iter().copied().cycle().take(len).collect()
+        }
+        BlobContent::Ascending => {
+            let start: u8 = mutator.rng.gen();
+
+            (0..len).map(|i| start.wrapping_add(i as u8)).collect()
+        }
+        BlobContent::Random => (0..len).map(|_| u8::new_fuzzed(mutator, None)).collect(),
+        BlobContent::TextLike => (0..len)
+            .map(|_| {
+                if mutator.gen_chance(0.15) {
+                    *b"  \t\n\r".choose(&mut mutator.rng).unwrap()
+                } else {
+                    mutator.gen_range(0x20u8, 0x7Fu8)
+                }
+            })
+            .collect(),
+        BlobContent::HighEntropy => {
+            // zlib (default and best compression) and gzip headers
+            const COMPRESSION_HEADERS: [&[u8]; 3] =
+                [&[0x78, 0x9C], &[0x78, 0xDA], &[0x1F, 0x8B, 0x08]];
+
+            let mut output = Vec::with_capacity(len);
+            if mutator.gen_chance(0.50) {
+                let header = COMPRESSION_HEADERS.choose(&mut mutator.rng).unwrap();
+                output.extend(header.iter().take(len));
+            }
+
+            while output.len() < len {
+                output.push(mutator.rng.gen());
+            }
+
+            output
+        }
+    }
+}
+
+impl NewFuzzed for Blob {
+    type RangeType = usize;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        let min: Self::RangeType;
+        let max: Self::RangeType;
+        let weight: Weighted;
+
+        trace!(
+            "Generating random Blob with constraints: {:#?}",
+            constraints
+        );
+
+        // if no min/max were supplied, we'll take a conservative approach
+        match constraints {
+            Some(constraints) => {
+                min = constraints.min.unwrap_or(0);
+                max = constraints.max.unwrap_or(0x400);
+                weight = constraints.weighted;
+            }
+            None => {
+                min = 0;
+                max = 0x400;
+                weight = Weighted::None;
+            }
+        }
+
+        // every byte of the blob is serialized as is, so sizes bound its length directly
+        let (min, max) = string_length_bounds(min, max, constraints);
+        let len = mutator.gen_weighted_range(min, max, weight);
+
+        let class = mutator.gen_blob_content();
+
+        Blob {
+            inner: gen_blob_content(mutator, class, len),
+        }
+    }
+}
+
+/// Picks one of the floats known to break numeric code: NaN, the infinities, negative zero,
+/// subnormals, the type's extremes, or a value at the boundary of an integer type
+pub(crate) fn gen_special_float<T: num_traits::Float, R: Rng>(mutator: &mut Mutator<R>) -> T {
+    const CHANCE_TO_PICK_INTEGER_BOUNDARY: f64 = 0.25;
+
+    if mutator.gen_chance(CHANCE_TO_PICK_INTEGER_BOUNDARY) {
+        return gen_integer_boundary_float(mutator);
+    }
+
+    let smallest_subnormal = T::min_positive_value() * T::epsilon();
+    let specials = [
+        T::nan(),
+        -T::nan(),
+        T::infinity(),
+        T::neg_infinity(),
+        T::zero(),
+        T::neg_zero(),
+        T::one(),
+        -T::one(),
+        T::max_value(),
+        T::min_value(),
+        T::epsilon(),
+        T::min_positive_value(),
+        smallest_subnormal,
+        -smallest_subnormal,
+        // the largest subnormal
+        T::min_positive_value() - smallest_subnormal,
+    ];
+
+    *specials.choose(&mut mutator.rng).unwrap()
+}
+
+/// Picks a power of two at which integers overflow (2^7, 2^8, 2^15, ... 2^64) or beyond which
+/// `T` can no longer represent every integer, exactly or off by a half or one, with either sign.
+/// These catch float-to-integer conversions which truncate, round, or saturate unexpectedly.
+fn gen_integer_boundary_float<T: num_traits::Float, R: Rng>(mutator: &mut Mutator<R>) -> T {
+    const EXPONENTS: [i32; 8] = [7, 8, 15, 16, 31, 32, 63, 64];
+
+    let two = T::one() + T::one();
+    let half = T::one() / two;
+    let power = if mutator.gen_chance(0.25) {
+        two / T::epsilon()
+    } else {
+        two.powi(*EXPONENTS.choose(&mut mutator.rng).unwrap())
+    };
+    let offset = *[-T::one(), -half, T::zero(), half, T::one()]
+        .choose(&mut mutator.rng)
+        .unwrap();
+
+    if mutator.rng.gen() {
+        power + offset
+    } else {
+        -(power + offset)
+    }
+}
+
+/// Picks a float which is either one of the values known to break numeric code (see
+/// [gen_special_float]) or a "realistic" value of moderate magnitude
+pub(crate) fn gen_float<T: num_traits::Float, R: Rng>(mutator: &mut Mutator<R>) -> T {
+    const CHANCE_TO_PICK_SPECIAL_FLOAT: f64 = 0.10;
+
+    if mutator.gen_chance(CHANCE_TO_PICK_SPECIAL_FLOAT) {
+        return gen_special_float(mutator);
+    }
+
+    let value: f64 = mutator.rng.gen_range(-1000.0..1000.0);
+    T::from(value).unwrap_or_else(T::zero)
+}
+
+/// Picks a length in `[min, max)` from the constraints, limited so that `len * element_size`
+/// stays within `max_size`
+fn gen_constrained_len<R: Rng>(
+    mutator: &mut Mutator<R>,
+    constraints: Option<&Constraints<usize>>,
+    default_min: usize,
+    default_max: usize,
+    element_size: usize,
+) -> usize {
+    let (min, mut max, weight) = match constraints {
+        Some(constraints) => (
+            constraints.min.unwrap_or(default_min),
+            constraints.max.unwrap_or(default_max),
+            constraints.weighted,
+        ),
+        None => (default_min, default_max, Weighted::None),
+    };
+
+    if let Some(max_size) = constraints.and_then(|c| c.max_size) {
+        max = cmp::min(max, max_size / cmp::max(element_size, 1) + 1);
+    }
+
+    if min >= max {
+        max.saturating_sub(1)
+    } else {
+        mutator.gen_weighted_range(min, max, weight)
+    }
+}
+
+impl<T: num_traits::Float> NewFuzzed for FloatVec<T> {
+    type RangeType = usize;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        trace!(
+            "Generating random FloatVec with constraints: {:#?}",
+            constraints
+        );
+
+        let len = gen_constrained_len(
+            mutator,
+            constraints,
+            0,
+            FloatVec::<T>::DEFAULT_MAX_LEN,
+            std::mem::size_of::<T>(),
+        );
+
+        FloatVec {
+            inner: (0..len).map(|_| gen_float(mutator)).collect(),
+        }
+    }
+}
+
+macro_rules! impl_ascii_number_new_fuzzed {
+    ( $($name:ident),* ) => {
+        $(
+            /// `min` and `max` constraints bound the value the digits are generated from
+            impl NewFuzzed for AsciiNumber<$name> {
+                type RangeType = $name;
+
+                fn new_fuzzed<R: Rng>(
+                    mutator: &mut Mutator<R>,
+                    constraints: Option<&Constraints<Self::RangeType>>,
+                ) -> Self {
+                    AsciiNumber::new($name::new_fuzzed(mutator, constraints))
+                }
+            }
+        )*
+    }
+}
+
+impl_ascii_number_new_fuzzed!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+/// `min` and `max` constraints bound the row and column counts individually
+impl<T: num_traits::Float> NewFuzzed for Matrix<T> {
+    type RangeType = usize;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        trace!(
+            "Generating random Matrix with constraints: {:#?}",
+            constraints
+        );
+
+        let element_size = std::mem::size_of::<T>();
+        let rows = gen_constrained_len(
+            mutator,
+            constraints,
+            1,
+            Matrix::<T>::DEFAULT_MAX_DIM,
+            element_size,
+        );
+
+        // whatever size the rows leave over bounds the number of columns
+        let cols = gen_constrained_len(
+            mutator,
+            constraints,
+            1,
+            Matrix::<T>::DEFAULT_MAX_DIM,
+            cmp::max(rows, 1) * element_size,
+        );
+
+        Matrix {
+            rows,
+            cols,
+            data: (0..rows * cols).map(|_| gen_float(mutator)).collect(),
+        }
+    }
+}
+
+impl<T> NewFuzzed for Lazy<T>
+where
+    T: NewFuzzed,
+    T::RangeType: Clone,
+{
+    type RangeType = T::RangeType;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        trace!(
+            "Generating random Lazy with constraints: {:#?}",
+            constraints
+        );
+
+        let seed = mutator.rng.gen();
+        match constraints {
+            Some(constraints) => Lazy::with_constraints(seed, constraints.clone()),
+            None => Lazy::new(seed),
+        }
+    }
+}
+
+fn gen_realistic_port<R: Rng>(mutator: &mut Mutator<R>) -> u16 {
+    match mutator.gen_range(0u8, 4u8) {
+        0 | 1 => *Port::WELL_KNOWN.choose(&mut mutator.rng).unwrap(),
+        2 => mutator
+            .rng
+            .gen_range(*Port::REGISTERED.start()..=*Port::REGISTERED.end()),
+        _ => mutator
+            .rng
+            .gen_range(*Port::EPHEMERAL.start()..=*Port::EPHEMERAL.end()),
+    }
+}
+
+fn gen_realistic_vlan_tag<R: Rng>(mutator: &mut Mutator<R>) -> u16 {
+    mutator
+        .rng
+        .gen_range(*VlanTag::VALID.start()..=*VlanTag::VALID.end())
+}
+
+fn gen_realistic_ttl<R: Rng>(mutator: &mut Mutator<R>) -> u8 {
+    // a packet which has already traveled a few hops
+    let hops = mutator.gen_range(0u8, 16u8);
+
+    Ttl::DEFAULTS.choose(&mut mutator.rng).unwrap() - hops
+}
+
+fn gen_realistic_window_size<R: Rng>(mutator: &mut Mutator<R>) -> u16 {
+    if mutator.gen_chance(0.60) {
+        *WindowSize::COMMON.choose(&mut mutator.rng).unwrap()
+    } else {
+        mutator.rng.gen_range(1024..=u16::MAX)
+    }
+}
+
+macro_rules! impl_semantic_new_fuzzed {
+    ( $($name:ident($inner:ident) => $realistic:ident),* ) => {
+        $(
+            impl NewFuzzed for $name {
+                type RangeType = $inner;
+
+                fn new_fuzzed<R: Rng>(
+                    mutator: &mut Mutator<R>,
+                    constraints: Option<&Constraints<Self::RangeType>>,
+                ) -> Self {
+                    trace!(
+                        "Generating random {} with constraints: {:#?}",
+                        stringify!($name),
+                        constraints
+                    );
+
+                    // explicit bounds take priority over the realistic ranges
+                    if let Some(constraints) = constraints {
+                        if constraints.min.is_some() || constraints.max.is_some() {
+                            return $name($inner::new_fuzzed(mutator, Some(constraints)));
+                        }
+                    }
+
+                    if mutator.gen_chance(mutator.invalid_value_chance()) {
+                        $name(*$name::ATTACK_VALUES.choose(&mut mutator.rng).unwrap())
+                    } else {
+                        $name($realistic(mutator))
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_semantic_new_fuzzed!(
+    Port(u16) => gen_realistic_port,
+    VlanTag(u16) => gen_realistic_vlan_tag,
+    Ttl(u8) => gen_realistic_ttl,
+    WindowSize(u16) => gen_realistic_window_size
+);
+
 impl NewFuzzed for Utf8Char {
     type RangeType = u32;
 
@@ -675,6 +1377,7 @@ impl NewFuzzed for char {
 impl NewFuzzed for bool {
     type RangeType = u8;
 
+    #[inline(always)]
     fn new_fuzzed<R: crate::rand::Rng>(
         mutator: &mut crate::mutator::Mutator<R>,
         _constraints: Option<&Constraints<Self::RangeType>>,
@@ -691,6 +1394,7 @@ macro_rules! impl_new_fuzzed {
             impl NewFuzzed for $name {
                 type RangeType = $name;
 
+                #[inline]
                 fn new_fuzzed<R: Rng>(mutator: &mut Mutator<R>, constraints: Option<&Constraints<Self::RangeType>>) -> Self {
                     let min: Self::RangeType;
                     let max: Self::RangeType;
@@ -747,120 +1451,115 @@ macro_rules! impl_new_fuzzed {
     }
 }
 
-// BUG: f32/f64 generate a number between 0/1 when no constraints are supplied,
-// otherwise they generate an *integer* between min/max.
-impl_new_fuzzed!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
-
-impl<T> NewFuzzed for [T; 0]
-where
-    T: NewFuzzed + Clone,
-{
-    type RangeType = usize;
+impl_new_fuzzed!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
 
-    fn new_fuzzed<R: Rng>(
-        _mutator: &mut Mutator<R>,
-        _constraints: Option<&Constraints<Self::RangeType>>,
-    ) -> [T; 0] {
-        // no-op
-        []
-    }
-}
-
-macro_rules! impl_new_fuzzed_array {
-    ( $($size:expr),* ) => {
+macro_rules! impl_new_fuzzed_float {
+    ( $($name:ident($bits:ident)),* ) => {
         $(
-            impl<T> NewFuzzed for [T; $size]
-            where T: NewFuzzed + Clone + SerializedSize {
-                type RangeType = usize;
-
-                fn new_fuzzed<R: Rng>(mutator: &mut Mutator<R>, constraints: Option<&Constraints<Self::RangeType>>) -> [T; $size] {
-                    let per_item_max_size: Option<usize> = constraints.and_then(|c| c.max_size.as_ref().and_then(|size| Some(*size / $size)));
-
-                    let mut output: MaybeUninit<[T; $size]> = MaybeUninit::uninit();
-                    let arr_ptr = output.as_mut_ptr() as *mut T;
-
-                    let constraints = per_item_max_size.as_ref().map(|size| {
-                        let mut constraints = Constraints::new();
-                        constraints.max_size(*size);
-                        constraints.set_base_size_accounted_for();
-
-                        constraints
-                    });
-
-                    let mut idx = 0;
-                    let mut element: T = T::new_fuzzed(mutator, constraints.as_ref());
-
-                    while idx < $size {
-                        unsafe {
-                            arr_ptr.add(idx).write(element.clone());
-                        }
-
-                        idx += 1;
-                        if $size - idx > 0 {
-                            if mutator.gen_chance(crate::mutator::CHANCE_TO_REPEAT_ARRAY_VALUE) {
-                                let repeat_end_idx = mutator.gen_range(idx, $size);
-                                while idx < repeat_end_idx {
-                                    unsafe {
-                                        arr_ptr.add(idx).write(element.clone());
-                                    }
-                                    idx += 1;
-                                }
-                            } else {
-                                let constraints = per_item_max_size.as_ref().map(|size| {
-                                    let mut constraints = Constraints::new();
-                                    constraints.max_size(*size);
-                                    constraints.set_base_size_accounted_for();
+            /// Picks a value in `[min, max)` if either is constrained. Otherwise the value is
+            /// biased towards the [special values][gen_special_float] which break numeric code,
+            /// and is sometimes an arbitrary bit pattern (such as a NaN with a payload).
+            impl NewFuzzed for $name {
+                type RangeType = $name;
 
-                                    constraints
-                                });
+                fn new_fuzzed<R: Rng>(
+                    mutator: &mut Mutator<R>,
+                    constraints: Option<&Constraints<Self::RangeType>>,
+                ) -> Self {
+                    const CHANCE_TO_PICK_SPECIAL_FLOAT: f64 = 0.40;
+                    const CHANCE_TO_PICK_BIT_PATTERN: f64 = 0.10;
+
+                    if let Some(constraints) = constraints {
+                        let constrained = constraints.min.is_some() || constraints.max.is_some();
+                        if constrained
+                            && !mutator.gen_chance(crate::mutator::CHANCE_TO_IGNORE_MIN_MAX)
+                        {
+                            let min = constraints.min.unwrap_or($name::MIN);
+                            let max = constraints.max.unwrap_or($name::MAX);
+                            if min.partial_cmp(&max) != Some(cmp::Ordering::Less) {
+                                return min;
+                            }
 
-                                element = T::new_fuzzed(mutator, constraints.as_ref());
+                            // the range may be too wide for its width to be representable
+                            if (max - min).is_finite() {
+                                return mutator.gen_weighted_range(min, max, constraints.weighted);
                             }
+
+                            let value: $name = gen_float(mutator);
+                            return if value.is_nan() { min } else { value.max(min).min(max) };
                         }
                     }
 
-                    unsafe { output.assume_init() }
+                    if mutator.gen_chance(CHANCE_TO_PICK_SPECIAL_FLOAT) {
+                        gen_special_float(mutator)
+                    } else if mutator.gen_chance(CHANCE_TO_PICK_BIT_PATTERN) {
+                        $name::from_bits(mutator.rng.gen::<$bits>())
+                    } else {
+                        gen_float(mutator)
+                    }
                 }
             }
+        )*
+    }
+}
 
-            impl<T> NewFuzzed for [T; $size]
-            where T: NewFuzzed + SerializedSize {
-                default type RangeType = usize;
+impl_new_fuzzed_float!(f32(u32), f64(u64));
 
-                default fn new_fuzzed<R: Rng>(mutator: &mut Mutator<R>, constraints: Option<&Constraints<Self::RangeType>>) -> [T; $size] {
-                    let per_item_max_size: Option<usize> = constraints.and_then(|c| c.max_size.as_ref().and_then(|size| Some(*size / $size)));
+impl<T, const N: usize> NewFuzzed for [T; N]
+where
+    T: NewFuzzed + Clone + SerializedSize,
+{
+    type RangeType = usize;
 
-                    let mut output: MaybeUninit<[T; $size]> = MaybeUninit::uninit();
-                    let arr_ptr = output.as_mut_ptr() as *mut T;
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> [T; N] {
+        if N == 0 {
+            return match std::convert::TryInto::<[T; N]>::try_into(vec![]) {
+                Ok(array) => array,
+                Err(_) => unreachable!(),
+            };
+        }
 
-                    for i in 0..$size {
-                        let constraints = per_item_max_size.as_ref().map(|size| {
-                            let mut constraints = Constraints::new();
-                            constraints.max_size(*size);
-                            constraints.set_base_size_accounted_for();
+        let per_item_max_size: Option<usize> =
+            constraints.and_then(|c| c.max_size.as_ref().map(|size| *size / N));
 
-                            constraints
-                        });
-                        let element = T::new_fuzzed(mutator, constraints.as_ref());
+        let item_constraints = || {
+            per_item_max_size.as_ref().map(|size| {
+                let mut constraints = Constraints::new();
+                constraints.max_size(*size);
+                constraints.set_base_size_accounted_for();
 
-                        unsafe {
-                            arr_ptr.offset(i).write(element);
-                        }
-                    }
+                constraints
+            })
+        };
+
+        let mut output: Vec<T> = Vec::with_capacity(N);
+        let mut element: T = T::new_fuzzed(mutator, item_constraints().as_ref());
 
-                    unsafe { output.assume_init() }
+        while output.len() < N {
+            output.push(element.clone());
+
+            if N - output.len() > 0 {
+                if mutator.gen_chance(crate::mutator::CHANCE_TO_REPEAT_ARRAY_VALUE) {
+                    let repeat_end_idx = mutator.gen_range(output.len(), N);
+                    while output.len() < repeat_end_idx {
+                        output.push(element.clone());
+                    }
+                } else {
+                    element = T::new_fuzzed(mutator, item_constraints().as_ref());
                 }
             }
-        )*
+        }
+
+        match std::convert::TryInto::<[T; N]>::try_into(output) {
+            Ok(array) => array,
+            Err(_) => unreachable!(),
+        }
     }
 }
 
-impl_new_fuzzed_array!(
-    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
-    27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50,
-    51, 52, 53, 54, 55, 56, 57, 58, 59, 60
-);
-
 impl NewFuzzed for *mut std::ffi::c_void {
     type RangeType = usize;
 
@@ -882,3 +1581,35 @@ impl NewFuzzed for *const std::ffi::c_void {
         std::ptr::null()
     }
 }
+
+/// How far from the current time generated timestamps usually land
+const TIMESTAMP_JITTER: std::time::Duration = std::time::Duration::from_secs(365 * 24 * 60 * 60);
+
+macro_rules! impl_timestamp_new_fuzzed {
+    ( $($name:ty),* ) => {
+        $(
+            /// Usually a time within a year of now, sometimes an extreme one. See
+            /// [Mutator::gen_timestamp].
+            impl NewFuzzed for $name {
+                type RangeType = u8;
+
+                fn new_fuzzed<R: Rng>(
+                    mutator: &mut Mutator<R>,
+                    _constraints: Option<&Constraints<Self::RangeType>>,
+                ) -> Self {
+                    Timestamp::from_unix_millis(
+                        mutator.gen_timestamp::<i64>(TimeUnit::Milliseconds, TIMESTAMP_JITTER),
+                    )
+                }
+            }
+        )*
+    }
+}
+
+impl_timestamp_new_fuzzed!(std::time::SystemTime);
+
+#[cfg(feature = "chrono_support")]
+impl_timestamp_new_fuzzed!(chrono::DateTime<chrono::Utc>);
+
+#[cfg(feature = "time_support")]
+impl_timestamp_new_fuzzed!(time::OffsetDateTime);
@@ -0,0 +1,219 @@
+//! Stable identifiers for the mutation operators lain applies.
+//!
+//! Every operator has a [name][MutationOperator::name] and a numeric [id][MutationOperator::id]
+//! which never change between releases, so traces, statistics, and bug reports can refer to
+//! exactly which operator produced a change. The [Mutator] records every operator it applies:
+//! [Mutator::operator_counts] tallies them over its lifetime, while
+//! [Mutator::take_applied_operators] returns the ones applied since it was last called, which
+//! the driver attaches to each [Finding][crate::report::Finding] as its lineage.
+//!
+//! ```compile_fail
+//! packet.mutate(&mut mutator, None);
+//! for operator in mutator.take_applied_operators() {
+//!     println!("{} ({})", operator, operator.id());
+//! }
+//! ```
+//!
+//! [Mutator]: crate::mutator::Mutator
+//! [Mutator::operator_counts]: crate::mutator::Mutator::operator_counts
+//! [Mutator::take_applied_operators]: crate::mutator::Mutator::take_applied_operators
+
+use std::fmt;
+
+/// A mutation operator. Discriminants are the operators' stable IDs; new operators are only
+/// ever appended.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MutationOperator {
+    /// A number was replaced with a boundary value (0, -1, the type's min/max, ...)
+    DangerousNumber = 1,
+    /// A single bit of a number was flipped
+    BitFlip = 2,
+    /// Several bits of a number were flipped
+    Flip = 3,
+    /// A small value was added to or subtracted from a number
+    Arithmetic = 4,
+    /// Newly generated elements were added to a list
+    GrowList = 5,
+    /// Elements were removed from a list
+    ShrinkList = 6,
+    /// The elements of a list were shuffled
+    ReorderList = 7,
+    /// A value was replaced with a newly generated one
+    Regenerate = 8,
+    /// Every `#[lain(offset)]` field of a structure was shifted by the same delta
+    RelocateOffsets = 9,
+    /// Two `#[lain(offset)]` fields of a structure swapped values
+    SwapOffsets = 10,
+    /// A bit of a serialized input was flipped
+    HavocBitFlip = 11,
+    /// A byte of a serialized input was replaced with a random one
+    HavocRandomByte = 12,
+    /// A byte of a serialized input was mutated like a number
+    HavocArithmetic = 13,
+    /// A random byte was inserted into a serialized input
+    HavocInsertByte = 14,
+    /// A byte was removed from a serialized input
+    HavocRemoveByte = 15,
+    /// A plugin's mutation operator was run on a serialized input
+    Plugin = 16,
+    /// Only a contiguous window of a list's elements were mutated
+    MutateWindow = 17,
+    /// Byte substitutions suggested by a concolic executor were applied to an earlier input
+    Concolic = 18,
+    /// The text of a number stored as ASCII digits was given a boundary value, a sign, leading
+    /// zeros, or an excessive number of digits
+    AsciiNumber = 19,
+    /// A keyword learned from interesting inputs was spliced into a serialized input
+    Keyword = 20,
+    /// A token from the mutator's dictionary was spliced into a value or serialized input
+    DictionaryToken = 21,
+    /// Parts of two inputs were combined into a new one
+    Crossover = 22,
+    /// A dictionary token found in a serialized input was replaced with its paired token
+    TokenReplace = 23,
+    /// A `#[lain(length_of)]` field was deliberately set to a wrong length
+    CorruptLength = 24,
+    /// A [Checksum][crate::checksum::Checksum] was set to be serialized with a wrong value
+    CorruptChecksum = 25,
+    /// A frame of the [framing][crate::framing] layer around a serialized input was corrupted
+    CorruptFrame = 26,
+    /// A number was replaced with one of its [interesting values][crate::interesting]
+    InterestingValue = 27,
+    /// A block of a byte buffer or string was copied to another offset of it
+    DuplicateBlock = 28,
+    /// A block of a byte buffer was overwritten with a single repeated byte
+    OverwriteBlock = 29,
+    /// Two blocks of a byte buffer swapped places
+    SwapBlocks = 30,
+    /// A block of random bytes was inserted anywhere in a byte buffer
+    InsertBlock = 31,
+    /// A field or whole substructure was reset to its `Default` value
+    ZeroFill = 32,
+    /// A C string's terminator was dropped or restored, a NUL was embedded in it, or it was
+    /// extended past its `max_size`
+    CorruptTerminator = 33,
+    /// A number was moved up or down by 1% or 10% of itself, doubled, or halved
+    ProportionalDelta = 34,
+    /// The serialized bytes of a field were replaced with those of the same field of another
+    /// corpus entry
+    Transplant = 35,
+    /// A single bit of a serialized input was flipped by the deterministic stage
+    DeterministicBitFlip = 36,
+    /// A whole byte of a serialized input was inverted by the deterministic stage
+    DeterministicByteFlip = 37,
+}
+
+impl MutationOperator {
+    /// Every operator, in ID order
+    pub const ALL: [MutationOperator; 37] = [
+        MutationOperator::DangerousNumber,
+        MutationOperator::BitFlip,
+        MutationOperator::Flip,
+        MutationOperator::Arithmetic,
+        MutationOperator::GrowList,
+        MutationOperator::ShrinkList,
+        MutationOperator::ReorderList,
+        MutationOperator::Regenerate,
+        MutationOperator::RelocateOffsets,
+        MutationOperator::SwapOffsets,
+        MutationOperator::HavocBitFlip,
+        MutationOperator::HavocRandomByte,
+        MutationOperator::HavocArithmetic,
+        MutationOperator::HavocInsertByte,
+        MutationOperator::HavocRemoveByte,
+        MutationOperator::Plugin,
+        MutationOperator::MutateWindow,
+        MutationOperator::Concolic,
+        MutationOperator::AsciiNumber,
+        MutationOperator::Keyword,
+        MutationOperator::DictionaryToken,
+        MutationOperator::Crossover,
+        MutationOperator::TokenReplace,
+        MutationOperator::CorruptLength,
+        MutationOperator::CorruptChecksum,
+        MutationOperator::CorruptFrame,
+        MutationOperator::InterestingValue,
+        MutationOperator::DuplicateBlock,
+        MutationOperator::OverwriteBlock,
+        MutationOperator::SwapBlocks,
+        MutationOperator::InsertBlock,
+        MutationOperator::ZeroFill,
+        MutationOperator::CorruptTerminator,
+        MutationOperator::ProportionalDelta,
+        MutationOperator::Transplant,
+        MutationOperator::DeterministicBitFlip,
+        MutationOperator::DeterministicByteFlip,
+    ];
+
+    pub fn id(&self) -> u16 {
+        *self as u16
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MutationOperator::DangerousNumber => "dangerous_number",
+            MutationOperator::BitFlip => "bit_flip",
+            MutationOperator::Flip => "flip",
+            MutationOperator::Arithmetic => "arithmetic",
+            MutationOperator::GrowList => "grow_list",
+            MutationOperator::ShrinkList => "shrink_list",
+            MutationOperator::ReorderList => "reorder_list",
+            MutationOperator::Regenerate => "regenerate",
+            MutationOperator::RelocateOffsets => "relocate_offsets",
+            MutationOperator::SwapOffsets => "swap_offsets",
+            MutationOperator::HavocBitFlip => "havoc_bit_flip",
+            MutationOperator::HavocRandomByte => "havoc_random_byte",
+            MutationOperator::HavocArithmetic => "havoc_arithmetic",
+            MutationOperator::HavocInsertByte => "havoc_insert_byte",
+            MutationOperator::HavocRemoveByte => "havoc_remove_byte",
+            MutationOperator::Plugin => "plugin",
+            MutationOperator::MutateWindow => "mutate_window",
+            MutationOperator::Concolic => "concolic",
+            MutationOperator::AsciiNumber => "ascii_number",
+            MutationOperator::Keyword => "keyword",
+            MutationOperator::DictionaryToken => "dictionary_token",
+            MutationOperator::Crossover => "crossover",
+            MutationOperator::TokenReplace => "token_replace",
+            MutationOperator::CorruptLength => "corrupt_length",
+            MutationOperator::CorruptChecksum => "corrupt_checksum",
+            MutationOperator::CorruptFrame => "corrupt_frame",
+            MutationOperator::InterestingValue => "interesting_value",
+            MutationOperator::DuplicateBlock => "duplicate_block",
+            MutationOperator::OverwriteBlock => "overwrite_block",
+            MutationOperator::SwapBlocks => "swap_blocks",
+            MutationOperator::InsertBlock => "insert_block",
+            MutationOperator::ZeroFill => "zero_fill",
+            MutationOperator::CorruptTerminator => "corrupt_terminator",
+            MutationOperator::ProportionalDelta => "proportional_delta",
+            MutationOperator::Transplant => "transplant",
+            MutationOperator::DeterministicBitFlip => "deterministic_bit_flip",
+            MutationOperator::DeterministicByteFlip => "deterministic_byte_flip",
+        }
+    }
+
+    pub fn from_id(id: u16) -> Option<MutationOperator> {
+        MutationOperator::ALL
+            .iter()
+            .copied()
+            .find(|op| op.id() == id)
+    }
+
+    pub fn from_name(name: &str) -> Option<MutationOperator> {
+        MutationOperator::ALL
+            .iter()
+            .copied()
+            .find(|op| op.name() == name)
+    }
+
+    /// Position of this operator in [MutationOperator::ALL]
+    pub(crate) fn index(&self) -> usize {
+        self.id() as usize - 1
+    }
+}
+
+impl fmt::Display for MutationOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
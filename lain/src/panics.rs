@@ -0,0 +1,123 @@
+//! Capturing panics raised by an in-process target as findings.
+//!
+//! Targets which are linked into the harness report many bugs by panicking: failed
+//! `assert!`/`debug_assert!` checks, explicit `panic!`/`unwrap` calls, and (in debug builds)
+//! arithmetic overflows. [catch_panic] runs a single iteration and catches its unwind so that the
+//! campaign can continue, returning a [CaughtPanic] which records what kind of panic it was and
+//! where it was raised. Each kind is reported as a distinct [FindingKind] and so ends up in a
+//! separate bucket.
+//!
+//! The driver does this itself when [FuzzerDriver::set_catch_panics] is enabled:
+//!
+//! ```compile_fail
+//! let mut driver = FuzzerDriver::<()>::new(4);
+//! driver.set_catch_panics(true);
+//!
+//! // panics in `parse_packet` are recorded in `driver.findings()` and the thread keeps going
+//! start_pipeline_fuzzer(driver.clone(), pipeline, |bytes, _packet, _ctx, _global| {
+//!     parse_packet(bytes);
+//!     Outcome::Ok
+//! });
+//! ```
+//!
+//! [FuzzerDriver::set_catch_panics]: crate::driver::FuzzerDriver::set_catch_panics
+
+use crate::report::FindingKind;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+thread_local! {
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static LAST_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Messages of the panics raised by overflow and division checks in arithmetic operations
+const ARITHMETIC_MESSAGES: &[&str] = &[
+    "attempt to add with overflow",
+    "attempt to subtract with overflow",
+    "attempt to multiply with overflow",
+    "attempt to divide with overflow",
+    "attempt to negate with overflow",
+    "attempt to shift left with overflow",
+    "attempt to shift right with overflow",
+    "attempt to divide by zero",
+    "attempt to calculate the remainder with overflow",
+    "attempt to calculate the remainder with a divisor of zero",
+];
+
+/// A panic caught by [catch_panic].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaughtPanic {
+    /// One of [FindingKind::Assertion], [FindingKind::Overflow], or [FindingKind::Panic]
+    pub kind: FindingKind,
+    pub message: String,
+    /// `file:line:column` the panic was raised at, if known
+    pub location: Option<String>,
+}
+
+impl CaughtPanic {
+    /// Classifies a panic by its message
+    pub fn new(message: String, location: Option<String>) -> Self {
+        let kind = if message.starts_with("assertion") {
+            FindingKind::Assertion
+        } else if ARITHMETIC_MESSAGES.iter().any(|m| message.starts_with(m)) {
+            FindingKind::Overflow
+        } else {
+            FindingKind::Panic
+        };
+
+        CaughtPanic {
+            kind,
+            message,
+            location,
+        }
+    }
+}
+
+/// Runs `f`, catching any panic it raises. Panics caught this way are not printed by the panic
+/// hook.
+///
+/// Whatever `f` was modifying when it panicked may be left in an inconsistent state, so state
+/// shared with it should be reset before it is used again.
+pub fn catch_panic<F: FnOnce() -> R, R>(f: F) -> Result<R, CaughtPanic> {
+    install_hook();
+
+    let was_catching = CATCHING.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|catching| catching.set(was_catching));
+
+    result.map_err(|payload| {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            String::from("panic with a non-string payload")
+        };
+
+        CaughtPanic::new(message, LAST_LOCATION.with(|location| location.take()))
+    })
+}
+
+/// Wraps the current panic hook with one which records the location of panics raised inside
+/// [catch_panic] instead of printing them. Panics raised elsewhere are passed on to the
+/// previous hook.
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            if CATCHING.with(Cell::get) {
+                let location = info
+                    .location()
+                    .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+                LAST_LOCATION.with(|last| *last.borrow_mut() = location);
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
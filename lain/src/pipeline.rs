@@ -0,0 +1,350 @@
+//! Composable multi-stage mutation.
+//!
+//! Mutating an input usually involves more than a single call to [Mutatable::mutate]: dependent
+//! fields need to be fixed up, the input needs to be serialized, and it's often worth applying
+//! some unstructured byte-level mutations before repairing checksums. A [MutationPipeline]
+//! spells these stages out as a chain so that each can be enabled, disabled, reordered, or
+//! replaced independently:
+//!
+//! ```compile_fail
+//! let pipeline = MutationPipeline::<Packet>::new()
+//!     .mutate(1.0)
+//!     .fixup(1.0)
+//!     .serialize::<LittleEndian>()
+//!     .havoc(0.25, 8)
+//!     .repair(1.0, |bytes| update_crc32(bytes));
+//!
+//! let bytes = pipeline.run(&mut mutator, &mut packet);
+//! ```
+//!
+//! Stages before [MutationPipeline::serialize] operate on the structured input and stages after
+//! it operate on the serialized bytes. Pipelines can be executed by the fuzzer driver with
+//! [start_pipeline_fuzzer][crate::driver::start_pipeline_fuzzer].
+
+use crate::byteorder::{BigEndian, ByteOrder};
+use crate::dictionary::KeywordDictionary;
+use crate::mutator::Mutator;
+use crate::operators::MutationOperator;
+#[cfg(feature = "plugin_support")]
+use crate::plugin::Plugin;
+use crate::rand::rngs::StdRng;
+use crate::rand::Rng;
+use crate::traits::{BinarySerialize, Mutatable};
+use std::fmt;
+use std::sync::Arc;
+
+type StructuredStage<I, R> = Box<dyn Fn(&mut I, &mut Mutator<R>) + Send + Sync>;
+type ByteStage<R> = Box<dyn Fn(&mut Vec<u8>, &mut Mutator<R>) + Send + Sync>;
+
+/// A single step of a [MutationPipeline].
+pub enum Stage<I, R: Rng = StdRng> {
+    /// Calls [Mutatable::mutate] on the input, as many times as
+    /// [Mutator::havoc_stacking] picks
+    Mutate,
+    /// Calls [Mutatable::fixup_dependents] on the input
+    Fixup,
+    /// A user-provided step operating on the structured input
+    Structured(StructuredStage<I, R>),
+    /// Serializes the input into bytes
+    Serialize(fn(&I, &mut Vec<u8>)),
+    /// Applies between 1 and `max_mutations` random byte-level mutations, or the next flip of
+    /// the deterministic pass if the mutator is in
+    /// [MutatorMode::Deterministic][crate::mutator::MutatorMode::Deterministic]
+    Havoc { max_mutations: usize },
+    /// A user-provided step operating on the serialized bytes, such as a checksum repair
+    Bytes(ByteStage<R>),
+}
+
+impl<I, R: Rng> Stage<I, R> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Mutate => "mutate",
+            Stage::Fixup => "fixup",
+            Stage::Structured(_) => "structured",
+            Stage::Serialize(_) => "serialize",
+            Stage::Havoc { .. } => "havoc",
+            Stage::Bytes(_) => "bytes",
+        }
+    }
+
+    fn is_structured(&self) -> bool {
+        matches!(self, Stage::Mutate | Stage::Fixup | Stage::Structured(_))
+    }
+}
+
+impl<I, R: Rng> fmt::Debug for Stage<I, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Havoc { max_mutations } => f
+                .debug_struct("Havoc")
+                .field("max_mutations", max_mutations)
+                .finish(),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+struct PipelineStage<I, R: Rng> {
+    stage: Stage<I, R>,
+    probability: f64,
+}
+
+/// An ordered chain of mutation stages, each of which runs with its own probability.
+pub struct MutationPipeline<I, R: Rng = StdRng> {
+    stages: Vec<PipelineStage<I, R>>,
+}
+
+impl<I, R: Rng> Default for MutationPipeline<I, R>
+where
+    I: Mutatable + BinarySerialize,
+{
+    /// Mutates, fixes up, and serializes (big endian) the input
+    fn default() -> Self {
+        MutationPipeline::new()
+            .mutate(1.0)
+            .fixup(1.0)
+            .serialize::<BigEndian>()
+    }
+}
+
+impl<I, R: Rng> MutationPipeline<I, R>
+where
+    I: Mutatable + BinarySerialize,
+{
+    /// Creates an empty pipeline
+    pub fn new() -> Self {
+        MutationPipeline { stages: vec![] }
+    }
+
+    /// Appends `stage`, which runs with the given `probability` (0.0 - 1.0).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a structured stage is added after the input has been serialized, or if the
+    /// input is serialized twice.
+    pub fn stage(mut self, probability: f64, stage: Stage<I, R>) -> Self {
+        let serialized = self.is_serialized();
+
+        if serialized && stage.is_structured() {
+            panic!(
+                "the {} stage operates on the structured input and must come before serialization",
+                stage.name()
+            );
+        }
+
+        if serialized && matches!(stage, Stage::Serialize(_)) {
+            panic!("a pipeline may only serialize the input once");
+        }
+
+        self.stages.push(PipelineStage { stage, probability });
+        self
+    }
+
+    /// Mutates the input with [Mutatable::mutate], stacking rounds of mutation as configured
+    /// with [Mutator::set_havoc_stacking]
+    pub fn mutate(self, probability: f64) -> Self {
+        self.stage(probability, Stage::Mutate)
+    }
+
+    /// Fixes up the input with [Mutatable::fixup_dependents]
+    pub fn fixup(self, probability: f64) -> Self {
+        self.stage(probability, Stage::Fixup)
+    }
+
+    /// Runs `f` on the structured input
+    pub fn structured<F>(self, probability: f64, f: F) -> Self
+    where
+        F: Fn(&mut I, &mut Mutator<R>) + Send + Sync + 'static,
+    {
+        self.stage(probability, Stage::Structured(Box::new(f)))
+    }
+
+    /// Serializes the input using the byte order `E`. This stage always runs.
+    pub fn serialize<E: ByteOrder>(self) -> Self {
+        self.stage(1.0, Stage::Serialize(serialize_input::<I, E>))
+    }
+
+    /// Applies between 1 and `max_mutations` random byte-level mutations (bit flips, byte
+    /// replacements, insertions, deletions) to the serialized input.
+    ///
+    /// While the mutator is in the middle of a
+    /// [deterministic pass][crate::mutator::MutatorMode::Deterministic], this stage always runs
+    /// and applies the pass's next flip instead, and the [mutate][MutationPipeline::mutate]
+    /// stage is skipped so that every flip is applied to the same input.
+    pub fn havoc(self, probability: f64, max_mutations: usize) -> Self {
+        self.stage(probability, Stage::Havoc { max_mutations })
+    }
+
+    /// Runs `f` on the serialized input. This is typically used to recompute checksums or
+    /// lengths that byte-level mutations may have invalidated.
+    pub fn repair<F>(self, probability: f64, f: F) -> Self
+    where
+        F: Fn(&mut Vec<u8>) + Send + Sync + 'static,
+    {
+        self.bytes(probability, move |bytes, _mutator| f(bytes))
+    }
+
+    /// Runs `f` on the serialized input
+    pub fn bytes<F>(self, probability: f64, f: F) -> Self
+    where
+        F: Fn(&mut Vec<u8>, &mut Mutator<R>) + Send + Sync + 'static,
+    {
+        self.stage(probability, Stage::Bytes(Box::new(f)))
+    }
+
+    /// Splices a keyword from `dictionary` into the serialized input. Does nothing until the
+    /// dictionary has learned its first keyword.
+    pub fn keywords(self, probability: f64, dictionary: Arc<KeywordDictionary>) -> Self {
+        self.bytes(probability, move |bytes, mutator| {
+            dictionary.mutate(mutator, bytes);
+        })
+    }
+
+    /// Replaces a token in the serialized input with its paired token from the mutator's
+    /// [dictionary replacements][crate::dictionary::Dictionary::add_replacement]. Does nothing
+    /// if none of the tokens to find occur in the input.
+    pub fn replace_tokens(self, probability: f64) -> Self {
+        self.bytes(probability, |bytes, mutator| {
+            mutator.replace_dictionary_token(bytes);
+        })
+    }
+
+    /// Runs the mutation operator of `plugin` on the serialized input
+    #[cfg(feature = "plugin_support")]
+    pub fn plugin_mutate(self, probability: f64, plugin: Arc<Plugin>) -> Self {
+        self.bytes(probability, move |bytes, mutator| {
+            plugin.mutate(mutator, bytes);
+        })
+    }
+
+    /// Passes the serialized input through the serializer of `plugin` (e.g. to add framing).
+    /// This stage always runs.
+    #[cfg(feature = "plugin_support")]
+    pub fn plugin_serialize(self, plugin: Arc<Plugin>) -> Self {
+        self.bytes(1.0, move |bytes, _mutator| {
+            if let Some(output) = plugin.serialize(bytes) {
+                *bytes = output;
+            }
+        })
+    }
+
+    /// The stages in this pipeline in execution order along with their probabilities
+    pub fn stages(&self) -> impl Iterator<Item = (&Stage<I, R>, f64)> {
+        self.stages.iter().map(|s| (&s.stage, s.probability))
+    }
+
+    fn is_serialized(&self) -> bool {
+        self.stages
+            .iter()
+            .any(|s| matches!(s.stage, Stage::Serialize(_)))
+    }
+
+    /// Runs each stage in order and returns the serialized input. If the pipeline has no
+    /// serialize stage, the input is serialized in big endian order after the last stage.
+    pub fn run(&self, mutator: &mut Mutator<R>, input: &mut I) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut serialized = false;
+
+        for PipelineStage { stage, probability } in self.stages.iter() {
+            if mutator.in_deterministic_pass() {
+                match stage {
+                    Stage::Mutate => continue,
+                    Stage::Havoc { .. } => {
+                        mutator.deterministic_step(&mut bytes);
+                        continue;
+                    }
+                    _ => (),
+                }
+            }
+
+            if !mutator.gen_chance(*probability) {
+                continue;
+            }
+
+            trace!("running {} pipeline stage", stage.name());
+
+            match stage {
+                Stage::Mutate => mutator.mutate_stacked(input),
+                Stage::Fixup => input.fixup_dependents(mutator),
+                Stage::Structured(f) => f(input, mutator),
+                Stage::Serialize(serialize) => {
+                    serialize(input, &mut bytes);
+                    serialized = true;
+                }
+                Stage::Havoc { max_mutations } => havoc(mutator, &mut bytes, *max_mutations),
+                Stage::Bytes(f) => f(&mut bytes, mutator),
+            }
+        }
+
+        if !serialized {
+            serialize_input::<I, BigEndian>(input, &mut bytes);
+        }
+
+        bytes
+    }
+}
+
+impl<I, R: Rng> fmt::Debug for MutationPipeline<I, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.stages.iter().map(|s| (&s.stage, s.probability)))
+            .finish()
+    }
+}
+
+fn serialize_input<I: BinarySerialize, E: ByteOrder>(input: &I, bytes: &mut Vec<u8>) {
+    input.binary_serialize::<_, E>(bytes);
+}
+
+/// Applies between 1 and `max_mutations` unstructured mutations to `bytes`
+pub(crate) fn havoc<R: Rng>(mutator: &mut Mutator<R>, bytes: &mut Vec<u8>, max_mutations: usize) {
+    let num_mutations = mutator.gen_range(1, std::cmp::max(max_mutations, 1) + 1);
+
+    for _i in 0..num_mutations {
+        if bytes.is_empty() {
+            bytes.push(mutator.rng.gen());
+            continue;
+        }
+
+        // splicing and replacing dictionary tokens are only options once the mutator has some
+        let mut num_operators = 5u8;
+        if !mutator.dictionary().is_empty() {
+            num_operators += 1;
+        }
+        if !mutator.dictionary().replacements().is_empty() {
+            num_operators += 1;
+        }
+
+        let idx = mutator.gen_range(0, bytes.len());
+        match mutator.gen_range(0u8, num_operators) {
+            0 => {
+                mutator.record_operator(MutationOperator::HavocBitFlip);
+                bytes[idx] ^= 1 << mutator.gen_range(0u8, 8u8)
+            }
+            1 => {
+                mutator.record_operator(MutationOperator::HavocRandomByte);
+                bytes[idx] = mutator.rng.gen()
+            }
+            2 => {
+                mutator.record_operator(MutationOperator::HavocArithmetic);
+                mutator.mutate(&mut bytes[idx])
+            }
+            3 => {
+                mutator.record_operator(MutationOperator::HavocInsertByte);
+                bytes.insert(idx, mutator.rng.gen())
+            }
+            4 => {
+                mutator.record_operator(MutationOperator::HavocRemoveByte);
+                bytes.remove(idx);
+            }
+            5 if !mutator.dictionary().is_empty() => {
+                if let Some(token) = mutator.random_dictionary_token(|_| true) {
+                    crate::dictionary::splice_token(mutator, bytes, token.into_iter());
+                }
+            }
+            _ => {
+                mutator.replace_dictionary_token(bytes);
+            }
+        }
+    }
+}
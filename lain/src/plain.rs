@@ -0,0 +1,229 @@
+//! Byte-level fuzzing of plain C-compatible types without lain derives.
+//!
+//! Bindings generated by bindgen and similar tools often define hundreds of
+//! `#[repr(C, packed)]` structures, and writing lain derives (or hand-written impls) for each
+//! of them isn't practical. Any type whose every bit pattern is a valid value can instead be
+//! wrapped in [Plain], which generates, mutates, and serializes it as the raw bytes of its
+//! in-memory representation and reinterprets them afterwards:
+//!
+//! ```compile_fail
+//! // generated by bindgen
+//! #[repr(C, packed)]
+//! #[derive(Copy, Clone)]
+//! struct ip_header { version_ihl: u8, tos: u8, len: u16, /* ... */ }
+//!
+//! unsafe impl PlainOldData for ip_header {}
+//!
+//! #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+//! struct Packet {
+//!     header: Plain<ip_header>,
+//!     payload: Vec<u8>,
+//! }
+//! ```
+//!
+//! [PlainOldData] carries the same guarantees as `zerocopy::AsBytes + FromBytes` or
+//! `bytemuck::Pod`, so types which already implement those can implement it without further
+//! review. With the `bytemuck_support` or `zerocopy_support` features, such types don't need
+//! an implementation at all: `Plain<T, ViaBytemuck>` and `Plain<T, ViaZerocopy>` rely on
+//! those traits instead.
+//!
+//! [Plain] values are always serialized in the host's byte order, since lain can't tell the
+//! fields of the wrapped type apart to swap them. Serializing a structure which contains one
+//! with `byteorder::NativeEndian` keeps the rest of it consistent with the C layout.
+
+use crate::byteorder::ByteOrder;
+use crate::mutator::Mutator;
+use crate::rand::Rng;
+use crate::traits::{BinaryDeserialize, BinarySerialize, Mutatable, NewFuzzed, SerializedSize};
+use crate::types::{Constraints, DeserializeError};
+use std::cmp;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// Most bytes changed by a single mutation of a [Plain] value
+const MAX_MUTATED_BYTES: usize = 8;
+
+/// Types which can be safely viewed as, and created from, arbitrary bytes.
+///
+/// # Safety
+///
+/// Implementors must be `Copy`, contain no padding bytes, no pointers or references, and no
+/// fields with invalid bit patterns (e.g. `bool`, `char`, or enums). `#[repr(C, packed)]`
+/// structures consisting only of integers and arrays of integers satisfy this.
+pub unsafe trait PlainOldData: Copy + 'static {}
+
+macro_rules! impl_plain_old_data {
+    ( $($name:ty),* ) => {
+        $(
+            unsafe impl PlainOldData for $name {}
+        )*
+    }
+}
+
+impl_plain_old_data!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+unsafe impl<T: PlainOldData, const N: usize> PlainOldData for [T; N] {}
+
+/// Selects the trait a [Plain] value relies on to view its type as bytes.
+///
+/// # Safety
+///
+/// This may only be implemented for types `T` which meet the requirements of [PlainOldData].
+pub unsafe trait PlainView<T: Copy> {}
+
+/// Views types implementing [PlainOldData] as bytes
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ViaPlainOldData;
+
+unsafe impl<T: PlainOldData> PlainView<T> for ViaPlainOldData {}
+
+/// Views types implementing `bytemuck::Pod` as bytes
+#[cfg(feature = "bytemuck_support")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ViaBytemuck;
+
+#[cfg(feature = "bytemuck_support")]
+unsafe impl<T: bytemuck::Pod> PlainView<T> for ViaBytemuck {}
+
+/// Views types implementing `zerocopy::AsBytes` and `zerocopy::FromBytes` as bytes
+#[cfg(feature = "zerocopy_support")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ViaZerocopy;
+
+#[cfg(feature = "zerocopy_support")]
+unsafe impl<T: zerocopy::AsBytes + zerocopy::FromBytes + Copy> PlainView<T> for ViaZerocopy {}
+
+/// Wrapper fuzzing a [PlainOldData] type (or with `V`, one implementing another trait which
+/// vouches for it, see [PlainView]) as the raw bytes of its in-memory representation.
+///
+/// Serialization writes those bytes as they are, so multi-byte fields keep the host's byte
+/// order just as they would if the structure were copied into a buffer in C. The byte order
+/// parameter of [BinarySerialize] and [BinaryDeserialize] is ignored: the bytes are written and
+/// read in the host's byte order whichever is given.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Plain<T: Copy, V: PlainView<T> = ViaPlainOldData>(pub T, PhantomData<V>);
+
+impl<T: Copy, V: PlainView<T>> Plain<T, V> {
+    pub fn new(value: T) -> Self {
+        Plain(value, PhantomData)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    /// The bytes of the wrapped value
+    pub fn as_bytes(&self) -> &[u8] {
+        // PlainView types have no padding, so every byte is initialized
+        unsafe { std::slice::from_raw_parts(&self.0 as *const T as *const u8, mem::size_of::<T>()) }
+    }
+
+    /// The bytes of the wrapped value, which may be modified freely
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        // any bit pattern is a valid PlainView value
+        unsafe {
+            std::slice::from_raw_parts_mut(&mut self.0 as *mut T as *mut u8, mem::size_of::<T>())
+        }
+    }
+
+    /// Reinterprets the first `size_of::<T>()` bytes of `bytes`, or returns `None` if there
+    /// are too few
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < mem::size_of::<T>() {
+            return None;
+        }
+
+        // the buffer has no alignment guarantees
+        Some(Plain::new(unsafe {
+            std::ptr::read_unaligned(bytes.as_ptr() as *const T)
+        }))
+    }
+}
+
+impl<T: Copy, V: PlainView<T>> From<T> for Plain<T, V> {
+    fn from(value: T) -> Self {
+        Plain::new(value)
+    }
+}
+
+impl<T: Copy, V: PlainView<T>> Deref for Plain<T, V> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Copy, V: PlainView<T>> DerefMut for Plain<T, V> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Copy, V: PlainView<T>> NewFuzzed for Plain<T, V> {
+    type RangeType = u8;
+
+    fn new_fuzzed<R: Rng>(
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) -> Self {
+        let mut bytes = vec![0u8; mem::size_of::<T>()];
+        mutator.rng.fill_bytes(&mut bytes);
+
+        Plain::from_bytes(&bytes).unwrap()
+    }
+}
+
+impl<T: Copy, V: PlainView<T>> Mutatable for Plain<T, V> {
+    type RangeType = u8;
+
+    fn mutate<R: Rng>(
+        &mut self,
+        mutator: &mut Mutator<R>,
+        _constraints: Option<&Constraints<Self::RangeType>>,
+    ) {
+        let len = mem::size_of::<T>();
+        if len == 0 {
+            return;
+        }
+
+        // change a handful of bytes rather than all of them so most of the structure survives
+        let num_bytes = mutator.gen_range(1, cmp::min(len, MAX_MUTATED_BYTES) + 1);
+        for _i in 0..num_bytes {
+            let offset = mutator.gen_range(0, len);
+            mutator.mutate(&mut self.as_bytes_mut()[offset]);
+        }
+    }
+}
+
+impl<T: Copy, V: PlainView<T>> SerializedSize for Plain<T, V> {
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        mem::size_of::<T>()
+    }
+
+    #[inline]
+    fn min_nonzero_elements_size() -> usize {
+        mem::size_of::<T>()
+    }
+}
+
+impl<T: Copy, V: PlainView<T>> BinarySerialize for Plain<T, V> {
+    fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        buffer.write_all(self.as_bytes()).unwrap();
+
+        mem::size_of::<T>()
+    }
+}
+
+impl<T: Copy, V: PlainView<T>> BinaryDeserialize for Plain<T, V> {
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError> {
+        let size = mem::size_of::<T>();
+        let value = Plain::from_bytes(bytes)
+            .ok_or_else(|| DeserializeError::unexpected_end(0, size - bytes.len()))?;
+
+        Ok((value, size))
+    }
+}
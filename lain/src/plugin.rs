@@ -0,0 +1,325 @@
+//! Mutation operators and serializers shipped as separate dynamic libraries.
+//!
+//! Requires the `plugin_support` feature. A plugin is a shared library exporting a C function
+//! named [PLUGIN_ENTRY_POINT] which returns a pointer to a static [PluginDescriptor]. Since the
+//! interface is a plain C ABI, plugins may be written in any language and don't need to be
+//! built against the same version of lain (or Rust) as the harness. This keeps proprietary
+//! protocol extensions out of the harness crate entirely:
+//!
+//! ```compile_fail
+//! // in the plugin's crate (crate-type = ["cdylib"])
+//! static DESCRIPTOR: PluginDescriptor = PluginDescriptor {
+//!     abi_version: PLUGIN_ABI_VERSION,
+//!     name: b"acme-framing\0".as_ptr() as *const c_char,
+//!     mutate: Some(mutate_acme_fields),
+//!     serialize: Some(add_acme_framing),
+//! };
+//!
+//! #[no_mangle]
+//! pub extern "C" fn lain_plugin_descriptor() -> *const PluginDescriptor {
+//!     &DESCRIPTOR
+//! }
+//!
+//! // in the harness, where the plugin is shared between the pipeline's stages
+//! let plugin: Arc<Plugin> = unsafe { driver.load_plugin("libacme_framing.so")? };
+//! let pipeline = MutationPipeline::<Packet>::default()
+//!     .plugin_mutate(0.5, Arc::clone(&plugin))
+//!     .plugin_serialize(plugin);
+//! ```
+
+use crate::mutator::Mutator;
+use crate::operators::MutationOperator;
+use crate::rand::Rng;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
+use std::path::Path;
+
+/// Version of the plugin interface described by [PluginDescriptor]
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Name of the function every plugin library must export
+pub const PLUGIN_ENTRY_POINT: &str = "lain_plugin_descriptor";
+
+/// Extra room given to a plugin's mutate function to grow the input into
+const MUTATE_HEADROOM: usize = 0x100;
+
+/// Mutates the `len` bytes at `data` in place. The buffer is `capacity` bytes long, so the
+/// input may grow up to that size. Returns the new length of the input. `seed` should be used
+/// as the source of randomness so that iterations remain reproducible.
+pub type PluginMutateFn =
+    unsafe extern "C" fn(data: *mut u8, len: usize, capacity: usize, seed: u64) -> usize;
+
+/// Transforms the `len` bytes at `input` (e.g. by adding framing or encoding them), writing
+/// the result to `output` if it fits within `capacity` bytes. Returns the length of the
+/// result; if it is larger than `capacity` the function is called again with a buffer large
+/// enough to hold it.
+pub type PluginSerializeFn =
+    unsafe extern "C" fn(input: *const u8, len: usize, output: *mut u8, capacity: usize) -> usize;
+
+/// Describes the operations a plugin provides. Either operation may be omitted.
+#[repr(C)]
+pub struct PluginDescriptor {
+    /// Must be [PLUGIN_ABI_VERSION]
+    pub abi_version: u32,
+    /// NUL-terminated UTF-8 name of the plugin
+    pub name: *const c_char,
+    pub mutate: Option<PluginMutateFn>,
+    pub serialize: Option<PluginSerializeFn>,
+}
+
+// descriptors are immutable statics; the name pointer always refers to static data
+unsafe impl Sync for PluginDescriptor {}
+
+/// Errors which may occur while loading a [Plugin].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginError {
+    /// The library could not be loaded
+    Load(String),
+    /// The library does not export [PLUGIN_ENTRY_POINT]
+    MissingEntryPoint,
+    /// The entry point returned a null descriptor
+    NullDescriptor,
+    /// The plugin was built for a different version of the plugin interface
+    AbiVersion { expected: u32, found: u32 },
+    /// The plugin's name is null or not valid UTF-8
+    InvalidName,
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PluginError::Load(message) => write!(f, "could not load plugin: {}", message),
+            PluginError::MissingEntryPoint => {
+                write!(f, "plugin does not export `{}`", PLUGIN_ENTRY_POINT)
+            }
+            PluginError::NullDescriptor => write!(f, "plugin returned a null descriptor"),
+            PluginError::AbiVersion { expected, found } => write!(
+                f,
+                "plugin uses ABI version {} but version {} is required",
+                found, expected
+            ),
+            PluginError::InvalidName => write!(f, "plugin name is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// A loaded plugin.
+pub struct Plugin {
+    name: String,
+    // points into `_library` if the plugin was loaded from one, so it's only valid for as long
+    // as the plugin is alive
+    descriptor: *const PluginDescriptor,
+    // kept open for as long as the descriptor is in use
+    _library: Option<Library>,
+}
+
+// the descriptor is immutable and only read through `&self`
+unsafe impl Send for Plugin {}
+unsafe impl Sync for Plugin {}
+
+impl Plugin {
+    /// Loads the plugin library at `path`.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization code, and the functions in its descriptor are
+    /// trusted to uphold the contracts of [PluginMutateFn] and [PluginSerializeFn].
+    pub unsafe fn load<P: AsRef<Path>>(path: P) -> Result<Plugin, PluginError> {
+        let library = Library::open(path.as_ref())?;
+        let entry_point = library.symbol(PLUGIN_ENTRY_POINT)?;
+        let entry_point: extern "C" fn() -> *const PluginDescriptor =
+            std::mem::transmute(entry_point);
+
+        let descriptor = entry_point();
+        if descriptor.is_null() {
+            return Err(PluginError::NullDescriptor);
+        }
+
+        let mut plugin = Plugin::from_raw_descriptor(descriptor)?;
+        plugin._library = Some(library);
+
+        info!(
+            "loaded plugin {} from {}",
+            plugin.name,
+            path.as_ref().display()
+        );
+
+        Ok(plugin)
+    }
+
+    /// Creates a plugin from a descriptor linked into the harness, e.g. to test a plugin
+    /// without building it as a separate library
+    pub fn from_descriptor(descriptor: &'static PluginDescriptor) -> Result<Plugin, PluginError> {
+        unsafe { Plugin::from_raw_descriptor(descriptor) }
+    }
+
+    /// Validates the descriptor at `descriptor`, which must stay valid for as long as the
+    /// returned plugin is alive
+    unsafe fn from_raw_descriptor(
+        descriptor: *const PluginDescriptor,
+    ) -> Result<Plugin, PluginError> {
+        let descriptor_ref = &*descriptor;
+        if descriptor_ref.abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiVersion {
+                expected: PLUGIN_ABI_VERSION,
+                found: descriptor_ref.abi_version,
+            });
+        }
+
+        if descriptor_ref.name.is_null() {
+            return Err(PluginError::InvalidName);
+        }
+
+        // the name lives as long as the descriptor, but is copied so it can be borrowed freely
+        let name = CStr::from_ptr(descriptor_ref.name)
+            .to_str()
+            .map_err(|_| PluginError::InvalidName)?
+            .to_string();
+
+        Ok(Plugin {
+            name,
+            descriptor,
+            _library: None,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn descriptor(&self) -> &PluginDescriptor {
+        // the library the descriptor may point into is closed only once `self` is dropped
+        unsafe { &*self.descriptor }
+    }
+
+    /// Whether the plugin provides a mutation operator
+    pub fn can_mutate(&self) -> bool {
+        self.descriptor().mutate.is_some()
+    }
+
+    /// Whether the plugin provides a serializer
+    pub fn can_serialize(&self) -> bool {
+        self.descriptor().serialize.is_some()
+    }
+
+    /// Runs the plugin's mutation operator on `bytes`. Returns `false` (leaving `bytes`
+    /// untouched) if the plugin doesn't provide one.
+    pub fn mutate<R: Rng>(&self, mutator: &mut Mutator<R>, bytes: &mut Vec<u8>) -> bool {
+        let mutate = match self.descriptor().mutate {
+            Some(mutate) => mutate,
+            None => return false,
+        };
+
+        mutator.record_operator(MutationOperator::Plugin);
+
+        let len = bytes.len();
+        let capacity = len + MUTATE_HEADROOM;
+        bytes.resize(capacity, 0);
+
+        let new_len = unsafe { mutate(bytes.as_mut_ptr(), len, capacity, mutator.rng.gen()) };
+        bytes.truncate(std::cmp::min(new_len, capacity));
+
+        true
+    }
+
+    /// Runs the plugin's serializer on `input`. Returns `None` if the plugin doesn't provide
+    /// one.
+    pub fn serialize(&self, input: &[u8]) -> Option<Vec<u8>> {
+        let serialize = self.descriptor().serialize?;
+
+        let mut output = vec![0u8; input.len() * 2 + 0x40];
+        loop {
+            let capacity = output.len();
+            let len =
+                unsafe { serialize(input.as_ptr(), input.len(), output.as_mut_ptr(), capacity) };
+
+            if len <= capacity {
+                output.truncate(len);
+                return Some(output);
+            }
+
+            output.resize(len, 0);
+        }
+    }
+}
+
+impl fmt::Debug for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Plugin")
+            .field("name", &self.name)
+            .field("mutate", &self.can_mutate())
+            .field("serialize", &self.can_serialize())
+            .finish()
+    }
+}
+
+/// Handle to a library opened with `dlopen`, closed when dropped
+struct Library(*mut libc::c_void);
+
+// the handle is only used to look up symbols and close the library, both of which are thread
+// safe
+unsafe impl Send for Library {}
+unsafe impl Sync for Library {}
+
+impl Library {
+    #[cfg(unix)]
+    unsafe fn open(path: &Path) -> Result<Library, PluginError> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| PluginError::Load(String::from("path contains a NUL byte")))?;
+
+        let handle = libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+        if handle.is_null() {
+            return Err(PluginError::Load(last_dl_error()));
+        }
+
+        Ok(Library(handle))
+    }
+
+    #[cfg(not(unix))]
+    unsafe fn open(_path: &Path) -> Result<Library, PluginError> {
+        Err(PluginError::Load(String::from(
+            "dynamic loading is only supported on unix platforms",
+        )))
+    }
+
+    #[cfg(unix)]
+    unsafe fn symbol(&self, name: &str) -> Result<*mut libc::c_void, PluginError> {
+        let name = std::ffi::CString::new(name).unwrap();
+        let symbol = libc::dlsym(self.0, name.as_ptr());
+
+        if symbol.is_null() {
+            Err(PluginError::MissingEntryPoint)
+        } else {
+            Ok(symbol)
+        }
+    }
+
+    #[cfg(not(unix))]
+    unsafe fn symbol(&self, _name: &str) -> Result<*mut libc::c_void, PluginError> {
+        Err(PluginError::MissingEntryPoint)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dlclose(self.0);
+        }
+    }
+}
+
+#[cfg(unix)]
+unsafe fn last_dl_error() -> String {
+    let error = libc::dlerror();
+    if error.is_null() {
+        String::from("unknown error")
+    } else {
+        CStr::from_ptr(error).to_string_lossy().into_owned()
+    }
+}
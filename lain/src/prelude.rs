@@ -1,7 +1,7 @@
 #[doc(no_inline)]
 pub use lain_derive::{
-    BinarySerialize, FuzzerObject, Mutatable, NewFuzzed, ToPrimitiveU16, ToPrimitiveU32,
-    ToPrimitiveU64, ToPrimitiveU8, VariableSizeObject,
+    BinaryDeserialize, BinarySerialize, Crossover, FuzzerObject, Minimize, Mutatable, NewFuzzed,
+    ToPrimitiveU16, ToPrimitiveU32, ToPrimitiveU64, ToPrimitiveU8, VariableSizeObject,
 };
 
 #[doc(no_inline)]
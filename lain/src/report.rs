@@ -0,0 +1,425 @@
+//! Machine-readable reports of campaign findings.
+//!
+//! A [FindingsReport] collects the crashes, hangs, and panics found by fuzzer threads and writes them out
+//! as JSON or as a [SARIF](https://sarifweb.azurewebsites.net/) 2.1.0 log so that CI systems and
+//! triage dashboards can consume the results directly. Every [Finding] carries the driver's root
+//! seed and the iteration it was found on, which is enough to replay it with
+//! [set_to_reproduce_mode][crate::driver::FuzzerDriver::set_to_reproduce_mode].
+//!
+//! ```compile_fail
+//! if let Err(e) = send_packet(&bytes) {
+//!     report.record(
+//!         Finding::new(FindingKind::Crash, &bytes, driver.seed())
+//!             .iteration(driver.num_iterations() as u64)
+//!             .message(e.to_string()),
+//!     );
+//! }
+//!
+//! // once the campaign is over
+//! report.write_sarif("findings.sarif")?;
+//! ```
+
+use crate::driver::current_thread_index;
+use crate::operators::MutationOperator;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write as FmtWrite;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Number of stack frames used to derive a bucket when none is given explicitly
+const BUCKET_FRAMES: usize = 3;
+
+/// The type of a [Finding].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FindingKind {
+    Crash,
+    Hang,
+    /// A failed `assert!` or `debug_assert!` in an in-process target
+    Assertion,
+    /// An explicit panic (e.g. `panic!` or `unwrap`) in an in-process target
+    Panic,
+    /// An arithmetic overflow or division by zero in an in-process target
+    Overflow,
+    /// An input which took far longer to execute than usual. See [crate::slow_units].
+    SlowUnit,
+    /// An input whose peak memory usage exceeded the memory budget. See [crate::memory].
+    Oom,
+}
+
+impl FindingKind {
+    pub const ALL: [FindingKind; 7] = [
+        FindingKind::Crash,
+        FindingKind::Hang,
+        FindingKind::Assertion,
+        FindingKind::Panic,
+        FindingKind::Overflow,
+        FindingKind::SlowUnit,
+        FindingKind::Oom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FindingKind::Crash => "crash",
+            FindingKind::Hang => "hang",
+            FindingKind::Assertion => "assertion",
+            FindingKind::Panic => "panic",
+            FindingKind::Overflow => "overflow",
+            FindingKind::SlowUnit => "slow_unit",
+            FindingKind::Oom => "oom",
+        }
+    }
+
+    /// Severity level used in SARIF output
+    fn sarif_level(&self) -> &'static str {
+        match self {
+            FindingKind::Hang | FindingKind::SlowUnit => "warning",
+            _ => "error",
+        }
+    }
+}
+
+/// A single crash or hang along with the metadata needed to triage and reproduce it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub kind: FindingKind,
+    /// Hash of the input which triggered the finding
+    pub input_hash: u64,
+    pub input_size: usize,
+    /// Root seed of the campaign the finding was found in
+    pub seed: u64,
+    /// Whether the input can be regenerated from `seed` and `iteration`. Findings from a
+    /// campaign drawing its randomness from the operating system (see
+    /// [EntropySource::Os][crate::mutator::EntropySource::Os]) can't be, and are reported
+    /// without a seed.
+    pub reproducible: bool,
+    /// Iteration the finding was found on, if known
+    pub iteration: Option<u64>,
+    /// Fuzzer thread the finding was found on, if known
+    pub thread: Option<usize>,
+    /// Stack frames, innermost first, if available
+    pub stack: Vec<String>,
+    pub message: String,
+    /// Mutation operators applied during the iteration which produced the input, in the order
+    /// they were applied
+    pub operators: Vec<MutationOperator>,
+    /// Peak memory usage of the iteration in bytes, if it was measured
+    pub peak_memory: Option<usize>,
+    bucket: Option<String>,
+}
+
+impl Finding {
+    /// Creates a finding for `input`, which was generated in a campaign using the root `seed`. If
+    /// called from a fuzzer thread, the thread index is recorded.
+    pub fn new(kind: FindingKind, input: &[u8], seed: u64) -> Self {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+
+        Finding {
+            kind,
+            input_hash: hasher.finish(),
+            input_size: input.len(),
+            seed,
+            reproducible: true,
+            iteration: None,
+            thread: current_thread_index(),
+            stack: vec![],
+            message: String::new(),
+            operators: vec![],
+            peak_memory: None,
+            bucket: None,
+        }
+    }
+
+    pub fn iteration(mut self, iteration: u64) -> Self {
+        self.iteration = Some(iteration);
+        self
+    }
+
+    /// Marks the finding as impossible to regenerate from its seed
+    pub fn non_reproducible(mut self) -> Self {
+        self.reproducible = false;
+        self
+    }
+
+    pub fn message<S: Into<String>>(mut self, message: S) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Sets the stack trace, innermost frame first
+    pub fn stack<S: Into<String>>(mut self, frames: impl IntoIterator<Item = S>) -> Self {
+        self.stack = frames.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the mutation operators which produced the input
+    pub fn operators(mut self, operators: impl IntoIterator<Item = MutationOperator>) -> Self {
+        self.operators = operators.into_iter().collect();
+        self
+    }
+
+    /// Sets the peak memory usage of the iteration in bytes
+    pub fn peak_memory(mut self, bytes: usize) -> Self {
+        self.peak_memory = Some(bytes);
+        self
+    }
+
+    /// Explicitly sets the bucket this finding belongs to. See [Finding::get_bucket].
+    pub fn bucket<S: Into<String>>(mut self, bucket: S) -> Self {
+        self.bucket = Some(bucket.into());
+        self
+    }
+
+    /// The bucket used to group findings with the same root cause. If one was not set with
+    /// [Finding::bucket], it is derived from the kind and the innermost stack frames (or the
+    /// message if there is no stack).
+    pub fn get_bucket(&self) -> String {
+        if let Some(ref bucket) = self.bucket {
+            return bucket.clone();
+        }
+
+        let mut hasher = DefaultHasher::new();
+        self.kind.hash(&mut hasher);
+        if self.stack.is_empty() {
+            self.message.hash(&mut hasher);
+        } else {
+            for frame in self.stack.iter().take(BUCKET_FRAMES) {
+                frame.hash(&mut hasher);
+            }
+        }
+
+        format!("{}-{:016x}", self.kind.name(), hasher.finish())
+    }
+
+    /// The seed to report, which is omitted if it can't be used to replay the finding
+    fn replay_seed(&self) -> Option<u64> {
+        if self.reproducible {
+            Some(self.seed)
+        } else {
+            None
+        }
+    }
+
+    fn operator_names(&self) -> Vec<String> {
+        self.operators
+            .iter()
+            .map(|operator| operator.name().to_string())
+            .collect()
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push('{');
+        write!(out, "\"kind\":{}", json_string(self.kind.name())).unwrap();
+        write!(out, ",\"bucket\":{}", json_string(&self.get_bucket())).unwrap();
+        write!(out, ",\"input_hash\":\"{:016x}\"", self.input_hash).unwrap();
+        write!(out, ",\"input_size\":{}", self.input_size).unwrap();
+        write!(out, ",\"seed\":{}", json_option(self.replay_seed())).unwrap();
+        write!(out, ",\"reproducible\":{}", self.reproducible).unwrap();
+        write!(out, ",\"iteration\":{}", json_option(self.iteration)).unwrap();
+        write!(out, ",\"thread\":{}", json_option(self.thread)).unwrap();
+        write!(out, ",\"peak_memory\":{}", json_option(self.peak_memory)).unwrap();
+        write!(out, ",\"message\":{}", json_string(&self.message)).unwrap();
+        write!(out, ",\"stack\":{}", json_string_array(&self.stack)).unwrap();
+        write!(
+            out,
+            ",\"operators\":{}",
+            json_string_array(&self.operator_names())
+        )
+        .unwrap();
+        out.push('}');
+    }
+
+    fn write_sarif_result(&self, out: &mut String) {
+        let message = if self.message.is_empty() {
+            format!("{} found by lain", self.kind.name())
+        } else {
+            self.message.clone()
+        };
+
+        out.push('{');
+        write!(out, "\"ruleId\":{}", json_string(self.kind.name())).unwrap();
+        write!(out, ",\"level\":{}", json_string(self.kind.sarif_level())).unwrap();
+        write!(out, ",\"message\":{{\"text\":{}}}", json_string(&message)).unwrap();
+        write!(
+            out,
+            ",\"partialFingerprints\":{{\"bucket\":{}}}",
+            json_string(&self.get_bucket())
+        )
+        .unwrap();
+
+        if !self.stack.is_empty() {
+            out.push_str(",\"stacks\":[{\"frames\":[");
+            for (i, frame) in self.stack.iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                write!(
+                    out,
+                    "{{\"location\":{{\"message\":{{\"text\":{}}}}}}}",
+                    json_string(frame)
+                )
+                .unwrap();
+            }
+            out.push_str("]}]");
+        }
+
+        write!(
+            out,
+            ",\"properties\":{{\"inputHash\":\"{:016x}\",\"inputSize\":{},\"seed\":{},\"reproducible\":{},\"iteration\":{},\"thread\":{},\"peakMemory\":{},\"operators\":{}}}",
+            self.input_hash,
+            self.input_size,
+            json_option(self.replay_seed()),
+            self.reproducible,
+            json_option(self.iteration),
+            json_option(self.thread),
+            json_option(self.peak_memory),
+            json_string_array(&self.operator_names())
+        )
+        .unwrap();
+        out.push('}');
+    }
+}
+
+/// Thread-safe collection of [Finding]s which can be exported as JSON or SARIF.
+#[derive(Debug, Default)]
+pub struct FindingsReport {
+    findings: Mutex<Vec<Finding>>,
+}
+
+impl FindingsReport {
+    pub fn new() -> Self {
+        FindingsReport::default()
+    }
+
+    /// Adds a finding to the report
+    pub fn record(&self, finding: Finding) {
+        info!(
+            "recorded {} in bucket {}",
+            finding.kind.name(),
+            finding.get_bucket()
+        );
+        self.findings.lock().unwrap().push(finding);
+    }
+
+    /// Returns a copy of every finding recorded so far
+    pub fn findings(&self) -> Vec<Finding> {
+        self.findings.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.findings.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of distinct buckets among the recorded findings
+    pub fn num_buckets(&self) -> usize {
+        let findings = self.findings.lock().unwrap();
+        let mut buckets: Vec<String> = findings.iter().map(Finding::get_bucket).collect();
+        buckets.sort();
+        buckets.dedup();
+
+        buckets.len()
+    }
+
+    /// Serializes the report as a JSON object with a `findings` array
+    pub fn to_json(&self) -> String {
+        let findings = self.findings.lock().unwrap();
+        let mut out = String::from("{\"tool\":\"lain\"");
+        write!(
+            out,
+            ",\"version\":{}",
+            json_string(env!("CARGO_PKG_VERSION"))
+        )
+        .unwrap();
+        out.push_str(",\"findings\":[");
+        for (i, finding) in findings.iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            finding.write_json(&mut out);
+        }
+        out.push_str("]}");
+
+        out
+    }
+
+    /// Serializes the report as a SARIF 2.1.0 log with one result per finding
+    pub fn to_sarif(&self) -> String {
+        let findings = self.findings.lock().unwrap();
+        let mut out = String::from(
+            "{\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\"version\":\"2.1.0\",\"runs\":[{",
+        );
+        write!(
+            out,
+            "\"tool\":{{\"driver\":{{\"name\":\"lain\",\"version\":{},\"rules\":[",
+            json_string(env!("CARGO_PKG_VERSION"))
+        )
+        .unwrap();
+        for (i, kind) in FindingKind::ALL.iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            write!(
+                out,
+                "{{\"id\":{},\"defaultConfiguration\":{{\"level\":{}}}}}",
+                json_string(kind.name()),
+                json_string(kind.sarif_level())
+            )
+            .unwrap();
+        }
+        out.push_str("]}},\"results\":[");
+        for (i, finding) in findings.iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            finding.write_sarif_result(&mut out);
+        }
+        out.push_str("]}]}");
+
+        out
+    }
+
+    /// Writes the output of [FindingsReport::to_json] to `path`
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Writes the output of [FindingsReport::to_sarif] to `path`
+    pub fn write_sarif<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_sarif())
+    }
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+
+    out
+}
+
+fn json_string_array(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|v| json_string(v)).collect();
+
+    format!("[{}]", items.join(","))
+}
+
+fn json_option<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| String::from("null"), |v| v.to_string())
+}
@@ -0,0 +1,1153 @@
+//! Fuzzable message formats described at runtime.
+//!
+//! Deriving lain's traits requires a Rust type for every message, and a rebuild of the harness
+//! whenever a format changes. A [Schema] instead describes a format as data, loosely modeled
+//! on Kaitai Struct's `.ksy` files, and generates, mutates, and serializes [Value]s against
+//! it:
+//!
+//! ```json
+//! {
+//!     "endian": "be",
+//!     "seq": [
+//!         { "id": "magic", "type": "u32", "contents": 3405691582 },
+//!         { "id": "kind", "type": "u8", "enum": { "ping": 1, "pong": 2 } },
+//!         { "id": "len", "type": "u16" },
+//!         { "id": "payload", "type": "bytes", "size": "len", "max_size": 64 },
+//!         { "id": "count", "type": "u8" },
+//!         { "id": "items", "type": "item", "repeat": "count", "max_repeat": 4 }
+//!     ],
+//!     "types": {
+//!         "item": { "seq": [{ "id": "tag", "type": "str", "size": 4 }] }
+//!     }
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! let schema = Schema::load("message.json")?;
+//! let mut message = schema.new_fuzzed(&mut mutator);
+//! schema.mutate(&mut message, &mut mutator);
+//! let bytes = schema.serialize(&message);
+//! ```
+//!
+//! Every struct (the schema itself, and each entry of `types`) has a `seq` of fields. A field
+//! has an `id` and a `type`, which is one of:
+//!
+//! - `u8`, `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, or `i64`, optionally with `min`/`max`
+//!   bounds (as with `#[lain(min, max)]`) or the `enum` of valid values. An `enum` is an object
+//!   of names and values, or the name of an entry of the top-level `enums` object. Integers use
+//!   the top-level `endian` (`be`, the default, or `le`) unless the field has its own.
+//! - `bytes`, or `str` for printable ASCII
+//! - the name of an entry of `types`
+//!
+//! `contents` gives a field a fixed value which is never mutated: an integer, or an array of
+//! bytes or a string for `bytes`/`str`. The `size` of `bytes`/`str` is a number of bytes or
+//! the id of an earlier integer field, which is then set to the actual size after generation
+//! and mutation like a `#[lain(length_of)]` field. Without a `size`, up to `max_size` (64 by
+//! default) bytes are generated. Fields with a `repeat` are lists of that many elements: a
+//! fixed count, or the id of an earlier integer field holding up to `max_repeat` (8 by default).
+
+use crate::byteorder::{BigEndian, ByteOrder, LittleEndian};
+use crate::mutator::Mutator;
+use crate::operators::MutationOperator;
+use crate::rand::Rng;
+use crate::traits::{Mutatable, NewFuzzed};
+use crate::types::{AsciiString, Constraints};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Size of `bytes`/`str` fields without a `size`, unless set by `max_size`
+pub const DEFAULT_MAX_SIZE: usize = 64;
+
+/// Number of elements of a repeated field counted by another field, unless set by `max_repeat`
+pub const DEFAULT_MAX_REPEAT: usize = 8;
+
+/// Chance for a field to be mutated when its struct is
+const FIELD_MUTATION_CHANCE: f64 = 0.98;
+
+/// Chance for a list of variable length to gain or lose an element instead of having its
+/// elements mutated
+const CHANCE_TO_RESIZE_LIST: f64 = 0.10;
+
+/// A value of a type described by a [Schema]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(i128),
+    Bytes(Vec<u8>),
+    /// The fields of a struct, in declaration order
+    Struct(Vec<(String, Value)>),
+    /// The elements of a field with a `repeat`
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// The value of the field `id` of a struct
+    pub fn field(&self, id: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(fields) => fields.iter().find(|(name, _)| name == id).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn field_mut(&mut self, id: &str) -> Option<&mut Value> {
+        match self {
+            Value::Struct(fields) => fields
+                .iter_mut()
+                .find(|(name, _)| name == id)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(elements) => Some(elements),
+            _ => None,
+        }
+    }
+}
+
+/// Errors which may occur while loading a [Schema]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// The schema file could not be read
+    Io(String),
+    /// The schema isn't valid JSON
+    Json { offset: usize, message: String },
+    /// The schema is valid JSON but doesn't describe a format. `path` locates the problem, as
+    /// in `types.item.seq[0].size`.
+    Invalid { path: String, message: String },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::Io(message) => write!(f, "could not read schema: {}", message),
+            SchemaError::Json { offset, message } => {
+                write!(f, "invalid JSON at offset {}: {}", offset, message)
+            }
+            SchemaError::Invalid { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+fn invalid<T>(path: &str, message: impl Into<String>) -> Result<T, SchemaError> {
+    Err(SchemaError::Invalid {
+        path: path.to_string(),
+        message: message.into(),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IntType {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+impl IntType {
+    fn from_name(name: &str) -> Option<IntType> {
+        match name {
+            "u8" => Some(IntType::U8),
+            "u16" => Some(IntType::U16),
+            "u32" => Some(IntType::U32),
+            "u64" => Some(IntType::U64),
+            "i8" => Some(IntType::I8),
+            "i16" => Some(IntType::I16),
+            "i32" => Some(IntType::I32),
+            "i64" => Some(IntType::I64),
+            _ => None,
+        }
+    }
+
+    fn width(self) -> usize {
+        match self {
+            IntType::U8 | IntType::I8 => 1,
+            IntType::U16 | IntType::I16 => 2,
+            IntType::U32 | IntType::I32 => 4,
+            IntType::U64 | IntType::I64 => 8,
+        }
+    }
+}
+
+/// Evaluates `$body` with `$t` bound to the Rust type of `$ty`, an [IntType]
+macro_rules! with_int_type {
+    ($ty:expr, $t:ident => $body:expr) => {
+        match $ty {
+            IntType::U8 => {
+                type $t = u8;
+                $body
+            }
+            IntType::U16 => {
+                type $t = u16;
+                $body
+            }
+            IntType::U32 => {
+                type $t = u32;
+                $body
+            }
+            IntType::U64 => {
+                type $t = u64;
+                $body
+            }
+            IntType::I8 => {
+                type $t = i8;
+                $body
+            }
+            IntType::I16 => {
+                type $t = i16;
+                $body
+            }
+            IntType::I32 => {
+                type $t = i32;
+                $body
+            }
+            IntType::I64 => {
+                type $t = i64;
+                $body
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone)]
+enum FieldType {
+    Int {
+        ty: IntType,
+        big_endian: bool,
+        min: Option<i128>,
+        max: Option<i128>,
+        valid: Option<Vec<i128>>,
+    },
+    Bytes {
+        printable: bool,
+    },
+    Struct(String),
+}
+
+/// A number of bytes or elements
+#[derive(Debug, Clone)]
+enum Count {
+    Fixed(usize),
+    /// Held by the named integer field, with an upper bound for generation
+    Field(String, usize),
+    Variable(usize),
+}
+
+impl Count {
+    fn generate<R: Rng>(&self, mutator: &mut Mutator<R>) -> usize {
+        match *self {
+            Count::Fixed(count) => count,
+            Count::Field(_, max) | Count::Variable(max) => mutator.gen_range(0, max + 1),
+        }
+    }
+
+    fn max(&self) -> usize {
+        match *self {
+            Count::Fixed(count) | Count::Field(_, count) | Count::Variable(count) => count,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FieldDef {
+    id: String,
+    ty: FieldType,
+    /// Size of `bytes`/`str` fields
+    size: Count,
+    repeat: Option<Count>,
+    contents: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+struct StructDef {
+    fields: Vec<FieldDef>,
+}
+
+/// A message format loaded at runtime. See the [module documentation][self] for the format.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: StructDef,
+    types: HashMap<String, StructDef>,
+}
+
+impl Schema {
+    /// Loads the JSON schema at `path`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Schema, SchemaError> {
+        let text = std::fs::read_to_string(path).map_err(|e| SchemaError::Io(e.to_string()))?;
+
+        Schema::from_json(&text)
+    }
+
+    pub fn from_json(text: &str) -> Result<Schema, SchemaError> {
+        let json = Json::parse(text)?;
+        let top = json.as_object("")?;
+
+        let big_endian = match get(top, "endian") {
+            Some(endian) => parse_endian(endian, "endian")?,
+            None => true,
+        };
+
+        let mut enums = HashMap::new();
+        if let Some(defs) = get(top, "enums") {
+            for (name, values) in defs.as_object("enums")? {
+                let path = format!("enums.{}", name);
+                enums.insert(name.clone(), parse_enum_values(values, &path)?);
+            }
+        }
+
+        let mut type_names = vec![];
+        if let Some(defs) = get(top, "types") {
+            type_names = defs
+                .as_object("types")?
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect();
+        }
+
+        let parser = SchemaParser {
+            big_endian,
+            enums: &enums,
+            type_names: &type_names,
+        };
+
+        let root = parser.parse_struct(top, "")?;
+        let mut types = HashMap::new();
+        if let Some(defs) = get(top, "types") {
+            for (name, def) in defs.as_object("types")? {
+                let path = format!("types.{}", name);
+                types.insert(
+                    name.clone(),
+                    parser.parse_struct(def.as_object(&path)?, &path)?,
+                );
+            }
+        }
+
+        let schema = Schema { root, types };
+        schema.check_recursion()?;
+
+        Ok(schema)
+    }
+
+    /// Rejects types which contain themselves, which would never finish generating
+    fn check_recursion(&self) -> Result<(), SchemaError> {
+        fn visit<'a>(
+            schema: &'a Schema,
+            def: &'a StructDef,
+            stack: &mut Vec<&'a str>,
+        ) -> Result<(), SchemaError> {
+            for field in &def.fields {
+                if let FieldType::Struct(ref name) = field.ty {
+                    if stack.contains(&name.as_str()) {
+                        return invalid(&format!("types.{}", name), "type contains itself");
+                    }
+
+                    stack.push(name);
+                    visit(schema, &schema.types[name], stack)?;
+                    stack.pop();
+                }
+            }
+
+            Ok(())
+        }
+
+        visit(self, &self.root, &mut vec![])
+    }
+
+    /// Generates a new value of the schema's top-level struct
+    pub fn new_fuzzed<R: Rng>(&self, mutator: &mut Mutator<R>) -> Value {
+        self.new_struct(&self.root, mutator)
+    }
+
+    /// Mutates `value`, which must have been generated from this schema
+    pub fn mutate<R: Rng>(&self, value: &mut Value, mutator: &mut Mutator<R>) {
+        self.mutate_struct(&self.root, value, mutator);
+    }
+
+    /// Serializes `value`. Panics if `value` doesn't have the shape described by the schema.
+    pub fn serialize(&self, value: &Value) -> Vec<u8> {
+        let mut buffer = vec![];
+        self.serialize_struct(&self.root, value, &mut buffer);
+
+        buffer
+    }
+
+    fn struct_def(&self, name: &str) -> &StructDef {
+        &self.types[name]
+    }
+
+    fn new_struct<R: Rng>(&self, def: &StructDef, mutator: &mut Mutator<R>) -> Value {
+        mutator.descend();
+        let fields = def
+            .fields
+            .iter()
+            .map(|field| (field.id.clone(), self.new_field(field, mutator)))
+            .collect();
+        mutator.ascend();
+
+        let mut value = Value::Struct(fields);
+        fix_counts(def, &mut value, mutator);
+
+        value
+    }
+
+    fn new_field<R: Rng>(&self, field: &FieldDef, mutator: &mut Mutator<R>) -> Value {
+        if let Some(ref contents) = field.contents {
+            return contents.clone();
+        }
+
+        match field.repeat {
+            Some(ref repeat) => {
+                let count = repeat.generate(mutator);
+                Value::List(
+                    (0..count)
+                        .map(|_| self.new_element(field, mutator))
+                        .collect(),
+                )
+            }
+            None => self.new_element(field, mutator),
+        }
+    }
+
+    fn new_element<R: Rng>(&self, field: &FieldDef, mutator: &mut Mutator<R>) -> Value {
+        match field.ty {
+            FieldType::Int {
+                ty,
+                min,
+                max,
+                ref valid,
+                ..
+            } => Value::Int(new_int(ty, min, max, valid.as_deref(), mutator)),
+            FieldType::Bytes { printable } => {
+                let mut constraints = Constraints::new();
+                match field.size {
+                    Count::Fixed(size) => constraints.exact_size(size),
+                    _ => constraints.max_size(field.size.max()),
+                };
+
+                let bytes = if printable {
+                    AsciiString::new_fuzzed(mutator, Some(&constraints))
+                        .inner
+                        .iter()
+                        .map(|c| c.0 as u8)
+                        .collect()
+                } else {
+                    Vec::<u8>::new_fuzzed(mutator, Some(&constraints))
+                };
+
+                Value::Bytes(bytes)
+            }
+            FieldType::Struct(ref name) => self.new_struct(self.struct_def(name), mutator),
+        }
+    }
+
+    fn mutate_struct<R: Rng>(&self, def: &StructDef, value: &mut Value, mutator: &mut Mutator<R>) {
+        let fields = match value {
+            Value::Struct(fields) => fields,
+            _ => panic!("value doesn't match the schema"),
+        };
+
+        mutator.descend();
+        for (field, (_, value)) in def.fields.iter().zip(fields.iter_mut()) {
+            if field.contents.is_none() && mutator.gen_chance(FIELD_MUTATION_CHANCE) {
+                self.mutate_field(field, value, mutator);
+            }
+
+            if mutator.should_early_bail_mutation() {
+                break;
+            }
+        }
+        mutator.ascend();
+
+        fix_counts(def, value, mutator);
+    }
+
+    fn mutate_field<R: Rng>(&self, field: &FieldDef, value: &mut Value, mutator: &mut Mutator<R>) {
+        let repeat = match field.repeat {
+            Some(ref repeat) => repeat,
+            None => return self.mutate_element(field, value, mutator),
+        };
+
+        let elements = match value {
+            Value::List(elements) => elements,
+            _ => panic!("value doesn't match the schema"),
+        };
+
+        let resizable = !matches!(repeat, Count::Fixed(_));
+        if resizable && mutator.gen_chance(CHANCE_TO_RESIZE_LIST) {
+            if elements.len() < repeat.max() && (elements.is_empty() || mutator.gen()) {
+                mutator.record_operator(MutationOperator::GrowList);
+                let position = mutator.gen_range(0, elements.len() + 1);
+                let element = self.new_element(field, mutator);
+                elements.insert(position, element);
+            } else if !elements.is_empty() {
+                mutator.record_operator(MutationOperator::ShrinkList);
+                let position = mutator.gen_range(0, elements.len());
+                elements.remove(position);
+            }
+
+            return;
+        }
+
+        for element in elements.iter_mut() {
+            self.mutate_element(field, element, mutator);
+        }
+    }
+
+    fn mutate_element<R: Rng>(
+        &self,
+        field: &FieldDef,
+        value: &mut Value,
+        mutator: &mut Mutator<R>,
+    ) {
+        match (&field.ty, value) {
+            (
+                FieldType::Int {
+                    ty,
+                    min,
+                    max,
+                    valid,
+                    ..
+                },
+                Value::Int(value),
+            ) => {
+                *value = mutate_int(*ty, *value, *min, *max, valid.as_deref(), mutator);
+            }
+            (FieldType::Bytes { .. }, Value::Bytes(bytes)) => match field.size {
+                Count::Fixed(_) => bytes.as_mut_slice().mutate(mutator, None),
+                _ => {
+                    let mut constraints = Constraints::new();
+                    constraints.max_size(field.size.max());
+                    bytes.mutate(mutator, Some(&constraints));
+                }
+            },
+            (FieldType::Struct(name), value) => {
+                self.mutate_struct(self.struct_def(name), value, mutator)
+            }
+            _ => panic!("value doesn't match the schema"),
+        }
+    }
+
+    fn serialize_struct(&self, def: &StructDef, value: &Value, buffer: &mut Vec<u8>) {
+        let fields = match value {
+            Value::Struct(fields) => fields,
+            _ => panic!("value doesn't match the schema"),
+        };
+
+        assert_eq!(
+            fields.len(),
+            def.fields.len(),
+            "value doesn't match the schema"
+        );
+        for (field, (_, value)) in def.fields.iter().zip(fields.iter()) {
+            match (&field.repeat, value) {
+                (Some(_), Value::List(elements)) => {
+                    for element in elements {
+                        self.serialize_element(field, element, buffer);
+                    }
+                }
+                (Some(_), _) => panic!("value doesn't match the schema"),
+                (None, value) => self.serialize_element(field, value, buffer),
+            }
+        }
+    }
+
+    fn serialize_element(&self, field: &FieldDef, value: &Value, buffer: &mut Vec<u8>) {
+        match (&field.ty, value) {
+            (FieldType::Int { ty, big_endian, .. }, Value::Int(value)) => {
+                let width = ty.width();
+                // values which don't fit (e.g. corrupted lengths) are truncated
+                let bits = *value as u128 as u64;
+                let bits = if width == 8 {
+                    bits
+                } else {
+                    bits & ((1u64 << (width * 8)) - 1)
+                };
+
+                let mut bytes = [0u8; 8];
+                if *big_endian {
+                    BigEndian::write_uint(&mut bytes, bits, width);
+                } else {
+                    LittleEndian::write_uint(&mut bytes, bits, width);
+                }
+
+                buffer.extend_from_slice(&bytes[..width]);
+            }
+            (FieldType::Bytes { .. }, Value::Bytes(bytes)) => buffer.extend_from_slice(bytes),
+            (FieldType::Struct(name), value) => {
+                self.serialize_struct(self.struct_def(name), value, buffer)
+            }
+            _ => panic!("value doesn't match the schema"),
+        }
+    }
+}
+
+/// Sets the integer fields holding the sizes and counts of other fields of `value`
+fn fix_counts<R: Rng>(def: &StructDef, value: &mut Value, mutator: &mut Mutator<R>) {
+    for field in &def.fields {
+        let counter = match (&field.repeat, &field.size) {
+            (Some(Count::Field(counter, _)), _) => counter,
+            (None, Count::Field(counter, _)) => counter,
+            _ => continue,
+        };
+
+        let len = match value.field(&field.id) {
+            Some(Value::List(elements)) => elements.len(),
+            Some(Value::Bytes(bytes)) => bytes.len(),
+            _ => continue,
+        };
+
+        if let Some(Value::Int(count)) = value.field_mut(counter) {
+            mutator.fix_length(count, len);
+        }
+    }
+}
+
+fn new_int<R: Rng>(
+    ty: IntType,
+    min: Option<i128>,
+    max: Option<i128>,
+    valid: Option<&[i128]>,
+    mutator: &mut Mutator<R>,
+) -> i128 {
+    if let Some(valid) = valid {
+        if !mutator.gen_chance(mutator.invalid_enum_chance()) {
+            return valid[mutator.gen_range(0, valid.len())];
+        }
+    }
+
+    with_int_type!(ty, T => {
+        let mut constraints = Constraints::new();
+        if let Some(min) = min {
+            constraints.min(min as T);
+        }
+        if let Some(max) = max {
+            constraints.max(max as T);
+        }
+
+        T::new_fuzzed(mutator, Some(&constraints)) as i128
+    })
+}
+
+fn mutate_int<R: Rng>(
+    ty: IntType,
+    value: i128,
+    min: Option<i128>,
+    max: Option<i128>,
+    valid: Option<&[i128]>,
+    mutator: &mut Mutator<R>,
+) -> i128 {
+    // like derived enums, values of enums are picked anew
+    if valid.is_some() {
+        return new_int(ty, min, max, valid, mutator);
+    }
+
+    with_int_type!(ty, T => {
+        let mut value = value as T;
+        value.mutate(mutator, None);
+
+        value as i128
+    })
+}
+
+/// Resolves the parts of a field definition which refer to the rest of the schema
+struct SchemaParser<'a> {
+    big_endian: bool,
+    enums: &'a HashMap<String, Vec<i128>>,
+    type_names: &'a [String],
+}
+
+impl<'a> SchemaParser<'a> {
+    fn parse_struct(&self, def: &[(String, Json)], path: &str) -> Result<StructDef, SchemaError> {
+        let seq_path = if path.is_empty() {
+            "seq".to_string()
+        } else {
+            format!("{}.seq", path)
+        };
+
+        let seq = match get(def, "seq") {
+            Some(seq) => seq.as_array(&seq_path)?,
+            None => return invalid(path, "missing `seq`"),
+        };
+
+        let mut fields: Vec<FieldDef> = vec![];
+        for (i, field) in seq.iter().enumerate() {
+            let path = format!("{}[{}]", seq_path, i);
+            let field = self.parse_field(field.as_object(&path)?, &fields, &path)?;
+            if fields.iter().any(|f| f.id == field.id) {
+                return invalid(&path, format!("duplicate field `{}`", field.id));
+            }
+
+            fields.push(field);
+        }
+
+        Ok(StructDef { fields })
+    }
+
+    fn parse_field(
+        &self,
+        def: &[(String, Json)],
+        earlier: &[FieldDef],
+        path: &str,
+    ) -> Result<FieldDef, SchemaError> {
+        let id = match get(def, "id") {
+            Some(id) => id.as_str(&format!("{}.id", path))?.to_string(),
+            None => return invalid(path, "missing `id`"),
+        };
+
+        let type_name = match get(def, "type") {
+            Some(ty) => ty.as_str(&format!("{}.type", path))?,
+            None => return invalid(path, "missing `type`"),
+        };
+
+        let ty = if let Some(ty) = IntType::from_name(type_name) {
+            let big_endian = match get(def, "endian") {
+                Some(endian) => parse_endian(endian, &format!("{}.endian", path))?,
+                None => self.big_endian,
+            };
+
+            let bound = |key: &str| -> Result<Option<i128>, SchemaError> {
+                get(def, key)
+                    .map(|v| v.as_int(&format!("{}.{}", path, key)))
+                    .transpose()
+            };
+
+            let valid = match get(def, "enum") {
+                Some(Json::String(name)) => match self.enums.get(name) {
+                    Some(values) => Some(values.clone()),
+                    None => return invalid(path, format!("unknown enum `{}`", name)),
+                },
+                Some(values) => Some(parse_enum_values(values, &format!("{}.enum", path))?),
+                None => None,
+            };
+
+            FieldType::Int {
+                ty,
+                big_endian,
+                min: bound("min")?,
+                max: bound("max")?,
+                valid,
+            }
+        } else if type_name == "bytes" || type_name == "str" {
+            FieldType::Bytes {
+                printable: type_name == "str",
+            }
+        } else if self.type_names.iter().any(|name| name == type_name) {
+            FieldType::Struct(type_name.to_string())
+        } else {
+            return invalid(path, format!("unknown type `{}`", type_name));
+        };
+
+        let max_size = get(def, "max_size")
+            .map(|v| v.as_usize(&format!("{}.max_size", path)))
+            .transpose()?
+            .unwrap_or(DEFAULT_MAX_SIZE);
+        let size = match get(def, "size") {
+            Some(size) => parse_count(size, max_size, earlier, &format!("{}.size", path))?,
+            None => Count::Variable(max_size),
+        };
+
+        let max_repeat = get(def, "max_repeat")
+            .map(|v| v.as_usize(&format!("{}.max_repeat", path)))
+            .transpose()?
+            .unwrap_or(DEFAULT_MAX_REPEAT);
+        let repeat = get(def, "repeat")
+            .map(|v| parse_count(v, max_repeat, earlier, &format!("{}.repeat", path)))
+            .transpose()?;
+
+        if get(def, "size").is_some() && !matches!(ty, FieldType::Bytes { .. }) {
+            return invalid(path, "only `bytes` and `str` fields have a `size`");
+        }
+
+        if repeat.is_some() && matches!(size, Count::Field(..)) {
+            return invalid(
+                path,
+                "repeated fields can't take their size from another field",
+            );
+        }
+
+        let contents = match get(def, "contents") {
+            Some(contents) => {
+                if repeat.is_some() {
+                    return invalid(path, "repeated fields can't have `contents`");
+                }
+
+                let contents_path = format!("{}.contents", path);
+                Some(match ty {
+                    FieldType::Int { .. } => Value::Int(contents.as_int(&contents_path)?),
+                    FieldType::Bytes { .. } => Value::Bytes(contents.as_bytes(&contents_path)?),
+                    FieldType::Struct(_) => {
+                        return invalid(path, "struct fields can't have `contents`")
+                    }
+                })
+            }
+            None => None,
+        };
+
+        Ok(FieldDef {
+            id,
+            ty,
+            size,
+            repeat,
+            contents,
+        })
+    }
+}
+
+fn parse_endian(endian: &Json, path: &str) -> Result<bool, SchemaError> {
+    match endian.as_str(path)? {
+        "be" => Ok(true),
+        "le" => Ok(false),
+        other => invalid(path, format!("unknown byte order `{}`", other)),
+    }
+}
+
+fn parse_enum_values(values: &Json, path: &str) -> Result<Vec<i128>, SchemaError> {
+    let values = values
+        .as_object(path)?
+        .iter()
+        .map(|(name, value)| value.as_int(&format!("{}.{}", path, name)))
+        .collect::<Result<Vec<i128>, SchemaError>>()?;
+
+    if values.is_empty() {
+        return invalid(path, "enums need at least one value");
+    }
+
+    Ok(values)
+}
+
+/// Parses a `size` or `repeat`: a fixed count, or the id of an earlier integer field
+fn parse_count(
+    count: &Json,
+    max: usize,
+    earlier: &[FieldDef],
+    path: &str,
+) -> Result<Count, SchemaError> {
+    let counter = match count {
+        Json::String(counter) => counter,
+        _ => return Ok(Count::Fixed(count.as_usize(path)?)),
+    };
+
+    match earlier.iter().find(|field| &field.id == counter) {
+        Some(FieldDef {
+            ty: FieldType::Int { .. },
+            repeat: None,
+            contents: None,
+            ..
+        }) => Ok(Count::Field(counter.clone(), max)),
+        Some(_) => invalid(
+            path,
+            format!("`{}` isn't an integer field without `contents`", counter),
+        ),
+        None => invalid(path, format!("`{}` isn't declared earlier", counter)),
+    }
+}
+
+fn get<'a>(object: &'a [(String, Json)], key: &str) -> Option<&'a Json> {
+    object.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+/// A parsed JSON document. Numbers must be integers.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Int(i128),
+    String(String),
+    Array(Vec<Json>),
+    /// Members in document order
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(text: &str) -> Result<Json, SchemaError> {
+        let mut parser = JsonParser {
+            bytes: text.as_bytes(),
+            offset: 0,
+        };
+
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.offset != parser.bytes.len() {
+            return parser.error("trailing characters");
+        }
+
+        Ok(value)
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Json::Null => "null",
+            Json::Bool(_) => "a boolean",
+            Json::Int(_) => "an integer",
+            Json::String(_) => "a string",
+            Json::Array(_) => "an array",
+            Json::Object(_) => "an object",
+        }
+    }
+
+    fn expected<T>(&self, path: &str, expected: &str) -> Result<T, SchemaError> {
+        invalid(
+            path,
+            format!("expected {}, found {}", expected, self.kind()),
+        )
+    }
+
+    fn as_object(&self, path: &str) -> Result<&[(String, Json)], SchemaError> {
+        match self {
+            Json::Object(members) => Ok(members),
+            _ => self.expected(path, "an object"),
+        }
+    }
+
+    fn as_array(&self, path: &str) -> Result<&[Json], SchemaError> {
+        match self {
+            Json::Array(elements) => Ok(elements),
+            _ => self.expected(path, "an array"),
+        }
+    }
+
+    fn as_str(&self, path: &str) -> Result<&str, SchemaError> {
+        match self {
+            Json::String(s) => Ok(s),
+            _ => self.expected(path, "a string"),
+        }
+    }
+
+    fn as_int(&self, path: &str) -> Result<i128, SchemaError> {
+        match self {
+            Json::Int(i) => Ok(*i),
+            _ => self.expected(path, "an integer"),
+        }
+    }
+
+    fn as_usize(&self, path: &str) -> Result<usize, SchemaError> {
+        match self {
+            Json::Int(i) if *i >= 0 && *i <= usize::MAX as i128 => Ok(*i as usize),
+            _ => self.expected(path, "a non-negative integer"),
+        }
+    }
+
+    /// An array of bytes, or the bytes of a string
+    fn as_bytes(&self, path: &str) -> Result<Vec<u8>, SchemaError> {
+        match self {
+            Json::String(s) => Ok(s.as_bytes().to_vec()),
+            Json::Array(elements) => elements
+                .iter()
+                .map(|element| match element {
+                    Json::Int(i) if (0..=0xFF).contains(i) => Ok(*i as u8),
+                    _ => element.expected(path, "a byte"),
+                })
+                .collect(),
+            _ => self.expected(path, "an array of bytes or a string"),
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn error<T>(&self, message: &str) -> Result<T, SchemaError> {
+        Err(SchemaError::Json {
+            offset: self.offset,
+            message: message.to_string(),
+        })
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.offset).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.offset += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), SchemaError> {
+        if self.bytes[self.offset..].starts_with(literal.as_bytes()) {
+            self.offset += literal.len();
+            Ok(())
+        } else {
+            self.error(&format!("expected `{}`", literal))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, SchemaError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => self.error("expected a value"),
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, SchemaError> {
+        self.expect("{")?;
+        let mut members = vec![];
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.offset += 1;
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            let value = self.value()?;
+            members.push((key, value));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b'}') => {
+                    self.offset += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return self.error("expected `,` or `}`"),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, SchemaError> {
+        self.expect("[")?;
+        let mut elements = vec![];
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.offset += 1;
+            return Ok(Json::Array(elements));
+        }
+
+        loop {
+            elements.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b']') => {
+                    self.offset += 1;
+                    return Ok(Json::Array(elements));
+                }
+                _ => return self.error("expected `,` or `]`"),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, SchemaError> {
+        self.expect("\"")?;
+        let mut s = String::new();
+
+        loop {
+            let start = self.offset;
+            while let Some(b) = self.peek() {
+                if b == b'"' || b == b'\\' || b < 0x20 {
+                    break;
+                }
+                self.offset += 1;
+            }
+
+            // the input is a &str and we only stopped at ASCII, so this is a char boundary
+            s.push_str(std::str::from_utf8(&self.bytes[start..self.offset]).unwrap());
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.offset += 1;
+                    return Ok(s);
+                }
+                Some(b'\\') => {
+                    self.offset += 1;
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let hex = self
+                                .bytes
+                                .get(self.offset + 1..self.offset + 5)
+                                .and_then(|hex| std::str::from_utf8(hex).ok())
+                                .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+
+                            match hex.and_then(std::char::from_u32) {
+                                Some(c) => {
+                                    self.offset += 4;
+                                    c
+                                }
+                                None => return self.error("invalid unicode escape"),
+                            }
+                        }
+                        _ => return self.error("invalid escape"),
+                    };
+
+                    self.offset += 1;
+                    s.push(escaped);
+                }
+                Some(_) => return self.error("control character in string"),
+                None => return self.error("unterminated string"),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, SchemaError> {
+        let start = self.offset;
+        if self.peek() == Some(b'-') {
+            self.offset += 1;
+        }
+
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.offset += 1;
+        }
+
+        if let Some(b'.') | Some(b'e') | Some(b'E') = self.peek() {
+            return self.error("only integers are supported");
+        }
+
+        // only ASCII digits and `-` were consumed
+        let text = std::str::from_utf8(&self.bytes[start..self.offset]).unwrap();
+        match text.parse::<i128>() {
+            Ok(i) => Ok(Json::Int(i)),
+            Err(_) => {
+                self.offset = start;
+                self.error("invalid integer")
+            }
+        }
+    }
+}
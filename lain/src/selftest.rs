@@ -0,0 +1,319 @@
+//! Consistency checks for data models, meant to be run before starting a fuzzing campaign.
+//!
+//! [selftest] generates and mutates a number of instances of a type and checks that its trait
+//! implementations agree with one another: the reported
+//! [serialized_size][SerializedSize::serialized_size] matches the number of bytes written, the
+//! field layout fits within the output, generation is reproducible from a seed, and (if a parser
+//! is provided with [SelfTest::round_trip]) the serialized bytes parse back into an equal value.
+//! Mistakes in hand-written trait impls or derive attributes tend to show up here long before
+//! they show up as confusing fuzzer behavior.
+//!
+//! ```compile_fail
+//! // e.g. in a unit test alongside the data model
+//! lain::selftest::<Packet>();
+//! ```
+
+use crate::byteorder::{BigEndian, ByteOrder, LittleEndian};
+use crate::mutator::Mutator;
+use crate::rand::rngs::StdRng;
+use crate::rand::SeedableRng;
+use crate::traits::{BinarySerialize, Mutatable, NewFuzzed, SerializedSize};
+use crate::types::{Constraints, FieldSpan};
+use std::fmt::{self, Debug};
+
+pub const DEFAULT_SELFTEST_ITERATIONS: usize = 100;
+pub const DEFAULT_SELFTEST_MUTATIONS: usize = 8;
+
+/// Failures past this count are counted but not recorded
+const MAX_RECORDED_FAILURES: usize = 16;
+
+type RoundTrip<T> = Box<dyn Fn(&T) -> Result<(), String>>;
+
+/// A single failed check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestFailure {
+    /// The instance being checked
+    pub iteration: usize,
+    /// Seed the instance was generated from
+    pub seed: u64,
+    /// What was being done when the check failed (e.g. `new_fuzzed` or `mutation 3`)
+    pub stage: String,
+    pub message: String,
+}
+
+impl fmt::Display for SelfTestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "instance {} (seed 0x{:X}) after {}: {}",
+            self.iteration, self.seed, self.stage, self.message
+        )
+    }
+}
+
+/// Returned by [SelfTest::run] when one or more checks failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestError {
+    pub type_name: &'static str,
+    /// The first failures encountered
+    pub failures: Vec<SelfTestFailure>,
+    /// Total number of failed checks, including those not recorded in `failures`
+    pub total_failures: usize,
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "self-test of {} failed {} check(s):",
+            self.type_name, self.total_failures
+        )?;
+
+        for failure in self.failures.iter() {
+            writeln!(f, "  {}", failure)?;
+        }
+
+        if self.total_failures > self.failures.len() {
+            write!(
+                f,
+                "  ... and {} more",
+                self.total_failures - self.failures.len()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for SelfTestError {}
+
+/// Configurable consistency check of `T`'s trait implementations.
+pub struct SelfTest<T> {
+    iterations: usize,
+    mutations: usize,
+    seed: u64,
+    max_size: Option<usize>,
+    round_trip: Option<RoundTrip<T>>,
+}
+
+impl<T> Default for SelfTest<T>
+where
+    T: NewFuzzed + Mutatable + BinarySerialize + SerializedSize + Debug,
+{
+    fn default() -> Self {
+        SelfTest::new()
+    }
+}
+
+impl<T> SelfTest<T>
+where
+    T: NewFuzzed + Mutatable + BinarySerialize + SerializedSize + Debug,
+{
+    pub fn new() -> Self {
+        SelfTest {
+            iterations: DEFAULT_SELFTEST_ITERATIONS,
+            mutations: DEFAULT_SELFTEST_MUTATIONS,
+            seed: 0,
+            max_size: None,
+            round_trip: None,
+        }
+    }
+
+    /// Number of instances to generate
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Number of times each instance is mutated (and re-checked) after being generated
+    pub fn mutations(mut self, mutations: usize) -> Self {
+        self.mutations = mutations;
+        self
+    }
+
+    /// Root seed. Instance `n` is generated from `seed + n`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generates and mutates instances with a `max_size` constraint and checks that it is
+    /// respected
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Checks that every instance serialized with the byte order `E` is parsed back into an
+    /// equal value by `parse`
+    pub fn round_trip<E, F>(mut self, parse: F) -> Self
+    where
+        E: ByteOrder + 'static,
+        F: Fn(&[u8]) -> Option<T> + 'static,
+        T: PartialEq,
+    {
+        self.round_trip = Some(Box::new(move |value: &T| {
+            let mut bytes = vec![];
+            value.binary_serialize::<_, E>(&mut bytes);
+
+            match parse(&bytes) {
+                Some(ref parsed) if parsed == value => Ok(()),
+                Some(parsed) => Err(format!(
+                    "round trip produced a different value: {:?}",
+                    parsed
+                )),
+                None => Err(String::from("serialized bytes could not be parsed")),
+            }
+        }));
+        self
+    }
+
+    /// Runs every check, returning all failures found
+    pub fn run(&self) -> Result<(), SelfTestError> {
+        let mut error = SelfTestError {
+            type_name: std::any::type_name::<T>(),
+            failures: vec![],
+            total_failures: 0,
+        };
+
+        let new_constraints = self.max_size.map(|max_size| {
+            let mut c = Constraints::<<T as NewFuzzed>::RangeType>::new();
+            c.max_size(max_size);
+            c
+        });
+        let mutate_constraints = self.max_size.map(|max_size| {
+            let mut c = Constraints::<<T as Mutatable>::RangeType>::new();
+            c.max_size(max_size);
+            c
+        });
+
+        for iteration in 0..self.iterations {
+            let seed = self.seed.wrapping_add(iteration as u64);
+            let mut record = |stage: &str, message: String| {
+                error.total_failures += 1;
+                if error.failures.len() < MAX_RECORDED_FAILURES {
+                    error.failures.push(SelfTestFailure {
+                        iteration,
+                        seed,
+                        stage: stage.to_string(),
+                        message,
+                    });
+                }
+            };
+
+            let mut mutator = Mutator::new(StdRng::seed_from_u64(seed));
+            let mut value = T::new_fuzzed(&mut mutator, new_constraints.as_ref());
+
+            let mut replay = Mutator::new(StdRng::seed_from_u64(seed));
+            let replayed = T::new_fuzzed(&mut replay, new_constraints.as_ref());
+            if serialize::<T, BigEndian>(&value).0 != serialize::<T, BigEndian>(&replayed).0 {
+                record(
+                    "new_fuzzed",
+                    String::from("generating from the same seed produced different output"),
+                );
+            }
+
+            for message in self.check(&value) {
+                record("new_fuzzed", message);
+            }
+
+            for i in 0..self.mutations {
+                value.mutate(&mut mutator, mutate_constraints.as_ref());
+
+                let stage = format!("mutation {}", i + 1);
+                for message in self.check(&value) {
+                    record(&stage, message);
+                }
+            }
+        }
+
+        if error.total_failures == 0 {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Returns a description of each check `value` fails
+    fn check(&self, value: &T) -> Vec<String> {
+        let mut messages = vec![];
+
+        let (bytes, written) = serialize::<T, BigEndian>(value);
+        let (le_bytes, _) = serialize::<T, LittleEndian>(value);
+        let size = value.serialized_size();
+
+        if written != bytes.len() {
+            messages.push(format!(
+                "binary_serialize reported writing {} bytes but wrote {}",
+                written,
+                bytes.len()
+            ));
+        }
+
+        if size != bytes.len() {
+            messages.push(format!(
+                "serialized_size is {} but {} bytes were written",
+                size,
+                bytes.len()
+            ));
+        }
+
+        if le_bytes.len() != bytes.len() {
+            messages.push(format!(
+                "serialized length depends on byte order ({} big endian, {} little endian)",
+                bytes.len(),
+                le_bytes.len()
+            ));
+        }
+
+        if let Some(max_size) = self.max_size {
+            if bytes.len() > max_size {
+                messages.push(format!(
+                    "serialized size {} exceeds max_size {}",
+                    bytes.len(),
+                    max_size
+                ));
+            }
+        }
+
+        let mut layout: Vec<FieldSpan> = vec![];
+        value.field_layout("", 0, &mut layout);
+        for span in layout.iter() {
+            if span.start > span.end || span.end > bytes.len() {
+                messages.push(format!(
+                    "field {} spans {}..{} which is outside of the {} serialized bytes",
+                    span.path,
+                    span.start,
+                    span.end,
+                    bytes.len()
+                ));
+            }
+        }
+
+        if let Some(round_trip) = self.round_trip.as_ref() {
+            if let Err(message) = round_trip(value) {
+                messages.push(message);
+            }
+        }
+
+        messages
+    }
+}
+
+fn serialize<T: BinarySerialize, E: ByteOrder>(value: &T) -> (Vec<u8>, usize) {
+    let mut bytes = vec![];
+    let written = value.binary_serialize::<_, E>(&mut bytes);
+
+    (bytes, written)
+}
+
+/// Runs [SelfTest] with its default settings and panics with a description of every failed
+/// check
+pub fn selftest<T>()
+where
+    T: NewFuzzed + Mutatable + BinarySerialize + SerializedSize + Debug,
+{
+    if let Err(e) = SelfTest::<T>::new().run() {
+        panic!("{}", e);
+    }
+}
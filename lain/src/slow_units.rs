@@ -0,0 +1,164 @@
+//! Detection of inputs which take unusually long to execute.
+//!
+//! Algorithmic-complexity bugs (quadratic parsers, catastrophic regex backtracking, hash
+//! flooding) rarely crash or hang the target outright; an input just takes far longer than
+//! usual. A [SlowUnitDetector] tracks the execution time of every iteration and flags the ones
+//! which are outliers as "slow units". The driver records them in its findings as
+//! [FindingKind::SlowUnit] and persists them to `slow/`:
+//!
+//! ```compile_fail
+//! // flag iterations which take more than 10x the median time
+//! driver.set_slow_unit_threshold(SlowUnitThreshold::MedianMultiple(10.0));
+//! ```
+//!
+//! [FindingKind::SlowUnit]: crate::report::FindingKind::SlowUnit
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Number of iterations observed before any are flagged
+pub const DEFAULT_MIN_SAMPLES: usize = 100;
+
+/// Iterations faster than this are never flagged, however much of an outlier they are
+pub const DEFAULT_MIN_SLOW_TIME: Duration = Duration::from_millis(1);
+
+/// Number of recent execution times the median is computed from
+const MEDIAN_WINDOW: usize = 0x400;
+
+/// How far above normal an execution time must be to be flagged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlowUnitThreshold {
+    /// More than this many standard deviations above the mean
+    StdDevs(f64),
+    /// More than this multiple of the median of recent execution times
+    MedianMultiple(f64),
+}
+
+impl fmt::Display for SlowUnitThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SlowUnitThreshold::StdDevs(n) => write!(f, "{} standard deviations", n),
+            SlowUnitThreshold::MedianMultiple(n) => write!(f, "{}x the median", n),
+        }
+    }
+}
+
+/// Running execution time statistics
+#[derive(Debug, Default)]
+struct ExecTimeStats {
+    count: usize,
+    /// Mean and sum of squared differences from it, in seconds (Welford's algorithm)
+    mean: f64,
+    m2: f64,
+    recent: VecDeque<f64>,
+}
+
+impl ExecTimeStats {
+    fn add(&mut self, secs: f64) {
+        self.count += 1;
+        let delta = secs - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (secs - self.mean);
+
+        if self.recent.len() == MEDIAN_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(secs);
+    }
+
+    fn std_dev(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            (self.m2 / (self.count - 1) as f64).sqrt()
+        }
+    }
+
+    fn median(&self) -> f64 {
+        let mut sorted: Vec<f64> = self.recent.iter().copied().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        sorted.get(sorted.len() / 2).copied().unwrap_or(0.0)
+    }
+}
+
+/// Flags execution times which are outliers compared to the ones observed before them. Safe to
+/// share between fuzzer threads.
+#[derive(Debug)]
+pub struct SlowUnitDetector {
+    threshold: SlowUnitThreshold,
+    min_samples: usize,
+    min_slow_time: Duration,
+    stats: Mutex<ExecTimeStats>,
+}
+
+impl SlowUnitDetector {
+    pub fn new(threshold: SlowUnitThreshold) -> Self {
+        SlowUnitDetector {
+            threshold,
+            min_samples: DEFAULT_MIN_SAMPLES,
+            min_slow_time: DEFAULT_MIN_SLOW_TIME,
+            stats: Mutex::new(ExecTimeStats::default()),
+        }
+    }
+
+    /// Sets the number of iterations observed before any are flagged
+    pub fn min_samples(mut self, samples: usize) -> Self {
+        self.min_samples = samples;
+        self
+    }
+
+    /// Sets the time below which iterations are never flagged
+    pub fn min_slow_time(mut self, time: Duration) -> Self {
+        self.min_slow_time = time;
+        self
+    }
+
+    pub fn threshold(&self) -> SlowUnitThreshold {
+        self.threshold
+    }
+
+    /// Records an iteration's execution time. Returns a description of why it's considered
+    /// slow if it's an outlier compared to the iterations observed before it.
+    pub fn observe(&self, elapsed: Duration) -> Option<String> {
+        let secs = elapsed.as_secs_f64();
+        let mut stats = self.stats.lock().unwrap();
+
+        let slow = if stats.count < self.min_samples || elapsed < self.min_slow_time {
+            None
+        } else {
+            let (limit, baseline) = match self.threshold {
+                SlowUnitThreshold::StdDevs(n) => {
+                    (stats.mean + n * stats.std_dev(), ("mean", stats.mean))
+                }
+                SlowUnitThreshold::MedianMultiple(n) => {
+                    let median = stats.median();
+                    (median * n, ("median", median))
+                }
+            };
+
+            if secs > limit {
+                Some(format!(
+                    "took {:?}, more than {} ({} {:?})",
+                    elapsed,
+                    self.threshold,
+                    baseline.0,
+                    Duration::from_secs_f64(baseline.1)
+                ))
+            } else {
+                None
+            }
+        };
+
+        stats.add(secs);
+
+        slow
+    }
+
+    /// Number of execution times observed so far
+    pub fn num_samples(&self) -> usize {
+        self.stats.lock().unwrap().count
+    }
+}
@@ -0,0 +1,121 @@
+//! Swarm testing: fuzzing with a different subset of the model's features in every epoch.
+//!
+//! A campaign which always generates every enum variant, applies every operator, and fills in
+//! every optional field produces inputs which all look alike on average. Features can also
+//! suppress each other: a parser may bail out on an early `Close` message before ever reaching
+//! the code which handles a long run of `Data` messages. Swarm testing instead splits the
+//! campaign into epochs and, in each one, leaves out a random subset of:
+//!
+//! - the variants of every enum deriving `NewFuzzed`
+//! - the mutation operators applied to numbers, lists, and byte buffers
+//! - the optional (`Option<T>`) fields of each type `T`, which are then always `None`
+//!
+//! Which features an epoch leaves out is derived from the campaign's seed and the epoch's
+//! number alone, so it doesn't depend on which thread runs an iteration and every epoch can be
+//! reproduced. The driver moves to a new epoch every
+//! [swarm_epoch_length][crate::driver::FuzzerDriver::set_swarm_epoch_length] iterations:
+//!
+//! ```compile_fail
+//! let mut driver = FuzzerDriver::<()>::new(4);
+//! driver.set_swarm_epoch_length(10_000);
+//! ```
+//!
+//! Leaving features out never makes generation impossible: an enum still gets a variant when
+//! the ones picked for it keep coming up disabled, and a number which can't be mutated by any
+//! enabled operator is mutated anyway.
+
+use crate::operators::MutationOperator;
+
+/// Chance for each feature to be left out of an epoch, unless set otherwise
+pub const DEFAULT_SWARM_DISABLE_CHANCE: f64 = 0.5;
+
+/// The operators which [Swarm] may leave out. Others (e.g. the havoc stage, crossover, or
+/// corrupted lengths) are configured through their own chances.
+pub const SWARMED_OPERATORS: [MutationOperator; 13] = [
+    MutationOperator::DangerousNumber,
+    MutationOperator::InterestingValue,
+    MutationOperator::BitFlip,
+    MutationOperator::Flip,
+    MutationOperator::Arithmetic,
+    MutationOperator::ProportionalDelta,
+    MutationOperator::GrowList,
+    MutationOperator::ShrinkList,
+    MutationOperator::DictionaryToken,
+    MutationOperator::DuplicateBlock,
+    MutationOperator::OverwriteBlock,
+    MutationOperator::SwapBlocks,
+    MutationOperator::InsertBlock,
+];
+
+/// The features left out of one epoch of a campaign
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Swarm {
+    seed: u64,
+    epoch: u64,
+    disable_chance: f64,
+}
+
+impl Swarm {
+    /// The features left out of `epoch` of the campaign with the given root `seed`
+    pub fn new(seed: u64, epoch: u64) -> Self {
+        Swarm {
+            seed,
+            epoch,
+            disable_chance: DEFAULT_SWARM_DISABLE_CHANCE,
+        }
+    }
+
+    /// Leaves out each feature with probability `chance`
+    pub fn disable_chance(mut self, chance: f64) -> Self {
+        self.disable_chance = chance;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Whether the variant at `index` of the enum named `type_name` (as returned by
+    /// [std::any::type_name]) is left out
+    pub fn disables_variant(&self, type_name: &str, index: usize) -> bool {
+        self.disables("variant", type_name, index as u64)
+    }
+
+    /// Whether `operator` is left out. Only [SWARMED_OPERATORS] ever are.
+    pub fn disables_operator(&self, operator: MutationOperator) -> bool {
+        SWARMED_OPERATORS.contains(&operator)
+            && self.disables("operator", operator.name(), operator.id() as u64)
+    }
+
+    /// Whether optional fields of the type named `type_name` are left out
+    pub fn disables_option(&self, type_name: &str) -> bool {
+        self.disables("option", type_name, 0)
+    }
+
+    fn disables(&self, kind: &str, name: &str, index: u64) -> bool {
+        // FNV-1a, so that the same features are left out regardless of the platform or release
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let (seed, epoch, index) = (
+            self.seed.to_le_bytes(),
+            self.epoch.to_le_bytes(),
+            index.to_le_bytes(),
+        );
+        for part in [&seed[..], &epoch, kind.as_bytes(), name.as_bytes(), &index].iter() {
+            for byte in part.iter() {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+
+        // FNV's low bits mix poorly, so finish with splitmix64's finalizer
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^= hash >> 31;
+
+        (hash as f64 / u64::MAX as f64) < self.disable_chance
+    }
+}
@@ -0,0 +1,74 @@
+//! Keeping in-process targets in a known state between iterations.
+//!
+//! An in-process target which keeps state in its thread context (a parser's cache, a session
+//! state machine, an emulator) accumulates changes from every input it processes. A finding may
+//! then depend on the hundreds of inputs before it and fail to reproduce on its own. Targets
+//! which can snapshot and restore their own state (with a reset function, a copy of the
+//! context, or a VM snapshot) implement [TargetSnapshot], which the driver calls around every
+//! iteration once it's [set][FuzzerDriver::set_target_snapshot].
+//!
+//! Targets which can't restore their state can instead be rebuilt from scratch now and then
+//! with a [FactoryReset]:
+//!
+//! ```compile_fail
+//! // start over with a fresh parser every 1000 iterations
+//! driver.set_target_snapshot(FactoryReset::new(1000, || ParserContext::new(&config)));
+//! ```
+//!
+//! [FuzzerDriver::set_target_snapshot]: crate::driver::FuzzerDriver::set_target_snapshot
+
+use crate::driver::Outcome;
+
+/// Saves and restores the state of a target kept in a fuzzer thread's context of type `C`.
+/// Shared by every fuzzer thread.
+pub trait TargetSnapshot<C>: Send + Sync {
+    /// Called whenever the driver creates a new context with `C::default()`: before a thread's
+    /// first iteration, and after an iteration which panicked or left the context poisoned
+    fn context_created(&self, _context: &mut C) {}
+
+    /// Called before every iteration, before the callback runs. `iterations` is the number of
+    /// iterations the calling thread has run, including this one.
+    fn before_iteration(&self, _context: &mut C, _iterations: usize) {}
+
+    /// Called after every iteration with its outcome, to return the target to the state it was
+    /// in before the iteration
+    fn after_iteration(&self, context: &mut C, outcome: &Outcome, iterations: usize);
+}
+
+/// A [TargetSnapshot] which replaces the context with a new one built by a factory whenever the
+/// driver creates one, every `interval` iterations, and after any iteration which crashed, hung,
+/// or panicked, since the target's state can't be trusted after those.
+pub struct FactoryReset<F> {
+    interval: usize,
+    factory: F,
+}
+
+impl<F> FactoryReset<F> {
+    pub fn new(interval: usize, factory: F) -> Self {
+        FactoryReset {
+            interval: std::cmp::max(interval, 1),
+            factory,
+        }
+    }
+
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+}
+
+impl<C, F> TargetSnapshot<C> for FactoryReset<F>
+where
+    F: Fn() -> C + Send + Sync,
+{
+    fn context_created(&self, context: &mut C) {
+        *context = (self.factory)();
+    }
+
+    fn after_iteration(&self, context: &mut C, outcome: &Outcome, iterations: usize) {
+        let failed = matches!(outcome, Outcome::Crash | Outcome::Hang | Outcome::Panic(_));
+
+        if failed || iterations.is_multiple_of(self.interval) {
+            *context = (self.factory)();
+        }
+    }
+}
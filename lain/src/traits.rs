@@ -5,6 +5,7 @@ use crate::rand::Rng;
 use crate::types::*;
 use byteorder::ByteOrder;
 use num_traits::Bounded;
+use std::any::Any;
 use std::fmt::Debug;
 use std::io::Write;
 
@@ -13,6 +14,93 @@ use std::io::Write;
 pub trait BinarySerialize {
     /// Pushes all fields in `self` to a buffer
     fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize;
+
+    /// Pushes all fields in `self` to a buffer without the padding a derived type adds to
+    /// reach its `#[lain(serialized_size)]`. Fields marked `#[lain(flatten)]` are serialized
+    /// this way so that they're laid out exactly as if their fields were declared in the
+    /// parent.
+    fn binary_serialize_flattened<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+        self.binary_serialize::<W, E>(buffer)
+    }
+
+    /// Appends the location of each field in `self` to `layout`, assuming `self` is
+    /// serialized starting at `offset`. Field paths are prefixed with `path`.
+    ///
+    /// Types without fields (primitives, strings, byte buffers) report nothing. This is
+    /// implemented automatically by `#[derive(BinarySerialize)]`.
+    fn field_layout(&self, _path: &str, _offset: usize, _layout: &mut Vec<FieldSpan>) {}
+
+    /// Replaces the field at `path`, as reported by [BinarySerialize::field_layout], with
+    /// `value` if the field has exactly `value`'s type. Returns whether a field was replaced.
+    ///
+    /// Types without fields have nothing to replace. This is implemented automatically by
+    /// `#[derive(BinarySerialize)]`, except for fields whose type depends on the derived
+    /// type's generic parameters.
+    fn set_field(&mut self, _path: &str, _value: &dyn Any) -> bool {
+        false
+    }
+
+    /// Pushes each of `items` to a buffer. Slices and `Vec<T>` are serialized through this so
+    /// that element types can write many of themselves at once (byte buffers are written
+    /// directly).
+    #[doc(hidden)]
+    fn binary_serialize_slice<W: Write, E: ByteOrder>(items: &[Self], buffer: &mut W) -> usize
+    where
+        Self: Sized,
+    {
+        let mut bytes_written = 0;
+        for item in items.iter() {
+            bytes_written += item.binary_serialize::<W, E>(buffer);
+        }
+
+        bytes_written
+    }
+
+    /// Appends the field locations of each of `items`, serialized back to back from `offset`,
+    /// to `layout` under the paths `path[0]`, `path[1]`, .... Slices and `Vec<T>` report their
+    /// elements through this so that element types without fields are skipped outright.
+    #[doc(hidden)]
+    fn field_layout_slice(
+        _items: &[Self],
+        _path: &str,
+        _offset: usize,
+        _layout: &mut Vec<FieldSpan>,
+    ) where
+        Self: Sized,
+    {
+    }
+}
+
+/// The inverse of [BinarySerialize]: parses a data type from the start of a byte buffer.
+///
+/// Implementations are provided for primitives, arrays, and types which consume the remainder of
+/// the buffer (`Vec<T>`, [Blob]). Error offsets are relative to the start of `bytes`; use
+/// [DeserializeError::offset_by] when parsing a nested field.
+pub trait BinaryDeserialize: Sized {
+    /// Parses a value from the start of `bytes`, returning it along with the number of bytes
+    /// consumed
+    fn binary_deserialize<E: ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), DeserializeError>;
+
+    /// Parses a value serialized with [BinarySerialize::binary_serialize_flattened], i.e.
+    /// without the padding a fixed-size type is serialized with
+    fn binary_deserialize_flattened<E: ByteOrder>(
+        bytes: &[u8],
+    ) -> Result<(Self, usize), DeserializeError> {
+        Self::binary_deserialize::<E>(bytes)
+    }
+
+    /// Parses a value which must take up all of `bytes`
+    fn from_bytes<E: ByteOrder>(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        let (value, consumed) = Self::binary_deserialize::<E>(bytes)?;
+        if consumed != bytes.len() {
+            return Err(DeserializeError::new(
+                consumed,
+                format!("{} unexpected trailing bytes", bytes.len() - consumed),
+            ));
+        }
+
+        Ok(value)
+    }
 }
 
 /// A trait to represent the output size (in bytes) of an object when serialized to binary.
@@ -25,6 +113,11 @@ pub trait SerializedSize {
     /// may be
     fn min_nonzero_elements_size() -> usize;
 
+    /// Number of bytes written by [BinarySerialize::binary_serialize_flattened]
+    fn flattened_serialized_size(&self) -> usize {
+        self.serialized_size()
+    }
+
     /// Maximum size in bytes of this data type with *the minimum amount of elements*. This is useful
     /// for determining the maximum size that a data type with a dynamic-sized member (e.g. Vec or String)
     /// may be within an enum with struct members.
@@ -36,6 +129,25 @@ pub trait SerializedSize {
     fn min_enum_variant_size(&self) -> usize {
         Self::min_nonzero_elements_size()
     }
+
+    /// Smallest [SerializedSize::variant_serialized_size] across all variants of an enum. For
+    /// types which are not enums this is the same as [SerializedSize::max_default_object_size].
+    fn min_variant_size() -> usize {
+        Self::max_default_object_size()
+    }
+
+    /// Largest [SerializedSize::variant_serialized_size] across all variants of an enum. For
+    /// types which are not enums this is the same as [SerializedSize::max_default_object_size].
+    fn max_variant_size() -> usize {
+        Self::max_default_object_size()
+    }
+
+    /// Size in bytes of the enum variant at `index` with *the minimum amount of elements*.
+    /// Indices follow declaration order (see [EnumVariants]). Returns `None` if `Self` is not an
+    /// enum or `index` is out of range.
+    fn variant_serialized_size(_index: usize) -> Option<usize> {
+        None
+    }
 }
 
 /// A data structure that can have a new instance of itself created completely randomly, with optional constraints.
@@ -49,6 +161,47 @@ pub trait NewFuzzed {
     ) -> Self;
 }
 
+/// A point in time which can be converted to and from milliseconds since the Unix epoch.
+///
+/// Fields of a type implementing this may be annotated with `#[lain(timestamp = "...")]` to
+/// select how they are serialized. See [TimestampFormat].
+///
+/// This is implemented, along with lain's other traits, for `std::time::SystemTime`, and with the
+/// `chrono_support` and `time_support` features for `chrono::DateTime<Utc>` and
+/// `time::OffsetDateTime`.
+pub trait Timestamp: Sized {
+    fn unix_millis(&self) -> i64;
+
+    /// Creates a timestamp from milliseconds since the Unix epoch. Times which can't be
+    /// represented fall back to the epoch.
+    fn from_unix_millis(millis: i64) -> Self;
+}
+
+/// Describes the variants of an enum so that containers (e.g. [VariantVec]) can reason about
+/// which variants they hold.
+///
+/// This is implemented automatically for enums by `#[derive(NewFuzzed)]`. Variant indices
+/// follow declaration order and include variants marked `#[lain(ignore)]`.
+pub trait EnumVariants: Sized {
+    /// Total number of variants
+    fn variant_count() -> usize;
+
+    /// Index of the variant `self` is an instance of
+    fn variant_index(&self) -> usize;
+
+    /// Name of the variant at `index`
+    fn variant_name(index: usize) -> &'static str;
+
+    /// Minimum and (optional) maximum number of times the variant at `index` may appear in a
+    /// collection, as specified by `#[lain(min_count = N)]` and `#[lain(max_count = N)]`
+    fn variant_count_limits(_index: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
+    /// Creates a new, randomly initialized instance of the variant at `index`
+    fn new_fuzzed_variant<R: Rng>(mutator: &mut Mutator<R>, index: usize) -> Self;
+}
+
 /// A data structure that can be mutated in-place from an existing data structure, possibly generated
 /// by [NewFuzzed].
 pub trait Mutatable {
@@ -59,19 +212,147 @@ pub trait Mutatable {
         mutator: &mut Mutator<R>,
         constraints: Option<&Constraints<Self::RangeType>>,
     );
+
+    /// Calls the [Fixup] implementation of `self`, if it has one. Does nothing by default; this
+    /// is implemented automatically by `#[derive(Mutatable)]`.
+    fn fixup_dependents<R: Rng>(&mut self, _mutator: &mut Mutator<R>) {}
+
+    /// A copy of `self` to continue mutating from, if `Self` implements `Clone`. Returns `None`
+    /// by default; this is implemented automatically by `#[derive(Mutatable)]`.
+    fn clone_seed(&self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// A child of `self` and `other` produced by [Mutator::crossover], if `Self` implements
+    /// [Crossover]. Returns `None` by default; this is implemented automatically by
+    /// `#[derive(Mutatable)]`.
+    fn crossover_seed<R: Rng>(&self, _other: &Self, _mutator: &mut Mutator<R>) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Mutates a list of `Self`. `Vec<T>` is mutated through this so that element types can
+    /// mutate lists of themselves differently (byte buffers have dictionary tokens spliced in).
+    #[doc(hidden)]
+    fn mutate_vec<R: Rng>(
+        vec: &mut Vec<Self>,
+        mutator: &mut Mutator<R>,
+        constraints: Option<&Constraints<usize>>,
+    ) where
+        Self: NewFuzzed + SerializedSize + Clone,
+        <Self as Mutatable>::RangeType: Clone,
+    {
+        crate::mutatable::mutate_vec(vec, mutator, constraints)
+    }
 }
 
 /// Trait used for performing fixups of a data structure when generating a new
 /// struct using [NewFuzzed].
 ///
 /// This trait is useful when you may have dependent data types, such as a "command" struct
-/// that needs to correspond with an enum.
+/// that needs to correspond with an enum. Derived implementations of lain's traits only call it
+/// for types which implement it.
 pub trait Fixup {
-    fn fixup<R: Rng>(&mut self, mutator: &mut Mutator<R>);
+    fn fixup<R: Rng>(&mut self, _mutator: &mut Mutator<R>) { /* nop */
+    }
 }
 
-impl<T> Fixup for T {
-    default fn fixup<R: Rng>(&mut self, _mutator: &mut Mutator<R>) { /* nop */
+/// Consistency rules of a data structure which its model is supposed to uphold, such as a count
+/// field matching the number of elements or two fields describing the same relation.
+///
+/// With the `invariant_checks` feature enabled, debug builds check the invariant of derived
+/// [Mutatable] types after each mutation which ends by fixing them up, and at the end of
+/// [Mutatable::fixup_dependents], and panic with the returned message if it doesn't hold. A
+/// [Fixup] which misses a case then shows up while the harness is being written, not as a
+/// corpus of inputs the target rejects. Mutations which skip the fixup deliberately leave
+/// dependent fields stale, so they aren't checked. Corrupted lengths and checksums (see
+/// [Mutator::set_length_corruption_chance]) may break invariants too, and should be turned off
+/// while checking ones which involve them.
+///
+/// [Mutator::set_length_corruption_chance]: crate::mutator::Mutator::set_length_corruption_chance
+pub trait Invariant {
+    /// Describes why the invariant doesn't hold for `self`, if it doesn't
+    fn check(&self) -> Result<(), String>;
+}
+
+/// The named subfields of a [ControlWord], from the least significant bit up. This is
+/// implemented on a marker type which is only used as the [ControlWord]'s type parameter:
+///
+/// ```compile_fail
+/// struct Opcode;
+///
+/// impl ControlWordLayout for Opcode {
+///     type Word = u8;
+///     const SUBFIELDS: &'static [Subfield] = &[
+///         Subfield::new("opcode", 4),
+///         Subfield::new("flags", 3).range(0, 5),
+///         Subfield::new("reserved", 1).range(0, 0),
+///     ];
+/// }
+///
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct Command {
+///     control: ControlWord<Opcode>,
+///     payload: Vec<u8>,
+/// }
+/// ```
+pub trait ControlWordLayout {
+    /// The unsigned integer the subfields are packed into, at most 64 bits wide
+    type Word: num_traits::PrimInt
+        + num_traits::Unsigned
+        + BinarySerialize
+        + BinaryDeserialize
+        + SerializedSize
+        + Debug
+        + Default;
+
+    /// The subfields, from the least significant bit up. Bits above the last subfield are
+    /// always 0.
+    const SUBFIELDS: &'static [Subfield];
+}
+
+/// A data type which can be reduced toward a simpler value while minimizing a crashing input
+/// with [minimize][crate::minimize::minimize].
+///
+/// This is implemented for primitives, `Vec`, `Option`, arrays, strings, and lain's wrapper
+/// types, and can be derived for structs and enums with `#[derive(Minimize)]`. Derived
+/// implementations leave fields whose types don't implement it as they are.
+pub trait Minimize: Clone {
+    /// Returns values which are strictly simpler than `self`, most aggressive reduction first.
+    /// Returns nothing once `self` can't be simplified further.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_>;
+}
+
+/// A data type whose instances can be combined into a new one, like AFL's splice stage but on
+/// structure rather than bytes. See [Mutator::crossover].
+///
+/// This is implemented for primitives, `Vec`, `Option`, arrays, boxes, and lain's string types,
+/// and can be derived for structs and enums with `#[derive(Crossover)]`. Derived implementations
+/// replace fields whose types don't implement it as a whole by the other parent's value half of
+/// the time.
+pub trait Crossover: Clone {
+    /// Replaces parts of `self` with the corresponding parts of `other`
+    fn crossover<R: Rng>(&mut self, other: &Self, mutator: &mut Mutator<R>);
+}
+
+/// A check performed on a generated input after it has been serialized, but before it is handed
+/// to the target. This is useful for rejecting inputs that are known to fail a trivial
+/// precondition in the target (e.g. a bad magic value) so that executions aren't wasted on them.
+///
+/// See [Mutator::new_validated][lain::mutator::Mutator::new_validated] and
+/// [Mutator::mutate_validated][lain::mutator::Mutator::mutate_validated].
+pub trait Validate {
+    /// Returns whether or not `serialized` (the serialized form of `self`) should be executed
+    fn validate(&self, serialized: &[u8]) -> bool;
+
+    /// Attempts to fix up `self` after failing validation. The input is validated again
+    /// afterwards and regenerated if it still fails.
+    fn repair<R: Rng>(&mut self, _mutator: &mut Mutator<R>) { /* nop */
     }
 }
 
@@ -90,6 +371,15 @@ pub trait ToPrimitive {
     type Output;
 
     fn to_primitive(&self) -> Self::Output;
+
+    /// Every value `to_primitive` returns for a valid instance, if known. Invalid values for
+    /// [UnsafeEnum][crate::types::UnsafeEnum] are aimed at the gaps between them.
+    fn valid_primitives() -> Vec<Self::Output>
+    where
+        Self: Sized,
+    {
+        vec![]
+    }
 }
 
 /// Trait for objects to derive in order to specify whether or not they are variable-size.
@@ -98,17 +388,12 @@ pub trait ToPrimitive {
 /// contain dynamic-size fields, the quality of fuzzing may be slightly worse. This is because
 /// calling [NewFuzzed::new_fuzzed] will, if a variable-sized field is in the data structure,
 /// initialize its fields in a random order. If you are working with size constraints, it may be useful
-/// to `#[derive(VariableSizeObject)]` to get random field initialization.
+/// to `#[derive(VariableSizeObject)]` to get random field initialization. Derived implementations
+/// treat types which don't implement it as fixed-size.
 pub trait VariableSizeObject {
     fn is_variable_size() -> bool;
 }
 
-impl<T> VariableSizeObject for T {
-    default fn is_variable_size() -> bool {
-        false
-    }
-}
-
 impl<T> VariableSizeObject for Vec<T> {
     fn is_variable_size() -> bool {
         true
@@ -126,3 +411,39 @@ impl VariableSizeObject for AsciiString {
         true
     }
 }
+
+impl VariableSizeObject for Utf16String {
+    fn is_variable_size() -> bool {
+        true
+    }
+}
+
+impl VariableSizeObject for WideCString {
+    fn is_variable_size() -> bool {
+        true
+    }
+}
+
+impl VariableSizeObject for CString {
+    fn is_variable_size() -> bool {
+        true
+    }
+}
+
+impl<T> VariableSizeObject for VariantVec<T> {
+    fn is_variable_size() -> bool {
+        true
+    }
+}
+
+impl VariableSizeObject for Blob {
+    fn is_variable_size() -> bool {
+        true
+    }
+}
+
+impl<T: NewFuzzed> VariableSizeObject for Lazy<T> {
+    fn is_variable_size() -> bool {
+        true
+    }
+}
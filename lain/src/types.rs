@@ -1,4 +1,4 @@
-use num_traits::Bounded;
+use num_traits::{Bounded, PrimInt, ToPrimitive as _, Zero};
 use std::fmt::Debug;
 
 #[cfg(feature = "serde_support")]
@@ -69,6 +69,10 @@ where
             UnsafeEnum::Invalid(n) => *n,
         }
     }
+
+    fn valid_primitives() -> Vec<T> {
+        E::valid_primitives()
+    }
 }
 
 // TODO: Clean up this string interface. This isn't the cleanest
@@ -86,6 +90,12 @@ impl Utf8String {
     }
 }
 
+impl std::fmt::Display for Utf8String {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.iter().try_for_each(|c| write!(f, "{}", c.0))
+    }
+}
+
 /// Wrapper around `String` that provides mutation methods appropriate for ASCII encoded Strings
 #[derive(Debug, Default, Clone)]
 pub struct AsciiString {
@@ -100,6 +110,1017 @@ impl AsciiString {
     }
 }
 
+impl std::fmt::Display for AsciiString {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.iter().try_for_each(|c| write!(f, "{}", c.0))
+    }
+}
+
+/// A UTF-16 string, as taken by Windows APIs and RPC interfaces. It's serialized as its 16-bit
+/// code units in the byte order it's serialized with, so UTF-16LE strings are declared with
+/// `#[lain(little_endian)]`.
+///
+/// Code units are kept as-is rather than decoded, so generated and mutated strings include the
+/// edge cases of UTF-16: surrogate pairs, unpaired and reversed surrogates, embedded nulls, and
+/// byte order marks. `min`/`max` constraints bound the number of code units.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Utf16String {
+    pub(crate) inner: Vec<u16>,
+}
+
+impl Utf16String {
+    pub fn new(s: &str) -> Self {
+        Utf16String {
+            inner: s.encode_utf16().collect(),
+        }
+    }
+
+    pub fn from_code_units(inner: Vec<u16>) -> Self {
+        Utf16String { inner }
+    }
+
+    pub fn code_units(&self) -> &[u16] {
+        &self.inner
+    }
+}
+
+/// Unpaired surrogates are replaced with U+FFFD
+impl std::fmt::Display for Utf16String {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&String::from_utf16_lossy(&self.inner))
+    }
+}
+
+/// A null-terminated UTF-16 string (a `wchar_t*` on Windows). The same as a [Utf16String], but
+/// serialized with a trailing null code unit. Nulls embedded in the string are kept, so the
+/// target sees a string cut short while the rest of the data is still there.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct WideCString {
+    pub(crate) inner: Vec<u16>,
+}
+
+impl WideCString {
+    pub fn new(s: &str) -> Self {
+        WideCString {
+            inner: s.encode_utf16().collect(),
+        }
+    }
+
+    pub fn from_code_units(inner: Vec<u16>) -> Self {
+        WideCString { inner }
+    }
+
+    /// The code units before the terminator
+    pub fn code_units(&self) -> &[u16] {
+        &self.inner
+    }
+}
+
+/// Unpaired surrogates are replaced with U+FFFD
+impl std::fmt::Display for WideCString {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&String::from_utf16_lossy(&self.inner))
+    }
+}
+
+/// A null-terminated byte string (a `char*`), serialized as its bytes followed by a NUL.
+///
+/// Besides mutating its bytes like an [AsciiString], the mutator sometimes (with probability
+/// [Mutator::c_string_anomaly_chance][crate::mutator::Mutator::c_string_anomaly_chance]) breaks
+/// what C code assumes about it: the terminator is dropped so that readers run past the end of
+/// the string, NULs are embedded so that it's cut short while the rest of the data is still
+/// there, or it's extended past its `max_size` to overflow the fixed-size buffer the target
+/// copies it into. `min_size` and `max_size` include the terminator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CString {
+    pub(crate) inner: Vec<u8>,
+    pub(crate) terminated: bool,
+}
+
+impl CString {
+    pub fn new(s: &str) -> Self {
+        CString::from_bytes(s.as_bytes().to_vec())
+    }
+
+    pub fn from_bytes(inner: Vec<u8>) -> Self {
+        CString {
+            inner,
+            terminated: true,
+        }
+    }
+
+    /// The bytes before the terminator, including any embedded NULs
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.inner
+    }
+
+    /// Whether the string is serialized with its terminator
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    pub fn set_terminated(&mut self, terminated: bool) {
+        self.terminated = terminated;
+    }
+}
+
+impl Default for CString {
+    fn default() -> Self {
+        CString::from_bytes(vec![])
+    }
+}
+
+/// Invalid UTF-8 is replaced with U+FFFD
+impl std::fmt::Display for CString {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.inner))
+    }
+}
+
+/// A named range of bits of a [ControlWord]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subfield {
+    name: &'static str,
+    bits: u32,
+    min: u64,
+    max: u64,
+}
+
+impl Subfield {
+    /// A subfield `bits` wide which may take any value that fits
+    pub const fn new(name: &'static str, bits: u32) -> Self {
+        let max = if bits >= 64 {
+            u64::MAX
+        } else {
+            (1 << bits) - 1
+        };
+
+        Subfield {
+            name,
+            bits,
+            min: 0,
+            max,
+        }
+    }
+
+    /// Limits the values the subfield is usually given to `min..=max`
+    pub const fn range(mut self, min: u64, max: u64) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn min(&self) -> u64 {
+        self.min
+    }
+
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// The mask of the subfield's bits, before shifting them into place
+    pub fn mask(&self) -> u64 {
+        Subfield::new(self.name, self.bits).max
+    }
+}
+
+/// An integer made up of named bit ranges, such as a protocol's control word or a packed
+/// header byte. The subfields are declared by `L` (see
+/// [ControlWordLayout][crate::traits::ControlWordLayout]) and read and written by name, so the
+/// shifts and masks live in one place instead of in every fixup.
+///
+/// Each subfield is generated and mutated on its own within its range, except that with
+/// probability [Mutator::invalid_value_chance][crate::mutator::Mutator::invalid_value_chance] it
+/// takes any value that fits in its bits, such as a set reserved bit or an undefined opcode.
+/// The word is serialized as the packed integer.
+pub struct ControlWord<L: crate::traits::ControlWordLayout> {
+    pub(crate) word: L::Word,
+    pub(crate) layout: std::marker::PhantomData<L>,
+}
+
+impl<L: crate::traits::ControlWordLayout> ControlWord<L> {
+    /// Wraps an already packed `word`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the subfields of `L` take more bits than `L::Word` has.
+    pub fn new(word: L::Word) -> Self {
+        let word_bits = L::Word::zero().count_zeros();
+        let subfield_bits: u32 = L::SUBFIELDS.iter().map(Subfield::bits).sum();
+        assert!(
+            subfield_bits <= word_bits && word_bits <= 64,
+            "the subfields of {} take {} bits but its word has {}",
+            std::any::type_name::<L>(),
+            subfield_bits,
+            word_bits
+        );
+
+        ControlWord {
+            word,
+            layout: std::marker::PhantomData,
+        }
+    }
+
+    /// The packed integer
+    pub fn word(&self) -> L::Word {
+        self.word
+    }
+
+    /// Index of the subfield named `name` in `L::SUBFIELDS`
+    fn index_of(name: &str) -> usize {
+        L::SUBFIELDS
+            .iter()
+            .position(|subfield| subfield.name == name)
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} has no subfield named {}",
+                    std::any::type_name::<L>(),
+                    name
+                )
+            })
+    }
+
+    /// The value of the subfield at `index` of `L::SUBFIELDS`
+    pub(crate) fn get_at(&self, index: usize) -> u64 {
+        let shift: u32 = L::SUBFIELDS[..index].iter().map(Subfield::bits).sum();
+        let word = self.word.to_u64().unwrap_or(0);
+        word.checked_shr(shift).unwrap_or(0) & L::SUBFIELDS[index].mask()
+    }
+
+    /// Sets the subfield at `index` of `L::SUBFIELDS`, truncating `value` to its bits
+    pub(crate) fn set_at(&mut self, index: usize, value: u64) {
+        let shift: u32 = L::SUBFIELDS[..index].iter().map(Subfield::bits).sum();
+        let mask = L::SUBFIELDS[index].mask().checked_shl(shift).unwrap_or(0);
+        let word = self.word.to_u64().unwrap_or(0);
+        let word = (word & !mask) | (value.checked_shl(shift).unwrap_or(0) & mask);
+        self.word = num::cast(word).unwrap_or_default();
+    }
+
+    /// The value of the subfield `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `L` has no such subfield.
+    pub fn get(&self, name: &str) -> u64 {
+        self.get_at(Self::index_of(name))
+    }
+
+    /// Sets the subfield `name`, truncating `value` to its bits
+    ///
+    /// # Panics
+    ///
+    /// Panics if `L` has no such subfield.
+    pub fn set(&mut self, name: &str, value: u64) {
+        self.set_at(Self::index_of(name), value);
+    }
+}
+
+impl<L: crate::traits::ControlWordLayout> Default for ControlWord<L> {
+    fn default() -> Self {
+        ControlWord::new(L::Word::default())
+    }
+}
+
+impl<L: crate::traits::ControlWordLayout> Clone for ControlWord<L> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L: crate::traits::ControlWordLayout> Copy for ControlWord<L> {}
+
+impl<L: crate::traits::ControlWordLayout> PartialEq for ControlWord<L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.word == other.word
+    }
+}
+
+impl<L: crate::traits::ControlWordLayout> Eq for ControlWord<L> {}
+
+/// Lists the subfields by name, e.g. `ControlWord { opcode: 3, flags: 0, reserved: 0 }`
+impl<L: crate::traits::ControlWordLayout> std::fmt::Debug for ControlWord<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut debug = f.debug_struct("ControlWord");
+        for (index, subfield) in L::SUBFIELDS.iter().enumerate() {
+            debug.field(subfield.name, &self.get_at(index));
+        }
+        debug.finish()
+    }
+}
+
+/// An integer of type `T` stored as ASCII decimal digits, as in `Content-Length: 1234`.
+///
+/// Mutations change the number rather than its digit bytes: the value is mutated like an
+/// integer, replaced with a boundary value of `T` (or one just outside its range), negated,
+/// padded with leading zeros, or replaced with a number of excessive length. The result is
+/// always a well-formed run of digits with an optional sign, so text protocols still get as
+/// far as parsing it. The text is serialized as-is, independent of byte order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct AsciiNumber<T> {
+    pub(crate) text: String,
+    #[cfg_attr(feature = "serde_support", serde(skip))]
+    pub(crate) marker: std::marker::PhantomData<T>,
+}
+
+impl<T: std::fmt::Display> AsciiNumber<T> {
+    /// Maximum number of leading zeros added by a single mutation
+    pub const MAX_LEADING_ZEROS: usize = 0x10;
+    /// Maximum number of digits in a number generated to exceed the range of `T`
+    pub const MAX_DIGITS: usize = 0x1000;
+
+    pub fn new(value: T) -> Self {
+        AsciiNumber {
+            text: value.to_string(),
+            marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T> AsciiNumber<T> {
+    /// The digits (and sign, if any) exactly as they are serialized
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Parses the text as a `T`. Returns `None` if the text no longer fits in a `T`, e.g.
+    /// after a mutation made it negative or longer than `T` allows.
+    pub fn value(&self) -> Option<T>
+    where
+        T: std::str::FromStr,
+    {
+        self.text.parse().ok()
+    }
+}
+
+/// The number 0, so that even a default value is a well-formed number
+impl<T> Default for AsciiNumber<T> {
+    fn default() -> Self {
+        AsciiNumber {
+            text: "0".to_string(),
+            marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: std::fmt::Display> From<T> for AsciiNumber<T> {
+    fn from(value: T) -> Self {
+        AsciiNumber::new(value)
+    }
+}
+
+/// A list of enum values where each variant may be required to appear a minimum and/or maximum
+/// number of times. The limits are specified on the enum's variants:
+///
+/// ```compile_fail
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// enum Chunk {
+///     #[lain(min_count = 1, max_count = 1)]
+///     Header(HeaderChunk),
+///     #[lain(min_count = 1)]
+///     Data(DataChunk),
+///     Comment(CommentChunk),
+/// }
+///
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct File {
+///     chunks: VariantVec<Chunk>,
+/// }
+/// ```
+///
+/// This fits chunk-based formats such as PNG or RIFF. The limits are respected when generating
+/// new lists and when growing, shrinking, reordering, or mutating the elements of a list. So is
+/// a `max_size`, unless the required variants alone don't fit in it.
+/// Serializes exactly like a `Vec<T>`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VariantVec<T> {
+    pub(crate) inner: Vec<T>,
+}
+
+impl<T> VariantVec<T> {
+    pub fn new(inner: Vec<T>) -> Self {
+        VariantVec { inner }
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.inner
+    }
+}
+
+impl<T: crate::traits::EnumVariants> VariantVec<T> {
+    /// Returns how many times each variant appears in this list, indexed by variant index
+    pub fn variant_counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; T::variant_count()];
+        for item in self.inner.iter() {
+            counts[item.variant_index()] += 1;
+        }
+
+        counts
+    }
+
+    /// Whether or not every variant's count is within the bounds set by its attributes
+    pub fn satisfies_variant_limits(&self) -> bool {
+        self.variant_counts()
+            .iter()
+            .enumerate()
+            .all(|(index, count)| {
+                let (min, max) = T::variant_count_limits(index);
+                *count >= min && max.map(|max| *count <= max).unwrap_or(true)
+            })
+    }
+}
+
+impl<T> std::ops::Deref for VariantVec<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.inner
+    }
+}
+
+impl<T> From<Vec<T>> for VariantVec<T> {
+    fn from(inner: Vec<T>) -> Self {
+        VariantVec { inner }
+    }
+}
+
+/// A blob of raw bytes whose contents are generated from one of several [BlobContent] classes.
+///
+/// Parsers tend to behave very differently when fed zeros, repeating patterns, text, or
+/// high-entropy data, and uniformly random bytes are often the least interesting of these. The
+/// likelihood of each class being picked can be tuned with
+/// [Mutator::set_blob_content_weights][lain::mutator::Mutator::set_blob_content_weights].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Blob {
+    pub(crate) inner: Vec<u8>,
+}
+
+impl Blob {
+    pub fn new(inner: Vec<u8>) -> Self {
+        Blob { inner }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.inner
+    }
+}
+
+impl From<Vec<u8>> for Blob {
+    fn from(inner: Vec<u8>) -> Self {
+        Blob { inner }
+    }
+}
+
+/// The classes of content a [Blob] may be generated with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub enum BlobContent {
+    /// Every byte is `0x00`
+    Zeros,
+    /// Every byte is `0xFF`
+    Ones,
+    /// A short random pattern repeated for the length of the blob
+    RepeatingPattern,
+    /// Bytes counting upwards (wrapping) from a random starting value
+    Ascending,
+    /// Bytes generated individually, biased towards "dangerous" values
+    Random,
+    /// Printable ASCII and whitespace
+    TextLike,
+    /// Uniformly distributed bytes which may be prefixed with a compression header
+    HighEntropy,
+}
+
+impl BlobContent {
+    pub const ALL: [BlobContent; 7] = [
+        BlobContent::Zeros,
+        BlobContent::Ones,
+        BlobContent::RepeatingPattern,
+        BlobContent::Ascending,
+        BlobContent::Random,
+        BlobContent::TextLike,
+        BlobContent::HighEntropy,
+    ];
+}
+
+/// Relative weights used when selecting the [BlobContent] class of a new [Blob]. A weight of 0
+/// disables the class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct BlobContentWeights {
+    pub zeros: u64,
+    pub ones: u64,
+    pub repeating_pattern: u64,
+    pub ascending: u64,
+    pub random: u64,
+    pub text_like: u64,
+    pub high_entropy: u64,
+}
+
+impl BlobContentWeights {
+    /// Returns the weight of `class`
+    pub fn weight(&self, class: BlobContent) -> u64 {
+        match class {
+            BlobContent::Zeros => self.zeros,
+            BlobContent::Ones => self.ones,
+            BlobContent::RepeatingPattern => self.repeating_pattern,
+            BlobContent::Ascending => self.ascending,
+            BlobContent::Random => self.random,
+            BlobContent::TextLike => self.text_like,
+            BlobContent::HighEntropy => self.high_entropy,
+        }
+    }
+
+    /// Sets the weight of `class`
+    pub fn set_weight(&mut self, class: BlobContent, weight: u64) {
+        let field = match class {
+            BlobContent::Zeros => &mut self.zeros,
+            BlobContent::Ones => &mut self.ones,
+            BlobContent::RepeatingPattern => &mut self.repeating_pattern,
+            BlobContent::Ascending => &mut self.ascending,
+            BlobContent::Random => &mut self.random,
+            BlobContent::TextLike => &mut self.text_like,
+            BlobContent::HighEntropy => &mut self.high_entropy,
+        };
+
+        *field = weight;
+    }
+}
+
+impl Default for BlobContentWeights {
+    fn default() -> Self {
+        BlobContentWeights {
+            zeros: 2,
+            ones: 2,
+            repeating_pattern: 3,
+            ascending: 2,
+            random: 1,
+            text_like: 3,
+            high_entropy: 2,
+        }
+    }
+}
+
+/// A vector of floating-point values (`f32` or `f64`), serialized as a packed array.
+///
+/// Elements are biased towards values which tend to break numeric code: NaN, infinities,
+/// signed zeros, subnormals, and the extremes of the type. The length is picked within the
+/// `min`/`max` constraints (defaulting to fewer than [FloatVec::DEFAULT_MAX_LEN] elements) and
+/// respects `max_size`.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct FloatVec<T> {
+    pub(crate) inner: Vec<T>,
+}
+
+impl<T> FloatVec<T> {
+    /// Exclusive upper bound on the length of a vector generated without constraints
+    pub const DEFAULT_MAX_LEN: usize = 0x40;
+
+    pub fn new(inner: Vec<T>) -> Self {
+        FloatVec { inner }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.inner
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.inner
+    }
+}
+
+impl<T> From<Vec<T>> for FloatVec<T> {
+    fn from(inner: Vec<T>) -> Self {
+        FloatVec { inner }
+    }
+}
+
+/// A row-major matrix of floating-point values (`f32` or `f64`), serialized as a packed array
+/// of `rows * cols` elements.
+///
+/// The row and column counts are each picked within the `min`/`max` constraints (defaulting to
+/// 1 up to, but not including, [Matrix::DEFAULT_MAX_DIM]) and the total size respects
+/// `max_size`. The data always matches the shape, including after mutation, so formats which
+/// store the shape in a header can fill it in from [Matrix::rows] and [Matrix::cols] in a
+/// [Fixup][crate::traits::Fixup] implementation.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct Matrix<T> {
+    pub(crate) rows: usize,
+    pub(crate) cols: usize,
+    pub(crate) data: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    /// Exclusive upper bound on the row and column counts of a matrix generated without
+    /// constraints
+    pub const DEFAULT_MAX_DIM: usize = 0x10;
+
+    /// Creates a matrix from row-major `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` does not contain exactly `rows * cols` elements.
+    pub fn new(rows: usize, cols: usize, data: Vec<T>) -> Self {
+        assert_eq!(
+            rows * cols,
+            data.len(),
+            "a {}x{} matrix needs {} elements",
+            rows,
+            cols,
+            rows * cols
+        );
+
+        Matrix { rows, cols, data }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// `(rows, cols)`
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// The element at `row`, `col`, if it is within the matrix
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+
+        self.data.get(row * self.cols + col)
+    }
+
+    /// The elements in row-major order
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.data
+    }
+}
+
+/// A value which is only generated when it is needed (e.g. at serialization time) from a stored
+/// seed.
+///
+/// This keeps inputs with very large fields small in memory and in a serialized corpus: only the
+/// seed and constraints are stored, and the same content is regenerated every time the value is
+/// serialized.
+///
+/// ```compile_fail
+/// #[derive(NewFuzzed)]
+/// struct Firmware {
+///     #[lain(min = 0x100000, max = 0x400000)]
+///     image: Vec<u8>,
+/// }
+///
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct UpdateRequest {
+///     header: UpdateHeader,
+///     firmware: Lazy<Firmware>,
+/// }
+/// ```
+///
+/// Since the content is regenerated on demand, [serialized_size][crate::traits::SerializedSize::serialized_size]
+/// and serialization both pay the cost of generating it.
+pub struct Lazy<T: crate::traits::NewFuzzed> {
+    pub(crate) seed: u64,
+    constraints: Option<Constraints<T::RangeType>>,
+    _marker: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: crate::traits::NewFuzzed> Lazy<T> {
+    pub fn new(seed: u64) -> Self {
+        Lazy {
+            seed,
+            constraints: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Creates a lazy value whose content is generated using `constraints`
+    pub fn with_constraints(seed: u64, constraints: Constraints<T::RangeType>) -> Self {
+        Lazy {
+            seed,
+            constraints: Some(constraints),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn constraints(&self) -> Option<&Constraints<T::RangeType>> {
+        self.constraints.as_ref()
+    }
+
+    /// Generates the content. Every call returns the same value.
+    pub fn generate(&self) -> T {
+        use crate::rand::SeedableRng;
+
+        let mut mutator =
+            crate::mutator::Mutator::new(crate::rand::rngs::StdRng::seed_from_u64(self.seed));
+
+        T::new_fuzzed(&mut mutator, self.constraints.as_ref())
+    }
+}
+
+impl<T> Clone for Lazy<T>
+where
+    T: crate::traits::NewFuzzed,
+    T::RangeType: Clone,
+{
+    fn clone(&self) -> Self {
+        Lazy {
+            seed: self.seed,
+            constraints: self.constraints.clone(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: crate::traits::NewFuzzed> Debug for Lazy<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Lazy")
+            .field("seed", &self.seed)
+            .field("constraints", &self.constraints)
+            .finish()
+    }
+}
+
+macro_rules! semantic_wrapper {
+    ( $(#[$meta:meta])* $name:ident($inner:ty) ) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+        pub struct $name(pub $inner);
+
+        impl $name {
+            pub fn new(value: $inner) -> Self {
+                $name(value)
+            }
+
+            pub fn value(&self) -> $inner {
+                self.0
+            }
+
+            /// Returns true if this value is one of the boundary or reserved values generated
+            /// when an invalid value is requested
+            pub fn is_attack_value(&self) -> bool {
+                Self::ATTACK_VALUES.contains(&self.0)
+            }
+        }
+
+        impl From<$inner> for $name {
+            fn from(value: $inner) -> Self {
+                $name(value)
+            }
+        }
+
+        impl crate::traits::ToPrimitive for $name {
+            type Output = $inner;
+
+            fn to_primitive(&self) -> $inner {
+                self.0
+            }
+        }
+    };
+}
+
+semantic_wrapper! {
+    /// A TCP/UDP port number. Generated values favor well-known ports, then registered and
+    /// ephemeral ranges.
+    Port(u16)
+}
+
+impl Port {
+    pub const WELL_KNOWN: [u16; 16] = [
+        20, 21, 22, 23, 25, 53, 67, 68, 80, 110, 123, 143, 161, 443, 993, 995,
+    ];
+    pub const REGISTERED: std::ops::RangeInclusive<u16> = 1024..=49151;
+    pub const EPHEMERAL: std::ops::RangeInclusive<u16> = 49152..=65535;
+    pub const ATTACK_VALUES: [u16; 5] = [0, 1, 1023, 1024, 65535];
+}
+
+semantic_wrapper! {
+    /// An IEEE 802.1Q VLAN identifier. Valid identifiers are 12 bits wide and 0 and 4095 are
+    /// reserved; attack values include the reserved identifiers and values which spill into the
+    /// priority/DEI bits of the tag control information.
+    VlanTag(u16)
+}
+
+impl VlanTag {
+    pub const VALID: std::ops::RangeInclusive<u16> = 1..=4094;
+    pub const ATTACK_VALUES: [u16; 5] = [0, 4095, 0x1000, 0x8000, 0xFFFF];
+}
+
+semantic_wrapper! {
+    /// An IP time-to-live/hop limit. Generated values are common operating system defaults
+    /// minus a small number of hops.
+    Ttl(u8)
+}
+
+impl Ttl {
+    pub const DEFAULTS: [u8; 4] = [32, 64, 128, 255];
+    pub const ATTACK_VALUES: [u8; 3] = [0, 1, 255];
+}
+
+semantic_wrapper! {
+    /// A TCP receive window size. Generated values favor common operating system defaults.
+    WindowSize(u16)
+}
+
+impl WindowSize {
+    pub const COMMON: [u16; 6] = [5840, 8192, 14600, 29200, 64240, 65535];
+    pub const ATTACK_VALUES: [u16; 4] = [0, 1, 0x7FFF, 65535];
+}
+
+pub const DEFAULT_ID_POOL_CAPACITY: usize = 1024;
+pub const DEFAULT_ID_POOL_GARBAGE_CHANCE: f64 = 0.10;
+
+/// A pool of identifiers (session IDs, file descriptors, object handles, sequence numbers, ...)
+/// which were issued by or observed from the target. Pools are registered on the
+/// [Mutator][crate::mutator::Mutator] by name and fields annotated with
+/// `#[lain(from_pool = "name")]` draw most of their values from the pool, producing messages
+/// which reference objects the target actually knows about:
+///
+/// ```compile_fail
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct CloseSession {
+///     #[lain(from_pool = "session_ids")]
+///     session_id: u32,
+/// }
+///
+/// mutator.register_id_pool("session_ids", IdPool::<u32>::new());
+/// // after parsing a response from the target
+/// mutator.record_id("session_ids", response.session_id);
+/// ```
+///
+/// With probability [IdPool::garbage_chance], or whenever the pool is empty, a random value is
+/// generated instead. Once the pool holds `capacity` IDs the oldest ID is evicted for each new one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdPool<T> {
+    ids: Vec<T>,
+    capacity: usize,
+    garbage_chance: f64,
+}
+
+impl<T> Default for IdPool<T> {
+    fn default() -> Self {
+        IdPool::with_capacity(DEFAULT_ID_POOL_CAPACITY)
+    }
+}
+
+impl<T> IdPool<T> {
+    pub fn new() -> Self {
+        IdPool::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        IdPool {
+            ids: vec![],
+            capacity: std::cmp::max(capacity, 1),
+            garbage_chance: DEFAULT_ID_POOL_GARBAGE_CHANCE,
+        }
+    }
+
+    /// Sets the probability (0.0 - 1.0) of generating a random value instead of drawing from the
+    /// pool
+    pub fn set_garbage_chance(&mut self, chance: f64) {
+        self.garbage_chance = chance;
+    }
+
+    pub fn garbage_chance(&self) -> f64 {
+        self.garbage_chance
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// The IDs in the pool, oldest first
+    pub fn ids(&self) -> &[T] {
+        &self.ids
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.ids.get(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+    }
+}
+
+impl<T: PartialEq> IdPool<T> {
+    /// Adds `id` to the pool if it isn't already present, evicting the oldest ID if the pool is
+    /// full
+    pub fn insert(&mut self, id: T) {
+        if self.ids.contains(&id) {
+            return;
+        }
+
+        if self.ids.len() == self.capacity {
+            self.ids.remove(0);
+        }
+
+        self.ids.push(id);
+    }
+
+    /// Removes `id` from the pool (e.g. after the target reports the object has been freed).
+    /// Returns whether the ID was present.
+    pub fn remove(&mut self, id: &T) -> bool {
+        match self.ids.iter().position(|i| i == id) {
+            Some(index) => {
+                self.ids.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, id: &T) -> bool {
+        self.ids.contains(id)
+    }
+}
+
+/// Error returned by [BinaryDeserialize][crate::traits::BinaryDeserialize] when bytes cannot be
+/// parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializeError {
+    /// Offset of the byte at which parsing failed
+    pub offset: usize,
+    pub message: String,
+}
+
+impl DeserializeError {
+    pub fn new<S: Into<String>>(offset: usize, message: S) -> Self {
+        DeserializeError {
+            offset,
+            message: message.into(),
+        }
+    }
+
+    /// Error for a value starting at `offset` which needed `needed` more bytes than were left
+    pub fn unexpected_end(offset: usize, needed: usize) -> Self {
+        DeserializeError::new(
+            offset,
+            format!("unexpected end of input ({} more bytes needed)", needed),
+        )
+    }
+
+    /// Shifts the error's offset by `base`. Useful for turning the offset of an error in a
+    /// nested field into an offset from the start of the parent.
+    pub fn offset_by(mut self, base: usize) -> Self {
+        self.offset += base;
+        self
+    }
+}
+
+impl std::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} at offset 0x{:X}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
 /// Represents a UTF-8 character.
 #[derive(Default, Debug, Clone)]
 pub(crate) struct Utf8Char(pub(crate) char);
@@ -120,6 +1141,10 @@ pub struct Constraints<T: Bounded + Debug> {
     pub weighted: Weighted,
     /// The space allotted for dynamically-sized objects
     pub max_size: Option<usize>,
+    /// The least space dynamically-sized objects should take up
+    pub min_size: Option<usize>,
+    /// Number of consecutive elements of a `Vec` which form one record
+    pub element_group: Option<usize>,
     pub base_object_size_accounted_for: bool,
 }
 
@@ -130,6 +1155,8 @@ impl<T: Bounded + Debug> Constraints<T> {
             max: None,
             weighted: Weighted::None,
             max_size: None,
+            min_size: None,
+            element_group: None,
             base_object_size_accounted_for: false,
         }
     }
@@ -154,6 +1181,31 @@ impl<T: Bounded + Debug> Constraints<T> {
         self
     }
 
+    /// Sets the least number of bytes a `Vec`, string, or [Blob] should serialize to. This is met by
+    /// generating and keeping enough elements, so it's never exceeded in the other direction:
+    /// when both are set, [Constraints::max_size] wins.
+    pub fn min_size(&mut self, min_size: usize) -> &mut Constraints<T> {
+        self.min_size = Some(min_size);
+        self
+    }
+
+    /// Requires a `Vec`, string, or [Blob] to serialize to exactly `size` bytes, by setting both
+    /// [Constraints::min_size] and [Constraints::max_size]. Elements of varying sizes may make
+    /// this impossible, in which case the result is as large as possible without going over.
+    pub fn exact_size(&mut self, size: usize) -> &mut Constraints<T> {
+        self.min_size = Some(size);
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Declares that every `group` consecutive elements of a `Vec` form one record (e.g. a
+    /// record spanning 3 entries), counting from the first element. Mutations which add, remove,
+    /// or move elements then do so in whole records rather than truncating one midway.
+    pub fn element_group(&mut self, group: usize) -> &mut Constraints<T> {
+        self.element_group = Some(group);
+        self
+    }
+
     pub fn account_for_base_object_size<U: crate::traits::SerializedSize>(
         &mut self,
     ) -> &mut Constraints<T> {
@@ -166,6 +1218,10 @@ impl<T: Bounded + Debug> Constraints<T> {
                 *max_size = max_size.saturating_sub(U::max_default_object_size());
             }
 
+            if let Some(ref mut min_size) = self.min_size {
+                *min_size = min_size.saturating_sub(U::max_default_object_size());
+            }
+
             self.base_object_size_accounted_for = true;
         }
 
@@ -176,6 +1232,71 @@ impl<T: Bounded + Debug> Constraints<T> {
         self.base_object_size_accounted_for = true;
         self
     }
+
+    /// Total number of bytes a value of type `U` may serialize to under these constraints, or
+    /// `None` if there's no size limit. Unlike [Constraints::max_size], this doesn't depend on
+    /// whether the caller already set aside `U`'s default size, so custom [NewFuzzed] and
+    /// [Mutatable] implementations can budget with it directly.
+    ///
+    /// [NewFuzzed]: crate::traits::NewFuzzed
+    /// [Mutatable]: crate::traits::Mutatable
+    pub fn remaining<U: crate::traits::SerializedSize>(&self) -> Option<usize> {
+        self.max_size.map(|max_size| {
+            if self.base_object_size_accounted_for {
+                max_size + U::max_default_object_size()
+            } else {
+                max_size
+            }
+        })
+    }
+
+    /// Number of bytes a value of type `U` may use beyond its default size (e.g. for extra
+    /// elements of a `Vec`), or `None` if there's no size limit
+    pub fn remaining_extra<U: crate::traits::SerializedSize>(&self) -> Option<usize> {
+        self.remaining::<U>()
+            .map(|remaining| remaining.saturating_sub(U::max_default_object_size()))
+    }
+}
+
+/// Location of a single field within a serialized buffer, as reported by
+/// [BinarySerialize::field_layout][lain::traits::BinarySerialize::field_layout].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpan {
+    /// Dotted path to the field from the root object (e.g. `header.length` or `items[2].id`)
+    pub path: String,
+    /// Offset of the first byte of this field
+    pub start: usize,
+    /// Offset one past the last byte of this field
+    pub end: usize,
+}
+
+impl FieldSpan {
+    /// Joins a parent path and a child member name
+    pub fn child_path(parent: &str, member: &str) -> String {
+        if parent.is_empty() {
+            member.to_string()
+        } else {
+            format!("{}.{}", parent, member)
+        }
+    }
+
+    /// Splits the first member off a path, e.g. `header.version` into `("header", "version")`
+    /// and `items[2].id` into `("items", "[2].id")`
+    pub fn split_path(path: &str) -> (&str, &str) {
+        match path.find(['.', '[']) {
+            Some(i) if path.as_bytes()[i] == b'.' => (&path[..i], &path[i + 1..]),
+            Some(i) => (&path[..i], &path[i..]),
+            None => (path, ""),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
 }
 
 /// Which direction to weigh ranges towards (min bound, upper bound, or none).
@@ -186,3 +1307,255 @@ pub enum Weighted {
     Min,
     Max,
 }
+
+/// Resolution of a timestamp field annotated with `#[lain(now)]`, counted from the Unix epoch.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TimeUnit {
+    #[default]
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl TimeUnit {
+    /// Number of whole units in `duration`
+    pub fn count(&self, duration: std::time::Duration) -> u128 {
+        match self {
+            TimeUnit::Seconds => duration.as_secs() as u128,
+            TimeUnit::Milliseconds => duration.as_millis(),
+            TimeUnit::Microseconds => duration.as_micros(),
+            TimeUnit::Nanoseconds => duration.as_nanos(),
+        }
+    }
+}
+
+/// How a [Timestamp][crate::traits::Timestamp] field is serialized, selected with
+/// `#[lain(timestamp = "s")]`, `#[lain(timestamp = "ms")]`, or `#[lain(timestamp = "iso8601")]`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TimestampFormat {
+    /// Seconds since the Unix epoch as an `i64`
+    #[default]
+    UnixSeconds,
+    /// Milliseconds since the Unix epoch as an `i64`
+    UnixMillis,
+    /// An ISO 8601 UTC string with millisecond precision, e.g. `2038-01-19T03:14:07.000Z`.
+    /// Years outside of 0000-9999 use the expanded `+YYYYYY` form.
+    Iso8601,
+}
+
+impl TimestampFormat {
+    /// Writes `value` to `buffer` in this format, returning the number of bytes written
+    pub fn serialize<W, E, T>(&self, value: &T, buffer: &mut W) -> usize
+    where
+        W: std::io::Write,
+        E: crate::byteorder::ByteOrder,
+        T: crate::traits::Timestamp,
+    {
+        use crate::traits::BinarySerialize;
+
+        let millis = value.unix_millis();
+        match self {
+            TimestampFormat::UnixSeconds => {
+                millis.div_euclid(1000).binary_serialize::<_, E>(buffer)
+            }
+            TimestampFormat::UnixMillis => millis.binary_serialize::<_, E>(buffer),
+            TimestampFormat::Iso8601 => format_iso8601(millis)
+                .as_bytes()
+                .binary_serialize::<_, E>(buffer),
+        }
+    }
+
+    /// Parses a timestamp written by [TimestampFormat::serialize] from the start of `bytes`,
+    /// returning it along with the number of bytes consumed
+    pub fn deserialize<E, T>(&self, bytes: &[u8]) -> Result<(T, usize), DeserializeError>
+    where
+        E: crate::byteorder::ByteOrder,
+        T: crate::traits::Timestamp,
+    {
+        use crate::traits::BinaryDeserialize;
+
+        let (millis, consumed) = match self {
+            TimestampFormat::UnixSeconds => i64::binary_deserialize::<E>(bytes)
+                .map(|(seconds, consumed)| (seconds.saturating_mul(1000), consumed))?,
+            TimestampFormat::UnixMillis => i64::binary_deserialize::<E>(bytes)?,
+            TimestampFormat::Iso8601 => parse_iso8601(bytes)
+                .ok_or_else(|| DeserializeError::new(0, "expected an ISO 8601 UTC timestamp"))?,
+        };
+
+        Ok((T::from_unix_millis(millis), consumed))
+    }
+
+    /// Number of bytes `value` takes up in this format
+    pub fn serialized_size<T: crate::traits::Timestamp>(&self, value: &T) -> usize {
+        match self {
+            TimestampFormat::UnixSeconds | TimestampFormat::UnixMillis => {
+                std::mem::size_of::<i64>()
+            }
+            TimestampFormat::Iso8601 => format_iso8601(value.unix_millis()).len(),
+        }
+    }
+
+    /// Smallest number of bytes any timestamp takes up in this format
+    pub fn min_size(&self) -> usize {
+        match self {
+            TimestampFormat::UnixSeconds | TimestampFormat::UnixMillis => {
+                std::mem::size_of::<i64>()
+            }
+            TimestampFormat::Iso8601 => "0000-01-01T00:00:00.000Z".len(),
+        }
+    }
+}
+
+/// Formats milliseconds since the Unix epoch as an ISO 8601 UTC timestamp
+pub fn format_iso8601(millis: i64) -> String {
+    let days = millis.div_euclid(86_400_000);
+    let ms_of_day = millis.rem_euclid(86_400_000);
+
+    // civil-from-days: http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let year = if (0..=9999).contains(&year) {
+        format!("{:04}", year)
+    } else {
+        format!("{:+07}", year)
+    };
+
+    format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
+
+/// Parses a timestamp in the format written by [format_iso8601] from the start of `bytes`,
+/// returning milliseconds since the Unix epoch and the number of bytes consumed
+fn parse_iso8601(bytes: &[u8]) -> Option<(i64, usize)> {
+    fn number(bytes: &[u8], start: usize, len: usize) -> Option<i64> {
+        let digits = bytes.get(start..start + len)?;
+        if !digits.iter().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        std::str::from_utf8(digits).ok()?.parse().ok()
+    }
+
+    // expanded years carry a sign and 6 digits
+    let (year, rest) = match bytes.first()? {
+        b'+' => (number(bytes, 1, 6)?, 7),
+        b'-' => (-number(bytes, 1, 6)?, 7),
+        _ => (number(bytes, 0, 4)?, 4),
+    };
+
+    let separators = [
+        (0, b'-'),
+        (3, b'-'),
+        (6, b'T'),
+        (9, b':'),
+        (12, b':'),
+        (15, b'.'),
+        (19, b'Z'),
+    ];
+    for (position, separator) in separators.iter() {
+        if bytes.get(rest + position) != Some(separator) {
+            return None;
+        }
+    }
+
+    let month = number(bytes, rest + 1, 2)?;
+    let day = number(bytes, rest + 4, 2)?;
+    let hours = number(bytes, rest + 7, 2)?;
+    let minutes = number(bytes, rest + 10, 2)?;
+    let seconds = number(bytes, rest + 13, 2)?;
+    let millis = number(bytes, rest + 16, 3)?;
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hours > 23
+        || minutes > 59
+        || seconds > 59
+    {
+        return None;
+    }
+
+    // days-from-civil: http://howardhinnant.github.io/date_algorithms.html
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let ms_of_day = ((hours * 60 + minutes) * 60 + seconds) * 1000 + millis;
+
+    Some((days * 86_400_000 + ms_of_day, rest + 20))
+}
+
+impl crate::traits::Timestamp for std::time::SystemTime {
+    fn unix_millis(&self) -> i64 {
+        use std::convert::TryFrom;
+
+        match self.duration_since(std::time::UNIX_EPOCH) {
+            Ok(after) => i64::try_from(after.as_millis()).unwrap_or(i64::MAX),
+            Err(e) => {
+                // round towards negative infinity so that sub-millisecond times before the epoch
+                // don't land on it
+                let before = e.duration();
+                let rounding = u128::from(before.subsec_nanos() % 1_000_000 != 0);
+                i64::try_from(before.as_millis() + rounding)
+                    .map(|millis| -millis)
+                    .unwrap_or(i64::MIN)
+            }
+        }
+    }
+
+    fn from_unix_millis(millis: i64) -> Self {
+        let offset = std::time::Duration::from_millis(millis.unsigned_abs());
+        let time = if millis < 0 {
+            std::time::UNIX_EPOCH.checked_sub(offset)
+        } else {
+            std::time::UNIX_EPOCH.checked_add(offset)
+        };
+
+        time.unwrap_or(std::time::UNIX_EPOCH)
+    }
+}
+
+#[cfg(feature = "chrono_support")]
+impl crate::traits::Timestamp for chrono::DateTime<chrono::Utc> {
+    fn unix_millis(&self) -> i64 {
+        self.timestamp_millis()
+    }
+
+    fn from_unix_millis(millis: i64) -> Self {
+        chrono::DateTime::from_timestamp_millis(millis).unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "time_support")]
+impl crate::traits::Timestamp for time::OffsetDateTime {
+    fn unix_millis(&self) -> i64 {
+        use std::convert::TryFrom;
+
+        let millis = self.unix_timestamp_nanos().div_euclid(1_000_000);
+        i64::try_from(millis).unwrap_or(if millis < 0 { i64::MIN } else { i64::MAX })
+    }
+
+    fn from_unix_millis(millis: i64) -> Self {
+        time::OffsetDateTime::from_unix_timestamp_nanos(i128::from(millis) * 1_000_000)
+            .unwrap_or(time::OffsetDateTime::UNIX_EPOCH)
+    }
+}
@@ -0,0 +1,191 @@
+//! Systematic exploration of the enum variant combinations of a data model.
+//!
+//! When a type contains several enums, the chance of randomly generating a particular
+//! combination of variants shrinks with every enum added, and some combinations are rarely if
+//! ever produced. A [VariantWalk] instead treats the variants of each enum as a dimension and
+//! walks the cartesian product of them: every call to [VariantWalk::generate] picks a
+//! combination which hasn't been generated yet, forces each enum to that variant, and leaves the
+//! remaining (scalar) fields to be randomized as usual.
+//!
+//! ```compile_fail
+//! let mut walk = VariantWalk::<Request>::new(&mut mutator);
+//! info!("walking {} variant combinations", walk.num_combinations());
+//!
+//! while let Some(request) = walk.generate(&mut mutator) {
+//!     send_request(&request);
+//! }
+//! ```
+//!
+//! Every occurrence of an enum type within an instance is forced to the same variant. Enums
+//! which are only reachable through specific variants of another enum are only exercised when
+//! those variants are selected, and variants which can't be generated (e.g. `#[lain(ignore)]`d
+//! ones) fall back to a random variant.
+
+use crate::mutator::Mutator;
+use crate::rand::rngs::StdRng;
+use crate::rand::{Rng, SeedableRng};
+use crate::traits::{EnumVariants, NewFuzzed};
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+/// Number of instances generated by [VariantWalk::new] to discover which enums a type contains
+pub const DEFAULT_DISCOVERY_SAMPLES: usize = 64;
+
+/// Number of random combinations tried before falling back to a linear search for one which
+/// hasn't been generated yet
+const MAX_SAMPLING_ATTEMPTS: usize = 16;
+
+/// An enum type taking part in a [VariantWalk].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkDimension {
+    pub type_name: &'static str,
+    pub variant_names: Vec<&'static str>,
+}
+
+/// Generates instances of `T` covering every combination of the variants of the enums it
+/// contains, without repeats.
+#[derive(Debug, Clone)]
+pub struct VariantWalk<T> {
+    dimensions: Vec<WalkDimension>,
+    visited: HashSet<Vec<usize>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: NewFuzzed> VariantWalk<T> {
+    /// Creates a walk over the enums found in [DEFAULT_DISCOVERY_SAMPLES] randomly generated
+    /// instances of `T`
+    pub fn new<R: Rng>(mutator: &mut Mutator<R>) -> Self {
+        VariantWalk::discover(mutator, DEFAULT_DISCOVERY_SAMPLES)
+    }
+
+    /// Creates a walk over the enums found in `samples` randomly generated instances of `T`.
+    /// The instances are generated with a separate mutator seeded from `mutator`, so any state
+    /// (e.g. variant counts) held by `mutator` is left untouched.
+    pub fn discover<R: Rng>(mutator: &mut Mutator<R>, samples: usize) -> Self {
+        let mut discovery = Mutator::new(StdRng::seed_from_u64(mutator.rng.gen()));
+        discovery.set_track_variants(true);
+
+        for _i in 0..samples {
+            discovery.random_flags();
+            T::new_fuzzed(&mut discovery, None);
+        }
+
+        let mut dimensions: Vec<WalkDimension> = discovery
+            .take_variant_counts()
+            .into_iter()
+            .map(|(type_name, counts)| WalkDimension {
+                type_name,
+                variant_names: counts.names,
+            })
+            .collect();
+        // keep the walk order independent of hash map iteration order
+        dimensions.sort_by_key(|dimension| dimension.type_name);
+
+        VariantWalk {
+            dimensions,
+            visited: HashSet::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Adds the enum `E` to the walk if discovery didn't find it, e.g. because it is only
+    /// reachable through a rarely generated variant
+    pub fn with_enum<E: EnumVariants>(mut self) -> Self {
+        let type_name = std::any::type_name::<E>();
+
+        if E::variant_count() > 0 && !self.dimensions.iter().any(|d| d.type_name == type_name) {
+            self.dimensions.push(WalkDimension {
+                type_name,
+                variant_names: (0..E::variant_count()).map(E::variant_name).collect(),
+            });
+            self.visited.clear();
+        }
+
+        self
+    }
+
+    /// The enums being walked, in the order their variants appear in a combination
+    pub fn dimensions(&self) -> &[WalkDimension] {
+        &self.dimensions
+    }
+
+    /// Total number of variant combinations, saturating at `usize::MAX`
+    pub fn num_combinations(&self) -> usize {
+        self.dimensions.iter().fold(1usize, |total, dimension| {
+            total.saturating_mul(dimension.variant_names.len())
+        })
+    }
+
+    /// Number of combinations generated so far
+    pub fn num_visited(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// Whether every combination has been generated
+    pub fn is_exhausted(&self) -> bool {
+        self.num_visited() >= self.num_combinations()
+    }
+
+    /// Forgets which combinations have been generated so the walk can start over
+    pub fn reset(&mut self) {
+        self.visited.clear();
+    }
+
+    /// Picks a combination which hasn't been generated yet and marks it as visited. The
+    /// combination holds the declaration index of a variant for each of
+    /// [VariantWalk::dimensions]. Returns `None` once the walk is exhausted.
+    pub fn next_combination<R: Rng>(&mut self, mutator: &mut Mutator<R>) -> Option<Vec<usize>> {
+        if self.is_exhausted() {
+            return None;
+        }
+
+        for _i in 0..MAX_SAMPLING_ATTEMPTS {
+            let combination: Vec<usize> = self
+                .dimensions
+                .iter()
+                .map(|dimension| mutator.gen_range(0, dimension.variant_names.len()))
+                .collect();
+
+            if self.visited.insert(combination.clone()) {
+                return Some(combination);
+            }
+        }
+
+        // most combinations have been visited. find one of the remaining ones directly
+        let combination = (0..self.num_combinations())
+            .map(|index| self.combination_at(index))
+            .find(|combination| !self.visited.contains(combination))?;
+        self.visited.insert(combination.clone());
+
+        Some(combination)
+    }
+
+    /// Generates an instance of `T` using the next unvisited combination of variants. Returns
+    /// `None` once the walk is exhausted.
+    pub fn generate<R: Rng>(&mut self, mutator: &mut Mutator<R>) -> Option<T> {
+        let combination = self.next_combination(mutator)?;
+
+        for (dimension, variant) in self.dimensions.iter().zip(combination.iter()) {
+            mutator.force_variant(dimension.type_name, *variant);
+        }
+
+        let value = T::new_fuzzed(mutator, None);
+        mutator.clear_forced_variants();
+
+        Some(value)
+    }
+
+    /// The combination at `index` when the combinations are ordered like an odometer, with
+    /// the last dimension changing fastest
+    fn combination_at(&self, mut index: usize) -> Vec<usize> {
+        let mut combination = vec![0; self.dimensions.len()];
+
+        for (i, dimension) in self.dimensions.iter().enumerate().rev() {
+            let len = dimension.variant_names.len();
+            combination[i] = index % len;
+            index /= len;
+        }
+
+        combination
+    }
+}
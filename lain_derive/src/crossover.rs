@@ -0,0 +1,110 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+use crate::dummy;
+use crate::fallback;
+use crate::internals::ast::{Container, Data, Field, Variant};
+use crate::internals::{Ctxt, Derive};
+use crate::mutations::length_fixups;
+
+pub fn expand_crossover(input: &syn::DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
+    let ctx = Ctxt::new();
+
+    let cont = match Container::from_ast(&ctx, input, Derive::Crossover) {
+        Some(cont) => cont,
+        None => return Err(ctx.check().unwrap_err()),
+    };
+
+    ctx.check()?;
+
+    let ident = &cont.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let lain = cont.attrs.lain_path();
+
+    let crossover_body = match cont.data {
+        Data::Enum(ref variants) => crossover_enum(variants, ident),
+        Data::Struct(_, ref fields) => crossover_struct(fields),
+    };
+
+    let lengths = length_fixups(&cont.data, quote! {self});
+    let fixup = fallback::fixup(quote! {&mut *self});
+
+    let impl_block = quote! {
+        #[allow(clippy)]
+        #[allow(unknown_lints)]
+        #[automatically_derived]
+        impl #impl_generics #lain::traits::Crossover for #ident #ty_generics #where_clause {
+            fn crossover<R: #lain::rand::Rng>(&mut self, other: &Self, mutator: &mut #lain::mutator::Mutator<R>) {
+                #crossover_body
+
+                // the parents' dependent fields (lengths, checksums) may no longer match
+                #lengths
+                #fixup
+            }
+        }
+    };
+
+    Ok(dummy::wrap_in_const("CROSSOVER", ident, impl_block))
+}
+
+fn crossover_struct(fields: &[Field]) -> TokenStream {
+    let crossovers = fields.iter().map(|field| {
+        let member = &field.member;
+        let crossover = fallback::crossover(quote! {&mut self.#member}, quote! {&other.#member});
+
+        quote_spanned! { field.original.span() =>
+            #crossover
+        }
+    });
+
+    quote! {
+        #(#crossovers)*
+    }
+}
+
+/// Parents of the same variant cross that variant's fields. Otherwise the child takes the other
+/// parent's variant as a whole half of the time.
+fn crossover_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream {
+    let arms = variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let members: &Vec<&syn::Member> =
+            &variant.fields.iter().map(|field| &field.member).collect();
+        let self_bindings: &Vec<syn::Ident> = &(0..variant.fields.len())
+            .map(|i| syn::Ident::new(&format!("__self{}", i), proc_macro2::Span::call_site()))
+            .collect();
+        let other_bindings: &Vec<syn::Ident> = &(0..variant.fields.len())
+            .map(|i| syn::Ident::new(&format!("__other{}", i), proc_macro2::Span::call_site()))
+            .collect();
+
+        let crossovers =
+            self_bindings
+                .iter()
+                .zip(other_bindings.iter())
+                .map(|(binding, other_binding)| {
+                    fallback::crossover(quote! {#binding}, quote! {#other_binding})
+                });
+
+        quote_spanned! { variant.original.span() =>
+            (
+                #cont_ident::#variant_ident { #(#members: #self_bindings,)* .. },
+                #cont_ident::#variant_ident { #(#members: #other_bindings,)* .. },
+            ) => {
+                #(#crossovers)*
+                true
+            }
+        }
+    });
+
+    quote! {
+        #[allow(unreachable_patterns)]
+        let same_variant = match (&mut *self, other) {
+            #(#arms)*
+            _ => false,
+        };
+
+        if !same_variant && mutator.gen_chance(_lain::mutator::CHANCE_TO_CROSS_VALUE) {
+            *self = other.clone();
+        }
+    }
+}
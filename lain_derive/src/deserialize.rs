@@ -0,0 +1,252 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+use crate::dummy;
+use crate::internals::ast::{Container, Data, Field, Style, Variant};
+use crate::internals::{Ctxt, Derive};
+
+pub fn expand_binary_deserialize(input: &syn::DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
+    let ctx = Ctxt::new();
+
+    let cont = match Container::from_ast(&ctx, input, Derive::BinaryDeserialize) {
+        Some(cont) => cont,
+        None => return Err(ctx.check().unwrap_err()),
+    };
+
+    check_fields(&ctx, &cont);
+    ctx.check()?;
+
+    let ident = &cont.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let lain = cont.attrs.lain_path();
+
+    let deserialize_body = binary_deserialize_body(&cont);
+
+    let impl_block = quote! {
+        #[allow(clippy)]
+        #[allow(unknown_lints)]
+        #[automatically_derived]
+        impl #impl_generics #lain::traits::BinaryDeserialize for #ident #ty_generics #where_clause {
+            fn binary_deserialize<E: #lain::byteorder::ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), #lain::types::DeserializeError> {
+                use #lain::traits::SerializedSize;
+
+                let (value, mut consumed) = Self::binary_deserialize_flattened::<E>(bytes)?;
+
+                // skip the padding BinarySerialize writes after the fields
+                let size = std::cmp::max(value.serialized_size(), Self::min_nonzero_elements_size());
+                if consumed < size {
+                    if bytes.len() < size {
+                        return Err(#lain::types::DeserializeError::unexpected_end(consumed, size - bytes.len()));
+                    }
+                    consumed = size;
+                }
+
+                Ok((value, consumed))
+            }
+
+            #[allow(unused_mut, unused_variables, unused_assignments)]
+            fn binary_deserialize_flattened<E: #lain::byteorder::ByteOrder>(bytes: &[u8]) -> Result<(Self, usize), #lain::types::DeserializeError> {
+                use #lain::traits::{BinaryDeserialize, ToPrimitive};
+                use #lain::types::DeserializeError;
+
+                let mut offset = 0;
+
+                #deserialize_body
+            }
+        }
+    };
+
+    Ok(dummy::wrap_in_const("BINARYDESERIALIZE", ident, impl_block))
+}
+
+/// Reports fields whose serialized form can't be parsed back into a value
+fn check_fields(cx: &Ctxt, cont: &Container) {
+    let fields: Vec<&Field> = match cont.data {
+        Data::Enum(ref variants) => variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        Data::Struct(_, ref fields) => fields.iter().collect(),
+    };
+
+    for field in fields {
+        if field.attrs.bits().is_some() && field.attrs.bitfield_type().is_some() {
+            cx.error_spanned_by(
+                field.original,
+                "BinaryDeserialize does not support bitfield members with a `bitfield_type`",
+            );
+        }
+    }
+}
+
+fn binary_deserialize_body(cont: &Container) -> TokenStream {
+    let ident = &cont.ident;
+
+    match cont.data {
+        Data::Enum(ref variants) if variants[0].style != Style::Unit => {
+            binary_deserialize_enum(variants, ident)
+        }
+        Data::Enum(ref variants) => binary_deserialize_unit_enum(variants, ident),
+        Data::Struct(style, ref fields) => {
+            let constructor = construct(quote! {#ident}, style, fields);
+
+            binary_deserialize_fields(fields, constructor)
+        }
+    }
+}
+
+/// Unit enums are serialized as their primitive value, so the value is mapped back to the
+/// variant it belongs to
+fn binary_deserialize_unit_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream {
+    let cont_ident_string = cont_ident.to_string();
+    let variant_paths = variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        quote! {#cont_ident::#variant_ident}
+    });
+
+    quote! {
+        let (primitive, consumed) = <<#cont_ident as ToPrimitive>::Output as BinaryDeserialize>::binary_deserialize::<E>(bytes)?;
+
+        for variant in vec![#(#variant_paths,)*] {
+            if variant.to_primitive() == primitive {
+                return Ok((variant, consumed));
+            }
+        }
+
+        Err(DeserializeError::new(0, format!("{:?} is not a valid {}", primitive, #cont_ident_string)))
+    }
+}
+
+/// Nothing in the serialized output says which variant was written, so each variant is tried in
+/// declaration order and the first one whose fields parse wins
+fn binary_deserialize_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream {
+    let cont_ident_string = cont_ident.to_string();
+    let attempts = variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let constructor = construct(
+            quote! {#cont_ident::#variant_ident},
+            variant.style,
+            &variant.fields,
+        );
+        let body = binary_deserialize_fields(&variant.fields, constructor);
+
+        quote_spanned! { variant.original.span() =>
+            let attempt = (|| -> Result<(Self, usize), DeserializeError> {
+                let mut offset = 0;
+
+                #body
+            })();
+
+            match attempt {
+                Ok(parsed) => return Ok(parsed),
+                Err(e) => {
+                    if furthest_error.as_ref().is_none_or(|furthest: &DeserializeError| e.offset >= furthest.offset) {
+                        furthest_error = Some(e);
+                    }
+                }
+            }
+        }
+    });
+
+    quote! {
+        let mut furthest_error: Option<DeserializeError> = None;
+
+        #(#attempts)*
+
+        Err(furthest_error.unwrap_or_else(|| DeserializeError::new(0, format!("no variant of {} matched", #cont_ident_string))))
+    }
+}
+
+/// Parses `fields` in order starting at `offset`, then returns `constructor` along with the
+/// number of bytes consumed
+fn binary_deserialize_fields(fields: &[Field], constructor: TokenStream) -> TokenStream {
+    let deserializers = fields.iter().map(field_deserializer);
+
+    quote! {
+        let mut bitfield: u64 = 0;
+
+        #(#deserializers)*
+
+        Ok((#constructor, offset))
+    }
+}
+
+fn construct(path: TokenStream, style: Style, fields: &[Field]) -> TokenStream {
+    let values: Vec<TokenStream> = fields.iter().map(field_value_ident).collect();
+
+    match style {
+        Style::Struct => {
+            let members = fields.iter().map(|field| &field.member);
+            quote! {#path { #(#members: #values,)* }}
+        }
+        Style::Tuple => quote! {#path(#(#values,)*)},
+        Style::Unit => quote! {#path},
+    }
+}
+
+fn field_value_ident(field: &Field) -> TokenStream {
+    let name = match field.member {
+        syn::Member::Named(ref ident) => format!("__field{}", ident),
+        syn::Member::Unnamed(ref idx) => format!("__field{}", idx.index),
+    };
+    let ident = syn::Ident::new(&name, proc_macro2::Span::call_site());
+
+    quote! {#ident}
+}
+
+fn field_deserializer(field: &Field) -> TokenStream {
+    let ty = &field.ty;
+    let value_ident = field_value_ident(field);
+
+    let endian = if field.attrs.big_endian() {
+        quote! {_lain::byteorder::BigEndian}
+    } else if field.attrs.little_endian() {
+        quote! {_lain::byteorder::LittleEndian}
+    } else {
+        // inherit
+        quote! {E}
+    };
+
+    if field.attrs.transient() {
+        // transient fields aren't in the serialized output
+        quote_spanned! { field.original.span() =>
+            let #value_ident = <#ty as Default>::default();
+        }
+    } else if let Some(bits) = field.attrs.bits() {
+        let bit_mask = 2_u64.pow(bits as u32) - 1;
+        let bit_shift = field.attrs.bit_shift().unwrap();
+
+        // the packed integer is read when its first member is reached
+        let read_bitfield = if bit_shift == 0 {
+            quote_spanned! { field.original.span() =>
+                let (packed, consumed) = <#ty as BinaryDeserialize>::binary_deserialize::<#endian>(&bytes[offset..])
+                    .map_err(|e| e.offset_by(offset))?;
+                bitfield = packed as u64;
+                offset += consumed;
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        quote_spanned! { field.original.span() =>
+            #read_bitfield
+            let #value_ident = ((bitfield >> #bit_shift) & #bit_mask) as #ty;
+        }
+    } else if let Some(format) = field.attrs.timestamp() {
+        quote_spanned! { field.original.span() =>
+            let (#value_ident, consumed) = #format.deserialize::<#endian, #ty>(&bytes[offset..])
+                .map_err(|e| e.offset_by(offset))?;
+            offset += consumed;
+        }
+    } else if field.attrs.flatten() {
+        quote_spanned! { field.original.span() =>
+            let (#value_ident, consumed) = <#ty as BinaryDeserialize>::binary_deserialize_flattened::<#endian>(&bytes[offset..])
+                .map_err(|e| e.offset_by(offset))?;
+            offset += consumed;
+        }
+    } else {
+        quote_spanned! { field.original.span() =>
+            let (#value_ident, consumed) = <#ty as BinaryDeserialize>::binary_deserialize::<#endian>(&bytes[offset..])
+                .map_err(|e| e.offset_by(offset))?;
+            offset += consumed;
+        }
+    }
+}
@@ -17,6 +17,9 @@ pub fn wrap_in_const(_trait_: &str, _ty: &Ident, code: TokenStream) -> TokenStre
         #[allow(clippy)]
         #[allow(unknown_lints)]
         #[allow(non_upper_case_globals, unused_attributes, unused_qualifications)]
+        // sizes and offsets are built up as sums starting at 0, and bitfields are packed with
+        // shifts and masks which may be 0
+        #[allow(clippy::identity_op, clippy::erasing_op, clippy::assign_op_pattern)]
         const #dummy_const: () = {
             #use_lain
             #code
@@ -0,0 +1,81 @@
+//! Calls to lain's traits on values whose types may not implement them. These resolve to a
+//! default when the trait isn't implemented; see `lain::fallback`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Calls `Fixup::fixup` on `value` (a `&mut T`) if `T` implements it
+pub fn fixup(value: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{FixupFallback as _, FixupProbe as _};
+        _lain::fallback::Probe(#value).fixup(mutator);
+    }}
+}
+
+/// Whether `ty` is variable-size, or `false` if it doesn't implement `VariableSizeObject`
+pub fn is_variable_size(ty: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{VariableSizeFallback as _, VariableSizeProbe as _};
+        _lain::fallback::Probe(::std::marker::PhantomData::<#ty>).variable_size()
+    }}
+}
+
+/// `Minimize::shrink` of `value` (a `&T`), or nothing if `T` doesn't implement it
+pub fn shrink(value: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{MinimizeFallback as _, MinimizeProbe as _};
+        _lain::fallback::Probe(#value).shrink()
+    }}
+}
+
+/// Calls `Crossover::crossover` on `value` (a `&mut T`) if `T` implements it, otherwise replaces
+/// `value` with `other` as a whole half of the time
+pub fn crossover(value: TokenStream, other: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{CrossoverFallback as _, CrossoverProbe as _};
+        _lain::fallback::Probe(#value).crossover(#other, mutator);
+    }}
+}
+
+/// A copy of `value` (a `&T`) if `T` implements `Clone`
+pub fn clone_seed(value: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{CloneFallback as _, CloneProbe as _};
+        _lain::fallback::Probe(#value).clone_seed()
+    }}
+}
+
+/// The `Mutator::crossover` of `value` and `other` (both `&T`) if `T` implements `Crossover`
+pub fn crossover_seed(value: TokenStream, other: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{CrossoverSeedFallback as _, CrossoverSeedProbe as _};
+        _lain::fallback::Probe(#value).crossover_seed(#other, mutator)
+    }}
+}
+
+/// Sets the fields of `value` (a `&mut T`) pinned with `Mutator::pin_field`, if `T` implements
+/// `BinarySerialize`. Evaluates to whether any field was set.
+pub fn apply_pinned_fields(value: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{PinnedFieldsFallback as _, PinnedFieldsProbe as _};
+        _lain::fallback::Probe(#value).apply_pinned_fields(mutator)
+    }}
+}
+
+/// Resets `value` (a `&mut T`) to `T::default()` if `T` implements `Default`. Evaluates to
+/// whether it was reset.
+pub fn zero_fill(value: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{ZeroFillFallback as _, ZeroFillProbe as _};
+        _lain::fallback::Probe(#value).zero_fill(mutator)
+    }}
+}
+
+/// Panics if `value` (a `&T`) breaks its `Invariant`, if `T` implements it and invariant checks
+/// are enabled
+pub fn check_invariant(value: TokenStream) -> TokenStream {
+    quote! {{
+        use _lain::fallback::{InvariantFallback as _, InvariantProbe as _};
+        _lain::fallback::Probe(#value).check_invariant();
+    }}
+}
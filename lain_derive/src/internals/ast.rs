@@ -74,6 +74,11 @@ impl<'a> Container<'a> {
             }
         };
 
+        check_dependencies(cx, &data);
+        check_variant_weights(cx, item, &data);
+        check_length_fields(cx, &data);
+        check_checksum_fields(cx, &data);
+
         let item = Container {
             ident: item.ident.clone(),
             attrs,
@@ -132,6 +137,10 @@ fn fields_from_ast<'a>(cx: &Ctxt, fields: &'a Punctuated<syn::Field, Token![,]>)
             original: field,
         };
 
+        if field.attrs.offset() && !is_primitive_integer(field.ty) {
+            cx.error_spanned_by(field.ty, "`offset` fields must be primitive integers");
+        }
+
         if let Some(bits) = field.attrs.bits() {
             field.attrs.set_bit_shift(bitfield_bits);
             bitfield_bits += bits;
@@ -163,17 +172,236 @@ fn fields_from_ast<'a>(cx: &Ctxt, fields: &'a Punctuated<syn::Field, Token![,]>)
     })
     .collect();
 
-    if fields.is_empty() {
-        return fields;
+    // transient fields aren't serialized, so a bitfield before them is the last one written
+    if let Some(field) = fields
+        .iter_mut()
+        .rev()
+        .find(|field| !field.attrs.transient())
+    {
+        field.attrs.set_is_last_field();
     }
 
-    let last_idx = fields.len() - 1;
-    let field = &mut fields[last_idx];
-    field.attrs.set_is_last_field();
-
     fields
 }
 
+/// Locals in scope of the derived `new_fuzzed` and `mutate` bodies, which dependencies can't
+/// be bound as without shadowing them
+const RESERVED_DEPENDENCY_NAMES: [&str; 10] = [
+    "mutator",
+    "max_size",
+    "constraints",
+    "parent_constraints",
+    "uninit_struct",
+    "uninit_struct_ptr",
+    "field_offset",
+    "previous_size",
+    "mutated",
+    "value",
+];
+
+/// Validates the `depends_on` attributes of every field
+fn check_dependencies(cx: &Ctxt, data: &Data) {
+    let fields = match *data {
+        Data::Struct(Style::Struct, ref fields) => fields,
+        Data::Struct(_, ref fields) => {
+            for field in fields.iter().filter(|f| !f.attrs.depends_on().is_empty()) {
+                cx.error_spanned_by(
+                    field.original,
+                    "`depends_on` requires a struct with named fields",
+                );
+            }
+            return;
+        }
+        Data::Enum(ref variants) => {
+            for field in variants
+                .iter()
+                .flat_map(|v| v.fields.iter())
+                .filter(|f| !f.attrs.depends_on().is_empty())
+            {
+                cx.error_spanned_by(
+                    field.original,
+                    "`depends_on` is not supported on enum variants",
+                );
+            }
+            return;
+        }
+    };
+
+    let mut valid = true;
+    for field in fields.iter() {
+        for dependency in field.attrs.depends_on() {
+            let name = dependency.to_string();
+            if !fields.iter().any(|f| member_is(&f.member, &name)) {
+                cx.error_spanned_by(dependency, format!("no field named `{}`", name));
+                valid = false;
+            } else if member_is(&field.member, &name) {
+                cx.error_spanned_by(dependency, "a field cannot depend on itself");
+                valid = false;
+            } else if RESERVED_DEPENDENCY_NAMES.contains(&name.as_str()) {
+                cx.error_spanned_by(
+                    dependency,
+                    format!(
+                        "`{}` is used by the generated code and cannot be depended on",
+                        name
+                    ),
+                );
+                valid = false;
+            }
+        }
+    }
+
+    if valid && generation_order(fields).is_none() {
+        cx.error_spanned_by(
+            fields[0].original,
+            "the `depends_on` attributes of this struct form a cycle",
+        );
+    }
+}
+
+/// Validates the `length_of` attributes of every field
+fn check_length_fields(cx: &Ctxt, data: &Data) {
+    let fields = match *data {
+        Data::Struct(Style::Struct, ref fields) => fields,
+        Data::Struct(_, ref fields) => {
+            for field in fields.iter().filter(|f| f.attrs.length_of().is_some()) {
+                cx.error_spanned_by(
+                    field.original,
+                    "`length_of` requires a struct with named fields",
+                );
+            }
+            return;
+        }
+        Data::Enum(ref variants) => {
+            for field in variants
+                .iter()
+                .flat_map(|v| v.fields.iter())
+                .filter(|f| f.attrs.length_of().is_some())
+            {
+                cx.error_spanned_by(
+                    field.original,
+                    "`length_of` is not supported on enum variants",
+                );
+            }
+            return;
+        }
+    };
+
+    for field in fields.iter() {
+        let target = match field.attrs.length_of() {
+            Some(target) => target,
+            None => continue,
+        };
+        let name = target.to_string();
+
+        if !fields.iter().any(|f| member_is(&f.member, &name)) {
+            cx.error_spanned_by(target, format!("no field named `{}`", name));
+        } else if member_is(&field.member, &name) {
+            cx.error_spanned_by(target, "a field cannot hold its own length");
+        }
+
+        if !is_primitive_integer(field.ty) {
+            cx.error_spanned_by(field.ty, "`length_of` fields must be primitive integers");
+        }
+    }
+}
+
+/// Validates the `checksum_from` attributes of every field. Checksums only cover the bytes
+/// serialized before them, so the range must start at an earlier field.
+fn check_checksum_fields(cx: &Ctxt, data: &Data) {
+    let fields = match *data {
+        Data::Struct(_, ref fields) => fields,
+        Data::Enum(ref variants) => {
+            for field in variants
+                .iter()
+                .flat_map(|v| v.fields.iter())
+                .filter(|f| f.attrs.checksum_from().is_some())
+            {
+                cx.error_spanned_by(
+                    field.original,
+                    "`checksum_from` is not supported on enum variants",
+                );
+            }
+            return;
+        }
+    };
+
+    for (i, field) in fields.iter().enumerate() {
+        let start = match field.attrs.checksum_from() {
+            Some(start) => start,
+            None => continue,
+        };
+        let name = start.to_string();
+
+        if !fields[..i].iter().any(|f| member_is(&f.member, &name)) {
+            cx.error_spanned_by(
+                start,
+                format!("no field named `{}` is declared before the checksum", name),
+            );
+        }
+    }
+}
+
+/// Variants are picked with a `WeightedIndex`, which can't be built if every variant which may
+/// be generated has a weight of 0
+fn check_variant_weights(cx: &Ctxt, item: &syn::DeriveInput, data: &Data) {
+    let variants = match data {
+        Data::Enum(variants) => variants,
+        Data::Struct(..) => return,
+    };
+
+    let mut weights = variants
+        .iter()
+        .filter(|variant| !variant.attrs.ignore())
+        .map(|variant| variant.attrs.weight().unwrap_or(1))
+        .peekable();
+
+    if weights.peek().is_some() && weights.all(|weight| weight == 0) {
+        cx.error_spanned_by(item, "at least one variant must have a nonzero `weight`");
+    }
+}
+
+/// Indices of `fields` in the order they must be generated so that every field comes after
+/// the fields it `depends_on`. Independent fields keep their declaration order. Returns `None`
+/// if the dependencies form a cycle.
+pub fn generation_order(fields: &[Field]) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = Vec::with_capacity(fields.len());
+
+    while order.len() < fields.len() {
+        let next = (0..fields.len()).find(|i| {
+            !order.contains(i)
+                && fields[*i].attrs.depends_on().iter().all(|dependency| {
+                    order
+                        .iter()
+                        .any(|j| member_is(&fields[*j].member, &dependency.to_string()))
+                })
+        })?;
+
+        order.push(next);
+    }
+
+    Some(order)
+}
+
+/// Whether any field of the struct `depends_on` another
+pub fn has_dependencies(fields: &[Field]) -> bool {
+    fields.iter().any(|f| !f.attrs.depends_on().is_empty())
+}
+
+fn member_is(member: &syn::Member, name: &str) -> bool {
+    match *member {
+        syn::Member::Named(ref ident) => ident == name,
+        syn::Member::Unnamed(_) => false,
+    }
+}
+
+fn is_primitive_integer(ty: &syn::Type) -> bool {
+    [
+        "u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "usize", "isize",
+    ]
+    .iter()
+    .any(|primitive| is_primitive_type(ty, primitive))
+}
+
 pub fn is_primitive_type(ty: &syn::Type, primitive: &str) -> bool {
     match *ty {
         syn::Type::Path(ref ty) => ty.qself.is_none() && is_primitive_path(&ty.path, primitive),
@@ -150,6 +150,76 @@ pub fn unraw(ident: &Ident) -> String {
     ident.to_string().trim_start_matches("r#").to_owned()
 }
 
+/// Resolution of a `#[lain(now)]` timestamp
+#[derive(PartialEq)]
+pub enum TimeUnit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl ToTokens for TimeUnit {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match *self {
+            TimeUnit::Seconds => tokens.extend(quote! {_lain::types::TimeUnit::Seconds}),
+            TimeUnit::Milliseconds => tokens.extend(quote! {_lain::types::TimeUnit::Milliseconds}),
+            TimeUnit::Microseconds => tokens.extend(quote! {_lain::types::TimeUnit::Microseconds}),
+            TimeUnit::Nanoseconds => tokens.extend(quote! {_lain::types::TimeUnit::Nanoseconds}),
+        }
+    }
+}
+
+/// Serialization format of a `#[lain(timestamp = "...")]` field
+pub enum TimestampFormat {
+    UnixSeconds,
+    UnixMillis,
+    Iso8601,
+}
+
+impl ToTokens for TimestampFormat {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match *self {
+            TimestampFormat::UnixSeconds => {
+                tokens.extend(quote! {_lain::types::TimestampFormat::UnixSeconds})
+            }
+            TimestampFormat::UnixMillis => {
+                tokens.extend(quote! {_lain::types::TimestampFormat::UnixMillis})
+            }
+            TimestampFormat::Iso8601 => {
+                tokens.extend(quote! {_lain::types::TimestampFormat::Iso8601})
+            }
+        }
+    }
+}
+
+/// Parses a unit suffix such as `ms` into a `TimeUnit` and the number of nanoseconds per unit
+fn parse_time_unit(unit: &str) -> Option<(TimeUnit, u64)> {
+    match unit {
+        "s" => Some((TimeUnit::Seconds, 1_000_000_000)),
+        "ms" => Some((TimeUnit::Milliseconds, 1_000_000)),
+        "us" => Some((TimeUnit::Microseconds, 1_000)),
+        "ns" => Some((TimeUnit::Nanoseconds, 1)),
+        _ => None,
+    }
+}
+
+/// Parses a duration such as `5s`, `250ms`, or `2h` into nanoseconds
+fn parse_duration_nanos(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value.parse().ok()?;
+
+    let nanos_per_unit = match unit.trim() {
+        "m" => 60 * 1_000_000_000,
+        "h" => 60 * 60 * 1_000_000_000,
+        unit => parse_time_unit(unit)?.1,
+    };
+
+    value.checked_mul(nanos_per_unit)
+}
+
 /// Represents field attribute information
 pub struct Field {
     bits: Option<usize>,
@@ -163,6 +233,24 @@ pub struct Field {
     little_endian: bool,
     big_endian: bool,
     weight_to: Option<WeightTo>,
+    mutation_weight: Option<u64>,
+    mutation_chance: Option<f64>,
+    from_pool: Option<String>,
+    auto_increment: Option<String>,
+    now: Option<TimeUnit>,
+    jitter: Option<u64>,
+    timestamp: Option<TimestampFormat>,
+    offset: bool,
+    flatten: bool,
+    trailer: Option<TokenStream>,
+    depends_on: Vec<syn::Ident>,
+    transient: bool,
+    memoize: bool,
+    length_of: Option<syn::Ident>,
+    checksum: bool,
+    checksum_from: Option<syn::Ident>,
+    element_group: Option<u64>,
+    shared: Option<String>,
     is_last_field: bool,
 }
 
@@ -179,6 +267,24 @@ impl Field {
         let mut big_endian = BoolAttr::none(cx, BIG_ENDIAN);
         let mut little_endian = BoolAttr::none(cx, LITTLE_ENDIAN);
         let mut weight_to = Attr::none(cx, WEIGHT_TO);
+        let mut mutation_weight = Attr::none(cx, MUTATION_WEIGHT);
+        let mut mutation_chance = Attr::none(cx, MUTATION_CHANCE);
+        let mut from_pool = Attr::none(cx, FROM_POOL);
+        let mut auto_increment = Attr::none(cx, AUTO_INCREMENT);
+        let mut now = Attr::none(cx, NOW);
+        let mut jitter = Attr::none(cx, JITTER);
+        let mut timestamp = Attr::none(cx, TIMESTAMP);
+        let mut offset = BoolAttr::none(cx, OFFSET);
+        let mut flatten = BoolAttr::none(cx, FLATTEN);
+        let mut trailer = Attr::none(cx, TRAILER);
+        let mut depends_on = Attr::none(cx, DEPENDS_ON);
+        let mut transient = BoolAttr::none(cx, TRANSIENT);
+        let mut memoize = BoolAttr::none(cx, MEMOIZE);
+        let mut length_of = Attr::none(cx, LENGTH_OF);
+        let mut checksum = BoolAttr::none(cx, CHECKSUM);
+        let mut checksum_from = Attr::none(cx, CHECKSUM_FROM);
+        let mut element_group = Attr::none(cx, ELEMENT_GROUP);
+        let mut shared = Attr::none(cx, SHARED);
 
         for meta_items in field.attrs.iter().filter_map(get_lain_meta_items) {
             for meta_item in meta_items {
@@ -287,6 +393,211 @@ impl Field {
                             }
                         }
                     }
+                    // `#[lain(mutation_weight = 10)]`
+                    Meta(NameValue(ref m)) if m.ident == MUTATION_WEIGHT => {
+                        if let Int(ref i) = m.lit {
+                            mutation_weight.set(&m.ident, i.value());
+                        } else {
+                            cx.error_spanned_by(
+                                &m.lit,
+                                format!(
+                                    "failed to parse integer expression for `{}`",
+                                    MUTATION_WEIGHT
+                                ),
+                            );
+                        }
+                    }
+                    // `#[lain(mutation_chance = 0.05)]`
+                    Meta(NameValue(ref m)) if m.ident == MUTATION_CHANCE => {
+                        let chance = match m.lit {
+                            Float(ref f) => Some(f.value()),
+                            Int(ref i) => Some(i.value() as f64),
+                            _ => None,
+                        };
+
+                        match chance {
+                            Some(chance) if (0.0..=1.0).contains(&chance) => {
+                                mutation_chance.set(&m.ident, chance);
+                            }
+                            Some(_) => cx.error_spanned_by(
+                                &m.lit,
+                                format!("`{}` must be between 0 and 1", MUTATION_CHANCE),
+                            ),
+                            None => cx.error_spanned_by(
+                                &m.lit,
+                                format!(
+                                    "failed to parse float expression for `{}`",
+                                    MUTATION_CHANCE
+                                ),
+                            ),
+                        }
+                    }
+                    // `#[lain(element_group = 3)]`
+                    Meta(NameValue(ref m)) if m.ident == ELEMENT_GROUP => match m.lit {
+                        Int(ref i) if i.value() > 0 => element_group.set(&m.ident, i.value()),
+                        _ => cx.error_spanned_by(
+                            &m.lit,
+                            format!("`{}` must be a positive integer", ELEMENT_GROUP),
+                        ),
+                    },
+                    // `#[lain(from_pool = "session_ids")]`
+                    Meta(NameValue(ref m)) if m.ident == FROM_POOL => {
+                        if let Ok(s) = get_lit_str(cx, FROM_POOL, FROM_POOL, &m.lit) {
+                            from_pool.set(&m.ident, s.value());
+                        }
+                    }
+                    // `#[lain(shared = "session_id")]`
+                    Meta(NameValue(ref m)) if m.ident == SHARED => {
+                        if let Ok(s) = get_lit_str(cx, SHARED, SHARED, &m.lit) {
+                            shared.set(&m.ident, s.value());
+                        }
+                    }
+                    // `#[lain(auto_increment)]`
+                    Meta(Word(ref word)) if word == AUTO_INCREMENT => match field.ident {
+                        Some(ref ident) => auto_increment.set(word, unraw(ident)),
+                        None => cx.error_spanned_by(
+                            word,
+                            format!(
+                                "`{}` on a tuple field requires a counter name: `{} = \"...\"`",
+                                AUTO_INCREMENT, AUTO_INCREMENT
+                            ),
+                        ),
+                    },
+                    // `#[lain(auto_increment = "seq")]`
+                    Meta(NameValue(ref m)) if m.ident == AUTO_INCREMENT => {
+                        if let Ok(s) = get_lit_str(cx, AUTO_INCREMENT, AUTO_INCREMENT, &m.lit) {
+                            auto_increment.set(&m.ident, s.value());
+                        }
+                    }
+                    // `#[lain(now)]`
+                    Meta(Word(ref word)) if word == NOW => {
+                        now.set(word, TimeUnit::Seconds);
+                    }
+                    // `#[lain(now = "ms")]`
+                    Meta(NameValue(ref m)) if m.ident == NOW => {
+                        if let Ok(s) = get_lit_str(cx, NOW, NOW, &m.lit) {
+                            match parse_time_unit(&s.value()) {
+                                Some((unit, _)) => now.set(&m.ident, unit),
+                                None => cx.error_spanned_by(
+                                    &m.lit,
+                                    format!(
+                                        "unknown time unit for `{}`, expected one of `s`, `ms`, `us`, `ns`",
+                                        NOW
+                                    ),
+                                ),
+                            }
+                        }
+                    }
+                    // `#[lain(jitter = "5s")]`
+                    Meta(NameValue(ref m)) if m.ident == JITTER => {
+                        if let Ok(s) = get_lit_str(cx, JITTER, JITTER, &m.lit) {
+                            match parse_duration_nanos(&s.value()) {
+                                Some(nanos) => jitter.set(&m.ident, nanos),
+                                None => cx.error_spanned_by(
+                                    &m.lit,
+                                    format!(
+                                        "failed to parse duration for `{}`, expected e.g. `\"5s\"` or `\"250ms\"`",
+                                        JITTER
+                                    ),
+                                ),
+                            }
+                        }
+                    }
+                    // `#[lain(offset)]`
+                    Meta(Word(ref word)) if word == OFFSET => {
+                        offset.set_true(word);
+                    }
+                    // `#[lain(flatten)]`
+                    Meta(Word(ref word)) if word == FLATTEN => {
+                        flatten.set_true(word);
+                    }
+                    // `#[lain(transient)]`
+                    Meta(Word(ref word)) if word == TRANSIENT => {
+                        transient.set_true(word);
+                    }
+                    // `#[lain(memoize)]`
+                    Meta(Word(ref word)) if word == MEMOIZE => {
+                        memoize.set_true(word);
+                    }
+                    // `#[lain(length_of = "payload")]`
+                    Meta(NameValue(ref m)) if m.ident == LENGTH_OF => {
+                        if let Ok(s) = get_lit_str(cx, LENGTH_OF, LENGTH_OF, &m.lit) {
+                            match syn::parse_str::<syn::Ident>(s.value().trim()) {
+                                Ok(name) => length_of.set(&m.ident, name),
+                                Err(_) => cx.error_spanned_by(
+                                    &m.lit,
+                                    format!("expected a field name for `{}`", LENGTH_OF),
+                                ),
+                            }
+                        }
+                    }
+                    // `#[lain(checksum)]`
+                    Meta(Word(ref word)) if word == CHECKSUM => {
+                        checksum.set_true(word);
+                    }
+                    // `#[lain(checksum_from = "payload")]`
+                    Meta(NameValue(ref m)) if m.ident == CHECKSUM_FROM => {
+                        if let Ok(s) = get_lit_str(cx, CHECKSUM_FROM, CHECKSUM_FROM, &m.lit) {
+                            match syn::parse_str::<syn::Ident>(s.value().trim()) {
+                                Ok(name) => checksum_from.set(&m.ident, name),
+                                Err(_) => cx.error_spanned_by(
+                                    &m.lit,
+                                    format!("expected a field name for `{}`", CHECKSUM_FROM),
+                                ),
+                            }
+                        }
+                    }
+                    // `#[lain(trailer = "crc32")]`
+                    Meta(NameValue(ref m)) if m.ident == TRAILER => {
+                        if let Ok(s) = get_lit_str(cx, TRAILER, TRAILER, &m.lit) {
+                            if let Ok(tokens) = TokenStream::from_str(&s.value()) {
+                                trailer.set(&m.ident, tokens);
+                            } else {
+                                cx.error_spanned_by(
+                                    &m.lit,
+                                    format!("failed to parse tokens for `{}`", TRAILER),
+                                )
+                            }
+                        }
+                    }
+                    // `#[lain(depends_on = "count, kind")]`
+                    Meta(NameValue(ref m)) if m.ident == DEPENDS_ON => {
+                        if let Ok(s) = get_lit_str(cx, DEPENDS_ON, DEPENDS_ON, &m.lit) {
+                            let names: Result<Vec<syn::Ident>, _> = s
+                                .value()
+                                .split(',')
+                                .map(|name| syn::parse_str::<syn::Ident>(name.trim()))
+                                .collect();
+
+                            match names {
+                                Ok(names) => depends_on.set(&m.ident, names),
+                                Err(_) => cx.error_spanned_by(
+                                    &m.lit,
+                                    format!(
+                                        "expected a comma-separated list of field names for `{}`",
+                                        DEPENDS_ON
+                                    ),
+                                ),
+                            }
+                        }
+                    }
+                    // `#[lain(timestamp = "iso8601")]`
+                    Meta(NameValue(ref m)) if m.ident == TIMESTAMP => {
+                        if let Ok(s) = get_lit_str(cx, TIMESTAMP, TIMESTAMP, &m.lit) {
+                            match s.value().as_ref() {
+                                "s" => timestamp.set(&m.ident, TimestampFormat::UnixSeconds),
+                                "ms" => timestamp.set(&m.ident, TimestampFormat::UnixMillis),
+                                "iso8601" => timestamp.set(&m.ident, TimestampFormat::Iso8601),
+                                _ => cx.error_spanned_by(
+                                    &m.lit,
+                                    format!(
+                                        "unknown format for `{}`, expected one of `s`, `ms`, `iso8601`",
+                                        TIMESTAMP
+                                    ),
+                                ),
+                            }
+                        }
+                    }
                     Meta(ref meta_item) => {
                         cx.error_spanned_by(
                             meta_item.name(),
@@ -300,6 +611,113 @@ impl Field {
             }
         }
 
+        if jitter.value.is_some() && now.value.is_none() {
+            cx.error_spanned_by(
+                &jitter.tokens,
+                format!("`{}` can only be used alongside `{}`", JITTER, NOW),
+            );
+        }
+
+        if timestamp.value.is_some() && bits.value.is_some() {
+            cx.error_spanned_by(
+                &timestamp.tokens,
+                format!("`{}` cannot be used on a bitfield", TIMESTAMP),
+            );
+        }
+
+        if flatten.get() && (bits.value.is_some() || timestamp.value.is_some()) {
+            cx.error_spanned_by(
+                &flatten.0.tokens,
+                format!(
+                    "`{}` cannot be used alongside `{}` or `{}`",
+                    FLATTEN, BITS, TIMESTAMP
+                ),
+            );
+        }
+
+        if trailer.value.is_some()
+            && (bits.value.is_some() || timestamp.value.is_some() || flatten.get())
+        {
+            cx.error_spanned_by(
+                &trailer.tokens,
+                format!(
+                    "`{}` cannot be used alongside `{}`, `{}`, or `{}`",
+                    TRAILER, BITS, TIMESTAMP, FLATTEN
+                ),
+            );
+        }
+
+        let is_checksum = checksum.get() || checksum_from.value.is_some();
+        if is_checksum
+            && (bits.value.is_some()
+                || timestamp.value.is_some()
+                || flatten.get()
+                || trailer.value.is_some())
+        {
+            let tokens = if checksum.get() {
+                &checksum.0.tokens
+            } else {
+                &checksum_from.tokens
+            };
+            cx.error_spanned_by(
+                tokens,
+                format!(
+                    "`{}` cannot be used alongside `{}`, `{}`, `{}`, or `{}`",
+                    CHECKSUM, BITS, TIMESTAMP, FLATTEN, TRAILER
+                ),
+            );
+        }
+
+        if transient.get()
+            && (bits.value.is_some()
+                || timestamp.value.is_some()
+                || flatten.get()
+                || trailer.value.is_some()
+                || offset.get()
+                || is_checksum)
+        {
+            cx.error_spanned_by(
+                &transient.0.tokens,
+                format!(
+                    "`{}` cannot be used alongside `{}`, `{}`, `{}`, `{}`, `{}`, or `{}`",
+                    TRANSIENT, BITS, TIMESTAMP, FLATTEN, TRAILER, OFFSET, CHECKSUM
+                ),
+            );
+        }
+
+        if memoize.get()
+            && (ignore.get()
+                || initializer.value.is_some()
+                || from_pool.value.is_some()
+                || auto_increment.value.is_some()
+                || now.value.is_some())
+        {
+            cx.error_spanned_by(
+                &memoize.0.tokens,
+                format!(
+                    "`{}` cannot be used alongside `{}`, `{}`, `{}`, `{}`, or `{}`",
+                    MEMOIZE, IGNORE, INITIALIZER, FROM_POOL, AUTO_INCREMENT, NOW
+                ),
+            );
+        }
+
+        if shared.value.is_some()
+            && (ignore.get()
+                || initializer.value.is_some()
+                || from_pool.value.is_some()
+                || auto_increment.value.is_some()
+                || now.value.is_some()
+                || memoize.get())
+        {
+            cx.error_spanned_by(
+                &shared.tokens,
+                format!(
+                    "`{}` cannot be used alongside `{}`, `{}`, `{}`, `{}`, `{}`, or `{}`",
+                    SHARED, IGNORE, INITIALIZER, FROM_POOL, AUTO_INCREMENT, NOW, MEMOIZE
+                ),
+            );
+        }
+
         Field {
             bits: bits.get(),
             bit_shift: None, // this gets fixed up later
@@ -312,6 +730,24 @@ impl Field {
             little_endian: little_endian.get(),
             big_endian: big_endian.get(),
             weight_to: weight_to.get(),
+            mutation_weight: mutation_weight.get(),
+            mutation_chance: mutation_chance.get(),
+            from_pool: from_pool.get(),
+            auto_increment: auto_increment.get(),
+            now: now.get(),
+            jitter: jitter.get(),
+            timestamp: timestamp.get(),
+            offset: offset.get(),
+            flatten: flatten.get(),
+            trailer: trailer.get(),
+            depends_on: depends_on.get().unwrap_or_default(),
+            transient: transient.get(),
+            memoize: memoize.get(),
+            length_of: length_of.get(),
+            checksum: is_checksum,
+            checksum_from: checksum_from.get(),
+            element_group: element_group.get(),
+            shared: shared.get(),
             is_last_field: false,
         }
     }
@@ -371,6 +807,98 @@ impl Field {
     pub fn weight_to(&self) -> Option<&WeightTo> {
         self.weight_to.as_ref()
     }
+
+    pub fn mutation_weight(&self) -> Option<u64> {
+        self.mutation_weight
+    }
+
+    pub fn mutation_chance(&self) -> Option<f64> {
+        self.mutation_chance
+    }
+
+    /// Number of consecutive elements of a `Vec` field which form one record
+    pub fn element_group(&self) -> Option<u64> {
+        self.element_group
+    }
+
+    pub fn pool(&self) -> Option<&str> {
+        self.from_pool.as_deref()
+    }
+
+    /// Name of the sequence counter used by an `auto_increment` field
+    pub fn auto_increment(&self) -> Option<&str> {
+        self.auto_increment.as_deref()
+    }
+
+    /// Resolution of a `now` timestamp field
+    pub fn now(&self) -> Option<&TimeUnit> {
+        self.now.as_ref()
+    }
+
+    /// Jitter applied to a `now` timestamp field, in nanoseconds
+    pub fn jitter(&self) -> u64 {
+        self.jitter.unwrap_or(0)
+    }
+
+    /// Whether the field holds an offset which is mutated along with the structure's other
+    /// offset fields
+    pub fn offset(&self) -> bool {
+        self.offset
+    }
+
+    /// Whether the field's own fields are serialized inline, as if they were declared in the
+    /// containing structure
+    pub fn flatten(&self) -> bool {
+        self.flatten
+    }
+
+    /// Function computing the field's serialized value from the bytes of the structure
+    /// serialized before it
+    pub fn trailer(&self) -> Option<&TokenStream> {
+        self.trailer.as_ref()
+    }
+
+    /// Fields which must be generated before this one, and which its attribute expressions
+    /// may refer to by name
+    pub fn depends_on(&self) -> &[syn::Ident] {
+        &self.depends_on
+    }
+
+    /// Whether the field is generated and mutated but never serialized
+    pub fn transient(&self) -> bool {
+        self.transient
+    }
+
+    /// The field whose serialized size this field holds, if any
+    pub fn length_of(&self) -> Option<&syn::Ident> {
+        self.length_of.as_ref()
+    }
+
+    /// Whether the field is a `Checksum` computed from the bytes serialized before it
+    pub fn checksum(&self) -> bool {
+        self.checksum
+    }
+
+    /// The field a checksum starts at, if not the start of the structure
+    pub fn checksum_from(&self) -> Option<&syn::Ident> {
+        self.checksum_from.as_ref()
+    }
+
+    /// Whether the field is generated once per mutator and copied afterwards
+    pub fn memoize(&self) -> bool {
+        self.memoize
+    }
+
+    /// Name of the shared value a `shared` field takes, which every field sharing the name
+    /// resolves to
+    pub fn shared(&self) -> Option<&str> {
+        self.shared.as_deref()
+    }
+
+    /// Format a timestamp field is serialized in, if it overrides the type's own serialization
+    pub fn timestamp(&self) -> Option<&TimestampFormat> {
+        self.timestamp.as_ref()
+    }
 }
 
 /// Represents enum variant information
@@ -378,6 +906,9 @@ pub struct Variant {
     weight: Option<u64>,
     ignore: bool,
     ignore_chance: Option<f64>,
+    min_count: Option<usize>,
+    max_count: Option<usize>,
+    value: Option<u64>,
 }
 
 impl Variant {
@@ -386,6 +917,9 @@ impl Variant {
         let mut weight = Attr::none(cx, WEIGHT);
         let mut ignore = BoolAttr::none(cx, IGNORE);
         let mut ignore_chance = Attr::none(cx, IGNORE_CHANCE);
+        let mut min_count = Attr::none(cx, MIN_COUNT);
+        let mut max_count = Attr::none(cx, MAX_COUNT);
+        let mut value = Attr::none(cx, VALUE);
 
         for meta_items in variant.attrs.iter().filter_map(get_lain_meta_items) {
             for meta_item in meta_items {
@@ -418,6 +952,39 @@ impl Variant {
                             );
                         }
                     }
+                    // `#[lain(min_count = 1)]`
+                    Meta(NameValue(ref m)) if m.ident == MIN_COUNT => {
+                        if let Int(ref i) = m.lit {
+                            min_count.set(&m.ident, i.value() as usize);
+                        } else {
+                            cx.error_spanned_by(
+                                &m.lit,
+                                format!("failed to parse integer expression for {}", MIN_COUNT),
+                            );
+                        }
+                    }
+                    // `#[lain(max_count = 1)]`
+                    Meta(NameValue(ref m)) if m.ident == MAX_COUNT => {
+                        if let Int(ref i) = m.lit {
+                            max_count.set(&m.ident, i.value() as usize);
+                        } else {
+                            cx.error_spanned_by(
+                                &m.lit,
+                                format!("failed to parse integer expression for {}", MAX_COUNT),
+                            );
+                        }
+                    }
+                    // `#[lain(value = 0x8001)]`
+                    Meta(NameValue(ref m)) if m.ident == VALUE => {
+                        if let Int(ref i) = m.lit {
+                            value.set(&m.ident, i.value());
+                        } else {
+                            cx.error_spanned_by(
+                                &m.lit,
+                                format!("failed to parse integer expression for {}", VALUE),
+                            );
+                        }
+                    }
                     Meta(ref meta_item) => {
                         cx.error_spanned_by(
                             meta_item.name(),
@@ -431,10 +998,24 @@ impl Variant {
             }
         }
 
+        let min_count = min_count.get();
+        let max_count = max_count.get();
+        if let (Some(min), Some(max)) = (min_count, max_count) {
+            if min > max {
+                cx.error_spanned_by(
+                    &variant.ident,
+                    format!("`{}` must not be greater than `{}`", MIN_COUNT, MAX_COUNT),
+                );
+            }
+        }
+
         Variant {
             weight: weight.get(),
             ignore: ignore.get(),
             ignore_chance: ignore_chance.get(),
+            min_count,
+            max_count,
+            value: value.get(),
         }
     }
 
@@ -449,6 +1030,19 @@ impl Variant {
     pub fn ignore_chance(&self) -> Option<f64> {
         self.ignore_chance
     }
+
+    pub fn min_count(&self) -> Option<usize> {
+        self.min_count
+    }
+
+    pub fn max_count(&self) -> Option<usize> {
+        self.max_count
+    }
+
+    /// The value serialized for this variant in place of its discriminant
+    pub fn value(&self) -> Option<u64> {
+        self.value
+    }
 }
 
 pub fn get_lain_meta_items(attr: &syn::Attribute) -> Option<Vec<syn::NestedMeta>> {
@@ -9,4 +9,7 @@ pub enum Derive {
     NewFuzzed,
     Mutatable,
     BinarySerialize,
+    BinaryDeserialize,
+    Minimize,
+    Crossover,
 }
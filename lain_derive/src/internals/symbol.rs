@@ -18,6 +18,27 @@ pub const SERIALIZED_SIZE: Symbol = Symbol("serialized_size");
 pub const MIN_SERIALIZED_SIZE: Symbol = Symbol("min_serialized_size");
 pub const WEIGHT: Symbol = Symbol("weight");
 pub const WEIGHT_TO: Symbol = Symbol("weight_to");
+pub const MIN_COUNT: Symbol = Symbol("min_count");
+pub const MAX_COUNT: Symbol = Symbol("max_count");
+pub const MUTATION_WEIGHT: Symbol = Symbol("mutation_weight");
+pub const MUTATION_CHANCE: Symbol = Symbol("mutation_chance");
+pub const FROM_POOL: Symbol = Symbol("from_pool");
+pub const AUTO_INCREMENT: Symbol = Symbol("auto_increment");
+pub const NOW: Symbol = Symbol("now");
+pub const JITTER: Symbol = Symbol("jitter");
+pub const VALUE: Symbol = Symbol("value");
+pub const TIMESTAMP: Symbol = Symbol("timestamp");
+pub const OFFSET: Symbol = Symbol("offset");
+pub const FLATTEN: Symbol = Symbol("flatten");
+pub const TRAILER: Symbol = Symbol("trailer");
+pub const DEPENDS_ON: Symbol = Symbol("depends_on");
+pub const TRANSIENT: Symbol = Symbol("transient");
+pub const MEMOIZE: Symbol = Symbol("memoize");
+pub const LENGTH_OF: Symbol = Symbol("length_of");
+pub const CHECKSUM: Symbol = Symbol("checksum");
+pub const CHECKSUM_FROM: Symbol = Symbol("checksum_from");
+pub const ELEMENT_GROUP: Symbol = Symbol("element_group");
+pub const SHARED: Symbol = Symbol("shared");
 
 impl PartialEq<Symbol> for Ident {
     fn eq(&self, word: &Symbol) -> bool {
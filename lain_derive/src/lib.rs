@@ -13,8 +13,12 @@ use proc_macro2::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
 //mod fuzzerobject;
+mod crossover;
+mod deserialize;
 mod dummy;
+mod fallback;
 mod internals;
+mod minimize;
 mod mutations;
 mod serialize;
 
@@ -44,6 +48,86 @@ fn to_compile_errors(errors: Vec<syn::Error>) -> proc_macro2::TokenStream {
 ///
 /// let choice: Foo = rand::gen();
 /// ```
+///
+/// Struct fields are generated in declaration order unless a field is marked
+/// `#[lain(depends_on = "field1, field2")]`, in which case the named fields are generated
+/// first and are in scope as references in the field's `min`, `max`, and `initializer`
+/// expressions. `Mutatable` mutates the fields in the same order.
+///
+/// ```compile_fail
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct Records {
+///     #[lain(depends_on = "count", min = "*count as usize", max = "*count as usize")]
+///     records: Vec<Record>,
+///     count: u8,
+/// }
+/// ```
+///
+/// Enum variants are picked uniformly unless they're marked `#[lain(weight = N)]`, which makes
+/// a variant `N` times as likely as an unmarked one (or never generated, if `N` is 0). The
+/// weights also apply when `Mutatable` switches an enum to another variant.
+///
+/// ```compile_fail
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// enum Header {
+///     Plain(PlainHeader),
+///     Compressed(CompressedHeader),
+///     #[lain(weight = 5)]
+///     Malformed(Vec<u8>),
+/// }
+/// ```
+///
+/// Fields marked `#[lain(memoize)]` are generated once per `Mutator` and copied into every value
+/// generated afterwards, which suits expensive substructures that only need to be valid, like a
+/// certificate chain. They're generated anew with probability
+/// `Mutator::memoized_regeneration_chance`, both when generating and when mutating, and are
+/// otherwise left alone by `Mutatable`. The field's type must implement `Clone` and `Send`.
+///
+/// ```compile_fail
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct Handshake {
+///     version: u16,
+///     #[lain(memoize)]
+///     certificates: CertificateChain,
+/// }
+/// ```
+///
+/// Fields marked `#[lain(shared = "name")]` take the mutator's shared value `name`, which is
+/// generated the first time any field sharing the name is generated. Messages generated one
+/// after another, such as a request and its response, thereby carry the same session ID or
+/// nonce until `Mutator::clear_shared_values` starts a new session. Mutating the field resets
+/// it to the shared value, except that with probability `Mutator::shared_mismatch_chance` it's
+/// mutated on its own. The field's type must implement `Clone` and `Send`.
+///
+/// ```compile_fail
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct Request {
+///     #[lain(shared = "session")]
+///     session_id: u64,
+///     payload: Vec<u8>,
+/// }
+///
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct Response {
+///     #[lain(shared = "session")]
+///     session_id: u64,
+///     status: u8,
+/// }
+/// ```
+///
+/// Integer fields marked `#[lain(length_of = "field")]` hold the serialized size of another
+/// field of the struct. They're set after the struct is generated, after it's mutated, and by
+/// `Mutatable::fixup_dependents`. With probability `Mutator::length_corruption_chance` the
+/// length is deliberately wrong instead.
+///
+/// ```compile_fail
+/// #[derive(NewFuzzed, Mutatable, BinarySerialize)]
+/// struct Record {
+///     #[lain(length_of = "payload")]
+///     length: u16,
+///     payload: Vec<u8>,
+/// }
+/// ```
 #[proc_macro_derive(NewFuzzed, attributes(lain))]
 pub fn new_fuzzed(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -56,6 +140,25 @@ pub fn new_fuzzed(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// The byteorder of fields can be overridden with `#[byteorder(big)]` or
 /// `#[byteorder(little)]`
 ///
+/// Fields marked `#[lain(flatten)]` are serialized inline as if the nested struct's fields
+/// were declared in the parent: the nested struct's `serialized_size` padding is dropped and
+/// its fields are reported by `field_layout` under the parent's path.
+///
+/// Fields marked `#[lain(trailer = "function")]` (total lengths, trailing checksums,
+/// terminating sentinels) are not serialized from their own value. Instead, `function` is
+/// passed everything serialized before the field within the same struct or enum variant as a
+/// `&[u8]` and returns the value to serialize. Trailer fields should have a fixed size since
+/// `serialized_size` still uses the size of the field's own value.
+///
+/// Fields marked `#[lain(checksum)]` hold a `lain::checksum::Checksum`, which is serialized as
+/// the checksum of everything serialized before it within the same struct or enum variant.
+/// `#[lain(checksum_from = "field")]` starts the checksummed range at an earlier field of a
+/// struct instead.
+///
+/// Fields marked `#[lain(transient)]` are generated and mutated like any other field, so they
+/// can drive the generation of the fields which depend on them, but are never serialized and
+/// don't count towards `serialized_size` or `field_layout`.
+///
 /// # Example
 ///
 /// ```compile_fail
@@ -105,6 +208,43 @@ pub fn binary_serialize(input: proc_macro::TokenStream) -> proc_macro::TokenStre
         .into()
 }
 
+/// Implements [lain::traits::BinaryDeserialize] on the given struct/enum, parsing the output of
+/// `#[derive(BinarySerialize)]` back into a value. The type must also implement
+/// `SerializedSize` so that padding written after the fields can be skipped.
+///
+/// Fields are parsed in declaration order and honor the same `#[lain(big_endian)]`,
+/// `#[lain(little_endian)]`, `#[lain(bits = N)]`, `#[lain(flatten)]`, and
+/// `#[lain(timestamp = "...")]` attributes as serialization. Trailer fields are parsed from
+/// the bytes as they are rather than recomputed, and transient fields are set to their
+/// `Default` value.
+/// Unit enums are matched by their primitive value. Since nothing in the serialized output
+/// says which variant of an enum with fields was written, each variant is tried in declaration
+/// order and the first one whose fields parse is returned.
+///
+/// # Example
+///
+/// ```compile_fail
+/// #[derive(BinarySerialize, BinaryDeserialize)]
+/// struct Header {
+///     magic: u32,
+///     #[lain(bits = 4)]
+///     version: u8,
+///     #[lain(bits = 4)]
+///     flags: u8,
+///     #[lain(little_endian)]
+///     length: u16,
+/// }
+///
+/// let header = Header::from_bytes::<BigEndian>(&packet)?;
+/// ```
+#[proc_macro_derive(BinaryDeserialize, attributes(lain))]
+pub fn binary_deserialize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    deserialize::expand_binary_deserialize(&input)
+        .unwrap_or_else(to_compile_errors)
+        .into()
+}
+
 /// Automatically implements [trait@lain::traits::Mutatable] with basic
 /// randomization
 ///
@@ -114,6 +254,25 @@ pub fn binary_serialize(input: proc_macro::TokenStream) -> proc_macro::TokenStre
 /// - Min/max values for primitives can be specified using `#[lain(min = 10, max = 20)]`.
 /// - Fields can be ignored using #[lain(ignore)].
 /// - Custom initializers can be specified using #[lain(initializer = "my_initializer_func()")]
+/// - Each field is mutated with probability 0.98 unless it's marked
+///   `#[lain(mutation_chance = 0.05)]`, which keeps fields such as magic numbers and versions
+///   mostly intact while the rest of the structure changes.
+/// - With probability `Mutator::zero_fill_chance`, a field whose type implements `Default` is
+///   reset to its default value (e.g. zero, an empty list, or `None`) instead of being mutated.
+///   Fields with a `min` or `max` are never reset. This is off unless enabled with
+///   `Mutator::set_zero_fill_chance`.
+/// - `Vec` fields whose records span several elements can be marked
+///   `#[lain(element_group = 3)]`, so that elements are only added, removed, or moved around in
+///   whole records.
+/// - Enums whose variants have fields are re-generated with `NewFuzzed`, possibly as another
+///   variant, with probability `Mutator::variant_switch_chance`. Otherwise the fields of the
+///   current variant are mutated.
+/// - Types which implement [trait@lain::traits::Invariant] are checked after mutations which
+///   end by fixing them up, when lain's `invariant_checks` feature is enabled in a debug build.
+/// - Fields are only fixed up and checked if their type implements `Fixup` or `Invariant`.
+///   Whether it does is decided where the type is derived, so a field of a generic type `T` is
+///   skipped unless `T` is bounded by the trait (e.g. `struct Wrapper<T: Fixup>`), even if the
+///   type it's instantiated with implements it.
 ///
 /// # Example
 ///
@@ -152,6 +311,64 @@ pub fn mutatable_helper(input: proc_macro::TokenStream) -> proc_macro::TokenStre
         .into()
 }
 
+/// Implements [lain::traits::Crossover] on the given struct/enum so that two instances can be
+/// combined with `Mutator::crossover`. The type must also implement `Clone`.
+///
+/// Struct fields are crossed one by one. Enums of the same variant cross the fields of that
+/// variant; otherwise the child takes the other parent's variant half of the time. The child's
+/// `Fixup` is run afterwards.
+///
+/// Fields whose types don't implement `Crossover` are taken whole from either parent. This
+/// includes fields of a generic type `T` unless `T` is bounded by `Crossover`.
+///
+/// # Example
+///
+/// ```compile_fail
+/// #[derive(Clone, NewFuzzed, Mutatable, BinarySerialize, Crossover)]
+/// struct Request {
+///     header: Header,
+///     body: Vec<Chunk>,
+/// }
+///
+/// let child = mutator.crossover(&first, &second);
+/// ```
+#[proc_macro_derive(Crossover, attributes(lain))]
+pub fn crossover(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    crossover::expand_crossover(&input)
+        .unwrap_or_else(to_compile_errors)
+        .into()
+}
+
+/// Implements [lain::traits::Minimize] on the given struct/enum so that crashing inputs can be
+/// reduced with `lain::minimize::minimize`. The type must also implement `Clone`.
+///
+/// Struct fields are shrunk in declaration order. An enum is first replaced with each unit
+/// variant declared before its current one (skipping variants marked `#[lain(ignore)]`), then
+/// the fields of its current variant are shrunk.
+///
+/// Fields whose types don't implement `Minimize` are left as they are. This includes fields of
+/// a generic type `T` unless `T` is bounded by `Minimize`.
+///
+/// # Example
+///
+/// ```compile_fail
+/// #[derive(Clone, NewFuzzed, BinarySerialize, Minimize)]
+/// struct Packet {
+///     kind: PacketKind,
+///     payload: Vec<u8>,
+/// }
+///
+/// let minimized = minimize(crashing_packet, 10_000, |packet| crashes(packet)).input;
+/// ```
+#[proc_macro_derive(Minimize, attributes(lain))]
+pub fn minimize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    minimize::expand_minimize(&input)
+        .unwrap_or_else(to_compile_errors)
+        .into()
+}
+
 /// Automatically implements [trait@lain::traits::VariableSizeObject]
 ///
 /// # Example
@@ -217,8 +434,9 @@ fn variable_size_object_helper(input: &DeriveInput) -> TokenStream {
 
                     for field in fields.named.iter() {
                         let ty = &field.ty;
+                        let is_variable_size = fallback::is_variable_size(quote! {#ty});
                         tokens.extend(quote_spanned! { field.span() =>
-                            || <#ty>::is_variable_size()
+                            || #is_variable_size
                         });
                     }
 
@@ -235,15 +453,17 @@ fn variable_size_object_helper(input: &DeriveInput) -> TokenStream {
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
-    quote! {
+    let impl_block = quote! {
         #[allow(clippy)]
         #[allow(unknown_lints)]
-        impl #impl_generics ::lain::traits::VariableSizeObject for #name #ty_generics #where_clause {
+        impl #impl_generics _lain::traits::VariableSizeObject for #name #ty_generics #where_clause {
             fn is_variable_size() -> bool {
                 #imp
             }
         }
-    }
+    };
+
+    dummy::wrap_in_const("VARIABLE_SIZE_OBJECT", name, impl_block)
 }
 
 /// A "catch-all" derive for NewFuzzed, Mutatable, and VariableObjectSize
@@ -264,25 +484,25 @@ pub fn fuzzer_object(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 }
 
 /// Implements `ToPrimitive<u8>` for the given enum.
-#[proc_macro_derive(ToPrimitiveU8)]
+#[proc_macro_derive(ToPrimitiveU8, attributes(lain))]
 pub fn to_primitive_u8(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     to_primitive_of_type(input, quote! {u8})
 }
 
 /// Implements `ToPrimitive<u16>` for the given enum.
-#[proc_macro_derive(ToPrimitiveU16)]
+#[proc_macro_derive(ToPrimitiveU16, attributes(lain))]
 pub fn to_primitive_u16(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     to_primitive_of_type(input, quote! {u16})
 }
 
 /// Implements `ToPrimitive<u32>` for the given enum.
-#[proc_macro_derive(ToPrimitiveU32)]
+#[proc_macro_derive(ToPrimitiveU32, attributes(lain))]
 pub fn to_primitive_u32(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     to_primitive_of_type(input, quote! {u32})
 }
 
 /// Implements `ToPrimitive<u64>` for the given enum.
-#[proc_macro_derive(ToPrimitiveU64)]
+#[proc_macro_derive(ToPrimitiveU64, attributes(lain))]
 pub fn to_primitive_u64(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     to_primitive_of_type(input, quote! {u64})
 }
@@ -293,19 +513,22 @@ fn to_primitive_of_type(
 ) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    let name = input.ident;
+    let name = &input.ident;
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
+    let body = match to_primitive_body(&input, &ty) {
+        Ok(body) => body,
+        Err(errors) => return to_compile_errors(errors).into(),
+    };
+
     let expanded = quote! {
         #[allow(clippy)]
         #[allow(unknown_lints)]
         impl #impl_generics ::lain::traits::ToPrimitive for #name #ty_generics #where_clause {
             type Output = #ty;
 
-            fn to_primitive(&self) -> #ty {
-                *self as #ty
-            }
+            #body
         }
     };
 
@@ -314,3 +537,72 @@ fn to_primitive_of_type(
 
     proc_macro::TokenStream::from(expanded)
 }
+
+/// Unit enum variants are converted to the value given by `#[lain(value = N)]`, or to their
+/// discriminant if none is given
+fn to_primitive_body(
+    input: &DeriveInput,
+    ty: &proc_macro2::TokenStream,
+) -> Result<proc_macro2::TokenStream, Vec<syn::Error>> {
+    use internals::ast::{Container, Data, Style};
+
+    let ctx = internals::Ctxt::new();
+    let cont = match Container::from_ast(&ctx, input, internals::Derive::BinarySerialize) {
+        Some(cont) => cont,
+        None => return Err(ctx.check().unwrap_err()),
+    };
+
+    let variants = match cont.data {
+        Data::Enum(ref variants) if variants.iter().all(|v| v.style == Style::Unit) => variants,
+        _ => {
+            ctx.check()?;
+            return Ok(quote! {
+                fn to_primitive(&self) -> #ty {
+                    *self as #ty
+                }
+            });
+        }
+    };
+
+    let ident = &cont.ident;
+    let mut seen_values = vec![];
+    let mut values = vec![];
+    let mut match_arms = vec![];
+
+    for variant in variants.iter() {
+        let variant_ident = &variant.ident;
+        let value = match variant.attrs.value() {
+            Some(value) => {
+                if seen_values.contains(&value) {
+                    ctx.error_spanned_by(
+                        &variant.ident,
+                        format!("value 0x{:X} is used by more than one variant", value),
+                    );
+                }
+                seen_values.push(value);
+
+                // unsuffixed so that values which don't fit the output type fail to compile
+                let value = proc_macro2::Literal::u64_unsuffixed(value);
+                quote! {#value}
+            }
+            None => quote! {#ident::#variant_ident as #ty},
+        };
+
+        match_arms.push(quote! {#ident::#variant_ident => #value,});
+        values.push(value);
+    }
+
+    ctx.check()?;
+
+    Ok(quote! {
+        fn to_primitive(&self) -> #ty {
+            match *self {
+                #(#match_arms)*
+            }
+        }
+
+        fn valid_primitives() -> Vec<#ty> {
+            vec![#(#values,)*]
+        }
+    })
+}
@@ -0,0 +1,123 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+use crate::dummy;
+use crate::fallback;
+use crate::internals::ast::{Container, Data, Field, Style, Variant};
+use crate::internals::{Ctxt, Derive};
+
+pub fn expand_minimize(input: &syn::DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
+    let ctx = Ctxt::new();
+
+    let cont = match Container::from_ast(&ctx, input, Derive::Minimize) {
+        Some(cont) => cont,
+        None => return Err(ctx.check().unwrap_err()),
+    };
+
+    ctx.check()?;
+
+    let ident = &cont.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let lain = cont.attrs.lain_path();
+
+    let shrink_body = match cont.data {
+        Data::Enum(ref variants) => shrink_enum(variants, ident),
+        Data::Struct(_, ref fields) => shrink_struct(fields),
+    };
+
+    let impl_block = quote! {
+        #[allow(clippy)]
+        #[allow(unknown_lints)]
+        #[automatically_derived]
+        impl #impl_generics #lain::traits::Minimize for #ident #ty_generics #where_clause {
+            #[allow(unused_mut, irrefutable_let_patterns)]
+            fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+                let mut candidates: Vec<Box<dyn Iterator<Item = Self> + '_>> = vec![];
+
+                #shrink_body
+
+                Box::new(candidates.into_iter().flatten())
+            }
+        }
+    };
+
+    Ok(dummy::wrap_in_const("MINIMIZE", ident, impl_block))
+}
+
+/// Each field is shrunk in declaration order while the others keep their values
+fn shrink_struct(fields: &[Field]) -> TokenStream {
+    let shrinkers = fields.iter().map(|field| {
+        let member = &field.member;
+        let shrink = fallback::shrink(quote! {&self.#member});
+
+        quote_spanned! { field.original.span() =>
+            candidates.push(Box::new(#shrink.map(move |simpler| {
+                let mut candidate = self.clone();
+                candidate.#member = simpler;
+                candidate
+            })));
+        }
+    });
+
+    quote! {
+        #(#shrinkers)*
+    }
+}
+
+/// An enum is first replaced with each unit variant declared before its current variant, then
+/// the fields of its current variant are shrunk. Variants marked `#[lain(ignore)]` are never
+/// chosen.
+fn shrink_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream {
+    let arms = variants.iter().enumerate().map(|(i, variant)| {
+        let variant_ident = &variant.ident;
+
+        let earlier_units = variants[..i]
+            .iter()
+            .filter(|earlier| earlier.style == Style::Unit && !earlier.attrs.ignore())
+            .map(|earlier| {
+                let earlier_ident = &earlier.ident;
+                quote! {#cont_ident::#earlier_ident}
+            });
+
+        let members: Vec<&syn::Member> =
+            variant.fields.iter().map(|field| &field.member).collect();
+        let bindings: Vec<syn::Ident> = (0..variant.fields.len())
+            .map(|j| syn::Ident::new(&format!("__field{}", j), proc_macro2::Span::call_site()))
+            .collect();
+
+        let shrinkers: Vec<TokenStream> = variant
+            .fields
+            .iter()
+            .zip(bindings.iter())
+            .map(|(field, binding)| {
+                let member = &field.member;
+                let shrink = fallback::shrink(quote! {#binding});
+
+                quote_spanned! { field.original.span() =>
+                    candidates.push(Box::new(#shrink.map(move |simpler| {
+                        let mut candidate = self.clone();
+                        if let #cont_ident::#variant_ident { #member: ref mut field, .. } = candidate {
+                            *field = simpler;
+                        }
+                        candidate
+                    })));
+                }
+            })
+            .collect();
+
+        quote_spanned! { variant.original.span() =>
+            #cont_ident::#variant_ident { #(#members: ref #bindings,)* .. } => {
+                candidates.push(Box::new(vec![#(#earlier_units,)*].into_iter()));
+
+                #(#shrinkers)*
+            }
+        }
+    });
+
+    quote! {
+        match *self {
+            #(#arms)*
+        }
+    }
+}
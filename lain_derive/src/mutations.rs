@@ -4,9 +4,13 @@ use std::str::FromStr;
 use syn::spanned::Spanned;
 
 use crate::dummy;
-use crate::internals::ast::{Container, Data, Field, Style, Variant};
+use crate::fallback;
+use crate::internals::ast::{self, Container, Data, Field, Style, Variant};
 use crate::internals::{attr, Ctxt, Derive};
 
+/// Chance for a field without `#[lain(mutation_chance)]` to be mutated
+const DEFAULT_FIELD_MUTATION_CHANCE: f64 = 0.98;
+
 pub fn expand_mutatable(input: &syn::DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
     let ctx = Ctxt::new();
 
@@ -24,6 +28,12 @@ pub fn expand_mutatable(input: &syn::DeriveInput) -> Result<TokenStream, Vec<syn
     let lain = cont.attrs.lain_path();
 
     let ident_str = ident.to_string();
+    let lengths = length_fixups(&cont.data, quote! {self});
+    let fixup = fallback::fixup(quote! {&mut *self});
+    let check_invariant = fallback::check_invariant(quote! {&*self});
+    let pins = fallback::apply_pinned_fields(quote! {&mut *self});
+    let clone_seed = fallback::clone_seed(quote! {self});
+    let crossover_seed = fallback::crossover_seed(quote! {self}, quote! {other});
 
     let impl_block = quote! {
         #[allow(clippy)]
@@ -34,18 +44,42 @@ pub fn expand_mutatable(input: &syn::DeriveInput) -> Result<TokenStream, Vec<syn
             // really use the min/max
             type RangeType = u8;
 
+            #[allow(unused_labels)]
             fn mutate<R: #lain::rand::Rng>(&mut self, mutator: &mut #lain::mutator::Mutator<R>, parent_constraints: Option<&#lain::types::Constraints<Self::RangeType>>)
             {
                 _lain::log::trace!("Mutating {}", #ident_str);
 
-                {
+                // mutation stops early by breaking out of this block, so the fixups below
+                // always run
+                'mutate: {
                     #body
                 };
 
+                if mutator.depth() == 0 {
+                    #pins;
+                }
+
+                #lengths
+
                 if mutator.gen_chance(0.10) {
-                    self.fixup(mutator);
+                    #fixup
+                    #check_invariant
                 }
             }
+
+            fn fixup_dependents<R: #lain::rand::Rng>(&mut self, mutator: &mut #lain::mutator::Mutator<R>) {
+                #lengths
+                #fixup
+                #check_invariant
+            }
+
+            fn clone_seed(&self) -> Option<Self> {
+                #clone_seed
+            }
+
+            fn crossover_seed<R: #lain::rand::Rng>(&self, other: &Self, mutator: &mut #lain::mutator::Mutator<R>) -> Option<Self> {
+                #crossover_seed
+            }
         }
     };
 
@@ -70,6 +104,10 @@ pub fn expand_new_fuzzed(input: &syn::DeriveInput) -> Result<TokenStream, Vec<sy
     let body = new_fuzzed_body(&cont);
     let lain = cont.attrs.lain_path();
 
+    let lengths = length_fixups(&cont.data, quote! {value});
+    let fixup = fallback::fixup(quote! {&mut value});
+    let pins = fallback::apply_pinned_fields(quote! {&mut value});
+
     let impl_block = quote! {
         #[allow(clippy)]
         #[allow(unknown_lints)]
@@ -81,16 +119,152 @@ pub fn expand_new_fuzzed(input: &syn::DeriveInput) -> Result<TokenStream, Vec<sy
             // really use the min/max
             fn new_fuzzed<R: #lain::rand::Rng>(mutator: &mut #lain::mutator::Mutator<R>, parent_constraints: Option<&#lain::types::Constraints<Self::RangeType>>) -> Self
             {
-                #body
+                let mut value: Self = {
+                    #body
+                };
+
+                // pinned fields may change what the dependent fields should be
+                if mutator.depth() == 0 && #pins {
+                    #lengths
+                    #fixup
+                }
+
+                value
             }
         }
     };
 
+    let mut impl_block = impl_block;
+    if let Data::Enum(ref variants) = cont.data {
+        impl_block.extend(enum_variants_impl(input, variants, ident));
+    }
+
     let data = dummy::wrap_in_const("NEWFUZZED", ident, impl_block);
 
     Ok(data)
 }
 
+fn enum_variants_impl(
+    input: &syn::DeriveInput,
+    variants: &[Variant],
+    cont_ident: &syn::Ident,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let variant_count = variants.len();
+    let cont_ident_string = cont_ident.to_string();
+
+    // unit enums are not required to implement SerializedSize, and have no fields that
+    // would need constraints anyways
+    let constraints_prelude = if variants.iter().all(|v| v.fields.is_empty()) {
+        TokenStream::new()
+    } else {
+        let prelude = constraints_prelude();
+        quote! {
+            let parent_constraints: Option<&_lain::types::Constraints<u8>> = None;
+
+            #prelude
+        }
+    };
+
+    let mut index_arms = vec![];
+    let mut names = vec![];
+    let mut limits = vec![];
+    let mut new_variant_arms = vec![];
+    let value_fixup = fallback::fixup(quote! {&mut value});
+
+    for (i, variant) in variants.iter().enumerate() {
+        let variant_ident = &variant.ident;
+        let full_ident = quote! {#cont_ident::#variant_ident};
+
+        index_arms.push(quote! {
+            #full_ident { .. } => #i,
+        });
+
+        names.push(variant_ident.to_string());
+
+        let min_count = variant.attrs.min_count().unwrap_or(0);
+        let max_count = variant
+            .attrs
+            .max_count()
+            .map_or_else(|| quote! {None}, |max| quote! {Some(#max)});
+        limits.push(quote! {(#min_count, #max_count)});
+
+        let mut field_identifiers = vec![];
+        let field_initializers: Vec<TokenStream> = variant
+            .fields
+            .iter()
+            .map(|field| {
+                let (value_ident, _field_ident_string, initializer) =
+                    field_initializer(field, "__field", Some(variant_ident));
+                field_identifiers.push(quote_spanned! { field.member.span() => #value_ident });
+
+                initializer
+            })
+            .collect();
+
+        let initializer = if field_initializers.is_empty() {
+            quote! {
+                let mut value = #full_ident;
+            }
+        } else {
+            quote! {
+                #(#field_initializers)*
+
+                let mut value = #full_ident(#(#field_identifiers,)*);
+            }
+        };
+
+        new_variant_arms.push(quote! {
+            #i => {
+                #initializer
+
+                #value_fixup
+
+                value
+            }
+        });
+    }
+
+    quote! {
+        #[allow(clippy)]
+        #[allow(unknown_lints)]
+        #[automatically_derived]
+        impl #impl_generics _lain::traits::EnumVariants for #cont_ident #ty_generics #where_clause {
+            fn variant_count() -> usize {
+                #variant_count
+            }
+
+            fn variant_index(&self) -> usize {
+                match *self {
+                    #(#index_arms)*
+                }
+            }
+
+            fn variant_name(index: usize) -> &'static str {
+                static names: [&str; #variant_count] = [#(#names,)*];
+
+                names[index]
+            }
+
+            fn variant_count_limits(index: usize) -> (usize, Option<usize>) {
+                static limits: [(usize, Option<usize>); #variant_count] = [#(#limits,)*];
+
+                limits[index]
+            }
+
+            #[allow(unused_mut)]
+            fn new_fuzzed_variant<R: _lain::rand::Rng>(mutator: &mut _lain::mutator::Mutator<R>, index: usize) -> Self {
+                #constraints_prelude
+
+                match index {
+                    #(#new_variant_arms)*
+                    _ => panic!("variant index {} is out of bounds for {}", index, #cont_ident_string),
+                }
+            }
+        }
+    }
+}
+
 fn mutatable_body(cont: &Container) -> TokenStream {
     match cont.data {
         Data::Enum(ref variants) if variants[0].style != Style::Unit => {
@@ -98,12 +272,46 @@ fn mutatable_body(cont: &Container) -> TokenStream {
         }
         Data::Enum(ref variants) => mutatable_unit_enum(variants, &cont.ident),
         Data::Struct(Style::Struct, ref fields) | Data::Struct(Style::Tuple, ref fields) => {
-            mutatable_struct(fields)
+            let offsets = mutatable_offsets(fields);
+            let body = mutatable_struct(fields);
+
+            quote! {
+                #offsets
+                #body
+            }
         }
         Data::Struct(Style::Unit, ref _fields) => TokenStream::new(),
     }
 }
 
+/// Occasionally mutates the struct's `#[lain(offset)]` fields together with
+/// `Mutator::mutate_offsets` instead of mutating each field independently
+fn mutatable_offsets(fields: &[Field]) -> TokenStream {
+    let offsets: Vec<&Field> = fields
+        .iter()
+        .filter(|f| f.attrs.offset() && !f.attrs.ignore())
+        .collect();
+
+    if offsets.is_empty() {
+        return TokenStream::new();
+    }
+
+    let members: Vec<&syn::Member> = offsets.iter().map(|f| &f.member).collect();
+    let targets = members.clone();
+    let types: Vec<&syn::Type> = offsets.iter().map(|f| f.ty).collect();
+    let indices: Vec<usize> = (0..offsets.len()).collect();
+
+    quote! {
+        if mutator.gen_chance(_lain::mutator::CHANCE_TO_MUTATE_OFFSETS) {
+            let mut offsets = [#(self.#members as u64,)*];
+            mutator.mutate_offsets(&mut offsets);
+            #(self.#targets = offsets[#indices] as #types;)*
+
+            break 'mutate;
+        }
+    }
+}
+
 fn mutatable_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream {
     let constraints_prelude = mutatable_constraints_prelude();
     let match_arms = mutatable_enum_visitor(variants, cont_ident);
@@ -113,24 +321,27 @@ fn mutatable_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream
     }
 
     quote! {
-        // 10% chance to re-generate this field
-        if mutator.gen_chance(0.10) {
+        // switch variants by re-generating this field
+        if mutator.gen_chance(mutator.variant_switch_chance()) {
+            mutator.record_operator(_lain::operators::MutationOperator::Regenerate);
             *self = Self::new_fuzzed(mutator, parent_constraints.and_then(|constraints| {
                 let mut constraints = constraints.clone();
 
                 // If base object size has already been accounted for, that means
                 // the max_size represents the amount of extra data that may be consumed.
-                // We want to give back the size of this object when generating a new instance
+                // We want to give back the size of this object when generating a new instance,
+                // which then has the whole budget to pick a variant from
                 if constraints.base_object_size_accounted_for {
                     if let Some(max_size) = constraints.max_size.as_mut() {
                         *max_size = self.serialized_size() + *max_size;
+                        constraints.base_object_size_accounted_for = false;
                     }
                 }
 
                 Some(constraints)
             }).as_ref());
 
-            return;
+            break 'mutate;
         }
 
         #constraints_prelude
@@ -163,9 +374,17 @@ fn mutatable_unit_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenSt
                 _lain::rand::distributions::WeightedIndex::new(weights.iter()).unwrap();
         }
 
-        let idx: usize = dist.sample(&mut mutator.rng);
+        let mut idx: usize = dist.sample(&mut mutator.rng);
+        // variants left out by swarm testing are skipped unless they keep coming up
+        for _i in 0..4 {
+            if !mutator.variant_disabled::<Self>(_lain::traits::EnumVariants::variant_index(&options[idx])) {
+                break;
+            }
+            idx = dist.sample(&mut mutator.rng);
+        }
 
         mutator.increment_fields_fuzzed();
+        mutator.record_variant::<Self>(_lain::traits::EnumVariants::variant_index(&options[idx]));
 
         *self = options[idx]
     }
@@ -190,12 +409,29 @@ fn mutatable_struct(fields: &[Field]) -> TokenStream {
         });
     }
 
+    if fields.iter().any(|f| f.attrs.mutation_weight().is_some()) {
+        return mutatable_weighted_struct(fields, &match_arms);
+    }
+
+    if ast::has_dependencies(fields) {
+        // dependencies are mutated before the fields whose constraints refer to them
+        let ordered_mutators = ordered_by_dependencies(fields, &mutators);
+
+        return quote! {
+            #prelude
+
+            #(#ordered_mutators)*
+        };
+    }
+
+    let self_is_variable_size = fallback::is_variable_size(quote! {Self});
+
     quote! {
         use _lain::rand::seq::index::sample;
 
         #prelude
 
-        if Self::is_variable_size() {
+        if #self_is_variable_size {
             // this makes for ugly code generation, but better perf
             for i in sample(&mut mutator.rng, #len, #len).iter() {
                 match i {
@@ -209,6 +445,42 @@ fn mutatable_struct(fields: &[Field]) -> TokenStream {
     }
 }
 
+/// Mutates fields picked in proportion to their `mutation_weight` (1 if unspecified). As many
+/// picks are made as there are fields, so a field may be mutated several times while another is
+/// left alone. The mutator's per-iteration field budget still applies.
+fn mutatable_weighted_struct(fields: &[Field], match_arms: &[TokenStream]) -> TokenStream {
+    let prelude = mutatable_constraints_prelude();
+    let weights: Vec<u64> = fields
+        .iter()
+        .map(|f| f.attrs.mutation_weight().unwrap_or(1))
+        .collect();
+    let len = weights.len();
+
+    if weights.iter().all(|w| *w == 0) {
+        return TokenStream::new();
+    }
+
+    quote! {
+        use _lain::rand::distributions::Distribution;
+
+        static mutation_weights: [u64; #len] = [#(#weights,)*];
+
+        _lain::lazy_static::lazy_static! {
+            static ref dist: _lain::rand::distributions::WeightedIndex<u64> =
+                _lain::rand::distributions::WeightedIndex::new(&mutation_weights).unwrap();
+        }
+
+        #prelude
+
+        for _i in 0..#len {
+            match dist.sample(&mut mutator.rng) {
+                #(#match_arms)*
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
 fn mutatable_unit_enum_visitor(
     variants: &[Variant],
     cont_ident: &syn::Ident,
@@ -218,13 +490,14 @@ fn mutatable_unit_enum_visitor(
     let variants = variants
         .iter()
         .filter_map(|variant| {
-            //if variant.attrs.ignore() {
-            //    None
-            //} else {
-            let variant_ident = &variant.ident;
-            weights.push(variant.attrs.weight().unwrap_or(1));
-            Some(quote! {#cont_ident::#variant_ident})
-            //}
+            // like NewFuzzed, never switch to a variant which is never generated
+            if variant.attrs.ignore() {
+                None
+            } else {
+                let variant_ident = &variant.ident;
+                weights.push(variant.attrs.weight().unwrap_or(1));
+                Some(quote! {#cont_ident::#variant_ident})
+            }
         })
         .collect();
 
@@ -248,7 +521,7 @@ fn mutatable_enum_visitor(variants: &[Variant], cont_ident: &syn::Ident) -> Vec<
                 .iter()
                 .map(|field| {
                     let (value_ident, _field_ident_string, initializer) =
-                        field_mutator(field, "__field", true);
+                        field_mutator(field, "__field", true, Some(variant_ident));
                     field_identifiers.push(quote_spanned! { field.member.span() => #value_ident });
 
                     initializer
@@ -273,15 +546,32 @@ fn mutatable_struct_visitor(fields: &[Field]) -> Vec<TokenStream> {
         .iter()
         .map(|field| {
             let (_field_ident, _field_ident_string, initializer) =
-                field_mutator(field, "self.", false);
+                field_mutator(field, "self.", false, None);
+            let dependencies = field.attrs.depends_on();
+            let dependency_members = dependencies;
 
             quote! {
+                #(let #dependencies = &self.#dependency_members;)*
+
                 #initializer
             }
         })
         .collect()
 }
 
+/// `tokens`, which are in field declaration order, rearranged into the fields' generation
+/// order
+fn ordered_by_dependencies<'a>(
+    fields: &[Field],
+    tokens: &'a [TokenStream],
+) -> Vec<&'a TokenStream> {
+    ast::generation_order(fields)
+        .expect("dependency cycles are rejected when parsing the container")
+        .into_iter()
+        .map(|i| &tokens[i])
+        .collect()
+}
+
 fn new_fuzzed_body(cont: &Container) -> TokenStream {
     match cont.data {
         Data::Enum(ref variants) if variants[0].style != Style::Unit => {
@@ -296,7 +586,7 @@ fn new_fuzzed_body(cont: &Container) -> TokenStream {
 }
 
 fn new_fuzzed_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream {
-    let constraints_prelude = constraints_prelude();
+    let constraints_prelude = enum_constraints_prelude();
     let (weights, new_fuzzed_fields, ignore_chances) =
         new_fuzzed_enum_visitor(variants, cont_ident);
     let variant_count = new_fuzzed_fields.len();
@@ -305,14 +595,23 @@ fn new_fuzzed_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream
         return quote! {Default::default()};
     }
 
+    // declaration indices of the variants which may be generated
+    let variant_indices: Vec<usize> = variants
+        .iter()
+        .enumerate()
+        .filter(|(_, variant)| !variant.attrs.ignore())
+        .map(|(i, _)| i)
+        .collect();
+
     let mut match_arms = vec![];
+    let value_fixup = fallback::fixup(quote! {&mut value});
 
     for (i, variant) in new_fuzzed_fields.iter().enumerate() {
         match_arms.push(quote! {
             #i => {
                 #variant
 
-                value.fixup(mutator);
+                #value_fixup
 
                 value
             }
@@ -325,6 +624,7 @@ fn new_fuzzed_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream
 
         static weights: [u64; #variant_count] = [#(#weights,)*];
         static ignore_chances: [f64; #variant_count] = [#(#ignore_chances,)*];
+        static variant_indices: [usize; #variant_count] = [#(#variant_indices,)*];
 
         _lain::lazy_static::lazy_static! {
             static ref dist: _lain::rand::distributions::WeightedIndex<u64> =
@@ -339,6 +639,11 @@ fn new_fuzzed_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream
         // loop a max of 5 times to avoid an infinite loop
         for _i in 0..5 {
             idx = Some(dist.sample(&mut mutator.rng));
+            // variants left out by swarm testing are skipped unless they keep coming up
+            if mutator.variant_disabled::<Self>(variant_indices[idx.unwrap()]) {
+                continue;
+            }
+
             let chance = ignore_chances[idx.unwrap()];
 
             // negate the gen_chance call since this is a chance to *ignore*
@@ -347,7 +652,39 @@ fn new_fuzzed_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream
             }
         }
 
-        let idx = idx.unwrap();
+        // a structured walk may dictate which variant is generated
+        let idx = mutator
+            .forced_variant::<Self>()
+            .and_then(|forced| variant_indices.iter().position(|i| *i == forced))
+            .unwrap_or_else(|| idx.unwrap());
+
+        let variant_size = |i: usize| {
+            <Self as _lain::traits::SerializedSize>::variant_serialized_size(variant_indices[i])
+                .unwrap_or_else(Self::max_default_object_size)
+        };
+
+        // the selected variant's fixed size comes out of the budget first, and its fields may
+        // use whatever is left. if it doesn't fit at all, fall back to a variant which does
+        let mut idx = idx;
+        if let Some(budget) = budget {
+            if variant_size(idx) > budget {
+                let fitting: Vec<usize> = (0..#variant_count)
+                    .filter(|i| variant_size(*i) <= budget)
+                    .collect();
+
+                idx = match fitting.choose(&mut mutator.rng) {
+                    Some(i) => *i,
+                    None => {
+                        warn!("No variant fits within max_size 0x{:X}. Using the smallest one", budget);
+                        (0..#variant_count).min_by_key(|i| variant_size(*i)).unwrap()
+                    }
+                };
+            }
+
+            max_size = Some(budget.saturating_sub(variant_size(idx)));
+        }
+
+        mutator.record_variant::<Self>(variant_indices[idx]);
 
         match idx {
             #(#match_arms)*
@@ -388,6 +725,11 @@ fn new_fuzzed_unit_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenS
             let idx: usize = dist.sample(&mut mutator.rng);
             option = Some(options[idx]);
 
+            // variants left out by swarm testing are skipped unless they keep coming up
+            if mutator.variant_disabled::<Self>(_lain::traits::EnumVariants::variant_index(&options[idx])) {
+                continue;
+            }
+
             let chance = ignore_chances[idx];
             // negate gen_chance since it's a chance to *ignore*
             if chance >= 1.0 || !mutator.gen_chance(chance) {
@@ -395,7 +737,19 @@ fn new_fuzzed_unit_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenS
             }
         }
 
-        option.unwrap()
+        // a structured walk may dictate which variant is generated
+        let value = mutator
+            .forced_variant::<Self>()
+            .and_then(|forced| {
+                options
+                    .iter()
+                    .copied()
+                    .find(|o| _lain::traits::EnumVariants::variant_index(o) == forced)
+            })
+            .unwrap_or_else(|| option.unwrap());
+        mutator.record_variant::<Self>(_lain::traits::EnumVariants::variant_index(&value));
+
+        value
     }
 }
 
@@ -417,6 +771,34 @@ fn new_fuzzed_struct(fields: &[Field], cont_ident: &syn::Ident) -> TokenStream {
 
     let type_name_string = cont_ident.to_string();
 
+    let self_is_variable_size = fallback::is_variable_size(quote! {Self});
+    let generate_fields = if ast::has_dependencies(fields) {
+        // fields can only be read once they've been written, so their dependencies must always
+        // be generated first
+        let ordered_initializers = ordered_by_dependencies(fields, &initializers);
+
+        quote! {
+            #(#ordered_initializers)*
+        }
+    } else {
+        quote! {
+            if #self_is_variable_size {
+                // this makes for ugly code generation, but better perf
+                for i in sample(&mut mutator.rng, #len, #len).iter() {
+                    match i {
+                        #(#match_arms)*
+                        _ => unreachable!(),
+                    }
+                }
+            } else {
+                #(#initializers)*
+            }
+        }
+    };
+
+    let lengths = struct_length_fixups(fields, quote! {initialized_struct});
+    let struct_fixup = fallback::fixup(quote! {&mut initialized_struct});
+
     quote! {
         use _lain::rand::seq::index::sample;
 
@@ -427,20 +809,11 @@ fn new_fuzzed_struct(fields: &[Field], cont_ident: &syn::Ident) -> TokenStream {
 
         _lain::log::trace!("Generating a new {} with constraints: {:#X?}", #type_name_string, parent_constraints);
 
-        if Self::is_variable_size() {
-            // this makes for ugly code generation, but better perf
-            for i in sample(&mut mutator.rng, #len, #len).iter() {
-                match i {
-                    #(#match_arms)*
-                    _ => unreachable!(),
-                }
-            }
-        } else {
-            #(#initializers)*
-        }
+        #generate_fields
 
         let mut initialized_struct = unsafe { uninit_struct.assume_init() };
-        initialized_struct.fixup(mutator);
+        #lengths
+        #struct_fixup
 
         initialized_struct
     }
@@ -456,11 +829,32 @@ fn new_fuzzed_struct_visitor(fields: &[Field], cont_ident: &syn::Ident) -> Vec<T
     fields
         .iter()
         .map(|field| {
-            let (field_ident, _field_ident_string, initializer) = field_initializer(field, "self");
+            let (field_ident, _field_ident_string, initializer) = field_initializer(field, "self", None);
             let ty = &field.ty;
             let member = &field.member;
 
+            // dependencies have already been written to the struct and are borrowed from it
+            let dependency_bindings = field.attrs.depends_on().iter().map(|dependency| {
+                let dependency_ty = fields
+                    .iter()
+                    .find(|f| match f.member {
+                        syn::Member::Named(ref ident) => ident == dependency,
+                        syn::Member::Unnamed(_) => false,
+                    })
+                    .map(|f| f.ty)
+                    .unwrap();
+
+                quote! {
+                    let #dependency: &#dependency_ty = unsafe {
+                        let field_offset = _lain::field_offset::offset_of!(#cont_ident => #dependency).get_byte_offset() as isize;
+                        &*((uninit_struct_ptr as *const u8).offset(field_offset) as *const #dependency_ty)
+                    };
+                }
+            });
+
             quote! {
+                #(#dependency_bindings)*
+
                 #initializer
 
                 let field_offset = _lain::field_offset::offset_of!(#cont_ident => #member).get_byte_offset() as isize;
@@ -483,6 +877,11 @@ fn struct_field_constraints(field: &Field, for_mutation: bool) -> TokenStream {
         return TokenStream::new();
     }
 
+    // sequence numbers and timestamps are generated by the mutator without constraints
+    if attrs.auto_increment().is_some() || attrs.now().is_some() {
+        return TokenStream::new();
+    }
+
     if attrs.min().is_some() || attrs.max().is_some() || attrs.bits().is_some() {
         let min: TokenStream;
         let max: TokenStream;
@@ -511,6 +910,15 @@ fn struct_field_constraints(field: &Field, for_mutation: bool) -> TokenStream {
             constraints.base_object_size_accounted_for = true;
             let constraints = Some(constraints);
         }
+    } else if let Some(group) = attrs.element_group() {
+        let group = group as usize;
+        quote! {
+            let mut constraints = Constraints::new();
+            constraints.max_size = max_size;
+            constraints.element_group = Some(#group);
+            constraints.base_object_size_accounted_for = true;
+            let constraints = Some(constraints);
+        }
     } else {
         quote! {
             let constraints = max_size.as_ref().and_then(|m| {
@@ -533,6 +941,7 @@ fn option_to_tokens<T: ToTokens + Spanned>(opt: Option<&T>) -> TokenStream {
 fn field_initializer(
     field: &Field,
     name_prefix: &'static str,
+    variant: Option<&syn::Ident>,
 ) -> (TokenStream, String, TokenStream) {
     let default_constraints = struct_field_constraints(field, false);
     let ty = &field.ty;
@@ -545,7 +954,12 @@ fn field_initializer(
         TokenStream::from_str(&format!("{}{}", name_prefix, field_ident_string)).unwrap();
 
     let default_initializer = quote! {
-        <#ty>::new_fuzzed(mutator, constraints.as_ref())
+        {
+            mutator.descend();
+            let value = <#ty>::new_fuzzed(mutator, constraints.as_ref());
+            mutator.ascend();
+            value
+        }
     };
 
     let initializer = if field.attrs.ignore() {
@@ -564,6 +978,28 @@ fn field_initializer(
         quote_spanned! { initializer.span() =>
             let #value_ident = #initializer;
         }
+    } else if let Some(counter) = field.attrs.auto_increment() {
+        quote_spanned! { ty.span() =>
+            let #value_ident = mutator.next_sequence_number::<#ty>(#counter);
+        }
+    } else if let Some(unit) = field.attrs.now() {
+        let jitter = field.attrs.jitter();
+        quote_spanned! { ty.span() =>
+            let #value_ident = mutator.gen_timestamp::<#ty>(#unit, std::time::Duration::from_nanos(#jitter));
+        }
+    } else if field.attrs.memoize() {
+        let memo_key = memo_key(variant, &field_ident_string);
+        quote_spanned! { ty.span() =>
+            let #value_ident = mutator.gen_memoized::<#ty>(std::any::type_name::<Self>(), #memo_key, constraints.as_ref());
+        }
+    } else if let Some(name) = field.attrs.shared() {
+        quote_spanned! { ty.span() =>
+            let #value_ident = mutator.gen_shared::<#ty>(#name, constraints.as_ref());
+        }
+    } else if let Some(pool) = field.attrs.pool() {
+        quote_spanned! { ty.span() =>
+            let #value_ident = mutator.gen_from_pool::<#ty>(#pool, constraints.as_ref());
+        }
     } else {
         quote_spanned! { ty.span() =>
         //  println!("{:?}", constraints);
@@ -584,6 +1020,11 @@ fn field_initializer(
 }
 
 fn decrement_max_size(field: &Field, value_ident: &TokenStream) -> TokenStream {
+    // transient fields aren't serialized, so they don't use up any of the budget
+    if field.attrs.transient() {
+        return TokenStream::new();
+    }
+
     let ty = field.ty;
     let _field_ident_string = match field.member {
         syn::Member::Named(ref ident) => ident.to_string(),
@@ -595,9 +1036,10 @@ fn decrement_max_size(field: &Field, value_ident: &TokenStream) -> TokenStream {
     };
 
     let ty_string = quote! {#ty}.to_string();
+    let is_variable_size = fallback::is_variable_size(quote! {#ty});
 
     quote! {
-        _lain::log::trace!("{} is variable size? {}", #ty_string, <#ty>::is_variable_size());
+        _lain::log::trace!("{} is variable size? {}", #ty_string, #is_variable_size);
 
         if let Some(ref mut max_size) = max_size {
             // we only subtract off the difference between the object's allocated size
@@ -616,6 +1058,12 @@ fn decrement_max_size(field: &Field, value_ident: &TokenStream) -> TokenStream {
 }
 
 fn mutatable_decrement_max_size(field: &Field, value_ident: &TokenStream) -> TokenStream {
+    if field.attrs.transient() {
+        return quote! {
+            let _ = previous_size;
+        };
+    }
+
     let ty = field.ty;
     let _field_ident_string = match field.member {
         syn::Member::Named(ref ident) => ident.to_string(),
@@ -627,9 +1075,10 @@ fn mutatable_decrement_max_size(field: &Field, value_ident: &TokenStream) -> Tok
     };
 
     let ty_string = quote! {#ty}.to_string();
+    let is_variable_size = fallback::is_variable_size(quote! {#ty});
 
     quote! {
-        _lain::log::trace!("{} is variable size? {}", #ty_string, <#ty>::is_variable_size());
+        _lain::log::trace!("{} is variable size? {}", #ty_string, #is_variable_size);
 
         if mutated {
             if let Some(ref mut max_size) = max_size {
@@ -653,6 +1102,7 @@ fn field_mutator(
     field: &Field,
     name_prefix: &'static str,
     is_destructured: bool,
+    variant: Option<&syn::Ident>,
 ) -> (TokenStream, String, TokenStream) {
     let default_constraints = struct_field_constraints(field, true);
     let ty = &field.ty;
@@ -669,16 +1119,67 @@ fn field_mutator(
         quote! {&mut}
     };
 
+    let mutate = if let Some(counter) = field.attrs.auto_increment() {
+        quote! {
+            mutator.mutate_sequence_number::<#ty>(#counter, #borrow #value_ident);
+        }
+    } else if let Some(unit) = field.attrs.now() {
+        let jitter = field.attrs.jitter();
+        let deref = if is_destructured {
+            quote! {*}
+        } else {
+            TokenStream::new()
+        };
+        quote! {
+            #deref #value_ident = mutator.gen_timestamp::<#ty>(#unit, std::time::Duration::from_nanos(#jitter));
+        }
+    } else if field.attrs.memoize() {
+        let memo_key = memo_key(variant, &field_ident_string);
+        quote! {
+            mutator.mutate_memoized::<#ty>(std::any::type_name::<Self>(), #memo_key, #borrow #value_ident, constraints.as_ref());
+        }
+    } else if let Some(name) = field.attrs.shared() {
+        quote! {
+            mutator.mutate_shared::<#ty>(#name, #borrow #value_ident, constraints.as_ref());
+        }
+    } else if let Some(pool) = field.attrs.pool() {
+        quote! {
+            mutator.mutate_from_pool::<#ty>(#pool, #borrow #value_ident, constraints.as_ref());
+        }
+    } else if field.attrs.min().is_some() || field.attrs.max().is_some() {
+        // the default value may lie outside of the field's range
+        quote! {
+            mutator.descend();
+            <#ty>::mutate(#borrow #value_ident, mutator, constraints.as_ref());
+            mutator.ascend();
+        }
+    } else {
+        // reborrowed so that destructured fields can still be mutated afterwards
+        let zero_fill = fallback::zero_fill(quote! {&mut *#borrow #value_ident});
+        quote! {
+            if !(mutator.gen_chance(mutator.zero_fill_chance()) && #zero_fill) {
+                mutator.descend();
+                <#ty>::mutate(#borrow #value_ident, mutator, constraints.as_ref());
+                mutator.ascend();
+            }
+        }
+    };
+
+    let mutation_chance = field
+        .attrs
+        .mutation_chance()
+        .unwrap_or(DEFAULT_FIELD_MUTATION_CHANCE);
+
     let mutator_stmts = quote! {
         let previous_size = #value_ident.serialized_size();
-        let mutated = mutator.gen_chance(0.98);
+        let mutated = mutator.gen_chance(#mutation_chance);
 
         if mutated {
-            <#ty>::mutate(#borrow #value_ident, mutator, constraints.as_ref());
+            #mutate
         }
 
         if mutator.should_early_bail_mutation() {
-            return;
+            break 'mutate;
         }
     };
 
@@ -695,6 +1196,39 @@ fn field_mutator(
     (value_ident, field_ident_string, initializer)
 }
 
+/// Sets the `#[lain(length_of)]` fields of `receiver`, a struct of the derived type, to the
+/// serialized size of the fields they name
+pub fn length_fixups(data: &Data, receiver: TokenStream) -> TokenStream {
+    match *data {
+        Data::Struct(_, ref fields) => struct_length_fixups(fields, receiver),
+        Data::Enum(_) => TokenStream::new(),
+    }
+}
+
+fn struct_length_fixups(fields: &[Field], receiver: TokenStream) -> TokenStream {
+    let fixups = fields.iter().filter_map(|field| {
+        let target = field.attrs.length_of()?;
+        let member = &field.member;
+
+        Some(quote_spanned! { field.original.span() =>
+            let len = _lain::traits::SerializedSize::serialized_size(&#receiver.#target);
+            mutator.fix_length(&mut #receiver.#member, len);
+        })
+    });
+
+    quote! {
+        #(#fixups)*
+    }
+}
+
+/// Name a `#[lain(memoize)]` field's value is stored under, unique within its type
+fn memo_key(variant: Option<&syn::Ident>, field_ident_string: &str) -> String {
+    match variant {
+        Some(variant) => format!("{}::{}", variant, field_ident_string),
+        None => field_ident_string.to_string(),
+    }
+}
+
 fn new_fuzzed_unit_enum_visitor(
     variants: &[Variant],
     cont_ident: &syn::Ident,
@@ -749,7 +1283,7 @@ fn new_fuzzed_enum_visitor(
                 .iter()
                 .map(|field| {
                     let (value_ident, _field_ident_string, initializer) =
-                        field_initializer(field, "__field");
+                        field_initializer(field, "__field", Some(variant_ident));
 
                     field_identifiers.push(quote_spanned! { field.member.span() => #value_ident });
                     field_ignore_chances.push(variant.attrs.ignore_chance().unwrap_or(1.0));
@@ -808,6 +1342,17 @@ fn constraints_prelude() -> TokenStream {
     }
 }
 
+/// Like [constraints_prelude], but for enums with fields. The size of an enum depends on the
+/// variant selected, so instead of reserving room for the largest variant this works out the
+/// total `budget` the value may occupy. `max_size` is set once a variant has been selected.
+fn enum_constraints_prelude() -> TokenStream {
+    quote! {
+        let budget = parent_constraints.and_then(|c| c.remaining::<Self>());
+
+        let mut max_size: Option<usize> = None;
+    }
+}
+
 fn mutatable_constraints_prelude() -> TokenStream {
     quote! {
         // Make a copy of the constraints that will remain immutable for
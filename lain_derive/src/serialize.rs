@@ -13,6 +13,8 @@ struct SerializedSizeBodies {
     min_nonzero_elements_size: TokenStream,
     max_default_object_size: TokenStream,
     min_enum_variant_size: TokenStream,
+    /// Per-variant sizes in declaration order. Only present for enums.
+    variant_sizes: Option<TokenStream>,
 }
 
 #[derive(Copy, Clone, PartialEq)]
@@ -37,12 +39,58 @@ pub fn expand_binary_serialize(input: &syn::DeriveInput) -> Result<TokenStream,
     let ident_as_string = ident.to_string();
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
-    let serialize_body = binary_serialize_body(&cont);
+    let serialize_body = if has_trailer(&cont) {
+        // trailers are computed from the bytes before them, so the body is serialized into a
+        // buffer of its own first
+        let serialize_body = binary_serialize_body(&cont);
+        quote! {
+            let mut body: Vec<u8> = Vec::new();
+            {
+                let buffer = &mut body;
+                #serialize_body
+            }
+            buffer.write_all(&body).unwrap();
+        }
+    } else {
+        binary_serialize_body(&cont)
+    };
+    // a fixed size only pads the output, so without the padding the size is the fields' size
+    let flattened_size_fn = cont.attrs.serialized_size().map(|_| {
+        let flattened_size =
+            serialized_size_body(&cont, None, cont.attrs.min_serialized_size()).serialized_size;
+
+        quote! {
+            #[inline]
+            fn flattened_serialized_size(&self) -> usize {
+                use _lain::traits::SerializedSize;
+                #flattened_size
+            }
+        }
+    });
+    let field_layout_body = field_layout_body(&cont);
+    // types without fields keep the no-op default so that slices of them are skipped
+    let field_layout_slice_fn = if has_fields(&cont) {
+        quote! {
+            fn field_layout_slice(items: &[Self], path: &str, offset: usize, layout: &mut Vec<_lain::types::FieldSpan>) {
+                use _lain::traits::SerializedSize;
+
+                let mut offset = offset;
+                for (i, item) in items.iter().enumerate() {
+                    item.field_layout(&format!("{}[{}]", path, i), offset, layout);
+                    offset += item.serialized_size();
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+    let set_field_body = set_field_body(&cont, cont.generics);
     let SerializedSizeBodies {
         serialized_size,
         min_nonzero_elements_size,
         max_default_object_size,
         min_enum_variant_size,
+        variant_sizes,
     } = serialized_size_body(
         &cont,
         cont.attrs.serialized_size(),
@@ -51,6 +99,25 @@ pub fn expand_binary_serialize(input: &syn::DeriveInput) -> Result<TokenStream,
 
     let lain = cont.attrs.lain_path();
 
+    let variant_size_fns = variant_sizes.map(|variant_sizes| {
+        quote! {
+            #[inline]
+            fn min_variant_size() -> usize {
+                *#variant_sizes.iter().min().unwrap_or(&0)
+            }
+
+            #[inline]
+            fn max_variant_size() -> usize {
+                *#variant_sizes.iter().max().unwrap_or(&0)
+            }
+
+            #[inline]
+            fn variant_serialized_size(index: usize) -> Option<usize> {
+                #variant_sizes.get(index).copied()
+            }
+        }
+    });
+
     let impl_block = quote! {
         #[allow(clippy)]
         #[allow(unknown_lints)]
@@ -58,11 +125,8 @@ pub fn expand_binary_serialize(input: &syn::DeriveInput) -> Result<TokenStream,
         impl #impl_generics #lain::traits::BinarySerialize for #ident #ty_generics #where_clause {
             fn binary_serialize<W: std::io::Write, E: #lain::byteorder::ByteOrder>(&self, buffer: &mut W) -> usize {
                 use #lain::traits::SerializedSize;
-                use #lain::byteorder::{LittleEndian, BigEndian, WriteBytesExt};
 
-                let mut bytes_written = 0;
-
-                #serialize_body
+                let mut bytes_written = self.binary_serialize_flattened::<_, E>(buffer);
 
                 if bytes_written < self.serialized_size() {
                     let padding_bytes = std::cmp::max(self.serialized_size(), Self::min_nonzero_elements_size()) - bytes_written;
@@ -76,6 +140,33 @@ pub fn expand_binary_serialize(input: &syn::DeriveInput) -> Result<TokenStream,
 
                 bytes_written
             }
+
+            fn binary_serialize_flattened<W: std::io::Write, E: #lain::byteorder::ByteOrder>(&self, buffer: &mut W) -> usize {
+                use #lain::traits::SerializedSize;
+                use #lain::byteorder::{LittleEndian, BigEndian, WriteBytesExt};
+
+                let mut bytes_written = 0;
+
+                #serialize_body
+
+                bytes_written
+            }
+
+            #[allow(unused_assignments, unused_mut)]
+            fn field_layout(&self, path: &str, offset: usize, layout: &mut Vec<#lain::types::FieldSpan>) {
+                use #lain::traits::SerializedSize;
+
+                let mut offset = offset;
+
+                #field_layout_body
+            }
+
+            #field_layout_slice_fn
+
+            #[allow(unused_variables)]
+            fn set_field(&mut self, path: &str, value: &dyn std::any::Any) -> bool {
+                #set_field_body
+            }
         }
 
         // TODO: Split this into its own derive
@@ -113,6 +204,10 @@ pub fn expand_binary_serialize(input: &syn::DeriveInput) -> Result<TokenStream,
             fn min_enum_variant_size(&self) -> usize {
                 #min_enum_variant_size
             }
+
+            #variant_size_fns
+
+            #flattened_size_fn
         }
     };
 
@@ -134,6 +229,345 @@ fn binary_serialize_body(cont: &Container) -> TokenStream {
     }
 }
 
+/// Whether any field is serialized from the bytes before it (trailers and checksums)
+fn has_trailer(cont: &Container) -> bool {
+    let is_trailer = |f: &Field| f.attrs.trailer().is_some() || f.attrs.checksum();
+    match cont.data {
+        Data::Enum(ref variants) => variants
+            .iter()
+            .any(|variant| variant.fields.iter().any(is_trailer)),
+        Data::Struct(_, ref fields) => fields.iter().any(is_trailer),
+    }
+}
+
+/// Whether `field_layout` reports anything for the container (unit structs and unit enums
+/// have no fields)
+fn has_fields(cont: &Container) -> bool {
+    match cont.data {
+        Data::Enum(ref variants) => variants[0].style != Style::Unit,
+        Data::Struct(ref style, _) => *style != Style::Unit,
+    }
+}
+
+fn field_layout_body(cont: &Container) -> TokenStream {
+    match cont.data {
+        Data::Enum(ref variants) if variants[0].style != Style::Unit => {
+            field_layout_enum(variants, &cont.ident)
+        }
+        Data::Struct(Style::Struct, ref fields) | Data::Struct(Style::Tuple, ref fields) => {
+            let spans: Vec<TokenStream> = fields
+                .iter()
+                .map(|field| field_layout_span(field, "self.", false, quote! {path}))
+                .collect();
+
+            quote! {
+                #(#spans)*
+            }
+        }
+        // unit enums and unit structs have no fields to report
+        _ => quote! {
+            let _ = (path, offset, layout);
+        },
+    }
+}
+
+fn field_layout_enum(variants: &[Variant], cont_ident: &syn::Ident) -> TokenStream {
+    let match_arms = variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let variant_ident_string = variant_ident.to_string();
+        let full_ident = quote! {#cont_ident::#variant_ident};
+
+        if variant.fields.is_empty() {
+            return quote! {
+                #full_ident => {}
+            };
+        }
+
+        let mut field_identifiers = vec![];
+        let spans: Vec<TokenStream> = variant
+            .fields
+            .iter()
+            .map(|field| {
+                let field_ident_string = match field.member {
+                    syn::Member::Named(ref ident) => ident.to_string(),
+                    syn::Member::Unnamed(ref idx) => idx.index.to_string(),
+                };
+                let value_ident =
+                    TokenStream::from_str(&format!("__field{}", field_ident_string)).unwrap();
+                field_identifiers.push(quote_spanned! { field.member.span() => #value_ident });
+
+                field_layout_span(field, "__field", true, quote! {variant_path})
+            })
+            .collect();
+
+        quote! {
+            #full_ident(#(ref #field_identifiers,)*) => {
+                let variant_path = _lain::types::FieldSpan::child_path(path, #variant_ident_string);
+
+                #(#spans)*
+            }
+        }
+    });
+
+    quote! {
+        match *self {
+            #(#match_arms)*
+        }
+    }
+}
+
+/// Generates the statements which record where `field` lands in the serialized
+/// output and advance the running `offset`
+fn field_layout_span(
+    field: &Field,
+    name_prefix: &'static str,
+    is_destructured: bool,
+    parent_path: TokenStream,
+) -> TokenStream {
+    let ty = &field.ty;
+    let field_ident_string = match field.member {
+        syn::Member::Named(ref ident) => ident.to_string(),
+        syn::Member::Unnamed(ref idx) => idx.index.to_string(),
+    };
+
+    let value_ident =
+        TokenStream::from_str(&format!("{}{}", name_prefix, field_ident_string)).unwrap();
+    let borrow = if is_destructured {
+        TokenStream::new()
+    } else {
+        quote! {&}
+    };
+
+    if field.attrs.transient() {
+        quote_spanned! { field.original.span() =>
+            let _ = #borrow#value_ident;
+        }
+    } else if field.attrs.flatten() {
+        // the nested fields are reported as if they belonged to the parent
+        quote_spanned! { field.original.span() =>
+            {
+                let size = _lain::traits::SerializedSize::flattened_serialized_size(#borrow#value_ident);
+                <#ty as _lain::traits::BinarySerialize>::field_layout(#borrow#value_ident, &#parent_path, offset, layout);
+                offset += size;
+            }
+        }
+    } else if field.attrs.bits().is_some() {
+        // every member of a bitfield occupies the whole packed integer, which is only
+        // written once the last member of the bitfield has been seen
+        let bitfield_type = field.attrs.bitfield_type().unwrap_or(field.ty);
+        let bits = field.attrs.bits().unwrap();
+        let bit_shift = field.attrs.bit_shift().unwrap();
+        let type_total_bits = bitfield_type_bits(bitfield_type);
+        let completes_bitfield = bits + bit_shift == type_total_bits || field.attrs.is_last_field();
+
+        let advance = if completes_bitfield {
+            quote! {offset += size;}
+        } else {
+            TokenStream::new()
+        };
+
+        quote_spanned! { field.original.span() =>
+            {
+                let size = std::mem::size_of::<#bitfield_type>();
+                layout.push(_lain::types::FieldSpan {
+                    path: _lain::types::FieldSpan::child_path(&#parent_path, #field_ident_string),
+                    start: offset,
+                    end: offset + size,
+                });
+                #advance
+            }
+        }
+    } else if let Some(format) = field.attrs.timestamp() {
+        quote_spanned! { field.original.span() =>
+            {
+                let size = #format.serialized_size(#borrow#value_ident);
+                layout.push(_lain::types::FieldSpan {
+                    path: _lain::types::FieldSpan::child_path(&#parent_path, #field_ident_string),
+                    start: offset,
+                    end: offset + size,
+                });
+                offset += size;
+            }
+        }
+    } else {
+        quote_spanned! { field.original.span() =>
+            {
+                let field_path = _lain::types::FieldSpan::child_path(&#parent_path, #field_ident_string);
+                let size = _lain::traits::SerializedSize::serialized_size(#borrow#value_ident);
+                layout.push(_lain::types::FieldSpan {
+                    path: field_path.clone(),
+                    start: offset,
+                    end: offset + size,
+                });
+                <#ty as _lain::traits::BinarySerialize>::field_layout(#borrow#value_ident, &field_path, offset, layout);
+                offset += size;
+            }
+        }
+    }
+}
+
+fn set_field_body(cont: &Container, generics: &syn::Generics) -> TokenStream {
+    match cont.data {
+        Data::Enum(ref variants) if variants[0].style != Style::Unit => {
+            set_field_enum(variants, &cont.ident, generics)
+        }
+        Data::Struct(Style::Struct, ref fields) | Data::Struct(Style::Tuple, ref fields) => {
+            set_field_members(fields, "self.", false, generics)
+        }
+        // unit enums and unit structs have no fields to set
+        _ => quote! {
+            false
+        },
+    }
+}
+
+fn set_field_enum(
+    variants: &[Variant],
+    cont_ident: &syn::Ident,
+    generics: &syn::Generics,
+) -> TokenStream {
+    let match_arms = variants.iter().filter(|v| !v.fields.is_empty()).map(|variant| {
+        let variant_ident = &variant.ident;
+        let variant_ident_string = variant_ident.to_string();
+        let full_ident = quote! {#cont_ident::#variant_ident};
+
+        let field_identifiers = variant.fields.iter().map(|field| {
+            let field_ident_string = match field.member {
+                syn::Member::Named(ref ident) => ident.to_string(),
+                syn::Member::Unnamed(ref idx) => idx.index.to_string(),
+            };
+            let value_ident =
+                TokenStream::from_str(&format!("__field{}", field_ident_string)).unwrap();
+            quote_spanned! { field.member.span() => #value_ident }
+        });
+
+        let members = set_field_members(&variant.fields, "__field", true, generics);
+
+        quote! {
+            #full_ident(#(ref mut #field_identifiers,)*) if variant == #variant_ident_string => {
+                let path = rest;
+
+                #members
+            }
+        }
+    });
+
+    quote! {
+        let (variant, rest) = _lain::types::FieldSpan::split_path(path);
+
+        match *self {
+            #(#match_arms)*
+            _ => false,
+        }
+    }
+}
+
+/// Generates the statements which replace the member of `fields` named by the start of `path`
+/// with `value`, or pass the rest of `path` on to that member
+fn set_field_members(
+    fields: &[Field],
+    name_prefix: &'static str,
+    is_destructured: bool,
+    generics: &syn::Generics,
+) -> TokenStream {
+    let mut flattened = vec![];
+    let mut match_arms = vec![];
+
+    for field in fields.iter().filter(|field| !field.attrs.transient()) {
+        let ty = &field.ty;
+        let field_ident_string = match field.member {
+            syn::Member::Named(ref ident) => ident.to_string(),
+            syn::Member::Unnamed(ref idx) => idx.index.to_string(),
+        };
+
+        let value_ident =
+            TokenStream::from_str(&format!("{}{}", name_prefix, field_ident_string)).unwrap();
+        let (borrow, deref) = if is_destructured {
+            (TokenStream::new(), quote! {*})
+        } else {
+            (quote! {&mut}, TokenStream::new())
+        };
+
+        if field.attrs.flatten() {
+            // the nested fields are reported as if they belonged to the parent
+            flattened.push(quote_spanned! { field.original.span() =>
+                if <#ty as _lain::traits::BinarySerialize>::set_field(#borrow #value_ident, path, value) {
+                    return true;
+                }
+            });
+            continue;
+        }
+
+        // values are matched by their `TypeId`, which only types without generic parameters
+        // or lifetimes are guaranteed to have
+        let replace = if mentions_generics(ty, generics) {
+            quote! {false}
+        } else {
+            let clone = crate::fallback::clone_seed(quote! {pinned});
+            quote! {
+                match value.downcast_ref::<#ty>().and_then(|pinned| #clone) {
+                    Some(pinned) => {
+                        #deref #value_ident = pinned;
+                        true
+                    }
+                    None => false,
+                }
+            }
+        };
+
+        match_arms.push(quote_spanned! { field.original.span() =>
+            #field_ident_string => if rest.is_empty() {
+                #replace
+            } else {
+                <#ty as _lain::traits::BinarySerialize>::set_field(#borrow #value_ident, rest, value)
+            },
+        });
+    }
+
+    quote! {
+        #(#flattened)*
+
+        let (member, rest) = _lain::types::FieldSpan::split_path(path);
+        match member {
+            #(#match_arms)*
+            _ => false,
+        }
+    }
+}
+
+/// Whether `ty` refers to any of the type parameters in `generics` or to a lifetime
+fn mentions_generics(ty: &syn::Type, generics: &syn::Generics) -> bool {
+    fn visit(tokens: TokenStream, params: &[String]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) => params.contains(&ident.to_string()),
+            proc_macro2::TokenTree::Punct(punct) => punct.as_char() == '\'',
+            proc_macro2::TokenTree::Group(group) => visit(group.stream(), params),
+            proc_macro2::TokenTree::Literal(_) => false,
+        })
+    }
+
+    let params: Vec<String> = generics
+        .type_params()
+        .map(|param| param.ident.to_string())
+        .collect();
+
+    visit(ty.into_token_stream(), &params)
+}
+
+fn bitfield_type_bits(bitfield_type: &syn::Type) -> usize {
+    if is_primitive_type(bitfield_type, "u8") {
+        8
+    } else if is_primitive_type(bitfield_type, "u16") {
+        16
+    } else if is_primitive_type(bitfield_type, "u32") {
+        32
+    } else if is_primitive_type(bitfield_type, "u64") {
+        64
+    } else {
+        panic!("got to field_layout with an unsupported bitfield type `{}`. ensure that checks in ast code are correct", bitfield_type.into_token_stream());
+    }
+}
+
 fn serialized_size_body(
     cont: &Container,
     size: Option<usize>,
@@ -147,6 +581,7 @@ fn serialized_size_body(
             min_nonzero_elements_size: size_tokens.clone(),
             max_default_object_size: size_tokens.clone(),
             min_enum_variant_size: size_tokens.clone(),
+            variant_sizes: None,
         };
     }
 
@@ -154,7 +589,7 @@ fn serialized_size_body(
         Data::Enum(ref variants) if variants[0].style != Style::Unit => {
             serialized_size_enum(variants, &cont.ident, size, min_size)
         }
-        Data::Enum(ref _variants) => serialized_size_unit_enum(&cont.ident),
+        Data::Enum(ref variants) => serialized_size_unit_enum(variants, &cont.ident),
         Data::Struct(Style::Struct, ref fields) | Data::Struct(Style::Tuple, ref fields) => {
             serialized_size_struct(fields)
         }
@@ -165,6 +600,7 @@ fn serialized_size_body(
                 min_nonzero_elements_size: zero_size.clone(),
                 max_default_object_size: zero_size.clone(),
                 min_enum_variant_size: zero_size,
+                variant_sizes: None,
             }
         }
     }
@@ -202,14 +638,36 @@ fn binary_serialize_struct_visitor(fields: &[Field]) -> Vec<TokenStream> {
     fields
         .iter()
         .map(|field| {
-            let (_field_ident, _field_ident_string, serializer) =
+            let (_field_ident, field_ident_string, serializer) =
                 field_serializer(field, "self.", false);
 
-            serializer
+            // checksums starting at this field need to know where it was written
+            let is_checksum_start = fields.iter().any(|f| {
+                f.attrs
+                    .checksum_from()
+                    .is_some_and(|start| *start == field_ident_string)
+            });
+            if is_checksum_start {
+                let start = checksum_start_ident(&field_ident_string);
+                quote! {
+                    let #start = buffer.len();
+                    #serializer
+                }
+            } else {
+                serializer
+            }
         })
         .collect()
 }
 
+/// Variable holding the offset in the serialized body at which `field` was written
+fn checksum_start_ident(field: &str) -> syn::Ident {
+    syn::Ident::new(
+        &format!("__checksum_start_{}", field),
+        proc_macro2::Span::call_site(),
+    )
+}
+
 fn field_serializer(
     field: &Field,
     name_prefix: &'static str,
@@ -238,7 +696,11 @@ fn field_serializer(
         quote! {E}
     };
 
-    let serialize_stmts = if let Some(bits) = field.attrs.bits() {
+    let serialize_stmts = if field.attrs.transient() {
+        quote_spanned! { field.original.span() =>
+            let _ = #borrow#value_ident;
+        }
+    } else if let Some(bits) = field.attrs.bits() {
         let bit_mask = 2_u64.pow(bits as u32) - 1;
         let bit_shift = field.attrs.bit_shift().unwrap();
         let is_last_field = field.attrs.is_last_field();
@@ -272,6 +734,36 @@ fn field_serializer(
         }
 
         bitfield_setter
+    } else if let Some(format) = field.attrs.timestamp() {
+        quote_spanned! { field.original.span() =>
+            bytes_written += #format.serialize::<_, #endian, _>(#borrow#value_ident, buffer);
+        }
+    } else if let Some(trailer) = field.attrs.trailer() {
+        // the field's own value is replaced with one computed from everything before it
+        quote_spanned! { field.original.span() =>
+            {
+                let value: #ty = #trailer(buffer.as_slice());
+                bytes_written += <#ty>::binary_serialize::<_, #endian>(&value, buffer);
+            }
+        }
+    } else if field.attrs.checksum() {
+        let start = match field.attrs.checksum_from() {
+            Some(start) => {
+                let start = checksum_start_ident(&start.to_string());
+                quote! {#start}
+            }
+            None => quote! {0},
+        };
+        quote_spanned! { field.original.span() =>
+            {
+                let value = #value_ident.compute(&buffer.as_slice()[#start..]);
+                bytes_written += _lain::traits::BinarySerialize::binary_serialize::<_, #endian>(&value, buffer);
+            }
+        }
+    } else if field.attrs.flatten() {
+        quote_spanned! { field.original.span() =>
+            bytes_written += _lain::traits::BinarySerialize::binary_serialize_flattened::<_, #endian>(#borrow#value_ident, buffer);
+        }
     } else if let syn::Type::Array(ref _a) = ty {
         // TODO: Change this once const generics are stabilized
         quote_spanned! { field.original.span() =>
@@ -374,7 +866,8 @@ fn serialized_size_enum(
         quote! {*[#(#nonzero_variants,)*].iter().min_by(|a, b| a.cmp(b)).unwrap()}
     };
 
-    let max_default = quote! {*[#(#max_obj,)*].iter().max_by(|a, b| a.cmp(b)).unwrap()};
+    let variant_sizes = quote! {[#(#max_obj,)*]};
+    let max_default = quote! {*#variant_sizes.iter().max_by(|a, b| a.cmp(b)).unwrap()};
 
     let min_variant = quote! {
         match *self {
@@ -387,19 +880,25 @@ fn serialized_size_enum(
         min_nonzero_elements_size: min_nonzero,
         max_default_object_size: max_default,
         min_enum_variant_size: min_variant,
+        variant_sizes: Some(variant_sizes),
     }
 }
 
-fn serialized_size_unit_enum(cont_ident: &syn::Ident) -> SerializedSizeBodies {
+fn serialized_size_unit_enum(
+    variants: &[Variant],
+    cont_ident: &syn::Ident,
+) -> SerializedSizeBodies {
     let size = quote! {
         std::mem::size_of::<<#cont_ident as _lain::traits::ToPrimitive>::Output>()
     };
+    let variant_count = variants.len();
 
     SerializedSizeBodies {
         serialized_size: size.clone(),
         min_nonzero_elements_size: size.clone(),
         max_default_object_size: size.clone(),
-        min_enum_variant_size: size,
+        min_enum_variant_size: size.clone(),
+        variant_sizes: Some(quote! {[#size; #variant_count]}),
     }
 }
 
@@ -418,6 +917,7 @@ fn serialized_size_struct(fields: &[Field]) -> SerializedSizeBodies {
         min_nonzero_elements_size: quote! { 0 #(+#min_nonzero)* },
         max_default_object_size: quote! {0 #(+#max_default)*},
         min_enum_variant_size: quote! {Self::min_nonzero_elements_size()},
+        variant_sizes: None,
     }
 }
 
@@ -456,7 +956,9 @@ fn field_serialized_size(
         quote! {&}
     };
 
-    let serialized_size_stmts = if let Some(bits) = field.attrs.bits() {
+    let serialized_size_stmts = if field.attrs.transient() {
+        quote! {0 /* transient */}
+    } else if let Some(bits) = field.attrs.bits() {
         let bit_shift = field.attrs.bit_shift().unwrap();
         let bitfield_type = field.attrs.bitfield_type().unwrap_or(field.ty);
         let is_last_field = field.attrs.is_last_field();
@@ -496,15 +998,25 @@ fn field_serialized_size(
         } else {
             quote! {0 /* bitfield */}
         }
+    } else if let Some(format) = field.attrs.timestamp() {
+        match visitor_type {
+            SerializedSizeVisitorType::SerializedSize => {
+                quote_spanned! { field.original.span() => #format.serialized_size(#borrow#value_ident)}
+            }
+            _ => quote_spanned! { field.original.span() => #format.min_size()},
+        }
     } else {
         match visitor_type {
+            SerializedSizeVisitorType::SerializedSize if field.attrs.flatten() => {
+                quote_spanned! { field.original.span() => _lain::traits::SerializedSize::flattened_serialized_size(#borrow#value_ident)}
+            }
             SerializedSizeVisitorType::SerializedSize => {
                 quote_spanned! { field.original.span() => _lain::traits::SerializedSize::serialized_size(#borrow#value_ident)}
             }
             SerializedSizeVisitorType::MinNonzeroElements
             | SerializedSizeVisitorType::MinEnumVariantSize => match ty {
                 syn::Type::Path(ref p)
-                    if p.path.segments[0].ident == "Vec" && field.attrs.min().is_some() =>
+                    if p.path.segments[0].ident == "Vec" && has_static_min(field) =>
                 {
                     let min = field.attrs.min().unwrap();
                     quote_spanned! { field.original.span() => <#ty>::min_nonzero_elements_size() * #min }
@@ -515,7 +1027,7 @@ fn field_serialized_size(
             },
             SerializedSizeVisitorType::MaxDefaultObjectSize => match ty {
                 syn::Type::Path(ref p)
-                    if p.path.segments[0].ident == "Vec" && field.attrs.min().is_some() =>
+                    if p.path.segments[0].ident == "Vec" && has_static_min(field) =>
                 {
                     let min = field
                         .attrs
@@ -600,3 +1112,9 @@ fn serialized_size_enum_visitor(
 
     match_arms
 }
+
+/// Whether the field's `min` can be evaluated without an instance of the struct, i.e. doesn't
+/// refer to the fields it `depends_on`
+fn has_static_min(field: &Field) -> bool {
+    field.attrs.min().is_some() && field.attrs.depends_on().is_empty()
+}
@@ -1,5 +1,3 @@
-#![feature(specialization)]
-
 extern crate criterion;
 extern crate lain;
 
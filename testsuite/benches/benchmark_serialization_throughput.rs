@@ -1,5 +1,3 @@
-#![feature(specialization)]
-
 #[macro_use]
 extern crate criterion;
 #[macro_use]
@@ -1,5 +1,3 @@
-#![feature(min_specialization)]
-
 extern crate lain;
 
 #[cfg(test)]
@@ -978,6 +976,5655 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_field_layout() {
+        let obj = NestedStruct {
+            test1: 0,
+            nested: TestStruct {
+                single_byte: 0,
+                bitfield_1: 0,
+                bitfield_2: 0,
+                bitfield_3: 0,
+                bitfield_4: 0,
+                bitfield_5: 0,
+                uint32: 0,
+                short: 0,
+                end_byte: 0,
+            },
+            test2: 0,
+        };
+
+        let mut layout = vec![];
+        obj.field_layout("", 0, &mut layout);
+
+        let find = |path: &str| {
+            layout
+                .iter()
+                .find(|span| span.path == path)
+                .map(|span| (span.start, span.end))
+                .unwrap_or_else(|| panic!("no span for {}", path))
+        };
+
+        assert_eq!(find("test1"), (0, 4));
+        assert_eq!(find("nested"), (4, 13));
+        assert_eq!(find("nested.single_byte"), (4, 5));
+        assert_eq!(find("nested.bitfield_1"), (5, 6));
+        assert_eq!(find("nested.bitfield_5"), (5, 6));
+        assert_eq!(find("nested.uint32"), (6, 10));
+        assert_eq!(find("nested.end_byte"), (12, 13));
+        assert_eq!(find("test2"), (13, 17));
+    }
+
+    #[test]
+    fn field_layout_reports_fields_of_list_elements() {
+        #[derive(Debug, Clone, BinarySerialize)]
+        struct Record {
+            id: u16,
+            name: Vec<u8>,
+        }
+
+        #[derive(Debug, Clone, BinarySerialize)]
+        struct Table {
+            raw: Vec<u8>,
+            records: Vec<Record>,
+            nested: Vec<Vec<Record>>,
+        }
+
+        let record = |id, name: &[u8]| Record {
+            id,
+            name: name.to_vec(),
+        };
+        let table = Table {
+            raw: vec![0; 3],
+            records: vec![record(1, b"ab"), record(2, b"")],
+            nested: vec![vec![record(3, b"c")]],
+        };
+
+        let mut layout = vec![];
+        table.field_layout("", 0, &mut layout);
+
+        let paths: Vec<(&str, usize, usize)> = layout
+            .iter()
+            .map(|span| (span.path.as_str(), span.start, span.end))
+            .collect();
+
+        // the bytes of `raw` have no fields, so only the list itself is reported
+        assert!(!paths.iter().any(|(path, _, _)| path.starts_with("raw[")));
+        assert!(paths.contains(&("raw", 0, 3)));
+        assert!(paths.contains(&("records[0].id", 3, 5)));
+        assert!(paths.contains(&("records[0].name", 5, 7)));
+        assert!(paths.contains(&("records[1].id", 7, 9)));
+        assert!(paths.contains(&("records[1].name", 9, 9)));
+        assert!(paths.contains(&("nested[0][0].id", 9, 11)));
+        assert!(paths.contains(&("nested[0][0].name", 11, 12)));
+    }
+
+    #[test]
+    fn test_differential_blame() {
+        use lain::differential::DifferentialTarget;
+
+        #[derive(Debug, Clone, BinarySerialize)]
+        struct Message {
+            a: u8,
+            b: u16,
+            c: u32,
+        }
+
+        let baseline = Message { a: 0, b: 0, c: 0 };
+        let candidate = Message {
+            a: 1,
+            b: 0x0100,
+            c: 0xFFFF_FFFF,
+        };
+
+        // the "buggy" implementation only looks at the low byte of `b`
+        let reference = |input: &[u8]| u16::from_be_bytes([input[1], input[2]]);
+        let buggy = |input: &[u8]| u16::from(input[2]);
+
+        let mut target = DifferentialTarget::new(reference, buggy);
+        assert!(target.blame::<_, BigEndian>(&baseline, &baseline).is_none());
+
+        let disagreement = target
+            .blame::<_, BigEndian>(&baseline, &candidate)
+            .expect("targets should disagree");
+
+        assert_eq!(disagreement.fields.len(), 1);
+        assert_eq!(disagreement.fields[0].path, "b");
+        assert_eq!(disagreement.fields[0].baseline, vec![0x00, 0x00]);
+        assert_eq!(disagreement.fields[0].candidate, vec![0x01, 0x00]);
+        assert_eq!(
+            disagreement.minimized_input,
+            vec![0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn variant_vec_respects_variant_counts() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        enum Chunk {
+            #[lain(min_count = 1, max_count = 1)]
+            Header(u32),
+            #[lain(min_count = 2)]
+            Data(u8),
+            #[lain(max_count = 3)]
+            Comment(u16),
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct File {
+            chunks: VariantVec<Chunk>,
+        }
+
+        assert_eq!(Chunk::variant_count(), 3);
+        assert_eq!(Chunk::variant_name(1), "Data");
+        assert_eq!(Chunk::variant_count_limits(0), (1, Some(1)));
+        assert_eq!(Chunk::variant_count_limits(2), (0, Some(3)));
+
+        let mut mutator = get_mutator();
+        for _i in 0..20 {
+            let mut file = File::new_fuzzed(&mut mutator, None);
+            assert!(file.chunks.satisfies_variant_limits());
+
+            for _j in 0..100 {
+                file.mutate(&mut mutator, None);
+                assert!(file.chunks.satisfies_variant_limits());
+            }
+        }
+    }
+
+    #[test]
+    fn variant_vec_respects_max_size() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        enum Chunk {
+            #[lain(min_count = 1, max_count = 1)]
+            Header(u32),
+            #[lain(min_count = 2)]
+            Data(u8),
+            Comment([u8; 6]),
+        }
+
+        let mut mutator = get_mutator();
+        let mut constraints = Constraints::new();
+        constraints.max_size(0x20);
+
+        let mut sizes = std::collections::HashSet::new();
+        for _i in 0..50 {
+            let mut chunks = VariantVec::<Chunk>::new_fuzzed(&mut mutator, Some(&constraints));
+            assert!(chunks.serialized_size() <= 0x20);
+            assert!(chunks.satisfies_variant_limits());
+
+            for _j in 0..1000 {
+                chunks.mutate(&mut mutator, Some(&constraints));
+                assert!(chunks.serialized_size() <= 0x20);
+                assert!(chunks.satisfies_variant_limits());
+                sizes.insert(chunks.serialized_size());
+            }
+        }
+        assert!(sizes.len() > 4);
+    }
+
+    #[test]
+    fn blob_content_classes_follow_weights() {
+        let mut mutator = get_mutator();
+
+        let mut weights = BlobContentWeights {
+            zeros: 0,
+            ones: 0,
+            repeating_pattern: 0,
+            ascending: 1,
+            random: 0,
+            text_like: 0,
+            high_entropy: 0,
+        };
+        mutator.set_blob_content_weights(weights.clone());
+
+        for _i in 0..20 {
+            let blob = Blob::new_fuzzed(&mut mutator, None);
+            for pair in blob.as_bytes().windows(2) {
+                assert_eq!(pair[1], pair[0].wrapping_add(1));
+            }
+        }
+
+        weights.ascending = 0;
+        weights.zeros = 1;
+        mutator.set_blob_content_weights(weights);
+
+        let mut constraints = Constraints::new();
+        constraints.min(4).max(32);
+        for _i in 0..20 {
+            let blob = Blob::new_fuzzed(&mut mutator, Some(&constraints));
+            assert!(blob.as_bytes().len() >= 4 && blob.as_bytes().len() < 32);
+            assert!(blob.as_bytes().iter().all(|b| *b == 0));
+        }
+    }
+
+    #[test]
+    fn validated_inputs_are_repaired_or_regenerated() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Packet {
+            magic: u8,
+            payload: u32,
+        }
+
+        impl Validate for Packet {
+            fn validate(&self, serialized: &[u8]) -> bool {
+                serialized[0] == 0x7F && serialized[1] != 0
+            }
+
+            fn repair<R: lain::rand::Rng>(&mut self, _mutator: &mut Mutator<R>) {
+                self.magic = 0x7F;
+            }
+        }
+
+        let mut mutator = get_mutator();
+        for _i in 0..50 {
+            let mut packet = mutator
+                .new_validated::<Packet, BigEndian>(None)
+                .expect("packet should be valid after repair");
+            assert_eq!(packet.magic, 0x7F);
+            assert_ne!(packet.payload >> 24, 0);
+
+            if mutator.mutate_validated::<_, BigEndian>(&mut packet, None) {
+                assert_eq!(packet.magic, 0x7F);
+                assert_ne!(packet.payload >> 24, 0);
+            }
+        }
+
+        assert!(mutator.take_validation_failures() > 0);
+        assert_eq!(mutator.take_validation_failures(), 0);
+    }
+
+    #[test]
+    fn pipeline_fuzzer_only_runs_validated_inputs() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::pipeline::MutationPipeline;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Packet {
+            magic: u8,
+            payload: u32,
+        }
+
+        impl Validate for Packet {
+            fn validate(&self, serialized: &[u8]) -> bool {
+                serialized[0] == 0x7F
+            }
+
+            fn repair<R: lain::rand::Rng>(&mut self, _mutator: &mut Mutator<R>) {
+                self.magic = 0x7F;
+            }
+        }
+
+        static EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+        static INVALID_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+        fn fuzzer_routine(
+            bytes: &[u8],
+            _packet: &Packet,
+            _context: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+            if bytes[0] != 0x7F {
+                INVALID_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+            }
+
+            Outcome::Ok
+        }
+
+        let run = |validated: bool| {
+            EXECUTIONS.store(0, Ordering::SeqCst);
+            INVALID_EXECUTIONS.store(0, Ordering::SeqCst);
+
+            let mut driver = FuzzerDriver::<()>::new(1);
+            driver.set_to_reproduce_mode(0, 500);
+            driver.set_calibration_samples(20);
+            if validated {
+                driver.set_input_validation::<Packet, BigEndian>();
+            }
+
+            let driver = Arc::new(driver);
+            start_pipeline_fuzzer(
+                driver.clone(),
+                Arc::new(MutationPipeline::default()),
+                fuzzer_routine,
+            );
+            driver.join_threads();
+
+            (
+                EXECUTIONS.load(Ordering::SeqCst),
+                INVALID_EXECUTIONS.load(Ordering::SeqCst),
+                driver.num_invalid_inputs(),
+            )
+        };
+
+        let (executions, invalid_executions, _) = run(false);
+        assert!(executions > 0);
+        assert!(invalid_executions > 0);
+
+        let (executions, invalid_executions, invalid_inputs) = run(true);
+        assert!(executions > 0);
+        assert_eq!(invalid_executions, 0);
+        assert!(invalid_inputs > 0);
+    }
+
+    #[test]
+    fn property_testing_adapters_generate_values() {
+        use lain::interop::{new_fuzzed_strategy, QuickcheckAdapter};
+        use proptest::strategy::{Strategy, ValueTree};
+        use proptest::test_runner::TestRunner;
+
+        #[derive(Debug, Clone, NewFuzzed, BinarySerialize)]
+        struct Header {
+            #[lain(min = 1, max = 10)]
+            version: u8,
+            length: u16,
+        }
+
+        fn serializes_to_three_bytes(header: QuickcheckAdapter<Header>) -> bool {
+            let mut bytes = vec![];
+            header.binary_serialize::<_, BigEndian>(&mut bytes);
+
+            bytes.len() == 3
+        }
+
+        quickcheck::QuickCheck::new()
+            .tests(50)
+            .quickcheck(serializes_to_three_bytes as fn(QuickcheckAdapter<Header>) -> bool);
+
+        let mut runner = TestRunner::deterministic();
+        let strategy = new_fuzzed_strategy::<Header>();
+        let first = strategy.new_tree(&mut runner).unwrap().current();
+        let second = strategy.new_tree(&mut runner).unwrap().current();
+
+        let mut first_bytes = vec![];
+        first.binary_serialize::<_, BigEndian>(&mut first_bytes);
+        let mut second_bytes = vec![];
+        second.binary_serialize::<_, BigEndian>(&mut second_bytes);
+
+        assert_eq!(first_bytes.len(), 3);
+        assert_ne!(first_bytes, second_bytes);
+    }
+
+    #[test]
+    fn sharded_corpus_merges_and_deduplicates() {
+        use lain::corpus::{start_merger, ShardedCorpus};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let corpus = Arc::new(ShardedCorpus::<Vec<u8>>::new(4));
+
+        let workers: Vec<_> = (0..4)
+            .map(|shard| {
+                let corpus = corpus.clone();
+                std::thread::spawn(move || {
+                    for i in 0..100u8 {
+                        // every worker finds the same 50 inputs plus 50 of its own
+                        let entry = if i < 50 { vec![i] } else { vec![shard, i] };
+                        corpus.add_to_shard(shard as usize, entry);
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert!(corpus.is_empty());
+        assert_eq!(corpus.merge(), 50 + 4 * 50);
+        assert_eq!(corpus.len(), 250);
+
+        let merger = start_merger(corpus.clone(), Duration::from_millis(10));
+        corpus.add(vec![0]);
+        corpus.add(vec![0xAA, 0xBB]);
+        merger.stop();
+
+        assert_eq!(corpus.len(), 251);
+
+        let metrics = corpus.metrics();
+        assert_eq!(metrics.entries_added, 402);
+        assert_eq!(metrics.entries_merged, 251);
+        assert_eq!(metrics.duplicates_dropped, 151);
+        assert!(metrics.merges >= 2);
+    }
+
+    #[test]
+    fn experiment_arms_share_seed_budget() {
+        use lain::experiments::{Experiment, IterationOutcome};
+        use lain::rand::rngs::StdRng;
+        use std::collections::HashSet;
+
+        fn routine(mutator: &mut Mutator<StdRng>, seen: &mut HashSet<u8>) -> IterationOutcome {
+            let blob = Blob::new_fuzzed(mutator, None);
+            let bytes = blob.as_bytes();
+
+            IterationOutcome {
+                crashed: !bytes.is_empty() && bytes.iter().all(|b| *b == 0),
+                new_corpus_entry: bytes.first().is_some_and(|b| seen.insert(*b)),
+                coverage: Some(seen.len()),
+            }
+        }
+
+        let zeros_only = |mutator: &mut Mutator<StdRng>| {
+            mutator.set_blob_content_weights(BlobContentWeights {
+                zeros: 1,
+                ones: 0,
+                repeating_pattern: 0,
+                ascending: 0,
+                random: 0,
+                text_like: 0,
+                high_entropy: 0,
+            });
+        };
+
+        let report = Experiment::new(200)
+            .seed(0x1234)
+            .sample_interval(50)
+            .arm("default", |_mutator| {})
+            .arm("zeros", zeros_only)
+            .run(routine);
+
+        assert_eq!(report.a.iterations, 200);
+        assert_eq!(report.b.iterations, 200);
+        assert!(report.b.crashes > report.a.crashes);
+        assert_eq!(report.b.first_crash.map(|i| i < 10), Some(true));
+        assert!(report.b.corpus_size <= 1);
+        assert!(report.a.corpus_size > report.b.corpus_size);
+        assert_eq!(report.a.coverage, Some(report.a.corpus_size));
+        assert_eq!(report.a.corpus_growth.len(), 4);
+        assert_eq!(report.a.corpus_growth[3], (200, report.a.corpus_size));
+        assert!(report.to_string().contains("zeros"));
+
+        // identical configurations with the same seed produce identical results
+        let report = Experiment::new(100)
+            .seed(0x1234)
+            .arm("first", zeros_only)
+            .arm("second", zeros_only)
+            .run(routine);
+
+        assert_eq!(report.a.crashes, report.b.crashes);
+        assert_eq!(report.a.corpus_growth, report.b.corpus_growth);
+    }
+
+    #[test]
+    fn semantic_network_types_generate_realistic_and_attack_values() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Header {
+            src_port: Port,
+            vlan: VlanTag,
+            ttl: Ttl,
+            window: WindowSize,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_invalid_value_chance(0.0);
+
+        for _i in 0..100 {
+            let header = Header::new_fuzzed(&mut mutator, None);
+            assert_ne!(header.src_port.value(), 0);
+            assert!(VlanTag::VALID.contains(&header.vlan.value()));
+            assert!(header.ttl.value() > 0);
+            assert!(header.window.value() > 0);
+            assert_eq!(header.serialized_size(), 7);
+        }
+
+        mutator.set_invalid_value_chance(1.0);
+
+        for _i in 0..100 {
+            let mut header = Header::new_fuzzed(&mut mutator, None);
+            assert!(header.src_port.is_attack_value());
+            assert!(header.vlan.is_attack_value());
+            assert!(header.ttl.is_attack_value());
+            assert!(header.window.is_attack_value());
+
+            header.mutate(&mut mutator, None);
+            let mut serialized = vec![];
+            header.binary_serialize::<_, BigEndian>(&mut serialized);
+            assert_eq!(serialized.len(), 7);
+        }
+
+        let mut constraints = Constraints::new();
+        constraints.min(8000).max(8080);
+        // bounds may occasionally be ignored on purpose
+        let in_range = (0..100)
+            .map(|_| Port::new_fuzzed(&mut mutator, Some(&constraints)).value())
+            .filter(|port| (8000..8080).contains(port))
+            .count();
+        assert!(in_range > 80);
+    }
+
+    #[test]
+    fn mutation_pipeline_runs_stages_in_order() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver};
+        use lain::pipeline::MutationPipeline;
+        use lain::rand::rngs::StdRng;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            length: u8,
+            #[lain(min = 1, max = 16)]
+            payload: Vec<u8>,
+        }
+
+        impl Fixup for Message {
+            fn fixup<R: lain::rand::Rng>(&mut self, _mutator: &mut Mutator<R>) {
+                self.length = self.payload.len() as u8;
+            }
+        }
+
+        fn append_checksum(bytes: &mut Vec<u8>) {
+            let sum = bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+            bytes.push(sum);
+        }
+
+        let pipeline = MutationPipeline::<Message, StdRng>::new()
+            .mutate(1.0)
+            .fixup(1.0)
+            .serialize::<BigEndian>()
+            .havoc(0.0, 8)
+            .repair(1.0, append_checksum);
+
+        let mut mutator = Mutator::new(StdRng::seed_from_u64(0));
+        let mut message = Message::new_fuzzed(&mut mutator, None);
+        for _i in 0..50 {
+            let bytes = pipeline.run(&mut mutator, &mut message);
+            let (checksum, data) = bytes.split_last().unwrap();
+
+            assert_eq!(data[0] as usize, data.len() - 1);
+            assert_eq!(
+                *checksum,
+                data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+            );
+        }
+
+        let stages: Vec<&str> = pipeline.stages().map(|(stage, _)| stage.name()).collect();
+        assert_eq!(stages, ["mutate", "fixup", "serialize", "havoc", "bytes"]);
+
+        let result = std::panic::catch_unwind(|| {
+            MutationPipeline::<Message, StdRng>::new()
+                .serialize::<BigEndian>()
+                .mutate(1.0)
+        });
+        assert!(result.is_err());
+
+        // the driver runs the pipeline and hands the serialized bytes to the callback
+        #[derive(Default)]
+        struct GlobalContext {
+            lengths_matched: usize,
+        }
+
+        fn fuzzer_routine(
+            bytes: &[u8],
+            message: &Message,
+            _ctx: &mut (),
+            global_ctx: Option<Arc<RwLock<GlobalContext>>>,
+        ) -> Result<(), ()> {
+            if bytes.len() == message.payload.len() + 2 {
+                global_ctx.unwrap().write().unwrap().lengths_matched += 1;
+            }
+
+            Ok(())
+        }
+
+        let mut driver = FuzzerDriver::<GlobalContext>::new(1);
+        let global_context: Arc<RwLock<GlobalContext>> = Default::default();
+        driver.set_global_context(global_context.clone());
+        driver.set_to_reproduce_mode(0, 20);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(driver.clone(), Arc::new(pipeline), fuzzer_routine);
+        driver.join_threads();
+
+        assert_eq!(driver.num_iterations(), 20);
+        assert_eq!(global_context.read().unwrap().lengths_matched, 20);
+    }
+
+    #[test]
+    fn enum_variant_sizes_are_exposed() {
+        #[derive(Debug, Clone, NewFuzzed, BinarySerialize)]
+        struct Payload {
+            #[lain(min = 0, max = 64)]
+            data: Vec<u8>,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, BinarySerialize)]
+        enum Record {
+            Small(u8),
+            Large(u64, u32),
+            Empty,
+            Data(Payload),
+        }
+
+        #[derive(Copy, Clone, NewFuzzed, BinarySerialize, ToPrimitiveU16)]
+        enum Opcode {
+            Read = 1,
+            Write = 2,
+            Reset = 3,
+        }
+
+        assert_eq!(Record::variant_serialized_size(0), Some(1));
+        assert_eq!(Record::variant_serialized_size(1), Some(12));
+        assert_eq!(Record::variant_serialized_size(2), Some(0));
+        assert_eq!(
+            Record::variant_serialized_size(3),
+            Some(Payload::max_default_object_size())
+        );
+        assert_eq!(Record::variant_serialized_size(4), None);
+        assert_eq!(Record::min_variant_size(), 0);
+        assert_eq!(Record::max_variant_size(), 12);
+
+        assert_eq!(Opcode::variant_serialized_size(2), Some(2));
+        assert_eq!(Opcode::variant_serialized_size(3), None);
+        assert_eq!(Opcode::min_variant_size(), 2);
+        assert_eq!(Opcode::max_variant_size(), 2);
+
+        assert_eq!(Payload::variant_serialized_size(0), None);
+
+        // smaller variants may use the room reserved for the largest one
+        let mut constraints = Constraints::new();
+        constraints.max_size(32);
+
+        let mut mutator = get_mutator();
+        let mut largest_payload = 0;
+        for _i in 0..200 {
+            let record = Record::new_fuzzed(&mut mutator, Some(&constraints));
+            assert!(record.serialized_size() <= 32);
+
+            if let Record::Data(ref payload) = record {
+                largest_payload = std::cmp::max(largest_payload, payload.data.len());
+            }
+        }
+
+        assert!(largest_payload > 32 - 2 * Record::max_variant_size());
+    }
+
+    #[test]
+    fn lazy_fields_are_generated_at_serialization_time() {
+        #[derive(Debug, Clone, NewFuzzed, BinarySerialize)]
+        struct Firmware {
+            #[lain(min = 0x10000, max = 0x20000)]
+            image: Vec<u8>,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct UpdateRequest {
+            version: u32,
+            firmware: Lazy<Firmware>,
+        }
+
+        assert!(std::mem::size_of::<UpdateRequest>() < 0x100);
+
+        let mut mutator = get_mutator();
+        let mut request = UpdateRequest::new_fuzzed(&mut mutator, None);
+
+        let mut first = vec![];
+        let written = request.binary_serialize::<_, BigEndian>(&mut first);
+        assert_eq!(written, first.len());
+        assert_eq!(request.serialized_size(), first.len());
+        assert!(first.len() >= 4 + 0x10000);
+
+        let mut second = vec![];
+        request
+            .clone()
+            .binary_serialize::<_, BigEndian>(&mut second);
+        assert_eq!(first, second);
+
+        let lazy = Lazy::<Firmware>::new(request.firmware.seed());
+        assert_eq!(lazy.generate().image, request.firmware.generate().image);
+
+        let seed = request.firmware.seed();
+        request.firmware.mutate(&mut mutator, None);
+        assert_ne!(request.firmware.seed(), seed);
+    }
+
+    #[test]
+    fn mutation_weight_distributes_mutations() {
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Packet {
+            #[lain(mutation_weight = 1)]
+            header: u64,
+            #[lain(mutation_weight = 20)]
+            body: u64,
+            #[lain(mutation_weight = 0)]
+            reserved: u64,
+        }
+
+        let mut mutator = get_mutator();
+        let original = Packet::default();
+
+        let mut header_mutations = 0;
+        let mut body_mutations = 0;
+        for _i in 0..500 {
+            let mut packet = original.clone();
+            packet.mutate(&mut mutator, None);
+
+            if packet.header != original.header {
+                header_mutations += 1;
+            }
+
+            if packet.body != original.body {
+                body_mutations += 1;
+            }
+
+            assert_eq!(packet.reserved, original.reserved);
+        }
+
+        assert!(header_mutations > 0);
+        assert!(body_mutations > header_mutations * 3);
+    }
+
+    #[test]
+    fn mutation_chance_limits_how_often_fields_change() {
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Packet {
+            #[lain(mutation_chance = 0.05)]
+            magic: u64,
+            #[lain(mutation_chance = 0)]
+            version: u64,
+            payload: u64,
+        }
+
+        let mut mutator = get_mutator();
+        let original = Packet::default();
+
+        let mut magic_mutations = 0;
+        let mut payload_mutations = 0;
+        for _i in 0..1000 {
+            let mut packet = original.clone();
+            packet.mutate(&mut mutator, None);
+
+            if packet.magic != original.magic {
+                magic_mutations += 1;
+            }
+
+            if packet.payload != original.payload {
+                payload_mutations += 1;
+            }
+
+            assert_eq!(packet.version, original.version);
+        }
+
+        assert!(magic_mutations > 0);
+        assert!(payload_mutations > magic_mutations * 5);
+    }
+
+    #[test]
+    fn selftest_catches_inconsistent_models() {
+        use lain::selftest::SelfTest;
+
+        #[derive(Debug, Clone, PartialEq, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Header {
+            kind: u16,
+            flags: u8,
+        }
+
+        fn parse_header(bytes: &[u8]) -> Option<Header> {
+            if bytes.len() != 3 {
+                return None;
+            }
+
+            Some(Header {
+                kind: u16::from_be_bytes([bytes[0], bytes[1]]),
+                flags: bytes[2],
+            })
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        #[lain(serialized_size = 2)]
+        struct Oversized {
+            a: u32,
+        }
+
+        lain::selftest::<Header>();
+
+        SelfTest::<Header>::new()
+            .iterations(20)
+            .round_trip::<BigEndian, _>(parse_header)
+            .run()
+            .unwrap();
+
+        // a little endian parser won't round trip big endian data
+        let result = SelfTest::<Header>::new()
+            .iterations(20)
+            .round_trip::<BigEndian, _>(|bytes| {
+                parse_header(bytes).map(|h| Header {
+                    kind: h.kind.swap_bytes(),
+                    flags: h.flags,
+                })
+            })
+            .run();
+        assert!(result.is_err());
+
+        let error = SelfTest::<Oversized>::new()
+            .iterations(5)
+            .mutations(0)
+            .run()
+            .unwrap_err();
+        assert_eq!(error.total_failures, 5);
+        assert!(error
+            .to_string()
+            .contains("serialized_size is 2 but 4 bytes were written"));
+    }
+
+    #[test]
+    fn import_raw_dir_parses_typed_seeds() {
+        use lain::byteorder::LittleEndian;
+        use lain::corpus::ShardedCorpus;
+
+        let dir = std::env::temp_dir().join(format!("lain_import_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a"), [1u8, 0, 2, 0]).unwrap();
+        std::fs::write(dir.join("b"), [3u8, 0, 4, 0]).unwrap();
+        std::fs::write(dir.join("short"), [5u8, 0, 6]).unwrap();
+        std::fs::write(dir.join("long"), [7u8, 0, 8, 0, 9]).unwrap();
+        std::fs::create_dir_all(dir.join("subdir")).unwrap();
+
+        let corpus = ShardedCorpus::<[u16; 2]>::new(2);
+        let summary = corpus
+            .import_raw_dir::<LittleEndian, _>(&dir)
+            .expect("failed to read corpus directory");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(summary.imported, 2);
+        assert_eq!(corpus.snapshot(), vec![[1, 2], [3, 4]]);
+
+        let failed: Vec<(String, usize)> = summary
+            .failed
+            .iter()
+            .map(|(path, e)| {
+                (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    e.offset,
+                )
+            })
+            .collect();
+        assert_eq!(
+            failed,
+            vec![("long".to_string(), 4), ("short".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn findings_report_exports_json_and_sarif() {
+        use lain::report::{Finding, FindingKind, FindingsReport};
+
+        let report = FindingsReport::new();
+        report.record(
+            Finding::new(FindingKind::Crash, &[1, 2, 3], 0x1234)
+                .iteration(42)
+                .message("null \"deref\"")
+                .stack(vec!["parse_header", "parse_packet", "main"]),
+        );
+        report.record(
+            Finding::new(FindingKind::Crash, &[4, 5], 0x1234)
+                .iteration(43)
+                .stack(vec!["parse_header", "parse_packet", "main", "start"]),
+        );
+        report.record(Finding::new(FindingKind::Hang, &[6], 0x1234).bucket("slow-path"));
+
+        let findings = report.findings();
+        assert_eq!(report.len(), 3);
+        assert_eq!(report.num_buckets(), 2);
+        assert_eq!(findings[0].get_bucket(), findings[1].get_bucket());
+        assert_eq!(findings[2].get_bucket(), "slow-path");
+        assert_ne!(findings[0].input_hash, findings[1].input_hash);
+
+        let json = report.to_json();
+        assert!(json.starts_with("{\"tool\":\"lain\""));
+        assert!(json.contains("\"message\":\"null \\\"deref\\\"\""));
+        assert!(json.contains("\"iteration\":42"));
+        assert!(json.contains("\"seed\":4660"));
+        assert!(json.contains("\"stack\":[\"parse_header\",\"parse_packet\",\"main\"]"));
+        assert!(json.contains("\"bucket\":\"slow-path\""));
+
+        let sarif = report.to_sarif();
+        assert!(sarif.contains("\"version\":\"2.1.0\""));
+        assert_eq!(sarif.matches("\"ruleId\":\"crash\"").count(), 2);
+        assert_eq!(sarif.matches("\"ruleId\":\"hang\"").count(), 1);
+        assert_eq!(sarif.matches('{').count(), sarif.matches('}').count());
+    }
+
+    #[test]
+    fn corpus_snapshots_are_written_and_rotated() {
+        use lain::corpus::{list_snapshots, rotate_snapshots, start_snapshotter, ShardedCorpus};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let root = std::env::temp_dir().join(format!("lain_snapshots_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+
+        let corpus = Arc::new(ShardedCorpus::<u16>::new(1));
+        corpus.add(0x0102);
+        corpus.add(0x0304);
+        corpus.merge();
+
+        let first = corpus.write_snapshot::<BigEndian, _>(&root).unwrap();
+        corpus.add(0x0506);
+        corpus.merge();
+        let second = corpus.write_snapshot::<BigEndian, _>(&root).unwrap();
+        assert!(first < second);
+
+        assert_eq!(std::fs::read(first.join("entries/000001")).unwrap(), [3, 4]);
+        assert_eq!(
+            std::fs::read_dir(second.join("entries")).unwrap().count(),
+            3
+        );
+        let stats = std::fs::read_to_string(second.join("stats.json")).unwrap();
+        assert!(stats.contains("\"entries\":3"));
+        assert!(stats.contains("\"merges\":2"));
+
+        assert_eq!(rotate_snapshots(&root, 1).unwrap(), 1);
+        assert_eq!(list_snapshots(&root).unwrap(), vec![second]);
+
+        let handle = start_snapshotter::<_, BigEndian>(
+            corpus.clone(),
+            root.clone(),
+            Duration::from_millis(5),
+            2,
+        );
+        std::thread::sleep(Duration::from_millis(50));
+        handle.stop();
+
+        assert_eq!(list_snapshots(&root).unwrap().len(), 2);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn from_pool_fields_draw_from_registered_ids() {
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct CloseSession {
+            #[lain(from_pool = "session_ids")]
+            session_id: u32,
+            #[lain(from_pool = "handles")]
+            handle: u16,
+            flags: u8,
+        }
+
+        let mut mutator = get_mutator();
+
+        let mut sessions = IdPool::<u32>::new();
+        sessions.set_garbage_chance(0.0);
+        sessions.insert(7);
+        sessions.insert(9);
+        sessions.insert(7);
+        assert_eq!(sessions.ids(), &[7, 9]);
+        mutator.register_id_pool("session_ids", sessions);
+
+        for _i in 0..100 {
+            let mut message = CloseSession::new_fuzzed(&mut mutator, None);
+            assert!(message.session_id == 7 || message.session_id == 9);
+
+            message.mutate(&mut mutator, None);
+            assert!(message.session_id == 7 || message.session_id == 9);
+        }
+
+        // ids of the wrong type are ignored and random values are generated
+        mutator.register_id_pool("handles", IdPool::<u32>::new());
+        mutator.record_id("handles", 0x1234u32);
+        assert!(mutator.id_pool::<u16>("handles").is_none());
+        let random_handles = (0..100)
+            .filter(|_| CloseSession::new_fuzzed(&mut mutator, None).handle != 0x1234)
+            .count();
+        assert!(random_handles > 90);
+
+        mutator.register_id_pool("handles", IdPool::<u16>::with_capacity(2));
+        for id in 1u16..=3 {
+            mutator.record_id("handles", id);
+        }
+        assert_eq!(mutator.id_pool::<u16>("handles").unwrap().ids(), &[2, 3]);
+
+        let pooled = (0..100)
+            .filter(|_| {
+                let handle = CloseSession::new_fuzzed(&mut mutator, None).handle;
+                handle == 2 || handle == 3
+            })
+            .count();
+        assert!(pooled > 70);
+
+        mutator
+            .id_pool_mut::<u32>("session_ids")
+            .unwrap()
+            .set_garbage_chance(1.0);
+        let garbage = (0..100)
+            .filter(|_| {
+                let id = CloseSession::new_fuzzed(&mut mutator, None).session_id;
+                id != 7 && id != 9
+            })
+            .count();
+        assert!(garbage > 90);
+    }
+
+    #[test]
+    fn auto_increment_fields_follow_session_counter() {
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Request {
+            #[lain(auto_increment)]
+            seq: u8,
+            #[lain(auto_increment = "message_id")]
+            id: u32,
+        }
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Ack(#[lain(auto_increment = "message_id")] u32);
+
+        let mut mutator = get_mutator();
+
+        for i in 0..300u32 {
+            let request = Request::new_fuzzed(&mut mutator, None);
+            assert_eq!(request.seq, (i % 256) as u8);
+            assert_eq!(request.id, 2 * i);
+
+            let ack = Ack::new_fuzzed(&mut mutator, None);
+            assert_eq!(ack.0, 2 * i + 1);
+        }
+
+        mutator.reset_sequence_numbers();
+        mutator.set_sequence_number("message_id", 1000);
+        let request = Request::new_fuzzed(&mut mutator, None);
+        assert_eq!(request.seq, 0);
+        assert_eq!(request.id, 1000);
+
+        let mut previous = request.id;
+        let (mut in_order, mut anomalies) = (0, 0);
+        let mut ack = Ack::default();
+        for _i in 0..1000 {
+            ack.mutate(&mut mutator, None);
+            if ack.0 == previous + 1 {
+                in_order += 1;
+            } else if ack.0 != previous {
+                anomalies += 1;
+                assert!(ack.0 == previous - 1 || ack.0 > previous + 1);
+            }
+            previous = ack.0;
+        }
+        assert!(in_order > 850);
+        assert!(anomalies > 0);
+
+        mutator.set_sequence_anomaly_chance(0.0);
+        mutator.set_sequence_number("message_id", 0);
+        let unchanged = (0..100)
+            .filter(|i| {
+                ack.mutate(&mut mutator, None);
+                ack.0 == *i
+            })
+            .count();
+        assert!(unchanged < 100);
+    }
+
+    #[test]
+    fn now_fields_are_generated_near_the_current_time() {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Login {
+            #[lain(now, jitter = "5s")]
+            timestamp: u32,
+            #[lain(now = "ms")]
+            timestamp_ms: u64,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        enum Message {
+            Ping(u8),
+            Login(#[lain(now, jitter = "1m")] u64),
+        }
+
+        let mut mutator = get_mutator();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let secs = now.as_secs() as i64;
+        let millis = now.as_millis() as i64;
+
+        let (mut near, mut near_ms) = (0, 0);
+        let mut login = Login::default();
+        for i in 0..200 {
+            if i % 2 == 0 {
+                login = Login::new_fuzzed(&mut mutator, None);
+            } else {
+                login.mutate(&mut mutator, None);
+            }
+
+            if (login.timestamp as i64 - secs).abs() <= 6 {
+                near += 1;
+            }
+
+            if (login.timestamp_ms as i64 - millis).abs() < 1000 {
+                near_ms += 1;
+            }
+        }
+        // a few timestamps are given extreme values
+        assert!(near > 150 && near < 200);
+        assert!(near_ms > 150 && near_ms < 200);
+
+        mutator.set_timestamp_extreme_chance(0.0);
+        for _i in 0..100 {
+            if let Message::Login(timestamp) = Message::new_fuzzed(&mut mutator, None) {
+                assert!((timestamp as i64 - secs).abs() <= 61);
+            }
+
+            let mut login = Message::Login(0);
+            login.mutate(&mut mutator, None);
+            if let Message::Login(timestamp) = login {
+                assert!(timestamp == 0 || (timestamp as i64 - secs).abs() <= 61);
+            }
+        }
+    }
+
+    #[test]
+    fn driver_routes_callback_outcomes() {
+        use lain::driver::{start_fuzzer, start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::pipeline::MutationPipeline;
+        use lain::report::FindingKind;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            #[lain(min = 1, max = 16)]
+            payload: Vec<u8>,
+        }
+
+        #[derive(Default)]
+        struct ThreadContext {
+            iterations: usize,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            _message: &Message,
+            ctx: &mut ThreadContext,
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            ctx.iterations += 1;
+            match ctx.iterations % 5 {
+                0 => Outcome::Ok,
+                1 => Outcome::Crash,
+                2 => Outcome::Hang,
+                3 => Outcome::Reject,
+                _ => Outcome::Interesting(String::from("new_edge")),
+            }
+        }
+
+        let output_dir = std::env::temp_dir().join(format!("lain_outcomes_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_output_dir(&output_dir);
+        driver.set_to_reproduce_mode(0, 20);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        assert_eq!(driver.num_crashes(), 4);
+        assert_eq!(driver.num_hangs(), 4);
+        assert_eq!(driver.num_rejected_inputs(), 4);
+        assert_eq!(driver.num_interesting_inputs(), 4);
+        assert_eq!(driver.num_failed_iterations(), 8);
+
+        let findings = driver.findings().findings();
+        assert_eq!(findings.len(), 8);
+        assert_eq!(findings[0].kind, FindingKind::Crash);
+        assert_eq!(findings[0].iteration, Some(0));
+        assert_eq!(findings[1].kind, FindingKind::Hang);
+        assert!(findings
+            .iter()
+            .all(|f| f.seed == driver.seed() && f.input_size > 0));
+
+        for subdir in ["crashes", "hangs", "interesting/new_edge"].iter() {
+            let persisted = std::fs::read_dir(output_dir.join(subdir)).unwrap().count();
+            assert!(persisted > 0 && persisted <= 4);
+        }
+        std::fs::remove_dir_all(&output_dir).unwrap();
+
+        // callbacks returning a Result are still supported, with errors counted as crashes
+        fn legacy_routine<R: lain::rand::Rng>(
+            mutator: &mut Mutator<R>,
+            _ctx: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Result<(), ()> {
+            if mutator.gen_chance(0.5) {
+                Err(())
+            } else {
+                Ok(())
+            }
+        }
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_to_reproduce_mode(0, 20);
+
+        let driver = Arc::new(driver);
+        start_fuzzer(driver.clone(), legacy_routine);
+        driver.join_threads();
+
+        assert!(driver.num_crashes() > 0);
+        assert_eq!(driver.num_crashes(), driver.num_failed_iterations());
+        assert_eq!(driver.findings().len(), driver.num_crashes());
+    }
+
+    #[test]
+    fn interesting_tags_cannot_escape_the_output_dir() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::pipeline::MutationPipeline;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            #[lain(min = 1, max = 16)]
+            payload: Vec<u8>,
+        }
+
+        #[derive(Default)]
+        struct ThreadContext {
+            iterations: usize,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            _message: &Message,
+            ctx: &mut ThreadContext,
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            ctx.iterations += 1;
+            let tag = match ctx.iterations % 3 {
+                0 => "../escaped",
+                1 => "/absolute",
+                _ => "..",
+            };
+
+            Outcome::Interesting(tag.to_string())
+        }
+
+        let root = std::env::temp_dir().join(format!("lain_tags_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let output_dir = root.join("out");
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_output_dir(&output_dir);
+        driver.set_to_reproduce_mode(0, 9);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        assert_eq!(driver.num_interesting_inputs(), 9);
+        assert!(!root.join("escaped").exists());
+        assert!(!output_dir.join("escaped").exists());
+
+        let mut dirs: Vec<String> = std::fs::read_dir(output_dir.join("interesting"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        dirs.sort();
+        assert_eq!(dirs, vec![".._escaped", "_..", "_absolute"]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn coverage_attribution_reports_field_effectiveness() {
+        use lain::attribution::CoverageAttribution;
+
+        #[derive(
+            Debug, Default, Copy, Clone, PartialEq, FuzzerObject, BinarySerialize, ToPrimitiveU8,
+        )]
+        #[repr(u8)]
+        enum Opcode {
+            #[default]
+            Read = 1,
+            Write = 2,
+            #[lain(weight = 0)]
+            Reset = 3,
+        }
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Item {
+            id: u16,
+        }
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Request {
+            #[lain(mutation_weight = 0)]
+            version: u8,
+            opcode: Opcode,
+            flags: u8,
+            #[lain(min = 1, max = 4)]
+            items: Vec<Item>,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_track_variants(true);
+
+        let mut attribution = CoverageAttribution::new();
+        let mut request = Request::new_fuzzed(&mut mutator, None);
+        for _i in 0..200 {
+            let before = request.clone();
+            request.mutate(&mut mutator, None);
+
+            // pretend only changes to item ids reach new code
+            let new_coverage = before.items.len() == request.items.len()
+                && before
+                    .items
+                    .iter()
+                    .zip(request.items.iter())
+                    .any(|(a, b)| a.id != b.id);
+            attribution.record(&before, &request, new_coverage);
+        }
+        attribution.merge_variants(&mut mutator);
+
+        assert_eq!(attribution.iterations(), 200);
+        assert!(attribution.field("items[].id").unwrap().new_coverage > 0);
+        assert!(attribution.field("flags").unwrap().mutations > 0);
+        assert_eq!(attribution.field("version").unwrap().mutations, 0);
+
+        let report = attribution.report();
+        assert!(report
+            .productive
+            .iter()
+            .any(|(path, _)| path == "items[].id"));
+        assert_eq!(report.never_mutated, vec!["version".to_string()]);
+        assert_eq!(report.unused_variants.len(), 1);
+        assert!(report.unused_variants[0].0.ends_with("Opcode"));
+        assert_eq!(report.unused_variants[0].1, "Reset");
+        assert!(report.to_string().contains("Opcode::Reset"));
+
+        // merging combines the statistics of both instances
+        let mut total = CoverageAttribution::new();
+        total.merge(&attribution);
+        total.merge(&attribution);
+        assert_eq!(total.iterations(), 400);
+        assert_eq!(
+            total.field("flags").unwrap().mutations,
+            2 * attribution.field("flags").unwrap().mutations
+        );
+    }
+
+    #[test]
+    fn calibration_tunes_blob_weights_and_max_size() {
+        use lain::calibration::Calibration;
+        use lain::driver::Outcome;
+
+        let report = Calibration::<Blob>::new()
+            .samples(700)
+            .seed(1)
+            .run(|bytes, _blob| {
+                if bytes.len() > 0x100 || (!bytes.is_empty() && bytes.iter().all(|b| *b == 0)) {
+                    Outcome::Reject
+                } else {
+                    Outcome::Ok
+                }
+            });
+
+        assert_eq!(report.samples, 700);
+        assert!(report.rejection_rate() > 0.5);
+        assert!(report.sizes.max > 0x100);
+        assert!(report.max_exec_time >= report.median_exec_time);
+
+        // blobs of zeros are always rejected, so they should barely be generated anymore
+        let zeros = report
+            .content
+            .iter()
+            .find(|stats| stats.content == BlobContent::Zeros)
+            .unwrap();
+        assert_eq!(zeros.samples, 100);
+        assert!(zeros.rejection_rate() > 0.95);
+        assert_eq!(report.blob_content_weights.zeros, 1);
+        assert!(report.blob_content_weights.text_like > 1);
+
+        // most rejected inputs were too large
+        let max_size = report.max_size.expect("max_size should have been shrunk");
+        assert!(max_size <= 0x100);
+
+        let mut mutator = get_mutator();
+        report.apply(&mut mutator);
+        assert_eq!(mutator.blob_content_weights(), &report.blob_content_weights);
+
+        let constraints = report.constraints::<usize>();
+        for _i in 0..100 {
+            let blob = Blob::new_fuzzed(&mut mutator, constraints.as_ref());
+            assert!(blob.as_bytes().len() <= max_size);
+        }
+    }
+
+    #[test]
+    fn variant_walk_covers_every_enum_combination() {
+        use lain::walk::VariantWalk;
+        use std::collections::HashSet;
+
+        #[derive(
+            Debug,
+            Default,
+            Copy,
+            Clone,
+            PartialEq,
+            Eq,
+            Hash,
+            FuzzerObject,
+            BinarySerialize,
+            ToPrimitiveU8,
+        )]
+        #[repr(u8)]
+        enum Mode {
+            #[default]
+            Idle = 1,
+            Active = 2,
+            #[lain(weight = 100)]
+            Standby = 3,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        enum Payload {
+            Short(u8),
+            #[lain(weight = 100)]
+            Long(u32),
+        }
+
+        impl Default for Payload {
+            fn default() -> Self {
+                Payload::Short(0)
+            }
+        }
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Command {
+            mode: Mode,
+            payload: Payload,
+            value: u32,
+        }
+
+        let mut mutator = get_mutator();
+        let mut walk = VariantWalk::<Command>::discover(&mut mutator, 256);
+
+        assert_eq!(walk.dimensions().len(), 2);
+        assert_eq!(walk.num_combinations(), 6);
+
+        let mut combinations = HashSet::new();
+        let mut values = HashSet::new();
+        while let Some(command) = walk.generate(&mut mutator) {
+            let payload = match command.payload {
+                Payload::Short(_) => "short",
+                Payload::Long(_) => "long",
+            };
+            assert!(combinations.insert((command.mode, payload)));
+            values.insert(command.value);
+        }
+
+        // every combination was generated exactly once, even the unlikely ones
+        assert_eq!(combinations.len(), 6);
+        assert!(walk.is_exhausted());
+        assert!(walk.generate(&mut mutator).is_none());
+        // scalars are still randomized
+        assert!(values.len() > 1);
+
+        // generation goes back to normal once the walk is done
+        assert_eq!(mutator.forced_variant::<Mode>(), None);
+
+        walk.reset();
+        assert_eq!(walk.num_visited(), 0);
+        assert!(walk.generate(&mut mutator).is_some());
+    }
+
+    #[test]
+    fn sparse_enum_values_are_serialized_and_targeted() {
+        use lain::traits::ToPrimitive;
+        use lain::types::UnsafeEnum;
+
+        #[derive(
+            Debug, Default, Copy, Clone, PartialEq, FuzzerObject, BinarySerialize, ToPrimitiveU16,
+        )]
+        enum MessageType {
+            #[default]
+            #[lain(value = 0x0001)]
+            Hello,
+            #[lain(value = 0x0010)]
+            Data,
+            #[lain(value = 0x8001)]
+            Error,
+            Unassigned,
+        }
+
+        assert_eq!(MessageType::Hello.to_primitive(), 0x0001);
+        assert_eq!(MessageType::Error.to_primitive(), 0x8001);
+        // variants without a value keep their discriminant
+        assert_eq!(MessageType::Unassigned.to_primitive(), 3);
+        assert_eq!(
+            MessageType::valid_primitives(),
+            vec![0x0001, 0x0010, 0x8001, 3]
+        );
+
+        let mut buffer = vec![];
+        MessageType::Error.binary_serialize::<_, BigEndian>(&mut buffer);
+        assert_eq!(buffer, [0x80, 0x01]);
+
+        let valid = MessageType::valid_primitives();
+        let mut mutator = get_mutator();
+        let mut near_valid = 0;
+        for _i in 0..1000 {
+            let value = mutator.gen_invalid_discriminant(&valid).unwrap();
+            assert!(!valid.contains(&value));
+            if value < 0x0010 || (0x8000..=0x8002).contains(&value) {
+                near_valid += 1;
+            }
+        }
+        assert!(near_valid > 500);
+
+        let mut invalid = 0;
+        for _i in 0..1000 {
+            let value = UnsafeEnum::<MessageType, u16>::new_fuzzed(&mut mutator, None);
+            if let UnsafeEnum::Invalid(_) = value {
+                invalid += 1;
+            }
+        }
+        assert!(invalid > 0);
+    }
+
+    #[test]
+    fn float_vectors_and_matrices_keep_their_shape() {
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Mesh {
+            #[lain(min = 2, max = 5)]
+            vertices: Matrix<f32>,
+            #[lain(min = 1, max = 8)]
+            weights: FloatVec<f64>,
+        }
+
+        let mut mutator = get_mutator();
+        let mut saw_special = false;
+        for _i in 0..200 {
+            let mut mesh = Mesh::new_fuzzed(&mut mutator, None);
+
+            let (rows, cols) = mesh.vertices.shape();
+            assert!((2..5).contains(&rows));
+            assert!((2..5).contains(&cols));
+            assert!((1..8).contains(&mesh.weights.len()));
+
+            saw_special |= mesh
+                .weights
+                .as_slice()
+                .iter()
+                .any(|w| !w.is_finite() || *w == 0.0);
+
+            for _j in 0..10 {
+                mesh.mutate(&mut mutator, None);
+            }
+
+            // mutation may transpose the matrix but never breaks its shape
+            let (rows, cols) = mesh.vertices.shape();
+            assert_eq!(mesh.vertices.as_slice().len(), rows * cols);
+
+            let mut buffer = vec![];
+            mesh.binary_serialize::<_, LittleEndian>(&mut buffer);
+            assert_eq!(buffer.len(), rows * cols * 4 + mesh.weights.len() * 8);
+            assert_eq!(buffer.len(), mesh.serialized_size());
+        }
+        assert!(saw_special);
+
+        let matrix = Matrix::new(2, 3, vec![1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(matrix.get(1, 0), Some(&4.0));
+        assert_eq!(matrix.get(2, 0), None);
+
+        // max_size limits the total number of elements
+        let mut constraints = Constraints::new();
+        constraints.max_size(64);
+        for _i in 0..100 {
+            let matrix = Matrix::<f64>::new_fuzzed(&mut mutator, Some(&constraints));
+            assert!(matrix.serialized_size() <= 64);
+
+            let vec = FloatVec::<f32>::new_fuzzed(&mut mutator, Some(&constraints));
+            assert!(vec.serialized_size() <= 64);
+        }
+    }
+
+    #[test]
+    fn plugins_run_as_pipeline_stages() {
+        use lain::pipeline::MutationPipeline;
+        use lain::plugin::{Plugin, PluginDescriptor, PluginError, PLUGIN_ABI_VERSION};
+        use std::os::raw::c_char;
+        use std::sync::Arc;
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            id: u32,
+        }
+
+        /// Inverts every byte and appends the low byte of the seed
+        unsafe extern "C" fn invert(
+            data: *mut u8,
+            len: usize,
+            capacity: usize,
+            seed: u64,
+        ) -> usize {
+            let data = std::slice::from_raw_parts_mut(data, capacity);
+            for b in data[..len].iter_mut() {
+                *b = !*b;
+            }
+            data[len] = seed as u8;
+
+            len + 1
+        }
+
+        /// Prefixes the input with its length
+        unsafe extern "C" fn frame(
+            input: *const u8,
+            len: usize,
+            output: *mut u8,
+            capacity: usize,
+        ) -> usize {
+            if len < capacity {
+                *output = len as u8;
+                std::ptr::copy_nonoverlapping(input, output.add(1), len);
+            }
+
+            len + 1
+        }
+
+        static DESCRIPTOR: PluginDescriptor = PluginDescriptor {
+            abi_version: PLUGIN_ABI_VERSION,
+            name: b"invert\0".as_ptr() as *const c_char,
+            mutate: Some(invert),
+            serialize: Some(frame),
+        };
+
+        static OUTDATED: PluginDescriptor = PluginDescriptor {
+            abi_version: 0,
+            name: b"outdated\0".as_ptr() as *const c_char,
+            mutate: None,
+            serialize: None,
+        };
+
+        let plugin = Arc::new(Plugin::from_descriptor(&DESCRIPTOR).unwrap());
+        assert_eq!(plugin.name(), "invert");
+        assert!(plugin.can_mutate() && plugin.can_serialize());
+        assert_eq!(plugin.serialize(&[0xAA; 0x100]).unwrap().len(), 0x101);
+
+        let pipeline = MutationPipeline::<Message, SmallRng>::new()
+            .serialize::<BigEndian>()
+            .plugin_mutate(1.0, plugin.clone())
+            .plugin_serialize(plugin);
+
+        let mut mutator = get_mutator();
+        let mut message = Message { id: 0x01020304 };
+        let bytes = pipeline.run(&mut mutator, &mut message);
+        assert_eq!(bytes.len(), 6);
+        assert_eq!(&bytes[..5], &[5, 0xFE, 0xFD, 0xFC, 0xFB]);
+
+        assert_eq!(
+            Plugin::from_descriptor(&OUTDATED).unwrap_err(),
+            PluginError::AbiVersion {
+                expected: PLUGIN_ABI_VERSION,
+                found: 0
+            }
+        );
+
+        let mut driver = lain::driver::FuzzerDriver::<()>::new(1);
+        match unsafe { driver.load_plugin("/nonexistent/libplugin.so") } {
+            Err(PluginError::Load(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(driver.plugins().is_empty());
+    }
+
+    #[test]
+    fn corpus_epochs_visit_every_entry_once() {
+        use lain::corpus::ShardedCorpus;
+        use std::collections::HashSet;
+
+        let corpus = ShardedCorpus::<u16>::new(1);
+        corpus.set_schedule_seed(7);
+        for i in 0..10u16 {
+            corpus.add(i);
+        }
+        corpus.merge();
+
+        let first: Vec<u16> = (0..10).map(|_| corpus.next_scheduled().unwrap()).collect();
+        assert_eq!(first.iter().copied().collect::<HashSet<u16>>().len(), 10);
+        assert_ne!(first, (0..10).collect::<Vec<u16>>());
+        assert_eq!(corpus.metrics().epochs_completed, 0);
+        assert_eq!(corpus.metrics().epoch_position, 10);
+
+        // entries merged mid-epoch are still visited in that epoch
+        let mut second = vec![corpus.next_scheduled().unwrap()];
+        assert_eq!(corpus.metrics().epochs_completed, 1);
+        corpus.add(10);
+        corpus.merge();
+        second.extend((0..10).map(|_| corpus.next_scheduled().unwrap()));
+        assert_eq!(second.iter().copied().collect::<HashSet<u16>>().len(), 11);
+        assert_ne!(first, second[..10]);
+
+        // the same seed gives the same order
+        corpus.set_schedule_seed(7);
+        let replayed: Vec<u16> = (0..11).map(|_| corpus.next_scheduled().unwrap()).collect();
+        let mut sorted = replayed.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..11).collect::<Vec<u16>>());
+
+        let other = ShardedCorpus::<u16>::new(1);
+        other.set_schedule_seed(7);
+        for i in 0..11u16 {
+            other.add(i);
+        }
+        other.merge();
+        let expected: Vec<u16> = (0..11).map(|_| other.next_scheduled().unwrap()).collect();
+        assert_eq!(replayed, expected);
+    }
+
+    #[test]
+    fn caught_panics_are_bucketed_by_kind() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::panics::catch_panic;
+        use lain::pipeline::MutationPipeline;
+        use lain::report::FindingKind;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            #[lain(min = 1, max = 16)]
+            payload: Vec<u8>,
+        }
+
+        #[derive(Default)]
+        struct ThreadContext {
+            iterations: usize,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            _message: &Message,
+            ctx: &mut ThreadContext,
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            ctx.iterations += 1;
+            // the context is reset after every panic, so this panics every other iteration
+            if ctx.iterations == 2 {
+                panic!("unexpected message type");
+            }
+
+            Outcome::Ok
+        }
+
+        let assertion = catch_panic(|| assert_eq!(1 + 1, 3)).unwrap_err();
+        assert_eq!(assertion.kind, FindingKind::Assertion);
+        assert!(assertion.location.unwrap().contains("testsuite"));
+
+        let overflow =
+            catch_panic(|| std::hint::black_box(u8::MAX) + std::hint::black_box(1)).unwrap_err();
+        assert_eq!(overflow.kind, FindingKind::Overflow);
+        assert_eq!(catch_panic(|| 7), Ok(7));
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_catch_panics(true);
+        driver.set_to_reproduce_mode(0, 10);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        assert_eq!(driver.num_iterations(), 10);
+        assert_eq!(driver.num_crashes(), 5);
+
+        let findings = driver.findings().findings();
+        assert!(findings.iter().all(|f| f.kind == FindingKind::Panic
+            && f.message == "unexpected message type"
+            && f.input_size > 0));
+        assert_eq!(driver.findings().num_buckets(), 1);
+    }
+
+    #[test]
+    fn timestamp_fields_serialize_in_the_selected_format() {
+        use lain::types::format_iso8601;
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Event {
+            created: SystemTime,
+            #[lain(timestamp = "ms")]
+            updated: SystemTime,
+            #[lain(timestamp = "iso8601")]
+            expires: SystemTime,
+        }
+
+        assert_eq!(format_iso8601(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_iso8601(-1), "1969-12-31T23:59:59.999Z");
+        assert_eq!(format_iso8601(951_782_400_000), "2000-02-29T00:00:00.000Z");
+        assert_eq!(
+            format_iso8601(253_402_300_800_000),
+            "+010000-01-01T00:00:00.000Z"
+        );
+
+        let y2038 = UNIX_EPOCH + Duration::from_secs(i32::MAX as u64);
+        let event = Event {
+            created: y2038,
+            updated: y2038 + Duration::from_millis(5),
+            expires: y2038,
+        };
+
+        let mut expected = vec![];
+        expected.extend_from_slice(&(i32::MAX as i64).to_be_bytes());
+        expected.extend_from_slice(&(i32::MAX as i64 * 1000 + 5).to_be_bytes());
+        expected.extend_from_slice(b"2038-01-19T03:14:07.000Z");
+
+        let mut bytes = vec![];
+        assert_eq!(event.binary_serialize::<_, BigEndian>(&mut bytes), 40);
+        assert_eq!(bytes, expected);
+        assert_eq!(event.serialized_size(), 40);
+
+        let mut layout = vec![];
+        event.field_layout("", 0, &mut layout);
+        assert_eq!((layout[2].start, layout[2].end), (16, 40));
+
+        let before_epoch = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(
+            SystemTime::from_bytes::<BigEndian>(&(-2i64).to_be_bytes()).unwrap(),
+            UNIX_EPOCH - Duration::from_secs(2)
+        );
+        assert_eq!(lain::traits::Timestamp::unix_millis(&before_epoch), -1500);
+
+        let mut mutator = get_mutator();
+        for _i in 0..100 {
+            let mut event = Event::new_fuzzed(&mut mutator, None);
+            event.mutate(&mut mutator, None);
+            assert!(event.serialized_size() >= 40);
+        }
+    }
+
+    #[test]
+    fn chrono_and_time_timestamps_are_fuzzed_like_system_times() {
+        use chrono::{DateTime, TimeZone, Utc};
+        use lain::traits::Timestamp;
+        use time::OffsetDateTime;
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Session {
+            started: DateTime<Utc>,
+            #[lain(timestamp = "ms")]
+            renewed: OffsetDateTime,
+            #[lain(timestamp = "iso8601")]
+            expires: DateTime<Utc>,
+        }
+
+        let y2038 = Utc.timestamp_opt(i32::MAX as i64, 0).unwrap();
+        let session = Session {
+            started: y2038,
+            renewed: OffsetDateTime::from_unix_timestamp(i32::MAX as i64).unwrap()
+                + time::Duration::milliseconds(5),
+            expires: y2038,
+        };
+
+        let mut expected = vec![];
+        expected.extend_from_slice(&(i32::MAX as i64).to_be_bytes());
+        expected.extend_from_slice(&(i32::MAX as i64 * 1000 + 5).to_be_bytes());
+        expected.extend_from_slice(b"2038-01-19T03:14:07.000Z");
+
+        let mut bytes = vec![];
+        assert_eq!(session.binary_serialize::<_, BigEndian>(&mut bytes), 40);
+        assert_eq!(bytes, expected);
+        assert_eq!(session.serialized_size(), 40);
+
+        assert_eq!(
+            DateTime::<Utc>::from_bytes::<BigEndian>(&(-2i64).to_be_bytes()).unwrap(),
+            Utc.timestamp_opt(-2, 0).unwrap()
+        );
+        assert_eq!(
+            OffsetDateTime::from_bytes::<BigEndian>(&(-2i64).to_be_bytes()).unwrap(),
+            OffsetDateTime::from_unix_timestamp(-2).unwrap()
+        );
+
+        // sub-millisecond times before the epoch round down, like SystemTime
+        let before_epoch = OffsetDateTime::UNIX_EPOCH - time::Duration::microseconds(1500);
+        assert_eq!(before_epoch.unix_millis(), -2);
+        let before_epoch = Utc.timestamp_opt(-2, 500_000).unwrap();
+        assert_eq!(before_epoch.unix_millis(), -2000);
+
+        // times which can't be represented fall back to the epoch
+        assert_eq!(DateTime::<Utc>::from_unix_millis(i64::MAX).timestamp(), 0);
+        assert_eq!(
+            OffsetDateTime::from_unix_millis(i64::MAX).unix_timestamp(),
+            0
+        );
+
+        let mut mutator = get_mutator();
+        let mut starts = std::collections::HashSet::new();
+        for _i in 0..100 {
+            let mut session = Session::new_fuzzed(&mut mutator, None);
+            session.mutate(&mut mutator, None);
+            assert!(session.serialized_size() >= 40);
+            starts.insert(session.started);
+        }
+        assert!(starts.len() > 50);
+    }
+
+    #[test]
+    fn offset_fields_are_relocated_and_swapped_together() {
+        #[derive(Debug, Clone, Copy, Default, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Header {
+            #[lain(offset)]
+            strings: u32,
+            #[lain(offset)]
+            symbols: u32,
+            #[lain(offset)]
+            relocations: u32,
+            flags: u8,
+        }
+
+        let mut mutator = get_mutator();
+
+        let mut offsets = [0x100u64, 0x200, 0x300];
+        for _i in 0..20 {
+            let before = offsets;
+            mutator.mutate_offsets(&mut offsets);
+
+            let mut sorted = offsets;
+            sorted.sort_unstable();
+            let mut sorted_before = before;
+            sorted_before.sort_unstable();
+
+            let delta = offsets[0].wrapping_sub(before[0]);
+            let shifted = offsets
+                .iter()
+                .zip(before.iter())
+                .all(|(after, before)| after.wrapping_sub(*before) == delta);
+            assert!(shifted || sorted == sorted_before);
+        }
+
+        let (mut relocated, mut swapped) = (0, 0);
+        for _i in 0..2000 {
+            let before = Header {
+                strings: 0x1000,
+                symbols: 0x2000,
+                relocations: 0x3000,
+                flags: 0,
+            };
+            let mut after = before;
+            after.mutate(&mut mutator, None);
+
+            let delta = after.strings.wrapping_sub(before.strings);
+            if delta != 0
+                && after.symbols.wrapping_sub(before.symbols) == delta
+                && after.relocations.wrapping_sub(before.relocations) == delta
+            {
+                relocated += 1;
+            }
+
+            let mut values = [after.strings, after.symbols, after.relocations];
+            values.sort_unstable();
+            if values == [0x1000, 0x2000, 0x3000] && after.strings != 0x1000 {
+                swapped += 1;
+            }
+        }
+
+        assert!(relocated > 0);
+        assert!(swapped > 0);
+    }
+
+    #[test]
+    fn mutation_operators_have_stable_ids_and_are_recorded() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::operators::MutationOperator;
+        use lain::pipeline::MutationPipeline;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            id: u32,
+            #[lain(min = 1, max = 16)]
+            payload: Vec<u8>,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            _message: &Message,
+            _ctx: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            Outcome::Crash
+        }
+
+        assert_eq!(MutationOperator::DangerousNumber.id(), 1);
+        assert_eq!(MutationOperator::Plugin.id(), 16);
+        for operator in MutationOperator::ALL.iter() {
+            assert_eq!(MutationOperator::from_id(operator.id()), Some(*operator));
+            assert_eq!(
+                MutationOperator::from_name(operator.name()),
+                Some(*operator)
+            );
+        }
+
+        let mut mutator = get_mutator();
+        let mut value = 0u32;
+        for _i in 0..100 {
+            value.mutate(&mut mutator, None);
+        }
+
+        let applied = mutator.take_applied_operators();
+        let total: usize = mutator
+            .operator_counts()
+            .iter()
+            .map(|(_op, count)| count)
+            .sum();
+        assert!(!applied.is_empty());
+        assert_eq!(applied.len(), total);
+        assert!(mutator.take_applied_operators().is_empty());
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_to_reproduce_mode(0, 10);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        let recorded: usize = driver
+            .operator_counts()
+            .iter()
+            .map(|(_op, count)| count)
+            .sum();
+        assert!(recorded > 0);
+
+        let findings = driver.findings().findings();
+        let lineage: usize = findings.iter().map(|f| f.operators.len()).sum();
+        assert_eq!(findings.len(), 10);
+        assert_eq!(lineage, recorded);
+        assert!(driver.findings().to_json().contains("\"operators\":[\""));
+    }
+
+    #[test]
+    fn unconstrained_copy_vecs_are_generated_and_mutated_without_size_checks() {
+        use lain::rand::rngs::StdRng;
+        use lain::rand::SeedableRng;
+
+        let generate = |seed: u64| {
+            let mut mutator = Mutator::new(StdRng::seed_from_u64(seed));
+            Vec::<u32>::new_fuzzed(&mut mutator, None)
+        };
+
+        for seed in 0..32 {
+            let values = generate(seed);
+            assert!(values.len() <= 0x1000);
+            assert_eq!(values, generate(seed));
+        }
+
+        let mut mutator = get_mutator();
+        let mut values: Vec<u32> = (0..0x100).collect();
+        let original = values.clone();
+        values.as_mut_slice().mutate(&mut mutator, None);
+        assert_eq!(values.len(), original.len());
+        assert_ne!(values, original);
+
+        let mut constraints = Constraints::new();
+        constraints.max_size(0x40);
+        for _i in 0..32 {
+            let values = Vec::<u32>::new_fuzzed(&mut mutator, Some(&constraints));
+            assert!(values.serialized_size() <= 0x40);
+        }
+    }
+
+    #[test]
+    fn lists_grow_when_near_empty_and_shrink_when_near_full() {
+        use lain::operators::MutationOperator;
+
+        let resizes = |bias: f64, len: usize| {
+            let mut mutator = get_mutator();
+            mutator.set_resize_bias(bias);
+
+            let mut constraints = Constraints::new();
+            constraints.max_size(0x40);
+
+            for _i in 0..20000 {
+                let mut list = vec![0u32; len];
+                list.mutate(&mut mutator, Some(&constraints));
+            }
+
+            let count = |operator| {
+                mutator
+                    .operator_counts()
+                    .into_iter()
+                    .find(|(counted, _)| *counted == operator)
+                    .unwrap()
+                    .1
+            };
+
+            (
+                count(MutationOperator::GrowList),
+                count(MutationOperator::ShrinkList),
+            )
+        };
+
+        let (grown, shrunk) = resizes(1.0, 1);
+        assert!(grown > shrunk * 4);
+
+        let (grown, shrunk) = resizes(1.0, 15);
+        assert!(shrunk > grown * 4);
+
+        // without the bias, a list near its max size is resized either way as often
+        let (grown, shrunk) = resizes(0.0, 15);
+        assert!(grown * 2 > shrunk && shrunk * 2 > grown);
+
+        // and a list below its min size is always grown
+        let mut mutator = get_mutator();
+        let mut constraints = Constraints::new();
+        constraints.min_size(0x20);
+        constraints.max_size(0x40);
+        for _i in 0..2000 {
+            let mut list = vec![0u32; 4];
+            list.mutate(&mut mutator, Some(&constraints));
+        }
+        let shrunk = mutator
+            .operator_counts()
+            .into_iter()
+            .find(|(operator, _)| *operator == MutationOperator::ShrinkList)
+            .unwrap()
+            .1;
+        assert_eq!(shrunk, 0);
+    }
+
+    #[test]
+    fn slice_mutation_can_be_limited_to_a_window() {
+        use lain::operators::MutationOperator;
+
+        let mut mutator = get_mutator();
+        let original: Vec<u32> = (0..0x100).collect();
+        let mut windowed = 0;
+
+        for _i in 0..100 {
+            let mut values = original.clone();
+            values.as_mut_slice().mutate(&mut mutator, None);
+
+            if mutator
+                .take_applied_operators()
+                .contains(&MutationOperator::MutateWindow)
+            {
+                windowed += 1;
+                let changed: Vec<usize> = (0..values.len())
+                    .filter(|&i| values[i] != original[i])
+                    .collect();
+                if let (Some(first), Some(last)) = (changed.first(), changed.last()) {
+                    assert!(last - first < original.len() / 4);
+                }
+            }
+        }
+
+        assert!(windowed > 0);
+
+        let window = mutator.gen_mutation_window(4);
+        assert_eq!(window, 0..4);
+    }
+
+    #[test]
+    fn wire_format_matches_the_recorded_golden_file() {
+        use lain::compat::{WireCompat, WireCompatError};
+
+        WireCompat::canonical()
+            .verify_file(concat!(env!("CARGO_MANIFEST_DIR"), "/wire_compat.golden"))
+            .unwrap();
+
+        let golden = WireCompat::new().case("value", &1u16).to_golden();
+        let changed = WireCompat::new().case("value", &2u16);
+        match changed.check(&golden) {
+            Err(WireCompatError::Changed(mismatches)) => {
+                assert_eq!(mismatches.len(), 2);
+                assert_eq!(mismatches[0].name, "value/be");
+                assert_eq!(mismatches[0].expected, vec![0, 1]);
+                assert_eq!(mismatches[0].found, Some(vec![0, 2]));
+            }
+            other => panic!("expected the change to be reported, got {:?}", other),
+        }
+
+        assert!(changed
+            .allow_change("value", "widened for testing")
+            .check(&golden)
+            .is_ok());
+        assert!(WireCompat::new().check(&golden).is_err());
+        assert!(WireCompat::new().check("value/be 0001").is_err());
+    }
+
+    #[test]
+    fn enum_variants_are_budgeted_against_max_size() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        enum Record {
+            Small(u8),
+            Medium(u32, #[lain(max = 4)] Vec<u8>),
+            Large([u8; 32]),
+        }
+
+        impl Default for Record {
+            fn default() -> Self {
+                Record::Small(0)
+            }
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            id: u16,
+            record: Record,
+        }
+
+        let mut mutator = get_mutator();
+        let mut constraints = Constraints::new();
+        constraints.max_size(0x10);
+
+        for _i in 0..100 {
+            let record = Record::new_fuzzed(&mut mutator, Some(&constraints));
+            assert!(record.serialized_size() <= 0x10);
+            assert!(!matches!(record, Record::Large(_)));
+        }
+
+        let mut constraints = Constraints::new();
+        constraints.max_size(0x40);
+        let mut records: Vec<Record> = vec![];
+        for _i in 0..20 {
+            records = Vec::<Record>::new_fuzzed(&mut mutator, Some(&constraints));
+            assert!(records.serialized_size() <= 0x40);
+        }
+        assert!(!records.is_empty());
+
+        let mut constraints = Constraints::new();
+        constraints.max_size(0x10 + Record::max_default_object_size());
+        for _i in 0..100 {
+            let mut message = Message::new_fuzzed(&mut mutator, Some(&constraints));
+            message.mutate(&mut mutator, Some(&constraints));
+            assert!(message.serialized_size() <= 0x10 + Record::max_default_object_size());
+        }
+    }
+
+    #[test]
+    fn custom_impls_can_query_remaining_budget_and_depth() {
+        use lain::byteorder::ByteOrder;
+        use lain::rand::Rng;
+        use std::io::Write;
+
+        // fills whatever room it is given, like a trailing padding field would
+        #[derive(Debug, Clone, Default)]
+        struct Padding {
+            bytes: Vec<u8>,
+            depth: usize,
+        }
+
+        impl SerializedSize for Padding {
+            fn serialized_size(&self) -> usize {
+                self.bytes.len()
+            }
+
+            fn min_nonzero_elements_size() -> usize {
+                0
+            }
+        }
+
+        impl BinarySerialize for Padding {
+            fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+                self.bytes.binary_serialize::<_, E>(buffer)
+            }
+        }
+
+        impl NewFuzzed for Padding {
+            type RangeType = u8;
+
+            fn new_fuzzed<R: Rng>(
+                mutator: &mut Mutator<R>,
+                constraints: Option<&Constraints<Self::RangeType>>,
+            ) -> Self {
+                let len = constraints.and_then(|c| c.remaining::<Self>()).unwrap_or(8);
+
+                Padding {
+                    bytes: vec![0x41; len],
+                    depth: mutator.depth(),
+                }
+            }
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, BinarySerialize)]
+        struct Inner {
+            id: u32,
+            padding: Padding,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, BinarySerialize)]
+        struct Outer {
+            tag: u8,
+            inner: Inner,
+        }
+
+        let mut constraints = Constraints::<u8>::new();
+        constraints.max_size(0x20);
+        assert_eq!(constraints.remaining::<u32>(), Some(0x20));
+        assert_eq!(constraints.remaining_extra::<u32>(), Some(0x1C));
+        constraints.set_base_size_accounted_for();
+        assert_eq!(constraints.remaining::<u32>(), Some(0x24));
+
+        let mut mutator = get_mutator();
+        assert_eq!(Padding::new_fuzzed(&mut mutator, None).depth, 0);
+
+        let mut constraints = Constraints::new();
+        constraints.max_size(0x20);
+        let inner = Inner::new_fuzzed(&mut mutator, Some(&constraints));
+        assert_eq!(inner.padding.depth, 1);
+        assert!(!inner.padding.bytes.is_empty());
+        assert!(inner.serialized_size() <= 0x20);
+
+        let outer = Outer::new_fuzzed(&mut mutator, Some(&constraints));
+        assert_eq!(outer.inner.padding.depth, 2);
+        assert!(outer.serialized_size() <= 0x20);
+        assert_eq!(mutator.depth(), 0);
+    }
+
+    #[test]
+    fn concolic_suggestions_are_exchanged_through_files_and_run_first() {
+        use lain::concolic::{ConcolicExecutor, FileExchange};
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::pipeline::MutationPipeline;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            magic: u32,
+            length: u16,
+        }
+
+        fn fuzzer_routine(
+            bytes: &[u8],
+            _message: &Message,
+            _ctx: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            if bytes.starts_with(b"LAIN") {
+                Outcome::Crash
+            } else {
+                Outcome::Ok
+            }
+        }
+
+        let root = std::env::temp_dir().join(format!("lain_concolic_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let exchange = Arc::new(FileExchange::new(&root).unwrap());
+
+        let base = vec![0u8; 6];
+        let id = FileExchange::input_id(&base);
+        let layout = vec![lain::types::FieldSpan {
+            path: String::from("magic"),
+            start: 0,
+            end: 4,
+        }];
+        exchange.submit(&base, &layout);
+        let fields = std::fs::read_to_string(root.join(format!("inputs/{}.fields", id))).unwrap();
+        assert_eq!(fields, "0 4 magic\n");
+
+        std::fs::write(
+            exchange.suggestions_dir().join(format!("{}.txt", id)),
+            "# solved magic == \"LAIN\"\n0 4c41494e\n",
+        )
+        .unwrap();
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_concolic_executor(exchange.clone(), 2);
+        driver.set_to_reproduce_mode(0, 10);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        assert_eq!(driver.num_concolic_suggestions_run(), 1);
+        assert_eq!(driver.num_concolic_submissions(), 5);
+        assert_eq!(driver.num_crashes(), 1);
+        assert_eq!(driver.findings().findings()[0].iteration, Some(0));
+        assert_eq!(
+            std::fs::read_dir(exchange.suggestions_dir())
+                .unwrap()
+                .count(),
+            0
+        );
+        assert!(exchange.poll().is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn plain_old_data_structs_are_fuzzed_as_raw_bytes() {
+        use lain::byteorder::NativeEndian;
+        use lain::plain::{Plain, PlainOldData};
+
+        #[cfg(target_endian = "little")]
+        type NonNativeEndian = BigEndian;
+        #[cfg(target_endian = "big")]
+        type NonNativeEndian = LittleEndian;
+
+        #[repr(C, packed)]
+        #[derive(Copy, Clone)]
+        struct RawHeader {
+            magic: u32,
+            flags: u8,
+            length: u16,
+            reserved: [u8; 3],
+        }
+
+        unsafe impl PlainOldData for RawHeader {}
+
+        #[derive(NewFuzzed, Mutatable, BinarySerialize, Clone)]
+        struct Packet {
+            header: Plain<RawHeader>,
+            payload: Vec<u8>,
+        }
+
+        let mut mutator = get_mutator();
+
+        let header = Plain::<RawHeader>::new_fuzzed(&mut mutator, None);
+        assert_eq!(header.serialized_size(), std::mem::size_of::<RawHeader>());
+        assert_eq!(header.serialized_size(), 10);
+
+        let mut mutated = header;
+        let mut changed = false;
+        for _i in 0..16 {
+            mutated.mutate(&mut mutator, None);
+            changed |= mutated.as_bytes() != header.as_bytes();
+        }
+        assert!(changed, "mutation never changed the header's bytes");
+
+        let mut serialized = vec![];
+        mutated.binary_serialize::<_, NativeEndian>(&mut serialized);
+        assert_eq!(serialized, mutated.as_bytes());
+
+        let (deserialized, consumed) =
+            Plain::<RawHeader>::binary_deserialize::<NativeEndian>(&serialized).unwrap();
+        assert_eq!(consumed, serialized.len());
+        assert_eq!(deserialized.as_bytes(), mutated.as_bytes());
+        assert!(Plain::<RawHeader>::from_bytes(&serialized[1..]).is_none());
+
+        // the fields can't be swapped, so the host's byte order is used whichever is given
+        let mut non_native = vec![];
+        assert_eq!(
+            mutated.binary_serialize::<_, NonNativeEndian>(&mut non_native),
+            10
+        );
+        assert_eq!(non_native, serialized);
+        let (deserialized, _) =
+            Plain::<RawHeader>::binary_deserialize::<NonNativeEndian>(&non_native).unwrap();
+        assert_eq!(deserialized.as_bytes(), mutated.as_bytes());
+
+        #[derive(NewFuzzed, Mutatable, BinarySerialize, Clone)]
+        struct Words {
+            words: Vec<Plain<u32>>,
+        }
+
+        let words = Words {
+            words: vec![Plain::new(1), Plain::new(2)],
+        };
+        let mut layout = vec![];
+        words.field_layout("", 0, &mut layout);
+        assert_eq!((layout[0].start, layout[0].end), (0, 8));
+
+        let packet = Packet::new_fuzzed(&mut mutator, None);
+        let mut serialized = vec![];
+        packet.binary_serialize::<_, NativeEndian>(&mut serialized);
+        assert_eq!(&serialized[..10], packet.header.as_bytes());
+        assert_eq!(packet.serialized_size(), 10 + packet.payload.len());
+    }
+
+    #[test]
+    fn bytemuck_and_zerocopy_structs_are_fuzzed_as_raw_bytes() {
+        use lain::byteorder::NativeEndian;
+        use lain::plain::{Plain, ViaBytemuck, ViaZerocopy};
+
+        // as generated by bindgen, with the traits implemented by the bindings' crate
+        #[repr(C, packed)]
+        #[derive(Copy, Clone)]
+        struct PodHeader {
+            magic: u32,
+            flags: u8,
+            length: u16,
+        }
+
+        unsafe impl bytemuck::Zeroable for PodHeader {}
+        unsafe impl bytemuck::Pod for PodHeader {}
+
+        #[repr(C, packed)]
+        #[derive(Copy, Clone, zerocopy::AsBytes, zerocopy::FromBytes, zerocopy::FromZeroes)]
+        struct ZerocopyHeader {
+            magic: u32,
+            flags: u8,
+            length: u16,
+        }
+
+        #[derive(NewFuzzed, Mutatable, BinarySerialize, Clone)]
+        struct Packet {
+            pod: Plain<PodHeader, ViaBytemuck>,
+            zerocopy: Plain<ZerocopyHeader, ViaZerocopy>,
+            payload: Vec<u8>,
+        }
+
+        let mut mutator = get_mutator();
+        let mut packet = Packet::new_fuzzed(&mut mutator, None);
+        assert_eq!(packet.pod.serialized_size(), 7);
+        assert_eq!(packet.zerocopy.serialized_size(), 7);
+
+        let original = packet.clone();
+        let mut changed = false;
+        for _i in 0..64 {
+            packet.mutate(&mut mutator, None);
+            changed |= packet.pod.as_bytes() != original.pod.as_bytes()
+                || packet.zerocopy.as_bytes() != original.zerocopy.as_bytes();
+        }
+        assert!(changed, "mutation never changed the headers' bytes");
+
+        let mut serialized = vec![];
+        packet.binary_serialize::<_, NativeEndian>(&mut serialized);
+        assert_eq!(&serialized[..7], bytemuck::bytes_of(&*packet.pod));
+        assert_eq!(
+            &serialized[7..14],
+            zerocopy::AsBytes::as_bytes(&*packet.zerocopy)
+        );
+
+        let (pod, consumed) =
+            Plain::<PodHeader, ViaBytemuck>::binary_deserialize::<NativeEndian>(&serialized)
+                .unwrap();
+        assert_eq!(consumed, 7);
+        assert_eq!(pod.as_bytes(), packet.pod.as_bytes());
+    }
+
+    #[test]
+    fn corpus_entries_can_be_tagged_and_filtered() {
+        use lain::corpus::{ShardedCorpus, TagFilter, IMPORTED_TAG};
+        use lain::rand::rngs::StdRng;
+
+        let corpus = ShardedCorpus::<Vec<u8>>::new(2);
+        corpus.add_to_shard_tagged(0, vec![1], &["handshake"]);
+        corpus.add_to_shard_tagged(0, vec![2], &["bulk-data"]);
+        corpus.add_to_shard_tagged(0, vec![3], &["handshake", IMPORTED_TAG]);
+        corpus.add_to_shard(0, vec![4]);
+        // a duplicate found by another campaign contributes its tags to the existing entry
+        corpus.add_to_shard_tagged(1, vec![2], &["handshake"]);
+        corpus.merge();
+
+        assert_eq!(corpus.len(), 4);
+        assert_eq!(
+            corpus.tags(1).unwrap(),
+            vec![String::from("bulk-data"), String::from("handshake")]
+        );
+        assert!(corpus.tags(3).unwrap().is_empty());
+
+        let handshakes = TagFilter::new().require("handshake");
+        assert_eq!(corpus.filter(&handshakes), vec![0, 1, 2]);
+        let generated_handshakes = handshakes.clone().exclude(IMPORTED_TAG);
+        assert_eq!(corpus.filter(&generated_handshakes), vec![0, 1]);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _i in 0..50 {
+            let entry = corpus
+                .choose_filtered(&mut rng, &generated_handshakes)
+                .unwrap();
+            assert!(entry == vec![1] || entry == vec![2]);
+        }
+
+        // weighting towards bulk data picks it far more often without excluding the rest
+        let weighted = TagFilter::new().weight("bulk-data", 20.0);
+        let bulk_picks = (0..1000)
+            .filter(|_| corpus.choose_filtered(&mut rng, &weighted).unwrap() == vec![2])
+            .count();
+        assert!(bulk_picks > 700 && bulk_picks < 1000, "{}", bulk_picks);
+
+        assert!(corpus
+            .choose_filtered(&mut rng, &TagFilter::new().require("missing"))
+            .is_none());
+
+        assert!(corpus.tag(3, "bulk-data"));
+        assert!(!corpus.tag(4, "bulk-data"));
+        assert_eq!(
+            corpus.filter(&TagFilter::new().require("bulk-data")),
+            vec![1, 3]
+        );
+    }
+
+    #[test]
+    fn admission_validator_keeps_foreign_sessions_out_of_the_corpus() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::pipeline::MutationPipeline;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            session_id: u8,
+            body: u32,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            _message: &Message,
+            _ctx: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            Outcome::Interesting(String::from("new_path"))
+        }
+
+        let output_dir =
+            std::env::temp_dir().join(format!("lain_admission_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_output_dir(&output_dir);
+        driver.set_admission_validator(|message: &Message| {
+            if message.session_id < 0x80 {
+                Ok(())
+            } else {
+                Err(format!("session {} is not ours", message.session_id))
+            }
+        });
+        driver.set_to_reproduce_mode(0, 50);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        let admitted = driver.num_interesting_inputs();
+        let inadmissible = driver.num_inadmissible_inputs();
+        assert!(admitted > 0);
+        assert!(inadmissible > 0);
+        assert_eq!(admitted + inadmissible, driver.num_iterations());
+
+        for entry in std::fs::read_dir(output_dir.join("interesting/new_path")).unwrap() {
+            let bytes = std::fs::read(entry.unwrap().path()).unwrap();
+            assert!(bytes[0] < 0x80);
+        }
+
+        std::fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn ascii_numbers_are_mutated_as_numbers() {
+        use std::collections::HashSet;
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct ContentLength {
+            length: AsciiNumber<u16>,
+        }
+
+        let mut mutator = get_mutator();
+
+        let number = AsciiNumber::new(1234u16);
+        let mut serialized = vec![];
+        number.binary_serialize::<_, LittleEndian>(&mut serialized);
+        assert_eq!(serialized, b"1234");
+        assert_eq!(number.serialized_size(), 4);
+
+        let (parsed, consumed) =
+            AsciiNumber::<u16>::binary_deserialize::<BigEndian>(b"-0042\r\n").unwrap();
+        assert_eq!(consumed, 5);
+        assert_eq!(parsed.as_str(), "-0042");
+        assert_eq!(parsed.value(), None);
+        assert!(AsciiNumber::<u16>::binary_deserialize::<BigEndian>(b"\r\n").is_err());
+
+        let mut seen_negative = false;
+        let mut seen_leading_zeros = false;
+        let mut seen_out_of_range = false;
+        let mut seen_values = HashSet::new();
+        let mut message = ContentLength::new_fuzzed(&mut mutator, None);
+        for _i in 0..2000 {
+            message.mutate(&mut mutator, None);
+
+            let text = message.length.as_str();
+            let digits = text.trim_start_matches('-');
+            assert!(!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()));
+            assert!(text.len() <= AsciiNumber::<u16>::MAX_DIGITS + 1);
+
+            seen_negative |= text.starts_with('-');
+            seen_leading_zeros |= digits.len() > 1 && digits.starts_with('0');
+            seen_out_of_range |= digits.len() > 5 || text == "65536";
+            if let Some(value) = message.length.value() {
+                seen_values.insert(value);
+            }
+
+            let mut serialized = vec![];
+            message.binary_serialize::<_, BigEndian>(&mut serialized);
+            assert_eq!(serialized, text.as_bytes());
+        }
+
+        assert!(seen_negative && seen_leading_zeros && seen_out_of_range);
+        assert!(seen_values.len() > 100);
+        assert!(seen_values.contains(&0) && seen_values.contains(&u16::MAX));
+
+        let mut constraints = Constraints::new();
+        constraints.max_size(8);
+        let mut number = AsciiNumber::new(7u32);
+        for _i in 0..500 {
+            number.mutate(&mut mutator, Some(&constraints));
+            assert!(number.serialized_size() <= 12, "{}", number.as_str());
+        }
+    }
+
+    #[test]
+    fn flattened_fields_serialize_inline_without_padding() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        #[lain(serialized_size = 8)]
+        struct CommonHeader {
+            version: u8,
+            flags: u8,
+            length: u16,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Framed {
+            header: CommonHeader,
+            body: u16,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Flattened {
+            #[lain(flatten)]
+            header: CommonHeader,
+            body: u16,
+        }
+
+        let header = CommonHeader {
+            version: 1,
+            flags: 2,
+            length: 0x0304,
+        };
+        let framed = Framed {
+            header: header.clone(),
+            body: 0x0506,
+        };
+        let flattened = Flattened {
+            header,
+            body: 0x0506,
+        };
+
+        let mut framed_bytes = vec![];
+        framed.binary_serialize::<_, BigEndian>(&mut framed_bytes);
+        assert_eq!(framed_bytes, [1, 2, 3, 4, 0, 0, 0, 0, 5, 6]);
+
+        let mut flattened_bytes = vec![];
+        flattened.binary_serialize::<_, BigEndian>(&mut flattened_bytes);
+        assert_eq!(flattened_bytes, [1, 2, 3, 4, 5, 6]);
+        assert_eq!(flattened.serialized_size(), 6);
+
+        let mut layout = vec![];
+        flattened.field_layout("", 0, &mut layout);
+        let spans: Vec<(&str, usize, usize)> = layout
+            .iter()
+            .map(|span| (span.path.as_str(), span.start, span.end))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("version", 0, 1),
+                ("flags", 1, 2),
+                ("length", 2, 4),
+                ("body", 4, 6)
+            ]
+        );
+    }
+
+    #[test]
+    fn trailer_fields_are_computed_from_the_serialized_body() {
+        fn total_len(body: &[u8]) -> u16 {
+            // includes the length field itself and the checksum after it
+            (body.len() + 3) as u16
+        }
+
+        fn checksum(body: &[u8]) -> u8 {
+            body.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Record {
+            magic: u8,
+            data: Vec<u8>,
+            #[lain(trailer = "total_len")]
+            length: u16,
+            #[lain(trailer = "checksum")]
+            sum: u8,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Envelope {
+            version: u8,
+            record: Record,
+        }
+
+        let record = Record {
+            magic: 0x10,
+            data: vec![1, 2, 3],
+            length: 0xFFFF,
+            sum: 0xFF,
+        };
+        let mut bytes = vec![];
+        record.binary_serialize::<_, BigEndian>(&mut bytes);
+        assert_eq!(bytes, [0x10, 1, 2, 3, 0, 7, 0x10 + 1 + 2 + 3 + 7]);
+        assert_eq!(record.serialized_size(), bytes.len());
+
+        // the digest only covers the enclosing struct, not the parent it's nested in
+        let envelope = Envelope {
+            version: 0xAA,
+            record,
+        };
+        let mut bytes = vec![];
+        envelope.binary_serialize::<_, LittleEndian>(&mut bytes);
+        assert_eq!(bytes, [0xAA, 0x10, 1, 2, 3, 7, 0, 0x10 + 1 + 2 + 3 + 7]);
+
+        let mut mutator = get_mutator();
+        let mut record = Record::new_fuzzed(&mut mutator, None);
+        for _i in 0..100 {
+            record.mutate(&mut mutator, None);
+
+            let mut bytes = vec![];
+            record.binary_serialize::<_, BigEndian>(&mut bytes);
+            let (body, sum) = bytes.split_at(bytes.len() - 1);
+            assert_eq!(sum[0], checksum(body));
+            assert_eq!(
+                u16::from_be_bytes([body[body.len() - 2], body[body.len() - 1]]) as usize,
+                bytes.len()
+            );
+        }
+    }
+
+    #[test]
+    fn fields_are_generated_after_the_fields_they_depend_on() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Records {
+            #[lain(depends_on = "count", min = "*count as usize", max = "*count as usize")]
+            records: Vec<u8>,
+            #[lain(max = 16)]
+            count: u8,
+            #[lain(
+                depends_on = "count, records",
+                initializer = "*count as u32 + records.len() as u32"
+            )]
+            total: u32,
+        }
+
+        let mut mutator = get_mutator();
+
+        for _i in 0..100 {
+            let records = Records::new_fuzzed(&mut mutator, None);
+
+            assert_eq!(records.count as usize, records.records.len());
+            assert_eq!(records.count as u32 * 2, records.total);
+        }
+    }
+
+    #[test]
+    fn keywords_are_learned_from_interesting_inputs() {
+        use lain::dictionary::KeywordDictionary;
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::operators::MutationOperator;
+        use lain::pipeline::MutationPipeline;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Request {
+            #[lain(initializer = "b\"GET /index.html\".to_vec()")]
+            path: Vec<u8>,
+            #[lain(initializer = "0")]
+            terminator: u8,
+            body: u32,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            _request: &Request,
+            _ctx: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            Outcome::Interesting(String::from("new_path"))
+        }
+
+        let dictionary = KeywordDictionary::new();
+        dictionary.observe(b"\x00\x01abc\x02");
+        assert!(dictionary.is_empty());
+
+        let dictionary = Arc::new(dictionary);
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_keyword_dictionary(dictionary.clone());
+        driver.set_to_reproduce_mode(0, 20);
+
+        let pipeline = MutationPipeline::<Request>::new()
+            .serialize::<BigEndian>()
+            .keywords(1.0, dictionary.clone());
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(driver.clone(), Arc::new(pipeline), fuzzer_routine);
+        driver.join_threads();
+
+        assert!(dictionary.num_observed() > 0);
+        assert!(dictionary.keywords().contains(&b"GET /index.html".to_vec()));
+
+        let (_, keyword_count) = driver
+            .operator_counts()
+            .into_iter()
+            .find(|(operator, _)| *operator == MutationOperator::Keyword)
+            .unwrap();
+        assert!(keyword_count > 0);
+    }
+
+    #[test]
+    fn maps_are_fuzzed_entry_by_entry() {
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Config {
+            #[lain(max = 8)]
+            settings: HashMap<u8, u32>,
+            ordered: BTreeMap<u16, u8>,
+        }
+
+        let mut mutator = get_mutator();
+        let mut constraints = Constraints::new();
+        constraints.max_size(0x40);
+
+        let mut config = Config::new_fuzzed(&mut mutator, Some(&constraints));
+        assert!(config.serialized_size() <= 0x40);
+        assert!(config.settings.len() <= 8);
+
+        let mut sizes = std::collections::HashSet::new();
+        for _i in 0..2000 {
+            config.mutate(&mut mutator, Some(&constraints));
+            sizes.insert(config.ordered.len());
+        }
+        assert!(sizes.len() > 1);
+
+        let mut ordered = BTreeMap::new();
+        ordered.insert(2u16, 0xBBu8);
+        ordered.insert(1u16, 0xAAu8);
+
+        let mut bytes = vec![];
+        ordered.binary_serialize::<_, BigEndian>(&mut bytes);
+        assert_eq!(bytes, [0x00, 0x01, 0xAA, 0x00, 0x02, 0xBB]);
+
+        let (deserialized, consumed) =
+            BTreeMap::<u16, u8>::binary_deserialize::<BigEndian>(&bytes).unwrap();
+        assert_eq!(deserialized, ordered);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn hash_maps_give_the_same_bytes_for_the_same_seed() {
+        use std::collections::HashMap;
+
+        // every HashMap gets its own hasher keys, so the iteration order differs between runs
+        let fuzz = || {
+            let mut mutator = get_mutator();
+            let mut constraints = Constraints::new();
+            constraints.max_size(0x100);
+
+            let mut map = HashMap::<u8, u16>::new_fuzzed(&mut mutator, Some(&constraints));
+            let mut serialized = vec![];
+            for _i in 0..500 {
+                map.mutate(&mut mutator, Some(&constraints));
+
+                let mut bytes = vec![];
+                map.binary_serialize::<_, BigEndian>(&mut bytes);
+                serialized.push(bytes);
+            }
+
+            serialized
+        };
+
+        let serialized = fuzz();
+        assert!(serialized.iter().any(|bytes| bytes.len() > 6));
+        for _i in 0..4 {
+            assert_eq!(fuzz(), serialized);
+        }
+
+        let mut map = HashMap::new();
+        map.insert(0x0200u16, 0xBBu8);
+        map.insert(0x0100u16, 0xAAu8);
+        map.insert(0x0300u16, 0xCCu8);
+
+        let mut bytes = vec![];
+        map.binary_serialize::<_, LittleEndian>(&mut bytes);
+        assert_eq!(
+            bytes,
+            [0x00, 0x01, 0xAA, 0x00, 0x02, 0xBB, 0x00, 0x03, 0xCC]
+        );
+    }
+
+    #[test]
+    fn slow_iterations_are_recorded_as_slow_units() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::pipeline::MutationPipeline;
+        use lain::report::FindingKind;
+        use lain::slow_units::{SlowUnitDetector, SlowUnitThreshold};
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Request {
+            value: u32,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            _request: &Request,
+            iterations: &mut usize,
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            *iterations += 1;
+            if iterations.is_multiple_of(40) {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+
+            Outcome::Ok
+        }
+
+        let output_dir =
+            std::env::temp_dir().join(format!("lain_slow_units_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_output_dir(&output_dir);
+        driver.set_slow_unit_detector(
+            SlowUnitDetector::new(SlowUnitThreshold::MedianMultiple(10.0))
+                .min_samples(20)
+                .min_slow_time(std::time::Duration::from_millis(10)),
+        );
+        driver.set_to_reproduce_mode(0, 100);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        assert!(driver.num_slow_units() >= 2);
+        assert_eq!(driver.num_failed_iterations(), 0);
+
+        let findings = driver.findings().findings();
+        assert_eq!(findings.len(), driver.num_slow_units());
+        assert!(findings.iter().all(|f| f.kind == FindingKind::SlowUnit));
+        assert!(findings[0].message.contains("median"));
+
+        assert!(std::fs::read_dir(output_dir.join("slow")).unwrap().count() > 0);
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+
+    #[test]
+    fn optional_fields_toggle_presence_at_the_configured_rate() {
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Extension {
+            kind: u8,
+            length: u16,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            id: u32,
+            extension: Option<Extension>,
+            payload: Option<Vec<u8>>,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_option_some_chance(1.0);
+        mutator.set_option_toggle_chance(0.0);
+
+        let mut message = Message::new_fuzzed(&mut mutator, None);
+        assert!(message.extension.is_some());
+        assert!(message.payload.is_some());
+
+        let original = message.extension.clone().unwrap();
+        let mut changed = false;
+        for _i in 0..100 {
+            message.mutate(&mut mutator, None);
+            let extension = message.extension.as_ref().unwrap();
+            changed |= extension.kind != original.kind || extension.length != original.length;
+        }
+        assert!(changed);
+
+        mutator.set_option_toggle_chance(1.0);
+        let mut extension = message.extension.take();
+        extension.mutate(&mut mutator, None);
+        assert!(extension.is_none());
+        extension.mutate(&mut mutator, None);
+        assert!(extension.is_some());
+    }
+
+    #[test]
+    fn dictionary_tokens_are_spliced_into_byte_and_string_values() {
+        use lain::dictionary::Dictionary;
+        use lain::operators::MutationOperator;
+
+        let mut mutator = get_mutator();
+        mutator.set_dictionary(["MAGIC", "\u{e9}t\u{e9}"].iter().collect::<Dictionary>());
+        mutator.set_dictionary_chance(1.0);
+
+        let mut bytes = vec![0u8; 8];
+        bytes.mutate(&mut mutator, None);
+        let contains = |haystack: &[u8], needle: &[u8]| {
+            haystack
+                .windows(needle.len())
+                .any(|window| window == needle)
+        };
+        assert!(contains(&bytes, b"MAGIC") || contains(&bytes, "\u{e9}t\u{e9}".as_bytes()));
+
+        let mut ascii = AsciiString::new("abcdefgh");
+        let mut utf8 = Utf8String::new("abcdefgh");
+        for _i in 0..10 {
+            ascii.mutate(&mut mutator, None);
+            utf8.mutate(&mut mutator, None);
+        }
+
+        let spliced = mutator
+            .operator_counts()
+            .into_iter()
+            .find(|(operator, _)| *operator == MutationOperator::DictionaryToken)
+            .unwrap()
+            .1;
+        assert_eq!(spliced, 21);
+    }
+
+    #[test]
+    fn inputs_exceeding_the_memory_budget_are_recorded_as_ooms() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::memory;
+        use lain::pipeline::MutationPipeline;
+        use lain::report::FindingKind;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Request {
+            value: u32,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            _request: &Request,
+            iterations: &mut usize,
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            *iterations += 1;
+            // stand in for a child process whose peak RSS was measured
+            let peak = if iterations.is_multiple_of(10) {
+                0x100000
+            } else {
+                0x1000
+            };
+            memory::report_peak_usage(peak);
+
+            Outcome::Ok
+        }
+
+        let output_dir = std::env::temp_dir().join(format!("lain_ooms_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_output_dir(&output_dir);
+        driver.set_memory_budget(0x10000);
+        driver.set_to_reproduce_mode(0, 50);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        assert_eq!(driver.num_ooms(), 5);
+        assert_eq!(driver.num_failed_iterations(), 0);
+
+        let findings = driver.findings().findings();
+        assert_eq!(findings.len(), 5);
+        assert!(findings.iter().all(|f| f.kind == FindingKind::Oom));
+        assert!(findings.iter().all(|f| f.peak_memory == Some(0x100000)));
+
+        assert!(std::fs::read_dir(output_dir.join("ooms")).unwrap().count() > 0);
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+
+    #[test]
+    fn derived_structures_round_trip_through_binary_deserialize() {
+        #[derive(
+            Debug,
+            Copy,
+            Clone,
+            PartialEq,
+            NewFuzzed,
+            BinarySerialize,
+            BinaryDeserialize,
+            ToPrimitiveU8,
+        )]
+        #[repr(u8)]
+        enum Opcode {
+            Read = 1,
+            Write = 2,
+        }
+
+        #[derive(Debug, Clone, PartialEq, NewFuzzed, BinarySerialize, BinaryDeserialize)]
+        struct Header {
+            #[lain(bits = 4)]
+            version: u8,
+            #[lain(bits = 4)]
+            flags: u8,
+            opcode: Opcode,
+            #[lain(little_endian)]
+            length: u16,
+        }
+
+        #[derive(Debug, Clone, PartialEq, NewFuzzed, BinarySerialize, BinaryDeserialize)]
+        struct Packet {
+            header: Header,
+            id: u32,
+            payload: Vec<u8>,
+        }
+
+        let mut mutator = get_mutator();
+        for _i in 0..20 {
+            let packet = Packet::new_fuzzed(&mut mutator, None);
+            let mut bytes = vec![];
+            packet.binary_serialize::<_, BigEndian>(&mut bytes);
+
+            // bitfield members which don't fit their bits are truncated when serialized, so
+            // compare the serialized forms
+            let parsed = Packet::from_bytes::<BigEndian>(&bytes).unwrap();
+            let mut reserialized = vec![];
+            parsed.binary_serialize::<_, BigEndian>(&mut reserialized);
+            assert_eq!(reserialized, bytes);
+        }
+
+        let bytes = [0x21, 0x02, 0x10, 0x00, 0xAA, 0xBB, 0xCC, 0xDD, 0x01, 0x02];
+        let packet = Packet::from_bytes::<BigEndian>(&bytes).unwrap();
+        assert_eq!(packet.header.version, 1);
+        assert_eq!(packet.header.flags, 2);
+        assert_eq!(packet.header.opcode, Opcode::Write);
+        assert_eq!(packet.header.length, 0x10);
+        assert_eq!(packet.id, 0xAABBCCDD);
+        assert_eq!(packet.payload, vec![1, 2]);
+
+        // an invalid opcode is reported at its offset
+        let error = Packet::from_bytes::<BigEndian>(&[0x21, 0x03, 0x10, 0x00]).unwrap_err();
+        assert_eq!(error.offset, 1);
+    }
+
+    #[test]
+    fn coverage_map_exports_json_and_lcov() {
+        use lain::coverage::CoverageMap;
+
+        let coverage = CoverageMap::new();
+        assert_eq!(coverage.record_entry(b"first", &[1, 0, 2, 0]), 2);
+        assert_eq!(coverage.record_entry(b"second", &[1, 0, 0, 3]), 1);
+        assert_eq!(coverage.record(&[0, 0, 1, 0]), 0);
+
+        assert_eq!(coverage.num_edges(), 3);
+        assert_eq!(coverage.executions(), 3);
+        assert_eq!(coverage.hits(2), 3);
+
+        // the execution which hit no new edges isn't a point on the timeline
+        let timeline: Vec<(usize, usize)> = coverage
+            .timeline()
+            .iter()
+            .map(|point| (point.executions, point.edges))
+            .collect();
+        assert_eq!(timeline, vec![(1, 2), (2, 3)]);
+
+        let entries = coverage.entries();
+        assert_eq!(entries[1].edges, vec![0, 3]);
+        assert_eq!(entries[1].new_edges, 1);
+
+        let json = coverage.to_json();
+        assert!(json.contains("\"edges_covered\":3,\"executions\":3"));
+        assert!(json.contains("\"edges\":[[0,2],[2,3],[3,3]]"));
+
+        assert!(coverage.to_lcov().is_empty());
+        coverage.set_source_location(0, "src/parser.rs", 10);
+        coverage.set_source_location(2, "src/parser.rs", 10);
+        coverage.set_source_location(1, "src/parser.rs", 12);
+        assert_eq!(
+            coverage.to_lcov(),
+            "TN:\nSF:src/parser.rs\nDA:10,5\nDA:12,0\nLF:2\nLH:1\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn inputs_reaching_new_coverage_are_kept_in_the_feedback_corpus() {
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::feedback::{
+            report_coverage, CoverageSignal, EdgeFeedback, FeedbackProvider, NEW_COVERAGE_TAG,
+        };
+        use lain::pipeline::MutationPipeline;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            #[lain(min = 1, max = 16)]
+            payload: Vec<u8>,
+        }
+
+        fn fuzzer_routine(
+            _bytes: &[u8],
+            message: &Message,
+            _ctx: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            // each input hits a single edge picked by its first byte
+            let edge = message.payload.first().map_or(0, |b| u64::from(*b % 8));
+            report_coverage(CoverageSignal::Edges(vec![edge].into_iter().collect()));
+
+            Outcome::Ok
+        }
+
+        let bitmap = EdgeFeedback::new();
+        assert!(bitmap.observe(&CoverageSignal::Bitmap(vec![0, 3, 0, 1])));
+        assert!(!bitmap.observe(&CoverageSignal::Bitmap(vec![0, 1, 0, 0])));
+        assert!(bitmap.observe(&CoverageSignal::Edges(vec![2].into_iter().collect())));
+        assert_eq!(bitmap.num_edges(), 3);
+
+        let feedback = Arc::new(EdgeFeedback::new());
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_seed(0);
+        driver.set_feedback_provider(feedback.clone());
+        driver.set_to_reproduce_mode(0, 100);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        assert_eq!(driver.num_iterations(), 100);
+        assert!(feedback.num_edges() > 1);
+        assert_eq!(driver.num_new_coverage_inputs(), feedback.num_edges());
+        assert_eq!(driver.num_interesting_inputs(), feedback.num_edges());
+
+        let corpus = driver.feedback_corpus();
+        corpus.merge();
+        assert_eq!(corpus.len(), feedback.num_edges());
+        assert_eq!(NEW_COVERAGE_TAG, "coverage");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn campaigns_are_controlled_through_the_control_socket() {
+        use lain::control::{handle_command, start_control_socket, MutatorChance};
+        use lain::driver::{start_fuzzer, FuzzerDriver, Outcome};
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::{Arc, RwLock};
+
+        static DICTIONARY_CHANCE: AtomicU64 = AtomicU64::new(0);
+
+        fn fuzzer_routine<R: Rng>(
+            mutator: &mut Mutator<R>,
+            _ctx: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            DICTIONARY_CHANCE.store(mutator.dictionary_chance().to_bits(), Ordering::SeqCst);
+            Outcome::Ok
+        }
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_to_reproduce_mode(0, 5);
+        let driver = Arc::new(driver);
+
+        assert_eq!(handle_command(&driver, "pause"), "ok");
+        assert!(driver.is_paused());
+        assert_eq!(handle_command(&driver, " set dictionary 0.5 "), "ok");
+        assert_eq!(
+            driver.mutator_chances(),
+            vec![(MutatorChance::Dictionary, 0.5)]
+        );
+        assert!(handle_command(&driver, "set dictionary 2").starts_with("error"));
+        assert!(handle_command(&driver, "set splines 0.5").starts_with("error"));
+        assert!(handle_command(&driver, "pause now").starts_with("error"));
+        assert!(handle_command(&driver, "explode").starts_with("error"));
+        assert_eq!(handle_command(&driver, "flush"), "ok: 0 entries");
+
+        start_fuzzer(driver.clone(), fuzzer_routine);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(driver.num_iterations(), 0);
+
+        let path = std::env::temp_dir().join(format!("lain_control_{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let control = start_control_socket(driver.clone(), &path).unwrap();
+
+        let mut stream = UnixStream::connect(control.path()).unwrap();
+        stream.write_all(b"stats\nresume\n").unwrap();
+        let mut replies = BufReader::new(stream.try_clone().unwrap()).lines();
+        let stats = replies.next().unwrap().unwrap();
+        assert!(stats.starts_with("{\"paused\":true,\"iterations\":0,"));
+        assert_eq!(replies.next().unwrap().unwrap(), "ok");
+        drop(replies);
+        drop(stream);
+
+        driver.join_threads();
+        control.stop();
+        assert!(!path.exists());
+
+        assert_eq!(driver.num_iterations(), 5);
+        assert_eq!(
+            f64::from_bits(DICTIONARY_CHANCE.load(Ordering::SeqCst)),
+            0.5
+        );
+    }
+
+    #[test]
+    fn corpus_persists_deduplicated_entries_across_runs() {
+        use lain::corpus::Corpus;
+        use lain::rand::rngs::StdRng;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let mut corpus = Corpus::new();
+        assert!(corpus.add(b"GET / HTTP/1.1".to_vec()));
+        assert!(corpus.add_weighted(&b"POST / HTTP/1.1"[..], 3.0));
+        assert!(!corpus.add(b"GET / HTTP/1.1".to_vec()));
+        assert!(corpus.add(Vec::new()));
+        assert_eq!(corpus.len(), 3);
+        assert!(corpus.contains(b"POST / HTTP/1.1"));
+
+        // entries are named after the same hash the driver persists inputs under
+        let mut hasher = DefaultHasher::new();
+        b"GET / HTTP/1.1"[..].hash(&mut hasher);
+        assert_eq!(corpus.get(0).unwrap().hash, hasher.finish());
+
+        assert!(corpus.set_weight(2, 0.0));
+        assert!(!corpus.set_weight(3, 1.0));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut picks = [0usize; 2];
+        for _ in 0..1000 {
+            let picked = corpus.pick_weighted(&mut rng).unwrap();
+            assert!(!picked.is_empty());
+            picks[picked.starts_with(b"POST") as usize] += 1;
+        }
+        assert!(picks[1] > picks[0] * 2);
+
+        let dir = std::env::temp_dir().join(format!("lain_corpus_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(corpus.save(&dir).unwrap(), 3);
+        assert_eq!(corpus.save(&dir).unwrap(), 0);
+        std::fs::create_dir(dir.join("nested")).unwrap();
+
+        let mut restored = Corpus::new();
+        assert!(restored.add(b"GET / HTTP/1.1".to_vec()));
+        assert_eq!(restored.load(&dir).unwrap(), 2);
+        assert_eq!(restored.len(), 3);
+        assert!(restored.contains(b""));
+
+        assert!(Corpus::new().pick_weighted(&mut rng).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn target_contexts_are_rebuilt_by_the_factory_reset() {
+        use lain::driver::{start_fuzzer, FuzzerDriver, Outcome};
+        use lain::target_snapshot::FactoryReset;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, RwLock};
+
+        static BUILDS: AtomicUsize = AtomicUsize::new(0);
+        static MOST_PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Default)]
+        struct ParserContext {
+            built: bool,
+            processed: usize,
+        }
+
+        fn fuzzer_routine<R: Rng>(
+            _mutator: &mut Mutator<R>,
+            ctx: &mut ParserContext,
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Outcome {
+            assert!(ctx.built);
+            ctx.processed += 1;
+            MOST_PROCESSED.fetch_max(ctx.processed, Ordering::SeqCst);
+
+            Outcome::Ok
+        }
+
+        let mut driver = FuzzerDriver::<()>::new(1);
+        driver.set_target_snapshot(FactoryReset::new(3, || {
+            BUILDS.fetch_add(1, Ordering::SeqCst);
+            ParserContext {
+                built: true,
+                processed: 0,
+            }
+        }));
+        driver.set_to_reproduce_mode(0, 10);
+
+        let driver = Arc::new(driver);
+        start_fuzzer(driver.clone(), fuzzer_routine);
+        driver.join_threads();
+
+        assert_eq!(driver.num_iterations(), 10);
+        assert_eq!(driver.num_crashes(), 0);
+        // once when the thread starts, then after the 3rd, 6th, and 9th iterations
+        assert_eq!(BUILDS.load(Ordering::SeqCst), 4);
+        assert_eq!(MOST_PROCESSED.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn transient_fields_drive_generation_but_are_not_serialized() {
+        #[derive(
+            Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize, BinaryDeserialize,
+        )]
+        struct Payload {
+            kind: u8,
+            #[lain(transient)]
+            entropy_bits: u8,
+            #[lain(min = 4, max = 4)]
+            data: Vec<u8>,
+        }
+
+        impl Fixup for Payload {
+            fn fixup<R: lain::rand::Rng>(&mut self, _mutator: &mut Mutator<R>) {
+                // the intended entropy decides how many of the bits of each byte may vary
+                let mask = ((1u16 << self.entropy_bits.min(8)) - 1) as u8;
+                for byte in self.data.iter_mut() {
+                    *byte &= mask;
+                }
+            }
+        }
+
+        let mut mutator = get_mutator();
+        for _ in 0..100 {
+            let mut payload = Payload::new_fuzzed(&mut mutator, None);
+            payload.fixup(&mut mutator);
+            let mask = ((1u16 << payload.entropy_bits.min(8)) - 1) as u8;
+            assert!(payload.data.iter().all(|byte| byte & !mask == 0));
+
+            let mut bytes = vec![];
+            payload.binary_serialize::<_, BigEndian>(&mut bytes);
+            assert_eq!(bytes.len(), 5);
+            assert_eq!(payload.serialized_size(), 5);
+            assert_eq!(bytes[0], payload.kind);
+            assert_eq!(&bytes[1..], payload.data.as_slice());
+
+            let mut layout = vec![];
+            payload.field_layout("", 0, &mut layout);
+            assert!(!layout.iter().any(|span| span.path == "entropy_bits"));
+            assert_eq!(layout.last().map(|span| span.end), Some(5));
+
+            let parsed = Payload::from_bytes::<BigEndian>(&bytes).unwrap();
+            assert_eq!(parsed.entropy_bits, 0);
+            assert_eq!(parsed.data, payload.data);
+        }
+    }
+
+    #[test]
+    fn minimize_reduces_crashing_inputs() {
+        use lain::minimize::minimize;
+
+        #[derive(Debug, Clone, PartialEq, Minimize)]
+        enum Command {
+            Nop,
+            Reset,
+            Write {
+                address: u32,
+                data: Vec<u8>,
+            },
+            #[lain(ignore)]
+            Debug,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Minimize)]
+        struct Request {
+            id: u32,
+            flags: Option<u16>,
+            commands: Vec<Command>,
+        }
+
+        assert_eq!(Command::Nop.shrink().count(), 0);
+        assert_eq!(
+            Command::Debug.shrink().collect::<Vec<_>>(),
+            vec![Command::Nop, Command::Reset]
+        );
+
+        let request = Request {
+            id: 0xdead_beef,
+            flags: Some(7),
+            commands: vec![
+                Command::Reset,
+                Command::Write {
+                    address: 0x1000,
+                    data: vec![1, 2, 0x41, 3],
+                },
+                Command::Debug,
+                Command::Nop,
+            ],
+        };
+
+        // crashes whenever 0x41 is written above address 0x100
+        let crashes = |request: &Request| {
+            request.commands.iter().any(|command| match command {
+                Command::Write { address, data } => *address > 0x100 && data.contains(&0x41),
+                _ => false,
+            })
+        };
+
+        let minimized = minimize(request.clone(), usize::MAX, crashes);
+        assert_eq!(
+            minimized.input,
+            Request {
+                id: 0,
+                flags: None,
+                commands: vec![Command::Write {
+                    address: 0x101,
+                    data: vec![0x41],
+                }],
+            }
+        );
+        assert!(minimized.reductions > 0);
+        assert!(minimized.attempts >= minimized.reductions);
+
+        let limited = minimize(request, 5, crashes);
+        assert_eq!(limited.attempts, 5);
+    }
+
+    #[test]
+    fn invalid_discriminants_are_capped_per_campaign() {
+        use lain::types::UnsafeEnum;
+        use std::collections::HashSet;
+
+        #[derive(Debug, Default, Copy, Clone, PartialEq, FuzzerObject, ToPrimitiveU8)]
+        enum Opcode {
+            #[default]
+            #[lain(value = 1)]
+            Read,
+            #[lain(value = 2)]
+            Write,
+            #[lain(value = 10)]
+            Close,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_invalid_enum_chance(0.0);
+        for _i in 0..200 {
+            let value = UnsafeEnum::<Opcode, u8>::new_fuzzed(&mut mutator, None);
+            assert!(matches!(value, UnsafeEnum::Valid(_)));
+        }
+
+        mutator.set_invalid_enum_chance(0.5);
+        mutator.set_invalid_discriminant_cap(3, 1234);
+        assert_eq!(mutator.invalid_discriminant_cap(), Some(3));
+
+        let mut seen = HashSet::new();
+        let mut valid = 0;
+        for _i in 0..1000 {
+            match UnsafeEnum::<Opcode, u8>::new_fuzzed(&mut mutator, None) {
+                UnsafeEnum::Invalid(value) => {
+                    assert!(![1, 2, 10].contains(&value));
+                    seen.insert(value);
+                }
+                UnsafeEnum::Valid(_) => valid += 1,
+            }
+        }
+        assert_eq!(seen.len(), 3);
+        assert!(valid > 300 && valid < 700);
+
+        // mutation stays within the sampled discriminants
+        let mut value = UnsafeEnum::<Opcode, u8>::Valid(Opcode::Read);
+        for _i in 0..200 {
+            value.mutate(&mut mutator, None);
+            if let UnsafeEnum::Invalid(value) = value {
+                assert!(seen.contains(&value));
+            }
+        }
+
+        // another thread with the same seed explores the same discriminants
+        let mut other = Mutator::new(SmallRng::seed_from_u64(99));
+        other.set_invalid_discriminant_cap(3, 1234);
+        let sampled: HashSet<u8> = other
+            .capped_invalid_discriminants::<Opcode, u8>()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(sampled, seen);
+
+        mutator.clear_invalid_discriminant_cap();
+        assert!(mutator
+            .capped_invalid_discriminants::<Opcode, u8>()
+            .is_none());
+    }
+
+    #[test]
+    fn crossover_combines_fields_of_both_parents() {
+        use lain::operators::MutationOperator;
+
+        #[derive(Debug, Clone, PartialEq, Crossover)]
+        enum Body {
+            Empty,
+            Data { tag: u8, bytes: Vec<u8> },
+        }
+
+        #[derive(Debug, Clone, PartialEq, Crossover)]
+        struct Message {
+            id: u32,
+            length: usize,
+            body: Body,
+        }
+
+        impl Fixup for Message {
+            fn fixup<R: lain::rand::Rng>(&mut self, _mutator: &mut Mutator<R>) {
+                self.length = match self.body {
+                    Body::Data { ref bytes, .. } => bytes.len(),
+                    Body::Empty => 0,
+                };
+            }
+        }
+
+        let first = Message {
+            id: 1,
+            length: 4,
+            body: Body::Data {
+                tag: 0xAA,
+                bytes: vec![1, 1, 1, 1],
+            },
+        };
+        let second = Message {
+            id: 2,
+            length: 6,
+            body: Body::Data {
+                tag: 0xBB,
+                bytes: vec![2, 2, 2, 2, 2, 2],
+            },
+        };
+
+        let mut mutator = get_mutator();
+        let mut ids = std::collections::HashSet::new();
+        let mut tags = std::collections::HashSet::new();
+        let mut spliced = false;
+        for _i in 0..200 {
+            let child = mutator.crossover(&first, &second);
+            ids.insert(child.id);
+
+            match child.body {
+                Body::Data { tag, ref bytes } => {
+                    tags.insert(tag);
+                    assert_eq!(child.length, bytes.len());
+
+                    assert!(bytes.iter().all(|byte| *byte == 1 || *byte == 2));
+                    // lists are either crossed element by element or spliced at a random point
+                    spliced |= bytes.len() != 4 && bytes.len() != 6;
+                }
+                Body::Empty => panic!("neither parent was empty"),
+            }
+        }
+        assert_eq!(ids.len(), 2);
+        assert_eq!(tags.len(), 2);
+        assert!(spliced);
+
+        let count = mutator
+            .operator_counts()
+            .into_iter()
+            .find(|(operator, _)| *operator == MutationOperator::Crossover)
+            .unwrap()
+            .1;
+        assert_eq!(count, 200);
+
+        // parents of different variants pass on one or the other
+        let empty = Message {
+            id: 3,
+            length: 0,
+            body: Body::Empty,
+        };
+        for _i in 0..50 {
+            let child = mutator.crossover(&empty, &first);
+            assert!(child.body == Body::Empty || child.body == first.body);
+        }
+    }
+
+    #[test]
+    fn dictionary_replacements_swap_tokens_in_serialized_inputs() {
+        use lain::dictionary::Dictionary;
+        use lain::operators::MutationOperator;
+        use lain::pipeline::MutationPipeline;
+
+        let mut dictionary = Dictionary::new();
+        dictionary.add_replacement("v1.0", "v9.9");
+        dictionary.add_replacement("TLSv1.2", "SSLv3");
+        dictionary.add_replacement("TLSv1.2", "SSLv3");
+        dictionary.add_replacement("same", "same");
+        assert_eq!(dictionary.replacements().len(), 2);
+        assert!(dictionary.is_empty());
+
+        let mut mutator = get_mutator();
+        mutator.set_dictionary(dictionary);
+
+        let mut bytes = b"HELLO".to_vec();
+        assert!(!mutator.replace_dictionary_token(&mut bytes));
+        assert_eq!(bytes, b"HELLO");
+
+        let mut seen = std::collections::HashSet::new();
+        for _i in 0..50 {
+            let mut bytes = b"HELLO v1.0 TLSv1.2".to_vec();
+            assert!(mutator.replace_dictionary_token(&mut bytes));
+            seen.insert(String::from_utf8(bytes).unwrap());
+        }
+        let expected: std::collections::HashSet<String> =
+            ["HELLO v9.9 TLSv1.2", "HELLO v1.0 SSLv3"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        assert_eq!(seen, expected);
+
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Greeting {
+            version: [u8; 4],
+        }
+
+        let pipeline = MutationPipeline::<Greeting, SmallRng>::new()
+            .fixup(1.0)
+            .serialize::<BigEndian>()
+            .replace_tokens(1.0);
+        let mut greeting = Greeting { version: *b"v1.0" };
+        assert_eq!(pipeline.run(&mut mutator, &mut greeting), b"v9.9");
+
+        let replaced = mutator
+            .operator_counts()
+            .into_iter()
+            .find(|(operator, _)| *operator == MutationOperator::TokenReplace)
+            .unwrap()
+            .1;
+        assert_eq!(replaced, 51);
+    }
+
+    #[test]
+    fn derived_mutatable_uses_optional_traits_when_implemented() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize, Crossover)]
+        struct Checked {
+            value: u8,
+            #[lain(ignore)]
+            checksum: u8,
+        }
+
+        impl Fixup for Checked {
+            fn fixup<R: lain::rand::Rng>(&mut self, _mutator: &mut Mutator<R>) {
+                self.checksum = !self.value;
+            }
+        }
+
+        #[derive(Debug, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Plain {
+            value: u8,
+        }
+
+        let mut mutator = get_mutator();
+
+        let mut checked = Checked {
+            value: 0x0F,
+            checksum: 0,
+        };
+        checked.fixup_dependents(&mut mutator);
+        assert_eq!(checked.checksum, 0xF0);
+
+        let other = Checked {
+            value: 0xFF,
+            checksum: 0,
+        };
+        let child = checked.crossover_seed(&other, &mut mutator).unwrap();
+        assert_eq!(child.checksum, !child.value);
+        assert_eq!(checked.clone_seed().unwrap().value, 0x0F);
+
+        // without Fixup, Clone, or Crossover implementations these fall back to doing nothing
+        let mut plain = Plain { value: 1 };
+        plain.fixup_dependents(&mut mutator);
+        assert_eq!(plain.value, 1);
+        assert!(plain.clone_seed().is_none());
+        let other = Plain { value: 2 };
+        assert!(plain.crossover_seed(&other, &mut mutator).is_none());
+    }
+
+    #[test]
+    fn duplicate_inputs_are_skipped() {
+        use lain::dedup::DuplicateCache;
+        use lain::driver::{start_pipeline_fuzzer, FuzzerDriver, Outcome};
+        use lain::pipeline::MutationPipeline;
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Request {
+            flag: bool,
+        }
+
+        fn fuzzer_routine(
+            bytes: &[u8],
+            _request: &Request,
+            executed: &mut Vec<Vec<u8>>,
+            global_ctx: Option<Arc<RwLock<usize>>>,
+        ) -> Outcome {
+            assert!(!executed.iter().any(|previous| previous == bytes));
+            executed.push(bytes.to_vec());
+            *global_ctx.unwrap().write().unwrap() += 1;
+
+            Outcome::Ok
+        }
+
+        let mut driver = FuzzerDriver::<usize>::new(1);
+        driver.set_global_context(Arc::new(RwLock::new(0)));
+        driver.set_duplicate_cache(DuplicateCache::default());
+        driver.set_to_reproduce_mode(0, 100);
+
+        let driver = Arc::new(driver);
+        start_pipeline_fuzzer(
+            driver.clone(),
+            Arc::new(MutationPipeline::default()),
+            fuzzer_routine,
+        );
+        driver.join_threads();
+
+        let executions = *driver.global_context().unwrap().read().unwrap();
+        assert!(executions <= 2);
+        assert_eq!(driver.num_failed_iterations(), 0);
+        assert_eq!(
+            executions + driver.num_duplicate_inputs(),
+            driver.num_iterations()
+        );
+        assert_eq!(driver.duplicate_cache().unwrap().len(), executions);
+    }
+
+    #[test]
+    fn enum_variant_weights_apply_to_generation_and_mutation() {
+        use lain::operators::MutationOperator;
+
+        #[derive(
+            Debug, Clone, Copy, PartialEq, NewFuzzed, Mutatable, BinarySerialize, ToPrimitiveU8,
+        )]
+        #[repr(u8)]
+        enum Mode {
+            Idle = 0,
+            #[lain(weight = 0)]
+            Reserved = 1,
+            #[lain(ignore)]
+            Invalid = 2,
+            #[lain(weight = 4)]
+            Active = 3,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        enum Header {
+            Plain(u8),
+            #[lain(weight = 4)]
+            Malformed(u32),
+        }
+
+        let mut mutator = get_mutator();
+
+        let mut generated = [0usize; 2];
+        for _ in 0..2000 {
+            match Header::new_fuzzed(&mut mutator, None) {
+                Header::Plain(_) => generated[0] += 1,
+                Header::Malformed(_) => generated[1] += 1,
+            }
+        }
+        assert!(generated[1] > generated[0] * 2);
+
+        // regenerating a header picks its new variant by weight too
+        let mut switched = [0usize; 2];
+        for _ in 0..2000 {
+            let mut header = Header::Plain(0);
+            header.mutate(&mut mutator, None);
+            let regenerated = mutator
+                .take_applied_operators()
+                .contains(&MutationOperator::Regenerate);
+            match header {
+                Header::Plain(_) if regenerated => switched[0] += 1,
+                Header::Malformed(_) => switched[1] += 1,
+                _ => (),
+            }
+        }
+        assert!(switched[1] > switched[0] * 2);
+
+        let mut mutated = [0usize; 4];
+        let mut mode = Mode::Idle;
+        for _ in 0..2000 {
+            mode.mutate(&mut mutator, None);
+            mutated[mode as usize] += 1;
+        }
+        assert_eq!(mutated[Mode::Reserved as usize], 0);
+        assert_eq!(mutated[Mode::Invalid as usize], 0);
+        assert!(mutated[Mode::Active as usize] > mutated[Mode::Idle as usize] * 2);
+    }
+
+    #[test]
+    fn variant_switch_chance_controls_enum_regeneration() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        enum Message {
+            Hello(u8),
+            Data(u32),
+            Goodbye(u16),
+        }
+
+        let mut mutator = get_mutator();
+
+        mutator.set_variant_switch_chance(0.0);
+        for _ in 0..200 {
+            let mut message = Message::Hello(0);
+            message.mutate(&mut mutator, None);
+            assert!(matches!(message, Message::Hello(_)));
+        }
+
+        mutator.set_variant_switch_chance(1.0);
+        let mut switched = 0;
+        for _ in 0..200 {
+            let mut message = Message::Hello(0);
+            message.mutate(&mut mutator, None);
+            if !matches!(message, Message::Hello(_)) {
+                switched += 1;
+            }
+        }
+        assert!(switched > 100);
+    }
+
+    #[test]
+    fn memoized_fields_are_generated_once() {
+        #[derive(Debug, Clone, PartialEq, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Chain {
+            certificates: [u32; 4],
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Handshake {
+            version: u16,
+            #[lain(memoize)]
+            chain: Chain,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_memoized_regeneration_chance(0.0);
+
+        let mut handshake = Handshake::new_fuzzed(&mut mutator, None);
+        let chain = handshake.chain.clone();
+        for _ in 0..100 {
+            assert_eq!(Handshake::new_fuzzed(&mut mutator, None).chain, chain);
+
+            handshake.mutate(&mut mutator, None);
+            assert_eq!(handshake.chain, chain);
+        }
+
+        mutator.set_memoized_regeneration_chance(1.0);
+        handshake.mutate(&mut mutator, None);
+        let regenerated = handshake.chain.clone();
+        assert_ne!(regenerated, chain);
+
+        // the regenerated value replaces the one copied into new values
+        mutator.set_memoized_regeneration_chance(0.0);
+        assert_eq!(Handshake::new_fuzzed(&mut mutator, None).chain, regenerated);
+    }
+
+    #[test]
+    fn length_fields_hold_the_size_of_their_field() {
+        use lain::byteorder::ByteOrder;
+        use lain::operators::MutationOperator;
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Record {
+            #[lain(length_of = "payload")]
+            length: u16,
+            #[lain(max = 64)]
+            payload: Vec<u32>,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_length_corruption_chance(0.0);
+
+        let mut record = Record::new_fuzzed(&mut mutator, None);
+        assert_eq!(record.length as usize, record.payload.len() * 4);
+
+        for _ in 0..100 {
+            mutator.random_flags();
+            record.mutate(&mut mutator, None);
+            assert_eq!(record.length as usize, record.payload.len() * 4);
+
+            let mut bytes = vec![];
+            record.binary_serialize::<_, BigEndian>(&mut bytes);
+            assert_eq!(BigEndian::read_u16(&bytes) as usize, bytes.len() - 2);
+        }
+
+        mutator.set_length_corruption_chance(1.0);
+        for _ in 0..20 {
+            record.fixup_dependents(&mut mutator);
+            assert_ne!(record.length as usize, record.payload.len() * 4);
+        }
+        assert!(mutator
+            .operator_counts()
+            .iter()
+            .any(|(operator, count)| *operator == MutationOperator::CorruptLength && *count > 0));
+    }
+
+    #[test]
+    fn byte_order_confusion_is_blamed_on_fields() {
+        use lain::byteorder::{ByteOrder, LittleEndian};
+        use lain::differential::{blame_byte_order, DualEndian};
+        use std::io::Write;
+
+        #[derive(Debug, Clone)]
+        struct ByteOrderMark;
+
+        impl SerializedSize for ByteOrderMark {
+            fn serialized_size(&self) -> usize {
+                2
+            }
+
+            fn min_nonzero_elements_size() -> usize {
+                2
+            }
+        }
+
+        impl BinarySerialize for ByteOrderMark {
+            fn binary_serialize<W: Write, E: ByteOrder>(&self, buffer: &mut W) -> usize {
+                let little_endian = E::read_u16(&[1, 0]) == 1;
+                buffer
+                    .write(if little_endian { b"II" } else { b"MM" })
+                    .unwrap()
+            }
+        }
+
+        #[derive(Debug, Clone, BinarySerialize)]
+        struct Header {
+            bom: ByteOrderMark,
+            width: u16,
+            height: u32,
+        }
+
+        let header = Header {
+            bom: ByteOrderMark,
+            width: 0x0102,
+            height: 0x0304_0506,
+        };
+
+        let dual = DualEndian::serialize(&header);
+        assert_eq!(dual.little_endian, b"II\x02\x01\x06\x05\x04\x03");
+        assert_eq!(dual.big_endian, b"MM\x01\x02\x03\x04\x05\x06");
+
+        // detects the byte order, but always reads the height as little endian
+        let parse = |input: &[u8]| {
+            let width = if &input[..2] == b"II" {
+                LittleEndian::read_u16(&input[2..4])
+            } else {
+                BigEndian::read_u16(&input[2..4])
+            };
+            (width, LittleEndian::read_u32(&input[4..8]))
+        };
+
+        let disagreement = blame_byte_order(&header, parse).expect("outputs should differ");
+        assert_eq!(disagreement.fields.len(), 1);
+        assert_eq!(disagreement.fields[0].path, "height");
+
+        let correct = |input: &[u8]| {
+            if &input[..2] == b"II" {
+                LittleEndian::read_u32(&input[4..8])
+            } else {
+                BigEndian::read_u32(&input[4..8])
+            }
+        };
+        assert!(blame_byte_order(&header, correct).is_none());
+    }
+
+    #[test]
+    fn pinned_fields_hold_their_value() {
+        use std::collections::HashSet;
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Header {
+            version: u8,
+            flags: u16,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        enum Body {
+            Ping(u32),
+            Data(u16, u64),
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            header: Header,
+            body: Body,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.pin_field("header.version", 3u8);
+        mutator.pin_field("body.Data.1", 0x41u64);
+        // a value of the wrong type never matches
+        mutator.pin_field("header.flags", 1u8);
+
+        let mut message = Message::new_fuzzed(&mut mutator, None);
+        let mut saw_data = false;
+        let mut flags = HashSet::new();
+        for _ in 0..200 {
+            assert_eq!(message.header.version, 3);
+            if let Body::Data(_, value) = message.body {
+                assert_eq!(value, 0x41);
+                saw_data = true;
+            }
+            flags.insert(message.header.flags);
+
+            message.mutate(&mut mutator, None);
+        }
+
+        assert!(saw_data);
+        assert!(flags.len() > 1);
+
+        mutator.unpin_field("header.version");
+        let versions: HashSet<u8> = (0..100)
+            .map(|_| Message::new_fuzzed(&mut mutator, None).header.version)
+            .collect();
+        assert!(versions.len() > 1);
+    }
+
+    #[test]
+    fn checksums_are_computed_during_serialization() {
+        use lain::byteorder::{BigEndian, ByteOrder};
+        use lain::checksum::*;
+        use lain::operators::MutationOperator;
+
+        assert_eq!(Crc16::checksum(b"123456789"), 0x29B1);
+        assert_eq!(Crc32::checksum(b"123456789"), 0xCBF4_3926);
+        assert_eq!(Adler32::checksum(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(Fletcher16::checksum(b"abcde"), 0xC8F0);
+        assert_eq!(Fletcher32::checksum(b"abcde"), 0xF04F_C729);
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            kind: u8,
+            payload: Vec<u8>,
+            #[lain(checksum_from = "payload")]
+            crc: Checksum<u32, Crc32>,
+            #[lain(checksum)]
+            sum: Checksum<u16, Fletcher16>,
+        }
+
+        let message = Message {
+            kind: 7,
+            payload: b"123456789".to_vec(),
+            crc: Checksum::new(),
+            sum: Checksum::new(),
+        };
+
+        let mut expected = vec![7u8];
+        expected.extend_from_slice(b"123456789");
+        expected.extend_from_slice(&[0xCB, 0xF4, 0x39, 0x26]);
+        let sum = Fletcher16::checksum(&expected) as u16;
+        expected.extend_from_slice(&sum.to_be_bytes());
+
+        let mut serialized = vec![];
+        message.binary_serialize::<_, BigEndian>(&mut serialized);
+        assert_eq!(serialized, expected);
+
+        let mut mutator = get_mutator();
+        mutator.set_bad_checksum_chance(1.0);
+        let mut message = Message::new_fuzzed(&mut mutator, None);
+        assert!(message.crc.is_bad() && message.sum.is_bad());
+
+        let mut serialized = vec![];
+        message.binary_serialize::<_, BigEndian>(&mut serialized);
+        let crc_offset = 1 + message.payload.len();
+        let crc = BigEndian::read_u32(&serialized[crc_offset..]);
+        assert_ne!(crc as u64, Crc32::checksum(&serialized[1..crc_offset]));
+        assert!(mutator
+            .operator_counts()
+            .iter()
+            .any(|(operator, count)| *operator == MutationOperator::CorruptChecksum && *count > 0));
+
+        mutator.set_bad_checksum_chance(0.0);
+        message.mutate(&mut mutator, None);
+        assert!(!message.crc.is_bad());
+    }
+
+    #[test]
+    fn vecs_and_blobs_respect_minimum_and_exact_sizes() {
+        let mut mutator = get_mutator();
+
+        let mut exact = Constraints::new();
+        exact.exact_size(12);
+
+        let mut minimum = Constraints::new();
+        minimum.min_size(40);
+        minimum.max_size(64);
+
+        for _i in 0..1000 {
+            let bytes = Vec::<u8>::new_fuzzed(&mut mutator, Some(&exact));
+            assert_eq!(bytes.len(), 12);
+
+            let mut bytes = Vec::<u8>::new_fuzzed(&mut mutator, Some(&minimum));
+            assert!(bytes.len() >= 40 && bytes.len() <= 64);
+
+            for _j in 0..10 {
+                bytes.mutate(&mut mutator, Some(&minimum));
+                assert!(bytes.len() >= 40 && bytes.len() <= 64, "{}", bytes.len());
+            }
+
+            let blob = Blob::new_fuzzed(&mut mutator, Some(&exact));
+            assert_eq!(blob.as_bytes().len(), 12);
+
+            let blob = Blob::new_fuzzed(&mut mutator, Some(&minimum));
+            assert!(blob.as_bytes().len() >= 40 && blob.as_bytes().len() <= 64);
+        }
+    }
+
+    #[test]
+    fn payloads_are_framed_for_delivery() {
+        use lain::framing::{FramedStream, LengthPrefixed, WebSocket};
+
+        let mut mutator = get_mutator();
+        let payload: Vec<u8> = (0..10).collect();
+
+        let framing = LengthPrefixed::new(2).max_frame_size(4).corrupt_chance(0.0);
+        let mut stream = FramedStream::new(Vec::new(), framing);
+        assert_eq!(stream.send(&payload, &mut mutator).unwrap(), 16);
+        assert_eq!(
+            stream.into_inner(),
+            [0, 4, 0, 1, 2, 3, 0, 4, 4, 5, 6, 7, 0, 2, 8, 9]
+        );
+
+        let framing = WebSocket::client().max_fragment_size(6).corrupt_chance(0.0);
+        let mut stream = FramedStream::new(Vec::new(), framing);
+        stream.send(&payload, &mut mutator).unwrap();
+
+        let frames = stream.into_inner();
+        let mut unmasked = Vec::new();
+        let mut offset = 0;
+        for (fin, opcode, length) in [(0x00, 0x2, 6), (0x80, 0x0, 4)].iter() {
+            assert_eq!(frames[offset], fin | opcode);
+            assert_eq!(frames[offset + 1], 0x80 | length);
+
+            let key = &frames[offset + 2..offset + 6];
+            let body = &frames[offset + 6..offset + 6 + *length as usize];
+            unmasked.extend(body.iter().enumerate().map(|(i, b)| b ^ key[i % 4]));
+            offset += 6 + *length as usize;
+        }
+
+        assert_eq!(offset, frames.len());
+        assert_eq!(unmasked, payload);
+    }
+
+    #[test]
+    fn entropy_sources_decide_reproducibility() {
+        fn generate<R: lain::rand::Rng>(mutator: &mut Mutator<R>) -> Vec<u64> {
+            (0..16).map(|_| u64::new_fuzzed(mutator, None)).collect()
+        }
+
+        assert_eq!(
+            generate(&mut Mutator::fast(1234)),
+            generate(&mut Mutator::fast(1234))
+        );
+
+        let secure = generate(&mut Mutator::secure());
+        assert_ne!(secure, generate(&mut Mutator::secure()));
+        assert_ne!(generate(&mut Mutator::os()), secure);
+
+        // findings of a campaign drawing from the OS can't be replayed, so they carry no seed
+        use lain::driver::{start_fuzzer, FuzzerDriver};
+        use lain::mutator::EntropySource;
+        use std::sync::{Arc, RwLock};
+
+        fn crashing_routine<R: lain::rand::Rng>(
+            _mutator: &mut Mutator<R>,
+            _ctx: &mut (),
+            _global_ctx: Option<Arc<RwLock<()>>>,
+        ) -> Result<(), ()> {
+            Err(())
+        }
+
+        for source in [EntropySource::Seeded, EntropySource::Os].iter() {
+            let mut driver = FuzzerDriver::<()>::new(1);
+            driver.set_entropy_source(*source);
+            driver.set_to_reproduce_mode(0, 2);
+
+            let driver = Arc::new(driver);
+            start_fuzzer(driver.clone(), crashing_routine);
+            driver.join_threads();
+
+            let reproducible = *source == EntropySource::Seeded;
+            let findings = driver.findings().findings();
+            assert_eq!(findings.len(), 2);
+            assert!(findings.iter().all(|f| f.reproducible == reproducible));
+
+            let json = driver.findings().to_json();
+            assert_eq!(json.contains("\"seed\":null"), !reproducible);
+            assert_eq!(json.contains("\"reproducible\":false"), !reproducible);
+        }
+    }
+
+    #[test]
+    fn schemas_describe_formats_at_runtime() {
+        use lain::byteorder::ByteOrder;
+        use lain::schema::{Schema, SchemaError, Value};
+
+        let schema = Schema::from_json(
+            r#"{
+                "endian": "be",
+                "seq": [
+                    { "id": "magic", "type": "u32", "contents": 3405691582 },
+                    { "id": "kind", "type": "u8", "enum": { "ping": 1, "pong": 2 } },
+                    { "id": "len", "type": "u16" },
+                    { "id": "payload", "type": "bytes", "size": "len", "max_size": 16 },
+                    { "id": "count", "type": "u8" },
+                    { "id": "items", "type": "item", "repeat": "count", "max_repeat": 3 }
+                ],
+                "types": {
+                    "item": { "seq": [
+                        { "id": "tag", "type": "str", "size": 4 },
+                        { "id": "value", "type": "i16", "endian": "le" }
+                    ] }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mut mutator = get_mutator();
+        mutator.set_length_corruption_chance(0.0);
+
+        let mut message = schema.new_fuzzed(&mut mutator);
+        for _i in 0..200 {
+            schema.mutate(&mut message, &mut mutator);
+
+            let payload_len = message.field("payload").unwrap().as_bytes().unwrap().len();
+            let items = message.field("items").unwrap().as_list().unwrap();
+            assert_eq!(
+                message.field("len").unwrap().as_int(),
+                Some(payload_len as i128)
+            );
+            assert_eq!(
+                message.field("count").unwrap().as_int(),
+                Some(items.len() as i128)
+            );
+            assert!(payload_len <= 16 && items.len() <= 3);
+
+            let bytes = schema.serialize(&message);
+            assert_eq!(bytes.len(), 4 + 1 + 2 + payload_len + 1 + items.len() * 6);
+            assert_eq!(&bytes[..4], &[0xCA, 0xFE, 0xBA, 0xBE]);
+            assert_eq!(BigEndian::read_u16(&bytes[5..7]) as usize, payload_len);
+
+            for item in items {
+                assert!(item.field("tag").unwrap().as_bytes().unwrap().len() == 4);
+            }
+        }
+
+        let tagged = Value::Struct(vec![(String::from("tag"), Value::Bytes(b"abcd".to_vec()))]);
+        assert_eq!(tagged.field("tag").unwrap().as_bytes(), Some(&b"abcd"[..]));
+
+        let error =
+            Schema::from_json(r#"{ "seq": [{ "id": "data", "type": "bytes", "size": "len" }] }"#);
+        match error {
+            Err(SchemaError::Invalid { path, .. }) => assert_eq!(path, "seq[0].size"),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        assert!(matches!(
+            Schema::from_json(r#"{ "seq": [ }"#),
+            Err(SchemaError::Json { .. })
+        ));
+        assert!(Schema::from_json(r#"{ "seq": [{ "id": "a", "type": "loop" }], "types": { "loop": { "seq": [{ "id": "b", "type": "loop" }] } } }"#).is_err());
+    }
+
+    #[test]
+    fn havoc_stacking_applies_more_mutations() {
+        #[derive(Debug, Default, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Packet {
+            a: u32,
+            b: u32,
+            c: u32,
+            d: u32,
+        }
+
+        fn count_operators(mutator: &mut Mutator<SmallRng>) -> usize {
+            let mut packet = Packet::default();
+            let mut count = 0;
+            for _i in 0..200 {
+                mutator.random_flags();
+                mutator.mutate_stacked(&mut packet);
+                count += mutator.take_applied_operators().len();
+            }
+
+            count
+        }
+
+        let mut mutator = get_mutator();
+        assert_eq!(mutator.havoc_stacking(), 1..=1);
+        let single = count_operators(&mut mutator);
+
+        mutator.set_havoc_stacking(0..=0);
+        assert_eq!(mutator.havoc_stacking(), 1..=1);
+
+        mutator.set_havoc_stacking(8..=16);
+        let stacked = count_operators(&mut mutator);
+
+        assert!(stacked > single * 4);
+    }
+
+    #[test]
+    fn integers_are_replaced_with_interesting_values() {
+        use lain::interesting::InterestingValue;
+
+        let table = u16::interesting_values();
+        for value in [
+            0, 1, 0xFFFF, 0xFFFE, 0x7FFF, 0x8000, 0x8001, 0x00FF, 0x0100, 0x0101,
+        ]
+        .iter()
+        {
+            assert!(table.contains(value), "{:#x} is missing", value);
+        }
+        assert!(i8::interesting_values().contains(&-128));
+        assert!(i32::interesting_values().contains(&-1));
+
+        let mut mutator = get_mutator();
+        mutator.set_interesting_value_chance(1.0);
+        mutator.add_interesting_value(0xCAFE_BABEu32);
+
+        let mut seen_custom = false;
+        for _i in 0..500 {
+            let mut value = 12345u32;
+            mutator.mutate(&mut value);
+
+            let custom = [0xCAFE_BABE, 0xCAFE_BABF, 0xCAFE_BABD].contains(&value);
+            seen_custom |= custom;
+            assert!(custom || u32::interesting_values().contains(&value) || value == 12345);
+        }
+        assert!(seen_custom);
+
+        mutator.clear_interesting_values();
+        for _i in 0..100 {
+            assert!(u32::interesting_values().contains(&mutator.gen_interesting_value::<u32>()));
+        }
+    }
+
+    #[test]
+    fn test_export_layout() {
+        use lain::export::{to_010_template, to_kaitai};
+
+        let obj = NestedStruct {
+            test1: 0,
+            nested: TestStruct {
+                single_byte: 0,
+                bitfield_1: 0,
+                bitfield_2: 0,
+                bitfield_3: 0,
+                bitfield_4: 0,
+                bitfield_5: 0,
+                uint32: 0,
+                short: 0,
+                end_byte: 0,
+            },
+            test2: 0,
+        };
+
+        let kaitai = to_kaitai(&obj, "NestedStruct");
+        assert!(kaitai.starts_with("meta:\n  id: nestedstruct\nseq:\n  - id: test1\n    size: 4\n"));
+        assert!(kaitai.contains("  - id: nested\n    type: nested\n"));
+        assert!(kaitai
+            .contains("types:\n  nested:\n    seq:\n      - id: single_byte\n        size: 1\n"));
+
+        let template = to_010_template(&obj, "obj");
+        assert!(template.starts_with("struct {\n    uchar test1[4];\n    struct {\n"));
+        assert!(
+            template.contains("        uchar bitfield_1[1]; // bitfield of nested.bitfield_1, ")
+        );
+        assert!(template.contains("        uchar short_[2];\n"));
+        assert!(template.ends_with("    } nested;\n    uchar test2[4];\n} obj;\n"));
+    }
+
+    #[test]
+    fn byte_buffers_get_block_mutations() {
+        use lain::operators::MutationOperator;
+
+        let mut mutator = get_mutator();
+        mutator.set_dictionary_chance(0.0);
+        mutator.set_byte_block_chance(1.0);
+
+        let mut constraints = Constraints::new();
+        constraints.max_size(64);
+
+        let mut seen = std::collections::HashSet::new();
+        for _i in 0..200 {
+            let mut bytes: Vec<u8> = (0..32).collect();
+            bytes.mutate(&mut mutator, Some(&constraints));
+            assert!(bytes.len() <= 64);
+
+            for operator in mutator.take_applied_operators() {
+                seen.insert(operator);
+            }
+        }
+
+        for operator in [
+            MutationOperator::DuplicateBlock,
+            MutationOperator::OverwriteBlock,
+            MutationOperator::SwapBlocks,
+            MutationOperator::InsertBlock,
+        ]
+        .iter()
+        {
+            assert!(seen.contains(operator), "{} was never applied", operator);
+        }
+    }
+
+    #[test]
+    fn poisoned_contexts_are_recreated() {
+        use lain::driver::{start_fuzzer, FuzzerDriver, Outcome};
+        use std::sync::{Arc, RwLock};
+
+        #[derive(Default)]
+        struct Context {
+            iterations: usize,
+            broken: bool,
+        }
+
+        fn fuzzer_routine<R: lain::rand::Rng>(
+            _mutator: &mut Mutator<R>,
+            ctx: &mut Context,
+            global_ctx: Option<Arc<RwLock<Vec<usize>>>>,
+        ) -> Outcome {
+            let global_ctx = global_ctx.unwrap();
+            ctx.iterations += 1;
+
+            // this would panic on every iteration if the lock stayed poisoned
+            let iteration = global_ctx.read().unwrap().len();
+            let mut seen = global_ctx.write().unwrap();
+            seen.push(ctx.iterations);
+            match iteration {
+                2 => panic!("panicked while holding the lock"),
+                5 => ctx.broken = true,
+                _ => {}
+            }
+
+            Outcome::Ok
+        }
+
+        let global_ctx = Arc::new(RwLock::new(vec![]));
+        let mut driver = FuzzerDriver::<Vec<usize>>::new(1);
+        driver.set_catch_panics(true);
+        driver.set_global_context(global_ctx.clone());
+        driver.set_context_check(|ctx: &Context| !ctx.broken);
+        driver.set_to_reproduce_mode(0, 10);
+
+        let driver = Arc::new(driver);
+        start_fuzzer(driver.clone(), fuzzer_routine);
+        driver.join_threads();
+
+        assert_eq!(driver.num_crashes(), 1);
+        assert_eq!(driver.num_poisoned_contexts(), 2);
+        assert!(!global_ctx.is_poisoned());
+        assert_eq!(*global_ctx.read().unwrap(), [1, 2, 3, 1, 2, 3, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn vecs_are_resized_at_interior_offsets() {
+        let mut mutator = get_mutator();
+        let mut constraints = Constraints::new();
+        constraints.max_size(1024);
+
+        let original: Vec<u32> = (1000..1016).collect();
+        let (mut grown, mut shrunk) = (false, false);
+        for _i in 0..50_000 {
+            let mut list = original.clone();
+            list.mutate(&mut mutator, Some(&constraints));
+
+            // the first and last elements survive only if the vec was resized in the middle
+            let ends_kept = list.first() == Some(&1000) && list.last() == Some(&1015);
+            grown |= ends_kept && list.len() > original.len();
+            shrunk |= ends_kept && list.len() < original.len();
+            if grown && shrunk {
+                return;
+            }
+        }
+
+        panic!(
+            "grown in the middle: {}, shrunk in the middle: {}",
+            grown, shrunk
+        );
+    }
+
+    #[test]
+    fn swarm_epochs_leave_out_features() {
+        use lain::operators::MutationOperator;
+        use lain::swarm::{Swarm, SWARMED_OPERATORS};
+        use lain::traits::EnumVariants;
+
+        #[derive(Debug, Clone, Copy, PartialEq, NewFuzzed)]
+        enum Shape {
+            Circle,
+            Square,
+            Triangle,
+            Hexagon,
+        }
+
+        // every operator is left out of some epochs and kept in others
+        let epochs: Vec<Swarm> = (0..32).map(|epoch| Swarm::new(7, epoch)).collect();
+        for operator in SWARMED_OPERATORS.iter() {
+            let disabled = epochs
+                .iter()
+                .filter(|swarm| swarm.disables_operator(*operator))
+                .count();
+            assert!(disabled > 0 && disabled < epochs.len());
+        }
+        assert!(!epochs[0].disables_operator(MutationOperator::Crossover));
+        assert_eq!(epochs[3], Swarm::new(7, 3));
+
+        let type_name = std::any::type_name::<Shape>();
+        let swarm = epochs
+            .iter()
+            .find(|swarm| {
+                let disabled = (0..4)
+                    .filter(|i| swarm.disables_variant(type_name, *i))
+                    .count();
+                disabled > 0 && disabled < 4 && swarm.disables_option(std::any::type_name::<u32>())
+            })
+            .copied()
+            .unwrap();
+
+        let mut mutator = get_mutator();
+        mutator.set_swarm(Some(swarm));
+        for _i in 0..200 {
+            let shape = Shape::new_fuzzed(&mut mutator, None);
+            assert!(!swarm.disables_variant(type_name, shape.variant_index()));
+            assert_eq!(Option::<u32>::new_fuzzed(&mut mutator, None), None);
+        }
+
+        mutator.set_swarm(None);
+        assert!((0..200).any(|_| Option::<u32>::new_fuzzed(&mut mutator, None).is_some()));
+    }
+
+    #[test]
+    fn strings_grow_and_shrink_within_max_size() {
+        use lain::operators::MutationOperator;
+
+        let mut mutator = get_mutator();
+        mutator.set_dictionary_chance(0.0);
+
+        let mut constraints = Constraints::new();
+        constraints.min_size(4);
+        constraints.max_size(24);
+
+        let mut ascii = AsciiString::new("abcdefghijkl");
+        let mut utf8 = Utf8String::new("\u{e9}t\u{e9}abcdefgh");
+        let mut lengths = std::collections::HashSet::new();
+        for _i in 0..500 {
+            ascii.mutate(&mut mutator, Some(&constraints));
+            utf8.mutate(&mut mutator, Some(&constraints));
+
+            for string in [ascii.to_string(), utf8.to_string()].iter() {
+                assert!(
+                    (4..=24).contains(&string.len()),
+                    "{:?} is out of bounds",
+                    string
+                );
+            }
+            lengths.insert(ascii.to_string().len());
+        }
+
+        assert!(lengths.len() > 4);
+        for operator in [
+            MutationOperator::GrowList,
+            MutationOperator::ShrinkList,
+            MutationOperator::DuplicateBlock,
+        ]
+        .iter()
+        {
+            let count = mutator
+                .operator_counts()
+                .into_iter()
+                .find(|(applied, _)| applied == operator)
+                .map_or(0, |(_, count)| count);
+            assert!(count > 0, "{} was never applied", operator);
+        }
+    }
+
+    #[test]
+    fn grouped_vec_elements_are_resized_in_whole_groups() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Table {
+            #[lain(element_group = 3)]
+            rows: Vec<u16>,
+            #[lain(element_group = 4)]
+            records: Vec<u8>,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_byte_block_chance(0.5);
+
+        let mut table = Table {
+            rows: (0..12).collect(),
+            records: (0..16).collect(),
+        };
+        let mut constraints = Constraints::new();
+        constraints.max_size(256);
+
+        let mut lengths = std::collections::HashSet::new();
+        for _i in 0..2000 {
+            table.mutate(&mut mutator, Some(&constraints));
+            assert_eq!(table.rows.len() % 3, 0, "{:?}", table.rows);
+            assert_eq!(table.records.len() % 4, 0, "{:?}", table.records);
+
+            lengths.insert((table.rows.len(), table.records.len()));
+        }
+
+        assert!(lengths.len() > 4);
+
+        // blocks of bytes are inserted and duplicated in whole groups as well
+        mutator.set_byte_block_chance(1.0);
+        let mut records: Vec<u8> = (0..16).collect();
+        let mut constraints = Constraints::new();
+        constraints.max_size(64).element_group(4);
+        for _i in 0..200 {
+            records.mutate(&mut mutator, Some(&constraints));
+            assert_eq!(records.len() % 4, 0);
+        }
+    }
+
+    #[test]
+    fn zero_fill_resets_fields_to_their_defaults() {
+        use lain::operators::MutationOperator;
+
+        #[derive(Debug, Default, Clone, PartialEq, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Section {
+            id: u32,
+            data: Vec<u8>,
+        }
+
+        #[derive(
+            Debug, PartialEq, Mutatable, Eq, Copy, Clone, NewFuzzed, BinarySerialize, ToPrimitiveU8,
+        )]
+        #[repr(u8)]
+        enum Kind {
+            A = 1,
+            B = 2,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            kind: Kind,
+            section: Section,
+            trailer: Vec<u16>,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_zero_fill_chance(1.0);
+
+        let mut message = Message {
+            kind: Kind::A,
+            section: Section {
+                id: 7,
+                data: vec![1, 2, 3],
+            },
+            trailer: vec![4, 5],
+        };
+        message.mutate(&mut mutator, None);
+
+        assert_eq!(message.section, Section::default());
+        assert!(message.trailer.is_empty());
+        assert!(mutator
+            .take_applied_operators()
+            .contains(&MutationOperator::ZeroFill));
+
+        // fields without a Default are mutated instead
+        mutator.set_zero_fill_chance(0.0);
+        let mut kinds = std::collections::HashSet::new();
+        for _i in 0..100 {
+            message.mutate(&mut mutator, None);
+            kinds.insert(message.kind as u8);
+        }
+        assert_eq!(kinds.len(), 2);
+    }
+
+    #[test]
+    fn utf16_strings_serialize_code_units_with_edge_cases() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Request {
+            #[lain(little_endian)]
+            name: Utf16String,
+            #[lain(little_endian)]
+            path: WideCString,
+        }
+
+        let request = Request {
+            name: Utf16String::new("a\u{1F600}"),
+            path: WideCString::new("C:"),
+        };
+        let mut serialized = vec![];
+        request.binary_serialize::<_, BigEndian>(&mut serialized);
+        assert_eq!(
+            serialized,
+            [0x61, 0x00, 0x3D, 0xD8, 0x00, 0xDE, 0x43, 0x00, 0x3A, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(request.serialized_size(), serialized.len());
+
+        let (path, consumed) =
+            WideCString::binary_deserialize::<LittleEndian>(&serialized[6..]).unwrap();
+        assert_eq!((path.to_string().as_str(), consumed), ("C:", 6));
+        assert!(WideCString::binary_deserialize::<LittleEndian>(&serialized[6..10]).is_err());
+
+        let mut mutator = get_mutator();
+        let mut constraints = Constraints::new();
+        constraints.max_size(64);
+
+        let (mut paired, mut unpaired, mut nulls) = (false, false, false);
+        let mut request = Request::new_fuzzed(&mut mutator, Some(&constraints));
+        for _i in 0..500 {
+            request.mutate(&mut mutator, Some(&constraints));
+            assert!(request.serialized_size() <= 64);
+
+            for c in std::char::decode_utf16(request.name.code_units().iter().copied()) {
+                match c {
+                    Ok(c) if c as u32 > 0xFFFF => paired = true,
+                    Ok('\0') => nulls = true,
+                    Err(_) => unpaired = true,
+                    _ => {}
+                }
+            }
+        }
+
+        assert!(paired && unpaired && nulls);
+    }
+
+    #[test]
+    fn c_strings_lose_their_terminator_and_overflow_their_buffer() {
+        let string = CString::new("GET");
+        let mut serialized = vec![];
+        string.binary_serialize::<_, BigEndian>(&mut serialized);
+        assert_eq!(serialized, b"GET\0");
+        assert_eq!(string.serialized_size(), serialized.len());
+
+        let (parsed, consumed) = CString::binary_deserialize::<BigEndian>(b"GET\0/").unwrap();
+        assert_eq!((parsed, consumed), (string, 4));
+        let (parsed, consumed) = CString::binary_deserialize::<BigEndian>(b"GET").unwrap();
+        assert!(!parsed.is_terminated());
+        assert_eq!(consumed, 3);
+
+        use lain::operators::MutationOperator;
+
+        let mut mutator = get_mutator();
+        let mut constraints = Constraints::<u8>::new();
+        constraints.max_size(16);
+        let mut generation_constraints = Constraints::<usize>::new();
+        generation_constraints.max_size(16);
+
+        mutator.set_c_string_anomaly_chance(0.0);
+        let mut string = CString::new_fuzzed(&mut mutator, Some(&generation_constraints));
+        for _i in 0..200 {
+            string.mutate(&mut mutator, Some(&constraints));
+            assert!(string.serialized_size() <= 16);
+            assert!(string.is_terminated());
+        }
+
+        mutator.set_c_string_anomaly_chance(1.0);
+        let (mut unterminated, mut embedded, mut overflowed) = (false, false, false);
+        for _i in 0..100 {
+            let mut string = CString::new("GET");
+            string.mutate(&mut mutator, Some(&constraints));
+            assert!(mutator
+                .take_applied_operators()
+                .contains(&MutationOperator::CorruptTerminator));
+
+            unterminated |= !string.is_terminated();
+            embedded |= string.as_bytes().contains(&0);
+            overflowed |= string.serialized_size() > 16;
+        }
+
+        assert!(unterminated && embedded && overflowed);
+    }
+
+    #[test]
+    fn proportional_delta_scales_numbers_by_their_magnitude() {
+        use lain::operators::MutationOperator;
+
+        let mut mutator = get_mutator();
+        let expected = [
+            990_000u64, 1_010_000, 900_000, 1_100_000, 2_000_000, 500_000,
+        ];
+        let mut seen = std::collections::HashSet::new();
+        for _i in 0..1000 {
+            let mut size = 1_000_000u64;
+            mutator.mutate(&mut size);
+            if mutator
+                .take_applied_operators()
+                .contains(&MutationOperator::ProportionalDelta)
+            {
+                assert!(expected.contains(&size), "unexpected value {}", size);
+                seen.insert(size);
+            }
+        }
+        assert_eq!(seen.len(), expected.len());
+
+        // small values still change, and overflows wrap
+        for _i in 0..100 {
+            let mut value = 0u16;
+            mutator.mutate(&mut value);
+            if mutator
+                .take_applied_operators()
+                .contains(&MutationOperator::ProportionalDelta)
+            {
+                assert!([0, 1, u16::MAX].contains(&value));
+            }
+
+            let mut value = u16::MAX;
+            mutator.mutate(&mut value);
+            if mutator
+                .take_applied_operators()
+                .contains(&MutationOperator::ProportionalDelta)
+            {
+                assert!([64880, 654, 58982, 6552, 65534, 32767].contains(&value));
+            }
+        }
+    }
+
+    #[test]
+    fn floats_are_biased_towards_special_values() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Sample {
+            scale: f32,
+            offset: f64,
+        }
+
+        let mut mutator = get_mutator();
+        let (mut nan, mut infinite, mut negative_zero, mut subnormal, mut boundary) =
+            (false, false, false, false, false);
+        let mut sample = Sample::new_fuzzed(&mut mutator, None);
+        for _i in 0..2000 {
+            if mutator.gen_chance(0.5) {
+                sample = Sample::new_fuzzed(&mut mutator, None);
+            } else {
+                sample.mutate(&mut mutator, None);
+            }
+
+            for value in [sample.scale as f64, sample.offset].iter() {
+                nan |= value.is_nan();
+                infinite |= value.is_infinite();
+                negative_zero |= *value == 0.0 && value.is_sign_negative();
+                subnormal |= value.is_subnormal();
+                boundary |= [2f64.powi(31), 2f64.powi(32) - 1.0].contains(&value.abs());
+            }
+        }
+        assert!(nan && infinite && negative_zero && subnormal && boundary);
+
+        // constrained values are within range unless the constraints are ignored
+        let mut constraints = Constraints::new();
+        constraints.min(-0.5f64).max(0.5);
+        let in_range = (0..1000)
+            .map(|_| f64::new_fuzzed(&mut mutator, Some(&constraints)))
+            .filter(|value| (-0.5..0.5).contains(value))
+            .count();
+        assert!(in_range > 900);
+
+        // as are those of ranges too wide to sample from directly
+        let mut constraints = Constraints::new();
+        constraints.min(f32::MIN).max(f32::MAX);
+        let finite = (0..1000)
+            .map(|_| f32::new_fuzzed(&mut mutator, Some(&constraints)))
+            .filter(|value| value.is_finite())
+            .count();
+        assert!(finite > 900);
+    }
+
+    #[test]
+    fn invariants_are_checked_after_fixups() {
+        use lain::rand::Rng;
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            count: u8,
+            #[lain(max = 8)]
+            items: Vec<u16>,
+        }
+
+        impl Fixup for Message {
+            fn fixup<R: Rng>(&mut self, _mutator: &mut Mutator<R>) {
+                // misses lists which don't fit in a byte, which can't be generated here
+                if self.items.len() < 0x100 {
+                    self.count = self.items.len() as u8;
+                }
+            }
+        }
+
+        impl Invariant for Message {
+            fn check(&self) -> Result<(), String> {
+                if self.count as usize == self.items.len() {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "count is {}, but there are {} items",
+                        self.count,
+                        self.items.len()
+                    ))
+                }
+            }
+        }
+
+        let mut mutator = get_mutator();
+        let mut message = Message::new_fuzzed(&mut mutator, None);
+        for _i in 0..200 {
+            message.mutate(&mut mutator, None);
+            message.fixup_dependents(&mut mutator);
+        }
+
+        message.items = vec![0; 0x100];
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            message.fixup_dependents(&mut mutator);
+        }));
+        let panic = result.unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("there are 256 items"), "{}", message);
+    }
+
+    #[test]
+    fn wide_integers_are_fuzzed_and_serialized() {
+        #[derive(
+            Debug, Clone, PartialEq, NewFuzzed, Mutatable, BinarySerialize, BinaryDeserialize,
+        )]
+        struct Record {
+            id: u128,
+            #[lain(little_endian)]
+            balance: i128,
+        }
+
+        let record = Record {
+            id: 0x0102_0304_0506_0708_090A_0B0C_0D0E_0F10,
+            balance: -2,
+        };
+        let mut serialized = vec![];
+        record.binary_serialize::<_, BigEndian>(&mut serialized);
+        assert_eq!(serialized.len(), record.serialized_size());
+        assert_eq!(&serialized[..16], &(1u8..=16).collect::<Vec<u8>>()[..]);
+        assert_eq!(&serialized[16..], &(-2i128).to_le_bytes());
+        assert_eq!(
+            Record::binary_deserialize::<BigEndian>(&serialized).unwrap(),
+            (record.clone(), 32)
+        );
+
+        let mut mutator = get_mutator();
+        mutator.add_interesting_value(u128::MAX - 0x10);
+        let mut high_bits_changed = false;
+        let mut interesting = false;
+        for _i in 0..500 {
+            let mut mutated = record.clone();
+            mutated.mutate(&mut mutator, None);
+            high_bits_changed |= (mutated.id ^ record.id) >> 64 != 0;
+            interesting |= (u128::MAX - 0x11..=u128::MAX - 0xF).contains(&mutated.id);
+        }
+        assert!(high_bits_changed && interesting);
+    }
+
+    #[test]
+    fn delta_minimizer_finds_the_changes_a_crash_needs() {
+        use lain::minimize::minimize_delta;
+
+        #[derive(Debug, Clone, NewFuzzed, BinarySerialize)]
+        struct Header {
+            version: u8,
+            flags: u32,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, BinarySerialize)]
+        struct Packet {
+            header: Header,
+            id: u16,
+            payload: Vec<u8>,
+        }
+
+        let parent = Packet {
+            header: Header {
+                version: 1,
+                flags: 0,
+            },
+            id: 7,
+            payload: vec![1, 2, 3],
+        };
+        let crashing = Packet {
+            header: Header {
+                version: 2,
+                flags: 0x8000_00FF,
+            },
+            id: 9,
+            payload: vec![1, 2, 3, 4],
+        };
+
+        // the target crashes when the top bit of the flags is set
+        let delta =
+            minimize_delta::<_, BigEndian, _>(&parent, &crashing, |bytes| bytes[1] & 0x80 != 0)
+                .unwrap();
+        assert_eq!(delta.fields.len(), 1);
+        assert_eq!(delta.fields[0].path, "header.flags");
+        assert_eq!(delta.fields[0].parent, [0, 0, 0, 0]);
+        assert_eq!(delta.fields[0].bytes, Some(vec![0]));
+        assert_eq!(delta.minimized_input, [1, 0x80, 0, 0, 0, 0, 7, 1, 2, 3]);
+        assert!(delta.to_string().contains("header.flags"));
+
+        // fields which change size are needed as a whole
+        let delta = minimize_delta::<_, BigEndian, _>(&parent, &crashing, |bytes| bytes.len() > 10)
+            .unwrap();
+        assert_eq!(delta.fields.len(), 1);
+        assert_eq!(delta.fields[0].path, "payload");
+        assert_eq!(delta.fields[0].bytes, None);
+
+        assert!(minimize_delta::<_, BigEndian, _>(&parent, &crashing, |_| false).is_none());
+    }
+
+    #[test]
+    fn shared_fields_agree_across_messages() {
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Request {
+            #[lain(shared = "session")]
+            session_id: u64,
+            #[lain(shared = "nonce")]
+            nonce: [u8; 8],
+            payload: Vec<u8>,
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Response {
+            status: u8,
+            #[lain(shared = "nonce")]
+            nonce: [u8; 8],
+            #[lain(shared = "session")]
+            session_id: u64,
+        }
+
+        let mut mutator = get_mutator();
+        mutator.set_shared_mismatch_chance(0.0);
+
+        let mut request = Request::new_fuzzed(&mut mutator, None);
+        let mut response = Response::new_fuzzed(&mut mutator, None);
+        assert_eq!(request.session_id, response.session_id);
+        assert_eq!(request.nonce, response.nonce);
+        assert_eq!(
+            mutator.shared_value::<u64>("session"),
+            Some(&request.session_id)
+        );
+
+        let session_id = request.session_id;
+        for _ in 0..100 {
+            request.mutate(&mut mutator, None);
+            response.mutate(&mut mutator, None);
+            assert_eq!(request.session_id, session_id);
+            assert_eq!(response.session_id, session_id);
+            assert_eq!(request.nonce, response.nonce);
+        }
+
+        // a new session resolves new values
+        mutator.clear_shared_values();
+        mutator.set_shared_value("session", 0x1234u64);
+        let request = Request::new_fuzzed(&mut mutator, None);
+        let response = Response::new_fuzzed(&mut mutator, None);
+        assert_eq!(request.session_id, 0x1234);
+        assert_eq!(response.session_id, 0x1234);
+        assert_eq!(request.nonce, response.nonce);
+
+        mutator.set_shared_mismatch_chance(1.0);
+        let mut mismatched = false;
+        for _ in 0..100 {
+            let mut response = response.clone();
+            response.mutate(&mut mutator, None);
+            mismatched |= response.session_id != 0x1234;
+        }
+        assert!(mismatched);
+    }
+
+    #[test]
+    fn pointer_sized_integers_are_fuzzed_with_the_platform_width() {
+        #[derive(
+            Debug, Clone, PartialEq, NewFuzzed, Mutatable, BinarySerialize, BinaryDeserialize,
+        )]
+        struct Allocation {
+            len: usize,
+            offset: isize,
+        }
+
+        let width = std::mem::size_of::<usize>();
+        let allocation = Allocation {
+            len: usize::MAX - 1,
+            offset: -1,
+        };
+        let mut serialized = vec![];
+        allocation.binary_serialize::<_, LittleEndian>(&mut serialized);
+        assert_eq!(serialized.len(), width * 2);
+        assert_eq!(allocation.serialized_size(), width * 2);
+        assert_eq!(
+            Allocation::binary_deserialize::<LittleEndian>(&serialized).unwrap(),
+            (allocation.clone(), width * 2)
+        );
+
+        let mut mutator = get_mutator();
+        let mut boundary = false;
+        let mut high_bits = false;
+        let mut negative = false;
+        for _i in 0..500 {
+            let generated = Allocation::new_fuzzed(&mut mutator, None);
+            boundary |= generated.len == usize::MAX || generated.len == isize::MAX as usize;
+            high_bits |= generated.len >> (width * 8 - 8) != 0;
+
+            let mut mutated = allocation.clone();
+            mutated.mutate(&mut mutator, None);
+            negative |= mutated.offset < -1;
+        }
+        assert!(boundary && high_bits && negative);
+    }
+
+    #[test]
+    fn arrays_of_any_length_are_supported() {
+        #[derive(
+            Debug, Clone, PartialEq, NewFuzzed, Mutatable, BinarySerialize, BinaryDeserialize,
+        )]
+        struct Page {
+            header: [u8; 0],
+            buffer: [u8; 256],
+            words: [u32; 100],
+        }
+
+        let mut mutator = get_mutator();
+        let page = Page::new_fuzzed(&mut mutator, None);
+        assert_eq!(page.serialized_size(), 256 + 400);
+        assert_eq!(Page::max_default_object_size(), 256 + 400);
+
+        let mut serialized = vec![];
+        page.binary_serialize::<_, BigEndian>(&mut serialized);
+        assert_eq!(
+            Page::binary_deserialize::<BigEndian>(&serialized).unwrap(),
+            (page.clone(), 656)
+        );
+
+        let mut tail_mutated = false;
+        for _i in 0..100 {
+            let mut mutated = page.clone();
+            mutated.mutate(&mut mutator, None);
+            tail_mutated |= mutated.buffer[200..] != page.buffer[200..];
+        }
+        assert!(tail_mutated);
+    }
+
+    #[test]
+    fn fields_are_transplanted_from_other_corpus_entries() {
+        use lain::crossover::transplant_field;
+        use lain::operators::MutationOperator;
+
+        #[derive(Debug, Clone, BinarySerialize)]
+        struct Header {
+            version: u8,
+            flags: u16,
+        }
+
+        #[derive(Debug, Clone, BinarySerialize)]
+        struct Request {
+            header: Header,
+            name: Vec<u8>,
+            id: u32,
+        }
+
+        let serialize = |request: &Request| {
+            let mut bytes = vec![];
+            request.binary_serialize::<_, BigEndian>(&mut bytes);
+            bytes
+        };
+
+        let input = Request {
+            header: Header {
+                version: 1,
+                flags: 0,
+            },
+            name: b"ab".to_vec(),
+            id: 7,
+        };
+        let donor = Request {
+            name: b"a much longer name".to_vec(),
+            id: 0xDEAD_BEEF,
+            ..input.clone()
+        };
+
+        // the fields after a transplanted field of another size move
+        let mut expected = input.clone();
+        expected.name = donor.name.clone();
+        assert_eq!(
+            transplant_field::<_, BigEndian>(&input, &donor, "name"),
+            Some(serialize(&expected))
+        );
+        assert_eq!(
+            transplant_field::<_, BigEndian>(&input, &donor, "missing"),
+            None
+        );
+
+        // only fields which differ are picked
+        let mut mutator = get_mutator();
+        assert_eq!(mutator.transplant::<_, BigEndian>(&input, &[]), None);
+        let corpus = vec![Request {
+            id: 0xDEAD_BEEF,
+            ..input.clone()
+        }];
+        let mut expected = input.clone();
+        expected.id = 0xDEAD_BEEF;
+        mutator.take_applied_operators();
+        for _i in 0..10 {
+            assert_eq!(
+                mutator.transplant::<_, BigEndian>(&input, &corpus),
+                Some(serialize(&expected))
+            );
+        }
+        assert!(mutator
+            .take_applied_operators()
+            .contains(&MutationOperator::Transplant));
+        assert_eq!(
+            mutator.transplant::<_, BigEndian>(&input, std::slice::from_ref(&input)),
+            None
+        );
+    }
+
+    #[test]
+    fn control_words_fuzz_subfields_within_their_ranges() {
+        use lain::traits::ControlWordLayout;
+        use lain::types::{ControlWord, Subfield};
+
+        struct Opcode;
+
+        impl ControlWordLayout for Opcode {
+            type Word = u8;
+
+            const SUBFIELDS: &'static [Subfield] = &[
+                Subfield::new("opcode", 4),
+                Subfield::new("flags", 3).range(0, 5),
+                Subfield::new("reserved", 1).range(0, 0),
+            ];
+        }
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Message {
+            control: ControlWord<Opcode>,
+            length: u16,
+        }
+
+        let mut control = ControlWord::<Opcode>::default();
+        control.set("opcode", 0xA);
+        control.set("flags", 0xF);
+        assert_eq!(control.get("flags"), 7);
+        assert_eq!(control.word(), 0b0111_1010);
+        let mut buffer = vec![];
+        control.binary_serialize::<_, BigEndian>(&mut buffer);
+        assert_eq!(buffer, [0x7A]);
+
+        let mut layout = vec![];
+        control.field_layout("control", 0, &mut layout);
+        let paths: Vec<_> = layout.iter().map(|span| span.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["control.opcode", "control.flags", "control.reserved"]
+        );
+        assert!(layout.iter().all(|span| (span.start, span.end) == (0, 1)));
+
+        let mut mutator = get_mutator();
+        mutator.set_invalid_value_chance(0.0);
+        let mut seen_words = std::collections::HashSet::new();
+        for _ in 0..200 {
+            let mut message = Message::new_fuzzed(&mut mutator, None);
+            assert!(message.control.get("flags") <= 5);
+            assert_eq!(message.control.get("reserved"), 0);
+            seen_words.insert(message.control.word());
+
+            let before = message.control;
+            message.control.mutate(&mut mutator, None);
+            if before != message.control {
+                seen_words.insert(message.control.word());
+            }
+        }
+        assert!(seen_words.len() > 20);
+
+        mutator.set_invalid_value_chance(1.0);
+        let escaped = (0..200)
+            .map(|_| ControlWord::<Opcode>::new_fuzzed(&mut mutator, None))
+            .any(|control| control.get("flags") > 5 || control.get("reserved") != 0);
+        assert!(escaped);
+    }
+
+    #[test]
+    fn deterministic_mode_flips_every_bit_and_byte_before_havoc() {
+        use lain::mutator::MutatorMode;
+        use lain::operators::MutationOperator;
+        use lain::pipeline::MutationPipeline;
+
+        #[derive(Debug, Clone, NewFuzzed, Mutatable, BinarySerialize)]
+        struct Header {
+            magic: u8,
+            version: u8,
+        }
+
+        let mut mutator = get_mutator();
+        let mut header = Header {
+            magic: 0x4C,
+            version: 1,
+        };
+        let pipeline = MutationPipeline::<Header, SmallRng>::new()
+            .mutate(1.0)
+            .serialize::<BigEndian>()
+            .havoc(0.0, 4);
+
+        mutator.set_mode(MutatorMode::Deterministic);
+        let mut outputs = vec![];
+        while mutator.in_deterministic_pass() {
+            outputs.push(pipeline.run(&mut mutator, &mut header));
+        }
+
+        let progress = mutator.deterministic_progress();
+        assert!(progress.is_complete());
+        assert_eq!(progress.total_steps(), Some(18));
+        assert_eq!(outputs.len(), 18);
+        for (step, output) in outputs[..16].iter().enumerate() {
+            let mut expected = vec![0x4C, 1];
+            expected[step / 8] ^= 0x80 >> (step % 8);
+            assert_eq!(*output, expected);
+        }
+        assert_eq!(outputs[16], [0xB3, 1]);
+        assert_eq!(outputs[17], [0x4C, 0xFE]);
+        assert_eq!((header.magic, header.version), (0x4C, 1));
+        assert!(mutator
+            .operator_counts()
+            .contains(&(MutationOperator::DeterministicBitFlip, 16)));
+
+        // once the pass is done the pipeline mutates the input again
+        (0..20).for_each(|_| {
+            pipeline.run(&mut mutator, &mut header);
+        });
+        assert_ne!((header.magic, header.version), (0x4C, 1));
+
+        mutator.reset_deterministic_progress();
+        assert!(mutator.in_deterministic_pass());
+    }
+
+    #[test]
+    fn corpus_tool_prints_converts_and_minimizes_entries() {
+        use lain::cli::{CliError, CorpusTool};
+
+        let tool = CorpusTool::new()
+            .register::<[u16; 2]>("pair")
+            .register_minimizable::<Vec<u8>>("bytes");
+        let run = |args: &[&str]| {
+            let mut output = vec![];
+            let args = std::iter::once("cargo-lain").chain(args.iter().copied());
+            tool.execute(args, &mut output)
+                .map(|()| String::from_utf8(output).unwrap())
+        };
+
+        let dir = std::env::temp_dir().join(format!("lain_cli_{}", std::process::id()));
+        let (input, output) = (dir.join("input"), dir.join("output"));
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("a"), [1u8, 0, 2, 0]).unwrap();
+        std::fs::write(input.join("b"), [1u8, 0, 2, 0]).unwrap();
+        std::fs::write(input.join("short"), [5u8, 0, 6]).unwrap();
+        let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+
+        assert_eq!(
+            run(&["lain", "types"]).unwrap(),
+            "pair
+bytes (minimizable)\n"
+        );
+
+        let entry = dir.join("input/a");
+        let printed = run(&["print", "pair", entry.to_str().unwrap(), "--endian", "le"]);
+        assert!(printed.unwrap().starts_with(
+            "[
+    1,\n    2,\n]"
+        ));
+
+        let stats = run(&["stats", input, "--type", "pair", "--endian", "le"]).unwrap();
+        assert!(stats.contains(
+            "entries:    3
+"
+        ));
+        assert!(stats.contains(
+            "unique:     2
+"
+        ));
+        assert!(stats.contains("parsed:     2 of 3 as pair"));
+
+        let converted = run(&["convert", "pair", input, output, "--from", "le"]).unwrap();
+        assert!(converted.ends_with(
+            "converted 1 entries (1 new files), skipped 1
+"
+        ));
+        let files: Vec<_> = std::fs::read_dir(output).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        let file = files[0].as_ref().unwrap().path();
+        assert_eq!(std::fs::read(&file).unwrap(), [0u8, 1, 0, 2]);
+
+        assert!(matches!(
+            run(&["print", "triple", input]),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            run(&["minimize", "pair", "x", "y"]),
+            Err(CliError::Usage(_))
+        ));
+
+        #[cfg(unix)]
+        {
+            let crash = dir.join("crash");
+            let minimized = dir.join("minimized");
+            std::fs::write(&crash, b"aaaaXbbbb").unwrap();
+            let (crash, minimized) = (crash.to_str().unwrap(), minimized.to_str().unwrap());
+            let script = "! grep -q X \"$0\"";
+
+            let result = run(&[
+                "minimize", "bytes", crash, minimized, "--", "sh", "-c", script, "@@",
+            ]);
+            assert!(result.unwrap().starts_with("minimized 9 to 1 bytes"));
+            assert_eq!(std::fs::read(minimized).unwrap(), b"X");
+
+            let result = run(&["minimize", "bytes", crash, minimized, "--", "true"]);
+            assert!(matches!(result, Err(CliError::NotFailing)));
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     fn compare_slices(expected: &[u8], actual: &[u8]) {
         assert_eq!(actual.len(), expected.len());
 